@@ -0,0 +1,89 @@
+use benchlib::{divan, Rng};
+use rkyv::{
+    collections::btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+    rancor::{Fallible, Panic, Source},
+    ser::{Allocator, Writer},
+    util::AlignedVec,
+    Archive, Archived, Place, Serialize,
+};
+
+const ENTRIES: u64 = 1_000_000;
+const LOOKUPS: usize = 10_000;
+
+/// A sorted list of key-value pairs which archives as a B-tree map with `E`
+/// entries per node.
+pub struct SortedPairs<const E: usize>(Vec<(u64, u64)>);
+
+impl<const E: usize> Archive for SortedPairs<E> {
+    type Archived = ArchivedBTreeMap<Archived<u64>, Archived<u64>, E>;
+    type Resolver = BTreeMapResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedBTreeMap::resolve_from_len(self.0.len(), resolver, out);
+    }
+}
+
+impl<S, const E: usize> Serialize<S> for SortedPairs<E>
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedBTreeMap::serialize_from_sorted_iter(
+            self.0.iter().map(|(k, v)| (k, v)),
+            serializer,
+        )
+    }
+}
+
+/// Generates sorted pairs with random values.
+pub fn generate_pairs<const E: usize>() -> SortedPairs<E> {
+    let mut rng = benchlib::rng();
+    SortedPairs((0..ENTRIES).map(|k| (k, rng.gen::<u64>())).collect())
+}
+
+fn archive<const E: usize>() -> AlignedVec {
+    rkyv::to_bytes_in::<_, Panic>(
+        &generate_pairs::<E>(),
+        AlignedVec::<16>::new(),
+    )
+    .unwrap()
+}
+
+#[divan::bench(consts = [1, 5, 15, 31, 63])]
+pub fn serialize_sorted<const E: usize>(bencher: divan::Bencher) {
+    let value = generate_pairs::<E>();
+
+    bencher.bench_local(|| {
+        rkyv::to_bytes_in::<_, Panic>(
+            divan::black_box(&value),
+            AlignedVec::<16>::new(),
+        )
+        .unwrap()
+    })
+}
+
+#[divan::bench(consts = [1, 5, 15, 31, 63])]
+pub fn lookup<const E: usize>(bencher: divan::Bencher) {
+    let bytes = archive::<E>();
+    let archived =
+        unsafe { rkyv::access_unchecked::<Archived<SortedPairs<E>>>(&bytes) };
+
+    let mut rng = benchlib::rng();
+    let keys = (0..LOOKUPS)
+        .map(|_| Archived::<u64>::from_native(rng.gen_range(0..ENTRIES)))
+        .collect::<Vec<_>>();
+
+    bencher.bench_local(|| {
+        for key in keys.iter() {
+            divan::black_box(archived.get(divan::black_box(key)));
+        }
+    })
+}
+
+fn main() {
+    divan::main();
+}
@@ -0,0 +1,105 @@
+use benchlib::{divan, Rng};
+use rkyv::{rancor::Panic, util::AlignedVec, Archive, Archived, Serialize};
+
+const ITEMS: usize = 1_000_000;
+
+#[derive(Archive, Serialize)]
+#[rkyv(derive_projections)]
+pub struct Item {
+    pub timestamp: u64,
+    pub value: f32,
+    pub flag: u16,
+    pub name: String,
+}
+
+fn archive() -> AlignedVec {
+    let mut rng = benchlib::rng();
+    let items = (0..ITEMS)
+        .map(|i| Item {
+            timestamp: i as u64,
+            value: rng.gen(),
+            flag: rng.gen(),
+            name: String::new(),
+        })
+        .collect::<Vec<_>>();
+    rkyv::to_bytes::<Panic>(&items).unwrap()
+}
+
+fn access(bytes: &[u8]) -> &Archived<Vec<Item>> {
+    unsafe { rkyv::access_unchecked::<Archived<Vec<Item>>>(bytes) }
+}
+
+#[divan::bench]
+pub fn collect_timestamps_map(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = access(&bytes);
+
+    bencher.bench_local(|| {
+        divan::black_box(archived)
+            .iter()
+            .map(|item| item.timestamp.to_native())
+            .collect::<Vec<_>>()
+    })
+}
+
+#[divan::bench]
+pub fn collect_timestamps_native(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = access(&bytes);
+
+    bencher.bench_local(|| {
+        divan::black_box(archived)
+            .timestamps()
+            .collect_native::<Vec<_>>()
+    })
+}
+
+#[divan::bench]
+pub fn sum_values_map(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = access(&bytes);
+
+    bencher.bench_local(|| {
+        divan::black_box(archived)
+            .iter()
+            .map(|item| item.value.to_native())
+            .sum::<f32>()
+    })
+}
+
+#[divan::bench]
+pub fn sum_values_projection(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = access(&bytes);
+
+    bencher.bench_local(|| divan::black_box(archived).values().sum::<f32>())
+}
+
+#[divan::bench]
+pub fn copy_flags_map(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = access(&bytes);
+    let mut out = vec![0u16; ITEMS];
+
+    bencher.bench_local(|| {
+        let archived = divan::black_box(archived);
+        for (out, item) in out.iter_mut().zip(archived.iter()) {
+            *out = item.flag.to_native();
+        }
+    })
+}
+
+#[divan::bench]
+pub fn copy_flags_native(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = access(&bytes);
+    let mut out = vec![0u16; ITEMS];
+
+    bencher.bench_local(|| {
+        divan::black_box(archived).flags().copy_to_slice(&mut out);
+    })
+}
+
+fn main() {
+    divan::main();
+}
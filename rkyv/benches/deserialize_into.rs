@@ -0,0 +1,59 @@
+use benchlib::{divan, Rng};
+use rkyv::{rancor::Panic, util::AlignedVec, Archived};
+
+#[global_allocator]
+static ALLOC: divan::AllocProfiler = divan::AllocProfiler::system();
+
+const STRINGS: usize = 10_000;
+
+type ArchivedStrings = Archived<Vec<String>>;
+
+/// Generates a vec of strings which are long enough to be out-of-line.
+fn generate_strings() -> Vec<String> {
+    let mut rng = benchlib::rng();
+    (0..STRINGS)
+        .map(|i| format!("a string which is stored out-of-line {i:08}"))
+        .map(|s| s[..s.len() - rng.gen_range(0..8)].to_string())
+        .collect()
+}
+
+fn archive() -> AlignedVec {
+    rkyv::to_bytes::<Panic>(&generate_strings()).unwrap()
+}
+
+/// Allocates a new vec and a new string for every element.
+#[divan::bench]
+fn deserialize(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = unsafe { rkyv::access_unchecked::<ArchivedStrings>(&bytes) };
+
+    bencher.bench_local(|| {
+        rkyv::deserialize::<Vec<String>, _, Panic>(
+            divan::black_box(archived),
+            &mut (),
+        )
+        .unwrap()
+    });
+}
+
+/// Reuses the vec and every string in it, so it should not allocate after the
+/// first iteration.
+#[divan::bench]
+fn deserialize_into(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = unsafe { rkyv::access_unchecked::<ArchivedStrings>(&bytes) };
+    let mut out = generate_strings();
+
+    bencher.bench_local(|| {
+        rkyv::deserialize_into::<Vec<String>, _, Panic>(
+            divan::black_box(archived),
+            &mut out,
+            &mut (),
+        )
+        .unwrap();
+    });
+}
+
+fn main() {
+    divan::main();
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use benchlib::{divan, Rng};
+use rkyv::{
+    collections::swiss_table::{
+        ArchivedHashMap, HashMapResolver, DEFAULT_LOAD_FACTOR,
+    },
+    de::Unpool,
+    rancor::{Fallible, Panic, Source},
+    ser::{Allocator, Writer},
+    util::AlignedVec,
+    Archive, Archived, Place, Serialize,
+};
+
+const ENTRIES: usize = 100_000;
+
+type Map = HashMap<String, Vec<u32>>;
+type ArchivedMap = Archived<Map>;
+
+/// Keeps about half of the entries of a map.
+fn keep(_: &Archived<String>, value: &Archived<Vec<u32>>) -> bool {
+    value.as_slice()[0] % 2 == 0
+}
+
+/// An archived map which archives as the entries matching [`keep`].
+pub struct Filtered<'a>(&'a ArchivedMap);
+
+impl Archive for Filtered<'_> {
+    type Archived = ArchivedMap;
+    type Resolver = HashMapResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedHashMap::resolve_filtered_from_archived(
+            self.0,
+            keep,
+            DEFAULT_LOAD_FACTOR,
+            resolver,
+            out,
+        );
+    }
+}
+
+impl<S> Serialize<S> for Filtered<'_>
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedMap::serialize_filtered_from_archived(
+            self.0,
+            keep,
+            DEFAULT_LOAD_FACTOR,
+            serializer,
+        )
+    }
+}
+
+/// Generates a map of random strings to short vecs of random numbers.
+pub fn generate_map() -> Map {
+    let mut rng = benchlib::rng();
+    (0..ENTRIES)
+        .map(|i| {
+            let len = rng.gen_range(1..8);
+            let value = (0..len).map(|_| rng.gen::<u32>()).collect();
+            (format!("key_{i}_{}", rng.gen::<u32>()), value)
+        })
+        .collect()
+}
+
+fn archive() -> AlignedVec {
+    rkyv::to_bytes_in::<_, Panic>(&generate_map(), AlignedVec::<16>::new())
+        .unwrap()
+}
+
+#[divan::bench]
+pub fn filter_deserialized(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = unsafe { rkyv::access_unchecked::<ArchivedMap>(&bytes) };
+
+    bencher.bench_local(|| {
+        let mut map = rkyv::deserialize::<Map, _, Panic>(
+            divan::black_box(archived),
+            &mut Unpool,
+        )
+        .unwrap();
+        map.retain(|_, value| value[0] % 2 == 0);
+        rkyv::to_bytes_in::<_, Panic>(&map, AlignedVec::<16>::new()).unwrap()
+    })
+}
+
+#[divan::bench]
+pub fn filter_archived(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived = unsafe { rkyv::access_unchecked::<ArchivedMap>(&bytes) };
+
+    bencher.bench_local(|| {
+        rkyv::to_bytes_in::<_, Panic>(
+            &Filtered(divan::black_box(archived)),
+            AlignedVec::<16>::new(),
+        )
+        .unwrap()
+    })
+}
+
+fn main() {
+    divan::main();
+}
@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use benchlib::{divan, Rng};
+use rkyv::{
+    rancor::Panic,
+    util::AlignedVec,
+    validation::{util::access_with_context, validators::DefaultValidator},
+    Archived,
+};
+
+const ENTRIES: usize = 100_000;
+
+type Map = HashMap<String, u64>;
+type ArchivedMap = Archived<Map>;
+
+/// Generates a map of random strings to random numbers.
+pub fn generate_map() -> Map {
+    let mut rng = benchlib::rng();
+    (0..ENTRIES)
+        .map(|i| (format!("key_{i}_{}", rng.gen::<u32>()), rng.gen::<u64>()))
+        .collect()
+}
+
+fn archive() -> AlignedVec {
+    rkyv::to_bytes_in::<_, Panic>(&generate_map(), AlignedVec::<16>::new())
+        .unwrap()
+}
+
+#[divan::bench(args = [true, false])]
+pub fn validate(bencher: divan::Bencher, check_duplicate_keys: bool) {
+    let bytes = archive();
+
+    bencher.bench_local(|| {
+        let bytes = divan::black_box(bytes.as_slice());
+        let mut validator = DefaultValidator::new(bytes);
+        validator.set_check_duplicate_keys(check_duplicate_keys);
+        access_with_context::<ArchivedMap, _, Panic>(bytes, &mut validator)
+            .map(|map| map.len())
+            .unwrap()
+    })
+}
+
+fn main() {
+    divan::main();
+}
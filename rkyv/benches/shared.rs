@@ -0,0 +1,74 @@
+use std::rc::Rc;
+
+use benchlib::{divan, Rng};
+use rkyv::{
+    de::{Pool, Unpool},
+    rancor::Panic,
+    util::AlignedVec,
+    Archived,
+};
+
+const VALUES: usize = 1_000_000;
+
+/// Generates a vec where every value is shared by two `Rc`s.
+pub fn generate_shared() -> Vec<Rc<u32>> {
+    let mut rng = benchlib::rng();
+    let values = (0..VALUES)
+        .map(|_| Rc::new(rng.gen::<u32>()))
+        .collect::<Vec<_>>();
+    values.iter().chain(values.iter()).cloned().collect()
+}
+
+fn archive() -> AlignedVec {
+    rkyv::to_bytes_in::<_, Panic>(&generate_shared(), AlignedVec::<16>::new())
+        .unwrap()
+}
+
+#[divan::bench(min_time = std::time::Duration::from_secs(3))]
+pub fn deserialize_pool(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived =
+        unsafe { rkyv::access_unchecked::<Archived<Vec<Rc<u32>>>>(&bytes) };
+
+    bencher.bench_local(|| {
+        rkyv::deserialize::<Vec<Rc<u32>>, _, Panic>(
+            divan::black_box(archived),
+            &mut Pool::new(),
+        )
+        .unwrap()
+    })
+}
+
+#[divan::bench(min_time = std::time::Duration::from_secs(3))]
+pub fn deserialize_pool_with_capacity(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived =
+        unsafe { rkyv::access_unchecked::<Archived<Vec<Rc<u32>>>>(&bytes) };
+
+    bencher.bench_local(|| {
+        rkyv::deserialize::<Vec<Rc<u32>>, _, Panic>(
+            divan::black_box(archived),
+            &mut Pool::with_capacity(VALUES),
+        )
+        .unwrap()
+    })
+}
+
+#[divan::bench(min_time = std::time::Duration::from_secs(3))]
+pub fn deserialize_unpool(bencher: divan::Bencher) {
+    let bytes = archive();
+    let archived =
+        unsafe { rkyv::access_unchecked::<Archived<Vec<Rc<u32>>>>(&bytes) };
+
+    bencher.bench_local(|| {
+        rkyv::deserialize::<Vec<Rc<u32>>, _, Panic>(
+            divan::black_box(archived),
+            &mut Unpool,
+        )
+        .unwrap()
+    })
+}
+
+fn main() {
+    divan::main();
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use benchlib::{divan, Rng};
+use rkyv::{
+    rancor::Panic,
+    ser::{
+        allocator::Arena,
+        sharing::{BloomShare, Share},
+        SerializerBuilder, Sharing,
+    },
+    util::serialize_into,
+};
+
+const NODES: usize = 5_000_000;
+
+/// Generates a graph of `Arc`s where one in every hundred nodes is shared with
+/// an earlier node.
+pub fn generate_graph() -> Vec<Arc<u32>> {
+    let mut rng = benchlib::rng();
+    let mut nodes = Vec::with_capacity(NODES);
+    for i in 0..NODES {
+        if i % 100 == 99 {
+            let shared = nodes[rng.gen_range(0..i)].clone();
+            nodes.push(shared);
+        } else {
+            nodes.push(Arc::new(rng.gen::<u32>()));
+        }
+    }
+    nodes
+}
+
+fn serialize_with<S: Sharing<Panic>>(
+    bencher: divan::Bencher,
+    sharing: impl Fn() -> S,
+) {
+    let graph = generate_graph();
+    let mut arena = Arena::new();
+
+    bencher.bench_local(|| {
+        let serializer = SerializerBuilder::high_level(&mut arena)
+            .sharing(sharing())
+            .build::<Panic>();
+        serialize_into::<_, Panic>(divan::black_box(&graph), serializer)
+            .unwrap()
+            .into_writer()
+    })
+}
+
+#[divan::bench(min_time = std::time::Duration::from_secs(3))]
+pub fn serialize_share(bencher: divan::Bencher) {
+    serialize_with(bencher, Share::new);
+}
+
+#[divan::bench(min_time = std::time::Duration::from_secs(3))]
+pub fn serialize_share_with_capacity(bencher: divan::Bencher) {
+    serialize_with(bencher, || Share::with_capacity(NODES));
+}
+
+#[divan::bench(min_time = std::time::Duration::from_secs(3))]
+pub fn serialize_bloom_share(bencher: divan::Bencher) {
+    serialize_with(bencher, BloomShare::new);
+}
+
+#[divan::bench(min_time = std::time::Duration::from_secs(3))]
+pub fn serialize_bloom_share_with_capacity(bencher: divan::Bencher) {
+    serialize_with(bencher, || BloomShare::with_capacity(NODES));
+}
+
+fn main() {
+    divan::main();
+}
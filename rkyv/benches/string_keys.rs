@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+
+use benchlib::{divan, Rng};
+use rkyv::{rancor::Panic, util::AlignedVec, Archived};
+
+const ENTRIES: usize = 10_000;
+const PREFIX: &str = "a long shared prefix which makes every key out-of-line";
+
+type Map = HashMap<String, u64>;
+type ArchivedMap = Archived<Map>;
+
+fn key(i: usize) -> String {
+    format!("{PREFIX}/{i:08}")
+}
+
+/// Generates a map of long strings to random numbers.
+fn generate_map() -> Map {
+    let mut rng = benchlib::rng();
+    (0..ENTRIES).map(|i| (key(i), rng.gen::<u64>())).collect()
+}
+
+fn archive() -> AlignedVec {
+    rkyv::to_bytes::<Panic>(&generate_map()).unwrap()
+}
+
+fn lookups(bencher: divan::Bencher, keys: Vec<String>) {
+    let bytes = archive();
+    let map = unsafe { rkyv::access_unchecked::<ArchivedMap>(&bytes) };
+
+    bencher.bench_local(|| {
+        keys.iter()
+            .filter(|key| map.get(divan::black_box(key.as_str())).is_some())
+            .count()
+    })
+}
+
+#[divan::bench]
+fn hit(bencher: divan::Bencher) {
+    lookups(bencher, (0..ENTRIES).map(key).collect());
+}
+
+/// Keys which are the same length as the keys in the map, so they have to be
+/// compared byte-by-byte when their hashes collide.
+#[divan::bench]
+fn miss_same_len(bencher: divan::Bencher) {
+    lookups(bencher, (ENTRIES..2 * ENTRIES).map(key).collect());
+}
+
+/// Keys which are longer than the keys in the map, so they are rejected by
+/// their lengths.
+#[divan::bench]
+fn miss_different_len(bencher: divan::Bencher) {
+    lookups(
+        bencher,
+        (0..ENTRIES).map(|i| format!("{}!", key(i))).collect(),
+    );
+}
+
+#[divan::bench]
+fn compare_archived(bencher: divan::Bencher) {
+    let keys = (0..ENTRIES).map(key).collect::<Vec<_>>();
+    let bytes = rkyv::to_bytes::<Panic>(&keys).unwrap();
+    let archived =
+        unsafe { rkyv::access_unchecked::<Archived<Vec<String>>>(&bytes) };
+
+    bencher.bench_local(|| {
+        archived
+            .windows(2)
+            .filter(|pair| divan::black_box(&pair[0]) == &pair[1])
+            .count()
+    })
+}
+
+fn main() {
+    divan::main();
+}
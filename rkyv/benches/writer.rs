@@ -0,0 +1,50 @@
+use benchlib::{divan, Rng};
+use rkyv::{rancor::Panic, util::AlignedVec};
+
+#[global_allocator]
+static ALLOC: divan::AllocProfiler = divan::AllocProfiler::system();
+
+const VECS: usize = 10_000;
+const VEC_LEN: usize = 256;
+
+/// Generates many mid-size vecs of strings.
+pub fn generate_vecs() -> Vec<Vec<String>> {
+    let mut rng = benchlib::rng();
+    (0..VECS)
+        .map(|_| {
+            (0..VEC_LEN)
+                .map(|_| {
+                    let len = rng.gen_range(24..64);
+                    (0..len).map(|_| rng.gen_range('a'..='z')).collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[divan::bench]
+pub fn serialize_aligned_vec(bencher: divan::Bencher) {
+    let value = generate_vecs();
+
+    bencher.bench_local(|| {
+        rkyv::to_bytes_in::<_, Panic>(
+            divan::black_box(&value),
+            AlignedVec::<16>::new(),
+        )
+        .unwrap()
+    })
+}
+
+#[divan::bench]
+pub fn serialize_vec(bencher: divan::Bencher) {
+    let value = generate_vecs();
+
+    bencher.bench_local(|| {
+        rkyv::to_bytes_in::<_, Panic>(divan::black_box(&value), Vec::new())
+            .unwrap()
+    })
+}
+
+fn main() {
+    divan::main();
+}
@@ -0,0 +1,83 @@
+use rkyv::{
+    collections::swiss_table::{
+        ArchivedHashMap, HashMapResolver, DEFAULT_LOAD_FACTOR,
+    },
+    rancor::{Error, Fallible, Source},
+    ser::{Allocator, Writer},
+    string::{ArchivedString, StrAdapter},
+    Archive, Deserialize, Place, Serialize,
+};
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+#[rkyv(check_bytes, derive(Debug))]
+struct Payload {
+    id: u32,
+    tags: Vec<String>,
+}
+
+// The archived type of `Rows` is the same as the archived type of a
+// `HashMap<String, Payload>`, but it borrows all of its data from the rows.
+type Map = ArchivedHashMap<ArchivedString, ArchivedPayload>;
+
+// Query results which borrow their keys and values from some other storage.
+struct Rows<'a>(&'a [(&'a str, &'a Payload)]);
+
+impl Archive for Rows<'_> {
+    type Archived = Map;
+    type Resolver = HashMapResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        Map::resolve_from_len(self.0.len(), DEFAULT_LOAD_FACTOR, resolver, out);
+    }
+}
+
+impl<S> Serialize<S> for Rows<'_>
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        // `StrAdapter` serializes a `&str` as an `ArchivedString`, and hashes
+        // the same as the `String` it stands in for. The payloads are only
+        // borrowed, so nothing is cloned.
+        Map::serialize_from_borrowed_iter::<_, _, _, StrAdapter, Payload, _>(
+            self.0.iter().map(|(key, value)| (StrAdapter(key), *value)),
+            DEFAULT_LOAD_FACTOR,
+            serializer,
+        )
+    }
+}
+
+fn main() {
+    let alice = Payload {
+        id: 1,
+        tags: vec!["admin".to_string()],
+    };
+    let bob = Payload {
+        id: 2,
+        tags: vec!["guest".to_string(), "new".to_string()],
+    };
+    let rows = vec![("alice", &alice), ("bob", &bob)];
+
+    let bytes = rkyv::to_bytes::<Error>(&Rows(&rows)).unwrap();
+
+    // The archive can be read back as if it were a `HashMap<String, Payload>`.
+    let archived = rkyv::access::<Map, Error>(&bytes).unwrap();
+    assert_eq!(archived.len(), 2);
+    assert!(archived.get("carol").is_none());
+
+    let archived_bob = archived.get("bob").unwrap();
+    assert_eq!(archived_bob.id, 2);
+    assert_eq!(archived_bob.tags[1], "new");
+
+    let deserialized =
+        rkyv::deserialize::<Payload, _, Error>(archived_bob, &mut ()).unwrap();
+    assert_eq!(deserialized, bob);
+
+    for (key, value) in archived.iter() {
+        println!("{key}: {value:?}");
+    }
+}
@@ -1,6 +1,6 @@
 use rkyv::{
     access_unchecked, deserialize,
-    rancor::{Error, Fallible},
+    rancor::{Error, Fallible, Source},
     ser::{Allocator, Writer},
     vec::{ArchivedVec, VecResolver},
     with::{ArchiveWith, DeserializeWith, SerializeWith},
@@ -46,6 +46,7 @@ impl ArchiveWith<Vec<Opcode>> for EncodeOpcodes {
 impl<S> SerializeWith<Vec<Opcode>, S> for EncodeOpcodes
 where
     S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize_with(
         field: &Vec<Opcode>,
@@ -0,0 +1,71 @@
+use rkyv::{rancor::Error, Archived};
+
+// This module stands in for the crate which writes messages. It defines the
+// full message type, and its archived type is shared with readers.
+mod writer {
+    use rkyv::{Archive, Serialize};
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(check_bytes)]
+    pub enum Event {
+        Login { user: String },
+        Logout { user: String },
+        Heartbeat,
+        Resize { width: u32, height: u32 },
+    }
+}
+
+// This module stands in for a crate which only cares about some of the events.
+// Its native type is trimmed down, but it archives as the writer's archived
+// type so that archives are validated with the full definition of `Event`.
+mod reader {
+    use rkyv::{Archive, Deserialize};
+
+    #[derive(Archive, Deserialize, Debug)]
+    #[rkyv(as = "crate::writer::ArchivedEvent")]
+    pub enum Event {
+        Login {
+            user: String,
+        },
+        Logout {
+            user: String,
+        },
+        // Every event that the reader doesn't handle is deserialized as this
+        // variant.
+        #[rkyv(other)]
+        Ignored,
+    }
+}
+
+fn main() {
+    let events = vec![
+        writer::Event::Login {
+            user: "alice".to_string(),
+        },
+        writer::Event::Heartbeat,
+        writer::Event::Resize {
+            width: 1920,
+            height: 1080,
+        },
+        writer::Event::Logout {
+            user: "alice".to_string(),
+        },
+    ];
+    let bytes = rkyv::to_bytes::<Error>(&events).unwrap();
+
+    // The reader validates the archive with the writer's archived type...
+    let archived =
+        rkyv::access::<Archived<Vec<writer::Event>>, Error>(&bytes).unwrap();
+
+    // ...and deserializes it into its own native type.
+    for event in archived.iter() {
+        let event =
+            rkyv::deserialize::<reader::Event, _, Error>(event, &mut ())
+                .unwrap();
+        match event {
+            reader::Event::Login { user } => println!("{} logged in", user),
+            reader::Event::Logout { user } => println!("{} logged out", user),
+            reader::Event::Ignored => println!("ignored an event"),
+        }
+    }
+}
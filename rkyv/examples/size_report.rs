@@ -0,0 +1,61 @@
+use rkyv::{
+    rancor::Error,
+    ser::{
+        allocator::Arena, sharing::Share, writer::TracingWriter, Serializer,
+    },
+    util::{serialize_into, AlignedVec},
+    Archive, Serialize,
+};
+
+#[derive(Archive, Serialize)]
+struct Document {
+    title: String,
+    authors: Vec<String>,
+    sections: Vec<Section>,
+}
+
+#[derive(Archive, Serialize)]
+struct Section {
+    heading: String,
+    paragraphs: Vec<String>,
+    tags: Vec<u32>,
+}
+
+fn main() {
+    let document = Document {
+        title: "A document with a fairly long title".to_string(),
+        authors: vec![
+            "Ada Lovelace".to_string(),
+            "Charles Babbage".to_string(),
+        ],
+        sections: (0..4)
+            .map(|i| Section {
+                heading: format!("Section number {i}"),
+                paragraphs: (0..3)
+                    .map(|j| {
+                        format!("Paragraph {j} of section {i}. ").repeat(4)
+                    })
+                    .collect(),
+                tags: (0..i).collect(),
+            })
+            .collect(),
+    };
+
+    let mut arena = Arena::new();
+    let writer = TracingWriter::new(AlignedVec::<16>::new());
+    let (bytes, report) = serialize_into::<_, Error>(
+        &document,
+        Serializer::new(writer, arena.acquire(), Share::new()),
+    )
+    .expect("failed to serialize document")
+    .into_writer()
+    .into_parts();
+
+    println!("By type:\n{report}\n");
+    println!("By callsite:");
+    for entry in report.by_callsite() {
+        println!("{entry}");
+    }
+
+    assert_eq!(report.total(), bytes.len());
+}
@@ -0,0 +1,441 @@
+//! Interoperability with Arrow buffers and arrays.
+//!
+//! Archived numeric vectors can be handed to code which expects Arrow buffers
+//! without copying them. To do this, the archive has to be kept alive for as
+//! long as the Arrow buffers which point into it. An [`ArrowArchive`] holds an
+//! archive in a reference-counted Arrow [`Buffer`], and buffers exported from
+//! it share ownership of the archive.
+//!
+//! Exporting is zero-copy when:
+//!
+//! - The archived values are inside of the archive.
+//! - The archived values have the same byte order as the native values. This is
+//!   the case when the archive's endianness matches the target's.
+//! - The archived values are aligned for their native type. This is always the
+//!   case unless the `unaligned` feature is enabled, as long as the archive
+//!   itself is suitably aligned.
+//!
+//! Otherwise, the values are copied into a new buffer.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{arrow::ArrowArchive, rancor::Error, Archived};
+//!
+//! let bytes = rkyv::to_bytes::<Error>(&vec![1u32, 2, 3, 4]).unwrap();
+//! let archive = ArrowArchive::from_aligned_vec(bytes);
+//!
+//! let archived =
+//!     rkyv::access::<Archived<Vec<u32>>, Error>(archive.as_bytes()).unwrap();
+//! let buffer = archived.as_arrow_buffer(&archive);
+//! assert_eq!(buffer.typed_data::<u32>(), &[1, 2, 3, 4]);
+//!
+//! // The buffer keeps the archive alive on its own.
+//! drop(archive);
+//! assert_eq!(buffer.typed_data::<u32>()[3], 4);
+//! ```
+
+use core::{
+    mem::{align_of, size_of_val},
+    ptr::NonNull,
+};
+use std::sync::Arc;
+
+use arrow_array::{
+    types::{
+        Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+        UInt16Type, UInt32Type, UInt64Type, UInt8Type,
+    },
+    ArrowPrimitiveType, PrimitiveArray,
+};
+use arrow_buffer::{
+    alloc::Allocation, BooleanBuffer, Buffer, NullBuffer, ScalarBuffer,
+};
+
+use crate::{
+    nullable::{ArchivedNullableColumn, NullableLayout},
+    option::ArchivedOption,
+    primitive::{
+        ArchivedF32, ArchivedF64, ArchivedI16, ArchivedI32, ArchivedI64,
+        ArchivedU16, ArchivedU32, ArchivedU64,
+    },
+    util::AlignedVec,
+    vec::ArchivedVec,
+    Portable,
+};
+
+/// An archived primitive which has an equivalent Arrow primitive type.
+///
+/// # Safety
+///
+/// If `NATIVE` is `true`, then `Self` must have the same size and bit patterns
+/// as the native type of `Type`.
+pub unsafe trait ArrowNative: Portable {
+    /// The equivalent Arrow primitive type.
+    type Type: ArrowPrimitiveType;
+
+    /// Whether the archived value has the same representation as its native
+    /// value.
+    const NATIVE: bool;
+
+    /// Converts the archived value into its native value.
+    fn to_arrow_native(&self) -> <Self::Type as ArrowPrimitiveType>::Native;
+}
+
+macro_rules! impl_arrow_native {
+    ($($archived:ty => $arrow:ty),* $(,)?) => {
+        $(
+            // SAFETY: Archived primitives have the same size as their native
+            // types, and the same bit patterns when their byte order matches
+            // the target's.
+            unsafe impl ArrowNative for $archived {
+                type Type = $arrow;
+
+                const NATIVE: bool =
+                    cfg!(feature = "big_endian") == cfg!(target_endian = "big");
+
+                #[inline]
+                fn to_arrow_native(
+                    &self,
+                ) -> <Self::Type as ArrowPrimitiveType>::Native {
+                    self.to_native()
+                }
+            }
+        )*
+    };
+}
+
+impl_arrow_native! {
+    ArchivedI16 => Int16Type,
+    ArchivedI32 => Int32Type,
+    ArchivedI64 => Int64Type,
+    ArchivedU16 => UInt16Type,
+    ArchivedU32 => UInt32Type,
+    ArchivedU64 => UInt64Type,
+    ArchivedF32 => Float32Type,
+    ArchivedF64 => Float64Type,
+}
+
+// SAFETY: `i8` is its own native type.
+unsafe impl ArrowNative for i8 {
+    type Type = Int8Type;
+
+    const NATIVE: bool = true;
+
+    #[inline]
+    fn to_arrow_native(&self) -> i8 {
+        *self
+    }
+}
+
+// SAFETY: `u8` is its own native type.
+unsafe impl ArrowNative for u8 {
+    type Type = UInt8Type;
+
+    const NATIVE: bool = true;
+
+    #[inline]
+    fn to_arrow_native(&self) -> u8 {
+        *self
+    }
+}
+
+/// An archive held in a reference-counted Arrow buffer.
+///
+/// Buffers exported from an `ArrowArchive` point into its bytes when possible,
+/// and keep the archive alive after the `ArrowArchive` is dropped. Cloning an
+/// `ArrowArchive` only increments its reference count.
+///
+/// The archive must be suitably aligned to access it. Archives created from an
+/// [`AlignedVec`] keep its alignment.
+#[derive(Clone, Debug)]
+pub struct ArrowArchive {
+    buffer: Buffer,
+}
+
+impl ArrowArchive {
+    /// Creates an `ArrowArchive` from a buffer containing an archive.
+    #[inline]
+    pub fn new(buffer: Buffer) -> Self {
+        Self { buffer }
+    }
+
+    /// Creates an `ArrowArchive` which takes ownership of the bytes of an
+    /// archive without copying them.
+    pub fn from_aligned_vec<const A: usize>(mut bytes: AlignedVec<A>) -> Self {
+        let len = bytes.len();
+        // SAFETY: The pointer of an `AlignedVec` is never null.
+        let ptr = unsafe { NonNull::new_unchecked(bytes.as_mut_ptr()) };
+        let owner: Arc<dyn Allocation> = Arc::new(bytes);
+        // SAFETY: Moving the `AlignedVec` into `owner` does not move its
+        // bytes, so `ptr` points to `len` bytes which live as long as `owner`.
+        let buffer = unsafe { Buffer::from_custom_allocation(ptr, len, owner) };
+        Self { buffer }
+    }
+
+    /// Creates an `ArrowArchive` which shares ownership of the bytes of an
+    /// archive without copying them.
+    #[cfg(feature = "bytes")]
+    pub fn from_bytes(bytes: bytes::Bytes) -> Self {
+        let len = bytes.len();
+        // SAFETY: The pointer of a `Bytes` is never null.
+        let ptr = unsafe { NonNull::new_unchecked(bytes.as_ptr() as *mut u8) };
+        let owner: Arc<dyn Allocation> = Arc::new(bytes);
+        // SAFETY: Moving the `Bytes` into `owner` does not move its bytes, so
+        // `ptr` points to `len` bytes which live as long as `owner`. Arrow
+        // never writes to custom allocations.
+        let buffer = unsafe { Buffer::from_custom_allocation(ptr, len, owner) };
+        Self { buffer }
+    }
+
+    /// Returns the bytes of the archive.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.buffer.as_slice()
+    }
+
+    /// Returns the buffer holding the archive.
+    #[inline]
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Returns the buffer holding the archive.
+    #[inline]
+    pub fn into_buffer(self) -> Buffer {
+        self.buffer
+    }
+
+    /// Returns a buffer which shares ownership of the archive and points to
+    /// the given bytes.
+    ///
+    /// Returns `None` if the bytes are not inside of the archive or are not
+    /// aligned to `align`.
+    fn share(
+        &self,
+        ptr: *const u8,
+        len: usize,
+        align: usize,
+    ) -> Option<Buffer> {
+        let start = self.buffer.as_ptr() as usize;
+        let end = start + self.buffer.len();
+        let ptr = ptr as usize;
+
+        if ptr % align != 0 || ptr < start || ptr + len > end {
+            return None;
+        }
+
+        Some(self.buffer.slice_with_length(ptr - start, len))
+    }
+
+    fn share_values<T: ArrowNative>(&self, values: &[T]) -> Option<Buffer> {
+        if !T::NATIVE {
+            return None;
+        }
+
+        self.share(
+            values.as_ptr().cast(),
+            size_of_val(values),
+            align_of::<<T::Type as ArrowPrimitiveType>::Native>(),
+        )
+    }
+}
+
+fn copy_values<T: ArrowNative>(values: &[T]) -> Buffer {
+    values.iter().map(T::to_arrow_native).collect()
+}
+
+impl<T: ArrowNative> ArchivedVec<T> {
+    /// Returns an Arrow buffer containing the native values of the vector.
+    ///
+    /// The buffer points into the archive and shares ownership of it when the
+    /// vector is in `archive`, and its values have the same byte order and
+    /// alignment as their native values. Otherwise, the values are copied as
+    /// with [`to_arrow_buffer`](ArchivedVec::to_arrow_buffer).
+    pub fn as_arrow_buffer(&self, archive: &ArrowArchive) -> Buffer {
+        archive
+            .share_values(self.as_slice())
+            .unwrap_or_else(|| self.to_arrow_buffer())
+    }
+
+    /// Copies the native values of the vector into a new Arrow buffer.
+    pub fn to_arrow_buffer(&self) -> Buffer {
+        copy_values(self.as_slice())
+    }
+}
+
+impl<T: ArrowNative> ArchivedVec<ArchivedOption<T>> {
+    /// Copies the values of a vector of optional values into a new Arrow
+    /// array.
+    pub fn to_arrow_array(&self) -> PrimitiveArray<T::Type> {
+        self.iter()
+            .map(|value| value.as_ref().map(T::to_arrow_native))
+            .collect()
+    }
+}
+
+impl<T: ArrowNative, L: NullableLayout> ArchivedNullableColumn<T, L> {
+    /// Returns an Arrow array containing the values of the column.
+    ///
+    /// Columns with the [`Slotted`](crate::nullable::Slotted) layout store a
+    /// value for every element, and the values buffer is shared with the
+    /// archive under the same conditions as
+    /// [`ArchivedVec::as_arrow_buffer`]. Columns with the
+    /// [`Packed`](crate::nullable::Packed) layout always copy their values.
+    ///
+    /// The validity bitmap has the same layout as an Arrow null buffer, and
+    /// is shared with the archive if the archive is little-endian.
+    pub fn to_arrow_array(
+        &self,
+        archive: &ArrowArchive,
+    ) -> PrimitiveArray<T::Type> {
+        let len = self.len();
+
+        let values = if L::PACKED {
+            None
+        } else {
+            archive.share_values(self.values())
+        };
+        let values = values.unwrap_or_else(|| {
+            if L::PACKED {
+                self.iter()
+                    .map(|value| {
+                        value.map_or_else(Default::default, T::to_arrow_native)
+                    })
+                    .collect()
+            } else {
+                copy_values(self.values())
+            }
+        });
+
+        let nulls = (self.null_count() != 0).then(|| {
+            let validity = self.validity();
+            let bits = if cfg!(feature = "big_endian") {
+                None
+            } else {
+                archive.share(
+                    validity.as_ptr().cast(),
+                    size_of_val(validity),
+                    1,
+                )
+            };
+            let bits = bits.map_or_else(
+                || BooleanBuffer::from_iter((0..len).map(|i| self.is_valid(i))),
+                |bits| BooleanBuffer::new(bits, 0, len),
+            );
+            NullBuffer::new(bits)
+        });
+
+        PrimitiveArray::new(ScalarBuffer::new(values, 0, len), nulls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{types::UInt32Type, Array, PrimitiveArray};
+    use rancor::Panic;
+
+    use super::{ArrowArchive, ArrowNative as _};
+    use crate::{
+        access, nullable::Slotted, primitive::ArchivedU32, to_bytes,
+        with::AsNullableColumn, Archive, Archived, Serialize,
+    };
+
+    #[test]
+    fn zero_copy_buffer() {
+        let values = (0..100u32).collect::<Vec<_>>();
+        let archive =
+            ArrowArchive::from_aligned_vec(to_bytes::<Panic>(&values).unwrap());
+        let archived =
+            access::<Archived<Vec<u32>>, Panic>(archive.as_bytes()).unwrap();
+
+        let shared = archived.as_arrow_buffer(&archive);
+        let copied = archived.to_arrow_buffer();
+        if ArchivedU32::NATIVE {
+            assert_eq!(shared.as_ptr(), archived.as_ptr().cast());
+        }
+        assert_ne!(copied.as_ptr(), archived.as_ptr().cast());
+        assert_eq!(shared, copied);
+
+        // The shared buffer keeps the archive alive.
+        drop(archive);
+        assert_eq!(shared.typed_data::<u32>(), values.as_slice());
+    }
+
+    #[test]
+    fn copy_from_other_archive() {
+        let bytes = to_bytes::<Panic>(&vec![1u32, 2, 3]).unwrap();
+        let archived =
+            access::<Archived<Vec<u32>>, Panic>(bytes.as_slice()).unwrap();
+
+        let other = ArrowArchive::from_aligned_vec(
+            to_bytes::<Panic>(&vec![1u32, 2, 3]).unwrap(),
+        );
+        let buffer = archived.as_arrow_buffer(&other);
+        assert_ne!(buffer.as_ptr(), archived.as_ptr().cast());
+        assert_eq!(buffer.typed_data::<u32>(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn optional_values() {
+        let values = vec![Some(1u32), None, Some(3)];
+        let bytes = to_bytes::<Panic>(&values).unwrap();
+        let archived =
+            access::<Archived<Vec<Option<u32>>>, Panic>(bytes.as_slice())
+                .unwrap();
+
+        let array = archived.to_arrow_array();
+        assert_eq!(array, PrimitiveArray::<UInt32Type>::from(values));
+    }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Columns {
+        #[with(AsNullableColumn)]
+        packed: Vec<Option<u32>>,
+        #[with(AsNullableColumn<Slotted>)]
+        slotted: Vec<Option<u32>>,
+    }
+
+    #[test]
+    fn nullable_columns() {
+        for values in [
+            vec![None; 70],
+            (0..70).map(Some).collect(),
+            (0..70)
+                .map(|i| if i % 3 == 0 { None } else { Some(i) })
+                .collect::<Vec<_>>(),
+        ] {
+            let columns = Columns {
+                packed: values.clone(),
+                slotted: values.clone(),
+            };
+            let archive = ArrowArchive::from_aligned_vec(
+                to_bytes::<Panic>(&columns).unwrap(),
+            );
+            let archived =
+                access::<ArchivedColumns, Panic>(archive.as_bytes()).unwrap();
+            let expected = PrimitiveArray::<UInt32Type>::from(values);
+
+            let packed = archived.packed.to_arrow_array(&archive);
+            assert_eq!(packed, expected);
+
+            let slotted = archived.slotted.to_arrow_array(&archive);
+            assert_eq!(slotted, expected);
+            if ArchivedU32::NATIVE {
+                assert_eq!(
+                    slotted.values().inner().as_ptr(),
+                    archived.slotted.values().as_ptr().cast(),
+                );
+            }
+            if let (Some(nulls), false) =
+                (slotted.nulls(), cfg!(feature = "big_endian"))
+            {
+                assert_eq!(
+                    nulls.buffer().as_ptr(),
+                    archived.slotted.validity().as_ptr().cast(),
+                );
+            }
+        }
+    }
+}
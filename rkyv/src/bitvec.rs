@@ -9,7 +9,11 @@ use bitvec::{
     view::{BitView, BitViewSized},
 };
 
-use crate::{primitive::ArchivedUsize, vec::ArchivedVec, Portable};
+use crate::{
+    primitive::{usize_from_archived, ArchivedUsize},
+    vec::ArchivedVec,
+    Portable,
+};
 
 /// An archived `BitVec`.
 // We also have to store the bit length in the archived `BitVec`.
@@ -32,7 +36,7 @@ impl<T: BitStore, O: BitOrder> Deref for ArchivedBitVec<T, O> {
     type Target = BitSlice<T, O>;
 
     fn deref(&self) -> &Self::Target {
-        &self.inner.view_bits::<O>()[..self.bit_len.to_native() as usize]
+        &self.inner.view_bits::<O>()[..usize_from_archived(self.bit_len)]
     }
 }
 
@@ -0,0 +1,327 @@
+//! Unsigned integers archived with validated bounds.
+//!
+//! An integer field can be archived with the
+//! [`Bounded<MIN, MAX>`](crate::with::Bounded) wrapper, either with
+//! `#[with(Bounded<MIN, MAX>)]` or `#[rkyv(with = Bounded<MIN, MAX>)]`. The
+//! archived type is an [`ArchivedBounded`], which has the same layout as the
+//! archived integer and only validates if the integer is between `MIN` and
+//! `MAX` inclusive. This lets types with range invariants, like port numbers,
+//! rely on those invariants when reading validated archives.
+
+use core::{cmp::Ordering, fmt};
+
+use munge::munge;
+
+use crate::{Archive, Archived, Place, Portable};
+
+/// An unsigned integer type which can be archived with bounds.
+///
+/// This is implemented for `u8`, `u16`, `u32`, `u64`, and `u128`.
+pub trait BoundedRepr:
+    Archive<Resolver = ()> + Copy + Ord + Into<u128> + sealed::Sealed
+{
+    /// Returns the native value of the given archived integer.
+    fn from_archived(archived: &Archived<Self>) -> Self;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+impl sealed::Sealed for u8 {}
+
+impl BoundedRepr for u8 {
+    #[inline]
+    fn from_archived(archived: &Archived<Self>) -> Self {
+        *archived
+    }
+}
+
+macro_rules! impl_multibyte_bounded_repr {
+    ($($ty:ty),*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl BoundedRepr for $ty {
+                #[inline]
+                fn from_archived(archived: &Archived<Self>) -> Self {
+                    archived.to_native()
+                }
+            }
+        )*
+    };
+}
+
+impl_multibyte_bounded_repr!(u16, u32, u64, u128);
+
+/// An error which occurs when a bounded integer is outside of its bounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfBounds {
+    value: u128,
+    min: u128,
+    max: u128,
+}
+
+impl OutOfBounds {
+    /// Returns a new error for the given value and bounds.
+    pub fn new(value: u128, min: u128, max: u128) -> Self {
+        Self { value, min, max }
+    }
+
+    /// Returns the value which was out of bounds.
+    pub fn value(&self) -> u128 {
+        self.value
+    }
+
+    /// Returns the inclusive lower bound.
+    pub fn min(&self) -> u128 {
+        self.min
+    }
+
+    /// Returns the inclusive upper bound.
+    pub fn max(&self) -> u128 {
+        self.max
+    }
+}
+
+impl fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "integer {} is out of bounds, expected a value in {}..={}",
+            self.value, self.min, self.max,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OutOfBounds {}
+
+/// An archived integer which is between `MIN` and `MAX` inclusive.
+///
+/// This has the same layout as `Archived<T>`.
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(transparent)]
+pub struct ArchivedBounded<T: BoundedRepr, const MIN: u128, const MAX: u128> {
+    value: Archived<T>,
+}
+
+impl<T: BoundedRepr, const MIN: u128, const MAX: u128>
+    ArchivedBounded<T, MIN, MAX>
+{
+    /// Returns whether the given value is between `MIN` and `MAX` inclusive.
+    #[inline]
+    pub fn in_bounds(value: T) -> bool {
+        (MIN..=MAX).contains(&value.into())
+    }
+
+    /// Returns the archived integer.
+    ///
+    /// The integer is always in bounds if the archive was validated.
+    #[inline]
+    pub fn get(&self) -> T {
+        T::from_archived(&self.value)
+    }
+
+    /// Returns the archived integer, or an error if it is out of bounds.
+    ///
+    /// This only fails if the archive was accessed without validation.
+    pub fn try_get(&self) -> Result<T, OutOfBounds> {
+        let value = self.get();
+        if Self::in_bounds(value) {
+            Ok(value)
+        } else {
+            Err(OutOfBounds::new(value.into(), MIN, MAX))
+        }
+    }
+
+    /// Resolves an `ArchivedBounded` from an integer.
+    #[inline]
+    pub fn resolve_from_value(value: T, out: Place<Self>) {
+        munge!(let ArchivedBounded { value: out_value } = out);
+        value.resolve((), out_value);
+    }
+}
+
+impl<T, const MIN: u128, const MAX: u128> fmt::Debug
+    for ArchivedBounded<T, MIN, MAX>
+where
+    T: BoundedRepr,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value: u128 = self.get().into();
+        f.debug_tuple("ArchivedBounded").field(&value).finish()
+    }
+}
+
+impl<T, const MIN: u128, const MAX: u128> PartialEq
+    for ArchivedBounded<T, MIN, MAX>
+where
+    T: BoundedRepr,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: BoundedRepr, const MIN: u128, const MAX: u128> Eq
+    for ArchivedBounded<T, MIN, MAX>
+{
+}
+
+impl<T, const MIN: u128, const MAX: u128> PartialOrd
+    for ArchivedBounded<T, MIN, MAX>
+where
+    T: BoundedRepr,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, const MIN: u128, const MAX: u128> Ord for ArchivedBounded<T, MIN, MAX>
+where
+    T: BoundedRepr,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.get().cmp(&other.get())
+    }
+}
+
+impl<T, const MIN: u128, const MAX: u128> PartialEq<T>
+    for ArchivedBounded<T, MIN, MAX>
+where
+    T: BoundedRepr,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.get() == *other
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+const _: () = {
+    use bytecheck::CheckBytes;
+    use rancor::{fail, Fallible, Source};
+
+    unsafe impl<T, C, const MIN: u128, const MAX: u128> CheckBytes<C>
+        for ArchivedBounded<T, MIN, MAX>
+    where
+        T: BoundedRepr,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        unsafe fn check_bytes(
+            value: *const Self,
+            _: &mut C,
+        ) -> Result<(), C::Error> {
+            // SAFETY: Every bit pattern is a valid archived integer, and the
+            // caller has guaranteed that `value` is aligned and points to
+            // enough bytes for one.
+            let value = unsafe { &*value };
+            match value.try_get() {
+                Ok(_) => Ok(()),
+                Err(e) => fail!(e),
+            }
+        }
+    }
+};
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+    use core::mem::{align_of, size_of};
+
+    use rancor::{Failure, Panic};
+
+    use super::{ArchivedBounded, OutOfBounds};
+    use crate::{
+        access, access_unchecked, primitive::ArchivedU16, test::roundtrip_with,
+        to_bytes, with::Bounded, Archive, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, derive(Debug))]
+    struct PortNumber(#[rkyv(with = Bounded<1, 49151>)] u16);
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, derive(Debug))]
+    struct Limits {
+        #[with(Bounded<0, 100>)]
+        percent: u8,
+        #[with(Bounded<1_000, { u64::MAX as u128 }>)]
+        timeout: u64,
+        #[rkyv(with = Bounded<{ 1 << 64 }, { u128::MAX }>)]
+        huge: u128,
+    }
+
+    #[test]
+    fn archived_layout() {
+        type Archived = ArchivedBounded<u16, 1, 49151>;
+
+        assert_eq!(size_of::<Archived>(), size_of::<ArchivedU16>());
+        assert_eq!(align_of::<Archived>(), align_of::<ArchivedU16>());
+        assert_eq!(size_of::<ArchivedPortNumber>(), size_of::<ArchivedU16>());
+    }
+
+    #[test]
+    fn roundtrip_in_bounds() {
+        for port in [1, 80, 8080, 49151] {
+            roundtrip_with(&PortNumber(port), |a, b| {
+                assert_eq!(b.0.get(), a.0);
+                assert_eq!(b.0, a.0);
+            });
+        }
+
+        let value = Limits {
+            percent: 100,
+            timeout: 30_000,
+            huge: u128::MAX,
+        };
+        roundtrip_with(&value, |a, b| {
+            assert_eq!(b.percent, a.percent);
+            assert_eq!(b.timeout, a.timeout);
+            assert_eq!(b.huge, a.huge);
+        });
+    }
+
+    #[test]
+    fn reject_out_of_bounds() {
+        let mut bytes = to_bytes::<Panic>(&PortNumber(443)).unwrap();
+        let root = bytes.len() - size_of::<ArchivedPortNumber>();
+        access::<ArchivedPortNumber, Failure>(&bytes).unwrap();
+
+        for port in [0u16, 49152, u16::MAX] {
+            let raw = to_bytes::<Panic>(&port).unwrap();
+            bytes[root..root + raw.len()].copy_from_slice(&raw);
+            access::<ArchivedPortNumber, Failure>(&bytes).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn try_get_out_of_bounds() {
+        let bytes = to_bytes::<Panic>(&0u16).unwrap();
+        let archived = unsafe {
+            access_unchecked::<ArchivedBounded<u16, 1, 49151>>(&bytes)
+        };
+        let error = archived.try_get().unwrap_err();
+        assert_eq!(error, OutOfBounds::new(0, 1, 49151));
+        assert_eq!(
+            error.to_string(),
+            "integer 0 is out of bounds, expected a value in 1..=49151",
+        );
+
+        let archived =
+            unsafe { access_unchecked::<ArchivedPortNumber>(&bytes) };
+        crate::deserialize::<PortNumber, _, Failure>(archived, &mut ())
+            .unwrap_err();
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic = "out of bounds"]
+    fn serialize_out_of_bounds() {
+        to_bytes::<Panic>(&PortNumber(0)).unwrap();
+    }
+}
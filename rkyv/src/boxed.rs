@@ -1,12 +1,19 @@
 //! An archived version of `Box`.
 
-use core::{borrow::Borrow, cmp, fmt, hash, ops::Deref, pin::Pin};
+use core::{
+    borrow::Borrow,
+    cmp, fmt, hash,
+    ops::{Deref, Index},
+    pin::Pin,
+    slice::SliceIndex,
+};
 
 use munge::munge;
 use rancor::Fallible;
 
 use crate::{
-    ArchivePointee, ArchiveUnsized, Place, Portable, RelPtr, SerializeUnsized,
+    collections::btree_map::LookupOrd, hash::LookupKey, ArchivePointee,
+    ArchiveUnsized, Place, Portable, RelPtr, SerializeUnsized,
 };
 
 /// An archived [`Box`].
@@ -31,6 +38,14 @@ impl<T: ArchivePointee + ?Sized> ArchivedBox<T> {
         unsafe { &*self.ptr.as_ptr() }
     }
 
+    /// Returns the archived pointer metadata of the value of this archived
+    /// box.
+    ///
+    /// For slices and strings, this is the archived length.
+    pub fn metadata(&self) -> &T::ArchivedMetadata {
+        self.ptr.metadata()
+    }
+
     /// Returns a pinned mutable reference to the value of this archived box
     pub fn get_pin_mut(self: Pin<&mut Self>) -> Pin<&mut T> {
         let ptr = unsafe { self.map_unchecked_mut(|s| &mut s.ptr) };
@@ -72,6 +87,35 @@ impl<T: ArchivePointee + ?Sized> ArchivedBox<T> {
     }
 }
 
+impl<T> ArchivedBox<[T]> {
+    /// Returns the elements of this archived boxed slice.
+    pub fn as_slice(&self) -> &[T] {
+        self.get()
+    }
+
+    /// Returns the number of elements in this archived boxed slice.
+    pub fn len(&self) -> usize {
+        self.metadata().to_native() as usize
+    }
+
+    /// Returns whether this archived boxed slice is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to an element or subslice of this archived boxed
+    /// slice, or `None` if the index is out of bounds.
+    ///
+    /// This is the same as [`<[T]>::get`](slice::get), which is shadowed by
+    /// [`get`](Self::get) when called through an `ArchivedBox`.
+    pub fn get_index<I: SliceIndex<[T]>>(
+        &self,
+        index: I,
+    ) -> Option<&I::Output> {
+        self.as_slice().get(index)
+    }
+}
+
 impl<T: ArchivePointee + ?Sized> AsRef<T> for ArchivedBox<T> {
     fn as_ref(&self) -> &T {
         self.get()
@@ -84,6 +128,22 @@ impl<T: ArchivePointee + ?Sized> Borrow<T> for ArchivedBox<T> {
     }
 }
 
+impl<T: ArchivePointee + hash::Hash + Eq + ?Sized> LookupKey<ArchivedBox<T>>
+    for T
+{
+    #[inline]
+    fn equivalent(&self, key: &ArchivedBox<T>) -> bool {
+        self == key.get()
+    }
+}
+
+impl<T: ArchivePointee + Ord + ?Sized> LookupOrd<ArchivedBox<T>> for T {
+    #[inline]
+    fn compare(&self, key: &ArchivedBox<T>) -> cmp::Ordering {
+        self.cmp(key.get())
+    }
+}
+
 impl<T: ArchivePointee + ?Sized> fmt::Debug for ArchivedBox<T>
 where
     T::ArchivedMetadata: fmt::Debug,
@@ -101,6 +161,14 @@ impl<T: ArchivePointee + ?Sized> Deref for ArchivedBox<T> {
     }
 }
 
+impl<T, I: SliceIndex<[T]>> Index<I> for ArchivedBox<[T]> {
+    type Output = I::Output;
+
+    fn index(&self, index: I) -> &Self::Output {
+        self.as_slice().index(index)
+    }
+}
+
 impl<T: ArchivePointee + fmt::Display + ?Sized> fmt::Display
     for ArchivedBox<T>
 {
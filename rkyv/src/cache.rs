@@ -0,0 +1,543 @@
+//! Archive caches stored in files.
+//!
+//! [`write_archive_atomic`] serializes a value into a temporary file next to
+//! the cache file, flushes it to disk, and then renames it over the cache file.
+//! Readers never observe a partially-written cache: they see either the old
+//! file or the new one. [`read_archive`] memory-maps a cache file and validates
+//! it, and returns a [`CacheError`] which distinguishes IO errors from caches
+//! that are stale or corrupt and should be rebuilt.
+//!
+//! Writers and readers also coordinate through an advisory lock on a lock file
+//! next to the cache file. Writers hold it exclusively while they write and
+//! rename the temporary file, so concurrent writers replace the cache one at a
+//! time. Readers hold it shared while they open and map the cache file. The
+//! lock is advisory, so it only coordinates processes which use this module.
+//!
+//! Cache files are framed with a [checksum trailer](crate::checksum), so files
+//! which were truncated or corrupted after being written are detected even if
+//! their bytes happen to be a valid archive. Mappings are page-aligned, so the
+//! archive bytes are always aligned.
+//!
+//! # Platform behavior
+//!
+//! On Unix, replacing a cache file does not affect readers which have the old
+//! file mapped; they keep reading the old file until they drop their
+//! [`MmapArchive`]. The directory containing the cache file is synced after
+//! the rename so that the replacement survives a crash.
+//!
+//! On Windows, a file can't be replaced while it is mapped. Replacing a cache
+//! file which is mapped by a reader returns an IO error (usually with kind
+//! [`PermissionDenied`](io::ErrorKind::PermissionDenied)), and can be retried
+//! after the readers drop their archives.
+//!
+//! # Examples
+//!
+//! ```
+//! use rkyv::{
+//!     cache::{read_archive, write_archive_atomic},
+//!     rancor::Error,
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[rkyv(check_bytes)]
+//! struct Index {
+//!     words: Vec<String>,
+//! }
+//!
+//! let path = std::env::temp_dir()
+//!     .join(format!("rkyv-doc-cache-{}", std::process::id()));
+//! let index = Index {
+//!     words: vec!["hello".to_string(), "world".to_string()],
+//! };
+//! write_archive_atomic::<_, Error>(&path, &index).unwrap();
+//!
+//! // SAFETY: The cache file is only ever replaced with
+//! // `write_archive_atomic`.
+//! match unsafe { read_archive::<Index, Error>(&path) } {
+//!     Ok(archive) => assert_eq!(archive.words[1].as_str(), "world"),
+//!     Err(e) if e.needs_rebuild() => {
+//!         // Regenerate the cache
+//!         # unreachable!();
+//!     }
+//!     Err(e) => panic!("failed to read cache: {e}"),
+//! }
+//!
+//! std::fs::remove_file(&path).unwrap();
+//! ```
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::Deref,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+};
+
+use bytecheck::CheckBytes;
+use memmap2::Mmap;
+use rancor::{ResultExt as _, Source, Strategy};
+
+use crate::{
+    access_unchecked,
+    checksum::{to_bytes_checksummed, verify_checksum},
+    ser::DefaultSerializer,
+    util::AlignedVec,
+    validation::validators::DefaultValidator,
+    Archive, Serialize,
+};
+
+/// An error which occurs when reading a cache file.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum CacheError<E> {
+    /// The cache file could not be opened or mapped.
+    Io(io::Error),
+    /// The cache file is stale or corrupt, and should be rebuilt.
+    Invalid(E),
+}
+
+impl<E> CacheError<E> {
+    /// Returns whether the cache file is missing, stale, or corrupt.
+    ///
+    /// Callers can regenerate the cache when this returns `true`. Other IO
+    /// errors, like missing permissions, usually can't be fixed by rebuilding
+    /// the cache.
+    pub fn needs_rebuild(&self) -> bool {
+        match self {
+            Self::Io(e) => e.kind() == io::ErrorKind::NotFound,
+            Self::Invalid(_) => true,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for CacheError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read cache file: {e}"),
+            Self::Invalid(e) => write!(
+                f,
+                "cache file is stale or corrupt and should be rebuilt: {e}",
+            ),
+        }
+    }
+}
+
+impl<E> std::error::Error for CacheError<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Invalid(e) => Some(e),
+        }
+    }
+}
+
+/// Returns the path of the lock file for the cache file at `path`.
+fn lock_path(path: &Path) -> io::Result<PathBuf> {
+    let Some(file_name) = path.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cache path does not have a file name",
+        ));
+    };
+    let mut lock_name = std::ffi::OsString::from(".");
+    lock_name.push(file_name);
+    lock_name.push(".lock");
+    Ok(path.with_file_name(lock_name))
+}
+
+/// Opens the lock file for the cache file at `path` and locks it exclusively.
+///
+/// The lock is released when the returned file is dropped.
+fn lock_exclusive(path: &Path) -> io::Result<File> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(lock_path(path)?)?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Opens the lock file for the cache file at `path` and locks it shared.
+///
+/// Returns `None` if the lock file does not exist, since readers may not be
+/// able to create files next to the cache file. The lock is released when the
+/// returned file is dropped.
+fn lock_shared(path: &Path) -> io::Result<Option<File>> {
+    let file = match OpenOptions::new().read(true).open(lock_path(path)?) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    file.lock_shared()?;
+    Ok(Some(file))
+}
+
+/// Returns a path for a temporary file in the same directory as `path`.
+///
+/// The temporary file must be in the same directory so that it can be renamed
+/// over the cache file, which is only atomic within a file system.
+fn temp_path(path: &Path) -> io::Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let Some(file_name) = path.file_name() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cache path does not have a file name",
+        ));
+    };
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(
+        ".{}-{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed),
+    ));
+    Ok(path.with_file_name(temp_name))
+}
+
+fn write_synced(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut file =
+        OpenOptions::new().write(true).create_new(true).open(path)?;
+    file.write_all(bytes)?;
+    file.sync_all()
+}
+
+#[cfg(unix)]
+fn sync_parent(path: &Path) -> io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_parent(_: &Path) -> io::Result<()> {
+    // Directories can't be opened and synced on other platforms. Renames are
+    // journaled by NTFS.
+    Ok(())
+}
+
+fn open_shared(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true);
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt as _;
+
+        // FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE, so that the
+        // file can be replaced while it is open but not mapped.
+        options.share_mode(0x7);
+    }
+    options.open(path)
+}
+
+/// Serializes the given value and atomically replaces the cache file at `path`
+/// with it.
+///
+/// The archive is written to a temporary file in the same directory and
+/// flushed to disk before being renamed over `path`. If writing fails, the
+/// temporary file is removed and the cache file is left unchanged. The lock
+/// file for `path` is held exclusively while writing, and waits for other
+/// writers and for readers which are opening the cache file.
+///
+/// See the [module docs](self) for platform-specific behavior.
+pub fn write_archive_atomic<T, E>(
+    path: impl AsRef<Path>,
+    value: &T,
+) -> Result<(), E>
+where
+    T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, E>>,
+    E: Source,
+{
+    let path = path.as_ref();
+    let bytes = to_bytes_checksummed::<E>(value)?;
+    let temp_path = temp_path(path).into_error()?;
+    let lock = lock_exclusive(path).into_error()?;
+
+    let result = write_synced(&temp_path, &bytes)
+        .and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.into_error()?;
+    drop(lock);
+
+    sync_parent(path).into_error()
+}
+
+/// Memory-maps the cache file at `path`, and validates the archive in it.
+///
+/// Returns [`CacheError::Invalid`] if the checksum trailer is missing or does
+/// not match, or if the archive is not a valid `T::Archived`. The lock file
+/// for `path` is held shared while the cache file is opened and mapped, if it
+/// exists.
+///
+/// # Safety
+///
+/// The cache file must not be modified in place while the returned archive is
+/// alive. Files which are only ever replaced with [`write_archive_atomic`] are
+/// never modified in place.
+pub unsafe fn read_archive<T, E>(
+    path: impl AsRef<Path>,
+) -> Result<MmapArchive<T>, CacheError<E>>
+where
+    T: Archive,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
+    E: Source,
+{
+    let path = path.as_ref();
+    let lock = lock_shared(path).map_err(CacheError::Io)?;
+    let file = open_shared(path).map_err(CacheError::Io)?;
+    // SAFETY: The caller has guaranteed that the file will not be modified
+    // while it is mapped.
+    let map = unsafe { Mmap::map(&file) }.map_err(CacheError::Io)?;
+    drop(lock);
+
+    let archive = verify_checksum::<E>(&map).map_err(CacheError::Invalid)?;
+    crate::access::<T::Archived, E>(archive).map_err(CacheError::Invalid)?;
+    let len = archive.len();
+
+    Ok(MmapArchive {
+        map,
+        len,
+        _phantom: PhantomData,
+    })
+}
+
+/// An archived `T` in a memory-mapped cache file.
+///
+/// Returned by [`read_archive`].
+pub struct MmapArchive<T: Archive> {
+    map: Mmap,
+    len: usize,
+    _phantom: PhantomData<T::Archived>,
+}
+
+impl<T: Archive> MmapArchive<T> {
+    /// Returns the archive bytes, without the checksum trailer.
+    pub fn bytes(&self) -> &[u8] {
+        &self.map[..self.len]
+    }
+
+    /// Returns a reference to the archived value.
+    pub fn archived(&self) -> &T::Archived {
+        // SAFETY: The archive was validated when the file was mapped, and the
+        // caller of `read_archive` guaranteed that it won't be modified.
+        unsafe { access_unchecked::<T::Archived>(self.bytes()) }
+    }
+}
+
+impl<T: Archive> Deref for MmapArchive<T> {
+    type Target = T::Archived;
+
+    fn deref(&self) -> &Self::Target {
+        self.archived()
+    }
+}
+
+impl<T: Archive> fmt::Debug for MmapArchive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MmapArchive")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        thread,
+        time::Duration,
+    };
+
+    use rancor::{Error, Failure};
+
+    use super::{
+        lock_exclusive, read_archive, temp_path, write_archive_atomic,
+        CacheError,
+    };
+    use crate::{Archive, Serialize};
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Generation {
+        generation: u32,
+        payload: Vec<u32>,
+    }
+
+    impl Generation {
+        fn new(generation: u32) -> Self {
+            Self {
+                generation,
+                payload: vec![generation; 1024],
+            }
+        }
+    }
+
+    fn read_generation(path: &Path) -> Result<u32, CacheError<Failure>> {
+        let archive = unsafe { read_archive::<Generation, Failure>(path)? };
+        let generation = archive.generation.to_native();
+        assert!(archive.payload.iter().all(|x| x.to_native() == generation));
+        Ok(generation)
+    }
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("rkyv-cache-test-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        fn cache_path(&self) -> PathBuf {
+            self.0.join("cache.bin")
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn write_and_read() {
+        let dir = TempDir::new("write-and-read");
+        let path = dir.cache_path();
+
+        write_archive_atomic::<_, Error>(&path, &Generation::new(1)).unwrap();
+        assert_eq!(read_generation(&path).unwrap(), 1);
+        write_archive_atomic::<_, Error>(&path, &Generation::new(2)).unwrap();
+        assert_eq!(read_generation(&path).unwrap(), 2);
+
+        // Only the cache file and its lock file are left behind.
+        assert_eq!(fs::read_dir(&dir.0).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn writers_wait_for_lock() {
+        let dir = TempDir::new("lock");
+        let path = dir.cache_path();
+        write_archive_atomic::<_, Error>(&path, &Generation::new(1)).unwrap();
+
+        let lock = lock_exclusive(&path).unwrap();
+        let writer = {
+            let path = path.clone();
+            thread::spawn(move || {
+                write_archive_atomic::<_, Error>(&path, &Generation::new(2))
+                    .unwrap();
+            })
+        };
+        thread::sleep(Duration::from_millis(50));
+        assert!(!writer.is_finished());
+        drop(lock);
+
+        writer.join().unwrap();
+        assert_eq!(read_generation(&path).unwrap(), 2);
+    }
+
+    #[test]
+    fn missing_file() {
+        let dir = TempDir::new("missing");
+        let error = read_generation(&dir.cache_path()).unwrap_err();
+        assert!(matches!(error, CacheError::Io(_)));
+        assert!(error.needs_rebuild());
+    }
+
+    #[test]
+    fn truncated_temp_file() {
+        let dir = TempDir::new("truncated-temp");
+        let path = dir.cache_path();
+        write_archive_atomic::<_, Error>(&path, &Generation::new(1)).unwrap();
+
+        // A writer crashed after writing part of its temporary file, and
+        // before renaming it over the cache file.
+        let bytes = fs::read(&path).unwrap();
+        let temp = temp_path(&path).unwrap();
+        fs::write(&temp, &bytes[..bytes.len() / 2]).unwrap();
+
+        assert_eq!(read_generation(&path).unwrap(), 1);
+        write_archive_atomic::<_, Error>(&path, &Generation::new(2)).unwrap();
+        assert_eq!(read_generation(&path).unwrap(), 2);
+    }
+
+    #[test]
+    fn truncated_cache_file() {
+        let dir = TempDir::new("truncated-cache");
+        let path = dir.cache_path();
+        write_archive_atomic::<_, Error>(&path, &Generation::new(1)).unwrap();
+
+        // A writer which didn't use `write_archive_atomic` crashed partway
+        // through overwriting the cache file.
+        let bytes = fs::read(&path).unwrap();
+        for len in [0, 8, bytes.len() / 2, bytes.len() - 1] {
+            fs::write(&path, &bytes[..len]).unwrap();
+            let error = read_generation(&path).unwrap_err();
+            assert!(matches!(error, CacheError::Invalid(_)));
+            assert!(error.needs_rebuild());
+        }
+    }
+
+    #[test]
+    fn corrupt_cache_file() {
+        let dir = TempDir::new("corrupt");
+        let path = dir.cache_path();
+        write_archive_atomic::<_, Error>(&path, &Generation::new(1)).unwrap();
+
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[0] ^= 1;
+        fs::write(&path, &bytes).unwrap();
+        let error = read_generation(&path).unwrap_err();
+        assert!(matches!(error, CacheError::Invalid(_)));
+    }
+
+    // Windows does not allow mapped files to be replaced.
+    #[cfg(unix)]
+    #[test]
+    fn concurrent_reader() {
+        const GENERATIONS: u32 = 50;
+
+        let dir = TempDir::new("concurrent");
+        let path = dir.cache_path();
+        write_archive_atomic::<_, Error>(&path, &Generation::new(0)).unwrap();
+
+        let reader = {
+            let path = path.clone();
+            thread::spawn(move || {
+                // Every read sees a complete generation, and generations are
+                // never seen out of order.
+                let mut last = 0;
+                while last != GENERATIONS - 1 {
+                    let generation = read_generation(&path).unwrap();
+                    assert!(generation >= last);
+                    last = generation;
+                }
+            })
+        };
+
+        for generation in 1..GENERATIONS {
+            write_archive_atomic::<_, Error>(
+                &path,
+                &Generation::new(generation),
+            )
+            .unwrap();
+        }
+        reader.join().unwrap();
+    }
+}
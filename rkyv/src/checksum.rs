@@ -0,0 +1,384 @@
+//! Checksummed archives for detecting torn or corrupted writes.
+//!
+//! [`to_bytes_checksummed`] serializes a value and appends a trailer containing
+//! a checksum of the archive bytes. [`access_checksummed`] verifies the trailer
+//! before validating the archive, and then accesses the root as if the trailer
+//! were not there.
+//!
+//! # Trailer layout
+//!
+//! The trailer is [`TRAILER_LEN`] bytes long and immediately follows the
+//! archive bytes:
+//!
+//! | Offset | Size | Contents                                                |
+//! |--------|------|---------------------------------------------------------|
+//! | 0      | 8    | The checksum of the archive bytes, as a little-endian   |
+//! |        |      | `u64`. 32-bit checksums are zero-extended.              |
+//! | 8      | 1    | The [`ChecksumAlgorithm`] ID.                           |
+//! | 9      | 1    | The trailer version, currently [`TRAILER_VERSION`].     |
+//! | 10     | 2    | Reserved, must be zero.                                 |
+//! | 12     | 4    | The magic bytes [`TRAILER_MAGIC`].                      |
+//!
+//! Newer trailer versions may change the length and layout of the trailer
+//! except for the version and magic bytes. Trailers with an unsupported
+//! version are rejected.
+//!
+//! Checksummed bytes can't be accessed directly with
+//! [`access_unchecked`](crate::access_unchecked) because the root is no longer
+//! at the end of the bytes. Use [`verify_checksum`] to get the archive bytes
+//! first.
+//!
+//! # Examples
+//!
+//! ```
+//! use rkyv::{
+//!     checksum::{access_checksummed, to_bytes_checksummed},
+//!     rancor::Error,
+//!     Archived,
+//! };
+//!
+//! type Strings = Archived<Vec<String>>;
+//!
+//! let value = vec!["hello".to_string(), "world".to_string()];
+//! let mut bytes = to_bytes_checksummed::<Error>(&value).unwrap();
+//!
+//! let archived = access_checksummed::<Strings, Error>(&bytes).unwrap();
+//! assert_eq!(archived[1], "world");
+//!
+//! // Flipping a bit fails the checksum
+//! bytes[0] ^= 1;
+//! assert!(access_checksummed::<Strings, Error>(&bytes).is_err());
+//! ```
+
+use core::fmt;
+
+use bytecheck::CheckBytes;
+use rancor::{fail, Source, Strategy};
+
+use crate::{
+    ser::DefaultSerializer, util::AlignedVec,
+    validation::validators::DefaultValidator, Portable, Serialize,
+};
+
+/// The length of the checksum trailer in bytes.
+pub const TRAILER_LEN: usize = 16;
+
+/// The current version of the checksum trailer.
+pub const TRAILER_VERSION: u8 = 1;
+
+/// The magic bytes at the end of the checksum trailer.
+pub const TRAILER_MAGIC: [u8; 4] = *b"rkcs";
+
+/// An algorithm used to checksum archive bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ChecksumAlgorithm {
+    /// CRC-32C (Castagnoli). This has ID 0.
+    #[default]
+    Crc32c,
+    /// The 64-bit variant of XXH3. This has ID 1.
+    #[cfg(feature = "xxh3")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "xxh3")))]
+    Xxh3,
+}
+
+impl ChecksumAlgorithm {
+    /// Returns the ID of the algorithm stored in the trailer.
+    pub const fn id(self) -> u8 {
+        match self {
+            Self::Crc32c => 0,
+            #[cfg(feature = "xxh3")]
+            Self::Xxh3 => 1,
+        }
+    }
+
+    /// Returns the algorithm with the given ID, or `None` if the ID is unknown
+    /// or the algorithm is not enabled.
+    pub const fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Crc32c),
+            #[cfg(feature = "xxh3")]
+            1 => Some(Self::Xxh3),
+            _ => None,
+        }
+    }
+
+    /// Computes the checksum of the given bytes.
+    pub fn checksum(self, bytes: &[u8]) -> u64 {
+        match self {
+            Self::Crc32c => crc32c(bytes) as u64,
+            #[cfg(feature = "xxh3")]
+            Self::Xxh3 => xxhash_rust::xxh3::xxh3_64(bytes),
+        }
+    }
+}
+
+const CRC32C_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Computes the CRC-32C (Castagnoli) checksum of the given bytes.
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc = CRC32C_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// An error which occurs when verifying the checksum trailer of an archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChecksumError {
+    /// The bytes were too short to contain a trailer.
+    Truncated {
+        /// The length of the bytes.
+        len: usize,
+    },
+    /// The trailer did not end with [`TRAILER_MAGIC`].
+    InvalidMagic,
+    /// The trailer has a version or reserved bytes which are not supported.
+    UnsupportedVersion {
+        /// The version of the trailer.
+        version: u8,
+    },
+    /// The trailer uses a checksum algorithm which is unknown or not enabled.
+    UnknownAlgorithm {
+        /// The ID of the algorithm.
+        id: u8,
+    },
+    /// The checksum of the archive bytes did not match the checksum in the
+    /// trailer.
+    Mismatch {
+        /// The checksum in the trailer.
+        expected: u64,
+        /// The checksum of the archive bytes.
+        actual: u64,
+    },
+}
+
+impl fmt::Display for ChecksumError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated { len } => write!(
+                f,
+                "{len} bytes are too short to contain a {TRAILER_LEN}-byte \
+                 checksum trailer",
+            ),
+            Self::InvalidMagic => {
+                write!(f, "the checksum trailer has invalid magic bytes")
+            }
+            Self::UnsupportedVersion { version } => {
+                write!(f, "unsupported checksum trailer version {version}")
+            }
+            Self::UnknownAlgorithm { id } => {
+                write!(f, "checksum algorithm {id} is unknown or not enabled")
+            }
+            Self::Mismatch { expected, actual } => write!(
+                f,
+                "checksum mismatch: expected {expected:#x}, found {actual:#x}",
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChecksumError {}
+
+/// Appends a checksum trailer for the given archive bytes.
+pub fn append_checksum(bytes: &mut AlignedVec, algorithm: ChecksumAlgorithm) {
+    let checksum = algorithm.checksum(bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    bytes.extend_from_slice(&[algorithm.id(), TRAILER_VERSION, 0, 0]);
+    bytes.extend_from_slice(&TRAILER_MAGIC);
+}
+
+/// Verifies the checksum trailer of the given bytes and returns the archive
+/// bytes without the trailer.
+pub fn verify_checksum<E: Source>(bytes: &[u8]) -> Result<&[u8], E> {
+    let Some(archive_len) = bytes.len().checked_sub(TRAILER_LEN) else {
+        fail!(ChecksumError::Truncated { len: bytes.len() });
+    };
+    let (archive, trailer) = bytes.split_at(archive_len);
+
+    if trailer[12..16] != TRAILER_MAGIC {
+        fail!(ChecksumError::InvalidMagic);
+    }
+    let version = trailer[9];
+    if version != TRAILER_VERSION || trailer[10..12] != [0, 0] {
+        fail!(ChecksumError::UnsupportedVersion { version });
+    }
+    let Some(algorithm) = ChecksumAlgorithm::from_id(trailer[8]) else {
+        fail!(ChecksumError::UnknownAlgorithm { id: trailer[8] });
+    };
+
+    let mut expected = [0; 8];
+    expected.copy_from_slice(&trailer[0..8]);
+    let expected = u64::from_le_bytes(expected);
+    let actual = algorithm.checksum(archive);
+    if expected != actual {
+        fail!(ChecksumError::Mismatch { expected, actual });
+    }
+
+    Ok(archive)
+}
+
+/// Serializes the given value and appends a CRC-32C checksum trailer.
+///
+/// See the [module docs](self) for the layout of the trailer.
+pub fn to_bytes_checksummed<E>(
+    value: &impl for<'a> Serialize<DefaultSerializer<'a, AlignedVec, E>>,
+) -> Result<AlignedVec, E>
+where
+    E: Source,
+{
+    to_bytes_checksummed_with(value, ChecksumAlgorithm::Crc32c)
+}
+
+/// Serializes the given value and appends a checksum trailer using the given
+/// algorithm.
+///
+/// See the [module docs](self) for the layout of the trailer.
+pub fn to_bytes_checksummed_with<E>(
+    value: &impl for<'a> Serialize<DefaultSerializer<'a, AlignedVec, E>>,
+    algorithm: ChecksumAlgorithm,
+) -> Result<AlignedVec, E>
+where
+    E: Source,
+{
+    let mut bytes = crate::to_bytes(value)?;
+    append_checksum(&mut bytes, algorithm);
+    Ok(bytes)
+}
+
+/// Verifies the checksum trailer of the given bytes, and then accesses the
+/// archived value after checking its validity.
+///
+/// Returns a [`ChecksumError`] if the trailer is missing, unsupported, or does
+/// not match the archive bytes.
+pub fn access_checksummed<T, E>(bytes: &[u8]) -> Result<&T, E>
+where
+    T: Portable + for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
+    E: Source,
+{
+    crate::access::<T, E>(verify_checksum::<E>(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{string::String, vec::Vec};
+
+    use rancor::{Failure, Panic};
+
+    use super::{
+        access_checksummed, crc32c, to_bytes_checksummed,
+        to_bytes_checksummed_with, verify_checksum, ChecksumAlgorithm,
+        TRAILER_LEN,
+    };
+    use crate::{access_unchecked, util::AlignedVec, Archive, Serialize};
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Cache {
+        name: String,
+        entries: Vec<u32>,
+    }
+
+    fn cache() -> Cache {
+        Cache {
+            name: String::from("a name which is stored out of line"),
+            entries: (0..16).collect(),
+        }
+    }
+
+    fn algorithms() -> impl Iterator<Item = ChecksumAlgorithm> {
+        (0..=u8::MAX).filter_map(ChecksumAlgorithm::from_id)
+    }
+
+    #[test]
+    fn crc32c_check_value() {
+        assert_eq!(crc32c(b""), 0);
+        assert_eq!(crc32c(b"123456789"), 0xe306_9283);
+    }
+
+    #[test]
+    fn round_trip() {
+        for algorithm in algorithms() {
+            let value = cache();
+            let bytes =
+                to_bytes_checksummed_with::<Panic>(&value, algorithm).unwrap();
+            let archived =
+                access_checksummed::<ArchivedCache, Panic>(&bytes).unwrap();
+            assert_eq!(archived.name.as_str(), value.name);
+            assert!(archived
+                .entries
+                .iter()
+                .map(|e| e.to_native())
+                .eq(value.entries.iter().copied()));
+
+            assert_eq!(bytes[bytes.len() - TRAILER_LEN + 8], algorithm.id());
+        }
+    }
+
+    #[test]
+    fn truncated() {
+        let bytes = to_bytes_checksummed::<Panic>(&cache()).unwrap();
+        for len in 0..bytes.len() {
+            assert!(access_checksummed::<ArchivedCache, Failure>(
+                &bytes[..len]
+            )
+            .is_err());
+        }
+    }
+
+    #[test]
+    fn bit_flip() {
+        let bytes = to_bytes_checksummed::<Panic>(&cache()).unwrap();
+        let mut corrupted = AlignedVec::<16>::new();
+        corrupted.extend_from_slice(&bytes);
+
+        for i in 0..bytes.len() * 8 {
+            corrupted[i / 8] ^= 1 << (i % 8);
+            assert!(
+                access_checksummed::<ArchivedCache, Failure>(&corrupted)
+                    .is_err(),
+                "flipping bit {i} was not detected",
+            );
+            corrupted[i / 8] ^= 1 << (i % 8);
+        }
+
+        assert!(
+            access_checksummed::<ArchivedCache, Failure>(&corrupted).is_ok()
+        );
+    }
+
+    #[test]
+    fn access_unchecked_after_verify() {
+        let value = cache();
+        let checksummed = to_bytes_checksummed::<Panic>(&value).unwrap();
+        let plain = crate::to_bytes::<Panic>(&value).unwrap();
+
+        let archive = verify_checksum::<Panic>(&checksummed).unwrap();
+        assert_eq!(archive, &*plain);
+        // SAFETY: The checksummed archive bytes are a valid archive.
+        let archived = unsafe { access_unchecked::<ArchivedCache>(archive) };
+        assert_eq!(archived.name.as_str(), value.name);
+    }
+}
@@ -1,13 +1,13 @@
 //! [`Archive`](crate::Archive) implementation for B-tree maps.
 
 use core::{
-    borrow::Borrow,
     cmp::Ordering,
     fmt,
+    iter::FusedIterator,
     marker::PhantomData,
     mem::{size_of, MaybeUninit},
-    ops::ControlFlow,
-    slice,
+    ops::{Bound, ControlFlow, RangeBounds},
+    ptr, slice,
 };
 
 use munge::munge;
@@ -16,7 +16,11 @@ use rancor::{fail, Fallible, Source};
 use crate::{
     collections::util::IteratorLengthMismatch,
     place::Initialized,
-    primitive::{ArchivedUsize, FixedUsize},
+    primitive::{
+        usize_from_archived, ArchivedChar, ArchivedI128, ArchivedI16,
+        ArchivedI32, ArchivedI64, ArchivedIsize, ArchivedU128, ArchivedU16,
+        ArchivedU32, ArchivedU64, ArchivedUsize, FixedIsize, FixedUsize,
+    },
     ser::{Allocator, Writer, WriterExt as _},
     util::{InlineVec, SerVec},
     Place, Portable, RawRelPtr, Serialize,
@@ -31,15 +35,15 @@ use crate::{
 // declare `branches: [RawRelPtr; N]` and then add another `last: RawRelPtr`
 // field. When the branching factor B is needed, it will be calculated as E + 1.
 
-const fn nodes_in_level<const E: usize>(i: u32) -> usize {
+const fn nodes_in_level(e: usize, i: u32) -> usize {
     // The root of the tree has one node, and each level down has B times as
     // many nodes at the last. Therefore, the number of nodes in the I-th level
     // is equal to B^I.
 
-    (E + 1).pow(i)
+    (e + 1).pow(i)
 }
 
-const fn entries_in_full_tree<const E: usize>(h: u32) -> usize {
+const fn entries_in_full_tree(e: usize, h: u32) -> usize {
     // The number of nodes in each layer I of a B-tree is equal to B^I. At layer
     // I = 0, the number of nodes is exactly one. At layer I = 1, the number of
     // nodes is B, at layer I = 2 the number of nodes is B^2, and so on. The
@@ -52,10 +56,10 @@ const fn entries_in_full_tree<const E: usize>(h: u32) -> usize {
     // Note that this is one less than the number of nodes in the level after
     // the final level of the B-tree.
 
-    nodes_in_level::<E>(h) - 1
+    nodes_in_level(e, h) - 1
 }
 
-const fn entries_to_height<const E: usize>(n: usize) -> u32 {
+const fn entries_to_height(e: usize, n: usize) -> u32 {
     // Solving B^H - 1 = N for H yields H = log_B(N + 1). However, we'll be
     // using an integer logarithm, and so the value of H will be rounded down
     // which underestimates the height of the tree:
@@ -90,15 +94,15 @@ const fn entries_to_height<const E: usize>(n: usize) -> u32 {
 
     // Putting it all together:
     // => H = ilog_B(C(N) + 1) = ilog_b(B * N - 1 + 1) = ilog_b(B * N)
-    ((E + 1) * n).ilog(E + 1)
+    ((e + 1) * n).ilog(e + 1)
 }
 
-const fn ll_entries<const E: usize>(height: u32, n: usize) -> usize {
+const fn ll_entries(e: usize, height: u32, n: usize) -> usize {
     // The number of entries not in the last level is equal to the number of
     // entries in a full B-tree of height H - 1. The number of entries in
     // the last level is thus the total number of entries minus the number
     // of entries not in the last level.
-    n - entries_in_full_tree::<E>(height - 1)
+    n - entries_in_full_tree(e, height - 1)
 }
 
 #[derive(Portable)]
@@ -133,6 +137,329 @@ struct InnerNode<K, V, const E: usize> {
     greater_node: RawRelPtr,
 }
 
+/// Resolves the pointers from an inner node to its children.
+///
+/// The pointers don't depend on the types of the keys and values, so they're
+/// resolved here instead of in the code which is generic over them.
+///
+/// # Safety
+///
+/// `lesser_nodes` must be the first of at least `lesser_node_pos.len()`
+/// consecutive relative pointers.
+unsafe fn resolve_links(
+    lesser_nodes: Place<RawRelPtr>,
+    lesser_node_pos: &[Option<usize>],
+    greater_node: Place<RawRelPtr>,
+    greater_node_pos: Option<usize>,
+) {
+    fn resolve_link(pos: Option<usize>, out: Place<RawRelPtr>) {
+        if let Some(pos) = pos {
+            RawRelPtr::emplace(pos, out);
+        } else {
+            RawRelPtr::emplace_invalid(out);
+        }
+    }
+
+    for (i, &pos) in lesser_node_pos.iter().enumerate() {
+        // SAFETY: The caller has guaranteed that there are at least
+        // `lesser_node_pos.len()` relative pointers starting at `lesser_nodes`.
+        let out = unsafe {
+            Place::new_unchecked(
+                lesser_nodes.pos() + i * size_of::<RawRelPtr>(),
+                lesser_nodes.ptr().add(i),
+            )
+        };
+        resolve_link(pos, out);
+    }
+    resolve_link(greater_node_pos, greater_node);
+}
+
+/// Returned by a [`NodeBuilder`] to stop bulk-loading after an error.
+struct Stop;
+
+/// The steps of bulk-loading a B-tree, which are taken by [`bulk_load`].
+///
+/// Entries are taken from the source in order.
+trait NodeBuilder {
+    /// Adds the next entry to the open leaf node.
+    fn push_leaf(&mut self) -> Result<(), Stop>;
+
+    /// Writes the open leaf node, which becomes the pending child.
+    fn close_leaf(&mut self) -> Result<(), Stop>;
+
+    /// Adds the next entry to the innermost open inner node, with the pending
+    /// child as its lesser node.
+    fn push_inner(&mut self) -> Result<(), Stop>;
+
+    /// Writes the innermost open inner node with the pending child as its
+    /// greater node and closes it. The written node becomes the pending child.
+    fn close_inner(&mut self) -> Result<(), Stop>;
+
+    /// Opens a new innermost inner node with no entries.
+    fn open_inner(&mut self) -> Result<(), Stop>;
+}
+
+/// Bulk-loads a B-tree of `len` entries with `fanout` entries per node.
+///
+/// The shape of the tree only depends on the number of entries and the
+/// fanout, so it's planned here instead of in the code which is generic over
+/// the keys, values, and serializer. `len` must not be zero.
+fn bulk_load(
+    len: usize,
+    fanout: usize,
+    builder: &mut dyn NodeBuilder,
+) -> Result<(), Stop> {
+    let height = entries_to_height(fanout, len);
+    let ll_entries = ll_entries(fanout, height, len);
+
+    // The number of entries in each open inner node, from the root down. The
+    // branching factor is at least two, so the height is at most the number
+    // of bits in a `usize`.
+    let mut inner_lens = [0; usize::BITS as usize];
+    let mut depth = height as usize - 1;
+
+    let mut taken = 0;
+    let mut leaf_len = 0;
+    let mut leaf_entries = 0;
+    while taken < len {
+        builder.push_leaf()?;
+        taken += 1;
+        leaf_len += 1;
+        leaf_entries += 1;
+
+        if leaf_entries == ll_entries || leaf_len == fanout {
+            builder.close_leaf()?;
+            leaf_len = 0;
+
+            // If on the transition node, fill and close open inner
+            if leaf_entries == ll_entries && depth > 0 {
+                depth -= 1;
+                while inner_lens[depth] < fanout && taken < len {
+                    builder.push_inner()?;
+                    taken += 1;
+                    inner_lens[depth] += 1;
+                }
+                builder.close_inner()?;
+            }
+
+            // Add closed node to open inner
+            let mut popped = 0;
+            while depth > 0 {
+                if inner_lens[depth - 1] == fanout {
+                    builder.close_inner()?;
+                    depth -= 1;
+                    popped += 1;
+                } else {
+                    builder.push_inner()?;
+                    taken += 1;
+                    inner_lens[depth - 1] += 1;
+                    break;
+                }
+            }
+
+            for _ in 0..popped {
+                inner_lens[depth] = 0;
+                depth += 1;
+                builder.open_inner()?;
+            }
+        }
+    }
+
+    if leaf_len != 0 {
+        builder.close_leaf()?;
+    }
+
+    // Close open inners
+    for _ in 0..depth {
+        builder.close_inner()?;
+    }
+
+    Ok(())
+}
+
+/// A [`NodeBuilder`] which serializes the entries of an iterator into nodes.
+struct TreeBuilder<'a, 'b, I, UK, UV, S, const E: usize>
+where
+    S: Fallible + ?Sized,
+{
+    iter: &'b mut I,
+    len: usize,
+    taken: usize,
+    serializer: &'b mut S,
+    open_inners: &'b mut SerVec<InlineVec<(&'a UK, &'a UV, Option<usize>), E>>,
+    open_leaf: InlineVec<(&'a UK, &'a UV), E>,
+    child_node_pos: Option<usize>,
+    error: Option<S::Error>,
+}
+
+impl<'a, I, UK, UV, S, const E: usize> TreeBuilder<'a, '_, I, UK, UV, S, E>
+where
+    I: Iterator<Item = (&'a UK, &'a UV)>,
+    S: Fallible + ?Sized,
+    S::Error: Source,
+{
+    fn next(&mut self) -> Result<(&'a UK, &'a UV), Stop> {
+        if let Some(entry) = self.iter.next() {
+            self.taken += 1;
+            Ok(entry)
+        } else {
+            self.error = Some(S::Error::new(IteratorLengthMismatch {
+                expected: self.len,
+                actual: self.taken,
+            }));
+            Err(Stop)
+        }
+    }
+
+    fn check<T>(&mut self, result: Result<T, S::Error>) -> Result<T, Stop> {
+        result.map_err(|error| {
+            self.error = Some(error);
+            Stop
+        })
+    }
+}
+
+impl<'a, I, UK, UV, S, const E: usize> NodeBuilder
+    for TreeBuilder<'a, '_, I, UK, UV, S, E>
+where
+    I: Iterator<Item = (&'a UK, &'a UV)>,
+    UK: Serialize<S>,
+    UV: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn push_leaf(&mut self) -> Result<(), Stop> {
+        let entry = self.next()?;
+        self.open_leaf.push(entry);
+        Ok(())
+    }
+
+    fn close_leaf(&mut self) -> Result<(), Stop> {
+        let result =
+            ArchivedBTreeMap::<UK::Archived, UV::Archived, E>::close_leaf(
+                &self.open_leaf,
+                self.serializer,
+            );
+        self.child_node_pos = Some(self.check(result)?);
+        self.open_leaf.clear();
+        Ok(())
+    }
+
+    fn push_inner(&mut self) -> Result<(), Stop> {
+        let (key, value) = self.next()?;
+        let child_node_pos = self.child_node_pos.take();
+        let inner = self.open_inners.last_mut().unwrap();
+        inner.push((key, value, child_node_pos));
+        Ok(())
+    }
+
+    fn close_inner(&mut self) -> Result<(), Stop> {
+        let inner = self.open_inners.pop().unwrap();
+        let result =
+            ArchivedBTreeMap::<UK::Archived, UV::Archived, E>::close_inner(
+                &inner,
+                self.child_node_pos,
+                self.serializer,
+            );
+        self.child_node_pos = Some(self.check(result)?);
+        Ok(())
+    }
+
+    fn open_inner(&mut self) -> Result<(), Stop> {
+        self.open_inners.push(InlineVec::new());
+        Ok(())
+    }
+}
+
+/// A type which can be used to look up keys of type `K` in archived B-tree
+/// maps and sets.
+///
+/// This allows archived B-tree maps to be queried with unarchived keys, for
+/// example looking up an archived `u32` key with a `&u32`. It is the ordered
+/// counterpart of [`LookupKey`](crate::hash::LookupKey).
+///
+/// `compare` must order lookup keys the same way that the unarchived keys are
+/// ordered.
+///
+/// Like with `LookupKey`, archived B-tree maps and sets don't look up keys
+/// through [`Borrow`](core::borrow::Borrow). Key types which implement
+/// `Borrow` to be looked up by another type should also implement `LookupOrd`
+/// for that type.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use rkyv::{access, rancor::Error, to_bytes, Archived};
+///
+/// let mut names = BTreeMap::new();
+/// names.insert("foo".to_string(), 1u32);
+/// let bytes = to_bytes::<Error>(&names).unwrap();
+/// let archived =
+///     access::<Archived<BTreeMap<String, u32>>, Error>(&bytes).unwrap();
+/// assert_eq!(archived.get("foo").map(|v| v.to_native()), Some(1));
+/// assert!(archived.get("bar").is_none());
+///
+/// let mut numbers = BTreeMap::new();
+/// numbers.insert(5u32, "five".to_string());
+/// let bytes = to_bytes::<Error>(&numbers).unwrap();
+/// let archived =
+///     access::<Archived<BTreeMap<u32, String>>, Error>(&bytes).unwrap();
+/// assert_eq!(archived.get(&5u32).map(|s| s.as_str()), Some("five"));
+/// assert!(archived.get(&6u32).is_none());
+/// ```
+pub trait LookupOrd<K: ?Sized>: Ord {
+    /// Returns the ordering of this lookup key relative to the given key.
+    fn compare(&self, key: &K) -> Ordering;
+}
+
+impl<T: Ord + ?Sized> LookupOrd<T> for T {
+    #[inline]
+    fn compare(&self, key: &T) -> Ordering {
+        self.cmp(key)
+    }
+}
+
+macro_rules! impl_lookup_ord_for_primitive {
+    ($($native:ty: $archived:ty),* $(,)?) => {
+        $(
+            impl LookupOrd<$archived> for $native {
+                #[inline]
+                fn compare(&self, key: &$archived) -> Ordering {
+                    self.cmp(&key.to_native())
+                }
+            }
+        )*
+    };
+}
+
+impl_lookup_ord_for_primitive! {
+    i16: ArchivedI16,
+    i32: ArchivedI32,
+    i64: ArchivedI64,
+    i128: ArchivedI128,
+    u16: ArchivedU16,
+    u32: ArchivedU32,
+    u64: ArchivedU64,
+    u128: ArchivedU128,
+    char: ArchivedChar,
+}
+
+impl LookupOrd<ArchivedUsize> for usize {
+    #[inline]
+    fn compare(&self, key: &ArchivedUsize) -> Ordering {
+        (*self as FixedUsize).cmp(&key.to_native())
+    }
+}
+
+impl LookupOrd<ArchivedIsize> for isize {
+    #[inline]
+    fn compare(&self, key: &ArchivedIsize) -> Ordering {
+        (*self as FixedIsize).cmp(&key.to_native())
+    }
+}
+
 /// An archived [`BTreeMap`](std::collections::BTreeMap).
 #[derive(Portable)]
 #[cfg_attr(
@@ -154,11 +481,33 @@ pub struct ArchivedBTreeMap<K, V, const E: usize = 5> {
 }
 
 impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
+    /// The branching factor of the B-tree.
+    ///
+    /// Each node stores up to `E` entries, and each inner node has up to
+    /// `E + 1` children.
+    pub const FANOUT: usize = E + 1;
+
+    /// Returns the branching factor of the B-tree.
+    pub fn fanout(&self) -> usize {
+        Self::FANOUT
+    }
+
+    /// Returns the height of the B-tree.
+    ///
+    /// This is the number of nodes visited by lookups of keys in the last
+    /// level of the tree, and is zero if the B-tree map is empty.
+    pub fn height(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            entries_to_height(E, self.len()) as usize
+        }
+    }
+
     /// Returns whether the B-tree map contains the given key.
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
-        Q: Ord + ?Sized,
-        K: Borrow<Q> + Ord,
+        Q: LookupOrd<K> + ?Sized,
     {
         self.get_key_value(key).is_some()
     }
@@ -167,8 +516,7 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
     /// not present in the B-tree map.
     pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
-        Q: Ord + ?Sized,
-        K: Borrow<Q> + Ord,
+        Q: LookupOrd<K> + ?Sized,
     {
         self.get_key_value(key).map(|(_, value)| value)
     }
@@ -180,15 +528,27 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
 
     /// Returns the number of entries in the B-tree map.
     pub fn len(&self) -> usize {
-        self.len.to_native() as usize
+        usize_from_archived(self.len)
     }
 
     /// Gets the key-value pair associated with the given key, or `None` if the
     /// key is not present in the B-tree map.
     pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
-        Q: Ord + ?Sized,
-        K: Borrow<Q> + Ord,
+        Q: LookupOrd<K> + ?Sized,
+    {
+        self.get_key_value_with(|k| key.compare(k).reverse())
+    }
+
+    /// Gets the key-value pair for which `cmp` returns `Ordering::Equal`, or
+    /// `None` if there is no such key in the B-tree map.
+    ///
+    /// `cmp` should return whether a key is less than, equal to, or greater
+    /// than the target, and must be consistent with the ordering of the keys
+    /// in the B-tree map.
+    pub fn get_key_value_with<F>(&self, mut cmp: F) -> Option<(&K, &V)>
+    where
+        F: FnMut(&K) -> Ordering,
     {
         if self.is_empty() {
             return None;
@@ -197,14 +557,14 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
         let mut current = unsafe { self.root.as_ptr().cast::<Node<K, V, E>>() };
         'outer: loop {
             let node = unsafe { &*current };
-            for i in 0..node.len.to_native() as usize {
+            for i in 0..usize_from_archived(node.len) {
                 let k = unsafe { node.keys[i].assume_init_ref() };
-                match key.cmp(k.borrow()) {
+                match cmp(k) {
                     Ordering::Equal => {
                         let v = unsafe { node.values[i].assume_init_ref() };
                         return Some((k, v));
                     }
-                    Ordering::Less => match node.kind {
+                    Ordering::Greater => match node.kind {
                         NodeKind::Inner => {
                             let inner_node = unsafe {
                                 &*current.cast::<InnerNode<K, V, E>>()
@@ -223,7 +583,7 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
                         }
                         NodeKind::Leaf => return None,
                     },
-                    Ordering::Greater => (),
+                    Ordering::Less => (),
                 }
             }
             match node.kind {
@@ -246,6 +606,148 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
         }
     }
 
+    /// Returns the first key-value pair in the B-tree map, or `None` if it is
+    /// empty.
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        self.partition_point(|_| true).1
+    }
+
+    /// Returns the last key-value pair in the B-tree map, or `None` if it is
+    /// empty.
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        self.partition_point(|_| false).0
+    }
+
+    /// Returns an iterator over the key-value pairs of the B-tree map, in
+    /// order by key.
+    pub fn iter(&self) -> Iter<'_, K, V, E> {
+        Iter {
+            range: Range::new(self, |_| true, |_| false),
+            remaining: self.len(),
+        }
+    }
+
+    /// Returns an iterator over the keys of the B-tree map, in order.
+    pub fn keys(&self) -> Keys<'_, K, V, E> {
+        Keys { iter: self.iter() }
+    }
+
+    /// Returns an iterator over the values of the B-tree map, in order by key.
+    pub fn values(&self) -> Values<'_, K, V, E> {
+        Values { iter: self.iter() }
+    }
+
+    /// Returns an iterator over the key-value pairs of the B-tree map whose
+    /// keys are within the given range, in order by key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end of the range,
+    /// or if the start and end of the range are equal and both excluded.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V, E>
+    where
+        Q: LookupOrd<K> + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        match (range.start_bound(), range.end_bound()) {
+            (Bound::Excluded(start), Bound::Excluded(end)) if start == end => {
+                panic!(
+                    "range start and end are equal and excluded in \
+                     ArchivedBTreeMap"
+                )
+            }
+            (
+                Bound::Included(start) | Bound::Excluded(start),
+                Bound::Included(end) | Bound::Excluded(end),
+            ) if start > end => {
+                panic!(
+                    "range start is greater than range end in ArchivedBTreeMap"
+                )
+            }
+            _ => (),
+        }
+
+        Range::new(
+            self,
+            |k| match range.start_bound() {
+                Bound::Included(start) => start.compare(k).is_le(),
+                Bound::Excluded(start) => start.compare(k).is_lt(),
+                Bound::Unbounded => true,
+            },
+            |k| match range.end_bound() {
+                Bound::Included(end) => end.compare(k).is_lt(),
+                Bound::Excluded(end) => end.compare(k).is_le(),
+                Bound::Unbounded => false,
+            },
+        )
+    }
+
+    /// Splits the B-tree map at the first key for which `pred` returns `true`.
+    ///
+    /// `pred` must return `false` for every key before some point and `true`
+    /// for every key after it. Returns the last key-value pair for which `pred`
+    /// returns `false` and the first key-value pair for which it returns
+    /// `true`.
+    fn partition_point(
+        &self,
+        mut pred: impl FnMut(&K) -> bool,
+    ) -> (Option<EntryRef<'_, K, V>>, Option<EntryRef<'_, K, V>>) {
+        let mut before = None;
+        let mut after = None;
+
+        if self.is_empty() {
+            return (before, after);
+        }
+
+        let mut current = unsafe { self.root.as_ptr().cast::<Node<K, V, E>>() };
+        loop {
+            let node = unsafe { &*current };
+            let len = usize_from_archived(node.len);
+
+            let mut i = 0;
+            while i < len && !pred(unsafe { node.keys[i].assume_init_ref() }) {
+                i += 1;
+            }
+
+            // The entries of the node before and after the split are closer
+            // than any found in the nodes above it, and the entries in the
+            // node between them are closer still.
+            if i > 0 {
+                before = Some(unsafe {
+                    (
+                        node.keys[i - 1].assume_init_ref(),
+                        node.values[i - 1].assume_init_ref(),
+                    )
+                });
+            }
+            if i < len {
+                after = Some(unsafe {
+                    (
+                        node.keys[i].assume_init_ref(),
+                        node.values[i].assume_init_ref(),
+                    )
+                });
+            }
+
+            match node.kind {
+                NodeKind::Leaf => return (before, after),
+                NodeKind::Inner => {
+                    let inner_node =
+                        unsafe { &*current.cast::<InnerNode<K, V, E>>() };
+                    let next = if i < len {
+                        unsafe { inner_node.lesser_nodes[i].assume_init_ref() }
+                    } else {
+                        &inner_node.greater_node
+                    };
+                    if next.is_invalid() {
+                        return (before, after);
+                    }
+                    current = unsafe { next.as_ptr().cast::<Node<K, V, E>>() };
+                }
+            }
+        }
+    }
+
     /// Resolves an `ArchivedBTreeMap` from the given length, resolver, and
     /// output place.
     pub fn resolve_from_len(
@@ -289,100 +791,32 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
             return Ok(BTreeMapResolver { root_node_pos: 0 });
         }
 
-        let height = entries_to_height::<E>(len);
-        let ll_entries = ll_entries::<E>(height, len);
+        let height = entries_to_height(E, len);
 
         SerVec::with_capacity(
             serializer,
             height as usize - 1,
             |open_inners, serializer| {
                 for _ in 0..height - 1 {
-                    open_inners.push(InlineVec::<
-                        (&'a UK, &'a UV, Option<usize>),
-                        E,
-                    >::new());
-                }
-
-                let mut open_leaf = InlineVec::<(&'a UK, &'a UV), E>::new();
-
-                let mut child_node_pos = None;
-                let mut leaf_entries = 0;
-                while let Some((key, value)) = iter.next() {
-                    open_leaf.push((key, value));
-                    leaf_entries += 1;
-
-                    if leaf_entries == ll_entries
-                        || open_leaf.len() == open_leaf.capacity()
-                    {
-                        // Close open leaf
-                        child_node_pos =
-                            Some(Self::close_leaf(&open_leaf, serializer)?);
-                        open_leaf.clear();
-
-                        // If on the transition node, fill and close open inner
-                        if leaf_entries == ll_entries {
-                            if let Some(mut inner) = open_inners.pop() {
-                                while inner.len() < inner.capacity() {
-                                    if let Some((k, v)) = iter.next() {
-                                        inner.push((k, v, child_node_pos));
-                                        child_node_pos = None;
-                                    } else {
-                                        break;
-                                    }
-                                }
-
-                                child_node_pos = Some(Self::close_inner(
-                                    &inner,
-                                    child_node_pos,
-                                    serializer,
-                                )?);
-                            }
-                        }
-
-                        // Add closed node to open inner
-                        let mut popped = 0;
-                        while let Some(last_inner) = open_inners.last_mut() {
-                            if last_inner.len() == last_inner.capacity() {
-                                // Close open inner
-                                child_node_pos = Some(Self::close_inner(
-                                    last_inner,
-                                    child_node_pos,
-                                    serializer,
-                                )?);
-                                open_inners.pop();
-                                popped += 1;
-                            } else {
-                                let (key, value) = iter.next().unwrap();
-                                last_inner.push((key, value, child_node_pos));
-                                child_node_pos = None;
-                                break;
-                            }
-                        }
-
-                        for _ in 0..popped {
-                            open_inners.push(InlineVec::default());
-                        }
-                    }
-                }
-
-                if !open_leaf.is_empty() {
-                    // Close open leaf
-                    child_node_pos =
-                        Some(Self::close_leaf(&open_leaf, serializer)?);
-                    open_leaf.clear();
+                    open_inners.push(InlineVec::new());
                 }
 
-                // Close open inners
-                while let Some(inner) = open_inners.pop() {
-                    child_node_pos = Some(Self::close_inner(
-                        &inner,
-                        child_node_pos,
-                        serializer,
-                    )?);
+                let mut builder = TreeBuilder {
+                    iter: &mut iter,
+                    len,
+                    taken: 0,
+                    serializer,
+                    open_inners,
+                    open_leaf: InlineVec::new(),
+                    child_node_pos: None,
+                    error: None,
+                };
+                if bulk_load(len, E, &mut builder).is_err() {
+                    return Err(builder.error.unwrap());
                 }
-
-                debug_assert!(open_inners.is_empty());
-                debug_assert!(open_leaf.is_empty());
+                let root_node_pos = builder.child_node_pos.unwrap();
+                debug_assert!(builder.open_inners.is_empty());
+                debug_assert!(builder.open_leaf.is_empty());
 
                 let leftovers = iter.count();
                 if leftovers != 0 {
@@ -392,13 +826,162 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
                     });
                 }
 
-                Ok(BTreeMapResolver {
-                    root_node_pos: child_node_pos.unwrap(),
-                })
+                Ok(BTreeMapResolver { root_node_pos })
             },
         )?
     }
 
+    /// Serializes an `ArchivedBTreeMap` from the given sorted iterator and
+    /// serializer.
+    ///
+    /// The B-tree is bulk-loaded with the fanout chosen by `E`, writing each
+    /// node as soon as it is full. This performs no per-entry allocations, and
+    /// the required scratch space is proportional to the height of the tree.
+    ///
+    /// Returns an error if the keys of `iter` are not in strictly ascending
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rkyv::{
+    ///     collections::btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+    ///     rancor::{Fallible, Source},
+    ///     ser::{Allocator, Writer},
+    ///     Archive, Archived, Place, Serialize,
+    /// };
+    ///
+    /// struct SortedPairs(Vec<(u32, u32)>);
+    ///
+    /// impl Archive for SortedPairs {
+    ///     type Archived = ArchivedBTreeMap<Archived<u32>, Archived<u32>, 15>;
+    ///     type Resolver = BTreeMapResolver;
+    ///
+    ///     fn resolve(
+    ///         &self,
+    ///         resolver: Self::Resolver,
+    ///         out: Place<Self::Archived>,
+    ///     ) {
+    ///         ArchivedBTreeMap::resolve_from_len(self.0.len(), resolver, out);
+    ///     }
+    /// }
+    ///
+    /// impl<S> Serialize<S> for SortedPairs
+    /// where
+    ///     S: Fallible + Allocator + Writer + ?Sized,
+    ///     S::Error: Source,
+    /// {
+    ///     fn serialize(
+    ///         &self,
+    ///         serializer: &mut S,
+    ///     ) -> Result<Self::Resolver, S::Error> {
+    ///         ArchivedBTreeMap::serialize_from_sorted_iter(
+    ///             self.0.iter().map(|(k, v)| (k, v)),
+    ///             serializer,
+    ///         )
+    ///     }
+    /// }
+    ///
+    /// let value = SortedPairs((0..1000).map(|i| (i, i * 2)).collect());
+    /// let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&value).unwrap();
+    /// let archived =
+    ///     unsafe { rkyv::access_unchecked::<Archived<SortedPairs>>(&bytes) };
+    /// assert_eq!(archived.fanout(), 16);
+    /// assert_eq!(archived.height(), 3);
+    /// let key = Archived::<u32>::from_native(21);
+    /// assert_eq!(archived.get(&key).map(|v| v.to_native()), Some(42));
+    ///
+    /// let unsorted = SortedPairs(vec![(1, 1), (0, 0)]);
+    /// assert!(rkyv::to_bytes::<rkyv::rancor::Error>(&unsorted).is_err());
+    /// ```
+    pub fn serialize_from_sorted_iter<'a, I, UK, UV, S>(
+        iter: I,
+        serializer: &mut S,
+    ) -> Result<BTreeMapResolver, S::Error>
+    where
+        I: ExactSizeIterator<Item = (&'a UK, &'a UV)>,
+        UK: 'a + Serialize<S, Archived = K> + Ord,
+        UV: 'a + Serialize<S, Archived = V>,
+        S: Fallible + Allocator + Writer + ?Sized,
+        S::Error: Source,
+    {
+        #[derive(Debug)]
+        struct UnsortedEntries {
+            index: usize,
+        }
+
+        impl fmt::Display for UnsortedEntries {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "the key at index {} of a sorted iterator was not greater \
+                     than the previous key",
+                    self.index,
+                )
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl std::error::Error for UnsortedEntries {}
+
+        struct Sorted<'a, 'b, I, UK> {
+            iter: I,
+            prev: Option<&'a UK>,
+            index: usize,
+            unsorted: &'b mut Option<usize>,
+        }
+
+        impl<'a, I, UK, UV> Iterator for Sorted<'a, '_, I, UK>
+        where
+            I: Iterator<Item = (&'a UK, &'a UV)>,
+            UK: 'a + Ord,
+            UV: 'a,
+        {
+            type Item = (&'a UK, &'a UV);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let (key, value) = self.iter.next()?;
+                if self.unsorted.is_none()
+                    && self.prev.map_or(false, |prev| prev >= key)
+                {
+                    *self.unsorted = Some(self.index);
+                }
+                self.prev = Some(key);
+                self.index += 1;
+                Some((key, value))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.iter.size_hint()
+            }
+        }
+
+        impl<'a, I, UK, UV> ExactSizeIterator for Sorted<'a, '_, I, UK>
+        where
+            I: ExactSizeIterator<Item = (&'a UK, &'a UV)>,
+            UK: 'a + Ord,
+            UV: 'a,
+        {
+        }
+
+        let mut unsorted = None;
+        let resolver = Self::serialize_from_ordered_iter(
+            Sorted {
+                iter,
+                prev: None,
+                index: 0,
+                unsorted: &mut unsorted,
+            },
+            serializer,
+        )?;
+
+        if let Some(index) = unsorted {
+            fail!(UnsortedEntries { index });
+        }
+
+        Ok(resolver)
+    }
+
     fn close_leaf<UK, UV, S>(
         items: &[(&UK, &UV)],
         serializer: &mut S,
@@ -484,6 +1067,7 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
 
         kind.write(NodeKind::Inner);
         len.write(ArchivedUsize::from_native(items.len() as FixedUsize));
+        let mut lesser_node_pos = InlineVec::<Option<usize>, E>::new();
         for (i, ((k, v, l), (kr, vr))) in
             items.iter().zip(resolvers.drain()).enumerate()
         {
@@ -491,20 +1075,18 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
             k.resolve(kr, out_key);
             let out_value = unsafe { values.index(i).cast_unchecked() };
             v.resolve(vr, out_value);
-
-            let out_lesser_node =
-                unsafe { lesser_nodes.index(i).cast_unchecked() };
-            if let Some(lesser_node) = l {
-                RawRelPtr::emplace(*lesser_node, out_lesser_node);
-            } else {
-                RawRelPtr::emplace_invalid(out_lesser_node);
-            }
+            lesser_node_pos.push(*l);
         }
 
-        if let Some(greater_node_pos) = greater_node_pos {
-            RawRelPtr::emplace(greater_node_pos, greater_node);
-        } else {
-            RawRelPtr::emplace_invalid(greater_node);
+        // SAFETY: `lesser_nodes` is an array of `E` relative pointers, and
+        // there are at most `E` items in the node.
+        unsafe {
+            resolve_links(
+                lesser_nodes.cast_unchecked(),
+                &lesser_node_pos,
+                greater_node,
+                greater_node_pos,
+            );
         }
 
         let bytes = unsafe {
@@ -544,7 +1126,7 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
         f: &mut impl FnMut(&K, &V) -> ControlFlow<T>,
     ) -> ControlFlow<T> {
         let node = unsafe { &*current };
-        for i in 0..node.len.to_native() as usize {
+        for i in 0..usize_from_archived(node.len) {
             let key = unsafe { node.keys[i].assume_init_ref() };
             let value = unsafe { node.values[i].assume_init_ref() };
             match node.kind {
@@ -579,10 +1161,451 @@ impl<K, V, const E: usize> ArchivedBTreeMap<K, V, E> {
 
         ControlFlow::Continue(())
     }
+}
+
+type EntryRef<'a, K, V> = (&'a K, &'a V);
+
+impl<'a, K, V, const E: usize> IntoIterator for &'a ArchivedBTreeMap<K, V, E>
+where
+    K: Ord,
+{
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The maximum height of an archived B-tree.
+///
+/// The height of a B-tree is at most the base-2 logarithm of twice its length,
+/// so a B-tree with a length that fits in a `FixedUsize` is at most this tall.
+/// Validation rejects B-trees which are any taller.
+const MAX_HEIGHT: usize = FixedUsize::BITS as usize;
+
+/// A position in an archived B-tree.
+///
+/// The cursor keeps the path from the root of the B-tree to its entry. Each
+/// frame but the last holds a node and the index of the child that the path
+/// descends into, where the index of the greater node is the length of the
+/// node. The last frame holds the node and index of the entry.
+struct Cursor<K, V, const E: usize> {
+    path: [(*const Node<K, V, E>, usize); MAX_HEIGHT],
+    depth: usize,
+}
+
+impl<K, V, const E: usize> Clone for Cursor<K, V, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<K, V, const E: usize> Copy for Cursor<K, V, E> {}
+
+// SAFETY: A cursor only reads the nodes of the B-tree it points into, like a
+// shared reference to the B-tree would.
+unsafe impl<K: Sync, V: Sync, const E: usize> Send for Cursor<K, V, E> {}
+
+// SAFETY: A cursor only reads the nodes of the B-tree it points into, like a
+// shared reference to the B-tree would.
+unsafe impl<K: Sync, V: Sync, const E: usize> Sync for Cursor<K, V, E> {}
+
+impl<K, V, const E: usize> Cursor<K, V, E> {
+    fn new() -> Self {
+        Self {
+            path: [(ptr::null(), 0); MAX_HEIGHT],
+            depth: 0,
+        }
+    }
+
+    fn push(&mut self, node: *const Node<K, V, E>, index: usize) {
+        self.path[self.depth] = (node, index);
+        self.depth += 1;
+    }
+
+    /// Returns the child of `node` at `index`, or `None` if `node` is a leaf
+    /// or the child is empty.
+    ///
+    /// # Safety
+    ///
+    /// `node` must point to a valid node and `index` must be at most its
+    /// length.
+    unsafe fn child(
+        node: *const Node<K, V, E>,
+        index: usize,
+    ) -> Option<*const Node<K, V, E>> {
+        let len = usize_from_archived(unsafe { (*node).len });
+        match unsafe { &(*node).kind } {
+            NodeKind::Leaf => None,
+            NodeKind::Inner => {
+                let inner = unsafe { &*node.cast::<InnerNode<K, V, E>>() };
+                let child = if index < len {
+                    unsafe { inner.lesser_nodes[index].assume_init_ref() }
+                } else {
+                    &inner.greater_node
+                };
+                if child.is_invalid() {
+                    None
+                } else {
+                    Some(unsafe { child.as_ptr().cast::<Node<K, V, E>>() })
+                }
+            }
+        }
+    }
+
+    /// Moves the cursor to the first entry for which `pred` returns `true`.
+    ///
+    /// `pred` must return `false` for every key before some point and `true`
+    /// for every key after it. Returns `false` if there is no such entry.
+    ///
+    /// # Safety
+    ///
+    /// `root` must point to the root node of a valid B-tree.
+    unsafe fn seek_first(
+        &mut self,
+        root: *const Node<K, V, E>,
+        mut pred: impl FnMut(&K) -> bool,
+    ) -> bool {
+        let mut current = root;
+        loop {
+            let i = unsafe { Self::partition(current, &mut pred) };
+            self.push(current, i);
+            match unsafe { Self::child(current, i) } {
+                Some(child) => current = child,
+                None => return unsafe { self.settle_forward() },
+            }
+        }
+    }
+
+    /// Moves the cursor to the last entry for which `pred` returns `false`.
+    ///
+    /// `pred` must return `false` for every key before some point and `true`
+    /// for every key after it. Returns `false` if there is no such entry.
+    ///
+    /// # Safety
+    ///
+    /// `root` must point to the root node of a valid B-tree.
+    unsafe fn seek_last(
+        &mut self,
+        root: *const Node<K, V, E>,
+        mut pred: impl FnMut(&K) -> bool,
+    ) -> bool {
+        let mut current = root;
+        loop {
+            let i = unsafe { Self::partition(current, &mut pred) };
+            self.push(current, i);
+            match unsafe { Self::child(current, i) } {
+                Some(child) => current = child,
+                None => return self.settle_back(),
+            }
+        }
+    }
+
+    /// Returns the index of the first key in `node` for which `pred` returns
+    /// `true`, or the length of `node` if there is no such key.
+    ///
+    /// # Safety
+    ///
+    /// `node` must point to a valid node.
+    unsafe fn partition(
+        node: *const Node<K, V, E>,
+        pred: &mut impl FnMut(&K) -> bool,
+    ) -> usize {
+        let node = unsafe { &*node };
+        let len = usize_from_archived(node.len);
+        let mut i = 0;
+        while i < len && !pred(unsafe { node.keys[i].assume_init_ref() }) {
+            i += 1;
+        }
+        i
+    }
+
+    /// Moves the cursor to the entry after the subtrees it has finished.
+    ///
+    /// The last frame is treated as the index of a child whose entries have
+    /// all been yielded. Returns `false` if there are no more entries.
+    ///
+    /// # Safety
+    ///
+    /// Every frame of the cursor must point to a valid node.
+    unsafe fn settle_forward(&mut self) -> bool {
+        while self.depth > 0 {
+            let (node, i) = self.path[self.depth - 1];
+            if i < usize_from_archived(unsafe { (*node).len }) {
+                return true;
+            }
+            self.depth -= 1;
+        }
+        false
+    }
+
+    /// Moves the cursor to the entry before the subtrees it has finished.
+    ///
+    /// The last frame is treated as the index of a child whose entries have
+    /// all been yielded. Returns `false` if there are no more entries.
+    fn settle_back(&mut self) -> bool {
+        while self.depth > 0 {
+            let (_, i) = &mut self.path[self.depth - 1];
+            if *i > 0 {
+                *i -= 1;
+                return true;
+            }
+            self.depth -= 1;
+        }
+        false
+    }
+
+    /// Moves the cursor to the next entry, returning `false` if there is none.
+    ///
+    /// # Safety
+    ///
+    /// The cursor must be at an entry of a valid B-tree.
+    unsafe fn move_next(&mut self) -> bool {
+        let (mut node, i) = self.path[self.depth - 1];
+        // The entries after entry `i` start with those in child `i + 1`.
+        self.path[self.depth - 1].1 = i + 1;
+        let mut index = i + 1;
+        while let Some(child) = unsafe { Self::child(node, index) } {
+            self.push(child, 0);
+            node = child;
+            index = 0;
+        }
+        unsafe { self.settle_forward() }
+    }
+
+    /// Moves the cursor to the previous entry, returning `false` if there is
+    /// none.
+    ///
+    /// # Safety
+    ///
+    /// The cursor must be at an entry of a valid B-tree.
+    unsafe fn move_prev(&mut self) -> bool {
+        // The entries before entry `i` end with those in child `i`.
+        let (mut node, mut index) = self.path[self.depth - 1];
+        while let Some(child) = unsafe { Self::child(node, index) } {
+            let len = usize_from_archived(unsafe { (*child).len });
+            self.push(child, len);
+            node = child;
+            index = len;
+        }
+        self.settle_back()
+    }
+
+    /// Returns the entry the cursor is at.
+    ///
+    /// # Safety
+    ///
+    /// The cursor must be at an entry of a valid B-tree which is borrowed for
+    /// `'a`.
+    unsafe fn entry<'a>(&self) -> EntryRef<'a, K, V> {
+        let (node, i) = self.path[self.depth - 1];
+        let node = unsafe { &*node };
+        unsafe {
+            (
+                node.keys[i].assume_init_ref(),
+                node.values[i].assume_init_ref(),
+            )
+        }
+    }
+}
 
-    // TODO: add entries iterator if alloc feature is enabled
+/// An iterator over a range of the key-value pairs of an archived B-tree map.
+///
+/// The iterator keeps the path from the root of the B-tree to the next entries
+/// at each end, so each step only visits the nodes between two adjacent
+/// entries.
+pub struct Range<'a, K, V, const E: usize> {
+    // Cursors at the next entries to yield from the front and back, or `None`
+    // if the iterator is exhausted.
+    ends: Option<(Cursor<K, V, E>, Cursor<K, V, E>)>,
+    _phantom: PhantomData<&'a ArchivedBTreeMap<K, V, E>>,
 }
 
+impl<K, V, const E: usize> Clone for Range<'_, K, V, E> {
+    fn clone(&self) -> Self {
+        Self {
+            ends: self.ends,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V, const E: usize> Range<'a, K, V, E> {
+    /// Returns a range from the first entry for which `front` returns `true`
+    /// to the last entry for which `back` returns `false`.
+    fn new(
+        map: &'a ArchivedBTreeMap<K, V, E>,
+        front: impl FnMut(&K) -> bool,
+        mut back: impl FnMut(&K) -> bool,
+    ) -> Self {
+        let mut ends = None;
+        if !map.is_empty() {
+            let root = unsafe { map.root.as_ptr().cast::<Node<K, V, E>>() };
+            let mut first = Cursor::new();
+            let mut last = Cursor::new();
+            // SAFETY: The map is not empty, so its root points to the root
+            // node of a valid B-tree.
+            let found = unsafe {
+                first.seek_first(root, front) && last.seek_last(root, &mut back)
+            };
+            // The range is empty if the first entry is after the last one,
+            // which is when `back` returns `true` for it.
+            // SAFETY: `first` is at an entry of the map, which is borrowed for
+            // `'a`.
+            if found && !back(unsafe { first.entry().0 }) {
+                ends = Some((first, last));
+            }
+        }
+
+        Self {
+            ends,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const E: usize> Iterator for Range<'a, K, V, E> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (front, back) = self.ends.as_mut()?;
+        // SAFETY: The cursors are at entries of the borrowed map until the
+        // iterator is exhausted.
+        let (entry, last) = unsafe { (front.entry(), back.entry()) };
+        if ptr::eq(entry.0, last.0) || !unsafe { front.move_next() } {
+            self.ends = None;
+        }
+        Some(entry)
+    }
+}
+
+impl<K: Ord, V, const E: usize> DoubleEndedIterator for Range<'_, K, V, E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (front, back) = self.ends.as_mut()?;
+        // SAFETY: The cursors are at entries of the borrowed map until the
+        // iterator is exhausted.
+        let (first, entry) = unsafe { (front.entry(), back.entry()) };
+        if ptr::eq(first.0, entry.0) || !unsafe { back.move_prev() } {
+            self.ends = None;
+        }
+        Some(entry)
+    }
+}
+
+impl<K: Ord, V, const E: usize> FusedIterator for Range<'_, K, V, E> {}
+
+/// An iterator over the key-value pairs of an archived B-tree map.
+pub struct Iter<'a, K, V, const E: usize> {
+    range: Range<'a, K, V, E>,
+    remaining: usize,
+}
+
+impl<K, V, const E: usize> Clone for Iter<'_, K, V, E> {
+    fn clone(&self) -> Self {
+        Self {
+            range: self.range.clone(),
+            remaining: self.remaining,
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const E: usize> Iterator for Iter<'a, K, V, E> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.range.next()?;
+        self.remaining -= 1;
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K: Ord, V, const E: usize> DoubleEndedIterator for Iter<'_, K, V, E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let result = self.range.next_back()?;
+        self.remaining -= 1;
+        Some(result)
+    }
+}
+
+impl<K: Ord, V, const E: usize> ExactSizeIterator for Iter<'_, K, V, E> {}
+
+impl<K: Ord, V, const E: usize> FusedIterator for Iter<'_, K, V, E> {}
+
+/// An iterator over the keys of an archived B-tree map.
+pub struct Keys<'a, K, V, const E: usize> {
+    iter: Iter<'a, K, V, E>,
+}
+
+impl<K, V, const E: usize> Clone for Keys<'_, K, V, E> {
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const E: usize> Iterator for Keys<'a, K, V, E> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(k, _)| k)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K: Ord, V, const E: usize> DoubleEndedIterator for Keys<'_, K, V, E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K: Ord, V, const E: usize> ExactSizeIterator for Keys<'_, K, V, E> {}
+
+impl<K: Ord, V, const E: usize> FusedIterator for Keys<'_, K, V, E> {}
+
+/// An iterator over the values of an archived B-tree map.
+pub struct Values<'a, K, V, const E: usize> {
+    iter: Iter<'a, K, V, E>,
+}
+
+impl<K, V, const E: usize> Clone for Values<'_, K, V, E> {
+    fn clone(&self) -> Self {
+        Self {
+            iter: self.iter.clone(),
+        }
+    }
+}
+
+impl<'a, K: Ord, V, const E: usize> Iterator for Values<'a, K, V, E> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K: Ord, V, const E: usize> DoubleEndedIterator for Values<'_, K, V, E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<K: Ord, V, const E: usize> ExactSizeIterator for Values<'_, K, V, E> {}
+
+impl<K: Ord, V, const E: usize> FusedIterator for Values<'_, K, V, E> {}
+
 impl<K, V, const E: usize> fmt::Debug for ArchivedBTreeMap<K, V, E>
 where
     K: fmt::Debug,
@@ -605,14 +1628,15 @@ pub struct BTreeMapResolver {
 
 #[cfg(feature = "bytecheck")]
 mod verify {
-    use core::{alloc::Layout, fmt, ptr::addr_of};
+    use core::{alloc::Layout, fmt, mem::size_of, ptr::addr_of};
 
     use bytecheck::{CheckBytes, Verify};
     use rancor::{fail, Fallible, Source};
 
-    use super::{ArchivedBTreeMap, InnerNode, Node};
+    use super::{ArchivedBTreeMap, InnerNode, Node, MAX_HEIGHT};
     use crate::{
         collections::btree_map::NodeKind,
+        primitive::native_len,
         validation::{ArchiveContext, ArchiveContextExt as _},
         RawRelPtr,
     };
@@ -637,6 +1661,25 @@ mod verify {
     #[cfg(feature = "std")]
     impl std::error::Error for InvalidLength {}
 
+    #[derive(Debug)]
+    struct InvalidHeight {
+        maximum: usize,
+    }
+
+    impl fmt::Display for InvalidHeight {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "Invalid height of B-tree: the tree was taller than the \
+                 maximum height {}",
+                self.maximum
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for InvalidHeight {}
+
     unsafe impl<C, K, V, const E: usize> Verify<C> for ArchivedBTreeMap<K, V, E>
     where
         C: Fallible + ArchiveContext + ?Sized,
@@ -645,18 +1688,19 @@ mod verify {
         V: CheckBytes<C>,
     {
         fn verify(&self, context: &mut C) -> Result<(), C::Error> {
-            let len = self.len();
+            let len = native_len(self.len)?;
 
             if len == 0 {
                 return Ok(());
             }
 
-            check_node_rel_ptr::<C, K, V, E>(&self.root, context)
+            check_node_rel_ptr::<C, K, V, E>(&self.root, 1, context)
         }
     }
 
     fn check_node_rel_ptr<C, K, V, const E: usize>(
         node_rel_ptr: &RawRelPtr,
+        depth: usize,
         context: &mut C,
     ) -> Result<(), C::Error>
     where
@@ -665,6 +1709,14 @@ mod verify {
         K: CheckBytes<C>,
         V: CheckBytes<C>,
     {
+        // Iterators keep the path from the root to their entries, which can be
+        // at most `MAX_HEIGHT` nodes long.
+        if depth > MAX_HEIGHT {
+            fail!(InvalidHeight {
+                maximum: MAX_HEIGHT,
+            });
+        }
+
         let node_ptr = node_rel_ptr.as_ptr_wrapping().cast::<Node<K, V, E>>();
         context.check_subtree_ptr(
             node_ptr.cast::<u8>(),
@@ -694,8 +1746,7 @@ mod verify {
         // SAFETY: `len_ptr` was always properly aligned and dereferenceable,
         // and we just checked to make sure it pointed to a valid
         // `ArchivedUsize`.
-        let len = unsafe { &*len_ptr };
-        let len = len.to_native() as usize;
+        let len = native_len(unsafe { len_ptr.read() })?;
         if len > E {
             fail!(InvalidLength { len, maximum: E });
         }
@@ -720,6 +1771,7 @@ mod verify {
                     check_inner_node::<C, K, V, E>(
                         node_ptr.cast(),
                         len,
+                        depth,
                         context,
                     )?
                 }
@@ -768,7 +1820,9 @@ mod verify {
             context.pop_subtree_range(range)?;
         }
 
-        Ok(())
+        // Progress is reported after every node so that validating a large
+        // B-tree can be cancelled partway through.
+        context.report_progress(size)
     }
 
     /// # Safety
@@ -821,6 +1875,7 @@ mod verify {
     unsafe fn check_inner_node<C, K, V, const E: usize>(
         node_ptr: *const InnerNode<K, V, E>,
         len: usize,
+        depth: usize,
         context: &mut C,
     ) -> Result<(), C::Error>
     where
@@ -850,7 +1905,11 @@ mod verify {
                 // succeeded, so it's safe to dereference.
                 let lesser_node = unsafe { &*lesser_node_ptr };
                 if !lesser_node.is_invalid() {
-                    check_node_rel_ptr::<C, K, V, E>(lesser_node, context)?;
+                    check_node_rel_ptr::<C, K, V, E>(
+                        lesser_node,
+                        depth + 1,
+                        context,
+                    )?;
                 }
             }
             // SAFETY: We checked that `node_ptr` is properly aligned and
@@ -867,7 +1926,11 @@ mod verify {
             // so it's safe to dereference.
             let greater_node = unsafe { &*greater_node_ptr };
             if !greater_node.is_invalid() {
-                check_node_rel_ptr::<C, K, V, E>(greater_node, context)?;
+                check_node_rel_ptr::<C, K, V, E>(
+                    greater_node,
+                    depth + 1,
+                    context,
+                )?;
             }
 
             // SAFETY: We checked that `node_ptr` is properly aligned and
@@ -877,7 +1940,7 @@ mod verify {
                 check_node_entries::<C, K, V, E>(node_ptr, len, context)?;
             }
 
-            Ok(())
+            context.report_progress(size_of::<InnerNode<K, V, E>>())
         })
     }
 }
@@ -1,12 +1,19 @@
 //! [`Archive`](crate::Archive) implementation for B-tree sets.
 
-use core::{borrow::Borrow, fmt, ops::ControlFlow};
+use core::{
+    cmp::Ordering,
+    fmt,
+    iter::FusedIterator,
+    ops::{ControlFlow, RangeBounds},
+};
 
 use munge::munge;
 use rancor::{Fallible, Source};
 
 use crate::{
-    collections::btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+    collections::btree_map::{
+        self, ArchivedBTreeMap, BTreeMapResolver, LookupOrd,
+    },
     ser::{Allocator, Writer},
     Place, Portable, Serialize,
 };
@@ -22,26 +29,89 @@ pub struct ArchivedBTreeSet<K, const E: usize = 5>(ArchivedBTreeMap<K, (), E>);
 impl<K, const E: usize> ArchivedBTreeSet<K, E> {
     /// Returns `true` if the set contains a value for the specified key.
     ///
-    /// The key may be any borrowed form of the set's key type, but the ordering
-    /// on the borrowed form _must_ match the ordering on the key type.
-    pub fn contains_key<Q: Ord + ?Sized>(&self, key: &Q) -> bool
-    where
-        K: Borrow<Q> + Ord,
-    {
+    /// The key may be any type which implements [`LookupOrd`] for the set's
+    /// key type, such as the unarchived form of the key type.
+    pub fn contains_key<Q: LookupOrd<K> + ?Sized>(&self, key: &Q) -> bool {
         self.0.contains_key(key)
     }
 
     /// Returns a reference to the value in the set, if any, that is equal to
     /// the given value.
     ///
-    /// The value may be any borrowed form of the set's value type, but the
-    /// ordering on the borrowed form _must_ match the ordering on the value
-    /// type.
-    pub fn get<Q: Ord + ?Sized>(&self, value: &Q) -> Option<&K>
+    /// The value may be any type which implements [`LookupOrd`] for the set's
+    /// value type, such as the unarchived form of the value type.
+    pub fn get<Q: LookupOrd<K> + ?Sized>(&self, value: &Q) -> Option<&K> {
+        self.0.get_key_value(value).map(|(key, _)| key)
+    }
+
+    /// Returns a reference to the value in the set for which `cmp` returns
+    /// `Ordering::Equal`, if any.
+    ///
+    /// `cmp` should return whether a value is less than, equal to, or greater
+    /// than the target, and must be consistent with the ordering of the values
+    /// in the set.
+    pub fn get_with<F>(&self, cmp: F) -> Option<&K>
     where
-        K: Borrow<Q> + Ord,
+        F: FnMut(&K) -> Ordering,
     {
-        self.0.get_key_value(value).map(|(key, _)| key)
+        self.0.get_key_value_with(cmp).map(|(key, _)| key)
+    }
+
+    /// Returns the first value in the set, if any.
+    pub fn first(&self) -> Option<&K> {
+        self.0.first_key_value().map(|(key, _)| key)
+    }
+
+    /// Returns the last value in the set, if any.
+    pub fn last(&self) -> Option<&K> {
+        self.0.last_key_value().map(|(key, _)| key)
+    }
+
+    /// Returns an iterator over the values of the set, in ascending order.
+    pub fn iter(&self) -> Iter<'_, K, E> {
+        Iter {
+            inner: self.0.keys(),
+        }
+    }
+
+    /// Returns an iterator over the values of the set which are within the
+    /// given range, in ascending order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than the end of the range,
+    /// or if the start and end of the range are equal and both excluded.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, E>
+    where
+        Q: LookupOrd<K> + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        Range {
+            inner: self.0.range(range),
+        }
+    }
+
+    /// Returns `true` if every value in the set is also in `other`.
+    pub fn is_subset<const F: usize>(
+        &self,
+        other: &ArchivedBTreeSet<K, F>,
+    ) -> bool
+    where
+        K: Ord,
+    {
+        self.len() <= other.len()
+            && is_sorted_subset(self.iter(), other.iter(), |a, b| a == b)
+    }
+
+    /// Returns `true` if every value in `other` is also in the set.
+    pub fn is_superset<const F: usize>(
+        &self,
+        other: &ArchivedBTreeSet<K, F>,
+    ) -> bool
+    where
+        K: Ord,
+    {
+        other.is_subset(self)
     }
 
     /// Returns `true` if the set contains no elements.
@@ -95,6 +165,89 @@ impl<K, const E: usize> ArchivedBTreeSet<K, E> {
     }
 }
 
+/// Returns whether every item of `sub` is also an item of `sup`, where both
+/// iterators yield their items in the same ascending order.
+pub(crate) fn is_sorted_subset<A, B>(
+    mut sub: impl Iterator<Item = A>,
+    mut sup: impl Iterator<Item = B>,
+    mut eq: impl FnMut(&A, &B) -> bool,
+) -> bool {
+    sub.all(|a| sup.any(|b| eq(&a, &b)))
+}
+
+impl<'a, K: Ord, const E: usize> IntoIterator for &'a ArchivedBTreeSet<K, E> {
+    type Item = &'a K;
+    type IntoIter = Iter<'a, K, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the values of an archived B-tree set.
+pub struct Iter<'a, K, const E: usize> {
+    inner: btree_map::Keys<'a, K, (), E>,
+}
+
+impl<K, const E: usize> Clone for Iter<'_, K, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K: Ord, const E: usize> Iterator for Iter<'a, K, E> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K: Ord, const E: usize> DoubleEndedIterator for Iter<'_, K, E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
+impl<K: Ord, const E: usize> ExactSizeIterator for Iter<'_, K, E> {}
+
+impl<K: Ord, const E: usize> FusedIterator for Iter<'_, K, E> {}
+
+/// An iterator over a range of the values of an archived B-tree set.
+pub struct Range<'a, K, const E: usize> {
+    inner: btree_map::Range<'a, K, (), E>,
+}
+
+impl<K, const E: usize> Clone for Range<'_, K, E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<'a, K: Ord, const E: usize> Iterator for Range<'a, K, E> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, _)| k)
+    }
+}
+
+impl<K: Ord, const E: usize> DoubleEndedIterator for Range<'_, K, E> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, _)| k)
+    }
+}
+
+impl<K: Ord, const E: usize> FusedIterator for Range<'_, K, E> {}
+
 impl<K, const E: usize> fmt::Debug for ArchivedBTreeSet<K, E>
 where
     K: fmt::Debug,
@@ -1,6 +1,150 @@
 //! Archived versions of standard library containers.
+//!
+//! Archived collections store their lengths, so `len` and `is_empty` always
+//! take constant time. References to them implement `IntoIterator` like
+//! references to the standard library collections do.
+//!
+//! ## Default mappings
+//!
+//! Each map and set type archives as the archived collection with the closest
+//! behavior:
+//!
+//! | Type                            | Archived type        |
+//! | ------------------------------- | -------------------- |
+//! | `HashMap`, `hashbrown::HashMap` | [`ArchivedHashMap`]  |
+//! | `HashSet`, `hashbrown::HashSet` | [`ArchivedHashSet`]  |
+//! | `BTreeMap`                      | [`ArchivedBTreeMap`] |
+//! | `BTreeSet`                      | [`ArchivedBTreeSet`] |
+//! | `indexmap::IndexMap`            | [`ArchivedIndexMap`] |
+//! | `indexmap::IndexSet`            | [`ArchivedIndexSet`] |
+//!
+//! A `HashMap` or `BTreeMap` field can archive as the other kind of map with
+//! the [`AsHashMap`](crate::with::AsHashMap) and
+//! [`AsBTreeMap`](crate::with::AsBTreeMap) wrappers. Archived hash maps have
+//! faster point lookups, while archived B-tree maps iterate in order by key.
+//! Either way, the archived map deserializes back into the type of the field.
+//!
+//! [`ArchivedHashMap`]: swiss_table::ArchivedHashMap
+//! [`ArchivedHashSet`]: swiss_table::ArchivedHashSet
+//! [`ArchivedBTreeMap`]: btree_map::ArchivedBTreeMap
+//! [`ArchivedBTreeSet`]: btree_set::ArchivedBTreeSet
+//! [`ArchivedIndexMap`]: swiss_table::ArchivedIndexMap
+//! [`ArchivedIndexSet`]: swiss_table::ArchivedIndexSet
 
 pub mod btree_map;
 pub mod btree_set;
 pub mod swiss_table;
+pub mod symbol_table;
 pub mod util;
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::ops::Index;
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+    use crate::{primitive::ArchivedU32, test::to_archived};
+
+    fn count<C: ?Sized>(collection: &C) -> usize
+    where
+        for<'a> &'a C: IntoIterator,
+    {
+        collection.into_iter().count()
+    }
+
+    fn sum_products<M: ?Sized>(map: &M) -> u32
+    where
+        for<'a> &'a M: IntoIterator<Item = (&'a ArchivedU32, &'a ArchivedU32)>,
+    {
+        map.into_iter()
+            .map(|(k, v)| k.to_native() * v.to_native())
+            .sum()
+    }
+
+    fn exact_len<I: ExactSizeIterator + Clone>(iter: I) -> usize {
+        let len = iter.len();
+        assert_eq!(iter.clone().count(), len);
+        assert_eq!(iter.size_hint(), (len, Some(len)));
+        len
+    }
+
+    fn ends<I>(iter: I) -> (Option<I::Item>, Option<I::Item>)
+    where
+        I: DoubleEndedIterator + ExactSizeIterator + Clone,
+    {
+        exact_len(iter.clone());
+        (iter.clone().next(), iter.rev().next())
+    }
+
+    fn first<C: Index<usize> + ?Sized>(collection: &C) -> &C::Output {
+        &collection[0]
+    }
+
+    #[test]
+    fn generic_hash_map() {
+        let mut value = HashMap::new();
+        value.insert(1u32, 10u32);
+        value.insert(2, 20);
+
+        to_archived(&value, |archived| {
+            assert_eq!(count(archived), 2);
+            assert_eq!(sum_products(archived), 50);
+            assert_eq!(exact_len(archived.iter()), 2);
+            assert_eq!(exact_len(archived.keys()), 2);
+            assert_eq!(exact_len(archived.values()), 2);
+        });
+    }
+
+    #[test]
+    fn generic_hash_set() {
+        let value = [1u32, 2, 3].into_iter().collect::<HashSet<_>>();
+
+        to_archived(&value, |archived| {
+            assert_eq!(count(archived), 3);
+            assert_eq!(exact_len(archived.iter()), 3);
+        });
+    }
+
+    #[test]
+    fn generic_btree_map() {
+        let mut value = BTreeMap::new();
+        value.insert(1u32, 10u32);
+        value.insert(2, 20);
+
+        to_archived(&value, |archived| {
+            assert_eq!(count(archived), 2);
+            assert_eq!(sum_products(archived), 50);
+            let native = |(k, v): (&ArchivedU32, &ArchivedU32)| {
+                (k.to_native(), v.to_native())
+            };
+            let (first, last) = ends(archived.iter());
+            assert_eq!(first.map(native), Some((1, 10)));
+            assert_eq!(last.map(native), Some((2, 20)));
+            assert_eq!(ends(archived.keys()).1.map(|k| k.to_native()), Some(2));
+        });
+    }
+
+    #[test]
+    fn generic_btree_set() {
+        let value = [3u32, 1, 2].into_iter().collect::<BTreeSet<_>>();
+
+        to_archived(&value, |archived| {
+            assert_eq!(count(archived), 3);
+            let (first, last) = ends(archived.iter());
+            assert_eq!(first.map(|k| k.to_native()), Some(1));
+            assert_eq!(last.map(|k| k.to_native()), Some(3));
+        });
+    }
+
+    #[test]
+    fn generic_vec() {
+        let value = vec![1u32, 2, 3];
+
+        to_archived(&value, |archived| {
+            assert_eq!(count(archived), 3);
+            assert_eq!(first(archived).to_native(), 1);
+            let (first, last) = ends(archived.iter());
+            assert_eq!(first.map(|k| k.to_native()), Some(1));
+            assert_eq!(last.map(|k| k.to_native()), Some(3));
+        });
+    }
+}
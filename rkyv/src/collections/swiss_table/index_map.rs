@@ -3,12 +3,14 @@
 
 use core::{
     borrow::Borrow,
+    cmp::Ordering,
     fmt,
     hash::{Hash, Hasher},
     iter::FusedIterator,
     marker::PhantomData,
+    ops::Index,
     pin::Pin,
-    slice::{from_raw_parts, from_raw_parts_mut},
+    slice::{self, from_raw_parts, from_raw_parts_mut},
 };
 
 use munge::munge;
@@ -19,7 +21,7 @@ use crate::{
         swiss_table::{ArchivedHashTable, HashTableResolver},
         util::{Entry, EntryAdapter, EntryResolver},
     },
-    hash::{hash_value, FxHasher64},
+    hash::{hash_value_with, FxHasher64, LookupKey},
     primitive::ArchivedUsize,
     ser::{Allocator, Writer, WriterExt as _},
     Place, Portable, RelPtr, Serialize,
@@ -37,7 +39,9 @@ use crate::{
 pub struct ArchivedIndexMap<K, V, H = FxHasher64> {
     table: ArchivedHashTable<ArchivedUsize>,
     entries: RelPtr<Entry<K, V>>,
-    _phantom: PhantomData<H>,
+    // The hasher is only used to hash keys, so it shouldn't affect the auto
+    // traits of the map.
+    _phantom: PhantomData<fn() -> H>,
 }
 
 impl<K, V, H> ArchivedIndexMap<K, V, H> {
@@ -87,6 +91,91 @@ impl<K, V, H> ArchivedIndexMap<K, V, H> {
             inner: unsafe { self.raw_iter() },
         }
     }
+
+    /// Returns an iterator over the mutable key-value pairs of the map in
+    /// order.
+    ///
+    /// The iterator holds the unique borrow of the map for as long as it or
+    /// any of the values it yields are alive.
+    pub fn iter_mut(self: Pin<&mut Self>) -> IterMut<'_, K, V> {
+        let entries = unsafe { Pin::into_inner_unchecked(self.entries_mut()) };
+        IterMut {
+            inner: entries.iter_mut(),
+        }
+    }
+
+    /// Returns an iterator over the mutable values of the map in order.
+    ///
+    /// The iterator holds the unique borrow of the map for as long as it or
+    /// any of the values it yields are alive.
+    pub fn values_mut(self: Pin<&mut Self>) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            inner: self.iter_mut(),
+        }
+    }
+
+    /// Gets a key and mutable value by index.
+    pub fn get_index_mut(
+        self: Pin<&mut Self>,
+        index: usize,
+    ) -> Option<(&K, Pin<&mut V>)> {
+        let entries = unsafe { Pin::into_inner_unchecked(self.entries_mut()) };
+        let entry = entries.get_mut(index)?;
+        let value = unsafe { Pin::new_unchecked(&mut entry.value) };
+        Some((&entry.key, value))
+    }
+
+    /// Returns the first key-value pair of the map, if any.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let entry = self.entries().first()?;
+        Some((&entry.key, &entry.value))
+    }
+
+    /// Returns the first key and mutable value of the map, if any.
+    pub fn first_mut(self: Pin<&mut Self>) -> Option<(&K, Pin<&mut V>)> {
+        self.get_index_mut(0)
+    }
+
+    /// Returns the last key-value pair of the map, if any.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let entry = self.entries().last()?;
+        Some((&entry.key, &entry.value))
+    }
+
+    /// Returns the last key and mutable value of the map, if any.
+    pub fn last_mut(self: Pin<&mut Self>) -> Option<(&K, Pin<&mut V>)> {
+        let index = self.len().checked_sub(1)?;
+        self.get_index_mut(index)
+    }
+
+    /// Searches the keys of the map for the given key with a binary search.
+    ///
+    /// This is only meaningful if the entries of the map are sorted by key,
+    /// for example because they were inserted in sorted order. If the key is
+    /// found, returns `Ok` with its index. Otherwise, returns `Err` with the
+    /// index where it could be inserted while maintaining the sorted order. If
+    /// the entries are not sorted, the result is unspecified.
+    pub fn binary_search_keys<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+    {
+        self.binary_search_by(|k, _| k.borrow().cmp(key))
+    }
+
+    /// Searches the entries of the map with a binary search using the given
+    /// comparison function.
+    ///
+    /// The comparison function should return the order of the given entry
+    /// relative to the target. See
+    /// [`binary_search_keys`](Self::binary_search_keys) for more details.
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&K, &V) -> Ordering,
+    {
+        self.entries()
+            .binary_search_by(|entry| f(&entry.key, &entry.value))
+    }
 }
 
 impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
@@ -98,7 +187,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
         cmp: C,
     ) -> Option<(usize, &K, &V)>
     where
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         let index = self.get_index_of_with(key, cmp)?;
@@ -110,17 +199,16 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
     /// Gets the index, key, and value corresponding to the supplied key.
     pub fn get_full<Q>(&self, key: &Q) -> Option<(usize, &K, &V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
-        self.get_full_with(key, |q, k| q == k.borrow())
+        self.get_full_with(key, |q, k| q.equivalent(k))
     }
 
     /// Returns the key-value pair corresponding to the supplied key using the
     /// given comparison function.
     pub fn get_key_value_with<Q, C>(&self, key: &Q, cmp: C) -> Option<(&K, &V)>
     where
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         let (_, k, v) = self.get_full_with(key, cmp)?;
@@ -130,8 +218,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
     /// Returns the key-value pair corresponding to the supplied key.
     pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         let (_, k, v) = self.get_full(key)?;
         Some((k, v))
@@ -141,7 +228,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
     /// the given comparison function.
     pub fn get_with<Q, C>(&self, key: &Q, cmp: C) -> Option<&V>
     where
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         Some(self.get_full_with(key, cmp)?.2)
@@ -150,8 +237,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
     /// Returns a reference to the value corresponding to the supplied key.
     pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         Some(self.get_full(key)?.2)
     }
@@ -164,7 +250,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
         cmp: C,
     ) -> Option<(usize, &K, Pin<&mut V>)>
     where
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         let index = self.get_index_of_with(key, cmp)?;
@@ -181,10 +267,9 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
         key: &Q,
     ) -> Option<(usize, &K, Pin<&mut V>)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
-        self.get_full_with_mut(key, |q, k| q == k.borrow())
+        self.get_full_with_mut(key, |q, k| q.equivalent(k))
     }
 
     /// Returns the mutable key-value pair corresponding to the supplied key
@@ -195,8 +280,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
         cmp: C,
     ) -> Option<(&K, Pin<&mut V>)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         let (_, k, v) = self.get_full_with_mut(key, cmp)?;
@@ -209,8 +293,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
         key: &Q,
     ) -> Option<(&K, Pin<&mut V>)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         let (_, k, v) = self.get_full_mut(key)?;
         Some((k, v))
@@ -224,8 +307,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
         cmp: C,
     ) -> Option<Pin<&mut V>>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         Some(self.get_full_with_mut(key, cmp)?.2)
@@ -235,8 +317,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
     /// key.
     pub fn get_mut<Q>(self: Pin<&mut Self>, key: &Q) -> Option<Pin<&mut V>>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         Some(self.get_full_mut(key)?.2)
     }
@@ -244,8 +325,7 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
     /// Returns whether a key is present in the hash map.
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         self.get(key).is_some()
     }
@@ -264,11 +344,11 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
     /// comparison function.
     pub fn get_index_of_with<Q, C>(&self, key: &Q, cmp: C) -> Option<usize>
     where
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         let entries = self.entries();
-        let index = self.table.get_with(hash_value::<Q, H>(key), |i| {
+        let index = self.table.get_with(hash_value_with::<Q, H>(key), |i| {
             cmp(key, &entries[i.to_native() as usize].key)
         })?;
         Some(index.to_native() as usize)
@@ -277,10 +357,9 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
     /// Gets the index of a key if it exists in the map.
     pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
-        self.get_index_of_with(key, |q, k| q == k.borrow())
+        self.get_index_of_with(key, |q, k| q.equivalent(k))
     }
 
     /// Resolves an archived index map from a given length and parameters.
@@ -318,8 +397,9 @@ impl<K, V, H: Hasher + Default> ArchivedIndexMap<K, V, H> {
         // Serialize hash table
         let table_resolver =
             ArchivedHashTable::<ArchivedUsize>::serialize_from_iter(
-                0..iter.len(),
-                iter.clone().map(|(key, _)| hash_value::<UK, H>(key)),
+                iter.clone()
+                    .enumerate()
+                    .map(|(i, (key, _))| (hash_value_with::<UK, H>(key), i)),
                 load_factor,
                 serializer,
             )?;
@@ -395,6 +475,16 @@ impl<'a, K, V> RawIter<'a, K, V> {
     }
 }
 
+impl<K, V> Clone for RawIter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            current: self.current,
+            remaining: self.remaining,
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<'a, K, V> Iterator for RawIter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
@@ -417,6 +507,20 @@ impl<'a, K, V> Iterator for RawIter<'a, K, V> {
     }
 }
 
+impl<K, V> DoubleEndedIterator for RawIter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        unsafe {
+            if self.remaining == 0 {
+                None
+            } else {
+                self.remaining -= 1;
+                let entry = &*self.current.add(self.remaining);
+                Some((&entry.key, &entry.value))
+            }
+        }
+    }
+}
+
 impl<'a, K, V> ExactSizeIterator for RawIter<'a, K, V> {}
 impl<'a, K, V> FusedIterator for RawIter<'a, K, V> {}
 
@@ -426,6 +530,14 @@ pub struct Iter<'a, K, V> {
     inner: RawIter<'a, K, V>,
 }
 
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 impl<'a, K, V> Iterator for Iter<'a, K, V> {
     type Item = (&'a K, &'a V);
 
@@ -438,6 +550,12 @@ impl<'a, K, V> Iterator for Iter<'a, K, V> {
     }
 }
 
+impl<K, V> DoubleEndedIterator for Iter<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+}
+
 impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
 impl<K, V> FusedIterator for Iter<'_, K, V> {}
 
@@ -447,6 +565,14 @@ pub struct Keys<'a, K, V> {
     inner: RawIter<'a, K, V>,
 }
 
+impl<K, V> Clone for Keys<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 impl<'a, K, V> Iterator for Keys<'a, K, V> {
     type Item = &'a K;
 
@@ -459,6 +585,12 @@ impl<'a, K, V> Iterator for Keys<'a, K, V> {
     }
 }
 
+impl<K, V> DoubleEndedIterator for Keys<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(k, _)| k)
+    }
+}
+
 impl<K, V> ExactSizeIterator for Keys<'_, K, V> {}
 impl<K, V> FusedIterator for Keys<'_, K, V> {}
 
@@ -468,6 +600,14 @@ pub struct Values<'a, K, V> {
     inner: RawIter<'a, K, V>,
 }
 
+impl<K, V> Clone for Values<'_, K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 impl<'a, K, V> Iterator for Values<'a, K, V> {
     type Item = &'a V;
 
@@ -480,9 +620,102 @@ impl<'a, K, V> Iterator for Values<'a, K, V> {
     }
 }
 
+impl<K, V> DoubleEndedIterator for Values<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
+
 impl<K, V> ExactSizeIterator for Values<'_, K, V> {}
 impl<K, V> FusedIterator for Values<'_, K, V> {}
 
+/// An iterator over the mutable key-value pairs of an index map.
+pub struct IterMut<'a, K, V> {
+    inner: slice::IterMut<'a, Entry<K, V>>,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, Pin<&'a mut V>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| {
+            let value = unsafe { Pin::new_unchecked(&mut entry.value) };
+            (&entry.key, value)
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for IterMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|entry| {
+            let value = unsafe { Pin::new_unchecked(&mut entry.value) };
+            (&entry.key, value)
+        })
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+impl<K, V> FusedIterator for IterMut<'_, K, V> {}
+
+/// An iterator over the mutable values of an index map.
+pub struct ValuesMut<'a, K, V> {
+    inner: IterMut<'a, K, V>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = Pin<&'a mut V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(_, v)| v)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<K, V> DoubleEndedIterator for ValuesMut<'_, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(|(_, v)| v)
+    }
+}
+
+impl<K, V> ExactSizeIterator for ValuesMut<'_, K, V> {}
+impl<K, V> FusedIterator for ValuesMut<'_, K, V> {}
+
+impl<K, Q, V, H> Index<&'_ Q> for ArchivedIndexMap<K, V, H>
+where
+    Q: LookupKey<K> + ?Sized,
+    H: Default + Hasher,
+{
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).unwrap()
+    }
+}
+
+impl<K, V, H: Default + Hasher> Index<usize> for ArchivedIndexMap<K, V, H> {
+    type Output = V;
+
+    fn index(&self, index: usize) -> &V {
+        self.get_index(index).unwrap().1
+    }
+}
+
+impl<'a, K, V, H> IntoIterator for &'a ArchivedIndexMap<K, V, H> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 /// The resolver for an `IndexMap`.
 pub struct IndexMapResolver {
     table_resolver: HashTableResolver,
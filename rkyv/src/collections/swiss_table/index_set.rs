@@ -3,8 +3,10 @@
 
 use core::{
     borrow::Borrow,
+    cmp::Ordering,
     fmt,
     hash::{Hash, Hasher},
+    ops::Index,
 };
 
 use munge::munge;
@@ -14,7 +16,7 @@ use crate::{
     collections::swiss_table::{
         index_map::Keys, ArchivedIndexMap, IndexMapResolver,
     },
-    hash::FxHasher64,
+    hash::{FxHasher64, LookupKey},
     ser::{Allocator, Writer},
     Place, Portable, Serialize,
 };
@@ -30,7 +32,7 @@ pub struct ArchivedIndexSet<K, H = FxHasher64> {
 
 impl<K, H> ArchivedIndexSet<K, H> {
     /// Returns whether the index set contains no values.
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.inner.is_empty()
     }
 
@@ -40,35 +42,90 @@ impl<K, H> ArchivedIndexSet<K, H> {
     }
 
     /// Returns the number of elements in the index set.
-    pub fn len(&self) -> usize {
+    pub const fn len(&self) -> usize {
         self.inner.len()
     }
+
+    /// Returns the first key of the index set, if any.
+    pub fn first(&self) -> Option<&K> {
+        self.inner.first().map(|(k, _)| k)
+    }
+
+    /// Returns the last key of the index set, if any.
+    pub fn last(&self) -> Option<&K> {
+        self.inner.last().map(|(k, _)| k)
+    }
+
+    /// Searches the index set for the given key with a binary search.
+    ///
+    /// This is only meaningful if the keys of the set are sorted, for example
+    /// because they were inserted in sorted order. If the key is found, returns
+    /// `Ok` with its index. Otherwise, returns `Err` with the index where it
+    /// could be inserted while maintaining the sorted order. If the keys are
+    /// not sorted, the result is unspecified.
+    pub fn binary_search<Q>(&self, key: &Q) -> Result<usize, usize>
+    where
+        Q: Ord + ?Sized,
+        K: Borrow<Q>,
+    {
+        self.inner.binary_search_keys(key)
+    }
+
+    /// Searches the index set with a binary search using the given comparison
+    /// function.
+    ///
+    /// The comparison function should return the order of the given key
+    /// relative to the target. See [`binary_search`](Self::binary_search) for
+    /// more details.
+    pub fn binary_search_by<F>(&self, mut f: F) -> Result<usize, usize>
+    where
+        F: FnMut(&K) -> Ordering,
+    {
+        self.inner.binary_search_by(|k, _| f(k))
+    }
 }
 
 impl<K, H: Default + Hasher> ArchivedIndexSet<K, H> {
     /// Returns whether a key is present in the hash set.
     pub fn contains<Q>(&self, k: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         self.inner.contains_key(k)
     }
 
+    /// Returns the value stored in the set using the given comparison
+    /// function, if any.
+    pub fn get_with<Q, C>(&self, k: &Q, cmp: C) -> Option<&K>
+    where
+        Q: Hash + ?Sized,
+        C: Fn(&Q, &K) -> bool,
+    {
+        self.inner.get_full_with(k, cmp).map(|(_, k, _)| k)
+    }
+
     /// Returns the value stored in the set, if any.
     pub fn get<Q>(&self, k: &Q) -> Option<&K>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         self.inner.get_full(k).map(|(_, k, _)| k)
     }
 
+    /// Returns the item index and value stored in the set using the given
+    /// comparison function, if any.
+    pub fn get_full_with<Q, C>(&self, k: &Q, cmp: C) -> Option<(usize, &K)>
+    where
+        Q: Hash + ?Sized,
+        C: Fn(&Q, &K) -> bool,
+    {
+        self.inner.get_full_with(k, cmp).map(|(i, k, _)| (i, k))
+    }
+
     /// Returns the item index and value stored in the set, if any.
     pub fn get_full<Q>(&self, k: &Q) -> Option<(usize, &K)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         self.inner.get_full(k).map(|(i, k, _)| (i, k))
     }
@@ -78,11 +135,20 @@ impl<K, H: Default + Hasher> ArchivedIndexSet<K, H> {
         self.inner.get_index(index).map(|(k, _)| k)
     }
 
+    /// Returns the index of a key if it exists in the set using the given
+    /// comparison function.
+    pub fn get_index_of_with<Q, C>(&self, key: &Q, cmp: C) -> Option<usize>
+    where
+        Q: Hash + ?Sized,
+        C: Fn(&Q, &K) -> bool,
+    {
+        self.inner.get_index_of_with(key, cmp)
+    }
+
     /// Returns the index of a key if it exists in the set.
     pub fn get_index_of<Q>(&self, key: &Q) -> Option<usize>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         self.inner.get_index_of(key)
     }
@@ -134,5 +200,22 @@ impl<K: PartialEq, H> PartialEq for ArchivedIndexSet<K, H> {
 
 impl<K: Eq, H> Eq for ArchivedIndexSet<K, H> {}
 
+impl<K, H: Default + Hasher> Index<usize> for ArchivedIndexSet<K, H> {
+    type Output = K;
+
+    fn index(&self, index: usize) -> &K {
+        self.get_index(index).unwrap()
+    }
+}
+
+impl<'a, K, H> IntoIterator for &'a ArchivedIndexSet<K, H> {
+    type Item = &'a K;
+    type IntoIter = Keys<'a, K, ()>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 /// The resolver for archived index sets.
 pub struct IndexSetResolver(IndexMapResolver);
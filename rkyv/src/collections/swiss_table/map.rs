@@ -1,5 +1,7 @@
 //! Archived hash map implementation using an archived SwissTable.
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::collections::BTreeMap;
 use core::{
     borrow::Borrow,
     fmt,
@@ -9,28 +11,45 @@ use core::{
     ops::Index,
     pin::Pin,
 };
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, HashMap};
 
 use munge::munge;
 use rancor::{Fallible, Source};
 
+#[cfg(feature = "alloc")]
+use crate::ser::Deferred;
 use crate::{
     collections::{
         swiss_table::table::{ArchivedHashTable, HashTableResolver, RawIter},
-        util::{Entry, EntryAdapter},
+        util::{Entry, EntryAdapter, EntryResolver},
     },
-    hash::{hash_value, FxHasher64},
+    hash::{hash_value_with, FxHasher64, LookupKey},
     ser::{Allocator, Writer},
-    Place, Portable, Serialize,
+    Archive, Place, Portable, Serialize,
 };
 
+/// The load factor used by the built-in implementations for hash maps and hash
+/// sets.
+///
+/// Hash maps and hash sets serialized with this load factor have at most seven
+/// items for every eight buckets.
+pub const DEFAULT_LOAD_FACTOR: (usize, usize) = (7, 8);
+
 /// An archived SwissTable hash map.
 #[derive(Portable)]
 #[rkyv(crate)]
 #[repr(transparent)]
-#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
 pub struct ArchivedHashMap<K, V, H = FxHasher64> {
     table: ArchivedHashTable<Entry<K, V>>,
-    _phantom: PhantomData<H>,
+    // The hasher is only used to hash keys, so it shouldn't affect the auto
+    // traits of the map.
+    _phantom: PhantomData<fn() -> H>,
 }
 
 impl<K, V, H> ArchivedHashMap<K, V, H> {
@@ -49,6 +68,13 @@ impl<K, V, H> ArchivedHashMap<K, V, H> {
         self.table.capacity()
     }
 
+    /// Returns the underlying hash table of the hash map.
+    ///
+    /// This can be used to inspect the probe statistics of the hash map.
+    pub fn raw_table(&self) -> &ArchivedHashTable<Entry<K, V>> {
+        &self.table
+    }
+
     /// Returns an iterator over the key-value entries in the hash map.
     pub fn iter(&self) -> Iter<'_, K, V, H> {
         Iter {
@@ -58,6 +84,9 @@ impl<K, V, H> ArchivedHashMap<K, V, H> {
     }
 
     /// Returns an iterator over the mutable key-value entries in the hash map.
+    ///
+    /// The iterator holds the unique borrow of the hash map for as long as it
+    /// or any of the values it yields are alive.
     pub fn iter_mut(self: Pin<&mut Self>) -> IterMut<'_, K, V, H> {
         let table = unsafe { self.map_unchecked_mut(|s| &mut s.table) };
         IterMut {
@@ -83,6 +112,9 @@ impl<K, V, H> ArchivedHashMap<K, V, H> {
     }
 
     /// Returns an iterator over the mutable values in the hash map.
+    ///
+    /// The iterator holds the unique borrow of the hash map for as long as it
+    /// or any of the values it yields are alive.
     pub fn values_mut(self: Pin<&mut Self>) -> ValuesMut<'_, K, V, H> {
         let table = unsafe { self.map_unchecked_mut(|s| &mut s.table) };
         ValuesMut {
@@ -90,46 +122,91 @@ impl<K, V, H> ArchivedHashMap<K, V, H> {
             _phantom: PhantomData,
         }
     }
+
+    /// Calls `f` with each value in the hash map as a pinned mutable
+    /// reference.
+    ///
+    /// The keys can't be changed because that would invalidate their hashes,
+    /// but every value can be updated in place. Like
+    /// [`ArchivedVec::fill`](crate::vec::ArchivedVec::fill), values which are
+    /// `Unpin` don't contain relative pointers and can be overwritten
+    /// entirely.
+    pub fn for_each_value_mut<F>(self: Pin<&mut Self>, f: F)
+    where
+        F: FnMut(Pin<&mut V>),
+    {
+        self.values_mut().for_each(f);
+    }
 }
 
 impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
+    /// Returns the key-value pair whose key has the given hash and matches the
+    /// given comparison function.
+    ///
+    /// The hash must be the same as the one produced by hashing the unarchived
+    /// key with `H`. This can be used to look up keys with query types which
+    /// don't implement `Hash`.
+    pub fn get_key_value_with_hasher<C>(
+        &self,
+        hash: u64,
+        cmp: C,
+    ) -> Option<(&K, &V)>
+    where
+        C: Fn(&K) -> bool,
+    {
+        let entry = self.table.get_with(hash, |e| cmp(&e.key))?;
+        Some((&entry.key, &entry.value))
+    }
+
+    /// Returns a reference to the value whose key has the given hash and
+    /// matches the given comparison function.
+    ///
+    /// See [`get_key_value_with_hasher`](Self::get_key_value_with_hasher) for
+    /// more details.
+    pub fn get_with_hasher<C>(&self, hash: u64, cmp: C) -> Option<&V>
+    where
+        C: Fn(&K) -> bool,
+    {
+        Some(self.get_key_value_with_hasher(hash, cmp)?.1)
+    }
+
     /// Returns the key-value pair corresponding to the supplied key using the
     /// given comparison function.
     pub fn get_key_value_with<Q, C>(&self, key: &Q, cmp: C) -> Option<(&K, &V)>
     where
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
-        let entry = self
-            .table
-            .get_with(hash_value::<Q, H>(key), |e| cmp(key, &e.key))?;
-        Some((&entry.key, &entry.value))
+        self.get_key_value_with_hasher(hash_value_with::<Q, H>(key), |k| {
+            cmp(key, k)
+        })
     }
 
     /// Returns the key-value pair corresponding to the supplied key.
     pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
-        self.get_key_value_with(key, |q, k| q == k.borrow())
+        self.get_key_value_with(key, |q, k| q.equivalent(k))
     }
 
     /// Returns a reference to the value corresponding to the supplied key using
     /// the given comparison function.
     pub fn get_with<Q, C>(&self, key: &Q, cmp: C) -> Option<&V>
     where
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         Some(self.get_key_value_with(key, cmp)?.1)
     }
 
     /// Returns a reference to the value corresponding to the supplied key.
+    ///
+    /// The key may be an unarchived version of the key type, such as a `str`
+    /// for `ArchivedString` keys. See [`LookupKey`] for more details.
     pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         Some(self.get_key_value(key)?.1)
     }
@@ -142,13 +219,12 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
         cmp: C,
     ) -> Option<(&K, Pin<&mut V>)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         let table = unsafe { Pin::map_unchecked_mut(self, |s| &mut s.table) };
         let entry = table
-            .get_with_mut(hash_value::<Q, H>(key), |e| cmp(key, &e.key))?;
+            .get_with_mut(hash_value_with::<Q, H>(key), |e| cmp(key, &e.key))?;
         let entry = unsafe { Pin::into_inner_unchecked(entry) };
         let key = &entry.key;
         let value = unsafe { Pin::new_unchecked(&mut entry.value) };
@@ -161,10 +237,9 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
         key: &Q,
     ) -> Option<(&K, Pin<&mut V>)>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
-        self.get_key_value_mut_with(key, |q, k| q == k.borrow())
+        self.get_key_value_mut_with(key, |q, k| q.equivalent(k))
     }
 
     /// Returns a mutable reference to the value corresponding to the supplied
@@ -175,8 +250,7 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
         cmp: C,
     ) -> Option<Pin<&mut V>>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: Hash + ?Sized,
         C: Fn(&Q, &K) -> bool,
     {
         Some(self.get_key_value_mut_with(key, cmp)?.1)
@@ -186,17 +260,38 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
     /// key.
     pub fn get_mut<Q>(self: Pin<&mut Self>, key: &Q) -> Option<Pin<&mut V>>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         Some(self.get_key_value_mut(key)?.1)
     }
 
+    /// Returns mutable references to the values corresponding to each of the
+    /// supplied keys.
+    ///
+    /// Returns `None` if any of the keys are missing, or if any two keys refer
+    /// to the same entry.
+    pub fn get_many_mut<Q, const N: usize>(
+        self: Pin<&mut Self>,
+        keys: [&Q; N],
+    ) -> Option<[Pin<&mut V>; N]>
+    where
+        Q: LookupKey<K> + ?Sized,
+    {
+        let table = unsafe { Pin::map_unchecked_mut(self, |s| &mut s.table) };
+        let entries = table.get_many_with_mut(
+            keys.map(|key| hash_value_with::<Q, H>(key)),
+            |i, e| keys[i].equivalent(&e.key),
+        )?;
+        Some(entries.map(|entry| {
+            let entry = unsafe { Pin::into_inner_unchecked(entry) };
+            unsafe { Pin::new_unchecked(&mut entry.value) }
+        }))
+    }
+
     /// Returns whether the hash map contains the given key.
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         self.get(key).is_some()
     }
@@ -208,21 +303,237 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
         serializer: &mut S,
     ) -> Result<HashMapResolver, S::Error>
     where
-        I: Clone + ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        I: ExactSizeIterator<Item = (&'a KU, &'a VU)>,
         KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
         VU: 'a + Serialize<S, Archived = V>,
         S: Fallible + Writer + Allocator + ?Sized,
         S::Error: Source,
+    {
+        Self::serialize_from_iter_with_buckets(
+            iter,
+            load_factor,
+            serializer,
+            |_| (),
+        )
+    }
+
+    /// Serializes an iterator of items which borrow as keys and values as a
+    /// hash map.
+    ///
+    /// Unlike [`serialize_from_iter`](Self::serialize_from_iter), the items
+    /// only have to borrow as `KU` and `VU`, so they can be owned adapters
+    /// built while iterating. `KU` and `VU` only have to serialize as `K` and
+    /// `V`, and don't have to be the types that `K` and `V` were archived
+    /// from. This lets an archived hash map be built from borrowed data without
+    /// collecting it into a `HashMap` first. The keys must hash the same as the
+    /// archived keys they're looked up with.
+    ///
+    /// Because the items can be borrowed or owned, the types of `KU` and `VU`
+    /// usually have to be specified.
+    ///
+    /// # Example
+    ///
+    /// Rows returned by a query can be archived as a hash map by serializing
+    /// the `&str` keys with a [`StrAdapter`](crate::string::StrAdapter):
+    ///
+    /// ```
+    /// use rkyv::{
+    ///     collections::swiss_table::{
+    ///         ArchivedHashMap, HashMapResolver, DEFAULT_LOAD_FACTOR,
+    ///     },
+    ///     rancor::{Error, Fallible, Source},
+    ///     ser::{Allocator, Writer},
+    ///     string::{ArchivedString, StrAdapter},
+    ///     Archive, Archived, Place, Serialize,
+    /// };
+    ///
+    /// type ArchivedRows = ArchivedHashMap<ArchivedString, Archived<u32>>;
+    ///
+    /// struct Rows<'a>(&'a [(&'a str, u32)]);
+    ///
+    /// impl Archive for Rows<'_> {
+    ///     type Archived = ArchivedRows;
+    ///     type Resolver = HashMapResolver;
+    ///
+    ///     fn resolve(
+    ///         &self,
+    ///         resolver: Self::Resolver,
+    ///         out: Place<Self::Archived>,
+    ///     ) {
+    ///         ArchivedRows::resolve_from_len(
+    ///             self.0.len(),
+    ///             DEFAULT_LOAD_FACTOR,
+    ///             resolver,
+    ///             out,
+    ///         );
+    ///     }
+    /// }
+    ///
+    /// impl<S> Serialize<S> for Rows<'_>
+    /// where
+    ///     S: Fallible + Allocator + Writer + ?Sized,
+    ///     S::Error: Source,
+    /// {
+    ///     fn serialize(
+    ///         &self,
+    ///         serializer: &mut S,
+    ///     ) -> Result<Self::Resolver, S::Error> {
+    ///         ArchivedRows::serialize_from_borrowed_iter::<
+    ///             _,
+    ///             _,
+    ///             _,
+    ///             StrAdapter,
+    ///             u32,
+    ///             _,
+    ///         >(
+    ///             self.0.iter().map(|(key, value)| (StrAdapter(key), value)),
+    ///             DEFAULT_LOAD_FACTOR,
+    ///             serializer,
+    ///         )
+    ///     }
+    /// }
+    ///
+    /// let rows = [("alice", 10), ("bob", 20)];
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&Rows(&rows)).unwrap();
+    /// let archived = rkyv::access::<Archived<Rows>, Error>(&bytes).unwrap();
+    /// assert_eq!(archived.len(), 2);
+    /// assert_eq!(archived.get("bob").map(|v| v.to_native()), Some(20));
+    /// ```
+    pub fn serialize_from_borrowed_iter<I, BKU, BVU, KU, VU, S>(
+        iter: I,
+        load_factor: (usize, usize),
+        serializer: &mut S,
+    ) -> Result<HashMapResolver, S::Error>
+    where
+        I: ExactSizeIterator<Item = (BKU, BVU)>,
+        BKU: Borrow<KU>,
+        BVU: Borrow<VU>,
+        KU: Serialize<S, Archived = K> + Hash + Eq,
+        VU: Serialize<S, Archived = V>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
     {
         ArchivedHashTable::<Entry<K, V>>::serialize_from_iter(
-            iter.clone().map(|(key, value)| EntryAdapter { key, value }),
-            iter.map(|(key, _)| hash_value::<KU, H>(key)),
+            iter.map(|(key, value)| {
+                let hash = hash_value_with::<KU, H>(key.borrow());
+                (hash, BorrowEntryAdapter::new(key, value))
+            }),
+            load_factor,
+            serializer,
+        )
+        .map(HashMapResolver)
+    }
+
+    /// Serializes an iterator of key-value pairs as a hash map, writing the
+    /// out-of-line data of the values after the table.
+    ///
+    /// The keys and the table are written before the values, so lookups only
+    /// touch the part of the archive with the keys until a value is read. The
+    /// values are serialized with a [`Deferred`] serializer.
+    ///
+    /// The archived hash map must be resolved with
+    /// [`resolve_from_len`](Self::resolve_from_len) using the same load factor.
+    #[cfg(feature = "alloc")]
+    pub fn serialize_from_iter_values_last<'a, I, KU, VU, S>(
+        iter: I,
+        load_factor: (usize, usize),
+        serializer: &mut S,
+    ) -> Result<HashMapResolver, S::Error>
+    where
+        I: ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+        VU: 'a + Archive<Archived = V> + for<'s> Serialize<Deferred<'s, S>>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        ArchivedHashTable::<Entry<K, V>>::serialize_entries_values_last(
+            iter.map(|(key, value)| {
+                (hash_value_with::<KU, H>(key), key, value)
+            }),
+            load_factor,
+            serializer,
+        )
+        .map(HashMapResolver)
+    }
+
+    /// Serializes an iterator of key-value pairs as a hash map, calling
+    /// `on_insert` with the bucket index of each entry in iteration order.
+    pub(crate) fn serialize_from_iter_with_buckets<'a, I, KU, VU, S, F>(
+        iter: I,
+        load_factor: (usize, usize),
+        serializer: &mut S,
+        on_insert: F,
+    ) -> Result<HashMapResolver, S::Error>
+    where
+        I: ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+        VU: 'a + Serialize<S, Archived = V>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+        F: FnMut(usize),
+    {
+        ArchivedHashTable::<Entry<K, V>>::serialize_from_iter_with_buckets(
+            iter.map(|(key, value)| {
+                (hash_value_with::<KU, H>(key), EntryAdapter { key, value })
+            }),
             load_factor,
             serializer,
+            on_insert,
         )
         .map(HashMapResolver)
     }
 
+    /// Serializes the entries of an archived hash map which match a predicate
+    /// as a new hash map.
+    ///
+    /// The keys and values are serialized from their archived forms, so the
+    /// archived key and value types must implement `Serialize`. The keys must
+    /// hash the same as the keys they were archived from.
+    ///
+    /// The archived hash map must be resolved with
+    /// [`resolve_filtered_from_archived`](Self::resolve_filtered_from_archived)
+    /// using the same predicate and load factor.
+    pub fn serialize_filtered_from_archived<KU, VU, HU, F, S>(
+        src: &ArchivedHashMap<KU, VU, HU>,
+        pred: F,
+        load_factor: (usize, usize),
+        serializer: &mut S,
+    ) -> Result<HashMapResolver, S::Error>
+    where
+        KU: Serialize<S, Archived = K> + Hash + Eq,
+        VU: Serialize<S, Archived = V>,
+        F: Fn(&KU, &VU) -> bool,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        let remaining = count_filtered(src, &pred);
+        Self::serialize_from_iter(
+            Filtered {
+                iter: src.iter(),
+                pred: |(key, value): &(&KU, &VU)| pred(key, value),
+                remaining,
+            },
+            load_factor,
+            serializer,
+        )
+    }
+
+    /// Resolves an archived hash map from the entries of an archived hash map
+    /// which match a predicate.
+    pub fn resolve_filtered_from_archived<KU, VU, HU, F>(
+        src: &ArchivedHashMap<KU, VU, HU>,
+        pred: F,
+        load_factor: (usize, usize),
+        resolver: HashMapResolver,
+        out: Place<Self>,
+    ) where
+        F: Fn(&KU, &VU) -> bool,
+    {
+        let len = count_filtered(src, &pred);
+        Self::resolve_from_len(len, load_factor, resolver, out);
+    }
+
     /// Resolves an archived hash map from a given length and parameters.
     pub fn resolve_from_len(
         len: usize,
@@ -240,6 +551,273 @@ impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
     }
 }
 
+fn count_filtered<K, V, H, F>(map: &ArchivedHashMap<K, V, H>, pred: &F) -> usize
+where
+    F: Fn(&K, &V) -> bool,
+{
+    map.iter().filter(|(key, value)| pred(key, value)).count()
+}
+
+/// An iterator which yields the items matching a predicate and knows how many
+/// of them there are.
+struct Filtered<I, F> {
+    iter: I,
+    pred: F,
+    remaining: usize,
+}
+
+impl<I, F> Iterator for Filtered<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.find(&mut self.pred)?;
+        // A predicate which is not pure may match more items than were
+        // counted, so the count saturates instead of underflowing.
+        self.remaining = self.remaining.saturating_sub(1);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<I, F> ExactSizeIterator for Filtered<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> bool,
+{
+}
+
+/// An entry adapter which owns values that borrow as its key and value.
+struct BorrowEntryAdapter<BKU, BVU, KU, VU> {
+    key: BKU,
+    value: BVU,
+    _phantom: PhantomData<(fn() -> KU, fn() -> VU)>,
+}
+
+impl<BKU, BVU, KU, VU> BorrowEntryAdapter<BKU, BVU, KU, VU> {
+    fn new(key: BKU, value: BVU) -> Self {
+        Self {
+            key,
+            value,
+            _phantom: PhantomData,
+        }
+    }
+
+    fn as_entry(&self) -> EntryAdapter<'_, KU, VU>
+    where
+        BKU: Borrow<KU>,
+        BVU: Borrow<VU>,
+    {
+        EntryAdapter {
+            key: self.key.borrow(),
+            value: self.value.borrow(),
+        }
+    }
+}
+
+impl<BKU, BVU, KU, VU> Archive for BorrowEntryAdapter<BKU, BVU, KU, VU>
+where
+    BKU: Borrow<KU>,
+    BVU: Borrow<VU>,
+    KU: Archive,
+    VU: Archive,
+{
+    type Archived = Entry<KU::Archived, VU::Archived>;
+    type Resolver = EntryResolver<KU::Resolver, VU::Resolver>;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        self.as_entry().resolve(resolver, out);
+    }
+}
+
+impl<BKU, BVU, KU, VU, S> Serialize<S> for BorrowEntryAdapter<BKU, BVU, KU, VU>
+where
+    BKU: Borrow<KU>,
+    BVU: Borrow<VU>,
+    KU: Serialize<S>,
+    VU: Serialize<S>,
+    S: Fallible + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        self.as_entry().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
+    /// Serializes a `HashMap` as an archived hash map with the
+    /// [default load factor](DEFAULT_LOAD_FACTOR).
+    ///
+    /// The archived hash map must be resolved with
+    /// [`resolve_from_map`](Self::resolve_from_map).
+    ///
+    /// # Example
+    ///
+    /// An archived hash map can be built without deriving `Archive`:
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use rkyv::{
+    ///     collections::swiss_table::{ArchivedHashMap, HashMapResolver},
+    ///     rancor::{Error, Fallible, Source},
+    ///     ser::{Allocator, Writer},
+    ///     string::ArchivedString,
+    ///     Archive, Archived, Place, Serialize,
+    /// };
+    ///
+    /// struct Scores(HashMap<String, u32>);
+    ///
+    /// impl Archive for Scores {
+    ///     type Archived = ArchivedHashMap<ArchivedString, Archived<u32>>;
+    ///     type Resolver = HashMapResolver;
+    ///
+    ///     fn resolve(
+    ///         &self,
+    ///         resolver: Self::Resolver,
+    ///         out: Place<Self::Archived>,
+    ///     ) {
+    ///         ArchivedHashMap::resolve_from_map(&self.0, resolver, out);
+    ///     }
+    /// }
+    ///
+    /// impl<S> Serialize<S> for Scores
+    /// where
+    ///     S: Fallible + Allocator + Writer + ?Sized,
+    ///     S::Error: Source,
+    /// {
+    ///     fn serialize(
+    ///         &self,
+    ///         serializer: &mut S,
+    ///     ) -> Result<Self::Resolver, S::Error> {
+    ///         ArchivedHashMap::serialize_from_map(&self.0, serializer)
+    ///     }
+    /// }
+    ///
+    /// let mut scores = HashMap::new();
+    /// scores.insert("alice".to_string(), 10);
+    /// scores.insert("bob".to_string(), 20);
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&Scores(scores)).unwrap();
+    /// let archived =
+    ///     unsafe { rkyv::access_unchecked::<Archived<Scores>>(&bytes) };
+    /// assert_eq!(archived.len(), 2);
+    /// assert_eq!(archived.get("alice").map(|v| v.to_native()), Some(10));
+    /// assert_eq!(archived.get("bob").map(|v| v.to_native()), Some(20));
+    /// ```
+    pub fn serialize_from_map<KU, VU, RS, S>(
+        map: &HashMap<KU, VU, RS>,
+        serializer: &mut S,
+    ) -> Result<HashMapResolver, S::Error>
+    where
+        KU: Serialize<S, Archived = K> + Hash + Eq,
+        VU: Serialize<S, Archived = V>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        Self::serialize_from_iter(map.iter(), DEFAULT_LOAD_FACTOR, serializer)
+    }
+
+    /// Resolves an archived hash map from a `HashMap` with the
+    /// [default load factor](DEFAULT_LOAD_FACTOR).
+    pub fn resolve_from_map<KU, VU, RS>(
+        map: &HashMap<KU, VU, RS>,
+        resolver: HashMapResolver,
+        out: Place<Self>,
+    ) {
+        Self::resolve_from_len(map.len(), DEFAULT_LOAD_FACTOR, resolver, out);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, V, H: Hasher + Default> ArchivedHashMap<K, V, H> {
+    /// Serializes a `BTreeMap` as an archived hash map with the
+    /// [default load factor](DEFAULT_LOAD_FACTOR).
+    ///
+    /// The archived hash map must be resolved with
+    /// [`resolve_from_btree`](Self::resolve_from_btree).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    ///
+    /// use rkyv::{
+    ///     collections::swiss_table::{ArchivedHashMap, HashMapResolver},
+    ///     rancor::{Error, Fallible, Source},
+    ///     ser::{Allocator, Writer},
+    ///     Archive, Archived, Place, Serialize,
+    /// };
+    ///
+    /// struct Lookup(BTreeMap<u32, u64>);
+    ///
+    /// impl Archive for Lookup {
+    ///     type Archived = ArchivedHashMap<Archived<u32>, Archived<u64>>;
+    ///     type Resolver = HashMapResolver;
+    ///
+    ///     fn resolve(
+    ///         &self,
+    ///         resolver: Self::Resolver,
+    ///         out: Place<Self::Archived>,
+    ///     ) {
+    ///         ArchivedHashMap::resolve_from_btree(&self.0, resolver, out);
+    ///     }
+    /// }
+    ///
+    /// impl<S> Serialize<S> for Lookup
+    /// where
+    ///     S: Fallible + Allocator + Writer + ?Sized,
+    ///     S::Error: Source,
+    /// {
+    ///     fn serialize(
+    ///         &self,
+    ///         serializer: &mut S,
+    ///     ) -> Result<Self::Resolver, S::Error> {
+    ///         ArchivedHashMap::serialize_from_btree(&self.0, serializer)
+    ///     }
+    /// }
+    ///
+    /// let lookup = Lookup((0..10).map(|i| (i, u64::from(i) * 100)).collect());
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&lookup).unwrap();
+    /// let archived =
+    ///     unsafe { rkyv::access_unchecked::<Archived<Lookup>>(&bytes) };
+    /// assert_eq!(archived.len(), 10);
+    /// assert_eq!(archived.get(&7u32).map(|v| v.to_native()), Some(700));
+    /// ```
+    pub fn serialize_from_btree<KU, VU, S>(
+        map: &BTreeMap<KU, VU>,
+        serializer: &mut S,
+    ) -> Result<HashMapResolver, S::Error>
+    where
+        KU: Serialize<S, Archived = K> + Hash + Eq,
+        VU: Serialize<S, Archived = V>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        Self::serialize_from_iter(map.iter(), DEFAULT_LOAD_FACTOR, serializer)
+    }
+
+    /// Resolves an archived hash map from a `BTreeMap` with the
+    /// [default load factor](DEFAULT_LOAD_FACTOR).
+    pub fn resolve_from_btree<KU, VU>(
+        map: &BTreeMap<KU, VU>,
+        resolver: HashMapResolver,
+        out: Place<Self>,
+    ) {
+        Self::resolve_from_len(map.len(), DEFAULT_LOAD_FACTOR, resolver, out);
+    }
+}
+
 impl<K, V, H> fmt::Debug for ArchivedHashMap<K, V, H>
 where
     K: fmt::Debug,
@@ -258,13 +836,14 @@ where
 {
 }
 
-impl<K, V, H> PartialEq for ArchivedHashMap<K, V, H>
+impl<K, V, U, H> PartialEq<ArchivedHashMap<K, U, H>>
+    for ArchivedHashMap<K, V, H>
 where
     K: Hash + Eq,
-    V: PartialEq,
+    V: PartialEq<U>,
     H: Default + Hasher,
 {
-    fn eq(&self, other: &Self) -> bool {
+    fn eq(&self, other: &ArchivedHashMap<K, U, H>) -> bool {
         if self.len() != other.len() {
             false
         } else {
@@ -277,8 +856,7 @@ where
 
 impl<K, Q, V, H> Index<&'_ Q> for ArchivedHashMap<K, V, H>
 where
-    K: Eq + Hash + Borrow<Q>,
-    Q: Eq + Hash + ?Sized,
+    Q: LookupKey<K> + ?Sized,
     H: Default + Hasher,
 {
     type Output = V;
@@ -288,6 +866,15 @@ where
     }
 }
 
+impl<'a, K, V, H> IntoIterator for &'a ArchivedHashMap<K, V, H> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 /// The resolver for [`ArchivedHashMap`].
 pub struct HashMapResolver(HashTableResolver);
 
@@ -297,6 +884,22 @@ pub struct Iter<'a, K, V, H> {
     _phantom: PhantomData<&'a ArchivedHashMap<K, V, H>>,
 }
 
+// SAFETY: `Iter` behaves like a `&'a ArchivedHashMap<K, V, H>` and only
+// yields shared references to entries.
+unsafe impl<K: Sync, V: Sync, H> Send for Iter<'_, K, V, H> {}
+
+// SAFETY: See the `Send` impl above.
+unsafe impl<K: Sync, V: Sync, H> Sync for Iter<'_, K, V, H> {}
+
+impl<K, V, H> Clone for Iter<'_, K, V, H> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<'a, K, V, H> Iterator for Iter<'a, K, V, H> {
     type Item = (&'a K, &'a V);
 
@@ -306,6 +909,10 @@ impl<'a, K, V, H> Iterator for Iter<'a, K, V, H> {
             (&entry.key, &entry.value)
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.raw.size_hint()
+    }
 }
 
 impl<K, V, H> ExactSizeIterator for Iter<'_, K, V, H> {
@@ -316,12 +923,28 @@ impl<K, V, H> ExactSizeIterator for Iter<'_, K, V, H> {
 
 impl<K, V, H> FusedIterator for Iter<'_, K, V, H> {}
 
+/// A parallel iterator over the key-value pairs of an [`ArchivedHashMap`].
+///
+/// The buckets of the hash map are split between threads, so the key-value
+/// pairs are yielded in an arbitrary order.
+#[cfg(feature = "rayon")]
+pub struct ParIter<'a, K, V, H> {
+    pub(crate) map: &'a ArchivedHashMap<K, V, H>,
+}
+
 /// An iterator over the mutable key-value pairs of an [`ArchivedHashMap`].
 pub struct IterMut<'a, K, V, H> {
     raw: RawIter<Entry<K, V>>,
-    _phantom: PhantomData<&'a ArchivedHashMap<K, V, H>>,
+    _phantom: PhantomData<&'a mut ArchivedHashMap<K, V, H>>,
 }
 
+// SAFETY: `IterMut` behaves like a `&'a mut ArchivedHashMap<K, V, H>`. It
+// yields shared references to keys and mutable references to values.
+unsafe impl<K: Sync, V: Send, H> Send for IterMut<'_, K, V, H> {}
+
+// SAFETY: A shared `IterMut` does not provide access to any entries.
+unsafe impl<K: Sync, V: Sync, H> Sync for IterMut<'_, K, V, H> {}
+
 impl<'a, K, V, H> Iterator for IterMut<'a, K, V, H> {
     type Item = (&'a K, Pin<&'a mut V>);
 
@@ -332,6 +955,10 @@ impl<'a, K, V, H> Iterator for IterMut<'a, K, V, H> {
             (&entry.key, value)
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.raw.size_hint()
+    }
 }
 
 impl<K, V, H> ExactSizeIterator for IterMut<'_, K, V, H> {
@@ -348,6 +975,22 @@ pub struct Keys<'a, K, V, H> {
     _phantom: PhantomData<&'a ArchivedHashMap<K, V, H>>,
 }
 
+// SAFETY: `Keys` behaves like a `&'a ArchivedHashMap<K, V, H>` and only
+// yields shared references to entries.
+unsafe impl<K: Sync, V: Sync, H> Send for Keys<'_, K, V, H> {}
+
+// SAFETY: See the `Send` impl above.
+unsafe impl<K: Sync, V: Sync, H> Sync for Keys<'_, K, V, H> {}
+
+impl<K, V, H> Clone for Keys<'_, K, V, H> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<'a, K, V, H> Iterator for Keys<'a, K, V, H> {
     type Item = &'a K;
 
@@ -357,6 +1000,10 @@ impl<'a, K, V, H> Iterator for Keys<'a, K, V, H> {
             &entry.key
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.raw.size_hint()
+    }
 }
 
 impl<K, V, H> ExactSizeIterator for Keys<'_, K, V, H> {
@@ -373,6 +1020,22 @@ pub struct Values<'a, K, V, H> {
     _phantom: PhantomData<&'a ArchivedHashMap<K, V, H>>,
 }
 
+// SAFETY: `Values` behaves like a `&'a ArchivedHashMap<K, V, H>` and only
+// yields shared references to entries.
+unsafe impl<K: Sync, V: Sync, H> Send for Values<'_, K, V, H> {}
+
+// SAFETY: See the `Send` impl above.
+unsafe impl<K: Sync, V: Sync, H> Sync for Values<'_, K, V, H> {}
+
+impl<K, V, H> Clone for Values<'_, K, V, H> {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
 impl<'a, K, V, H> Iterator for Values<'a, K, V, H> {
     type Item = &'a V;
 
@@ -382,6 +1045,10 @@ impl<'a, K, V, H> Iterator for Values<'a, K, V, H> {
             &entry.value
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.raw.size_hint()
+    }
 }
 
 impl<K, V, H> ExactSizeIterator for Values<'_, K, V, H> {
@@ -395,9 +1062,16 @@ impl<K, V, H> FusedIterator for Values<'_, K, V, H> {}
 /// An iterator over the mutable values of an [`ArchivedHashMap`].
 pub struct ValuesMut<'a, K, V, H> {
     raw: RawIter<Entry<K, V>>,
-    _phantom: PhantomData<&'a ArchivedHashMap<K, V, H>>,
+    _phantom: PhantomData<&'a mut ArchivedHashMap<K, V, H>>,
 }
 
+// SAFETY: `ValuesMut` behaves like a `&'a mut ArchivedHashMap<K, V, H>`. It
+// yields shared references to keys and mutable references to values.
+unsafe impl<K: Sync, V: Send, H> Send for ValuesMut<'_, K, V, H> {}
+
+// SAFETY: A shared `ValuesMut` does not provide access to any entries.
+unsafe impl<K: Sync, V: Sync, H> Sync for ValuesMut<'_, K, V, H> {}
+
 impl<'a, K, V, H> Iterator for ValuesMut<'a, K, V, H> {
     type Item = Pin<&'a mut V>;
 
@@ -407,6 +1081,10 @@ impl<'a, K, V, H> Iterator for ValuesMut<'a, K, V, H> {
             unsafe { Pin::new_unchecked(&mut entry.value) }
         })
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.raw.size_hint()
+    }
 }
 
 impl<K, V, H> ExactSizeIterator for ValuesMut<'_, K, V, H> {
@@ -416,3 +1094,123 @@ impl<K, V, H> ExactSizeIterator for ValuesMut<'_, K, V, H> {
 }
 
 impl<K, V, H> FusedIterator for ValuesMut<'_, K, V, H> {}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::hash::{Hash, Hasher};
+
+    use bytecheck::Verify;
+    use rancor::{Fallible, Source};
+
+    use super::ArchivedHashMap;
+    use crate::{hash::hash_value_with, validation::ArchiveContext};
+
+    // Deserializing a hash map with duplicate keys would silently drop all but
+    // one of the entries, and lookups would only ever find one of them. Every
+    // key is looked up to make sure that it finds its own entry.
+    unsafe impl<C, K, V, H> Verify<C> for ArchivedHashMap<K, V, H>
+    where
+        C: Fallible + ArchiveContext + ?Sized,
+        C::Error: Source,
+        K: Hash + Eq,
+        H: Hasher + Default,
+    {
+        fn verify(&self, context: &mut C) -> Result<(), C::Error> {
+            if !context.check_duplicate_keys() {
+                return Ok(());
+            }
+
+            // SAFETY: The table is a field of the map, and so has already been
+            // checked.
+            unsafe {
+                self.table.check_lookups(
+                    |entry| hash_value_with::<K, H>(&entry.key),
+                    |a, b| a.key == b.key,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{format, string::String, vec::Vec};
+
+    use rancor::{Fallible, Source};
+
+    use super::{ArchivedHashMap, HashMapResolver, DEFAULT_LOAD_FACTOR};
+    use crate::{
+        ser::{Allocator, Deferred, Writer},
+        string::ArchivedString,
+        test::{access, to_bytes},
+        Archive, Archived, Place, Serialize,
+    };
+
+    struct ValuesLast(Vec<(u32, String)>);
+
+    impl Archive for ValuesLast {
+        type Archived = ArchivedHashMap<Archived<u32>, ArchivedString>;
+        type Resolver = HashMapResolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            ArchivedHashMap::resolve_from_len(
+                self.0.len(),
+                DEFAULT_LOAD_FACTOR,
+                resolver,
+                out,
+            );
+        }
+    }
+
+    impl<S> Serialize<S> for ValuesLast
+    where
+        S: Fallible + Allocator + Writer + ?Sized,
+        S::Error: Source,
+        String: for<'s> Serialize<Deferred<'s, S>>,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedHashMap::serialize_from_iter_values_last(
+                self.0.iter().map(|(key, value)| (key, value)),
+                DEFAULT_LOAD_FACTOR,
+                serializer,
+            )
+        }
+    }
+
+    #[test]
+    fn values_after_table() {
+        let value = ValuesLast(
+            (0..32)
+                .map(|i| (i, format!("the value for key {i}, stored last")))
+                .collect(),
+        );
+
+        to_bytes(&value, |bytes| {
+            access::<Archived<ValuesLast>>(bytes, |map| {
+                let start = bytes.as_ptr() as usize;
+                let table_end = map
+                    .iter()
+                    .map(|(key, _)| key as *const _ as usize - start)
+                    .max()
+                    .unwrap();
+
+                assert_eq!(map.len(), value.0.len());
+                for (key, expected) in value.0.iter() {
+                    let archived = map.get(key).unwrap();
+                    assert_eq!(archived, expected);
+
+                    let data = archived.as_str().as_ptr() as usize - start;
+                    assert!(data > table_end);
+                }
+            });
+        });
+    }
+}
@@ -3,11 +3,13 @@
 pub mod index_map;
 pub mod index_set;
 pub mod map;
+pub mod ordered_map;
 pub mod set;
 pub mod table;
 
 pub use index_map::{ArchivedIndexMap, IndexMapResolver};
 pub use index_set::{ArchivedIndexSet, IndexSetResolver};
-pub use map::{ArchivedHashMap, HashMapResolver};
+pub use map::{ArchivedHashMap, HashMapResolver, DEFAULT_LOAD_FACTOR};
+pub use ordered_map::{ArchivedOrderedHashMap, OrderedHashMapResolver};
 pub use set::{ArchivedHashSet, HashSetResolver};
 pub use table::{ArchivedHashTable, HashTableResolver};
@@ -0,0 +1,471 @@
+//! An archived hash map which remembers the order its entries were serialized
+//! in.
+
+use core::{
+    fmt,
+    hash::{Hash, Hasher},
+    iter::FusedIterator,
+    ops::Deref,
+    slice::{self, from_raw_parts},
+};
+
+use munge::munge;
+use rancor::{fail, Fallible, Source};
+
+use crate::{
+    collections::{
+        swiss_table::{map::HashMapResolver, ArchivedHashMap},
+        util::Entry,
+    },
+    hash::FxHasher64,
+    primitive::ArchivedU32,
+    ser::{Allocator, Writer, WriterExt as _},
+    util::SerVec,
+    Place, Portable, RelPtr, Serialize,
+};
+
+/// An archived SwissTable hash map which can be iterated in the order that its
+/// entries were serialized in.
+///
+/// The map has the same layout as an [`ArchivedHashMap`] followed by a pointer
+/// to a permutation array. The permutation array holds one `u32` for each
+/// entry: the index of the bucket that the entry was inserted into. This is
+/// much smaller than the entries array of an
+/// [`ArchivedIndexMap`](crate::collections::swiss_table::ArchivedIndexMap),
+/// but only supports iterating in input order. Indexing by position is not
+/// supported.
+///
+/// The map dereferences to its [`ArchivedHashMap`], so lookups and
+/// [`iter`](ArchivedHashMap::iter) behave exactly as they do for a hash map.
+/// Use [`iter_in_input_order`](Self::iter_in_input_order) to iterate in input
+/// order.
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(C)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+pub struct ArchivedOrderedHashMap<K, V, H = FxHasher64> {
+    map: ArchivedHashMap<K, V, H>,
+    order: RelPtr<ArchivedU32>,
+}
+
+impl<K, V, H> ArchivedOrderedHashMap<K, V, H> {
+    /// Returns the underlying hash map.
+    pub fn as_hash_map(&self) -> &ArchivedHashMap<K, V, H> {
+        &self.map
+    }
+
+    fn order(&self) -> &[ArchivedU32] {
+        if self.map.is_empty() {
+            &[]
+        } else {
+            unsafe { from_raw_parts(self.order.as_ptr(), self.map.len()) }
+        }
+    }
+
+    /// Returns an iterator over the key-value pairs of the map in the order
+    /// they were serialized in.
+    pub fn iter_in_input_order(&self) -> InputOrderIter<'_, K, V, H> {
+        InputOrderIter {
+            map: &self.map,
+            order: self.order().iter(),
+        }
+    }
+
+    /// Resolves an archived ordered hash map from a given length and
+    /// parameters.
+    pub fn resolve_from_len(
+        len: usize,
+        load_factor: (usize, usize),
+        resolver: OrderedHashMapResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedOrderedHashMap { map, order } = out);
+        ArchivedHashMap::resolve_from_len(
+            len,
+            load_factor,
+            resolver.map_resolver,
+            map,
+        );
+        if len == 0 {
+            RelPtr::emplace_invalid(order);
+        } else {
+            RelPtr::emplace(resolver.order_pos, order);
+        }
+    }
+}
+
+impl<K, V, H: Hasher + Default> ArchivedOrderedHashMap<K, V, H> {
+    /// Serializes an iterator of key-value pairs as an ordered hash map.
+    ///
+    /// The hash map is serialized exactly as
+    /// [`ArchivedHashMap::serialize_from_iter`] would serialize it, and is
+    /// followed by a permutation array which records the input order.
+    pub fn serialize_from_iter_ordered<'a, I, KU, VU, S>(
+        iter: I,
+        load_factor: (usize, usize),
+        serializer: &mut S,
+    ) -> Result<OrderedHashMapResolver, S::Error>
+    where
+        I: ExactSizeIterator<Item = (&'a KU, &'a VU)>,
+        KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
+        VU: 'a + Serialize<S, Archived = V>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        #[derive(Debug)]
+        struct TooManyBuckets {
+            index: usize,
+        }
+
+        impl fmt::Display for TooManyBuckets {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "bucket index {} is too large to store in the permutation \
+                     array of an ordered hash map",
+                    self.index,
+                )
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl std::error::Error for TooManyBuckets {}
+
+        SerVec::with_capacity(serializer, iter.len(), |buckets, serializer| {
+            let map_resolver =
+                ArchivedHashMap::<K, V, H>::serialize_from_iter_with_buckets(
+                    iter,
+                    load_factor,
+                    serializer,
+                    |index| buckets.push(index),
+                )?;
+
+            let order_pos = serializer.align_for::<ArchivedU32>()?;
+            for &index in buckets.as_slice() {
+                let Ok(index) = u32::try_from(index) else {
+                    fail!(TooManyBuckets { index });
+                };
+                unsafe {
+                    serializer.resolve_aligned(&index, ())?;
+                }
+            }
+
+            Ok(OrderedHashMapResolver {
+                map_resolver,
+                order_pos,
+            })
+        })?
+    }
+}
+
+impl<K, V, H> Deref for ArchivedOrderedHashMap<K, V, H> {
+    type Target = ArchivedHashMap<K, V, H>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.map
+    }
+}
+
+impl<K, V, H> fmt::Debug for ArchivedOrderedHashMap<K, V, H>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter_in_input_order()).finish()
+    }
+}
+
+impl<'a, K, V, H> IntoIterator for &'a ArchivedOrderedHashMap<K, V, H> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = InputOrderIter<'a, K, V, H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_in_input_order()
+    }
+}
+
+/// The resolver for [`ArchivedOrderedHashMap`].
+pub struct OrderedHashMapResolver {
+    map_resolver: HashMapResolver,
+    order_pos: usize,
+}
+
+/// An iterator over the key-value pairs of an [`ArchivedOrderedHashMap`] in
+/// the order they were serialized in.
+pub struct InputOrderIter<'a, K, V, H> {
+    map: &'a ArchivedHashMap<K, V, H>,
+    order: slice::Iter<'a, ArchivedU32>,
+}
+
+impl<K, V, H> Clone for InputOrderIter<'_, K, V, H> {
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map,
+            order: self.order.clone(),
+        }
+    }
+}
+
+impl<'a, K, V, H> Iterator for InputOrderIter<'a, K, V, H> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.order.next()?.to_native() as usize;
+        let entry: &Entry<K, V> = self.map.raw_table().bucket(index)?;
+        Some((&entry.key, &entry.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.order.size_hint()
+    }
+}
+
+impl<K, V, H> ExactSizeIterator for InputOrderIter<'_, K, V, H> {
+    fn len(&self) -> usize {
+        self.order.len()
+    }
+}
+
+impl<K, V, H> FusedIterator for InputOrderIter<'_, K, V, H> {}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::fmt;
+
+    use bytecheck::{CheckBytes, Verify};
+    use rancor::{fail, Fallible, Source};
+
+    use super::ArchivedOrderedHashMap;
+    use crate::{
+        primitive::ArchivedU32,
+        validation::{ArchiveContext, ArchiveContextExt},
+    };
+
+    #[derive(Debug)]
+    struct InvalidOrderIndex {
+        index: usize,
+    }
+
+    impl fmt::Display for InvalidOrderIndex {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "permutation array of ordered hash map refers to bucket {}, \
+                 which is not full",
+                self.index,
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for InvalidOrderIndex {}
+
+    // Every index in the permutation array must refer to a full bucket. The
+    // array is not checked for repeated indices, since iterating in input
+    // order only hands out shared references.
+    unsafe impl<C, K, V, H> Verify<C> for ArchivedOrderedHashMap<K, V, H>
+    where
+        C: Fallible + ArchiveContext + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, context: &mut C) -> Result<(), C::Error> {
+            if self.map.is_empty() {
+                return Ok(());
+            }
+
+            let ptr = core::ptr::slice_from_raw_parts(
+                self.order.as_ptr_wrapping(),
+                self.map.len(),
+            );
+
+            context.in_subtree(ptr, |context| {
+                // SAFETY: `in_subtree` has checked that `ptr` is aligned and
+                // points to enough bytes to represent its slice.
+                unsafe { <[ArchivedU32]>::check_bytes(ptr, context) }
+            })?;
+
+            let table = self.map.raw_table();
+            for index in self.order() {
+                let index = index.to_native() as usize;
+                if table.bucket(index).is_none() {
+                    fail!(InvalidOrderIndex { index });
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec::Vec,
+    };
+    use core::hash::Hasher;
+
+    use rancor::{Fallible, Source};
+
+    use super::{ArchivedOrderedHashMap, OrderedHashMapResolver};
+    use crate::{
+        hash::FxHasher64,
+        primitive::ArchivedU32,
+        ser::{Allocator, Writer},
+        string::ArchivedString,
+        test::to_archived,
+        Archive, Place, Serialize,
+    };
+
+    /// Hashes every key to the same value, so every entry collides.
+    #[derive(Default)]
+    struct Colliding;
+
+    impl Hasher for Colliding {
+        fn finish(&self) -> u64 {
+            0
+        }
+
+        fn write(&mut self, _: &[u8]) {}
+    }
+
+    struct Entries<H> {
+        entries: Vec<(u32, String)>,
+        _phantom: core::marker::PhantomData<H>,
+    }
+
+    impl<H> Entries<H> {
+        fn new(entries: Vec<(u32, String)>) -> Self {
+            Self {
+                entries,
+                _phantom: core::marker::PhantomData,
+            }
+        }
+    }
+
+    impl<H> Archive for Entries<H> {
+        type Archived = ArchivedOrderedHashMap<ArchivedU32, ArchivedString, H>;
+        type Resolver = OrderedHashMapResolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            ArchivedOrderedHashMap::resolve_from_len(
+                self.entries.len(),
+                (7, 8),
+                resolver,
+                out,
+            );
+        }
+    }
+
+    impl<H, S> Serialize<S> for Entries<H>
+    where
+        H: Hasher + Default,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedOrderedHashMap::<_, _, H>::serialize_from_iter_ordered(
+                self.entries.iter().map(|(k, v)| (k, v)),
+                (7, 8),
+                serializer,
+            )
+        }
+    }
+
+    fn check_input_order<H: Hasher + Default>(keys: &[u32]) {
+        let value = Entries::<H>::new(
+            keys.iter().map(|&k| (k, k.to_string())).collect(),
+        );
+        to_archived(&value, |archived| {
+            assert_eq!(archived.len(), keys.len());
+            let ordered = archived
+                .iter_in_input_order()
+                .map(|(k, v)| (k.to_native(), v.as_str()))
+                .collect::<Vec<_>>();
+            let expected = value
+                .entries
+                .iter()
+                .map(|(k, v)| (*k, v.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(ordered, expected);
+            assert_eq!(archived.iter_in_input_order().len(), keys.len());
+
+            // Lookups and unordered iteration are unchanged.
+            assert_eq!(archived.iter().count(), keys.len());
+            for (key, value) in value.entries.iter() {
+                let archived_value = archived.get(&ArchivedU32::from(*key));
+                assert_eq!(archived_value.unwrap().as_str(), value);
+            }
+        });
+    }
+
+    #[test]
+    fn empty_input_order() {
+        check_input_order::<FxHasher64>(&[]);
+    }
+
+    #[test]
+    fn input_order_non_colliding() {
+        check_input_order::<FxHasher64>(&[5, 3, 9, 1, 7, 2, 8, 4, 6, 0]);
+        check_input_order::<FxHasher64>(
+            &(0..200).rev().chain(200..400).collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn input_order_colliding() {
+        check_input_order::<Colliding>(&[5, 3, 9, 1, 7, 2, 8, 4, 6, 0]);
+        check_input_order::<Colliding>(&(0..100).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn debug_in_input_order() {
+        let value = Entries::<FxHasher64>::new(
+            [3, 1, 2].iter().map(|&k| (k, k.to_string())).collect(),
+        );
+        to_archived(&value, |archived| {
+            assert_eq!(
+                format!("{:?}", archived),
+                r#"{3: "3", 1: "1", 2: "2"}"#,
+            );
+        });
+    }
+
+    #[cfg(feature = "bytecheck")]
+    #[test]
+    fn reject_invalid_order_index() {
+        use rancor::Error;
+
+        use crate::access;
+
+        type Archived = ArchivedOrderedHashMap<ArchivedU32, ArchivedString>;
+
+        let value = Entries::<FxHasher64>::new(
+            [3, 1, 2].iter().map(|&k| (k, k.to_string())).collect(),
+        );
+        let mut bytes = crate::to_bytes::<Error>(&value).unwrap();
+        access::<Archived, Error>(&bytes).unwrap();
+
+        // The permutation array is written right before the root.
+        let root = bytes.len() - core::mem::size_of::<Archived>();
+        let last = root - core::mem::size_of::<ArchivedU32>();
+        bytes[last..root].copy_from_slice(&u32::MAX.to_le_bytes());
+
+        let err = access::<Archived, Error>(&bytes).unwrap_err();
+        assert!(err.to_string().contains("not full"));
+    }
+}
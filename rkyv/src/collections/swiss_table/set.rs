@@ -1,17 +1,28 @@
 //! Archived hash set implementation using an archived SwissTable.
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::collections::BTreeSet;
 use core::{
-    borrow::Borrow,
     fmt,
     hash::{Hash, Hasher},
 };
+#[cfg(feature = "std")]
+use std::collections::{BTreeSet, HashSet};
 
 use munge::munge;
 use rancor::{Fallible, Source};
 
 use crate::{
-    collections::swiss_table::map::{ArchivedHashMap, HashMapResolver, Keys},
-    hash::FxHasher64,
+    collections::{
+        swiss_table::{
+            map::{
+                ArchivedHashMap, HashMapResolver, Keys, DEFAULT_LOAD_FACTOR,
+            },
+            ArchivedHashTable,
+        },
+        util::Entry,
+    },
+    hash::{FxHasher64, LookupKey},
     ser::{Allocator, Writer},
     Place, Portable, Serialize,
 };
@@ -37,18 +48,43 @@ impl<K, H> ArchivedHashSet<K, H> {
         self.inner.is_empty()
     }
 
-    /// Gets an iterator over the keys of the underlying hash map.
+    /// Returns the underlying hash table of the hash set.
+    ///
+    /// This can be used to inspect the probe statistics of the hash set.
+    pub fn raw_table(&self) -> &ArchivedHashTable<Entry<K, ()>> {
+        self.inner.raw_table()
+    }
+
+    /// Gets an iterator over the keys of the hash set.
+    ///
+    /// The iterator yields references to the archived keys in the order of
+    /// their buckets, which is unspecified. The references point into the
+    /// archive.
     pub fn iter(&self) -> Keys<K, (), H> {
         self.inner.keys()
     }
 }
 
 impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
-    /// Gets the key corresponding to the given key in the hash set.
+    /// Gets the key stored in the hash set which is equal to the given key
+    /// using the given comparison function.
+    pub fn get_with<Q, C>(&self, k: &Q, cmp: C) -> Option<&K>
+    where
+        Q: Hash + ?Sized,
+        C: Fn(&Q, &K) -> bool,
+    {
+        self.inner.get_key_value_with(k, cmp).map(|(k, _)| k)
+    }
+
+    /// Gets the key stored in the hash set which is equal to the given key.
+    ///
+    /// The returned reference points to the key in the archive rather than to
+    /// the given key, so it can be used to canonicalize or intern values. The
+    /// key may be an unarchived version of the key type, such as a `str` for
+    /// `ArchivedString` keys. See [`LookupKey`] for more details.
     pub fn get<Q>(&self, k: &Q) -> Option<&K>
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         self.inner.get_key_value(k).map(|(k, _)| k)
     }
@@ -56,8 +92,7 @@ impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
     /// Returns whether the given key is in the hash set.
     pub fn contains<Q>(&self, k: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Hash + Eq + ?Sized,
+        Q: LookupKey<K> + ?Sized,
     {
         self.inner.contains_key(k)
     }
@@ -83,7 +118,7 @@ impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
         KU: 'a + Serialize<S, Archived = K> + Hash + Eq,
         S: Fallible + Writer + Allocator + ?Sized,
         S::Error: Source,
-        I: Clone + ExactSizeIterator<Item = &'a KU>,
+        I: ExactSizeIterator<Item = &'a KU>,
     {
         Ok(HashSetResolver(
             ArchivedHashMap::<K, (), H>::serialize_from_iter(
@@ -93,6 +128,171 @@ impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
             )?,
         ))
     }
+
+    /// Serializes the keys of an archived hash set which match a predicate as
+    /// a new hash set.
+    ///
+    /// The keys are serialized from their archived forms, so the archived key
+    /// type must implement `Serialize`. The keys must hash the same as the keys
+    /// they were archived from.
+    ///
+    /// The archived hash set must be resolved with
+    /// [`resolve_filtered_from_archived`](Self::resolve_filtered_from_archived)
+    /// using the same predicate and load factor.
+    pub fn serialize_filtered_from_archived<KU, HU, F, S>(
+        src: &ArchivedHashSet<KU, HU>,
+        pred: F,
+        load_factor: (usize, usize),
+        serializer: &mut S,
+    ) -> Result<HashSetResolver, S::Error>
+    where
+        KU: Serialize<S, Archived = K> + Hash + Eq,
+        F: Fn(&KU) -> bool,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        Ok(HashSetResolver(
+            ArchivedHashMap::<K, (), H>::serialize_filtered_from_archived(
+                &src.inner,
+                |key, _| pred(key),
+                load_factor,
+                serializer,
+            )?,
+        ))
+    }
+
+    /// Resolves an archived hash set from the keys of an archived hash set
+    /// which match a predicate.
+    pub fn resolve_filtered_from_archived<KU, HU, F>(
+        src: &ArchivedHashSet<KU, HU>,
+        pred: F,
+        load_factor: (usize, usize),
+        resolver: HashSetResolver,
+        out: Place<Self>,
+    ) where
+        F: Fn(&KU) -> bool,
+    {
+        munge!(let ArchivedHashSet { inner } = out);
+        ArchivedHashMap::resolve_filtered_from_archived(
+            &src.inner,
+            |key, _| pred(key),
+            load_factor,
+            resolver.0,
+            inner,
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
+    /// Serializes a `HashSet` as an archived hash set with the
+    /// [default load factor](DEFAULT_LOAD_FACTOR).
+    ///
+    /// The archived hash set must be resolved with
+    /// [`resolve_from_set`](Self::resolve_from_set).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    ///
+    /// use rkyv::{
+    ///     collections::swiss_table::{ArchivedHashSet, HashSetResolver},
+    ///     rancor::{Error, Fallible, Source},
+    ///     ser::{Allocator, Writer},
+    ///     string::ArchivedString,
+    ///     Archive, Archived, Place, Serialize,
+    /// };
+    ///
+    /// struct Tags(HashSet<String>);
+    ///
+    /// impl Archive for Tags {
+    ///     type Archived = ArchivedHashSet<ArchivedString>;
+    ///     type Resolver = HashSetResolver;
+    ///
+    ///     fn resolve(
+    ///         &self,
+    ///         resolver: Self::Resolver,
+    ///         out: Place<Self::Archived>,
+    ///     ) {
+    ///         ArchivedHashSet::resolve_from_set(&self.0, resolver, out);
+    ///     }
+    /// }
+    ///
+    /// impl<S> Serialize<S> for Tags
+    /// where
+    ///     S: Fallible + Allocator + Writer + ?Sized,
+    ///     S::Error: Source,
+    /// {
+    ///     fn serialize(
+    ///         &self,
+    ///         serializer: &mut S,
+    ///     ) -> Result<Self::Resolver, S::Error> {
+    ///         ArchivedHashSet::serialize_from_set(&self.0, serializer)
+    ///     }
+    /// }
+    ///
+    /// let tags = ["red", "green"].iter().map(|s| s.to_string()).collect();
+    /// let tags = Tags(tags);
+    ///
+    /// let bytes = rkyv::to_bytes::<Error>(&tags).unwrap();
+    /// let archived = rkyv::access::<Archived<Tags>, Error>(&bytes).unwrap();
+    /// assert_eq!(archived.len(), 2);
+    /// assert!(archived.contains("red"));
+    /// assert!(!archived.contains("blue"));
+    /// ```
+    pub fn serialize_from_set<KU, RS, S>(
+        set: &HashSet<KU, RS>,
+        serializer: &mut S,
+    ) -> Result<HashSetResolver, S::Error>
+    where
+        KU: Serialize<S, Archived = K> + Hash + Eq,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        Self::serialize_from_iter(set.iter(), DEFAULT_LOAD_FACTOR, serializer)
+    }
+
+    /// Resolves an archived hash set from a `HashSet` with the
+    /// [default load factor](DEFAULT_LOAD_FACTOR).
+    pub fn resolve_from_set<KU, RS>(
+        set: &HashSet<KU, RS>,
+        resolver: HashSetResolver,
+        out: Place<Self>,
+    ) {
+        Self::resolve_from_len(set.len(), DEFAULT_LOAD_FACTOR, resolver, out);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K, H: Hasher + Default> ArchivedHashSet<K, H> {
+    /// Serializes a `BTreeSet` as an archived hash set with the
+    /// [default load factor](DEFAULT_LOAD_FACTOR).
+    ///
+    /// The archived hash set must be resolved with
+    /// [`resolve_from_btree`](Self::resolve_from_btree). See
+    /// [`serialize_from_set`](Self::serialize_from_set) for an example.
+    pub fn serialize_from_btree<KU, S>(
+        set: &BTreeSet<KU>,
+        serializer: &mut S,
+    ) -> Result<HashSetResolver, S::Error>
+    where
+        KU: Serialize<S, Archived = K> + Hash + Eq,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        Self::serialize_from_iter(set.iter(), DEFAULT_LOAD_FACTOR, serializer)
+    }
+
+    /// Resolves an archived hash set from a `BTreeSet` with the
+    /// [default load factor](DEFAULT_LOAD_FACTOR).
+    pub fn resolve_from_btree<KU>(
+        set: &BTreeSet<KU>,
+        resolver: HashSetResolver,
+        out: Place<Self>,
+    ) {
+        Self::resolve_from_len(set.len(), DEFAULT_LOAD_FACTOR, resolver, out);
+    }
 }
 
 impl<K: fmt::Debug, H> fmt::Debug for ArchivedHashSet<K, H> {
@@ -109,5 +309,14 @@ impl<K: Hash + Eq, H: Hasher + Default> PartialEq for ArchivedHashSet<K, H> {
 
 impl<K: Hash + Eq, H: Hasher + Default> Eq for ArchivedHashSet<K, H> {}
 
+impl<'a, K, H> IntoIterator for &'a ArchivedHashSet<K, H> {
+    type Item = &'a K;
+    type IntoIter = Keys<'a, K, (), H>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 /// The resolver for archived hash sets.
 pub struct HashSetResolver(HashMapResolver);
@@ -20,26 +20,128 @@
 use core::{
     alloc::Layout,
     fmt,
+    iter::FusedIterator,
     marker::PhantomData,
-    mem::size_of,
+    mem::ManuallyDrop,
     pin::Pin,
     ptr::{self, null, NonNull},
     slice,
 };
 
 use munge::munge;
-use rancor::{fail, Fallible, OptionExt, Panic, ResultExt as _, Source};
+use rancor::{
+    fail, Failure, Fallible, OptionExt, Panic, ResultExt as _, Source,
+};
 
 use crate::{
     collections::util::IteratorLengthMismatch,
-    primitive::ArchivedUsize,
+    primitive::{usize_from_archived, ArchivedUsize},
     ser::{Allocator, Writer, WriterExt},
     simd::{Bitmask, Group, MAX_GROUP_WIDTH},
     util::SerVec,
     Archive as _, Place, Portable, RawRelPtr, Serialize,
 };
+#[cfg(feature = "alloc")]
+use crate::{
+    collections::util::{Entry, EntryAdapter, EntryResolver},
+    ser::Deferred,
+    Archive,
+};
 
 /// A low-level archived SwissTable hash table with explicit hashing.
+///
+/// This is the storage used by the archived hash maps and sets in this module,
+/// and can be used to build other archived collections. The hash table does not
+/// hash its items itself: each item is inserted with a caller-provided hash,
+/// and lookups must provide the same hash along with a function to compare
+/// items. The load factor used to serialize a hash table must also be used to
+/// resolve it.
+///
+/// # Examples
+///
+/// A minimal archived map from `u32` to `String`:
+///
+/// ```
+/// use rkyv::{
+///     collections::{
+///         swiss_table::{ArchivedHashTable, HashTableResolver},
+///         util::{Entry, EntryAdapter},
+///     },
+///     hash::hash_value,
+///     rancor::{Error, Fallible, Source},
+///     ser::{Allocator, Writer},
+///     string::ArchivedString,
+///     Archive, Archived, Place, Serialize,
+/// };
+///
+/// const LOAD_FACTOR: (usize, usize) = (7, 8);
+///
+/// struct SmallMap(Vec<(u32, String)>);
+///
+/// impl Archive for SmallMap {
+///     type Archived = ArchivedHashTable<Entry<Archived<u32>, ArchivedString>>;
+///     type Resolver = HashTableResolver;
+///
+///     fn resolve(
+///         &self,
+///         resolver: Self::Resolver,
+///         out: Place<Self::Archived>,
+///     ) {
+///         ArchivedHashTable::resolve_from_len(
+///             self.0.len(),
+///             LOAD_FACTOR,
+///             resolver,
+///             out,
+///         );
+///     }
+/// }
+///
+/// impl<S> Serialize<S> for SmallMap
+/// where
+///     S: Fallible + Writer + Allocator + ?Sized,
+///     S::Error: Source,
+/// {
+///     fn serialize(
+///         &self,
+///         serializer: &mut S,
+///     ) -> Result<Self::Resolver, S::Error> {
+///         ArchivedHashTable::serialize_from_iter(
+///             self.0.iter().map(|(key, value)| {
+///                 let hash = hash_value::<u32>(key);
+///                 (hash, EntryAdapter { key, value })
+///             }),
+///             LOAD_FACTOR,
+///             serializer,
+///         )
+///     }
+/// }
+///
+/// let value = SmallMap(vec![
+///     (1, "one".to_string()),
+///     (2, "two".to_string()),
+///     (3, "three".to_string()),
+/// ]);
+/// let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+/// let table = rkyv::access::<Archived<SmallMap>, Error>(&bytes).unwrap();
+///
+/// let hash = hash_value::<u32>(&2);
+/// let entry = table.get_with(hash, |e| e.key.to_native() == 2).unwrap();
+/// assert_eq!(entry.value.as_str(), "two");
+/// assert!(table.get_with(hash, |e| e.key.to_native() == 4).is_none());
+///
+/// let full = (0..table.capacity())
+///     .filter_map(|i| table.bucket(i))
+///     .count();
+/// assert_eq!(full, 3);
+///
+/// // SAFETY: The pointers from `raw_iter` are valid while `table` is borrowed.
+/// let mut keys = table
+///     .raw_iter()
+///     .map(|e| unsafe { e.as_ref().key.to_native() })
+///     .collect::<Vec<_>>();
+/// keys.sort();
+/// assert_eq!(keys, [1, 2, 3]);
+/// ```
 #[derive(Portable)]
 #[rkyv(crate)]
 #[repr(C)]
@@ -71,6 +173,14 @@ struct ProbeSeq {
 }
 
 impl ProbeSeq {
+    #[inline]
+    fn new(hash: u64, capacity: usize) -> Self {
+        Self {
+            pos: h1(hash) % capacity,
+            stride: 0,
+        }
+    }
+
     #[inline]
     fn next_group(&mut self) {
         self.pos += Group::WIDTH;
@@ -78,22 +188,282 @@ impl ProbeSeq {
 
     #[inline]
     fn move_next(&mut self, bucket_mask: usize) {
-        loop {
-            self.pos += self.stride;
-            self.pos &= bucket_mask;
-            self.stride += MAX_GROUP_WIDTH;
+        self.pos += self.stride;
+        self.pos &= bucket_mask;
+        self.stride += MAX_GROUP_WIDTH;
+    }
+}
+
+/// Returns one less than `capacity` rounded up to the next power of two.
+///
+/// The capacity comes from the archive, so this saturates instead of
+/// panicking when that power of two is not representable.
+#[inline]
+fn bucket_mask_for(capacity: usize) -> usize {
+    usize::MAX
+        .checked_shr(capacity.saturating_sub(1).leading_zeros())
+        .unwrap_or(0)
+}
+
+fn capacity_from_len<E: Source>(
+    len: usize,
+    load_factor: (usize, usize),
+) -> Result<usize, E> {
+    if len == 0 {
+        Ok(0)
+    } else {
+        Ok(usize::max(
+            len.checked_mul(load_factor.1)
+                .into_trace("overflow while adjusting capacity")?
+                / load_factor.0,
+            len + 1,
+        ))
+    }
+}
+
+fn control_count<E: Source>(capacity: usize) -> Result<usize, E> {
+    capacity
+        .checked_add(MAX_GROUP_WIDTH - 1)
+        .into_trace("overflow while calculating buckets from adjusted capacity")
+}
+
+/// Returns the layout of the storage for a hash table with `capacity` buckets
+/// of the given layout, along with the offset of the control bytes.
+///
+/// Buckets of zero-sized items take up no space, so the control bytes start at
+/// offset 0 and the storage is only as large as the control bytes. The storage
+/// is still aligned for the items, so pointers to the buckets are aligned.
+fn memory_layout<E: Source>(
+    bucket: Layout,
+    capacity: usize,
+    control_count: usize,
+) -> Result<(Layout, usize), E> {
+    // A size which overflows is replaced with `usize::MAX`, which is always
+    // rejected because it's greater than `isize::MAX`.
+    let buckets_layout = Layout::from_size_align(
+        bucket.size().checked_mul(capacity).unwrap_or(usize::MAX),
+        bucket.align(),
+    )
+    .into_error()?;
+    let control_layout = Layout::array::<u8>(control_count).into_error()?;
+    buckets_layout.extend(control_layout).into_error()
+}
+
+fn check_load_factor<E: Source>(load_factor: (usize, usize)) -> Result<(), E> {
+    #[derive(Debug)]
+    struct InvalidLoadFactor {
+        numerator: usize,
+        denominator: usize,
+    }
+
+    impl fmt::Display for InvalidLoadFactor {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "invalid load factor {} / {}, load factor must be a fraction \
+                 in the range (0, 1]",
+                self.numerator, self.denominator
+            )
         }
     }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for InvalidLoadFactor {}
+
+    if load_factor.0 == 0 || load_factor.1 == 0 || load_factor.0 > load_factor.1
+    {
+        fail!(InvalidLoadFactor {
+            numerator: load_factor.0,
+            denominator: load_factor.1,
+        });
+    }
+
+    Ok(())
 }
 
-impl<T> ArchivedHashTable<T> {
-    fn probe_seq(hash: u64, capacity: usize) -> ProbeSeq {
-        ProbeSeq {
-            pos: h1(hash) % capacity,
-            stride: 0,
+/// An error which occurs when the storage of a hash table would be too large.
+#[derive(Debug)]
+struct TableTooLarge {
+    len: usize,
+    load_factor: (usize, usize),
+}
+
+impl fmt::Display for TableTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the storage of a hash table with {} items and load factor {} / \
+             {} is too large",
+            self.len, self.load_factor.0, self.load_factor.1,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TableTooLarge {}
+
+/// The storage of a hash table which is being serialized.
+///
+/// Hash tables with different item types only differ in the size and alignment
+/// of their buckets, so everything besides serializing and resolving the items
+/// themselves is done here without being generic over the item type. This
+/// keeps the amount of code generated for each kind of hash table small.
+struct RawTableBuilder {
+    capacity: usize,
+    control_count: usize,
+    control_offset: usize,
+    bucket_size: usize,
+    layout: Layout,
+}
+
+impl RawTableBuilder {
+    fn new(
+        len: usize,
+        load_factor: (usize, usize),
+        bucket: Layout,
+    ) -> Result<Self, TableTooLarge> {
+        let too_large = |_| TableTooLarge { len, load_factor };
+        let capacity = capacity_from_len::<Failure>(len, load_factor)
+            .map_err(too_large)?;
+        let control_count =
+            control_count::<Failure>(capacity).map_err(too_large)?;
+        let (layout, control_offset) =
+            memory_layout::<Failure>(bucket, capacity, control_count)
+                .map_err(too_large)?;
+
+        Ok(Self {
+            capacity,
+            control_count,
+            control_offset,
+            bucket_size: bucket.size(),
+            layout,
+        })
+    }
+
+    /// Zeroes the buckets and marks every control byte as EMPTY.
+    ///
+    /// # Safety
+    ///
+    /// `storage` must be valid for writes of `self.layout.size()` bytes.
+    unsafe fn init(&self, storage: *mut u8) {
+        // Initialize all non-control bytes to zero
+        unsafe {
+            ptr::write_bytes(storage, 0, self.control_offset);
+        }
+
+        // Initialize all control bytes to EMPTY (0xFF)
+        unsafe {
+            ptr::write_bytes(
+                storage.add(self.control_offset),
+                0xff,
+                self.control_count,
+            );
         }
     }
 
+    /// Marks the first empty bucket in the probe sequence of `hash` as full
+    /// and returns the index of that bucket.
+    ///
+    /// # Safety
+    ///
+    /// `storage` must have been initialized with [`init`](Self::init), and
+    /// fewer than `capacity` buckets may have been inserted into it.
+    unsafe fn insert(&self, storage: *mut u8, hash: u64) -> usize {
+        let capacity = self.capacity;
+        let ptr = unsafe { storage.add(self.control_offset) };
+        let bucket_mask = bucket_mask_for(capacity);
+        let h2_hash = h2(hash);
+        let mut probe_seq = ProbeSeq::new(hash, capacity);
+
+        loop {
+            for _ in 0..MAX_GROUP_WIDTH / Group::WIDTH {
+                let group = unsafe { Group::read(ptr.add(probe_seq.pos)) };
+
+                if let Some(bit) = group.match_empty().lowest_set_bit() {
+                    let index = (probe_seq.pos + bit) % capacity;
+
+                    // Update control byte
+                    unsafe {
+                        ptr.add(index).write(h2_hash);
+                    }
+                    // If it's near the end of the group, update the wraparound
+                    // control byte
+                    if index < self.control_count - capacity {
+                        unsafe {
+                            ptr.add(capacity + index).write(h2_hash);
+                        }
+                    }
+
+                    return index;
+                }
+
+                probe_seq.next_group();
+            }
+
+            loop {
+                probe_seq.move_next(bucket_mask);
+                if probe_seq.pos < capacity {
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Returns the offset of the bucket at the given index from the start of
+    /// the storage.
+    ///
+    /// This can't underflow because `index` is less than `capacity` and the
+    /// buckets are laid out before `control_offset`. Every bucket of a
+    /// zero-sized item is at `control_offset`.
+    fn bucket_offset(&self, index: usize) -> usize {
+        self.control_offset - (index + 1) * self.bucket_size
+    }
+}
+
+/// Scratch space for the storage of a hash table which is being serialized.
+///
+/// The space is returned to the serializer when the `TableScratch` is dropped,
+/// including during unwinding. This keeps the storage from leaking when writing
+/// the table fails or resolving an item panics.
+struct TableScratch<'a, S: Fallible + Allocator + ?Sized> {
+    serializer: &'a mut S,
+    ptr: NonNull<u8>,
+    layout: Layout,
+}
+
+impl<'a, S: Fallible + Allocator + ?Sized> TableScratch<'a, S> {
+    fn new(serializer: &'a mut S, layout: Layout) -> Result<Self, S::Error> {
+        // SAFETY: The space is returned to the serializer with `pop_alloc`
+        // when the `TableScratch` is freed or dropped.
+        let ptr = unsafe { serializer.push_alloc(layout)?.cast::<u8>() };
+        Ok(Self {
+            serializer,
+            ptr,
+            layout,
+        })
+    }
+
+    /// Returns the space to the serializer.
+    ///
+    /// This is equivalent to dropping the scratch space, but returns any error
+    /// which occurs while returning the space.
+    fn free(self) -> Result<(), S::Error> {
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY: The space was allocated by `push_alloc` with `layout`, and
+        // `this` is never used again.
+        unsafe { this.serializer.pop_alloc(this.ptr, this.layout) }
+    }
+}
+
+impl<S: Fallible + Allocator + ?Sized> Drop for TableScratch<'_, S> {
+    fn drop(&mut self) {
+        // SAFETY: The space was allocated by `push_alloc` with `layout`, and
+        // the scratch space is being dropped so it will not be used again.
+        let _ = unsafe { self.serializer.pop_alloc(self.ptr, self.layout) };
+    }
+}
+
+impl<T> ArchivedHashTable<T> {
     /// # Safety
     ///
     /// `index` must be less than `len()`.
@@ -111,7 +481,16 @@ impl<T> ArchivedHashTable<T> {
         unsafe { ptr.cast::<u8>().add(index) }
     }
 
-    unsafe fn bucket(&self, index: usize) -> NonNull<T> {
+    /// # Safety
+    ///
+    /// This hash table must not be empty and `index` must be less than
+    /// `capacity()`.
+    unsafe fn bucket_unchecked(&self, index: usize) -> NonNull<T> {
+        // SAFETY: The buckets are laid out backwards before the control bytes,
+        // so the bucket at `index` is in bounds. If `T` is zero-sized, then
+        // `sub` doesn't move the pointer and every bucket is at the control
+        // bytes, which are aligned for `T` because the control bytes start at
+        // offset 0 of the storage.
         unsafe {
             NonNull::new_unchecked(
                 self.ptr.as_ptr().cast::<T>().sub(index + 1).cast_mut(),
@@ -119,11 +498,17 @@ impl<T> ArchivedHashTable<T> {
         }
     }
 
-    fn bucket_mask(capacity: usize) -> usize {
-        capacity.checked_next_power_of_two().unwrap() - 1
+    fn get_entry<C>(&self, hash: u64, cmp: C) -> Option<NonNull<T>>
+    where
+        C: Fn(&T) -> bool,
+    {
+        let index = self.get_bucket_index(hash, cmp)?;
+        // SAFETY: `get_bucket_index` only returns indices of full buckets,
+        // which are less than `capacity()` in a non-empty hash table.
+        Some(unsafe { self.bucket_unchecked(index) })
     }
 
-    fn get_entry<C>(&self, hash: u64, cmp: C) -> Option<NonNull<T>>
+    fn get_bucket_index<C>(&self, hash: u64, cmp: C) -> Option<usize>
     where
         C: Fn(&T) -> bool,
     {
@@ -132,10 +517,10 @@ impl<T> ArchivedHashTable<T> {
         }
 
         let h2_hash = h2(hash);
-        let mut probe_seq = Self::probe_seq(hash, self.capacity());
+        let mut probe_seq = ProbeSeq::new(hash, self.capacity());
 
         let capacity = self.capacity();
-        let bucket_mask = Self::bucket_mask(capacity);
+        let bucket_mask = bucket_mask_for(capacity);
 
         loop {
             let mut any_empty = false;
@@ -145,12 +530,12 @@ impl<T> ArchivedHashTable<T> {
 
                 for bit in group.match_byte(h2_hash) {
                     let index = (probe_seq.pos + bit) % capacity;
-                    let bucket_ptr = unsafe { self.bucket(index) };
+                    let bucket_ptr = unsafe { self.bucket_unchecked(index) };
                     let bucket = unsafe { bucket_ptr.as_ref() };
 
                     // Opt: These can be marked as likely true on nightly.
                     if cmp(bucket) {
-                        return Some(bucket_ptr);
+                        return Some(index);
                     }
                 }
 
@@ -173,7 +558,9 @@ impl<T> ArchivedHashTable<T> {
         }
     }
 
-    /// Returns the key-value pair corresponding to the supplied key.
+    /// Returns the item with the given hash for which `cmp` returns `true`.
+    ///
+    /// `hash` must be the same hash that the item was serialized with.
     pub fn get_with<C>(&self, hash: u64, cmp: C) -> Option<&T>
     where
         C: Fn(&T) -> bool,
@@ -182,7 +569,10 @@ impl<T> ArchivedHashTable<T> {
         Some(unsafe { ptr.as_ref() })
     }
 
-    /// Returns the mutable key-value pair corresponding to the supplied key.
+    /// Returns the mutable item with the given hash for which `cmp` returns
+    /// `true`.
+    ///
+    /// `hash` must be the same hash that the item was serialized with.
     pub fn get_with_mut<C>(
         self: Pin<&mut Self>,
         hash: u64,
@@ -195,6 +585,39 @@ impl<T> ArchivedHashTable<T> {
         Some(unsafe { Pin::new_unchecked(ptr.as_mut()) })
     }
 
+    /// Returns the mutable items with the given hashes for which `cmp` returns
+    /// `true`.
+    ///
+    /// `cmp` is called with the position of the hash that is being looked up
+    /// and each candidate item. Returns `None` if any of the items are missing
+    /// or if any two lookups find the same bucket.
+    pub fn get_many_with_mut<C, const N: usize>(
+        self: Pin<&mut Self>,
+        hashes: [u64; N],
+        cmp: C,
+    ) -> Option<[Pin<&mut T>; N]>
+    where
+        C: Fn(usize, &T) -> bool,
+    {
+        let mut indices = [0; N];
+        for (i, &hash) in hashes.iter().enumerate() {
+            let index = self.get_bucket_index(hash, |e| cmp(i, e))?;
+            if indices[..i].contains(&index) {
+                return None;
+            }
+            indices[i] = index;
+        }
+
+        Some(indices.map(|index| {
+            // SAFETY: Each index was returned by `get_bucket_index`, so the
+            // hash table is not empty and the index is less than `capacity()`.
+            // The indices are all different, so the returned references don't
+            // alias.
+            let mut ptr = unsafe { self.bucket_unchecked(index) };
+            unsafe { Pin::new_unchecked(ptr.as_mut()) }
+        }))
+    }
+
     /// Returns whether the hash table is empty.
     pub const fn is_empty(&self) -> bool {
         self.len.to_native() == 0
@@ -202,12 +625,142 @@ impl<T> ArchivedHashTable<T> {
 
     /// Returns the number of elements in the hash table.
     pub const fn len(&self) -> usize {
-        self.len.to_native() as usize
+        usize_from_archived(self.len)
     }
 
     /// Returns the total capacity of the hash table.
+    ///
+    /// This is the number of buckets in the hash table, and is determined by
+    /// the length and load factor the hash table was serialized with.
     pub fn capacity(&self) -> usize {
-        self.cap.to_native() as usize
+        usize_from_archived(self.cap)
+    }
+
+    /// Returns the item in the bucket at the given index, or `None` if the
+    /// bucket is empty or `index` is not less than `capacity()`.
+    pub fn bucket(&self, index: usize) -> Option<&T> {
+        if self.is_empty() || index >= self.capacity() {
+            return None;
+        }
+
+        // SAFETY: The hash table is not empty and `index` is less than
+        // `capacity()`, which is less than the number of control bytes.
+        if unsafe { *self.control(index) } == 0xff {
+            None
+        } else {
+            // SAFETY: The hash table is not empty, `index` is less than
+            // `capacity()`, and the control byte for the bucket is full.
+            Some(unsafe { self.bucket_unchecked(index).as_ref() })
+        }
+    }
+
+    /// Returns the mask applied to positions in the probe sequence.
+    ///
+    /// This is one less than the capacity rounded up to the next power of two.
+    pub fn bucket_mask(&self) -> usize {
+        bucket_mask_for(self.capacity())
+    }
+
+    /// Returns the control bytes of the hash table.
+    ///
+    /// The first `capacity()` control bytes correspond to the buckets of the
+    /// hash table, and the remaining bytes repeat the first few. Each control
+    /// byte is either EMPTY (`0xFF`) or the top seven bits of the hash of the
+    /// item in the corresponding bucket.
+    pub fn control_bytes(&self) -> &[u8] {
+        if self.is_empty() {
+            return &[];
+        }
+
+        // The control count of an archived hash table can't overflow because
+        // it was checked when the hash table was serialized.
+        let count = control_count::<Panic>(self.capacity()).always_ok();
+        // SAFETY: The hash table is not empty, so there are `count` control
+        // bytes following `self.ptr`.
+        unsafe { slice::from_raw_parts(self.control(0), count) }
+    }
+
+    /// Returns the ratio of the number of elements to the capacity of the hash
+    /// table.
+    ///
+    /// Empty hash tables have a load factor of zero.
+    pub fn load_factor(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            self.len() as f64 / self.capacity() as f64
+        }
+    }
+
+    /// Returns the number of probes an unsuccessful lookup for the given hash
+    /// performs.
+    ///
+    /// Each probe reads one maximum-width group of control bytes, and lookups
+    /// stop after the first probe which finds an empty bucket. A successful
+    /// lookup for the same hash performs at most this many probes. Empty hash
+    /// tables always return zero.
+    pub fn probe_length(&self, hash: u64) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        self.probe_length_from(ProbeSeq::new(hash, self.capacity()))
+    }
+
+    /// Returns the length of the longest probe sequence of any lookup.
+    ///
+    /// See [`probe_length`](Self::probe_length) for how probes are counted.
+    pub fn max_probe_length(&self) -> usize {
+        self.probe_lengths().max().unwrap_or(0)
+    }
+
+    /// Returns the average length of the probe sequences of lookups, assuming
+    /// that hashes are uniformly distributed.
+    ///
+    /// See [`probe_length`](Self::probe_length) for how probes are counted.
+    /// A well-distributed hash function and a low load factor will result in
+    /// an average probe length close to one.
+    pub fn average_probe_length(&self) -> f64 {
+        if self.is_empty() {
+            0.0
+        } else {
+            let total = self.probe_lengths().sum::<usize>();
+            total as f64 / self.capacity() as f64
+        }
+    }
+
+    /// Returns the probe lengths of lookups starting at each bucket.
+    fn probe_lengths(&self) -> impl Iterator<Item = usize> + '_ {
+        let capacity = if self.is_empty() { 0 } else { self.capacity() };
+        (0..capacity)
+            .map(|pos| self.probe_length_from(ProbeSeq { pos, stride: 0 }))
+    }
+
+    fn probe_length_from(&self, mut probe_seq: ProbeSeq) -> usize {
+        let capacity = self.capacity();
+        let bucket_mask = bucket_mask_for(capacity);
+
+        let mut probes = 1;
+        loop {
+            let mut any_empty = false;
+            for _ in 0..MAX_GROUP_WIDTH / Group::WIDTH {
+                let group = unsafe { Group::read(self.control(probe_seq.pos)) };
+                any_empty = any_empty || group.match_empty().any_bit_set();
+                probe_seq.next_group();
+            }
+
+            if any_empty {
+                return probes;
+            }
+
+            loop {
+                probe_seq.move_next(bucket_mask);
+                if probe_seq.pos < capacity {
+                    break;
+                }
+            }
+            probes += 1;
+        }
     }
 
     /// # Safety
@@ -221,6 +774,9 @@ impl<T> ArchivedHashTable<T> {
     }
 
     /// Returns an iterator over the entry pointers in the hash table.
+    ///
+    /// The entries are yielded in bucket order. The pointers are valid for
+    /// reads for as long as the hash table is borrowed.
     pub fn raw_iter(&self) -> RawIter<T> {
         if self.is_empty() {
             RawIter::empty()
@@ -237,6 +793,9 @@ impl<T> ArchivedHashTable<T> {
     }
 
     /// Returns a mutable iterator over the entry pointers in the hash table.
+    ///
+    /// The pointers are valid for reads and writes for as long as the hash
+    /// table is borrowed. The pointed-to entries must not be moved.
     pub fn raw_iter_mut(mut self: Pin<&mut Self>) -> RawIter<T> {
         if self.is_empty() {
             RawIter::empty()
@@ -256,80 +815,51 @@ impl<T> ArchivedHashTable<T> {
         }
     }
 
-    fn capacity_from_len<E: Source>(
-        len: usize,
+    /// Serializes an iterator of hashes and items as a hash table.
+    ///
+    /// Each item is paired with its hash, so the iterator only needs to be
+    /// traversed once and each hash is computed exactly once. The items are
+    /// kept in scratch space until they are resolved into the table.
+    ///
+    /// The load factor is a fraction in the range `(0, 1]` which bounds the
+    /// ratio of items to buckets. The returned resolver must be resolved with
+    /// [`resolve_from_len`](Self::resolve_from_len) using the number of items
+    /// and the same load factor.
+    pub fn serialize_from_iter<I, U, S>(
+        items: I,
         load_factor: (usize, usize),
-    ) -> Result<usize, E> {
-        if len == 0 {
-            Ok(0)
-        } else {
-            Ok(usize::max(
-                len.checked_mul(load_factor.1)
-                    .into_trace("overflow while adjusting capacity")?
-                    / load_factor.0,
-                len + 1,
-            ))
-        }
-    }
-
-    fn control_count<E: Source>(capacity: usize) -> Result<usize, E> {
-        capacity.checked_add(MAX_GROUP_WIDTH - 1).into_trace(
-            "overflow while calculating buckets from adjusted capacity",
+        serializer: &mut S,
+    ) -> Result<HashTableResolver, S::Error>
+    where
+        I: ExactSizeIterator<Item = (u64, U)>,
+        U: Serialize<S, Archived = T>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        Self::serialize_from_iter_with_buckets(
+            items,
+            load_factor,
+            serializer,
+            |_| (),
         )
     }
 
-    fn memory_layout<E: Source>(
-        capacity: usize,
-        control_count: usize,
-    ) -> Result<(Layout, usize), E> {
-        let buckets_layout = Layout::array::<T>(capacity).into_error()?;
-        let control_layout = Layout::array::<u8>(control_count).into_error()?;
-        buckets_layout.extend(control_layout).into_error()
-    }
-
-    /// Serializes an iterator of items as a hash table.
-    pub fn serialize_from_iter<I, H, S>(
+    /// Serializes an iterator of hashes and items as a hash table, calling
+    /// `on_insert` with the bucket index of each item in iteration order.
+    pub(crate) fn serialize_from_iter_with_buckets<I, U, S, F>(
         items: I,
-        hashes: H,
         load_factor: (usize, usize),
         serializer: &mut S,
+        mut on_insert: F,
     ) -> Result<HashTableResolver, S::Error>
     where
-        I: Clone + ExactSizeIterator,
-        I::Item: Serialize<S, Archived = T>,
-        H: ExactSizeIterator<Item = u64>,
+        I: ExactSizeIterator<Item = (u64, U)>,
+        U: Serialize<S, Archived = T>,
         S: Fallible + Writer + Allocator + ?Sized,
         S::Error: Source,
+        F: FnMut(usize),
     {
-        #[derive(Debug)]
-        struct InvalidLoadFactor {
-            numerator: usize,
-            denominator: usize,
-        }
-
-        impl fmt::Display for InvalidLoadFactor {
-            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                write!(
-                    f,
-                    "invalid load factor {} / {}, load factor must be a \
-                     fraction in the range (0, 1]",
-                    self.numerator, self.denominator
-                )
-            }
-        }
-
-        #[cfg(feature = "std")]
-        impl std::error::Error for InvalidLoadFactor {}
-
-        if load_factor.0 == 0
-            || load_factor.1 == 0
-            || load_factor.0 > load_factor.1
-        {
-            fail!(InvalidLoadFactor {
-                numerator: load_factor.0,
-                denominator: load_factor.1,
-            });
-        }
+        check_load_factor::<S::Error>(load_factor)?;
 
         let len = items.len();
 
@@ -346,114 +876,63 @@ impl<T> ArchivedHashTable<T> {
         }
 
         // Serialize all items
-        SerVec::with_capacity(serializer, len, |resolvers, serializer| {
-            for i in items.clone() {
-                if resolvers.len() == len {
+        SerVec::with_capacity(serializer, len, |entries, serializer| {
+            let mut items = items;
+            while let Some((hash, item)) = items.next() {
+                if entries.len() == len {
                     fail!(IteratorLengthMismatch {
                         expected: len,
-                        actual: len + items.count(),
+                        actual: len + 1 + items.count(),
                     });
                 }
 
-                resolvers.push(i.serialize(serializer)?);
+                let resolver = item.serialize(serializer)?;
+                entries.push((hash, item, resolver));
             }
 
             // Allocate scratch space for the hash table storage
-            let capacity = Self::capacity_from_len(len, load_factor)?;
-            let control_count = Self::control_count(capacity)?;
-
-            let (layout, control_offset) =
-                Self::memory_layout(capacity, control_count)?;
-
-            let alloc = unsafe { serializer.push_alloc(layout)?.cast::<u8>() };
-
-            // Initialize all non-control bytes to zero
-            unsafe {
-                ptr::write_bytes(alloc.as_ptr(), 0, control_offset);
-            }
-
-            let ptr = unsafe { alloc.as_ptr().add(control_offset) };
+            let table =
+                RawTableBuilder::new(len, load_factor, Layout::new::<T>())
+                    .into_error()?;
+            let layout = table.layout;
+            serializer.reserve_hint(layout.size() + layout.align());
+            let pos = serializer.align(layout.align())?;
 
-            // Initialize all control bytes to EMPTY (0xFF)
+            let mut scratch = TableScratch::new(&mut *serializer, layout)?;
+            let alloc = scratch.ptr;
             unsafe {
-                ptr::write_bytes(ptr, 0xff, control_count);
+                table.init(alloc.as_ptr());
             }
 
-            let bucket_mask = Self::bucket_mask(capacity);
-
-            let pos = serializer.align(layout.align())?;
-
-            for ((i, resolver), hash) in
-                items.zip(resolvers.drain()).zip(hashes)
-            {
-                let h2_hash = h2(hash);
-                let mut probe_seq = Self::probe_seq(hash, capacity);
-
-                'insert: loop {
-                    for _ in 0..MAX_GROUP_WIDTH / Group::WIDTH {
-                        let group =
-                            unsafe { Group::read(ptr.add(probe_seq.pos)) };
-
-                        if let Some(bit) = group.match_empty().lowest_set_bit()
-                        {
-                            let index = (probe_seq.pos + bit) % capacity;
-
-                            // Update control byte
-                            unsafe {
-                                ptr.add(index).write(h2_hash);
-                            }
-                            // If it's near the end of the group, update the
-                            // wraparound control byte
-                            if index < control_count - capacity {
-                                unsafe {
-                                    ptr.add(capacity + index).write(h2_hash);
-                                }
-                            }
-
-                            let entry_offset =
-                                control_offset - (index + 1) * size_of::<T>();
-                            let out = unsafe {
-                                Place::new_unchecked(
-                                    pos + entry_offset,
-                                    alloc
-                                        .as_ptr()
-                                        .add(entry_offset)
-                                        .cast::<T>(),
-                                )
-                            };
-                            i.resolve(resolver, out);
-
-                            break 'insert;
-                        }
-
-                        probe_seq.next_group();
-                    }
-
-                    loop {
-                        probe_seq.move_next(bucket_mask);
-                        if probe_seq.pos < capacity {
-                            break;
-                        }
-                    }
-                }
+            for (hash, item, resolver) in entries.drain() {
+                let index = unsafe { table.insert(alloc.as_ptr(), hash) };
+                on_insert(index);
+                let offset = table.bucket_offset(index);
+                let out = unsafe {
+                    Place::new_unchecked(
+                        pos + offset,
+                        alloc.as_ptr().add(offset).cast::<T>(),
+                    )
+                };
+                item.resolve(resolver, out);
             }
 
             // Write out-of-line data
             let slice =
                 unsafe { slice::from_raw_parts(alloc.as_ptr(), layout.size()) };
-            serializer.write(slice)?;
-
-            unsafe {
-                serializer.pop_alloc(alloc, layout)?;
-            }
+            scratch.serializer.write(slice)?;
+            scratch.free()?;
 
             Ok(HashTableResolver {
-                pos: pos + control_offset,
+                pos: pos + table.control_offset,
             })
         })?
     }
 
     /// Resolves an archived hash table from a given length and parameters.
+    ///
+    /// `len` and `load_factor` must be the number of items and load factor
+    /// that the hash table was serialized with.
     pub fn resolve_from_len(
         len: usize,
         load_factor: (usize, usize),
@@ -470,19 +949,138 @@ impl<T> ArchivedHashTable<T> {
 
         len.resolve((), out_len);
 
-        let capacity =
-            Self::capacity_from_len::<Panic>(len, load_factor).always_ok();
+        let capacity = capacity_from_len::<Panic>(len, load_factor).always_ok();
         capacity.resolve((), cap);
 
         // PhantomData doesn't need to be initialized
     }
 }
 
+#[cfg(feature = "alloc")]
+impl<K, V> ArchivedHashTable<Entry<K, V>> {
+    /// Serializes an iterator of hashes, keys, and values as a hash table of
+    /// entries, writing the out-of-line data of the values after the table.
+    ///
+    /// The keys are serialized first and the table is written right after
+    /// them, so the keys stay close to the table. The values are serialized
+    /// with a [`Deferred`] serializer which is written out after the table.
+    ///
+    /// The returned resolver must be resolved with
+    /// [`resolve_from_len`](Self::resolve_from_len) using the number of items
+    /// and the same load factor.
+    pub fn serialize_entries_values_last<'a, I, KU, VU, S>(
+        items: I,
+        load_factor: (usize, usize),
+        serializer: &mut S,
+    ) -> Result<HashTableResolver, S::Error>
+    where
+        I: ExactSizeIterator<Item = (u64, &'a KU, &'a VU)>,
+        KU: 'a + Serialize<S, Archived = K>,
+        VU: 'a + Archive<Archived = V> + for<'s> Serialize<Deferred<'s, S>>,
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        check_load_factor::<S::Error>(load_factor)?;
+
+        let len = items.len();
+
+        if len == 0 {
+            let count = items.count();
+            if count != 0 {
+                fail!(IteratorLengthMismatch {
+                    expected: 0,
+                    actual: count,
+                });
+            }
+
+            return Ok(HashTableResolver { pos: 0 });
+        }
+
+        // Serialize all keys
+        SerVec::with_capacity(serializer, len, |entries, serializer| {
+            let mut items = items;
+            while let Some((hash, key, value)) = items.next() {
+                if entries.len() == len {
+                    fail!(IteratorLengthMismatch {
+                        expected: len,
+                        actual: len + 1 + items.count(),
+                    });
+                }
+
+                let resolver = key.serialize(serializer)?;
+                entries.push((hash, key, value, resolver));
+            }
+
+            let table = RawTableBuilder::new(
+                len,
+                load_factor,
+                Layout::new::<Entry<K, V>>(),
+            )
+            .into_error()?;
+            let layout = table.layout;
+            let pos = serializer.align(layout.align())?;
+
+            // Serialize all values as if they were written after the table
+            SerVec::with_capacity(serializer, len, |values, serializer| {
+                let mut deferred =
+                    Deferred::new(&mut *serializer, pos + layout.size());
+                for (_, _, value, _) in entries.as_slice() {
+                    values.push(value.serialize(&mut deferred)?);
+                }
+                let deferred = deferred.into_bytes();
+
+                serializer.reserve_hint(layout.size() + deferred.len());
+
+                // Allocate scratch space for the hash table storage
+                let mut scratch = TableScratch::new(&mut *serializer, layout)?;
+                let alloc = scratch.ptr;
+                unsafe {
+                    table.init(alloc.as_ptr());
+                }
+
+                for ((hash, key, value, key_resolver), value_resolver) in
+                    entries.drain().zip(values.drain())
+                {
+                    let index = unsafe { table.insert(alloc.as_ptr(), hash) };
+                    let offset = table.bucket_offset(index);
+                    let out = unsafe {
+                        Place::new_unchecked(
+                            pos + offset,
+                            alloc.as_ptr().add(offset).cast::<Entry<K, V>>(),
+                        )
+                    };
+                    EntryAdapter { key, value }.resolve(
+                        EntryResolver {
+                            key: key_resolver,
+                            value: value_resolver,
+                        },
+                        out,
+                    );
+                }
+
+                let slice = unsafe {
+                    slice::from_raw_parts(alloc.as_ptr(), layout.size())
+                };
+                scratch.serializer.write(slice)?;
+                scratch.free()?;
+
+                // Write the values after the table
+                serializer.write(&deferred)?;
+
+                Ok(HashTableResolver {
+                    pos: pos + table.control_offset,
+                })
+            })?
+        })?
+    }
+}
+
 /// The resolver for [`ArchivedHashTable`].
 pub struct HashTableResolver {
     pos: usize,
 }
 
+#[derive(Clone)]
 struct ControlIter {
     current_mask: Bitmask,
     next_group: *const u8,
@@ -515,6 +1113,9 @@ impl ControlIter {
 }
 
 /// An iterator over the entry pointers of an [`ArchivedHashTable`].
+///
+/// This is returned by [`ArchivedHashTable::raw_iter`] and
+/// [`ArchivedHashTable::raw_iter_mut`].
 pub struct RawIter<T> {
     controls: ControlIter,
     entries: NonNull<T>,
@@ -532,6 +1133,16 @@ impl<T> RawIter<T> {
     }
 }
 
+impl<T> Clone for RawIter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            controls: self.controls.clone(),
+            entries: self.entries,
+            items_left: self.items_left,
+        }
+    }
+}
+
 impl<T> Iterator for RawIter<T> {
     type Item = NonNull<T>;
 
@@ -544,6 +1155,8 @@ impl<T> Iterator for RawIter<T> {
                     break bit;
                 }
                 self.controls.move_next();
+                // For zero-sized items, these `sub`s never move the pointer,
+                // and every entry is at the control bytes.
                 self.entries = unsafe {
                     NonNull::new_unchecked(
                         self.entries.as_ptr().sub(Group::WIDTH),
@@ -557,6 +1170,10 @@ impl<T> Iterator for RawIter<T> {
             Some(entry)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.items_left, Some(self.items_left))
+    }
 }
 
 impl<T> ExactSizeIterator for RawIter<T> {
@@ -565,17 +1182,21 @@ impl<T> ExactSizeIterator for RawIter<T> {
     }
 }
 
+impl<T> FusedIterator for RawIter<T> {}
+
 #[cfg(feature = "bytecheck")]
 mod verify {
-    use core::fmt;
+    use core::{alloc::Layout, fmt, mem::size_of};
 
     use bytecheck::{CheckBytes, Verify};
     use rancor::{fail, Fallible, Source};
 
-    use super::ArchivedHashTable;
+    use super::{control_count, memory_layout, ArchivedHashTable};
     use crate::{
         simd::Group,
-        validation::{ArchiveContext, ArchiveContextExt as _},
+        validation::{
+            progress_batch_len, ArchiveContext, ArchiveContextExt as _,
+        },
     };
 
     #[derive(Debug)]
@@ -612,6 +1233,102 @@ mod verify {
     #[cfg(feature = "std")]
     impl std::error::Error for UnwrappedControlByte {}
 
+    #[derive(Debug)]
+    struct DuplicateKey {
+        first: usize,
+        second: usize,
+    }
+
+    impl fmt::Display for DuplicateKey {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "hash table contains duplicate keys in buckets {} and {}",
+                self.first, self.second,
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for DuplicateKey {}
+
+    #[derive(Debug)]
+    struct UnreachableItem {
+        index: usize,
+    }
+
+    impl fmt::Display for UnreachableItem {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "item in bucket {} is not found by looking up its own key",
+                self.index,
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for UnreachableItem {}
+
+    impl<T> ArchivedHashTable<T> {
+        /// Checks that looking up each item in the hash table finds that same
+        /// item.
+        ///
+        /// `hash` must return the hash that an item was serialized with, and
+        /// `eq` must return whether two items have equal keys. This rejects
+        /// hash tables which contain multiple items with equal keys, as well as
+        /// items which are not in the probe sequence of their hash.
+        ///
+        /// # Safety
+        ///
+        /// The hash table must have been checked with `CheckBytes`.
+        pub(crate) unsafe fn check_lookups<E, F, C>(
+            &self,
+            hash: F,
+            eq: C,
+        ) -> Result<(), E>
+        where
+            E: Source,
+            F: Fn(&T) -> u64,
+            C: Fn(&T, &T) -> bool,
+        {
+            let cap = self.capacity();
+            if cap == 0 {
+                return Ok(());
+            }
+
+            // SAFETY: We have checked that `self` is not empty.
+            let mut controls = unsafe { self.control_iter() };
+            let mut base_index = 0;
+            'outer: while base_index < cap {
+                while let Some(bit) = controls.next_full() {
+                    let index = base_index + bit;
+                    if index >= cap {
+                        break 'outer;
+                    }
+
+                    // SAFETY: `index` is less than the capacity of the hash
+                    // table, and the caller has guaranteed that the item in
+                    // every full bucket has been checked.
+                    let item = unsafe { self.bucket_unchecked(index).as_ref() };
+                    match self.get_bucket_index(hash(item), |x| eq(x, item)) {
+                        Some(found) if found == index => (),
+                        Some(found) => fail!(DuplicateKey {
+                            first: usize::min(found, index),
+                            second: usize::max(found, index),
+                        }),
+                        None => fail!(UnreachableItem { index }),
+                    }
+                }
+
+                controls.move_next();
+                base_index += Group::WIDTH;
+            }
+
+            Ok(())
+        }
+    }
+
     unsafe impl<C, T> Verify<C> for ArchivedHashTable<T>
     where
         C: Fallible + ArchiveContext + ?Sized,
@@ -631,9 +1348,9 @@ mod verify {
             }
 
             // Check memory allocation
-            let control_count = Self::control_count(cap)?;
+            let control_count = control_count(cap)?;
             let (layout, control_offset) =
-                Self::memory_layout(cap, control_count)?;
+                memory_layout(Layout::new::<T>(), cap, control_count)?;
             let ptr = self
                 .ptr
                 .as_ptr_wrapping()
@@ -646,6 +1363,8 @@ mod verify {
                 // SAFETY: We have checked that `self` is not empty.
                 let mut controls = unsafe { self.control_iter() };
                 let mut base_index = 0;
+                let batch_len = progress_batch_len::<T>();
+                let mut unreported = 0;
                 'outer: while base_index < cap {
                     while let Some(bit) = controls.next_full() {
                         let index = base_index + bit;
@@ -655,15 +1374,23 @@ mod verify {
 
                         unsafe {
                             T::check_bytes(
-                                self.bucket(index).as_ptr(),
+                                self.bucket_unchecked(index).as_ptr(),
                                 context,
                             )?;
                         }
+
+                        unreported += 1;
+                        if unreported == batch_len {
+                            context
+                                .report_progress(unreported * size_of::<T>())?;
+                            unreported = 0;
+                        }
                     }
 
                     controls.move_next();
                     base_index += Group::WIDTH;
                 }
+                context.report_progress(unreported * size_of::<T>())?;
 
                 // Verify that wrapped bytes are set correctly
                 for i in cap..usize::min(2 * cap, control_count) {
@@ -679,3 +1406,612 @@ mod verify {
         }
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{vec, vec::Vec};
+    use core::{
+        cell::Cell,
+        hash::{Hash, Hasher},
+    };
+    #[cfg(feature = "std")]
+    use std::collections::HashMap;
+
+    use rancor::{Fallible, Source};
+
+    use crate::{
+        collections::swiss_table::{
+            ArchivedHashSet, ArchivedHashTable, HashSetResolver,
+            HashTableResolver,
+        },
+        hash::hash_value,
+        primitive::ArchivedU32,
+        ser::{Allocator, Writer},
+        simd::MAX_GROUP_WIDTH,
+        test::{to_archived, to_bytes},
+        Archive, Archived, Place, Serialize,
+    };
+
+    struct Keys {
+        keys: Vec<u32>,
+        load_factor: (usize, usize),
+    }
+
+    impl Archive for Keys {
+        type Archived = ArchivedHashSet<ArchivedU32>;
+        type Resolver = HashSetResolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            ArchivedHashSet::resolve_from_len(
+                self.keys.len(),
+                self.load_factor,
+                resolver,
+                out,
+            );
+        }
+    }
+
+    impl<S> Serialize<S> for Keys
+    where
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedHashSet::serialize_from_iter(
+                self.keys.iter(),
+                self.load_factor,
+                serializer,
+            )
+        }
+    }
+
+    struct Stats {
+        load_factor: f64,
+        max_probe_length: usize,
+        average_probe_length: f64,
+    }
+
+    fn stats(keys: &[u32], load_factor: (usize, usize)) -> Stats {
+        let value = Keys {
+            keys: keys.to_vec(),
+            load_factor,
+        };
+
+        let mut result = None;
+        to_archived(&value, |archived| {
+            let table = archived.raw_table();
+            let capacity = table.capacity();
+
+            assert_eq!(table.bucket_mask(), capacity.next_power_of_two() - 1);
+            let controls = table.control_bytes();
+            assert_eq!(controls.len(), capacity + MAX_GROUP_WIDTH - 1);
+            let full = controls[..capacity].iter().filter(|&&c| c != 0xff);
+            assert_eq!(full.count(), keys.len());
+
+            let max = table.max_probe_length();
+            for key in keys {
+                let probes = table.probe_length(hash_value::<u32>(key));
+                assert!(probes >= 1 && probes <= max);
+            }
+
+            result = Some(Stats {
+                load_factor: table.load_factor(),
+                max_probe_length: max,
+                average_probe_length: table.average_probe_length(),
+            });
+        });
+        result.unwrap()
+    }
+
+    #[test]
+    fn empty_stats() {
+        let value = Keys {
+            keys: Vec::new(),
+            load_factor: (7, 8),
+        };
+        to_archived(&value, |archived| {
+            let table = archived.raw_table();
+            assert!(table.control_bytes().is_empty());
+            assert_eq!(table.load_factor(), 0.0);
+            assert_eq!(table.probe_length(0), 0);
+            assert_eq!(table.max_probe_length(), 0);
+            assert_eq!(table.average_probe_length(), 0.0);
+        });
+    }
+
+    #[test]
+    fn bucket_mask_for() {
+        for capacity in 0..=65 {
+            assert_eq!(
+                super::bucket_mask_for(capacity),
+                capacity.next_power_of_two() - 1,
+            );
+        }
+        assert_eq!(super::bucket_mask_for(usize::MAX / 2 + 1), usize::MAX / 2);
+        assert_eq!(super::bucket_mask_for(usize::MAX / 2 + 2), usize::MAX);
+        assert_eq!(super::bucket_mask_for(usize::MAX), usize::MAX);
+    }
+
+    #[test]
+    fn stats_by_load_factor() {
+        let keys = (0..1000).collect::<Vec<u32>>();
+
+        let sparse = stats(&keys, (1, 4));
+        let medium = stats(&keys, (1, 2));
+        let dense = stats(&keys, (7, 8));
+
+        assert!(sparse.load_factor < medium.load_factor);
+        assert!(medium.load_factor < dense.load_factor);
+        assert!(dense.load_factor <= 0.875);
+
+        assert!(sparse.average_probe_length >= 1.0);
+        assert!(sparse.average_probe_length <= medium.average_probe_length);
+        assert!(medium.average_probe_length <= dense.average_probe_length);
+        assert!(sparse.max_probe_length <= dense.max_probe_length);
+    }
+
+    #[test]
+    fn zero_sized_buckets() {
+        use core::alloc::Layout;
+
+        use super::RawTableBuilder;
+
+        for bucket in [Layout::new::<()>(), Layout::new::<[u64; 0]>()] {
+            let table = RawTableBuilder::new(100, (7, 8), bucket).unwrap();
+            assert_eq!(table.control_offset, 0);
+            assert_eq!(table.layout.size(), table.control_count);
+            assert_eq!(table.layout.align(), bucket.align());
+            assert_eq!(table.bucket_offset(0), 0);
+            assert_eq!(table.bucket_offset(table.capacity - 1), 0);
+        }
+
+        // Even a huge table of zero-sized items only needs its control bytes.
+        let table = RawTableBuilder::new(
+            isize::MAX as usize / 2,
+            (1, 1),
+            Layout::new::<()>(),
+        )
+        .unwrap();
+        assert_eq!(table.layout.size(), table.control_count);
+    }
+
+    #[test]
+    fn reject_duplicate_keys() {
+        #[cfg(not(feature = "std"))]
+        use alloc::string::ToString;
+
+        use rancor::Error;
+
+        use crate::{
+            access,
+            validation::{
+                util::access_with_context, validators::DefaultValidator,
+            },
+        };
+
+        // Serializing from an iterator doesn't deduplicate keys, so this
+        // produces a hash set which contains `2` twice.
+        let value = Keys {
+            keys: vec![1, 2, 3, 2],
+            load_factor: (7, 8),
+        };
+        to_bytes(&value, |bytes| {
+            let error = access::<ArchivedHashSet<ArchivedU32>, Error>(bytes)
+                .expect_err("expected duplicate key error")
+                .to_string();
+            assert!(error.contains("duplicate keys"), "{}", error);
+
+            let mut validator = DefaultValidator::new(bytes);
+            validator.set_check_duplicate_keys(false);
+            let archived = access_with_context::<
+                ArchivedHashSet<ArchivedU32>,
+                _,
+                Error,
+            >(bytes, &mut validator)
+            .unwrap();
+            assert_eq!(archived.len(), 4);
+        });
+
+        let value = Keys {
+            keys: vec![1, 2, 3],
+            load_factor: (7, 8),
+        };
+        to_bytes(&value, |bytes| {
+            access::<ArchivedHashSet<ArchivedU32>, Error>(bytes).unwrap();
+        });
+    }
+
+    struct CountedKey<'a> {
+        key: u32,
+        hashes: &'a Cell<usize>,
+    }
+
+    impl Hash for CountedKey<'_> {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.hashes.set(self.hashes.get() + 1);
+            self.key.hash(state);
+        }
+    }
+
+    impl PartialEq for CountedKey<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.key == other.key
+        }
+    }
+
+    impl Eq for CountedKey<'_> {}
+
+    impl Archive for CountedKey<'_> {
+        type Archived = ArchivedU32;
+        type Resolver = ();
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            self.key.resolve(resolver, out);
+        }
+    }
+
+    impl<S: Fallible + ?Sized> Serialize<S> for CountedKey<'_> {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            self.key.serialize(serializer)
+        }
+    }
+
+    struct OneShotKeys<'a> {
+        keys: Vec<CountedKey<'a>>,
+        load_factor: (usize, usize),
+    }
+
+    impl Archive for OneShotKeys<'_> {
+        type Archived = ArchivedHashSet<ArchivedU32>;
+        type Resolver = HashSetResolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            ArchivedHashSet::resolve_from_len(
+                self.keys.len(),
+                self.load_factor,
+                resolver,
+                out,
+            );
+        }
+    }
+
+    impl<S> Serialize<S> for OneShotKeys<'_>
+    where
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            // A by-reference iterator can't be cloned, so this checks that the
+            // items are only traversed once.
+            let mut iter = self.keys.iter();
+            ArchivedHashSet::serialize_from_iter(
+                iter.by_ref(),
+                self.load_factor,
+                serializer,
+            )
+        }
+    }
+
+    // The hash of a key is truncated to a `usize` before it's reduced to a
+    // bucket index, so the layout of the table depends on the pointer width.
+    #[test]
+    #[cfg(all(
+        target_pointer_width = "64",
+        not(feature = "big_endian"),
+        not(feature = "unaligned"),
+        not(any(feature = "pointer_width_16", feature = "pointer_width_64")),
+    ))]
+    fn single_pass_matches_archive() {
+        // The archived bytes of the keys with each load factor, as written by
+        // `tests/hash_set_fixtures.rs`.
+        const GOLDEN: [((usize, usize), &[u8]); 3] = [
+            (
+                (1, 4),
+                include_bytes!("../../../tests/data/hash_set_1_4.bin"),
+            ),
+            (
+                (1, 2),
+                include_bytes!("../../../tests/data/hash_set_1_2.bin"),
+            ),
+            (
+                (7, 8),
+                include_bytes!("../../../tests/data/hash_set_7_8.bin"),
+            ),
+        ];
+
+        let keys = (0..100).map(|i| i * 31 + 7).collect::<Vec<u32>>();
+        for (load_factor, expected) in GOLDEN {
+            to_bytes(
+                &Keys {
+                    keys: keys.clone(),
+                    load_factor,
+                },
+                |bytes| assert_eq!(bytes, expected),
+            );
+
+            let hashes = Cell::new(0);
+            let value = OneShotKeys {
+                keys: keys
+                    .iter()
+                    .map(|&key| CountedKey {
+                        key,
+                        hashes: &hashes,
+                    })
+                    .collect(),
+                load_factor,
+            };
+            to_bytes(&value, |bytes| assert_eq!(bytes, expected));
+            assert_eq!(hashes.get(), keys.len());
+        }
+    }
+
+    struct Hashed(Vec<(u64, u32)>);
+
+    impl Archive for Hashed {
+        type Archived = ArchivedHashTable<ArchivedU32>;
+        type Resolver = HashTableResolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            ArchivedHashTable::resolve_from_len(
+                self.0.len(),
+                (7, 8),
+                resolver,
+                out,
+            );
+        }
+    }
+
+    impl<S> Serialize<S> for Hashed
+    where
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedHashTable::serialize_from_iter(
+                self.0.iter().copied(),
+                (7, 8),
+                serializer,
+            )
+        }
+    }
+
+    #[cfg(not(any(
+        feature = "big_endian",
+        feature = "unaligned",
+        feature = "pointer_width_16",
+        feature = "pointer_width_64",
+    )))]
+    #[test]
+    fn golden_bytes() {
+        let value = Hashed(vec![
+            // Bucket 1
+            ((0x12 << 57) | 1, 0x1111_1111),
+            // Also probes bucket 1 first, and so is placed in bucket 2
+            ((0x34 << 57) | 5, 0x2222_2222),
+            // Bucket 3
+            ((0x56 << 57) | 3, 0x3333_3333),
+        ]);
+
+        #[rustfmt::skip]
+        let expected: &[u8] = &[
+            0x33, 0x33, 0x33, 0x33, // bucket 3
+            0x22, 0x22, 0x22, 0x22, // bucket 2
+            0x11, 0x11, 0x11, 0x11, // bucket 1
+            0x00, 0x00, 0x00, 0x00, // bucket 0
+            0xff, 0x12, 0x34, 0x56, 0xff, 0x12, 0x34, 0x56, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // control bytes
+            0x00, // padding to 4-alignment
+            0xec, 0xff, 0xff, 0xff, // points 20 bytes backward
+            0x03, 0x00, 0x00, 0x00, // len
+            0x04, 0x00, 0x00, 0x00, // capacity
+        ];
+        to_bytes(&value, |bytes| assert_eq!(bytes, expected));
+    }
+
+    /// The value of a [`Flaky`] which fails to serialize.
+    #[cfg(feature = "std")]
+    const FAIL: u32 = 3;
+    /// The value of a [`Flaky`] which panics while resolving.
+    #[cfg(feature = "std")]
+    const PANIC: u32 = 7;
+
+    #[cfg(feature = "std")]
+    #[derive(Debug)]
+    struct FlakyError;
+
+    #[cfg(feature = "std")]
+    impl core::fmt::Display for FlakyError {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            write!(f, "flaky value failed to serialize")
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for FlakyError {}
+
+    /// A value which writes some out-of-line data when serialized, and then
+    /// fails if it is `FAIL` or panics while resolving if it is `PANIC`.
+    #[cfg(feature = "std")]
+    struct Flaky(u32);
+
+    #[cfg(feature = "std")]
+    impl Archive for Flaky {
+        type Archived = ArchivedU32;
+        type Resolver = ();
+
+        fn resolve(&self, _: Self::Resolver, out: Place<Self::Archived>) {
+            if self.0 == PANIC {
+                panic!("resolving a flaky value panicked");
+            }
+            self.0.resolve((), out);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl<S> Serialize<S> for Flaky
+    where
+        S: Fallible + Writer + ?Sized,
+        S::Error: Source,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            serializer.write(&[0xaa; 16])?;
+            if self.0 == FAIL {
+                rancor::fail!(FlakyError);
+            }
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn flaky_map(values: core::ops::Range<u32>) -> HashMap<u32, Flaky> {
+        values.map(|i| (i, Flaky(i))).collect()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn failed_serialization_returns_scratch() {
+        use rancor::Failure;
+
+        use crate::{
+            ser::{
+                allocator::{AllocationTracker, Arena},
+                sharing::Share,
+                writer::Buffer,
+                Serializer,
+            },
+            util::{serialize, serialize_into, Align, AlignedVec},
+        };
+
+        let failing = flaky_map(0..10);
+        let passing = flaky_map(10..20);
+        let mut arena = Arena::new();
+
+        // Serialization fails while serializing an item, before the table is
+        // written.
+        let mut serializer = Serializer::new(
+            AlignedVec::<16>::new(),
+            AllocationTracker::new(arena.acquire()),
+            Share::new(),
+        );
+        let checkpoint = serializer.checkpoint();
+        for _ in 0..10_000 {
+            let result = serialize::<_, Failure>(&failing, &mut serializer);
+            assert!(result.is_err());
+            assert_eq!(serializer.allocator.stats().allocations(), 0);
+            assert_eq!(serializer.allocator.stats().bytes_allocated(), 0);
+            serializer.reset(checkpoint);
+        }
+        serialize::<_, Failure>(&passing, &mut serializer).unwrap();
+        let stats = serializer.into_raw_parts().1.into_stats();
+        let capacity = stats.max_bytes_allocated;
+
+        // Serialization fails while writing the table, after its storage was
+        // allocated.
+        let mut buffer = Align([0u8; 256]);
+        let mut serializer = Serializer::new(
+            Buffer::from(&mut *buffer),
+            AllocationTracker::new(arena.acquire()),
+            Share::new(),
+        );
+        let checkpoint = serializer.checkpoint();
+        for _ in 0..10_000 {
+            let result = serialize::<_, Failure>(&passing, &mut serializer);
+            assert!(result.is_err());
+            assert_eq!(serializer.allocator.stats().allocations(), 0);
+            serializer.reset(checkpoint);
+        }
+        let stats = serializer.into_raw_parts().1.into_stats();
+        assert_eq!(stats.max_bytes_allocated, capacity);
+
+        // A fresh serialization with the same arena succeeds.
+        let serializer = serialize_into::<_, Failure>(
+            &passing,
+            Serializer::new(AlignedVec::<16>::new(), arena.acquire(), ()),
+        )
+        .unwrap();
+        let bytes = serializer.into_writer();
+        let archived =
+            crate::access::<Archived<HashMap<u32, Flaky>>, Failure>(&bytes)
+                .unwrap();
+        assert_eq!(archived.len(), 10);
+        assert!(archived
+            .values()
+            .all(|value| (10..20).contains(&value.to_native())));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn panicking_resolve_returns_scratch() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        use rancor::Failure;
+
+        use crate::{
+            ser::{
+                allocator::{AllocationTracker, Arena},
+                sharing::Share,
+                Serializer,
+            },
+            util::{serialize, AlignedVec},
+        };
+
+        // Skip the value which fails so that serialization reaches the value
+        // which panics.
+        let panicking = flaky_map(FAIL + 1..10);
+        let passing = flaky_map(10..20);
+        let mut arena = Arena::new();
+        let mut serializer = Serializer::new(
+            AlignedVec::<16>::new(),
+            AllocationTracker::new(arena.acquire()),
+            Share::new(),
+        );
+        let checkpoint = serializer.checkpoint();
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            serialize::<_, Failure>(&panicking, &mut serializer)
+        }));
+        assert!(result.is_err());
+        assert_eq!(serializer.allocator.stats().allocations(), 0);
+        assert_eq!(serializer.allocator.stats().bytes_allocated(), 0);
+
+        serializer.reset(checkpoint);
+        serialize::<_, Failure>(&passing, &mut serializer).unwrap();
+        let bytes = serializer.into_writer();
+        let archived =
+            crate::access::<Archived<HashMap<u32, Flaky>>, Failure>(&bytes)
+                .unwrap();
+        assert_eq!(archived.len(), 10);
+    }
+}
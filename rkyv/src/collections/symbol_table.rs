@@ -0,0 +1,541 @@
+//! Archived symbol tables and hash maps keyed by symbols.
+//!
+//! Hash maps with string keys store a copy of each key, so many maps with the
+//! same keys duplicate the bytes of those keys. Archiving those maps with
+//! [`SymbolKeys`](crate::with::SymbolKeys) replaces each key with a `u32`
+//! symbol, and the strings for all of the symbols are stored once in an
+//! [`ArchivedSymbolTable`].
+//!
+//! Symbols are assigned by the serializer's [`Interning`] strategy, such as a
+//! [`SymbolTable`](crate::ser::sharing::SymbolTable). The symbol table is
+//! archived by serializing a [`Symbols`], which must be serialized after every
+//! map that uses its symbols. Fields are serialized in declaration order, so a
+//! `Symbols` field should be the last field of the struct that contains the
+//! maps.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use rkyv::{
+//!     collections::symbol_table::Symbols,
+//!     rancor::Error,
+//!     ser::{allocator::Arena, sharing::SymbolTable, SerializerBuilder},
+//!     util::serialize_into,
+//!     with::SymbolKeys,
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[rkyv(check_bytes)]
+//! struct Document {
+//!     #[with(SymbolKeys)]
+//!     first: HashMap<String, u32>,
+//!     #[with(SymbolKeys)]
+//!     second: HashMap<String, u32>,
+//!     symbols: Symbols,
+//! }
+//!
+//! let value = Document {
+//!     first: [("alpha".to_string(), 1), ("beta".to_string(), 2)].into(),
+//!     second: [("beta".to_string(), 3), ("gamma".to_string(), 4)].into(),
+//!     symbols: Symbols,
+//! };
+//!
+//! let mut arena = Arena::new();
+//! let serializer = SerializerBuilder::high_level(&mut arena)
+//!     .sharing(SymbolTable::new())
+//!     .build::<Error>();
+//! let bytes = serialize_into::<_, Error>(&value, serializer)
+//!     .unwrap()
+//!     .into_writer();
+//!
+//! let archived = rkyv::access::<ArchivedDocument, Error>(&bytes).unwrap();
+//! // "beta" is only stored once
+//! assert_eq!(archived.symbols.len(), 3);
+//! let second = archived.second.get_str("beta", &archived.symbols);
+//! assert_eq!(second.map(|v| v.to_native()), Some(3));
+//! assert!(archived.first.get_str("gamma", &archived.symbols).is_none());
+//! ```
+//!
+//! [`Interning`]: crate::ser::Interning
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use munge::munge;
+#[cfg(feature = "alloc")]
+use rancor::Source;
+use rancor::{Fallible, ResultExt as _};
+
+#[cfg(feature = "alloc")]
+use crate::ser::Interning;
+use crate::{
+    collections::swiss_table::{
+        map::Iter, ArchivedHashMap, ArchivedHashTable, HashMapResolver,
+        HashTableResolver,
+    },
+    hash::{hash_value, FxHasher64},
+    primitive::ArchivedU32,
+    ser::{Allocator, Writer},
+    string::ArchivedString,
+    util::SerVec,
+    vec::{ArchivedVec, VecResolver},
+    Archive, Deserialize, Place, Portable, Serialize,
+};
+
+const LOAD_FACTOR: (usize, usize) = (7, 8);
+
+/// An archived table of interned strings.
+///
+/// Each string is identified by its `u32` symbol.
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(C)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+pub struct ArchivedSymbolTable {
+    strings: ArchivedVec<ArchivedString>,
+    index: ArchivedHashTable<ArchivedU32>,
+}
+
+impl ArchivedSymbolTable {
+    /// Returns the number of strings in the symbol table.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns whether the symbol table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Returns the string for the given symbol, or `None` if the symbol is not
+    /// in the symbol table.
+    pub fn get(&self, symbol: u32) -> Option<&str> {
+        self.strings.get(symbol as usize).map(|s| s.as_str())
+    }
+
+    /// Returns the symbol for the given string, or `None` if the string is not
+    /// in the symbol table.
+    pub fn symbol(&self, value: &str) -> Option<u32> {
+        let hash = hash_value::<str>(value);
+        self.index
+            .get_with(hash, |s| self.get(s.to_native()) == Some(value))
+            .map(|s| s.to_native())
+    }
+
+    /// Returns the strings in the symbol table, in order of their symbols.
+    pub fn strings(&self) -> &[ArchivedString] {
+        self.strings.as_slice()
+    }
+}
+
+impl fmt::Debug for ArchivedSymbolTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.strings.iter()).finish()
+    }
+}
+
+/// Archives the strings interned by the serializer as an
+/// [`ArchivedSymbolTable`].
+///
+/// Serializing a `Symbols` takes the interned strings from the serializer's
+/// [`Interning`](crate::ser::Interning) strategy, so it must be serialized
+/// after every value which interns strings. Deserializing an
+/// `ArchivedSymbolTable` as a `Symbols` discards the strings.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Symbols;
+
+/// The resolver for [`Symbols`].
+pub struct SymbolsResolver {
+    len: usize,
+    strings: VecResolver,
+    index: HashTableResolver,
+}
+
+impl Archive for Symbols {
+    type Archived = ArchivedSymbolTable;
+    type Resolver = SymbolsResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        munge!(let ArchivedSymbolTable { strings, index } = out);
+        ArchivedVec::resolve_from_len(resolver.len, resolver.strings, strings);
+        ArchivedHashTable::resolve_from_len(
+            resolver.len,
+            LOAD_FACTOR,
+            resolver.index,
+            index,
+        );
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<S> Serialize<S> for Symbols
+where
+    S: Fallible + Interning + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        let strings = serializer.take_interned()?;
+        let strings_resolver =
+            ArchivedVec::<ArchivedString>::serialize_from_slice(
+                strings.as_slice(),
+                serializer,
+            )?;
+        let index = ArchivedHashTable::<ArchivedU32>::serialize_from_iter(
+            strings
+                .iter()
+                .enumerate()
+                .map(|(i, s)| (hash_value::<str>(s), i as u32)),
+            LOAD_FACTOR,
+            serializer,
+        )?;
+
+        Ok(SymbolsResolver {
+            len: strings.len(),
+            strings: strings_resolver,
+            index,
+        })
+    }
+}
+
+impl<D: Fallible + ?Sized> Deserialize<Symbols, D> for ArchivedSymbolTable {
+    fn deserialize(&self, _: &mut D) -> Result<Symbols, D::Error> {
+        Ok(Symbols)
+    }
+}
+
+/// An archived hash map with string keys which are stored as symbols.
+///
+/// The strings for the symbols are stored in an [`ArchivedSymbolTable`].
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(transparent)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+pub struct ArchivedSymbolMap<V> {
+    inner: ArchivedHashMap<ArchivedU32, V>,
+}
+
+impl<V> ArchivedSymbolMap<V> {
+    /// Returns the number of entries in the map.
+    pub const fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns whether the map is empty.
+    pub const fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns the value for the given symbol.
+    pub fn get(&self, symbol: u32) -> Option<&V> {
+        self.inner.get(&symbol)
+    }
+
+    /// Returns the value for the given key, using `symbols` to find the symbol
+    /// for the key.
+    ///
+    /// `symbols` must be the symbol table that the map was serialized with.
+    pub fn get_str(
+        &self,
+        key: &str,
+        symbols: &ArchivedSymbolTable,
+    ) -> Option<&V> {
+        self.get(symbols.symbol(key)?)
+    }
+
+    /// Returns an iterator over the symbols and values of the map.
+    pub fn iter(&self) -> Iter<'_, ArchivedU32, V, FxHasher64> {
+        self.inner.iter()
+    }
+
+    /// Returns the underlying hash map from symbols to values.
+    pub fn as_map(&self) -> &ArchivedHashMap<ArchivedU32, V> {
+        &self.inner
+    }
+
+    /// Resolves an archived symbol map from a given length.
+    pub fn resolve_from_len(
+        len: usize,
+        resolver: SymbolMapResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedSymbolMap { inner } = out);
+        ArchivedHashMap::resolve_from_len(len, LOAD_FACTOR, resolver.0, inner);
+    }
+
+    /// Serializes an iterator of string keys and values as a symbol map.
+    ///
+    /// Each key is interned with the serializer's
+    /// [`Interning`](crate::ser::Interning) strategy.
+    #[cfg(feature = "alloc")]
+    pub fn serialize_from_iter<'a, I, VU, S>(
+        iter: I,
+        serializer: &mut S,
+    ) -> Result<SymbolMapResolver, S::Error>
+    where
+        I: Clone + ExactSizeIterator<Item = (&'a str, &'a VU)>,
+        VU: 'a + Serialize<S, Archived = V>,
+        S: Fallible + Interning + Allocator + Writer + ?Sized,
+        S::Error: Source,
+    {
+        SerVec::with_capacity(serializer, iter.len(), |symbols, serializer| {
+            for (key, _) in iter.clone() {
+                symbols.push(serializer.intern(key)?);
+            }
+
+            ArchivedHashMap::<ArchivedU32, V>::serialize_from_iter(
+                symbols.iter().zip(iter.map(|(_, value)| value)),
+                LOAD_FACTOR,
+                serializer,
+            )
+            .map(SymbolMapResolver)
+        })?
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct UnknownSymbol {
+    symbol: u32,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for UnknownSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "symbol {} is not in the symbol table", self.symbol)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownSymbol {}
+
+#[cfg(feature = "std")]
+impl<V> ArchivedSymbolMap<V> {
+    /// Deserializes the symbol map into a `HashMap`, using `symbols` to find
+    /// the key for each symbol.
+    ///
+    /// `symbols` must be the symbol table that the map was serialized with.
+    pub fn deserialize_with_symbols<T, D>(
+        &self,
+        symbols: &ArchivedSymbolTable,
+        deserializer: &mut D,
+    ) -> Result<HashMap<String, T>, D::Error>
+    where
+        V: Deserialize<T, D>,
+        D: Fallible + ?Sized,
+        D::Error: Source,
+    {
+        let mut result = HashMap::with_capacity(self.len());
+        for (symbol, value) in self.iter() {
+            let symbol = symbol.to_native();
+            let key = symbols
+                .get(symbol)
+                .ok_or(UnknownSymbol { symbol })
+                .into_error()?;
+            result.insert(String::from(key), value.deserialize(deserializer)?);
+        }
+        Ok(result)
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for ArchivedSymbolMap<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(f)
+    }
+}
+
+/// The resolver for [`ArchivedSymbolMap`].
+pub struct SymbolMapResolver(HashMapResolver);
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use rancor::{Failure, Panic};
+
+    use super::{ArchivedSymbolMap, Symbols};
+    use crate::{
+        de::Pool,
+        rancor::Strategy,
+        ser::{
+            allocator::{Arena, ArenaHandle},
+            sharing::SymbolTable,
+            Serializer, SerializerBuilder,
+        },
+        test::access,
+        util::{serialize_into, AlignedVec},
+        with::SymbolKeys,
+        Archive, Serialize,
+    };
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Record {
+        #[with(SymbolKeys)]
+        fields: HashMap<String, u32>,
+    }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Records {
+        records: Vec<Record>,
+        symbols: Symbols,
+    }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct PlainRecords {
+        records: Vec<HashMap<String, u32>>,
+    }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate)]
+    struct SymbolsFirst {
+        symbols: Symbols,
+        #[with(SymbolKeys)]
+        fields: HashMap<String, u32>,
+    }
+
+    const KEYS: [&str; 4] = [
+        "a key which is long enough to be stored out of line",
+        "another key which is long enough to be stored out of line",
+        "short",
+        "yet another key which is long enough to be stored out of line",
+    ];
+
+    fn fields(i: u32) -> HashMap<String, u32> {
+        KEYS.iter()
+            .enumerate()
+            .filter(|(k, _)| (i as usize + k) % 4 != 0)
+            .map(|(k, key)| (key.to_string(), i * 10 + k as u32))
+            .collect()
+    }
+
+    fn serialize_with_symbols<T>(value: &T) -> AlignedVec
+    where
+        T: for<'a> Serialize<
+            Strategy<
+                Serializer<AlignedVec, ArenaHandle<'a>, SymbolTable>,
+                Panic,
+            >,
+        >,
+    {
+        let mut arena = Arena::new();
+        let serializer = SerializerBuilder::high_level(&mut arena)
+            .sharing(SymbolTable::new())
+            .build::<Panic>();
+        serialize_into(value, serializer).unwrap().into_writer()
+    }
+
+    #[test]
+    fn symbol_keys() {
+        let value = Records {
+            records: (0..100).map(|i| Record { fields: fields(i) }).collect(),
+            symbols: Symbols,
+        };
+        let bytes = serialize_with_symbols(&value);
+
+        access::<ArchivedRecords>(&bytes, |archived| {
+            let symbols = &archived.symbols;
+            assert_eq!(symbols.len(), KEYS.len());
+            for key in KEYS {
+                let symbol = symbols.symbol(key).unwrap();
+                assert_eq!(symbols.get(symbol), Some(key));
+            }
+            assert_eq!(symbols.symbol("missing"), None);
+            assert_eq!(symbols.get(KEYS.len() as u32), None);
+
+            let records = archived.records.iter().zip(value.records.iter());
+            for (archived, record) in records {
+                let map = &archived.fields;
+                assert_eq!(map.len(), record.fields.len());
+                for key in KEYS {
+                    assert_eq!(
+                        map.get_str(key, symbols).map(|v| v.to_native()),
+                        record.fields.get(key).copied(),
+                    );
+                }
+                assert!(map.get_str("missing", symbols).is_none());
+
+                let deserialized = map
+                    .deserialize_with_symbols::<u32, _>(
+                        symbols,
+                        Strategy::<_, Panic>::wrap(&mut Pool::new()),
+                    )
+                    .unwrap();
+                assert_eq!(deserialized, record.fields);
+            }
+        });
+    }
+
+    #[test]
+    fn size_reduction() {
+        let records = (0..100).map(fields).collect::<Vec<_>>();
+        let plain = PlainRecords {
+            records: records.clone(),
+        };
+        let interned = Records {
+            records: records
+                .into_iter()
+                .map(|fields| Record { fields })
+                .collect(),
+            symbols: Symbols,
+        };
+
+        let plain_bytes = crate::to_bytes::<Panic>(&plain).unwrap();
+        let interned_bytes = serialize_with_symbols(&interned);
+
+        // The long keys are stored once instead of once per record
+        assert!(
+            2 * interned_bytes.len() < plain_bytes.len(),
+            "interned: {} bytes, plain: {} bytes",
+            interned_bytes.len(),
+            plain_bytes.len(),
+        );
+    }
+
+    #[test]
+    fn symbols_before_keys() {
+        let value = SymbolsFirst {
+            symbols: Symbols,
+            fields: fields(1),
+        };
+
+        let mut arena = Arena::new();
+        let serializer = SerializerBuilder::high_level(&mut arena)
+            .sharing(SymbolTable::new())
+            .build::<Failure>();
+        assert!(serialize_into(&value, serializer).is_err());
+    }
+
+    #[test]
+    fn unknown_symbol() {
+        let value = Records {
+            records: vec![Record { fields: fields(1) }],
+            symbols: Symbols,
+        };
+        let bytes = serialize_with_symbols(&value);
+        let empty = serialize_with_symbols(&Symbols);
+
+        access::<ArchivedRecords>(&bytes, |archived| {
+            access::<super::ArchivedSymbolTable>(&empty, |empty| {
+                let map: &ArchivedSymbolMap<_> = &archived.records[0].fields;
+                assert!(map.get_str(KEYS[1], empty).is_none());
+                assert!(map
+                    .deserialize_with_symbols::<u32, _>(
+                        empty,
+                        Strategy::<_, Failure>::wrap(&mut Pool::new()),
+                    )
+                    .is_err());
+            });
+        });
+    }
+}
@@ -16,6 +16,7 @@ pub struct EntryAdapter<'a, K, V> {
 }
 
 /// A resolver for a key-value pair.
+#[derive(Default)]
 pub struct EntryResolver<K, V> {
     /// The key resolver.
     pub key: K,
@@ -0,0 +1,476 @@
+//! Iterators over one field of every element of an archived slice.
+//!
+//! Structs which derive `Archive` with `#[rkyv(derive_projections)]` get an
+//! extension trait for slices of their archived type, with one method for
+//! each field that returns a [`Column`] of references to that field of every
+//! element. Fields of fixed-size primitive types instead return a
+//! [`NativeColumn`], which copies the field out of every element as its native
+//! type.
+//!
+//! # Examples
+//!
+//! ```
+//! use rkyv::{rancor::Error, Archive, Serialize};
+//!
+//! #[derive(Archive, Serialize)]
+//! #[rkyv(check_bytes, derive_projections)]
+//! struct Sample {
+//!     timestamp: u64,
+//!     value: f32,
+//!     label: String,
+//! }
+//!
+//! let samples = (0..10)
+//!     .map(|i| Sample {
+//!         timestamp: 1000 + i,
+//!         value: i as f32 / 2.0,
+//!         label: format!("sample {i}"),
+//!     })
+//!     .collect::<Vec<_>>();
+//! let bytes = rkyv::to_bytes::<Error>(&samples).unwrap();
+//! let archived =
+//!     rkyv::access::<rkyv::Archived<Vec<Sample>>, Error>(&bytes).unwrap();
+//!
+//! let timestamps = archived.timestamps().collect_native::<Vec<_>>();
+//! assert_eq!(timestamps, (1000..1010).collect::<Vec<_>>());
+//! assert_eq!(archived.values().sum::<f32>(), 22.5);
+//! assert_eq!(archived.labels().nth(3).unwrap(), "sample 3");
+//! ```
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::vec::Vec;
+use core::{array, fmt, iter::FusedIterator, marker::PhantomData, slice};
+
+use crate::primitive::{
+    ArchivedChar, ArchivedF32, ArchivedF64, ArchivedI128, ArchivedI16,
+    ArchivedI32, ArchivedI64, ArchivedU128, ArchivedU16, ArchivedU32,
+    ArchivedU64,
+};
+
+/// An archived primitive which can be copied out as its native type.
+///
+/// This is implemented for the archived versions of `bool`, `char`, and the
+/// fixed-size integer and floating-point types.
+pub trait CopyNative: Copy {
+    /// The native type.
+    type Native: Copy;
+
+    /// Returns the native value of the archived primitive.
+    fn copy_native(&self) -> Self::Native;
+}
+
+macro_rules! impl_copy_native {
+    ($($archived:ty: $native:ty),* $(,)?) => {
+        $(
+            impl CopyNative for $archived {
+                type Native = $native;
+
+                #[inline]
+                fn copy_native(&self) -> Self::Native {
+                    self.to_native()
+                }
+            }
+        )*
+    };
+}
+
+impl_copy_native! {
+    ArchivedI16: i16,
+    ArchivedI32: i32,
+    ArchivedI64: i64,
+    ArchivedI128: i128,
+    ArchivedU16: u16,
+    ArchivedU32: u32,
+    ArchivedU64: u64,
+    ArchivedU128: u128,
+    ArchivedF32: f32,
+    ArchivedF64: f64,
+    ArchivedChar: char,
+}
+
+macro_rules! impl_copy_native_single_byte {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl CopyNative for $ty {
+                type Native = $ty;
+
+                #[inline]
+                fn copy_native(&self) -> Self::Native {
+                    *self
+                }
+            }
+        )*
+    };
+}
+
+impl_copy_native_single_byte!(bool, i8, u8);
+
+/// An iterator over references to one field of every element of a slice.
+///
+/// This is returned by the methods generated by
+/// `#[rkyv(derive_projections)]`.
+pub struct Column<'a, T, F> {
+    elements: slice::Iter<'a, T>,
+    offset: usize,
+    _phantom: PhantomData<&'a F>,
+}
+
+impl<'a, T, F> Column<'a, T, F> {
+    /// Returns a new `Column` over the field of each element of `elements`
+    /// which is `offset` bytes from the start of the element.
+    ///
+    /// # Safety
+    ///
+    /// `T` must have a field of type `F` at `offset`.
+    #[inline]
+    pub unsafe fn new(elements: &'a [T], offset: usize) -> Self {
+        Self {
+            elements: elements.iter(),
+            offset,
+            _phantom: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn project(&self, element: &'a T) -> &'a F {
+        let element = (element as *const T).cast::<u8>();
+        // SAFETY: The caller of `new` has guaranteed that `T` has a field of
+        // type `F` at `offset`.
+        unsafe { &*element.add(self.offset).cast::<F>() }
+    }
+
+    /// Returns the elements which have not been iterated over yet.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [T] {
+        self.elements.as_slice()
+    }
+}
+
+impl<'a, T, F: CopyNative> Column<'a, T, F> {
+    /// Returns an iterator which copies the remaining fields out as their
+    /// native type.
+    #[inline]
+    pub fn natives(self) -> NativeColumn<'a, T, F> {
+        NativeColumn { column: self }
+    }
+}
+
+impl<T, F> Clone for Column<'_, T, F> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            elements: self.elements.clone(),
+            offset: self.offset,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<T, F: fmt::Debug> fmt::Debug for Column<'_, T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<'a, T, F> Iterator for Column<'a, T, F> {
+    type Item = &'a F;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.elements.next().map(|element| self.project(element))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.elements.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.elements.nth(n).map(|element| self.project(element))
+    }
+}
+
+impl<T, F> DoubleEndedIterator for Column<'_, T, F> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.elements
+            .next_back()
+            .map(|element| self.project(element))
+    }
+}
+
+impl<T, F> ExactSizeIterator for Column<'_, T, F> {}
+
+impl<T, F> FusedIterator for Column<'_, T, F> {}
+
+/// The number of elements which [`NativeColumn`] copies fields from at once.
+const CHUNK_LEN: usize = 8;
+
+/// An iterator which copies one primitive field out of every element of a
+/// slice.
+///
+/// Besides iterating, the fields can be copied into a slice with
+/// [`copy_to_slice`](NativeColumn::copy_to_slice) or collected with
+/// [`collect_native`](NativeColumn::collect_native). Both copy the fields of
+/// several elements at a time, which is faster than iterating for large
+/// slices.
+pub struct NativeColumn<'a, T, F> {
+    column: Column<'a, T, F>,
+}
+
+impl<'a, T, F: CopyNative> NativeColumn<'a, T, F> {
+    /// Returns a new `NativeColumn` over the field of each element of
+    /// `elements` which is `offset` bytes from the start of the element.
+    ///
+    /// # Safety
+    ///
+    /// `T` must have a field of type `F` at `offset`.
+    #[inline]
+    pub unsafe fn new(elements: &'a [T], offset: usize) -> Self {
+        // SAFETY: The caller has guaranteed that `T` has a field of type `F`
+        // at `offset`.
+        unsafe { Column::new(elements, offset).natives() }
+    }
+
+    /// Returns the elements which have not been iterated over yet.
+    #[inline]
+    pub fn as_slice(&self) -> &'a [T] {
+        self.column.as_slice()
+    }
+
+    #[inline]
+    fn copy_chunks(self, mut f: impl FnMut(&[F::Native])) {
+        let column = self.column;
+        let mut chunks = column.as_slice().chunks_exact(CHUNK_LEN);
+        for chunk in &mut chunks {
+            let values: [F::Native; CHUNK_LEN] =
+                array::from_fn(|i| column.project(&chunk[i]).copy_native());
+            f(&values);
+        }
+        for element in chunks.remainder() {
+            f(&[column.project(element).copy_native()]);
+        }
+    }
+
+    /// Copies the remaining fields into `out`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `out` is not the number of remaining fields.
+    pub fn copy_to_slice(self, out: &mut [F::Native]) {
+        assert_eq!(
+            out.len(),
+            self.len(),
+            "destination and column have different lengths",
+        );
+        let mut out = out.iter_mut();
+        self.copy_chunks(|values| {
+            for (out, value) in (&mut out).zip(values) {
+                *out = *value;
+            }
+        });
+    }
+
+    /// Copies the remaining fields into a new collection.
+    ///
+    /// The fields are copied into a `Vec` which is then converted into `B`, so
+    /// collecting into a `Vec<_>`, `Box<[_]>`, or `Arc<[_]>` is fast.
+    #[cfg(feature = "alloc")]
+    pub fn collect_native<B: From<Vec<F::Native>>>(self) -> B {
+        let mut out = Vec::with_capacity(self.len());
+        self.copy_chunks(|values| out.extend_from_slice(values));
+        B::from(out)
+    }
+}
+
+impl<T, F> Clone for NativeColumn<'_, T, F> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            column: self.column.clone(),
+        }
+    }
+}
+
+impl<T, F> fmt::Debug for NativeColumn<'_, T, F>
+where
+    F: CopyNative,
+    F::Native: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+impl<T, F: CopyNative> Iterator for NativeColumn<'_, T, F> {
+    type Item = F::Native;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.column.next().map(F::copy_native)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.column.size_hint()
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        self.column.nth(n).map(F::copy_native)
+    }
+}
+
+impl<T, F: CopyNative> DoubleEndedIterator for NativeColumn<'_, T, F> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.column.next_back().map(F::copy_native)
+    }
+}
+
+impl<T, F: CopyNative> ExactSizeIterator for NativeColumn<'_, T, F> {}
+
+impl<T, F: CopyNative> FusedIterator for NativeColumn<'_, T, F> {}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{format, string::String, vec::Vec};
+
+    use crate::{test::to_archived, Archive, Serialize};
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes, derive_projections)]
+    struct Primitives {
+        flag: bool,
+        letter: char,
+        tiny: i8,
+        byte: u8,
+        short: i16,
+        word: u16,
+        int: i32,
+        dword: u32,
+        long: i64,
+        qword: u64,
+        huge: i128,
+        wide: u128,
+        single: f32,
+        double: f64,
+        name: String,
+        status: u8,
+        entry: u32,
+    }
+
+    fn primitives(len: usize) -> Vec<Primitives> {
+        (0..len)
+            .map(|i| Primitives {
+                flag: i % 3 == 0,
+                letter: char::from_u32('a' as u32 + i as u32 % 26).unwrap(),
+                tiny: -(i as i8),
+                byte: i as u8,
+                short: -(i as i16) * 3,
+                word: i as u16 * 5,
+                int: -(i as i32) * 7,
+                dword: i as u32 * 11,
+                long: -(i as i64) << 33,
+                qword: (i as u64) << 40,
+                huge: -(i as i128) << 100,
+                wide: (i as u128) << 120,
+                single: i as f32 * 0.5,
+                double: i as f64 * -0.25,
+                name: format!("a name which is out of line {}", i),
+                status: (i * 2) as u8,
+                entry: i as u32 + 1,
+            })
+            .collect()
+    }
+
+    macro_rules! check_fields {
+        ($values:ident, $archived:ident, $($method:ident: $field:ident),*) => {
+            $(
+                let expected =
+                    $values.iter().map(|v| v.$field).collect::<Vec<_>>();
+                assert_eq!($archived.$method().len(), $values.len());
+                assert_eq!($archived.$method().collect::<Vec<_>>(), expected);
+                assert_eq!(
+                    $archived.$method().collect_native::<Vec<_>>(),
+                    expected,
+                );
+                let mut copied = expected.clone();
+                copied.reverse();
+                $archived.$method().copy_to_slice(&mut copied);
+                assert_eq!(copied, expected);
+                assert_eq!(
+                    $archived.$method().rev().collect::<Vec<_>>(),
+                    expected.iter().copied().rev().collect::<Vec<_>>(),
+                );
+            )*
+        };
+    }
+
+    #[test]
+    fn project_primitives() {
+        // Lengths around the chunk length check the remainders.
+        for len in [0, 1, 7, 8, 9, 16, 100] {
+            let values = primitives(len);
+            to_archived(&values, |archived| {
+                check_fields!(
+                    values,
+                    archived,
+                    flags: flag,
+                    letters: letter,
+                    tinies: tiny,
+                    bytes: byte,
+                    shorts: short,
+                    words: word,
+                    ints: int,
+                    dwords: dword,
+                    longs: long,
+                    qwords: qword,
+                    huges: huge,
+                    wides: wide,
+                    singles: single,
+                    doubles: double,
+                    statuses: status,
+                    entries: entry
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn project_references() {
+        let values = primitives(20);
+        to_archived(&values, |archived| {
+            let names = archived.names();
+            assert_eq!(names.len(), 20);
+            for (name, value) in names.zip(values.iter()) {
+                assert_eq!(name.as_str(), value.name);
+            }
+
+            let mut names = archived.names();
+            assert_eq!(names.nth(5).unwrap(), "a name which is out of line 5");
+            assert_eq!(names.len(), 14);
+            assert_eq!(
+                names.next_back().unwrap(),
+                "a name which is out of line 19",
+            );
+            assert_eq!(names.as_slice().len(), 13);
+        });
+    }
+
+    #[test]
+    fn project_partially_iterated() {
+        let values = primitives(30);
+        to_archived(&values, |archived| {
+            let mut qwords = archived.qwords();
+            qwords.nth(10);
+            qwords.next_back();
+            let rest = qwords.collect_native::<Vec<_>>();
+            let expected =
+                values[11..29].iter().map(|v| v.qword).collect::<Vec<_>>();
+            assert_eq!(rest, expected);
+        });
+    }
+}
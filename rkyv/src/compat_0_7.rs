@@ -0,0 +1,324 @@
+//! Deprecated shims for the rkyv 0.7 API.
+//!
+//! These functions and types have the same names and generic parameters as
+//! their 0.7 counterparts so that large codebases can migrate to the 0.8 API
+//! gradually. Each one is implemented on top of the 0.8 API and is deprecated
+//! with a note naming its replacement, so every remaining use shows up as a
+//! compiler warning.
+//!
+//! The main differences from the 0.7 API are:
+//!
+//! - 0.8 accessors are generic over the _archived_ type, while 0.7 accessors
+//!   were generic over the unarchived type. `archived_root::<T>` is
+//!   `access_unchecked::<Archived<T>>`.
+//! - Errors are reported through [`rancor`]. The shims which can fail return a
+//!   [`rancor::Error`], which can hold any error.
+//! - Serializers no longer take the size of their scratch space as a const
+//!   generic parameter. The [`to_bytes`] shim accepts and ignores it.
+//! - As in 0.7, the root of an archive is located at the end of the buffer.
+//!   Values serialized at other positions can be accessed with
+//!   [`archived_value`] and [`check_archived_value`].
+
+use core::pin::Pin;
+
+use rancor::{Fallible, Panic};
+
+use crate::{
+    util::{access_pos_unchecked, access_unchecked, access_unchecked_mut},
+    Archive,
+};
+
+/// Accesses the archived root of a `T` at the end of the given bytes.
+///
+/// # Safety
+///
+/// The byte slice must represent an archived `T`, with the root of the archive
+/// stored at the end of the slice.
+#[deprecated(
+    since = "0.8.0",
+    note = "use `rkyv::access_unchecked::<Archived<T>>` instead"
+)]
+pub unsafe fn archived_root<T: Archive>(bytes: &[u8]) -> &T::Archived {
+    // SAFETY: The caller has guaranteed that an archived `T` is located at the
+    // root position in the byte slice.
+    unsafe { access_unchecked::<T::Archived>(bytes) }
+}
+
+/// Accesses the mutable archived root of a `T` at the end of the given bytes.
+///
+/// # Safety
+///
+/// The byte slice must represent an archived `T`, with the root of the archive
+/// stored at the end of the slice.
+#[deprecated(
+    since = "0.8.0",
+    note = "use `rkyv::access_unchecked_mut::<Archived<T>>` instead"
+)]
+pub unsafe fn archived_root_mut<T: Archive>(
+    bytes: Pin<&mut [u8]>,
+) -> Pin<&mut T::Archived> {
+    // SAFETY: The caller has guaranteed that an archived `T` is located at the
+    // root position in the byte slice.
+    unsafe { access_unchecked_mut::<T::Archived>(Pin::into_inner(bytes)) }
+}
+
+/// Accesses an archived `T` at the given position in the given bytes.
+///
+/// # Safety
+///
+/// An archived `T` must be located at the given position in the byte slice.
+#[deprecated(
+    since = "0.8.0",
+    note = "use `rkyv::util::access_pos_unchecked::<Archived<T>>` instead"
+)]
+pub unsafe fn archived_value<T: Archive>(
+    bytes: &[u8],
+    pos: usize,
+) -> &T::Archived {
+    // SAFETY: The caller has guaranteed that an archived `T` is located at
+    // `pos` in the byte slice.
+    unsafe { access_pos_unchecked::<T::Archived>(bytes, pos) }
+}
+
+/// A deserializer which doesn't support any contextual deserialization.
+///
+/// Deserializing with `Infallible` panics on errors instead of returning them.
+/// Types which share pointers, like `Rc` and `Arc`, need a deserializer which
+/// implements [`Pooling`](crate::de::Pooling) instead.
+#[deprecated(
+    since = "0.8.0",
+    note = "use `rkyv::deserialize::<T, _, Panic>(archived, &mut ())` instead"
+)]
+pub struct Infallible;
+
+#[allow(deprecated)]
+impl Fallible for Infallible {
+    type Error = Panic;
+}
+
+#[cfg(feature = "alloc")]
+mod alloc {
+    use rancor::{Error, Strategy};
+
+    use crate::{
+        de::Pool,
+        ser::DefaultSerializer,
+        util::{self, AlignedVec},
+        Archive, Deserialize, Serialize,
+    };
+
+    /// Serializes the given value and returns the resulting bytes.
+    ///
+    /// The scratch space size `N` is ignored.
+    #[deprecated(
+        since = "0.8.0",
+        note = "use `rkyv::to_bytes::<rancor::Error>` instead"
+    )]
+    pub fn to_bytes<T, const N: usize>(value: &T) -> Result<AlignedVec, Error>
+    where
+        T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+    {
+        util::to_bytes::<Error>(value)
+    }
+
+    /// Deserializes a `T` from the archive at the end of the given bytes
+    /// without validating it.
+    ///
+    /// # Safety
+    ///
+    /// The byte slice must represent an archived `T`, with the root of the
+    /// archive stored at the end of the slice.
+    #[deprecated(
+        since = "0.8.0",
+        note = "use `rkyv::from_bytes_unchecked::<T, rancor::Error>` instead"
+    )]
+    pub unsafe fn from_bytes_unchecked<T>(bytes: &[u8]) -> Result<T, Error>
+    where
+        T: Archive,
+        T::Archived: Deserialize<T, Strategy<Pool, Error>>,
+    {
+        // SAFETY: The caller has guaranteed that an archived `T` is located at
+        // the root position in the byte slice.
+        unsafe { util::from_bytes_unchecked::<T, Error>(bytes) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use self::alloc::*;
+
+#[cfg(all(feature = "alloc", feature = "bytecheck"))]
+mod validation {
+    use bytecheck::CheckBytes;
+    use rancor::{Error, Strategy};
+
+    use crate::{
+        de::Pool,
+        validation::{util, validators::DefaultValidator},
+        Archive, Deserialize,
+    };
+
+    /// Validates the archived root of a `T` at the end of the given bytes and
+    /// returns a reference to it.
+    #[deprecated(
+        since = "0.8.0",
+        note = "use `rkyv::access::<Archived<T>, rancor::Error>` instead"
+    )]
+    pub fn check_archived_root<T>(bytes: &[u8]) -> Result<&T::Archived, Error>
+    where
+        T: Archive,
+        T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Error>>,
+    {
+        util::access::<T::Archived, Error>(bytes)
+    }
+
+    /// Validates an archived `T` at the given position in the given bytes and
+    /// returns a reference to it.
+    #[deprecated(
+        since = "0.8.0",
+        note = "use `rkyv::validation::util::access_pos::<Archived<T>, \
+                rancor::Error>` instead"
+    )]
+    pub fn check_archived_value<T>(
+        bytes: &[u8],
+        pos: usize,
+    ) -> Result<&T::Archived, Error>
+    where
+        T: Archive,
+        T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Error>>,
+    {
+        util::access_pos::<T::Archived, Error>(bytes, pos)
+    }
+
+    /// Validates the archive at the end of the given bytes and deserializes a
+    /// `T` from it.
+    #[deprecated(
+        since = "0.8.0",
+        note = "use `rkyv::from_bytes::<T, rancor::Error>` instead"
+    )]
+    pub fn from_bytes<T>(bytes: &[u8]) -> Result<T, Error>
+    where
+        T: Archive,
+        T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Error>>
+            + Deserialize<T, Strategy<Pool, Error>>,
+    {
+        util::from_bytes::<T, Error>(bytes)
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "bytecheck"))]
+pub use self::validation::*;
+
+#[cfg(all(test, feature = "alloc", feature = "bytecheck"))]
+#[allow(deprecated)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    use core::pin::Pin;
+
+    use rancor::{Error, Failure};
+
+    use super::{
+        archived_root, archived_root_mut, archived_value, check_archived_root,
+        check_archived_value, from_bytes, from_bytes_unchecked, to_bytes,
+        Infallible,
+    };
+    use crate::{
+        access, access_unchecked,
+        ser::{allocator::Arena, sharing::Share, Serializer},
+        util::{serialize, AlignedVec},
+        Archive, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, derive(Debug))]
+    struct Example {
+        name: String,
+        values: Vec<u32>,
+    }
+
+    fn example() -> Example {
+        Example {
+            name: "hello world".to_string(),
+            values: vec![1, 2, 3, 4],
+        }
+    }
+
+    #[test]
+    fn to_bytes_ignores_scratch_size() {
+        let value = example();
+        let expected = crate::to_bytes::<Error>(&value).unwrap();
+        let expected = expected.as_slice();
+        assert_eq!(to_bytes::<_, 0>(&value).unwrap().as_slice(), expected);
+        assert_eq!(to_bytes::<_, 256>(&value).unwrap().as_slice(), expected);
+        assert_eq!(to_bytes::<_, 4096>(&value).unwrap().as_slice(), expected);
+    }
+
+    #[test]
+    fn access_root() {
+        let value = example();
+        let mut bytes = to_bytes::<_, 256>(&value).unwrap();
+
+        let old = unsafe { archived_root::<Example>(&bytes) };
+        let new = unsafe { access_unchecked::<ArchivedExample>(&bytes) };
+        assert!(core::ptr::eq(old, new));
+
+        let old = check_archived_root::<Example>(&bytes).unwrap();
+        let new = access::<ArchivedExample, Error>(&bytes).unwrap();
+        assert!(core::ptr::eq(old, new));
+        assert_eq!(old.name, value.name);
+
+        let archived = unsafe {
+            archived_root_mut::<Example>(Pin::new(bytes.as_mut_slice()))
+        };
+        assert_eq!(archived.values, value.values);
+    }
+
+    #[test]
+    fn access_value_at_position() {
+        // Serialize another value after the first one, so the first value is
+        // not at the root position.
+        let value = example();
+        let mut arena = Arena::new();
+        let mut serializer = Serializer::new(
+            AlignedVec::<16>::new(),
+            arena.acquire(),
+            Share::new(),
+        );
+        let pos = serialize::<_, Failure>(&value, &mut serializer).unwrap();
+        serialize::<_, Failure>(&42u32, &mut serializer).unwrap();
+        let bytes = serializer.into_writer();
+
+        let archived = unsafe { archived_value::<Example>(&bytes, pos) };
+        assert_eq!(archived.name, value.name);
+        let archived = check_archived_value::<Example>(&bytes, pos).unwrap();
+        assert_eq!(archived.values, value.values);
+        check_archived_value::<Example>(&bytes, bytes.len()).unwrap_err();
+    }
+
+    #[test]
+    fn deserialize_infallible() {
+        let value = example();
+        let bytes = to_bytes::<_, 256>(&value).unwrap();
+
+        let archived = unsafe { archived_root::<Example>(&bytes) };
+        let deserialized: Example =
+            archived.deserialize(&mut Infallible).unwrap();
+        assert_eq!(deserialized, value);
+
+        assert_eq!(from_bytes::<Example>(&bytes).unwrap(), value);
+        assert_eq!(
+            unsafe { from_bytes_unchecked::<Example>(&bytes).unwrap() },
+            crate::from_bytes::<Example, Error>(&bytes).unwrap(),
+        );
+
+        let mut bytes = bytes;
+        let len = bytes.len();
+        bytes[len - 1] = 0xff;
+        from_bytes::<Example>(&bytes).unwrap_err();
+        check_archived_root::<Example>(&bytes).unwrap_err();
+    }
+}
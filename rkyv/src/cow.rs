@@ -0,0 +1,660 @@
+//! An archived string or byte slice which is either stored inline or shared.
+
+use core::{
+    any::{type_name, TypeId},
+    borrow::Borrow,
+    cmp, fmt, hash,
+    marker::PhantomData,
+    ops::Deref,
+    ptr, slice,
+};
+
+use munge::munge;
+use ptr_meta::Pointee;
+use rancor::{Fallible, ResultExt as _, Source};
+
+use crate::{
+    primitive::{fixed_len, ArchivedIsize, ArchivedUsize},
+    ser::{Sharing, Writer, WriterExt as _},
+    Place, Portable, RawRelPtr,
+};
+
+/// The maximum number of bytes that can be stored inline in an
+/// [`ArchivedCow`].
+pub const INLINE_CAPACITY: usize = 14;
+
+const INLINE_TAG: u8 = 0;
+const SHARED_TAG: u8 = 1;
+
+#[derive(Clone, Copy, Portable)]
+#[rkyv(crate)]
+#[repr(C)]
+struct InlineRepr {
+    tag: u8,
+    len: u8,
+    bytes: [u8; INLINE_CAPACITY],
+}
+
+#[derive(Clone, Copy, Portable)]
+#[rkyv(crate)]
+#[repr(C)]
+struct SharedRepr {
+    tag: u8,
+    // The offset of a `RawRelPtr` to the length of the shared bytes, which are
+    // stored immediately after it. Storing the length with the bytes
+    // guarantees that every `ArchivedCow` which shares them agrees on their
+    // length.
+    offset: ArchivedIsize,
+}
+
+impl SharedRepr {
+    fn ptr(&self) -> &RawRelPtr {
+        // SAFETY: `RawRelPtr` is a transparent wrapper around its offset.
+        unsafe { &*(&self.offset as *const ArchivedIsize).cast::<RawRelPtr>() }
+    }
+}
+
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(C)]
+union CowRepr {
+    inline: InlineRepr,
+    shared: SharedRepr,
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for str {}
+    impl Sealed for [u8] {}
+}
+
+/// A type which can be stored in an [`ArchivedCow`].
+///
+/// This is implemented for `str` and `[u8]`.
+pub trait CowPointee:
+    Pointee<Metadata = usize> + sealed::Sealed + 'static
+{
+}
+
+impl CowPointee for str {}
+impl CowPointee for [u8] {}
+
+fn as_bytes<T: CowPointee + ?Sized>(value: &T) -> &[u8] {
+    // SAFETY: The pointer metadata of every `CowPointee` is its length in
+    // bytes.
+    unsafe {
+        slice::from_raw_parts(
+            (value as *const T).cast::<u8>(),
+            ptr_meta::metadata(value),
+        )
+    }
+}
+
+/// An archived string or byte slice which is either stored inline or shared.
+///
+/// Short values are stored inside the structure, and longer values are stored
+/// out-of-line along with their length. Out-of-line values are serialized
+/// through [`Sharing`], so values which share the same memory (e.g. clones of
+/// the same `Rc<str>`) are only serialized once. Both representations are
+/// accessed through [`get`](ArchivedCow::get).
+///
+/// `ArchivedCow` is usually serialized with the
+/// [`CowThreshold`](crate::with::CowThreshold) wrapper.
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(C)]
+pub struct ArchivedCow<T: ?Sized> {
+    repr: CowRepr,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: CowPointee + ?Sized> ArchivedCow<T> {
+    /// Returns whether the value is stored inline.
+    #[inline]
+    pub fn is_inline(&self) -> bool {
+        // SAFETY: Both representations start with their tag, and every bit
+        // pattern is valid for it.
+        unsafe { self.repr.inline.tag == INLINE_TAG }
+    }
+
+    /// Returns whether the value is shared with other `ArchivedCow`s.
+    #[inline]
+    pub fn is_shared(&self) -> bool {
+        !self.is_inline()
+    }
+
+    /// Returns the bytes of the value.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        if self.is_inline() {
+            // SAFETY: The representation is inline, and the length of inline
+            // values is always at most `INLINE_CAPACITY`.
+            unsafe {
+                let inline = &self.repr.inline;
+                inline.bytes.get_unchecked(..inline.len as usize)
+            }
+        } else {
+            // SAFETY: The representation is shared, so its pointer points to
+            // the length of the shared bytes followed by the bytes.
+            unsafe {
+                let len =
+                    self.repr.shared.ptr().as_ptr().cast::<ArchivedUsize>();
+                slice::from_raw_parts(
+                    len.add(1).cast::<u8>(),
+                    (*len).to_native() as usize,
+                )
+            }
+        }
+    }
+
+    /// Returns the length of the value in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.as_bytes().len()
+    }
+
+    /// Returns whether the value is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the value.
+    #[inline]
+    pub fn get(&self) -> &T {
+        let bytes = self.as_bytes();
+        // SAFETY: `bytes` were serialized from a `T` and were checked to be a
+        // valid `T` during validation.
+        unsafe {
+            &*ptr_meta::from_raw_parts(bytes.as_ptr().cast(), bytes.len())
+        }
+    }
+
+    /// Resolves an archived `Cow` from a given reference.
+    pub fn resolve_from_ref(
+        value: &T,
+        resolver: CowResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedCow { repr, _phantom: _ } = out);
+        if let Some(pos) = resolver.pos {
+            munge! {
+                let CowRepr { shared: SharedRepr { tag, offset } } = repr;
+            }
+            tag.write(SHARED_TAG);
+            // SAFETY: `RawRelPtr` is a transparent wrapper around its offset.
+            let ptr = unsafe { offset.cast_unchecked::<RawRelPtr>() };
+            RawRelPtr::emplace(pos, ptr);
+        } else {
+            let bytes = as_bytes(value);
+            munge! {
+                let CowRepr { inline: InlineRepr { tag, len, bytes: out } } =
+                    repr;
+            }
+            tag.write(INLINE_TAG);
+            len.write(bytes.len() as u8);
+            // SAFETY: The resolver is only inline if the length of `value` is
+            // at most `INLINE_CAPACITY`.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    bytes.as_ptr(),
+                    out.ptr().cast::<u8>(),
+                    bytes.len(),
+                );
+            }
+        }
+    }
+
+    /// Serializes an archived `Cow` from a given reference.
+    ///
+    /// Values with a length of at most `inline_threshold` bytes are stored
+    /// inline, and longer values are shared. Thresholds greater than
+    /// [`INLINE_CAPACITY`] are treated as `INLINE_CAPACITY`.
+    pub fn serialize_from_ref<S>(
+        value: &T,
+        inline_threshold: usize,
+        serializer: &mut S,
+    ) -> Result<CowResolver, S::Error>
+    where
+        S: Fallible + Writer + Sharing + ?Sized,
+        S::Error: Source,
+    {
+        let bytes = as_bytes(value);
+        if bytes.len() <= inline_threshold.min(INLINE_CAPACITY) {
+            return Ok(CowResolver { pos: None });
+        }
+
+        // Out-of-line values are shared by their address and type, so they are
+        // kept apart from `Rc`s and `Arc`s of the same value, which are shared
+        // in a different format.
+        let address = value as *const T as *const () as usize;
+        let type_id = TypeId::of::<ArchivedCow<T>>();
+        serializer.check_shared_type(type_name::<T>())?;
+        let pos = if let Some(pos) =
+            serializer.get_typed_shared_ptr(address, type_id)
+        {
+            pos
+        } else {
+            let len = fixed_len(bytes.len())?;
+            serializer.align_for::<ArchivedUsize>()?;
+            // SAFETY: We just aligned the serializer for an `ArchivedUsize`,
+            // which is the archived type of `FixedUsize`.
+            let pos = unsafe { serializer.resolve_aligned(&len, ())? };
+            serializer.write(bytes)?;
+            serializer.add_typed_shared_ptr(address, type_id, pos)?;
+            pos
+        };
+
+        Ok(CowResolver { pos: Some(pos) })
+    }
+}
+
+impl<T: CowPointee + ?Sized> AsRef<T> for ArchivedCow<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T: CowPointee + ?Sized> Borrow<T> for ArchivedCow<T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.get()
+    }
+}
+
+impl<T: CowPointee + ?Sized> Deref for ArchivedCow<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+impl<T: CowPointee + fmt::Debug + ?Sized> fmt::Debug for ArchivedCow<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<T: CowPointee + fmt::Display + ?Sized> fmt::Display for ArchivedCow<T> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl<T: CowPointee + Eq + ?Sized> Eq for ArchivedCow<T> {}
+
+impl<T: CowPointee + hash::Hash + ?Sized> hash::Hash for ArchivedCow<T> {
+    #[inline]
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.get().hash(state)
+    }
+}
+
+impl<T: CowPointee + Ord + ?Sized> Ord for ArchivedCow<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.get().cmp(other.get())
+    }
+}
+
+impl<T: CowPointee + PartialEq + ?Sized> PartialEq for ArchivedCow<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: CowPointee + PartialEq + ?Sized> PartialEq<T> for ArchivedCow<T> {
+    #[inline]
+    fn eq(&self, other: &T) -> bool {
+        self.get() == other
+    }
+}
+
+impl<T: CowPointee + PartialOrd + ?Sized> PartialOrd for ArchivedCow<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.get().partial_cmp(other.get())
+    }
+}
+
+/// The resolver for [`ArchivedCow`].
+pub struct CowResolver {
+    pos: Option<usize>,
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::{alloc::Layout, any::TypeId, fmt};
+
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        CheckBytes,
+    };
+    use rancor::{fail, ResultExt as _};
+
+    use super::{
+        ArchivedCow, CowPointee, InlineRepr, SharedRepr, INLINE_CAPACITY,
+        INLINE_TAG, SHARED_TAG,
+    };
+    use crate::{
+        primitive::ArchivedUsize,
+        validation::{ArchiveContext, ArchiveContextExt, SharedContext},
+    };
+
+    #[derive(Debug)]
+    struct InvalidCowTag {
+        tag: u8,
+    }
+
+    impl fmt::Display for InvalidCowTag {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "invalid cow tag: expected {} or {}, found {}",
+                INLINE_TAG, SHARED_TAG, self.tag,
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for InvalidCowTag {}
+
+    #[derive(Debug)]
+    struct InlineCowTooLong {
+        len: u8,
+    }
+
+    impl fmt::Display for InlineCowTooLong {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "inline cow length {} exceeds the inline capacity of {}",
+                self.len, INLINE_CAPACITY,
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for InlineCowTooLong {}
+
+    unsafe impl<T, C> CheckBytes<C> for ArchivedCow<T>
+    where
+        T: CowPointee + CheckBytes<C> + ?Sized + 'static,
+        C: Fallible + ArchiveContext + SharedContext + ?Sized,
+        C::Error: Source,
+    {
+        unsafe fn check_bytes(
+            value: *const Self,
+            context: &mut C,
+        ) -> Result<(), C::Error> {
+            // SAFETY: Both representations start with their tag, and every bit
+            // pattern is valid for it.
+            let tag = unsafe { *value.cast::<u8>() };
+            match tag {
+                INLINE_TAG => {
+                    // SAFETY: Every bit pattern is valid for `InlineRepr`.
+                    let inline = unsafe { &*value.cast::<InlineRepr>() };
+                    if inline.len as usize > INLINE_CAPACITY {
+                        fail!(InlineCowTooLong { len: inline.len });
+                    }
+                    let ptr = ptr_meta::from_raw_parts::<T>(
+                        inline.bytes.as_ptr().cast(),
+                        inline.len as usize,
+                    );
+                    // SAFETY: `ptr` points to the first `len` bytes of the
+                    // inline representation.
+                    unsafe { T::check_bytes(ptr, context) }
+                }
+                SHARED_TAG => {
+                    // SAFETY: Every bit pattern is valid for `SharedRepr`.
+                    let shared = unsafe { &*value.cast::<SharedRepr>() };
+                    let len_ptr =
+                        shared.ptr().as_ptr_wrapping().cast::<ArchivedUsize>();
+                    let type_id = TypeId::of::<ArchivedCow<T>>();
+
+                    let is_new = context
+                        .register_shared_ptr(len_ptr as usize, type_id)?;
+                    if !is_new {
+                        return Ok(());
+                    }
+
+                    context.check_subtree_ptr(
+                        len_ptr.cast(),
+                        &Layout::new::<ArchivedUsize>(),
+                    )?;
+                    // SAFETY: We checked that `len_ptr` is properly aligned
+                    // and points to an `ArchivedUsize` inside the archive.
+                    let len = unsafe { (*len_ptr).to_native() as usize };
+                    let (layout, offset) = Layout::new::<ArchivedUsize>()
+                        .extend(Layout::array::<u8>(len).into_error()?)
+                        .into_error()?;
+
+                    context.in_subtree_raw(len_ptr.cast(), layout, |context| {
+                        let ptr = ptr_meta::from_raw_parts::<T>(
+                            len_ptr.cast::<u8>().wrapping_add(offset).cast(),
+                            len,
+                        );
+                        // SAFETY: `in_subtree_raw` has guaranteed that the
+                        // `len` bytes after the length are located inside the
+                        // archive.
+                        unsafe { T::check_bytes(ptr, context) }
+                    })
+                }
+                tag => fail!(InvalidCowTag { tag }),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        format,
+        rc::Rc,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    #[cfg(feature = "std")]
+    use std::rc::Rc;
+
+    use rancor::{Failure, Panic};
+
+    use super::INLINE_CAPACITY;
+    use crate::{
+        access,
+        test::{deserialize, to_archived},
+        to_bytes,
+        util::AlignedVec,
+        with::CowThreshold,
+        Archive, Archived, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Entry {
+        #[with(CowThreshold<INLINE_CAPACITY>)]
+        name: Rc<str>,
+        #[with(CowThreshold<0>)]
+        data: Rc<[u8]>,
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct PlainEntry {
+        name: String,
+        data: Vec<u8>,
+    }
+
+    #[test]
+    fn inline_and_shared() {
+        let long = Rc::<str>::from("a string which is too long to be inline");
+        let bytes = Rc::<[u8]>::from(&[1, 2, 3][..]);
+        let value = vec![
+            Entry {
+                name: Rc::from("short"),
+                data: bytes.clone(),
+            },
+            Entry {
+                name: long.clone(),
+                data: bytes.clone(),
+            },
+            Entry {
+                name: long,
+                data: Rc::from(&[][..]),
+            },
+        ];
+
+        to_archived(&value, |archived| {
+            assert!(archived[0].name.is_inline());
+            assert_eq!(archived[0].name, *"short");
+            assert!(archived[1].name.is_shared());
+            assert_eq!(
+                archived[1].name.get(),
+                "a string which is too long to be inline",
+            );
+            assert!(core::ptr::eq(
+                archived[1].name.get(),
+                archived[2].name.get(),
+            ));
+
+            assert!(archived[0].data.is_shared());
+            assert_eq!(archived[0].data.as_bytes(), &[1, 2, 3]);
+            assert!(core::ptr::eq(
+                archived[0].data.get(),
+                archived[1].data.get(),
+            ));
+            assert!(archived[2].data.is_inline());
+            assert!(archived[2].data.is_empty());
+
+            assert_eq!(deserialize::<Vec<Entry>>(archived), value);
+        });
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Mixed {
+        #[with(CowThreshold<0>)]
+        cow: Rc<str>,
+        rc: Rc<str>,
+    }
+
+    #[test]
+    fn shared_with_rc() {
+        let name = Rc::<str>::from("a string which is also serialized as Rc");
+        let value = vec![
+            Mixed {
+                cow: name.clone(),
+                rc: name.clone(),
+            },
+            Mixed {
+                cow: name.clone(),
+                rc: name,
+            },
+        ];
+
+        to_archived(&value, |archived| {
+            for mixed in archived.iter() {
+                assert_eq!(
+                    mixed.cow,
+                    *"a string which is also serialized as Rc"
+                );
+                assert_eq!(
+                    &*mixed.rc,
+                    "a string which is also serialized as Rc"
+                );
+            }
+            assert!(core::ptr::eq(
+                archived[0].cow.get(),
+                archived[1].cow.get(),
+            ));
+            assert!(core::ptr::eq(&*archived[0].rc, &*archived[1].rc));
+
+            assert_eq!(deserialize::<Vec<Mixed>>(archived), value);
+        });
+    }
+
+    #[test]
+    fn duplicate_corpus_size() {
+        let shared = (0..10)
+            .map(|i| Rc::<str>::from(format!("shared value {:0>50}", i)))
+            .collect::<Vec<_>>();
+        let names = (0..1000)
+            .map(|i| {
+                if i % 10 == 0 {
+                    Rc::<str>::from(format!("unique value {:0>50}", i))
+                } else {
+                    shared[i % shared.len()].clone()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let cows = names
+            .iter()
+            .map(|name| Entry {
+                name: name.clone(),
+                data: Rc::from(&[][..]),
+            })
+            .collect::<Vec<_>>();
+        let plain = names
+            .iter()
+            .map(|name| PlainEntry {
+                name: name.to_string(),
+                data: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        let cow_bytes = to_bytes::<Panic>(&cows).unwrap();
+        let plain_bytes = to_bytes::<Panic>(&plain).unwrap();
+        // Each of the 110 distinct names is only serialized once
+        assert!(cow_bytes.len() * 2 < plain_bytes.len());
+
+        let archived =
+            access::<Archived<Vec<Entry>>, Panic>(&cow_bytes).unwrap();
+        for (archived, name) in archived.iter().zip(names.iter()) {
+            assert_eq!(archived.name, **name);
+        }
+    }
+
+    #[test]
+    fn invalid_archives() {
+        let value = Entry {
+            name: Rc::from("short"),
+            data: Rc::from(&[1, 2, 3][..]),
+        };
+        let bytes = to_bytes::<Panic>(&value).unwrap();
+        let root = bytes.len() - core::mem::size_of::<ArchivedEntry>();
+
+        // Invalid tag
+        let mut corrupted = AlignedVec::<16>::new();
+        corrupted.extend_from_slice(&bytes);
+        corrupted[root] = 2;
+        assert!(access::<ArchivedEntry, Failure>(&corrupted).is_err());
+
+        // Inline length too long
+        corrupted.clear();
+        corrupted.extend_from_slice(&bytes);
+        corrupted[root + 1] = INLINE_CAPACITY as u8 + 1;
+        assert!(access::<ArchivedEntry, Failure>(&corrupted).is_err());
+
+        // Invalid UTF-8
+        corrupted.clear();
+        corrupted.extend_from_slice(&bytes);
+        corrupted[root + 2] = 0xff;
+        assert!(access::<ArchivedEntry, Failure>(&corrupted).is_err());
+
+        // Shared length past the end of the archive
+        corrupted.clear();
+        corrupted.extend_from_slice(&bytes);
+        corrupted[0] = 0xff;
+        assert!(access::<ArchivedEntry, Failure>(&corrupted).is_err());
+    }
+}
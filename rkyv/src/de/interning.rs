@@ -0,0 +1,163 @@
+//! Deserializers which intern strings.
+
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+use rancor::{Fallible, Strategy};
+
+use crate::de::pooling::{ErasedPtr, Pooling};
+
+/// A source of `&'static str`s.
+///
+/// Interners are supplied by the user to deserialize fields with
+/// [`Interned`](crate::with::Interned). An interner decides how each string
+/// gets a `'static` lifetime, for example by returning it from a fixed set of
+/// known strings or by leaking it the first time it's seen.
+pub trait StrInterner {
+    /// Returns a `'static` string equal to `value`.
+    fn intern(&mut self, value: &str) -> &'static str;
+}
+
+/// Leaks each distinct string the first time it's interned.
+///
+/// The leaked strings are never freed. Each distinct string is only leaked
+/// once per set, so memory use is bounded by the number of distinct strings
+/// rather than the number of deserialized values.
+#[cfg(feature = "std")]
+impl StrInterner for HashSet<&'static str> {
+    fn intern(&mut self, value: &str) -> &'static str {
+        if let Some(interned) = self.get(value) {
+            *interned
+        } else {
+            let interned = Box::leak(Box::<str>::from(value));
+            self.insert(interned);
+            interned
+        }
+    }
+}
+
+/// A deserialization strategy which interns strings.
+///
+/// This trait is required to deserialize fields with
+/// [`Interned`](crate::with::Interned).
+pub trait Interning<E = <Self as Fallible>::Error> {
+    /// Returns a `'static` string equal to `value`.
+    fn intern_str(&mut self, value: &str) -> Result<&'static str, E>;
+}
+
+impl<T, E> Interning<E> for Strategy<T, E>
+where
+    T: Interning<E> + ?Sized,
+{
+    #[inline]
+    fn intern_str(&mut self, value: &str) -> Result<&'static str, E> {
+        T::intern_str(self, value)
+    }
+}
+
+/// A deserializer which interns strings with a user-supplied [`StrInterner`].
+///
+/// Shared pointers are pooled by the wrapped pooling strategy.
+pub struct WithInterner<'a, P> {
+    pooling: P,
+    interner: &'a mut dyn StrInterner,
+}
+
+impl<'a, P> WithInterner<'a, P> {
+    /// Creates a new deserializer from a pooling strategy and an interner.
+    #[inline]
+    pub fn new(pooling: P, interner: &'a mut dyn StrInterner) -> Self {
+        Self { pooling, interner }
+    }
+
+    /// Consumes the deserializer and returns the pooling strategy.
+    #[inline]
+    pub fn into_pooling(self) -> P {
+        self.pooling
+    }
+}
+
+impl<P, E> Interning<E> for WithInterner<'_, P> {
+    #[inline]
+    fn intern_str(&mut self, value: &str) -> Result<&'static str, E> {
+        Ok(self.interner.intern(value))
+    }
+}
+
+impl<P: Pooling<E>, E> Pooling<E> for WithInterner<'_, P> {
+    #[inline]
+    fn get_shared_ptr(&mut self, address: usize) -> Option<ErasedPtr> {
+        self.pooling.get_shared_ptr(address)
+    }
+
+    #[inline]
+    unsafe fn add_shared_ptr(
+        &mut self,
+        address: usize,
+        ptr: ErasedPtr,
+        drop: unsafe fn(ErasedPtr),
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `add_shared_ptr` are the same as
+        // the requirements for calling this function.
+        unsafe { self.pooling.add_shared_ptr(address, ptr, drop) }
+    }
+
+    #[inline]
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        self.pooling.check_shared_type(type_name)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::HashSet;
+
+    use rancor::Panic;
+
+    use crate::{
+        de::{Pool, WithInterner},
+        test::{access, to_bytes},
+        with::Interned,
+        Archive, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Symbol {
+        #[with(Interned)]
+        name: &'static str,
+        id: u32,
+    }
+
+    #[test]
+    fn interned_static_str() {
+        let value = Symbol {
+            name: "a symbol name which is not inlined",
+            id: 42,
+        };
+
+        let mut interner = HashSet::<&'static str>::new();
+        to_bytes(&value, |bytes| {
+            access::<ArchivedSymbol>(bytes, |archived| {
+                let mut deserializer =
+                    WithInterner::new(Pool::new(), &mut interner);
+                let first = crate::deserialize::<Symbol, _, Panic>(
+                    archived,
+                    &mut deserializer,
+                )
+                .unwrap();
+                let second = crate::deserialize::<Symbol, _, Panic>(
+                    archived,
+                    &mut deserializer,
+                )
+                .unwrap();
+
+                assert_eq!(first, value);
+                assert_eq!(second, value);
+                // Both deserializations share the same interned string.
+                assert_eq!(first.name.as_ptr(), second.name.as_ptr());
+            });
+        });
+        assert_eq!(interner.len(), 1);
+    }
+}
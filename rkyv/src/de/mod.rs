@@ -1,11 +1,12 @@
 //! Deserialization traits, deserializers, and adapters.
 
+pub mod interning;
 pub mod pooling;
 
 use rancor::Strategy;
 
 #[doc(inline)]
-pub use self::pooling::*;
+pub use self::{interning::*, pooling::*};
 
 /// A deserializer suitable for environments where allocations cannot be made.
 pub type CoreDeserializer<E> = Strategy<Unpool, E>;
@@ -1,8 +1,9 @@
 //! Adapters wrap deserializers and add support for deserializer traits.
 
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
 use core::{fmt, mem::size_of};
 
-use hashbrown::hash_map::{Entry, HashMap};
 use rancor::{fail, Source};
 
 use super::{ErasedPtr, Pooling};
@@ -42,9 +43,20 @@ impl Drop for SharedPointer {
 
 /// A shared pointer strategy that pools together deserializations of the same
 /// shared pointer.
+///
+/// Shared pointers are pooled by their offset from the base of the archive.
+/// Pools created with [`for_archive`](Pool::for_archive) use the start of the
+/// given byte slice as the base, and can be [`reset`](Pool::reset) to
+/// deserialize from another buffer without reallocating. Pools created with
+/// [`new`](Pool::new) use a base of zero and so pool by absolute address.
+///
+/// If the archive is known to contain no shared pointers (or if duplicating the
+/// values of shared pointers is acceptable), [`Unpool`](super::Unpool) skips
+/// tracking shared pointers entirely.
 #[derive(Default)]
 pub struct Pool {
-    shared_pointers: HashMap<usize, SharedPointer>,
+    base: usize,
+    shared_pointers: OffsetMap<SharedPointer>,
 }
 
 impl Pool {
@@ -58,9 +70,35 @@ impl Pool {
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
-            shared_pointers: HashMap::with_capacity(capacity),
+            base: 0,
+            shared_pointers: OffsetMap::with_capacity(capacity),
         }
     }
+
+    /// Creates a new shared pointer unifier for deserializing from the given
+    /// archive bytes.
+    #[inline]
+    pub fn for_archive(bytes: &[u8]) -> Self {
+        Self {
+            base: bytes.as_ptr() as usize,
+            shared_pointers: OffsetMap::default(),
+        }
+    }
+
+    /// Drops all pooled shared pointers and prepares the pool for
+    /// deserializing from the given archive bytes.
+    ///
+    /// The memory used to track shared pointers is kept for reuse.
+    #[inline]
+    pub fn reset(&mut self, bytes: &[u8]) {
+        self.base = bytes.as_ptr() as usize;
+        self.shared_pointers.clear();
+    }
+
+    #[inline]
+    fn offset_of(&self, address: usize) -> usize {
+        address.wrapping_sub(self.base)
+    }
 }
 
 impl fmt::Debug for Pool {
@@ -70,24 +108,175 @@ impl fmt::Debug for Pool {
 }
 
 impl<E: Source> Pooling<E> for Pool {
+    #[inline]
     fn get_shared_ptr(&mut self, address: usize) -> Option<ErasedPtr> {
-        self.shared_pointers.get(&address).map(|p| p.ptr)
+        let offset = self.offset_of(address);
+        self.shared_pointers.get(offset).map(|p| p.ptr)
     }
 
+    #[inline]
     unsafe fn add_shared_ptr(
         &mut self,
         address: usize,
         ptr: ErasedPtr,
         drop: unsafe fn(ErasedPtr),
     ) -> Result<(), E> {
-        match self.shared_pointers.entry(address) {
-            Entry::Occupied(_) => {
-                fail!(DuplicateSharedPointer { address });
+        let offset = self.offset_of(address);
+        if !self
+            .shared_pointers
+            .insert_with(offset, || SharedPointer { ptr, drop })
+        {
+            fail!(DuplicateSharedPointer { address });
+        }
+        Ok(())
+    }
+}
+
+/// An open-addressing hash map from the offsets of shared pointers to values.
+///
+/// Archived shared values are aligned and located close together, so their
+/// offsets are hashed with a single multiplication instead of a
+/// general-purpose hasher. Collisions are resolved with linear probing.
+struct OffsetMap<V> {
+    slots: Vec<Option<(usize, V)>>,
+    len: usize,
+}
+
+impl<V> Default for OffsetMap<V> {
+    fn default() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<V> OffsetMap<V> {
+    const MIN_SLOTS: usize = 16;
+
+    fn with_capacity(capacity: usize) -> Self {
+        let mut result = Self::default();
+        if capacity > 0 {
+            result.resize(Self::slots_for(capacity));
+        }
+        result
+    }
+
+    /// Returns the number of slots needed to hold `len` entries without
+    /// exceeding the maximum load factor of 3/4.
+    fn slots_for(len: usize) -> usize {
+        (len.saturating_mul(4) / 3 + 1)
+            .max(Self::MIN_SLOTS)
+            .next_power_of_two()
+    }
+
+    #[inline]
+    fn index_of(&self, offset: usize) -> usize {
+        // Fibonacci hashing: the high bits of the product depend on all of the
+        // bits of the offset, including the low bits which vary the most.
+        let hash = (offset as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let bits = self.slots.len().trailing_zeros();
+        (hash >> (64 - bits)) as usize
+    }
+
+    #[inline]
+    fn find(&self, offset: usize) -> Result<usize, usize> {
+        let mask = self.slots.len() - 1;
+        let mut index = self.index_of(offset);
+        loop {
+            match &self.slots[index] {
+                Some((key, _)) if *key == offset => return Ok(index),
+                Some(_) => index = (index + 1) & mask,
+                None => return Err(index),
             }
-            Entry::Vacant(e) => {
-                e.insert(SharedPointer { ptr, drop });
-                Ok(())
+        }
+    }
+
+    #[inline]
+    fn get(&self, offset: usize) -> Option<&V> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let index = self.find(offset).ok()?;
+        self.slots[index].as_ref().map(|(_, value)| value)
+    }
+
+    /// Inserts the value returned by `f` for the given offset if the offset
+    /// is not already present. Returns `false` without calling `f` otherwise.
+    #[inline]
+    fn insert_with(&mut self, offset: usize, f: impl FnOnce() -> V) -> bool {
+        if (self.len + 1) * 4 > self.slots.len() * 3 {
+            self.resize(Self::slots_for(self.len + 1));
+        }
+
+        match self.find(offset) {
+            Ok(_) => false,
+            Err(index) => {
+                self.slots[index] = Some((offset, f()));
+                self.len += 1;
+                true
             }
         }
     }
+
+    /// Removes all entries while keeping the allocated slots.
+    fn clear(&mut self) {
+        if self.len > 0 {
+            self.slots.iter_mut().for_each(|slot| *slot = None);
+            self.len = 0;
+        }
+    }
+
+    fn resize(&mut self, slots: usize) {
+        let mut new_slots = Vec::with_capacity(slots);
+        new_slots.resize_with(slots, || None);
+        let old_slots = core::mem::replace(&mut self.slots, new_slots);
+
+        for (offset, value) in old_slots.into_iter().flatten() {
+            let index = match self.find(offset) {
+                Ok(index) | Err(index) => index,
+            };
+            self.slots[index] = Some((offset, value));
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&usize, &V)> {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|(offset, value)| (offset, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OffsetMap;
+
+    #[test]
+    fn offset_map() {
+        let mut map = OffsetMap::default();
+        assert_eq!(map.get(0x1000), None);
+
+        // Offsets which differ only in their high bits, and aligned
+        // offsets which differ only in a few low bits
+        let offsets = (0..1000)
+            .map(|i| i << (usize::BITS - 10))
+            .chain((1..1000).map(|i| 0x1000 + i * 8));
+        for (i, offset) in offsets.clone().enumerate() {
+            assert!(map.insert_with(offset, || i));
+            assert!(!map.insert_with(offset, || unreachable!()));
+        }
+        for (i, offset) in offsets.enumerate() {
+            assert_eq!(map.get(offset), Some(&i));
+        }
+        assert_eq!(map.get(0x1004), None);
+        assert_eq!(map.iter().count(), 1999);
+
+        map.clear();
+        assert_eq!(map.get(0x1008), None);
+        assert_eq!(map.iter().count(), 0);
+        assert!(map.insert_with(0x1008, || 0));
+        assert_eq!(map.get(0x1008), Some(&0));
+    }
 }
@@ -1,15 +1,40 @@
+use core::fmt;
+
+use rancor::{fail, Source};
+
 use super::{ErasedPtr, Pooling};
 
 /// A shared pointer strategy that duplicates deserializations of the same
 /// shared pointer.
+///
+/// This does not keep track of deserialized shared pointers at all, and so is
+/// faster than [`Pool`](super::Pool) when the archive is known to contain no
+/// shared pointers. Otherwise, each shared pointer to the same value is
+/// deserialized into a separate allocation.
+///
+/// # Examples
+///
+/// ```
+/// use rkyv::{de::Unpool, deserialize, rancor::Error, to_bytes, Archived};
+///
+/// let value = vec![Box::new(1), Box::new(2)];
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+/// let archived =
+///     unsafe { rkyv::access_unchecked::<Archived<Vec<Box<i32>>>>(&bytes) };
+/// let deserialized =
+///     deserialize::<Vec<Box<i32>>, _, Error>(archived, &mut Unpool).unwrap();
+/// assert_eq!(deserialized, value);
+/// ```
 #[derive(Debug, Default)]
 pub struct Unpool;
 
 impl<E> Pooling<E> for Unpool {
+    #[inline]
     fn get_shared_ptr(&mut self, _: usize) -> Option<ErasedPtr> {
         None
     }
 
+    #[inline]
     unsafe fn add_shared_ptr(
         &mut self,
         _: usize,
@@ -19,3 +44,75 @@ impl<E> Pooling<E> for Unpool {
         Ok(())
     }
 }
+
+/// An error which occurs when a shared pointer is deserialized with
+/// [`DenyPool`].
+#[derive(Debug)]
+pub struct PoolingDenied {
+    type_name: &'static str,
+}
+
+impl PoolingDenied {
+    /// Returns the name of the type of the shared value.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl fmt::Display for PoolingDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "attempted to deserialize a shared pointer to `{}` while pooling \
+             is denied",
+            self.type_name,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PoolingDenied {}
+
+/// A shared pointer strategy that returns an error whenever a shared pointer is
+/// deserialized.
+///
+/// This is the deserializing counterpart of
+/// [`DenyShared`](crate::ser::sharing::DenyShared). Deserializing a shared
+/// pointer like `Rc` or `Arc` fails with a [`PoolingDenied`] error which names
+/// the type of the shared value.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use rkyv::{de::DenyPool, deserialize, rancor::Error, to_bytes, Archived};
+///
+/// let bytes = to_bytes::<Error>(&Arc::new(42u32)).unwrap();
+/// let archived = rkyv::access::<Archived<Arc<u32>>, Error>(&bytes).unwrap();
+/// let result = deserialize::<Arc<u32>, _, Error>(archived, &mut DenyPool);
+/// assert!(result.is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct DenyPool;
+
+impl<E: Source> Pooling<E> for DenyPool {
+    #[inline]
+    fn get_shared_ptr(&mut self, _: usize) -> Option<ErasedPtr> {
+        None
+    }
+
+    #[inline]
+    unsafe fn add_shared_ptr(
+        &mut self,
+        _: usize,
+        _: ErasedPtr,
+        _: unsafe fn(ErasedPtr),
+    ) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        fail!(PoolingDenied { type_name });
+    }
+}
@@ -157,16 +157,27 @@ pub trait Pooling<E = <Self as Fallible>::Error> {
         ptr: ErasedPtr,
         drop: unsafe fn(ErasedPtr),
     ) -> Result<(), E>;
+
+    /// Checks whether a value with the given type name may be pooled.
+    ///
+    /// This is called before each shared pointer is deserialized. By default,
+    /// values of every type may be pooled.
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        let _ = type_name;
+        Ok(())
+    }
 }
 
 impl<T, E> Pooling<E> for Strategy<T, E>
 where
     T: Pooling<E>,
 {
+    #[inline]
     fn get_shared_ptr(&mut self, address: usize) -> Option<ErasedPtr> {
         T::get_shared_ptr(self, address)
     }
 
+    #[inline]
     unsafe fn add_shared_ptr(
         &mut self,
         address: usize,
@@ -177,6 +188,11 @@ where
         // the requirements for calling this function.
         unsafe { T::add_shared_ptr(self, address, ptr, drop) }
     }
+
+    #[inline]
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        T::check_shared_type(self, type_name)
+    }
 }
 
 /// Helper methods for [`Pooling`].
@@ -184,6 +200,7 @@ pub trait PoolingExt<E>: Pooling<E> {
     /// Checks whether the given reference has been deserialized and either uses
     /// the existing shared pointer to it, or deserializes it and converts
     /// it to a shared pointer with `to_shared`.
+    #[inline]
     fn deserialize_shared<T, P>(
         &mut self,
         value: &T::Archived,
@@ -206,6 +223,8 @@ pub trait PoolingExt<E>: Pooling<E> {
             unsafe { P::drop(ptr.downcast_unchecked::<T>()) }
         }
 
+        self.check_shared_type(::core::any::type_name::<T>())?;
+
         let address = value as *const T::Archived as *const () as usize;
         let metadata = T::Archived::deserialize_metadata(value);
 
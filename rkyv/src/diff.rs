@@ -0,0 +1,468 @@
+//! Structural differences between archived values.
+//!
+//! [`Diff`] compares two archived values of the same type without
+//! deserializing either of them, and records the paths of the parts which
+//! differ in a [`DiffReport`]. Paths are written like Rust place expressions:
+//! struct fields are joined with `.`, ranges of vec indices are written as
+//! `[start..end]`, and map keys are written as `[key]` using their `Debug`
+//! representation.
+//!
+//! `Diff` can be derived for archived types with `#[rkyv(derive_diff)]`.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     diff::{ChangeKind, Diff, DiffReport},
+//!     rancor::Error,
+//!     Archive, Archived, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[rkyv(derive_diff)]
+//! struct Config {
+//!     name: String,
+//!     ports: Vec<u16>,
+//! }
+//!
+//! let old = rkyv::to_bytes::<Error>(&Config {
+//!     name: "server".to_string(),
+//!     ports: vec![80, 443],
+//! })
+//! .unwrap();
+//! let new = rkyv::to_bytes::<Error>(&Config {
+//!     name: "server".to_string(),
+//!     ports: vec![8080, 443, 8443],
+//! })
+//! .unwrap();
+//!
+//! let old = rkyv::access::<Archived<Config>, Error>(&old).unwrap();
+//! let new = rkyv::access::<Archived<Config>, Error>(&new).unwrap();
+//!
+//! let mut report = DiffReport::new();
+//! old.diff(new, &mut report);
+//!
+//! let changes = report
+//!     .changes()
+//!     .iter()
+//!     .map(|change| (change.path(), change.kind()))
+//!     .collect::<Vec<_>>();
+//! assert_eq!(
+//!     changes,
+//!     [
+//!         ("ports[0..1]", ChangeKind::Changed),
+//!         ("ports[2..3]", ChangeKind::Added),
+//!     ],
+//! );
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::{self, Debug, Write as _},
+    hash::{Hash, Hasher},
+    num::{NonZeroI8, NonZeroU8},
+    ops::Range,
+};
+
+use crate::{
+    collections::swiss_table::ArchivedHashMap, option::ArchivedOption,
+    primitive::*, string::ArchivedString, vec::ArchivedVec,
+};
+
+/// A type whose archived values can be compared structurally.
+///
+/// `self` is treated as the old value and `other` as the new value, so parts
+/// which are only present in `other` are reported as
+/// [added](ChangeKind::Added).
+pub trait Diff {
+    /// Records the paths of the parts of `self` and `other` which differ in
+    /// `out`.
+    fn diff(&self, other: &Self, out: &mut DiffReport);
+}
+
+/// The kind of difference found at a path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ChangeKind {
+    /// The value at the path is present in both values, but differs.
+    Changed,
+    /// The value at the path is only present in the new value.
+    Added,
+    /// The value at the path is only present in the old value.
+    Removed,
+}
+
+/// A difference found between two archived values.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Change {
+    path: String,
+    kind: ChangeKind,
+}
+
+impl Change {
+    /// Returns the path to the part of the values which differs.
+    ///
+    /// The path is empty if the values differ as a whole.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the kind of difference.
+    pub fn kind(&self) -> ChangeKind {
+        self.kind
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            ChangeKind::Changed => "changed",
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+        };
+        if self.path.is_empty() {
+            write!(f, "value {}", kind)
+        } else {
+            write!(f, "`{}` {}", self.path, kind)
+        }
+    }
+}
+
+/// The differences found while comparing two archived values.
+///
+/// The report keeps track of the path to the part of the values currently
+/// being compared. [`Diff`] implementations descend into their parts with
+/// [`field`](Self::field), [`range`](Self::range), and [`key`](Self::key),
+/// and record differences at the current path with
+/// [`changed`](Self::changed), [`added`](Self::added), and
+/// [`removed`](Self::removed).
+#[derive(Debug, Default)]
+pub struct DiffReport {
+    path: String,
+    changes: Vec<Change>,
+}
+
+impl DiffReport {
+    /// Returns a new, empty `DiffReport`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the recorded differences in the order they were found.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Returns the recorded differences by value.
+    pub fn into_changes(self) -> Vec<Change> {
+        self.changes
+    }
+
+    /// Returns whether no differences have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn record(&mut self, kind: ChangeKind) {
+        self.changes.push(Change {
+            path: self.path.clone(),
+            kind,
+        });
+    }
+
+    /// Records that the value at the current path changed.
+    pub fn changed(&mut self) {
+        self.record(ChangeKind::Changed);
+    }
+
+    /// Records that the value at the current path was added.
+    pub fn added(&mut self) {
+        self.record(ChangeKind::Added);
+    }
+
+    /// Records that the value at the current path was removed.
+    pub fn removed(&mut self) {
+        self.record(ChangeKind::Removed);
+    }
+
+    fn with_segment(
+        &mut self,
+        segment: fmt::Arguments<'_>,
+        f: impl FnOnce(&mut Self),
+    ) {
+        let len = self.path.len();
+        // Writing to a `String` never fails.
+        let _ = self.path.write_fmt(segment);
+        f(self);
+        self.path.truncate(len);
+    }
+
+    /// Calls `f` with the current path extended by a struct field.
+    pub fn field(&mut self, name: &str, f: impl FnOnce(&mut Self)) {
+        if self.path.is_empty() {
+            self.with_segment(format_args!("{}", name), f);
+        } else {
+            self.with_segment(format_args!(".{}", name), f);
+        }
+    }
+
+    /// Calls `f` with the current path extended by a range of indices.
+    pub fn range(&mut self, range: Range<usize>, f: impl FnOnce(&mut Self)) {
+        self.with_segment(format_args!("[{}..{}]", range.start, range.end), f);
+    }
+
+    /// Calls `f` with the current path extended by a map key.
+    pub fn key<K: Debug + ?Sized>(
+        &mut self,
+        key: &K,
+        f: impl FnOnce(&mut Self),
+    ) {
+        self.with_segment(format_args!("[{:?}]", key), f);
+    }
+}
+
+macro_rules! impl_diff_partial_eq {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Diff for $ty {
+                fn diff(&self, other: &Self, out: &mut DiffReport) {
+                    if self != other {
+                        out.changed();
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_diff_partial_eq! {
+    (),
+    bool,
+    i8,
+    u8,
+    NonZeroI8,
+    NonZeroU8,
+    ArchivedI16,
+    ArchivedI32,
+    ArchivedI64,
+    ArchivedI128,
+    ArchivedU16,
+    ArchivedU32,
+    ArchivedU64,
+    ArchivedU128,
+    ArchivedF32,
+    ArchivedF64,
+    ArchivedChar,
+    ArchivedNonZeroI16,
+    ArchivedNonZeroI32,
+    ArchivedNonZeroI64,
+    ArchivedNonZeroI128,
+    ArchivedNonZeroU16,
+    ArchivedNonZeroU32,
+    ArchivedNonZeroU64,
+    ArchivedNonZeroU128,
+    ArchivedString,
+}
+
+/// Vecs report contiguous ranges of changed elements, and the range of
+/// elements which were added or removed from the end.
+impl<T: PartialEq> Diff for ArchivedVec<T> {
+    fn diff(&self, other: &Self, out: &mut DiffReport) {
+        let (old, new) = (self.as_slice(), other.as_slice());
+        let common = old.len().min(new.len());
+
+        let mut i = 0;
+        while i < common {
+            if old[i] == new[i] {
+                i += 1;
+                continue;
+            }
+            let start = i;
+            while i < common && old[i] != new[i] {
+                i += 1;
+            }
+            out.range(start..i, DiffReport::changed);
+        }
+
+        if old.len() > common {
+            out.range(common..old.len(), DiffReport::removed);
+        } else if new.len() > common {
+            out.range(common..new.len(), DiffReport::added);
+        }
+    }
+}
+
+impl<T: Diff> Diff for ArchivedOption<T> {
+    fn diff(&self, other: &Self, out: &mut DiffReport) {
+        match (self, other) {
+            (ArchivedOption::Some(old), ArchivedOption::Some(new)) => {
+                old.diff(new, out)
+            }
+            (ArchivedOption::None, ArchivedOption::Some(_)) => out.added(),
+            (ArchivedOption::Some(_), ArchivedOption::None) => out.removed(),
+            (ArchivedOption::None, ArchivedOption::None) => (),
+        }
+    }
+}
+
+/// Maps report keys which were added or removed, and the differences between
+/// the values of keys which are in both maps.
+impl<K, V, H> Diff for ArchivedHashMap<K, V, H>
+where
+    K: Hash + Eq + Debug,
+    V: Diff,
+    H: Hasher + Default,
+{
+    fn diff(&self, other: &Self, out: &mut DiffReport) {
+        for (key, old) in self.iter() {
+            match other.get(key) {
+                Some(new) => out.key(key, |out| old.diff(new, out)),
+                None => out.key(key, DiffReport::removed),
+            }
+        }
+        for key in other.keys() {
+            if !self.contains_key(key) {
+                out.key(key, DiffReport::added);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use bytecheck::CheckBytes;
+    use rancor::{Panic, Strategy};
+
+    use super::{ChangeKind, Diff, DiffReport};
+    use crate::{
+        test::{access, to_archived, to_bytes, TestSerializer, TestValidator},
+        Archive, Archived, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Clone)]
+    #[rkyv(crate, derive_diff)]
+    struct Limits {
+        connections: u32,
+        timeout: Option<u32>,
+    }
+
+    #[derive(Archive, Serialize, Clone)]
+    #[rkyv(crate, derive_diff)]
+    enum Mode {
+        Debug,
+        Release { level: u8 },
+    }
+
+    #[derive(Archive, Serialize, Clone)]
+    #[rkyv(crate, derive_diff)]
+    struct Config {
+        name: String,
+        mode: Mode,
+        ports: Vec<u16>,
+        limits: Limits,
+        features: HashMap<String, bool>,
+    }
+
+    fn diff<T>(old: &T, new: &T) -> Vec<(String, ChangeKind)>
+    where
+        T: for<'a> Serialize<TestSerializer<'a>>,
+        T::Archived:
+            Diff + for<'a> CheckBytes<Strategy<TestValidator<'a>, Panic>>,
+    {
+        let mut changes = Vec::new();
+        to_bytes(old, |old_bytes| {
+            access::<T::Archived>(old_bytes, |old| {
+                to_archived(new, |new| {
+                    let mut report = DiffReport::new();
+                    old.diff(new, &mut report);
+                    changes = report
+                        .into_changes()
+                        .into_iter()
+                        .map(|change| {
+                            (change.path().to_string(), change.kind())
+                        })
+                        .collect();
+                });
+            });
+        });
+        changes.sort();
+        changes
+    }
+
+    fn config() -> Config {
+        let mut features = HashMap::new();
+        features.insert("compression".to_string(), true);
+        features.insert("tracing".to_string(), false);
+        features.insert("legacy".to_string(), true);
+
+        Config {
+            name: "server".to_string(),
+            mode: Mode::Release { level: 2 },
+            ports: vec![80, 443, 8080, 8443],
+            limits: Limits {
+                connections: 100,
+                timeout: Some(30),
+            },
+            features,
+        }
+    }
+
+    #[test]
+    fn diff_identical() {
+        assert!(diff(&config(), &config()).is_empty());
+    }
+
+    #[test]
+    fn diff_config() {
+        let old = config();
+        let mut new = config();
+        new.mode = Mode::Release { level: 3 };
+        new.ports = vec![81, 444, 8080, 8443, 9000];
+        new.limits.timeout = None;
+        new.features.insert("tracing".to_string(), true);
+        new.features.remove("legacy");
+        new.features.insert("metrics".to_string(), false);
+
+        let expected = [
+            ("features[\"legacy\"]", ChangeKind::Removed),
+            ("features[\"metrics\"]", ChangeKind::Added),
+            ("features[\"tracing\"]", ChangeKind::Changed),
+            ("limits.timeout", ChangeKind::Removed),
+            ("mode.level", ChangeKind::Changed),
+            ("ports[0..2]", ChangeKind::Changed),
+            ("ports[4..5]", ChangeKind::Added),
+        ];
+        let expected = expected
+            .iter()
+            .map(|(path, kind)| (path.to_string(), *kind))
+            .collect::<Vec<_>>();
+        assert_eq!(diff(&old, &new), expected);
+    }
+
+    #[test]
+    fn diff_enum_variant() {
+        let old = config();
+        let mut new = config();
+        new.mode = Mode::Debug;
+        new.ports.truncate(1);
+
+        let expected = [
+            ("mode".to_string(), ChangeKind::Changed),
+            ("ports[1..4]".to_string(), ChangeKind::Removed),
+        ];
+        assert_eq!(diff(&old, &new), expected);
+    }
+
+    #[test]
+    fn diff_root_primitive() {
+        let mut report = DiffReport::new();
+        to_archived(&1u32, |old: &Archived<u32>| {
+            to_archived(&2u32, |new| old.diff(new, &mut report));
+        });
+        assert_eq!(report.changes().len(), 1);
+        assert_eq!(report.changes()[0].path(), "");
+        assert_eq!(report.changes()[0].to_string(), "value changed");
+    }
+}
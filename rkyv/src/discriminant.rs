@@ -0,0 +1,351 @@
+//! Fieldless enums archived as their discriminants.
+//!
+//! A C-like enum can be archived as a bare integer of its discriminant, either
+//! by deriving `Archive` with `#[rkyv(as_discriminant)]` or by archiving a
+//! field with the [`AsDiscriminant`](crate::with::AsDiscriminant) wrapper.
+//! Either way, the archived type is an [`ArchivedDiscriminant`], which has the
+//! same layout as the archived integer and only validates if it holds the
+//! discriminant of one of the enum's variants.
+
+use core::{fmt, marker::PhantomData};
+
+use munge::munge;
+
+use crate::{Archive, Archived, Place, Portable};
+
+/// An integer type which can hold the discriminant of an archived enum.
+///
+/// This is implemented for `u8`, `u16`, and `u32`.
+pub trait DiscriminantRepr:
+    Archive<Resolver = ()> + Copy + Eq + fmt::Debug + Into<u32> + sealed::Sealed
+{
+    /// Returns the native value of the given archived integer.
+    fn from_archived(archived: &Archived<Self>) -> Self;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+impl sealed::Sealed for u8 {}
+
+impl DiscriminantRepr for u8 {
+    #[inline]
+    fn from_archived(archived: &Archived<Self>) -> Self {
+        *archived
+    }
+}
+
+macro_rules! impl_multibyte_discriminant_repr {
+    ($($ty:ty),*) => {
+        $(
+            impl sealed::Sealed for $ty {}
+
+            impl DiscriminantRepr for $ty {
+                #[inline]
+                fn from_archived(archived: &Archived<Self>) -> Self {
+                    archived.to_native()
+                }
+            }
+        )*
+    };
+}
+
+impl_multibyte_discriminant_repr!(u16, u32);
+
+/// A fieldless enum which can be archived as its discriminant.
+///
+/// This is implemented by `#[rkyv(as_discriminant)]`, along with `TryFrom<R>`
+/// which converts discriminants back into the enum.
+pub trait ToDiscriminant<R>: TryFrom<R> {
+    /// Returns the discriminant of this variant.
+    fn to_discriminant(&self) -> R;
+}
+
+/// An error which occurs when a discriminant does not belong to any variant of
+/// an enum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownDiscriminant {
+    type_name: &'static str,
+    discriminant: u32,
+}
+
+impl UnknownDiscriminant {
+    /// Returns a new error for the given enum and discriminant.
+    pub fn new(type_name: &'static str, discriminant: u32) -> Self {
+        Self {
+            type_name,
+            discriminant,
+        }
+    }
+
+    /// Returns the name of the enum.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the unknown discriminant.
+    pub fn discriminant(&self) -> u32 {
+        self.discriminant
+    }
+}
+
+impl fmt::Display for UnknownDiscriminant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "unknown discriminant {} for enum `{}`",
+            self.discriminant, self.type_name,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownDiscriminant {}
+
+/// An archived fieldless enum, stored as its discriminant.
+///
+/// This has the same layout as `Archived<R>`.
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(transparent)]
+pub struct ArchivedDiscriminant<T, R: DiscriminantRepr = u8> {
+    repr: Archived<R>,
+    _phantom: PhantomData<T>,
+}
+
+impl<T, R: DiscriminantRepr> ArchivedDiscriminant<T, R> {
+    /// Returns the archived discriminant.
+    #[inline]
+    pub fn discriminant(&self) -> R {
+        R::from_archived(&self.repr)
+    }
+
+    /// Resolves an `ArchivedDiscriminant` from a discriminant.
+    #[inline]
+    pub fn resolve_from_discriminant(discriminant: R, out: Place<Self>) {
+        munge!(let ArchivedDiscriminant { repr, _phantom: _ } = out);
+        discriminant.resolve((), repr);
+    }
+}
+
+impl<T: ToDiscriminant<R>, R: DiscriminantRepr> ArchivedDiscriminant<T, R> {
+    /// Resolves an `ArchivedDiscriminant` from a variant of the enum.
+    #[inline]
+    pub fn resolve_from_enum(value: &T, out: Place<Self>) {
+        Self::resolve_from_discriminant(value.to_discriminant(), out);
+    }
+
+    /// Converts the archived discriminant back into the enum.
+    ///
+    /// This only fails if the archive was accessed without validation and the
+    /// discriminant does not belong to any variant.
+    pub fn try_to_native(&self) -> Result<T, UnknownDiscriminant> {
+        let discriminant = self.discriminant();
+        T::try_from(discriminant).map_err(|_| {
+            UnknownDiscriminant::new(
+                core::any::type_name::<T>(),
+                discriminant.into(),
+            )
+        })
+    }
+}
+
+impl<T, R: DiscriminantRepr> fmt::Debug for ArchivedDiscriminant<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ArchivedDiscriminant")
+            .field(&self.discriminant())
+            .finish()
+    }
+}
+
+impl<T, R: DiscriminantRepr> PartialEq for ArchivedDiscriminant<T, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.discriminant() == other.discriminant()
+    }
+}
+
+impl<T, R: DiscriminantRepr> Eq for ArchivedDiscriminant<T, R> {}
+
+impl<T, R> PartialEq<T> for ArchivedDiscriminant<T, R>
+where
+    T: ToDiscriminant<R>,
+    R: DiscriminantRepr,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.discriminant() == other.to_discriminant()
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+const _: () = {
+    use bytecheck::CheckBytes;
+    use rancor::{fail, Fallible, Source};
+
+    unsafe impl<T, R, C> CheckBytes<C> for ArchivedDiscriminant<T, R>
+    where
+        T: ToDiscriminant<R>,
+        R: DiscriminantRepr,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        unsafe fn check_bytes(
+            value: *const Self,
+            _: &mut C,
+        ) -> Result<(), C::Error> {
+            // SAFETY: Every bit pattern is a valid archived integer, and the
+            // caller has guaranteed that `value` is aligned and points to
+            // enough bytes for one.
+            let value = unsafe { &*value };
+            match value.try_to_native() {
+                Ok(_) => Ok(()),
+                Err(e) => fail!(e),
+            }
+        }
+    }
+};
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::string::ToString;
+    use core::mem::{align_of, size_of};
+
+    use rancor::{Failure, Panic};
+
+    use super::{ArchivedDiscriminant, ToDiscriminant, UnknownDiscriminant};
+    use crate::{
+        access, access_unchecked,
+        primitive::ArchivedU16,
+        test::{roundtrip, roundtrip_with},
+        to_bytes,
+        with::AsDiscriminant,
+        Archive, Archived, Deserialize, Serialize,
+    };
+
+    #[derive(
+        Archive, Serialize, Deserialize, Clone, Copy, Debug, PartialEq,
+    )]
+    #[rkyv(crate, as_discriminant, repr(u16))]
+    #[repr(u16)]
+    enum Opcode {
+        Nop = 0,
+        Load = 3,
+        Store,
+        Jump = 0x1000,
+    }
+
+    #[test]
+    fn archived_layout() {
+        assert_eq!(size_of::<Archived<Opcode>>(), size_of::<ArchivedU16>());
+        assert_eq!(align_of::<Archived<Opcode>>(), align_of::<ArchivedU16>());
+    }
+
+    #[test]
+    fn roundtrip_non_contiguous() {
+        for opcode in [Opcode::Nop, Opcode::Load, Opcode::Store, Opcode::Jump] {
+            roundtrip(&opcode);
+            roundtrip_with(&opcode, |a, b| {
+                assert_eq!(b.discriminant(), *a as u16);
+            });
+        }
+    }
+
+    #[test]
+    fn try_from_discriminant() {
+        assert_eq!(Opcode::try_from(4u16), Ok(Opcode::Store));
+        assert_eq!(Opcode::try_from(0x1000u16), Ok(Opcode::Jump));
+
+        let error = Opcode::try_from(1u16).unwrap_err();
+        assert_eq!(error.discriminant(), 1);
+        assert_eq!(
+            error.to_string(),
+            "unknown discriminant 1 for enum `Opcode`",
+        );
+    }
+
+    #[test]
+    fn reject_unknown_discriminant() {
+        for discriminant in [1u16, 2, 5, 0x0fff, 0xffff] {
+            let bytes = to_bytes::<Panic>(&discriminant).unwrap();
+            access::<Archived<Opcode>, Failure>(&bytes).unwrap_err();
+        }
+
+        let bytes = to_bytes::<Panic>(&3u16).unwrap();
+        let archived = access::<Archived<Opcode>, Failure>(&bytes).unwrap();
+        assert_eq!(*archived, Opcode::Load);
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Color {
+        Red = 1,
+        Green = 2,
+        Blue = 4,
+    }
+
+    impl TryFrom<u8> for Color {
+        type Error = UnknownDiscriminant;
+
+        fn try_from(value: u8) -> Result<Self, Self::Error> {
+            match value {
+                1 => Ok(Self::Red),
+                2 => Ok(Self::Green),
+                4 => Ok(Self::Blue),
+                _ => Err(UnknownDiscriminant::new("Color", value.into())),
+            }
+        }
+    }
+
+    impl ToDiscriminant<u8> for Color {
+        fn to_discriminant(&self) -> u8 {
+            *self as u8
+        }
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, derive(Debug))]
+    struct Pixel {
+        #[with(AsDiscriminant)]
+        color: Color,
+        #[with(AsDiscriminant<u16>)]
+        opcode: Opcode,
+    }
+
+    #[test]
+    fn with_wrapper() {
+        assert_eq!(size_of::<ArchivedDiscriminant<Color>>(), 1);
+
+        let value = Pixel {
+            color: Color::Blue,
+            opcode: Opcode::Jump,
+        };
+        roundtrip_with(&value, |a, b| {
+            assert_eq!(b.color, a.color);
+            assert_eq!(b.opcode, a.opcode);
+        });
+
+        // Three is not the discriminant of any color.
+        let mut bytes = to_bytes::<Panic>(&value).unwrap();
+        let root = bytes.len() - size_of::<ArchivedPixel>();
+        bytes[root] = 3;
+        access::<ArchivedPixel, Failure>(&bytes).unwrap_err();
+    }
+
+    #[test]
+    fn deserialize_unknown_discriminant() {
+        let bytes = to_bytes::<Panic>(&2u8).unwrap();
+        let archived =
+            unsafe { access_unchecked::<ArchivedDiscriminant<Color>>(&bytes) };
+        assert_eq!(archived.try_to_native(), Ok(Color::Green));
+
+        let bytes = to_bytes::<Panic>(&3u8).unwrap();
+        let archived =
+            unsafe { access_unchecked::<ArchivedDiscriminant<Color>>(&bytes) };
+        assert!(archived.try_to_native().is_err());
+
+        let bytes = to_bytes::<Panic>(&1u16).unwrap();
+        let archived = unsafe { access_unchecked::<Archived<Opcode>>(&bytes) };
+        crate::deserialize::<Opcode, _, Failure>(archived, &mut ())
+            .unwrap_err();
+    }
+}
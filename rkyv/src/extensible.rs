@@ -0,0 +1,387 @@
+//! Support for archived structs which can gain trailing fields.
+//!
+//! Structs deriving `Archive` with `#[rkyv(extensible)]` store their fields
+//! out-of-line behind an [`ArchivedExtensible`], along with the number of
+//! bytes that were serialized. Fields that are added to the end of the struct
+//! later on lie past the end of archives written by older versions, so
+//! readers report them as missing instead of reading out of bounds.
+
+use core::{fmt, marker::PhantomData, mem, mem::MaybeUninit};
+
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{
+    primitive::{usize_from_archived, ArchivedUsize},
+    ser::{Writer, WriterExt as _},
+    Archive as _, Place, Portable, RawRelPtr,
+};
+
+/// The serialized fields of an extensible struct.
+///
+/// `T` is the `#[repr(C)]` struct holding the archived fields of the newest
+/// version of the extensible struct. Archives written by older versions may
+/// only contain a prefix of `T`, which is tracked by the serialized length.
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(C)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+pub struct ArchivedExtensible<T> {
+    len: ArchivedUsize,
+    ptr: RawRelPtr,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> ArchivedExtensible<T> {
+    /// Returns the number of bytes of `T` which were serialized.
+    ///
+    /// This may be less than the size of `T` if the archive was written by an
+    /// older version of the struct, or greater if it was written by a newer
+    /// one.
+    pub fn len(&self) -> usize {
+        usize_from_archived(self.len)
+    }
+
+    /// Returns whether no fields were serialized.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a reference to the field of type `F` at the given offset into
+    /// `T`, or `None` if the field was not serialized.
+    ///
+    /// # Safety
+    ///
+    /// There must be a field of type `F` located at `offset` in `T`.
+    pub unsafe fn get<F>(&self, offset: usize) -> Option<&F> {
+        if offset + mem::size_of::<F>() <= self.len() {
+            // SAFETY: The field lies entirely within the serialized bytes, and
+            // the caller has guaranteed that a field of type `F` is located
+            // at `offset`.
+            Some(unsafe {
+                &*self.ptr.as_ptr().cast::<u8>().add(offset).cast::<F>()
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Serializes the fields of an extensible struct.
+    ///
+    /// `resolve` is called with an out place for the fields, which it must
+    /// fully resolve.
+    pub fn serialize_from_fn<S>(
+        serializer: &mut S,
+        resolve: impl FnOnce(Place<T>),
+    ) -> Result<ExtensibleResolver, S::Error>
+    where
+        S: Fallible + Writer + ?Sized,
+    {
+        let pos = serializer.align_for::<T>()?;
+
+        let mut resolved = MaybeUninit::<T>::zeroed();
+        // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed
+        // `MaybeUninit`, and so is properly aligned, dereferenceable, and all
+        // of its bytes are initialized.
+        let out = unsafe { Place::new_unchecked(pos, resolved.as_mut_ptr()) };
+        resolve(out);
+        serializer.write(out.as_slice())?;
+
+        Ok(ExtensibleResolver { pos })
+    }
+
+    /// Resolves an archived extensible struct from the serialized length of
+    /// its fields.
+    ///
+    /// `len` should be the offset of the end of the last field of `T`, so that
+    /// trailing padding is not mistaken for fields added in later versions.
+    pub fn resolve_from_len(
+        len: usize,
+        resolver: ExtensibleResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let Self { len: out_len, ptr, _phantom: _ } = out);
+        len.resolve((), out_len);
+        RawRelPtr::emplace(resolver.pos, ptr);
+    }
+}
+
+impl<T> fmt::Debug for ArchivedExtensible<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedExtensible")
+            .field("len", &self.len())
+            .field("ptr", &self.ptr)
+            .finish()
+    }
+}
+
+/// The resolver for an [`ArchivedExtensible`].
+pub struct ExtensibleResolver {
+    pos: usize,
+}
+
+impl ExtensibleResolver {
+    /// Creates a new [`ExtensibleResolver`] from the position of the serialized
+    /// fields.
+    ///
+    /// In most cases, you won't need to create an [`ExtensibleResolver`]
+    /// yourself and can instead obtain it through
+    /// [`ArchivedExtensible::serialize_from_fn`].
+    pub fn from_pos(pos: usize) -> Self {
+        Self { pos }
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+pub use self::verify::{check_field, CheckFields};
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::{alloc::Layout, fmt, mem};
+
+    use bytecheck::{CheckBytes, Verify};
+    use rancor::{fail, Fallible, ResultExt as _, Source};
+
+    use super::ArchivedExtensible;
+    use crate::{
+        fmt::Pointer,
+        primitive::native_len,
+        validation::{ArchiveContext, ArchiveContextExt as _},
+    };
+
+    /// The fields of an extensible struct, which can be checked given only a
+    /// prefix of their bytes.
+    ///
+    /// # Safety
+    ///
+    /// `check_fields` must only return `Ok` if every field which lies entirely
+    /// within the first `len` bytes of `Self` is valid.
+    pub unsafe trait CheckFields<C: Fallible + ?Sized> {
+        /// Checks the fields of `Self` which lie within the first `len` bytes
+        /// pointed to by `ptr`.
+        ///
+        /// # Safety
+        ///
+        /// `ptr` must be non-null and valid for reads of `len` bytes.
+        unsafe fn check_fields(
+            ptr: *const u8,
+            len: usize,
+            context: &mut C,
+        ) -> Result<(), C::Error>;
+    }
+
+    #[derive(Debug)]
+    struct UnalignedField {
+        address: usize,
+        align: usize,
+    }
+
+    impl fmt::Display for UnalignedField {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(
+                f,
+                "unaligned extensible field: ptr {} unaligned for alignment {}",
+                Pointer(self.address),
+                self.align,
+            )
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for UnalignedField {}
+
+    /// Checks the field of type `F` at the given offset if it lies within the
+    /// first `len` bytes pointed to by `ptr`.
+    ///
+    /// Fields which lie past the end of the serialized bytes are not checked.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null and valid for reads of `len` bytes.
+    pub unsafe fn check_field<F, C>(
+        ptr: *const u8,
+        offset: usize,
+        len: usize,
+        context: &mut C,
+    ) -> Result<(), C::Error>
+    where
+        F: CheckBytes<C>,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        if offset + mem::size_of::<F>() > len {
+            return Ok(());
+        }
+
+        // SAFETY: The field lies entirely within the `len` bytes that the
+        // caller has guaranteed are valid for reads.
+        let field_ptr = unsafe { ptr.add(offset) };
+        let align = mem::align_of::<F>();
+        if (field_ptr as usize) & (align - 1) != 0 {
+            fail!(UnalignedField {
+                address: field_ptr as usize,
+                align,
+            });
+        }
+
+        // SAFETY: `field_ptr` is properly aligned and points to `size_of::<F>`
+        // bytes which are valid for reads.
+        unsafe { F::check_bytes(field_ptr.cast(), context) }
+    }
+
+    unsafe impl<T, C> Verify<C> for ArchivedExtensible<T>
+    where
+        T: CheckFields<C>,
+        C: Fallible + ArchiveContext + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, context: &mut C) -> Result<(), C::Error> {
+            let ptr = self.ptr.as_ptr_wrapping().cast::<u8>();
+            let len = native_len(self.len)?;
+            let layout = Layout::from_size_align(len, 1).into_error()?;
+
+            // Only the serialized bytes are claimed. Alignment is checked per
+            // field since older versions of `T` may be less aligned.
+            context.in_subtree_raw(ptr, layout, |context| unsafe {
+                T::check_fields(ptr, len, context)
+            })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+    };
+
+    use rancor::Panic;
+
+    use crate::{access, de::Pool, deserialize, to_bytes};
+
+    mod v1 {
+        #[cfg(not(feature = "std"))]
+        use alloc::string::String;
+
+        use crate::{Archive, Deserialize, Serialize};
+
+        #[derive(
+            Archive, Serialize, Deserialize, Debug, Default, PartialEq,
+        )]
+        #[rkyv(crate, check_bytes, extensible)]
+        pub struct Example {
+            pub id: u32,
+            pub name: String,
+            pub flag: bool,
+        }
+    }
+
+    mod v2 {
+        #[cfg(not(feature = "std"))]
+        use alloc::{string::String, vec::Vec};
+
+        use crate::{Archive, Deserialize, Serialize};
+
+        #[derive(
+            Archive, Serialize, Deserialize, Debug, Default, PartialEq,
+        )]
+        #[rkyv(crate, check_bytes, extensible)]
+        pub struct Example {
+            pub id: u32,
+            pub name: String,
+            pub flag: bool,
+            // Lands in the trailing padding of the v1 fields
+            pub extra: u16,
+            pub tags: Vec<String>,
+        }
+    }
+
+    #[test]
+    fn read_older_version() {
+        let value = v1::Example {
+            id: 42,
+            name: "hello".to_string(),
+            flag: true,
+        };
+        let bytes = to_bytes::<Panic>(&value).unwrap();
+
+        let archived =
+            access::<v2::ArchivedExample, Panic>(bytes.as_ref()).unwrap();
+        assert_eq!(archived.id().map(|id| id.to_native()), Some(42));
+        assert_eq!(archived.name().map(|name| name.as_str()), Some("hello"));
+        assert_eq!(archived.flag(), Some(&true));
+        assert!(archived.extra().is_none());
+        assert!(archived.tags().is_none());
+
+        let deserialized =
+            deserialize::<v2::Example, _, Panic>(archived, &mut Pool::new())
+                .unwrap();
+        assert_eq!(
+            deserialized,
+            v2::Example {
+                id: 42,
+                name: "hello".to_string(),
+                flag: true,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn read_newer_version() {
+        let value = v2::Example {
+            id: 42,
+            name: "hello".to_string(),
+            flag: true,
+            extra: 7,
+            tags: vec!["a".to_string(), "b".to_string()],
+        };
+        let bytes = to_bytes::<Panic>(&value).unwrap();
+
+        let archived =
+            access::<v1::ArchivedExample, Panic>(bytes.as_ref()).unwrap();
+        assert_eq!(archived.id().map(|id| id.to_native()), Some(42));
+        assert_eq!(archived.name().map(|name| name.as_str()), Some("hello"));
+        assert_eq!(archived.flag(), Some(&true));
+
+        let deserialized =
+            deserialize::<v1::Example, _, Panic>(archived, &mut Pool::new())
+                .unwrap();
+        assert_eq!(
+            deserialized,
+            v1::Example {
+                id: 42,
+                name: "hello".to_string(),
+                flag: true,
+            },
+        );
+    }
+
+    #[test]
+    fn read_same_version() {
+        let value = v2::Example {
+            id: 42,
+            name: "hello".to_string(),
+            flag: false,
+            extra: 7,
+            tags: vec!["a".to_string(), "b".to_string()],
+        };
+        let bytes = to_bytes::<Panic>(&value).unwrap();
+
+        let archived =
+            access::<v2::ArchivedExample, Panic>(bytes.as_ref()).unwrap();
+        assert_eq!(archived.extra().map(|extra| extra.to_native()), Some(7));
+        assert_eq!(archived.tags().map(|tags| tags.len()), Some(2));
+
+        let deserialized =
+            deserialize::<v2::Example, _, Panic>(archived, &mut Pool::new())
+                .unwrap();
+        assert_eq!(deserialized, value);
+    }
+}
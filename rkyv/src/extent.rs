@@ -0,0 +1,521 @@
+//! Byte extents of archived subtrees.
+//!
+//! An archived value and everything it points to is stored in one range of
+//! the archive, called its extent. Copying the bytes of the extent somewhere
+//! else copies the whole subtree. Relative pointers inside the extent stay
+//! valid as long as the bytes keep their alignment, so only the root of the
+//! subtree needs to be resolved again when it's written somewhere new.
+//!
+//! This is used by [`KeepArchived`](crate::with::KeepArchived) to deserialize
+//! a field into an [`OwnedArchivedField`] without converting it.
+
+#[cfg(feature = "alloc")]
+use core::{fmt, marker::PhantomData, ops::Deref, slice};
+use core::{
+    mem,
+    num::{NonZeroI8, NonZeroU8},
+    ops::Range,
+};
+
+use crate::{
+    string::{ArchivedString, StringResolver},
+    vec::{ArchivedVec, VecResolver},
+    Place, Portable,
+};
+#[cfg(feature = "alloc")]
+use crate::{util::AlignedVec, Archive};
+
+/// A mapping from addresses in one buffer to positions in another.
+///
+/// The range starting at the base address has been copied to the buffer being
+/// serialized, starting at the position.
+#[derive(Clone, Copy, Debug)]
+pub struct Relocation {
+    base: usize,
+    pos: usize,
+}
+
+impl Relocation {
+    /// Creates a relocation which maps `base` to `pos`.
+    #[inline]
+    pub fn new(base: *const u8, pos: usize) -> Self {
+        Self {
+            base: base as usize,
+            pos,
+        }
+    }
+
+    /// Returns the position in the output that the given pointer maps to.
+    #[inline]
+    pub fn pos_of<T: ?Sized>(&self, ptr: *const T) -> usize {
+        (ptr as *const u8 as usize).wrapping_sub(self.base) + self.pos
+    }
+}
+
+/// An archived type which can report the byte extent of its subtree.
+///
+/// # Safety
+///
+/// `extent` must return a range of addresses which contains all of the bytes
+/// of `self` and of everything that `self` points to. Every byte of that range
+/// must be readable for as long as `self` is borrowed.
+pub unsafe trait ArchivedExtent: Portable {
+    /// Whether values of this type never point to other data.
+    ///
+    /// The extent of a flat type is just the bytes of the value, so containers
+    /// of flat types don't need to visit their elements.
+    const IS_FLAT: bool = false;
+
+    /// Returns the range of addresses which contains this value and all of the
+    /// data it points to.
+    fn extent(&self) -> Range<usize>;
+
+    /// Returns the range of `archive` which contains this value and all of the
+    /// data it points to, relative to the start of `archive`.
+    ///
+    /// Returns `None` if the extent is not contained in `archive`.
+    fn extent_in(&self, archive: &[u8]) -> Option<Range<usize>> {
+        let base = archive.as_ptr() as usize;
+        let extent = self.extent();
+        let start = extent.start.checked_sub(base)?;
+        let end = extent.end.checked_sub(base)?;
+        (end <= archive.len()).then_some(start..end)
+    }
+
+    /// Resolves a copy of this value whose extent has been moved according to
+    /// the given relocation.
+    fn resolve_relocated(&self, relocation: Relocation, out: Place<Self>);
+}
+
+fn range_of<T: ?Sized>(value: &T) -> Range<usize> {
+    let start = value as *const T as *const u8 as usize;
+    start..start + mem::size_of_val(value)
+}
+
+fn union(a: Range<usize>, b: Range<usize>) -> Range<usize> {
+    a.start.min(b.start)..a.end.max(b.end)
+}
+
+macro_rules! impl_archived_extent_for_primitive {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            unsafe impl ArchivedExtent for $ty {
+                const IS_FLAT: bool = true;
+
+                #[inline]
+                fn extent(&self) -> Range<usize> {
+                    range_of(self)
+                }
+
+                #[inline]
+                fn resolve_relocated(&self, _: Relocation, out: Place<Self>) {
+                    out.write(*self);
+                }
+            }
+        )*
+    };
+}
+
+impl_archived_extent_for_primitive! {
+    (),
+    bool,
+    i8,
+    u8,
+    NonZeroI8,
+    NonZeroU8,
+    rend::NonZeroI16_be,
+    rend::NonZeroI16_le,
+    rend::NonZeroI32_be,
+    rend::NonZeroI32_le,
+    rend::NonZeroI64_be,
+    rend::NonZeroI64_le,
+    rend::NonZeroI128_be,
+    rend::NonZeroI128_le,
+    rend::NonZeroU16_be,
+    rend::NonZeroU16_le,
+    rend::NonZeroU32_be,
+    rend::NonZeroU32_le,
+    rend::NonZeroU64_be,
+    rend::NonZeroU64_le,
+    rend::NonZeroU128_be,
+    rend::NonZeroU128_le,
+    rend::char_be,
+    rend::char_le,
+    rend::f32_be,
+    rend::f32_le,
+    rend::f64_be,
+    rend::f64_le,
+    rend::i16_be,
+    rend::i16_le,
+    rend::i32_be,
+    rend::i32_le,
+    rend::i64_be,
+    rend::i64_le,
+    rend::i128_be,
+    rend::i128_le,
+    rend::u16_be,
+    rend::u16_le,
+    rend::u32_be,
+    rend::u32_le,
+    rend::u64_be,
+    rend::u64_le,
+    rend::u128_be,
+    rend::u128_le,
+    rend::unaligned::NonZeroI16_ube,
+    rend::unaligned::NonZeroI16_ule,
+    rend::unaligned::NonZeroI32_ube,
+    rend::unaligned::NonZeroI32_ule,
+    rend::unaligned::NonZeroI64_ube,
+    rend::unaligned::NonZeroI64_ule,
+    rend::unaligned::NonZeroI128_ube,
+    rend::unaligned::NonZeroI128_ule,
+    rend::unaligned::NonZeroU16_ube,
+    rend::unaligned::NonZeroU16_ule,
+    rend::unaligned::NonZeroU32_ube,
+    rend::unaligned::NonZeroU32_ule,
+    rend::unaligned::NonZeroU64_ube,
+    rend::unaligned::NonZeroU64_ule,
+    rend::unaligned::NonZeroU128_ube,
+    rend::unaligned::NonZeroU128_ule,
+    rend::unaligned::char_ube,
+    rend::unaligned::char_ule,
+    rend::unaligned::f32_ube,
+    rend::unaligned::f32_ule,
+    rend::unaligned::f64_ube,
+    rend::unaligned::f64_ule,
+    rend::unaligned::i16_ube,
+    rend::unaligned::i16_ule,
+    rend::unaligned::i32_ube,
+    rend::unaligned::i32_ule,
+    rend::unaligned::i64_ube,
+    rend::unaligned::i64_ule,
+    rend::unaligned::i128_ube,
+    rend::unaligned::i128_ule,
+    rend::unaligned::u16_ube,
+    rend::unaligned::u16_ule,
+    rend::unaligned::u32_ube,
+    rend::unaligned::u32_ule,
+    rend::unaligned::u64_ube,
+    rend::unaligned::u64_ule,
+    rend::unaligned::u128_ube,
+    rend::unaligned::u128_ule,
+}
+
+unsafe impl<T: ArchivedExtent> ArchivedExtent for ArchivedVec<T> {
+    fn extent(&self) -> Range<usize> {
+        let mut extent = union(range_of(self), range_of(self.as_slice()));
+        if !T::IS_FLAT {
+            for element in self.iter() {
+                extent = union(extent, element.extent());
+            }
+        }
+        extent
+    }
+
+    #[inline]
+    fn resolve_relocated(&self, relocation: Relocation, out: Place<Self>) {
+        ArchivedVec::resolve_from_len(
+            self.len(),
+            VecResolver::from_pos(relocation.pos_of(self.as_ptr())),
+            out,
+        );
+    }
+}
+
+unsafe impl ArchivedExtent for ArchivedString {
+    #[inline]
+    fn extent(&self) -> Range<usize> {
+        if self.is_inline() {
+            range_of(self)
+        } else {
+            union(range_of(self), range_of(self.as_str()))
+        }
+    }
+
+    #[inline]
+    fn resolve_relocated(&self, relocation: Relocation, out: Place<Self>) {
+        let value = self.as_str();
+        if self.is_inline() {
+            ArchivedString::resolve_from_str(
+                value,
+                StringResolver::from_pos(0),
+                out,
+            );
+        } else {
+            ArchivedString::resolve_from_str_out_of_line(
+                value,
+                StringResolver::from_pos(relocation.pos_of(value.as_ptr())),
+                out,
+            );
+        }
+    }
+}
+
+/// An archived `T` which owns a copy of the bytes of its subtree.
+///
+/// The bytes are copied without being converted, so creating one costs a
+/// single copy no matter how many elements the subtree has. The copy keeps
+/// the alignment of the original bytes modulo 16, so archived types must not
+/// require an alignment greater than 16.
+#[cfg(feature = "alloc")]
+pub struct OwnedArchivedField<T: Archive> {
+    bytes: AlignedVec,
+    root: usize,
+    _phantom: PhantomData<T>,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Archive> OwnedArchivedField<T>
+where
+    T::Archived: ArchivedExtent,
+{
+    /// Creates a new `OwnedArchivedField` by copying the extent of the given
+    /// archived value.
+    pub fn new(archived: &T::Archived) -> Self {
+        let extent = archived.extent();
+        let pad = extent.start % AlignedVec::<16>::ALIGNMENT;
+
+        // SAFETY: `ArchivedExtent` guarantees that every byte of the extent is
+        // readable while `archived` is borrowed.
+        let extent_bytes = unsafe {
+            slice::from_raw_parts(extent.start as *const u8, extent.len())
+        };
+        let mut bytes = AlignedVec::with_capacity(pad + extent_bytes.len());
+        bytes.resize(pad, 0);
+        bytes.extend_from_slice(extent_bytes);
+
+        let root = archived as *const T::Archived as usize;
+        Self {
+            bytes,
+            root: pad + (root - extent.start),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the archived value.
+    #[inline]
+    pub fn get(&self) -> &T::Archived {
+        // SAFETY: The archived value was copied to `root` along with all of
+        // the data it points to, and its alignment was preserved.
+        unsafe {
+            crate::util::access_pos_unchecked::<T::Archived>(
+                &self.bytes,
+                self.root,
+            )
+        }
+    }
+
+    /// Returns the bytes of the subtree, including leading padding.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// The resolver for an [`OwnedArchivedField`].
+#[cfg(feature = "alloc")]
+pub struct OwnedArchivedFieldResolver {
+    pub(crate) pos: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Archive> Clone for OwnedArchivedField<T> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            root: self.root,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Archive> Deref for OwnedArchivedField<T>
+where
+    T::Archived: ArchivedExtent,
+{
+    type Target = T::Archived;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.get()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Archive> fmt::Debug for OwnedArchivedField<T>
+where
+    T::Archived: ArchivedExtent + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OwnedArchivedField")
+            .field(self.get())
+            .finish()
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use crate::{
+        extent::{ArchivedExtent, OwnedArchivedField},
+        hash::hash_value,
+        test::{access, deserialize, to_archived, to_bytes},
+        vec::ArchivedVec,
+        with::KeepArchived,
+        Archive, Deserialize, Serialize,
+    };
+
+    // Large enough to span many pages, but small enough to keep the test
+    // cheap.
+    #[cfg(not(miri))]
+    const BLOB_LEN: usize = 256 * 1024;
+    #[cfg(miri)]
+    const BLOB_LEN: usize = 1024;
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Document {
+        name: String,
+        blob: Vec<u8>,
+    }
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(crate, check_bytes)]
+    struct LazyDocument {
+        name: String,
+        #[with(KeepArchived)]
+        blob: OwnedArchivedField<Vec<u8>>,
+    }
+
+    #[test]
+    fn keep_archived_blob() {
+        let value = Document {
+            name: "large document".to_string(),
+            blob: (0..BLOB_LEN).map(|i| i as u8).collect(),
+        };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedLazyDocument>(bytes, |archived| {
+                let lazy = deserialize::<LazyDocument>(archived);
+                assert_eq!(lazy.name, value.name);
+
+                // The blob was copied as one block of bytes, and its elements
+                // are read from the copy.
+                let blob = lazy.blob.get();
+                assert_eq!(blob.len(), BLOB_LEN);
+                assert_ne!(blob.as_ptr(), archived.blob.as_ptr());
+                assert!(lazy.blob.as_bytes().len() < BLOB_LEN + 32);
+                assert_eq!(blob.as_slice(), value.blob.as_slice());
+
+                to_bytes(&lazy, |bytes| {
+                    access::<ArchivedDocument>(bytes, |archived| {
+                        assert_eq!(deserialize::<Document>(archived), value);
+                    });
+                });
+            });
+        });
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Names {
+        names: Vec<String>,
+    }
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(crate, check_bytes)]
+    struct LazyNames {
+        #[with(KeepArchived)]
+        names: OwnedArchivedField<Vec<String>>,
+    }
+
+    #[test]
+    fn keep_archived_strings() {
+        let value = Names {
+            names: vec![
+                String::new(),
+                "short".to_string(),
+                "a string which is too long to be stored inline".to_string(),
+            ],
+        };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedLazyNames>(bytes, |archived| {
+                let lazy = deserialize::<LazyNames>(archived);
+                assert_eq!(lazy.names.len(), 3);
+                for (name, expected) in lazy.names.iter().zip(&value.names) {
+                    assert_eq!(name, expected.as_str());
+                }
+
+                to_bytes(&lazy, |bytes| {
+                    access::<ArchivedNames>(bytes, |archived| {
+                        assert_eq!(deserialize::<Names>(archived), value);
+                    });
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn extent_in_matches_standalone() {
+        let element = vec![1u8, 2, 3, 4, 5];
+
+        to_bytes(&vec![element.clone()], |bytes| {
+            access::<ArchivedVec<ArchivedVec<u8>>>(bytes, |archived| {
+                let extent = archived[0].extent_in(bytes).unwrap();
+
+                // The extent of the only element has the same layout as the
+                // element serialized on its own.
+                to_bytes(&element, |standalone| {
+                    assert_eq!(
+                        hash_value(&bytes[extent.clone()]),
+                        hash_value(standalone),
+                    );
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn extent_in_contains_element() {
+        let value = vec![vec![1u8, 2, 3], vec![4, 5, 6, 7], vec![8]];
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedVec<ArchivedVec<u8>>>(bytes, |archived| {
+                for (element, expected) in archived.iter().zip(&value) {
+                    let extent = element.extent_in(bytes).unwrap();
+                    let base = bytes.as_ptr() as usize;
+                    let header = element as *const ArchivedVec<u8> as usize;
+                    let data = element.as_ptr() as usize;
+
+                    assert!(extent.contains(&(header - base)));
+                    assert!(extent.contains(&(data - base)));
+                    assert!(extent.end <= bytes.len());
+                    assert_eq!(element.as_slice(), expected.as_slice());
+                }
+
+                assert!(archived[0].extent_in(&bytes[..4]).is_none());
+            });
+        });
+    }
+
+    #[test]
+    fn get_range() {
+        to_archived(&vec![1u8, 2, 3, 4, 5], |archived| {
+            assert_eq!(archived.get_range(1..3), Some([2u8, 3].as_slice()));
+            assert_eq!(archived.get_range(0..5).map(<[u8]>::len), Some(5));
+            assert_eq!(archived.get_range(5..5), Some([].as_slice()));
+            assert!(archived.get_range(4..6).is_none());
+            #[allow(clippy::reversed_empty_ranges)]
+            let reversed = archived.get_range(3..2);
+            assert!(reversed.is_none());
+        });
+    }
+}
@@ -0,0 +1,434 @@
+//! C descriptions of archived types.
+
+use core::{
+    any::type_name,
+    fmt::{self, Write as _},
+    mem::{align_of, size_of},
+};
+
+use crate::{
+    boxed::ArchivedBox,
+    primitive::{
+        ArchivedChar, ArchivedF32, ArchivedF64, ArchivedI16, ArchivedI32,
+        ArchivedI64, ArchivedU16, ArchivedU32, ArchivedU64,
+    },
+    string::ArchivedString,
+    traits::ArchivePointee,
+    vec::ArchivedVec,
+    RelPtr,
+};
+
+/// An archived type which can be described as an equivalent C type.
+///
+/// This is implemented for archived primitives, arrays of types which
+/// implement `Describe`, and archived structs which derive `Archive` with
+/// `#[rkyv(derive_describe)]`. Archived types which contain relative pointers,
+/// like [`ArchivedVec`] and [`ArchivedString`], also implement `Describe` but
+/// always return an error since they have no C equivalent.
+pub trait Describe {
+    /// Returns the C type equivalent to this archived type.
+    fn describe() -> Result<CType, DescribeError>;
+}
+
+/// A C type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CType {
+    /// A primitive C type like `uint32_t`.
+    Primitive {
+        /// The name of the type.
+        name: &'static str,
+        /// The size of the type in bytes.
+        size: usize,
+    },
+    /// A fixed-size array.
+    Array {
+        /// The type of the elements.
+        element: Box<CType>,
+        /// The number of elements.
+        len: usize,
+    },
+    /// A struct.
+    Struct(CStruct),
+}
+
+impl CType {
+    /// Returns the size of the type in bytes.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Primitive { size, .. } => *size,
+            Self::Array { element, len } => element.size() * len,
+            Self::Struct(s) => s.size,
+        }
+    }
+
+    fn primitive<T>(name: &'static str) -> Self {
+        Self::Primitive {
+            name,
+            size: size_of::<T>(),
+        }
+    }
+
+    fn declare(&self, name: &str, out: &mut impl fmt::Write) -> fmt::Result {
+        let mut ty = self;
+        let mut dims = String::new();
+        while let Self::Array { element, len } = ty {
+            write!(dims, "[{}]", len)?;
+            ty = &**element;
+        }
+        let base = match ty {
+            Self::Primitive { name, .. } => *name,
+            Self::Struct(s) => s.name.as_str(),
+            Self::Array { .. } => unreachable!(),
+        };
+        write!(out, "{} {}{}", base, name, dims)
+    }
+}
+
+/// A C struct with the same layout as an archived struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CStruct {
+    name: String,
+    size: usize,
+    align: usize,
+    fields: Vec<CField>,
+}
+
+impl CStruct {
+    /// Returns a new `CStruct` with the given name and the size and alignment
+    /// of `T`, and no fields.
+    pub fn new<T>(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            size: size_of::<T>(),
+            align: align_of::<T>(),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Adds a field of type `T` at the given offset.
+    ///
+    /// Fields must be added in order of their offsets.
+    pub fn push_field<T: Describe>(
+        &mut self,
+        name: impl Into<String>,
+        offset: usize,
+    ) -> Result<(), DescribeError> {
+        let ty = T::describe()?;
+        debug_assert!(
+            self.fields
+                .last()
+                .map_or(true, |last| last.offset + last.ty.size() <= offset),
+            "fields must be added in order of their offsets",
+        );
+        self.fields.push(CField {
+            name: name.into(),
+            offset,
+            ty,
+        });
+        Ok(())
+    }
+
+    /// Returns the name of the struct.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the size of the struct in bytes.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the alignment of the struct in bytes.
+    pub fn align(&self) -> usize {
+        self.align
+    }
+
+    /// Returns the fields of the struct.
+    pub fn fields(&self) -> &[CField] {
+        &self.fields
+    }
+}
+
+/// A field of a [`CStruct`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CField {
+    name: String,
+    offset: usize,
+    ty: CType,
+}
+
+impl CField {
+    /// Returns the name of the field.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the offset of the field from the start of the struct.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the type of the field.
+    pub fn ty(&self) -> &CType {
+        &self.ty
+    }
+}
+
+/// An error which occurs when an archived type can't be written to a C header.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DescribeError {
+    /// The type has no C equivalent.
+    Unsupported {
+        /// The name of the type.
+        type_name: &'static str,
+        /// Why the type has no C equivalent.
+        reason: &'static str,
+    },
+    /// The root type of a header is not a struct.
+    NotAStruct {
+        /// The name of the type.
+        type_name: &'static str,
+    },
+    /// Two different structs have the same name.
+    ConflictingNames {
+        /// The name of the structs.
+        name: String,
+    },
+    /// Writing the header failed.
+    Write,
+}
+
+impl DescribeError {
+    fn unsupported<T: ?Sized>(reason: &'static str) -> Self {
+        Self::Unsupported {
+            type_name: type_name::<T>(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for DescribeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported { type_name, reason } => write!(
+                f,
+                "`{}` can't be described as a C type because {}",
+                type_name, reason,
+            ),
+            Self::NotAStruct { type_name } => write!(
+                f,
+                "`{}` is not a struct, only structs can be written to a C \
+                 header",
+                type_name,
+            ),
+            Self::ConflictingNames { name } => {
+                write!(f, "two different structs are named `{}`", name)
+            }
+            Self::Write => write!(f, "failed to write the C header"),
+        }
+    }
+}
+
+impl std::error::Error for DescribeError {}
+
+impl From<fmt::Error> for DescribeError {
+    fn from(_: fmt::Error) -> Self {
+        Self::Write
+    }
+}
+
+macro_rules! impl_describe_primitive {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(
+            impl Describe for $ty {
+                fn describe() -> Result<CType, DescribeError> {
+                    Ok(CType::primitive::<Self>($name))
+                }
+            }
+        )*
+    };
+}
+
+impl_describe_primitive! {
+    bool => "bool",
+    i8 => "int8_t",
+    u8 => "uint8_t",
+    ArchivedI16 => "int16_t",
+    ArchivedI32 => "int32_t",
+    ArchivedI64 => "int64_t",
+    ArchivedU16 => "uint16_t",
+    ArchivedU32 => "uint32_t",
+    ArchivedU64 => "uint64_t",
+    ArchivedF32 => "float",
+    ArchivedF64 => "double",
+    ArchivedChar => "uint32_t",
+}
+
+impl<T: Describe, const N: usize> Describe for [T; N] {
+    fn describe() -> Result<CType, DescribeError> {
+        if N == 0 {
+            return Err(DescribeError::unsupported::<Self>(
+                "C arrays must have at least one element",
+            ));
+        }
+
+        Ok(CType::Array {
+            element: Box::new(T::describe()?),
+            len: N,
+        })
+    }
+}
+
+const RELATIVE_POINTER: &str = "it contains a relative pointer";
+
+impl<T: ArchivePointee + ?Sized, O> Describe for RelPtr<T, O> {
+    fn describe() -> Result<CType, DescribeError> {
+        Err(DescribeError::unsupported::<Self>(RELATIVE_POINTER))
+    }
+}
+
+impl<T: ArchivePointee + ?Sized> Describe for ArchivedBox<T> {
+    fn describe() -> Result<CType, DescribeError> {
+        Err(DescribeError::unsupported::<Self>(RELATIVE_POINTER))
+    }
+}
+
+impl<T> Describe for ArchivedVec<T> {
+    fn describe() -> Result<CType, DescribeError> {
+        Err(DescribeError::unsupported::<Self>(RELATIVE_POINTER))
+    }
+}
+
+impl Describe for ArchivedString {
+    fn describe() -> Result<CType, DescribeError> {
+        Err(DescribeError::unsupported::<Self>(RELATIVE_POINTER))
+    }
+}
+
+/// Writes a C header which defines structs with the same layout as the
+/// archived struct `T` and the structs it contains.
+///
+/// The structs are packed and padded explicitly so that their fields are at
+/// the same offsets as the fields of the archived structs, and the header
+/// checks that their sizes match. Multibyte values are stored in the byte
+/// order of the archive, which is noted in the header.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{ffi::write_header, Archive};
+///
+/// #[derive(Archive)]
+/// #[rkyv(derive_describe)]
+/// struct Point {
+///     x: f32,
+///     y: f32,
+/// }
+///
+/// let mut header = String::new();
+/// write_header::<ArchivedPoint>(&mut header).unwrap();
+/// assert!(header.contains("typedef struct ArchivedPoint {"));
+/// ```
+pub fn write_header<T: Describe>(
+    out: &mut impl fmt::Write,
+) -> Result<(), DescribeError> {
+    let root = T::describe()?;
+    let root_name = match &root {
+        CType::Struct(s) => s.name(),
+        _ => {
+            return Err(DescribeError::NotAStruct {
+                type_name: type_name::<T>(),
+            })
+        }
+    };
+
+    let mut structs = Vec::new();
+    collect_structs(&root, &mut structs)?;
+
+    let guard = format!("RKYV_{}_H", root_name.to_ascii_uppercase());
+    let byte_order = if cfg!(feature = "big_endian") {
+        "big-endian"
+    } else {
+        "little-endian"
+    };
+
+    writeln!(out, "/* Generated by rkyv. Do not edit. */")?;
+    writeln!(out)?;
+    writeln!(out, "#ifndef {}", guard)?;
+    writeln!(out, "#define {}", guard)?;
+    writeln!(out)?;
+    writeln!(out, "#include <stdbool.h>")?;
+    writeln!(out, "#include <stdint.h>")?;
+    writeln!(out)?;
+    writeln!(
+        out,
+        "/* Multibyte values are stored in {} byte order. */",
+        byte_order
+    )?;
+    writeln!(out)?;
+    writeln!(out, "#pragma pack(push, 1)")?;
+    for s in structs {
+        writeln!(out)?;
+        write_struct(s, out)?;
+    }
+    writeln!(out)?;
+    writeln!(out, "#pragma pack(pop)")?;
+    writeln!(out)?;
+    writeln!(out, "#endif /* {} */", guard)?;
+
+    Ok(())
+}
+
+/// Collects the structs in `ty` so that every struct comes after the structs
+/// it contains.
+fn collect_structs<'a>(
+    ty: &'a CType,
+    out: &mut Vec<&'a CStruct>,
+) -> Result<(), DescribeError> {
+    match ty {
+        CType::Primitive { .. } => Ok(()),
+        CType::Array { element, .. } => collect_structs(element, out),
+        CType::Struct(s) => {
+            for field in s.fields.iter() {
+                collect_structs(&field.ty, out)?;
+            }
+            match out.iter().find(|other| other.name == s.name) {
+                Some(other) if *other != s => {
+                    Err(DescribeError::ConflictingNames {
+                        name: s.name.clone(),
+                    })
+                }
+                Some(_) => Ok(()),
+                None => {
+                    out.push(s);
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+fn write_struct(s: &CStruct, out: &mut impl fmt::Write) -> fmt::Result {
+    writeln!(out, "typedef struct {} {{", s.name)?;
+    let mut pads = 0;
+    let mut end = 0;
+    for field in s.fields.iter() {
+        if field.offset > end {
+            writeln!(out, "    uint8_t _pad{}[{}];", pads, field.offset - end)?;
+            pads += 1;
+        }
+        write!(out, "    ")?;
+        field.ty.declare(&field.name, out)?;
+        writeln!(out, ";")?;
+        end = field.offset + field.ty.size();
+    }
+    if s.size > end {
+        writeln!(out, "    uint8_t _pad{}[{}];", pads, s.size - end)?;
+    }
+    writeln!(out, "}} {};", s.name)?;
+    writeln!(
+        out,
+        "typedef char {0}_size_check[sizeof({0}) == {1} ? 1 : -1];",
+        s.name, s.size,
+    )
+}
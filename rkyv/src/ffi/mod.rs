@@ -1,5 +1,8 @@
 //! Archived versions of FFI types.
 
+#[cfg(feature = "ffi_describe")]
+mod describe;
+
 use core::{
     borrow::Borrow,
     cmp, fmt, hash,
@@ -11,8 +14,12 @@ use std::ffi::CStr;
 use munge::munge;
 use rancor::Fallible;
 
+#[cfg(feature = "ffi_describe")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi_describe")))]
+pub use self::describe::*;
 use crate::{
-    ser::Writer, ArchiveUnsized, Place, Portable, RelPtr, SerializeUnsized,
+    collections::btree_map::LookupOrd, hash::LookupKey, ser::Writer,
+    ArchiveUnsized, Place, Portable, RelPtr, SerializeUnsized,
 };
 
 /// An archived [`CString`](std::ffi::CString).
@@ -97,6 +104,20 @@ impl Borrow<CStr> for ArchivedCString {
     }
 }
 
+impl LookupKey<ArchivedCString> for CStr {
+    #[inline]
+    fn equivalent(&self, key: &ArchivedCString) -> bool {
+        self == key.as_c_str()
+    }
+}
+
+impl LookupOrd<ArchivedCString> for CStr {
+    #[inline]
+    fn compare(&self, key: &ArchivedCString) -> cmp::Ordering {
+        self.cmp(key.as_c_str())
+    }
+}
+
 impl fmt::Debug for ArchivedCString {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1,11 +1,509 @@
+//! Formatting utilities for archived data.
+//!
+//! The `Debug` output of large archives can be too long to be useful. The
+//! [`pretty`] and [`pretty_with`] adapters format values with their pretty
+//! `Debug` implementations, and truncate deeply-nested values, long sequences,
+//! and long strings.
+
 use core::fmt;
 
 const PTR_WIDTH: usize = (usize::BITS / 4 + 2) as usize;
 
-pub struct Pointer(pub usize);
+pub(crate) struct Pointer(pub usize);
 
 impl fmt::Display for Pointer {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{:#0w$x}", self.0, w = PTR_WIDTH)
     }
 }
+
+#[cfg(feature = "alloc")]
+pub use self::pretty::{pretty, pretty_with, Pretty, PrettyOptions};
+
+#[cfg(feature = "alloc")]
+mod pretty {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+    use core::fmt::{self, Write as _};
+
+    /// Options which control how values are truncated by [`pretty_with`].
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct PrettyOptions {
+        /// The maximum number of nested structs, tuples, sequences, and maps
+        /// to print. The contents of more deeply-nested values are
+        /// replaced with `..`.
+        pub max_depth: usize,
+        /// The maximum number of fields, elements, or entries to print for
+        /// each struct, tuple, sequence, or map. The rest are replaced with a
+        /// count of the omitted items.
+        pub max_seq_items: usize,
+        /// The maximum number of characters to print for each string. Longer
+        /// strings are cut off and end with `..`.
+        pub max_str_len: usize,
+    }
+
+    impl Default for PrettyOptions {
+        fn default() -> Self {
+            Self {
+                max_depth: 8,
+                max_seq_items: 16,
+                max_str_len: 64,
+            }
+        }
+    }
+
+    /// Returns an adapter which pretty-prints the given value with the default
+    /// [`PrettyOptions`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rkyv::fmt::pretty;
+    ///
+    /// let values = (0..100).collect::<Vec<i32>>();
+    /// let output = pretty(&values).to_string();
+    /// assert!(output.ends_with("    15,\n    ..84 more\n]"));
+    /// ```
+    pub fn pretty<T: fmt::Debug + ?Sized>(value: &T) -> Pretty<'_, T> {
+        pretty_with(value, PrettyOptions::default())
+    }
+
+    /// Returns an adapter which pretty-prints the given value with the given
+    /// options.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rkyv::fmt::{pretty_with, PrettyOptions};
+    ///
+    /// let value = ("a long string", vec![vec![1, 2, 3]]);
+    /// let options = PrettyOptions {
+    ///     max_depth: 2,
+    ///     max_seq_items: 16,
+    ///     max_str_len: 6,
+    /// };
+    /// assert_eq!(
+    ///     pretty_with(&value, options).to_string(),
+    ///     "(\n    \"a long..\",\n    [\n        [..],\n    ],\n)",
+    /// );
+    /// ```
+    pub fn pretty_with<T: fmt::Debug + ?Sized>(
+        value: &T,
+        options: PrettyOptions,
+    ) -> Pretty<'_, T> {
+        Pretty { value, options }
+    }
+
+    /// A value which is pretty-printed with truncation.
+    ///
+    /// This is returned by [`pretty`] and [`pretty_with`]. Note that the value
+    /// is still fully formatted; truncation only limits the size of the
+    /// output.
+    pub struct Pretty<'a, T: ?Sized> {
+        value: &'a T,
+        options: PrettyOptions,
+    }
+
+    impl<T: fmt::Debug + ?Sized> fmt::Display for Pretty<'_, T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let mut out = Truncate::new(f, self.options);
+            write!(out, "{:#?}", self.value)?;
+            out.finish()
+        }
+    }
+
+    impl<T: fmt::Debug + ?Sized> fmt::Debug for Pretty<'_, T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            fmt::Display::fmt(self, f)
+        }
+    }
+
+    enum Hidden {
+        /// The contents of a value nested more than `max_depth` levels deep.
+        Nested,
+        /// The remaining items of a value with more than `max_seq_items`
+        /// items, along with the number of items hidden so far.
+        Items(usize),
+    }
+
+    /// An apostrophe which may be the start of a char literal.
+    enum Apostrophe {
+        /// Only the apostrophe has been written.
+        Open,
+        /// The apostrophe and one char after it have been written.
+        Char(char),
+    }
+
+    /// A writer which truncates the alternate `Debug` output written to it.
+    struct Truncate<'a, W: ?Sized> {
+        out: &'a mut W,
+        options: PrettyOptions,
+        /// The number of items printed in each open value.
+        items: Vec<usize>,
+        /// The number of open values, including hidden ones.
+        depth: usize,
+        /// The depth of the value being hidden and why it's hidden.
+        hidden: Option<(usize, Hidden)>,
+        /// The opening quote of the string or char being written.
+        quote: Option<char>,
+        /// The apostrophe which was just written, if it may start a char.
+        apostrophe: Option<Apostrophe>,
+        escaped: bool,
+        str_len: usize,
+    }
+
+    impl<'a, W: fmt::Write + ?Sized> Truncate<'a, W> {
+        fn new(out: &'a mut W, options: PrettyOptions) -> Self {
+            Self {
+                out,
+                options,
+                items: Vec::new(),
+                depth: 0,
+                hidden: None,
+                quote: None,
+                apostrophe: None,
+                escaped: false,
+                str_len: 0,
+            }
+        }
+
+        fn indent(&mut self, depth: usize) -> fmt::Result {
+            for _ in 0..depth {
+                self.out.write_str("    ")?;
+            }
+            Ok(())
+        }
+
+        fn quoted(&mut self, quote: char, c: char) -> fmt::Result {
+            if self.escaped {
+                self.escaped = false;
+            } else if c == '\\' {
+                self.escaped = true;
+            } else if c == quote {
+                self.quote = None;
+                if self.hidden.is_none() {
+                    self.out.write_char(c)?;
+                }
+                return Ok(());
+            }
+
+            if self.hidden.is_some() {
+                return Ok(());
+            }
+
+            // Escape sequences are counted as a single character
+            if self.str_len < self.options.max_str_len {
+                self.out.write_char(c)?;
+                if !self.escaped {
+                    self.str_len += 1;
+                }
+            } else if self.str_len == self.options.max_str_len {
+                self.out.write_str("..")?;
+                self.str_len += 1;
+            }
+            Ok(())
+        }
+
+        fn plain(&mut self, c: char) -> fmt::Result {
+            if self.hidden.is_none() {
+                self.out.write_char(c)?;
+            }
+            Ok(())
+        }
+
+        /// Writes the char after an apostrophe.
+        ///
+        /// Apostrophes are only treated as the start of a char literal if they
+        /// are followed by a char and another apostrophe (`'x'`), or by an
+        /// escape sequence (`'\n'`). Any other apostrophe is written as-is.
+        fn after_apostrophe(
+            &mut self,
+            apostrophe: Apostrophe,
+            c: char,
+        ) -> fmt::Result {
+            match (apostrophe, c) {
+                (Apostrophe::Open, '\\') => {
+                    self.plain('\'')?;
+                    self.quote = Some('\'');
+                    self.str_len = 0;
+                    self.quoted('\'', c)
+                }
+                (Apostrophe::Open, '\'') => {
+                    self.plain('\'')?;
+                    self.apostrophe = Some(Apostrophe::Open);
+                    Ok(())
+                }
+                (Apostrophe::Open, c) => {
+                    self.apostrophe = Some(Apostrophe::Char(c));
+                    Ok(())
+                }
+                (Apostrophe::Char(x), '\'') => {
+                    self.plain('\'')?;
+                    self.plain(x)?;
+                    self.plain('\'')
+                }
+                (Apostrophe::Char(x), c) => {
+                    self.plain('\'')?;
+                    self.write_char(x)?;
+                    self.write_char(c)
+                }
+            }
+        }
+
+        /// Writes any chars which are still held back after an apostrophe.
+        fn finish(&mut self) -> fmt::Result {
+            match self.apostrophe.take() {
+                Some(Apostrophe::Open) => self.plain('\''),
+                Some(Apostrophe::Char(x)) => {
+                    self.plain('\'')?;
+                    self.write_char(x)
+                }
+                None => Ok(()),
+            }
+        }
+
+        fn open(&mut self, c: char) -> fmt::Result {
+            self.depth += 1;
+            if self.hidden.is_some() {
+                return Ok(());
+            }
+
+            self.out.write_char(c)?;
+            if self.depth > self.options.max_depth {
+                self.out.write_str("..")?;
+                self.hidden = Some((self.depth, Hidden::Nested));
+            } else {
+                self.items.push(0);
+                if self.options.max_seq_items == 0 {
+                    self.hidden = Some((self.depth, Hidden::Items(0)));
+                }
+            }
+            Ok(())
+        }
+
+        fn close(&mut self, c: char) -> fmt::Result {
+            let depth = self.depth;
+            self.depth -= 1;
+            match self.hidden {
+                Some((hidden_depth, ref hidden)) if hidden_depth == depth => {
+                    if let Hidden::Items(count) = *hidden {
+                        self.items.pop();
+                        self.out.write_char('\n')?;
+                        if count > 0 {
+                            self.indent(depth)?;
+                            writeln!(self.out, "..{} more", count)?;
+                        }
+                        self.indent(depth - 1)?;
+                    }
+                    self.hidden = None;
+                    self.out.write_char(c)
+                }
+                Some(_) => Ok(()),
+                None => {
+                    self.items.pop();
+                    self.out.write_char(c)
+                }
+            }
+        }
+
+        fn comma(&mut self) -> fmt::Result {
+            match self.hidden {
+                Some((hidden_depth, Hidden::Items(ref mut count)))
+                    if hidden_depth == self.depth =>
+                {
+                    *count += 1;
+                    Ok(())
+                }
+                Some(_) => Ok(()),
+                None => {
+                    self.out.write_char(',')?;
+                    if let Some(count) = self.items.last_mut() {
+                        *count += 1;
+                        if *count == self.options.max_seq_items {
+                            self.hidden = Some((self.depth, Hidden::Items(0)));
+                        }
+                    }
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    impl<W: fmt::Write + ?Sized> fmt::Write for Truncate<'_, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            for c in s.chars() {
+                self.write_char(c)?;
+            }
+            Ok(())
+        }
+
+        fn write_char(&mut self, c: char) -> fmt::Result {
+            if let Some(quote) = self.quote {
+                return self.quoted(quote, c);
+            }
+            if let Some(apostrophe) = self.apostrophe.take() {
+                return self.after_apostrophe(apostrophe, c);
+            }
+
+            match c {
+                '{' | '[' | '(' => self.open(c),
+                '}' | ']' | ')' => self.close(c),
+                ',' => self.comma(),
+                '\'' => {
+                    self.apostrophe = Some(Apostrophe::Open);
+                    Ok(())
+                }
+                _ => {
+                    if c == '"' {
+                        self.quote = Some(c);
+                        self.str_len = 0;
+                    }
+                    self.plain(c)
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        #[cfg(not(feature = "std"))]
+        use alloc::{
+            format,
+            string::{String, ToString},
+            vec,
+            vec::Vec,
+        };
+        use core::fmt;
+
+        use super::{pretty, pretty_with, PrettyOptions};
+        use crate::{test::to_archived, Archive, Serialize};
+
+        #[derive(Archive, Serialize)]
+        #[rkyv(crate, check_bytes, derive(Debug))]
+        struct Example {
+            name: String,
+            values: Vec<u32>,
+            children: Vec<Example>,
+        }
+
+        fn options(
+            max_depth: usize,
+            max_seq_items: usize,
+            max_str_len: usize,
+        ) -> PrettyOptions {
+            PrettyOptions {
+                max_depth,
+                max_seq_items,
+                max_str_len,
+            }
+        }
+
+        #[test]
+        fn untruncated() {
+            let value = vec![Some("hello"), None];
+            assert_eq!(pretty(&value).to_string(), format!("{:#?}", value));
+        }
+
+        #[test]
+        fn truncate_depth() {
+            let value = vec![vec![vec![1]], vec![]];
+            assert_eq!(
+                pretty_with(&value, options(2, 16, 64)).to_string(),
+                "[\n    [\n        [..],\n    ],\n    [],\n]",
+            );
+            assert_eq!(
+                pretty_with(&value, options(0, 16, 64)).to_string(),
+                "[..]",
+            );
+        }
+
+        #[test]
+        fn truncate_items() {
+            let value = (0..10).collect::<Vec<i32>>();
+            assert_eq!(
+                pretty_with(&value, options(8, 2, 64)).to_string(),
+                "[\n    0,\n    1,\n    ..8 more\n]",
+            );
+            assert_eq!(
+                pretty_with(&value, options(8, 0, 64)).to_string(),
+                "[\n    ..10 more\n]",
+            );
+            assert_eq!(
+                pretty_with(&value, options(8, 10, 64)).to_string(),
+                format!("{:#?}", value),
+            );
+        }
+
+        #[test]
+        fn truncate_strings() {
+            let value = ["a (string), [with] {brackets}", "\"quoted\""];
+            assert_eq!(
+                pretty_with(&value, options(8, 16, 10)).to_string(),
+                "[\n    \"a (string)..\",\n    \"\\\"quoted\\\"\",\n]",
+            );
+            let output = pretty_with(&'[', options(0, 0, 64)).to_string();
+            assert_eq!(output, "'['");
+        }
+
+        #[test]
+        fn truncate_chars() {
+            let value = ['a', '\'', '\n', '"', '(', '\u{1f980}'];
+            assert_eq!(
+                pretty_with(&value, options(8, 16, 64)).to_string(),
+                format!("{:#?}", value),
+            );
+            assert_eq!(
+                pretty_with(&value, options(8, 1, 64)).to_string(),
+                "[\n    'a',\n    ..5 more\n]",
+            );
+        }
+
+        #[test]
+        fn truncate_apostrophes() {
+            struct Note;
+
+            impl fmt::Debug for Note {
+                fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    f.write_str("it's ")?;
+                    f.debug_list().entries(0..5).finish()?;
+                    f.write_str(" and 'x")
+                }
+            }
+
+            assert_eq!(
+                pretty_with(&Note, options(8, 2, 64)).to_string(),
+                "it's [\n    0,\n    1,\n    ..3 more\n] and 'x",
+            );
+        }
+
+        #[test]
+        fn truncate_archived() {
+            let leaf = |name: &str| Example {
+                name: name.to_string(),
+                values: Vec::new(),
+                children: Vec::new(),
+            };
+            let value = Example {
+                name: "root".repeat(100),
+                values: (0..1000).collect(),
+                children: vec![Example {
+                    children: vec![leaf("a"), leaf("b")],
+                    ..leaf("child")
+                }],
+            };
+
+            to_archived(&value, |archived| {
+                let output = pretty(archived).to_string();
+                assert!(output.len() < 2000);
+                assert!(output.contains("..984 more\n"));
+                assert!(output.contains("rootroot..\""));
+                assert!(output.contains("name: \"child\""));
+
+                let output =
+                    pretty_with(archived, options(2, 16, 64)).to_string();
+                assert!(output.contains("ArchivedExample {..}"));
+                assert!(!output.contains("\"a\""));
+            });
+        }
+    }
+}
@@ -0,0 +1,593 @@
+//! Golden file tests for archive layouts.
+//!
+//! A snapshot is a file containing the serialized bytes of a value. Checking a
+//! value against its snapshot catches any change to the archived layout of its
+//! type, whether it comes from a change to the type or from a new version of
+//! rkyv. This is most useful to crates which store archives on disk or send
+//! them over the network, since those archives must keep their layout across
+//! releases.
+//!
+//! When a value doesn't match its snapshot, the new bytes are written next to
+//! the snapshot with a `.new` extension and the check panics with a report of
+//! the bytes which differ. If the change was intentional, rerun the tests with
+//! the `RKYV_UPDATE_SNAPSHOTS` environment variable set to overwrite the
+//! snapshots instead.
+//!
+//! If the archived type implements [`Diff`], [`check_snapshot_diff`] and
+//! [`assert_archive_snapshot_diff!`] also report the paths of the fields which
+//! differ, as long as the snapshot is still a valid archive of the type.
+//!
+//! # Nondeterministic values
+//!
+//! Snapshots only work for values which always serialize to the same bytes.
+//! Values are serialized twice before they're compared to their snapshot, and
+//! the check panics if the two serializations differ. The most common source
+//! of nondeterminism is iteration order: a `HashMap` with a randomly-seeded
+//! hasher iterates its entries in a different order every time the program
+//! runs, and so may write its keys and values in a different order. Use a
+//! `BTreeMap` or a hasher with a fixed seed for values which are checked
+//! against snapshots.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use rkyv::{assert_archive_snapshot, Archive, Serialize};
+//!
+//! #[derive(Archive, Serialize)]
+//! struct Header {
+//!     version: u16,
+//!     name: String,
+//! }
+//!
+//! let header = Header {
+//!     version: 3,
+//!     name: "example".to_string(),
+//! };
+//!
+//! // The path is relative to the directory of the crate being tested.
+//! assert_archive_snapshot!(header, "tests/snapshots/header.bin");
+//! ```
+
+use std::{
+    env,
+    ffi::OsString,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bytecheck::CheckBytes;
+use rancor::{Error, Strategy};
+
+use crate::{
+    access,
+    diff::{Change, Diff, DiffReport},
+    ser::DefaultSerializer,
+    to_bytes,
+    util::{align_or_copy, AlignedVec},
+    validation::validators::DefaultValidator,
+    Archive, Portable, Serialize,
+};
+
+/// The environment variable which makes snapshot checks overwrite their
+/// snapshots instead of comparing against them.
+///
+/// Snapshots are updated when this variable is set to any value other than an
+/// empty string or `0`.
+pub const UPDATE_ENV_VAR: &str = "RKYV_UPDATE_SNAPSHOTS";
+
+/// The maximum number of differing runs of bytes shown in a [`Report`].
+const MAX_REPORTED_DIFFS: usize = 16;
+
+/// A run of consecutive bytes which differ between two byte slices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByteDiff {
+    /// The offset of the first differing byte.
+    pub offset: usize,
+    /// The bytes in the old slice. This is shorter than `new` if the old
+    /// slice ended partway through the run.
+    pub old: Vec<u8>,
+    /// The bytes in the new slice. This is shorter than `old` if the new
+    /// slice ended partway through the run.
+    pub new: Vec<u8>,
+}
+
+/// Returns the runs of bytes which differ between `old` and `new`.
+///
+/// If one slice is longer than the other, its remaining bytes are part of the
+/// last run.
+pub fn diff(old: &[u8], new: &[u8]) -> Vec<ByteDiff> {
+    let len = old.len().max(new.len());
+    let mut diffs = Vec::new();
+    let mut i = 0;
+    while i < len {
+        if old.get(i) == new.get(i) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < len && old.get(i) != new.get(i) {
+            i += 1;
+        }
+        diffs.push(ByteDiff {
+            offset: start,
+            old: old[start.min(old.len())..i.min(old.len())].to_vec(),
+            new: new[start.min(new.len())..i.min(new.len())].to_vec(),
+        });
+    }
+    diffs
+}
+
+/// A report of the differences between a snapshot and the bytes it was
+/// checked against.
+///
+/// The `Display` implementation lists the paths which differ, if any, followed
+/// by the offset of each differing run of bytes along with the old and new
+/// bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    /// The length of the snapshot.
+    pub old_len: usize,
+    /// The length of the new bytes.
+    pub new_len: usize,
+    /// The runs of bytes which differ.
+    pub diffs: Vec<ByteDiff>,
+    /// The paths which differ between the archived values.
+    ///
+    /// This is only filled in by [`Report::with_diff`] when both byte slices
+    /// are valid archives of the type, and is empty otherwise.
+    pub changes: Vec<Change>,
+}
+
+impl Report {
+    /// Compares `old` and `new`, returning `None` if they are equal.
+    pub fn new(old: &[u8], new: &[u8]) -> Option<Self> {
+        let diffs = diff(old, new);
+        if diffs.is_empty() {
+            None
+        } else {
+            Some(Self {
+                old_len: old.len(),
+                new_len: new.len(),
+                diffs,
+                changes: Vec::new(),
+            })
+        }
+    }
+
+    /// Compares `old` and `new` as archives of `T`, returning `None` if they
+    /// are equal.
+    ///
+    /// If both are valid archives of `T`, the paths which differ between them
+    /// are recorded in [`changes`](Self::changes). An old snapshot may not be
+    /// valid anymore if the layout of `T` changed, in which case only the
+    /// differing bytes are reported.
+    pub fn with_diff<T>(old: &[u8], new: &[u8]) -> Option<Self>
+    where
+        T: Portable + Diff + for<'a> CheckBytes<Validator<'a>>,
+    {
+        let mut report = Self::new(old, new)?;
+
+        let mut old_scratch = AlignedVec::<16>::new();
+        let mut new_scratch = AlignedVec::<16>::new();
+        let old = access::<T, Error>(align_or_copy(old, &mut old_scratch));
+        let new = access::<T, Error>(align_or_copy(new, &mut new_scratch));
+        if let (Ok(old), Ok(new)) = (old, new) {
+            let mut changes = DiffReport::new();
+            old.diff(new, &mut changes);
+            report.changes = changes.into_changes();
+        }
+
+        Some(report)
+    }
+}
+
+type Validator<'a> = Strategy<DefaultValidator<'a>, Error>;
+
+struct Hex<'a>(&'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "(none)");
+        }
+        for (i, byte) in self.0.iter().enumerate() {
+            if i != 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} differing byte run(s); snapshot is {} bytes, new archive is \
+             {} bytes",
+            self.diffs.len(),
+            self.old_len,
+            self.new_len,
+        )?;
+        if !self.changes.is_empty() {
+            write!(f, "\n  paths which differ:")?;
+            for change in self.changes.iter().take(MAX_REPORTED_DIFFS) {
+                write!(f, "\n    {}", change)?;
+            }
+            if self.changes.len() > MAX_REPORTED_DIFFS {
+                write!(
+                    f,
+                    "\n    ... and {} more",
+                    self.changes.len() - MAX_REPORTED_DIFFS,
+                )?;
+            }
+        }
+        for diff in self.diffs.iter().take(MAX_REPORTED_DIFFS) {
+            write!(
+                f,
+                "\n  at {:#06x}:\n    old: {}\n    new: {}",
+                diff.offset,
+                Hex(&diff.old),
+                Hex(&diff.new),
+            )?;
+        }
+        if self.diffs.len() > MAX_REPORTED_DIFFS {
+            write!(
+                f,
+                "\n  ... and {} more",
+                self.diffs.len() - MAX_REPORTED_DIFFS,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether snapshots should be updated instead of checked.
+///
+/// See [`UPDATE_ENV_VAR`].
+pub fn update_requested() -> bool {
+    match env::var_os(UPDATE_ENV_VAR) {
+        Some(value) => !value.is_empty() && value != "0",
+        None => false,
+    }
+}
+
+fn new_path(path: &Path) -> PathBuf {
+    let mut new = OsString::from(path.as_os_str());
+    new.push(".new");
+    PathBuf::from(new)
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn write_snapshot(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, bytes)
+}
+
+fn compare(bytes: &[u8], path: &Path, update: bool) -> Result<(), String> {
+    compare_with(bytes, path, update, Report::new)
+}
+
+fn compare_with(
+    bytes: &[u8],
+    path: &Path,
+    update: bool,
+    report: impl FnOnce(&[u8], &[u8]) -> Option<Report>,
+) -> Result<(), String> {
+    let new = new_path(path);
+    let fail_io = |action: &str, path: &Path, e: io::Error| {
+        format!("failed to {} `{}`: {}", action, path.display(), e)
+    };
+
+    if update {
+        write_snapshot(path, bytes).map_err(|e| fail_io("write", path, e))?;
+        return remove_if_exists(&new).map_err(|e| fail_io("remove", &new, e));
+    }
+
+    let message = match fs::read(path) {
+        Ok(old) => match report(&old, bytes) {
+            None => {
+                return remove_if_exists(&new)
+                    .map_err(|e| fail_io("remove", &new, e));
+            }
+            Some(report) => format!(
+                "archive doesn't match snapshot `{}`\n{}",
+                path.display(),
+                report,
+            ),
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            format!("snapshot `{}` doesn't exist", path.display())
+        }
+        Err(e) => return Err(fail_io("read", path, e)),
+    };
+
+    write_snapshot(&new, bytes).map_err(|e| fail_io("write", &new, e))?;
+    Err(format!(
+        "{}\nthe new archive was written to `{}`; rerun with {}=1 to update \
+         the snapshot",
+        message,
+        new.display(),
+        UPDATE_ENV_VAR,
+    ))
+}
+
+/// Checks the given bytes against the snapshot at `path`.
+///
+/// If the bytes don't match, they are written to the same path with a `.new`
+/// extension. If [`update_requested`] returns `true`, the snapshot is
+/// overwritten instead.
+///
+/// # Panics
+///
+/// Panics if the snapshot doesn't exist or doesn't match the bytes, or if the
+/// snapshot can't be read or written.
+#[track_caller]
+pub fn check_snapshot_bytes(bytes: &[u8], path: impl AsRef<Path>) {
+    if let Err(message) = compare(bytes, path.as_ref(), update_requested()) {
+        panic!("{}", message);
+    }
+}
+
+#[track_caller]
+fn serialize_twice<T>(value: &T, path: &Path) -> AlignedVec
+where
+    T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+{
+    let serialize = || {
+        to_bytes::<Error>(value).unwrap_or_else(|e| {
+            panic!(
+                "failed to serialize value for snapshot `{}`: {}",
+                path.display(),
+                e,
+            )
+        })
+    };
+
+    let bytes = serialize();
+    if let Some(report) = Report::new(&bytes, &serialize()) {
+        panic!(
+            "value for snapshot `{}` serialized to different bytes each time; \
+             only deterministic values can be checked against snapshots\n{}",
+            path.display(),
+            report,
+        );
+    }
+    bytes
+}
+
+/// Serializes the given value and checks it against the snapshot at `path`.
+///
+/// The value is serialized twice to check that it serializes
+/// deterministically. See [`check_snapshot_bytes`] for how the bytes are
+/// checked against the snapshot.
+///
+/// # Panics
+///
+/// Panics if the value cannot be serialized, if it serializes to different
+/// bytes each time, or if it doesn't match the snapshot.
+#[track_caller]
+pub fn check_snapshot<T>(value: &T, path: impl AsRef<Path>)
+where
+    T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+{
+    let path = path.as_ref();
+    let bytes = serialize_twice(value, path);
+    check_snapshot_bytes(&bytes, path);
+}
+
+/// Serializes the given value and checks it against the snapshot at `path`,
+/// reporting the paths which differ.
+///
+/// This is like [`check_snapshot`], but the report also lists the paths which
+/// differ between the snapshot and the new archive. See [`Report::with_diff`].
+///
+/// # Panics
+///
+/// Panics if the value cannot be serialized, if it serializes to different
+/// bytes each time, or if it doesn't match the snapshot.
+#[track_caller]
+pub fn check_snapshot_diff<T>(value: &T, path: impl AsRef<Path>)
+where
+    T: Archive + for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+    T::Archived: Diff + for<'a> CheckBytes<Validator<'a>>,
+{
+    let path = path.as_ref();
+    let bytes = serialize_twice(value, path);
+    let result = compare_with(
+        &bytes,
+        path,
+        update_requested(),
+        Report::with_diff::<T::Archived>,
+    );
+    if let Err(message) = result {
+        panic!("{}", message);
+    }
+}
+
+/// Serializes a value and checks it against a snapshot file.
+///
+/// The path is relative to the root of the crate being tested. See
+/// [`check_snapshot`](crate::golden::check_snapshot) for details.
+///
+/// # Examples
+///
+/// ```no_run
+/// rkyv::assert_archive_snapshot!(vec![1u32, 2, 3], "tests/snapshots/vec.bin");
+/// ```
+#[macro_export]
+macro_rules! assert_archive_snapshot {
+    ($value:expr, $path:expr $(,)?) => {
+        $crate::golden::check_snapshot(
+            &$value,
+            ::std::path::Path::new(::core::env!("CARGO_MANIFEST_DIR"))
+                .join($path),
+        )
+    };
+}
+
+/// Serializes a value and checks it against a snapshot file, reporting the
+/// paths which differ.
+///
+/// The archived type must implement [`Diff`](crate::diff::Diff). See
+/// [`check_snapshot_diff`](crate::golden::check_snapshot_diff) for details.
+///
+/// # Examples
+///
+/// ```no_run
+/// rkyv::assert_archive_snapshot_diff!(
+///     vec![1u32, 2, 3],
+///     "tests/snapshots/vec.bin",
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_archive_snapshot_diff {
+    ($value:expr, $path:expr $(,)?) => {
+        $crate::golden::check_snapshot_diff(
+            &$value,
+            ::std::path::Path::new(::core::env!("CARGO_MANIFEST_DIR"))
+                .join($path),
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf, process};
+
+    use rancor::Error;
+
+    use super::{compare, diff, new_path, ByteDiff, Report};
+    use crate::{to_bytes, Archive, Serialize};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("rkyv-golden-{}", process::id()))
+            .join(name)
+    }
+
+    #[test]
+    fn diff_runs() {
+        assert!(diff(&[1, 2, 3], &[1, 2, 3]).is_empty());
+        assert_eq!(
+            diff(&[1, 2, 3, 4, 5], &[1, 9, 9, 4, 9]),
+            vec![
+                ByteDiff {
+                    offset: 1,
+                    old: vec![2, 3],
+                    new: vec![9, 9],
+                },
+                ByteDiff {
+                    offset: 4,
+                    old: vec![5],
+                    new: vec![9],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn diff_lengths() {
+        assert_eq!(
+            diff(&[1, 2], &[1, 3, 4]),
+            vec![ByteDiff {
+                offset: 1,
+                old: vec![2],
+                new: vec![3, 4],
+            }],
+        );
+        assert_eq!(
+            diff(&[1, 2, 3], &[1]),
+            vec![ByteDiff {
+                offset: 1,
+                old: vec![2, 3],
+                new: vec![],
+            }],
+        );
+    }
+
+    #[test]
+    fn report_format() {
+        let report = Report::new(&[0, 1, 2], &[0, 0xab]).unwrap();
+        assert_eq!(
+            report.to_string(),
+            "1 differing byte run(s); snapshot is 3 bytes, new archive is 2 \
+             bytes\n  at 0x0001:\n    old: 01 02\n    new: ab",
+        );
+        assert!(Report::new(&[1, 2], &[1, 2]).is_none());
+    }
+
+    #[test]
+    fn report_paths() {
+        #[derive(Archive, Serialize)]
+        #[rkyv(crate, check_bytes, derive_diff)]
+        struct Record {
+            id: u32,
+            name: String,
+        }
+
+        let old = to_bytes::<Error>(&Record {
+            id: 1,
+            name: "record".to_string(),
+        })
+        .unwrap();
+        let new = to_bytes::<Error>(&Record {
+            id: 2,
+            name: "record".to_string(),
+        })
+        .unwrap();
+
+        let report = Report::with_diff::<ArchivedRecord>(&old, &new).unwrap();
+        let paths = report
+            .changes
+            .iter()
+            .map(|change| change.path())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["id"]);
+        assert!(report
+            .to_string()
+            .contains("paths which differ:\n    `id` changed"));
+        assert!(Report::with_diff::<ArchivedRecord>(&old, &old).is_none());
+
+        // Snapshots which aren't valid anymore only report their bytes.
+        let report =
+            Report::with_diff::<ArchivedRecord>(&[0xff; 4], &new).unwrap();
+        assert!(report.changes.is_empty());
+        assert!(!report.to_string().contains("paths which differ"));
+    }
+
+    #[test]
+    fn snapshot_lifecycle() {
+        let path = temp_path("lifecycle.bin");
+        let new = new_path(&path);
+
+        // A missing snapshot fails and writes the new bytes.
+        let message = compare(&[1, 2, 3], &path, false).unwrap_err();
+        assert!(message.contains("doesn't exist"));
+        assert_eq!(fs::read(&new).unwrap(), [1, 2, 3]);
+
+        // Updating writes the snapshot and cleans up the new bytes.
+        compare(&[1, 2, 3], &path, true).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), [1, 2, 3]);
+        assert!(!new.exists());
+        compare(&[1, 2, 3], &path, false).unwrap();
+
+        // A mismatch reports the differing bytes and leaves the snapshot.
+        let message = compare(&[1, 4, 3], &path, false).unwrap_err();
+        assert!(message.contains("at 0x0001"));
+        assert_eq!(fs::read(&path).unwrap(), [1, 2, 3]);
+        assert_eq!(fs::read(&new).unwrap(), [1, 4, 3]);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -5,9 +5,27 @@ use core::{
     ops::BitXor as _,
 };
 
-use crate::primitive::{FixedIsize, FixedUsize};
+use crate::primitive::{
+    ArchivedChar, ArchivedI128, ArchivedI16, ArchivedI32, ArchivedI64,
+    ArchivedIsize, ArchivedU128, ArchivedU16, ArchivedU32, ArchivedU64,
+    ArchivedUsize, FixedIsize, FixedUsize,
+};
+
+/// The version of the hash function used by archived hash maps and sets.
+///
+/// Archived hash maps, hash sets, and index maps store their entries according
+/// to the hashes of their keys, so the hash function is part of the archive
+/// format. For a given format version, [`hash_value`], [`hash_bytes`], and
+/// [`FxHasher64`] are guaranteed to produce the same output for the same input
+/// on every platform and in every release of rkyv. If the hash function ever
+/// has to change, this version will be incremented and archives containing
+/// hash maps or sets serialized with a different version will not be readable.
+pub const FORMAT_VERSION: u32 = 1;
 
 /// A cross-platform 64-bit implementation of fxhash.
+///
+/// This is the default hasher of archived hash maps and sets. Its output is
+/// stable for a given [`FORMAT_VERSION`].
 #[derive(Default)]
 pub struct FxHasher64 {
     hash: u64,
@@ -22,7 +40,7 @@ fn hash_word(hash: u64, word: u64) -> u64 {
 }
 
 #[inline]
-fn hash_bytes(mut hash: u64, bytes: &[u8]) -> u64 {
+fn hash_slice(mut hash: u64, bytes: &[u8]) -> u64 {
     let ptr = bytes.as_ptr();
     let len = bytes.len();
 
@@ -56,7 +74,7 @@ fn hash_bytes(mut hash: u64, bytes: &[u8]) -> u64 {
 impl Hasher for FxHasher64 {
     #[inline]
     fn write(&mut self, bytes: &[u8]) {
-        self.hash = hash_bytes(self.hash, bytes);
+        self.hash = hash_slice(self.hash, bytes);
     }
 
     #[inline]
@@ -114,8 +132,38 @@ impl Hasher for FxHasher64 {
     }
 }
 
+/// Hashes the given value with [`FxHasher64`].
+///
+/// This is the hash archived hash maps and sets compute for their keys by
+/// default. Its output is stable for a given [`FORMAT_VERSION`].
+///
+/// # Examples
+///
+/// ```
+/// use rkyv::hash::hash_value;
+///
+/// assert_eq!(hash_value("hello"), 0xd940_de97_f501_1cc0);
+/// assert_eq!(hash_value(&1u64), 0x517c_c1b7_2722_0a95);
+/// ```
+pub fn hash_value<T: Hash + ?Sized>(value: &T) -> u64 {
+    hash_value_with::<T, FxHasher64>(value)
+}
+
+/// Hashes the given bytes with [`FxHasher64`].
+///
+/// This is equivalent to writing `bytes` to a new `FxHasher64` with a single
+/// call to [`Hasher::write`]. It can be used to compute key hashes for custom
+/// `Hash` implementations that write raw bytes. Note that this is different
+/// from hashing a `[u8]` with [`hash_value`], which also hashes the length of
+/// the slice.
+///
+/// Its output is stable for a given [`FORMAT_VERSION`].
+pub fn hash_bytes(bytes: &[u8]) -> u64 {
+    hash_slice(0, bytes)
+}
+
 /// Hashes the given value with the default value of the specified `Hasher`.
-pub fn hash_value<Q, H: Hasher + Default>(value: &Q) -> u64
+pub fn hash_value_with<Q, H: Hasher + Default>(value: &Q) -> u64
 where
     Q: Hash + ?Sized,
 {
@@ -123,3 +171,147 @@ where
     value.hash(&mut state);
     state.finish()
 }
+
+/// A type which can be used to look up keys of type `K` in archived hash maps
+/// and sets.
+///
+/// This allows archived maps to be queried with unarchived keys, for example
+/// looking up an [`ArchivedString`](crate::string::ArchivedString) key with a
+/// `&str` or an archived `u32` key with a `&u32`.
+///
+/// Archived hash maps and sets hash their keys _before_ they are archived. To
+/// find an entry, the lookup key must hash the same way as the unarchived key,
+/// and `equivalent` must return `true` exactly when the lookup key is equal to
+/// the unarchived key.
+///
+/// Archived maps and sets look up keys through this trait instead of
+/// [`Borrow`](core::borrow::Borrow). Archived boxes and shared pointers can be
+/// looked up by their pointee, and archived C strings by a `&CStr`. Key types
+/// which implement `Borrow` to be looked up by another type should also
+/// implement `LookupKey` for that type.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use rkyv::{access, rancor::Error, to_bytes, Archived};
+///
+/// let mut names = HashMap::new();
+/// names.insert("foo".to_string(), 1u32);
+/// let bytes = to_bytes::<Error>(&names).unwrap();
+/// let archived =
+///     access::<Archived<HashMap<String, u32>>, Error>(&bytes).unwrap();
+/// assert_eq!(archived.get("foo").map(|v| v.to_native()), Some(1));
+/// assert!(archived.get("bar").is_none());
+///
+/// let mut numbers = HashMap::new();
+/// numbers.insert(5u32, "five".to_string());
+/// let bytes = to_bytes::<Error>(&numbers).unwrap();
+/// let archived =
+///     access::<Archived<HashMap<u32, String>>, Error>(&bytes).unwrap();
+/// assert_eq!(archived.get(&5u32).map(|s| s.as_str()), Some("five"));
+/// assert!(archived.get(&6u32).is_none());
+/// ```
+pub trait LookupKey<K: ?Sized>: Hash {
+    /// Returns whether this lookup key is equal to the given key.
+    fn equivalent(&self, key: &K) -> bool;
+}
+
+impl<T: Hash + Eq + ?Sized> LookupKey<T> for T {
+    #[inline]
+    fn equivalent(&self, key: &T) -> bool {
+        self == key
+    }
+}
+
+macro_rules! impl_lookup_key_for_primitive {
+    ($($native:ty: $archived:ty),* $(,)?) => {
+        $(
+            impl LookupKey<$archived> for $native {
+                #[inline]
+                fn equivalent(&self, key: &$archived) -> bool {
+                    key.to_native() == *self
+                }
+            }
+        )*
+    };
+}
+
+impl_lookup_key_for_primitive! {
+    i16: ArchivedI16,
+    i32: ArchivedI32,
+    i64: ArchivedI64,
+    i128: ArchivedI128,
+    u16: ArchivedU16,
+    u32: ArchivedU32,
+    u64: ArchivedU64,
+    u128: ArchivedU128,
+    char: ArchivedChar,
+}
+
+impl LookupKey<ArchivedUsize> for usize {
+    #[inline]
+    fn equivalent(&self, key: &ArchivedUsize) -> bool {
+        key.to_native() as usize == *self
+    }
+}
+
+impl LookupKey<ArchivedIsize> for isize {
+    #[inline]
+    fn equivalent(&self, key: &ArchivedIsize) -> bool {
+        key.to_native() as isize == *self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::hash::Hasher as _;
+
+    use super::{hash_bytes, hash_value, FxHasher64, LookupKey};
+    use crate::primitive::{ArchivedU32, ArchivedUsize, FixedUsize};
+
+    #[test]
+    fn lookup_primitives() {
+        let key = ArchivedU32::from_native(5);
+        assert!(5u32.equivalent(&key));
+        assert!(!6u32.equivalent(&key));
+
+        let key = ArchivedUsize::from_native(5);
+        assert!(5usize.equivalent(&key));
+        assert!(!6usize.equivalent(&key));
+    }
+
+    #[test]
+    fn hash_usize_matches_archived_width() {
+        assert_eq!(hash_value::<usize>(&5), hash_value::<FixedUsize>(&5));
+    }
+
+    // These hashes are part of the archive format. If any of them change, then
+    // `FORMAT_VERSION` must be incremented.
+    #[test]
+    fn stable_hashes() {
+        assert_eq!(hash_value(""), 0x2b44_f56f_fae8_8a6b);
+        assert_eq!(hash_value("hello"), 0xd940_de97_f501_1cc0);
+        assert_eq!(hash_value(&0u64), 0);
+        assert_eq!(hash_value(&1u64), 0x517c_c1b7_2722_0a95);
+        assert_eq!(
+            hash_value(&0x0123_4567_89ab_cdefu64),
+            0x56cc_4aad_99c8_321b
+        );
+        assert_eq!(hash_value(&u64::MAX), 0xae83_3e48_d8dd_f56b);
+        assert_eq!(hash_value(&(1u32, 2u64)), 0x6a4b_e67f_f98f_abc8);
+        assert_eq!(hash_value(&("a", 1u8)), 0x5434_47ef_1488_6104);
+    }
+
+    #[test]
+    fn stable_byte_hashes() {
+        assert_eq!(hash_bytes(b""), 0);
+        assert_eq!(hash_bytes(b"hello"), 0xfb2d_d66d_2309_31c9);
+        assert_eq!(hash_bytes(b"hello, world!"), 0x647b_4e85_b797_b3f4);
+
+        let mut hasher = FxHasher64::default();
+        hasher.write(b"hello, world!");
+        assert_eq!(hash_bytes(b"hello, world!"), hasher.finish());
+    }
+}
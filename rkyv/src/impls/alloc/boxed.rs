@@ -96,7 +96,22 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::test::roundtrip;
+    #[cfg(not(feature = "std"))]
+    use alloc::{boxed::Box, string::ToString, vec, vec::Vec};
+
+    use rancor::{Failure, Panic};
+
+    use crate::{
+        access, access_unchecked,
+        test::{roundtrip, roundtrip_with},
+        to_bytes, Archived,
+    };
+
+    fn boxed_strs(strs: &[&str]) -> Box<[Box<str>]> {
+        strs.iter()
+            .map(|s| s.to_string().into_boxed_str())
+            .collect()
+    }
 
     #[test]
     fn roundtrip_box() {
@@ -167,4 +182,68 @@ mod tests {
         roundtrip(&Err::<(), _>(Vec::<i32>::new().into_boxed_slice()));
         roundtrip(&Err::<(), _>(vec![1, 2, 3, 4].into_boxed_slice()));
     }
+
+    #[test]
+    fn roundtrip_nested_boxed_slices() {
+        roundtrip(&boxed_strs(&[]));
+        roundtrip(&boxed_strs(&[""]));
+        roundtrip(&boxed_strs(&["", "", ""]));
+        roundtrip(&boxed_strs(&["hello", "", "a string which is not inline"]));
+
+        let value: Box<[Box<[Box<str>]>]> = vec![
+            boxed_strs(&[]),
+            boxed_strs(&["", "foo"]),
+            boxed_strs(&["bar"]),
+        ]
+        .into_boxed_slice();
+        roundtrip(&value);
+        roundtrip(&Vec::<Box<[Box<str>]>>::new().into_boxed_slice());
+
+        let value: Box<[Box<[u32]>]> =
+            vec![vec![].into_boxed_slice(), vec![1, 2, 3].into_boxed_slice()]
+                .into_boxed_slice();
+        roundtrip(&value);
+    }
+
+    #[test]
+    fn boxed_slice_access() {
+        let value = boxed_strs(&["hello", "", "world"]);
+        roundtrip_with(&value, |_, archived| {
+            assert_eq!(archived.metadata().to_native(), 3);
+            assert_eq!(archived.len(), 3);
+            assert!(!archived.is_empty());
+
+            assert_eq!(
+                archived.get_index(0).unwrap().metadata().to_native(),
+                5
+            );
+            assert_eq!(
+                archived.get_index(1).unwrap().metadata().to_native(),
+                0
+            );
+            assert_eq!(archived[2].get(), "world");
+            assert_eq!(archived.get_index(1..).unwrap().len(), 2);
+            assert!(archived.get_index(3).is_none());
+        });
+
+        roundtrip_with(&boxed_strs(&[]), |_, archived| {
+            assert!(archived.is_empty());
+            assert!(archived.get_index(0).is_none());
+            assert!(archived.as_slice().is_empty());
+        });
+    }
+
+    #[test]
+    fn reject_invalid_nested_str() {
+        type Value = Box<[Box<str>]>;
+
+        let mut bytes =
+            to_bytes::<Panic>(&boxed_strs(&["valid", "", "ab"])).unwrap();
+        access::<Archived<Value>, Failure>(&bytes).unwrap();
+
+        let archived = unsafe { access_unchecked::<Archived<Value>>(&bytes) };
+        let offset = archived[2].as_ptr() as usize - bytes.as_ptr() as usize;
+        bytes[offset + 1] = 0xff;
+        access::<Archived<Value>, Failure>(&bytes).unwrap_err();
+    }
 }
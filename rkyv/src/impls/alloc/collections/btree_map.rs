@@ -98,14 +98,161 @@ where
 
 #[cfg(test)]
 mod tests {
-    use rancor::Failure;
+    use core::ops::Bound;
+    use std::{
+        ffi::{CStr, CString},
+        rc::Rc,
+        sync::Arc,
+    };
+
+    use rancor::{Failure, Fallible, Source};
 
     use super::BTreeMap;
     use crate::{
-        access, test::roundtrip, util::Align, Archive, Archived, Deserialize,
-        Serialize,
+        access,
+        collections::btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+        ser::{Allocator, Writer},
+        test::{roundtrip, to_archived, to_bytes},
+        util::Align,
+        Archive, Archived, Deserialize, Place, Serialize,
     };
 
+    struct SortedPairs<const E: usize>(Vec<(u32, String)>);
+
+    impl<const E: usize> Archive for SortedPairs<E> {
+        type Archived = ArchivedBTreeMap<Archived<u32>, Archived<String>, E>;
+        type Resolver = BTreeMapResolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            ArchivedBTreeMap::resolve_from_len(self.0.len(), resolver, out);
+        }
+    }
+
+    impl<S, const E: usize> Serialize<S> for SortedPairs<E>
+    where
+        S: Fallible + Allocator + Writer + ?Sized,
+        S::Error: Source,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedBTreeMap::serialize_from_sorted_iter(
+                self.0.iter().map(|(k, v)| (k, v)),
+                serializer,
+            )
+        }
+    }
+
+    fn sorted_pairs<const E: usize>(len: u32) -> SortedPairs<E> {
+        SortedPairs((0..len).map(|i| (i * 2, i.to_string())).collect())
+    }
+
+    fn check_sorted<const E: usize>(len: u32) -> usize {
+        let value = sorted_pairs::<E>(len);
+        let mut height = 0;
+        to_archived(&value, |archived| {
+            assert_eq!(archived.fanout(), E + 1);
+            assert_eq!(archived.len(), len as usize);
+            for (k, v) in value.0.iter() {
+                let key = Archived::<u32>::from_native(*k);
+                assert_eq!(archived.get(&key).unwrap(), v);
+                let missing = Archived::<u32>::from_native(*k + 1);
+                assert!(archived.get(&missing).is_none());
+            }
+            height = archived.height();
+        });
+        height
+    }
+
+    #[test]
+    fn serialize_from_sorted_iter() {
+        assert_eq!(check_sorted::<5>(0), 0);
+        assert_eq!(check_sorted::<5>(1), 1);
+
+        let narrow = check_sorted::<1>(1000);
+        let default = check_sorted::<5>(1000);
+        let wide = check_sorted::<31>(1000);
+        assert_eq!(narrow, 10);
+        assert_eq!(default, 4);
+        assert_eq!(wide, 2);
+    }
+
+    fn check_ranges<const E: usize>(len: u32) {
+        let value = sorted_pairs::<E>(len);
+        to_archived(&value, |archived| {
+            let keys = archived.keys().map(|k| k.to_native());
+            assert!(keys.eq(value.0.iter().map(|(k, _)| *k)));
+            let keys = archived.keys().rev().map(|k| k.to_native());
+            assert!(keys.eq(value.0.iter().rev().map(|(k, _)| *k)));
+
+            for start in 0..=len * 2 {
+                let end = (start + 7).min(len * 2);
+                let range = Archived::<u32>::from_native(start)
+                    ..Archived::<u32>::from_native(end);
+                let expected =
+                    (start..end).filter(|k| k % 2 == 0).collect::<Vec<_>>();
+
+                let forward = archived.range(range.clone()).map(|(k, _)| k);
+                assert!(forward.map(|k| k.to_native()).eq(expected.clone()));
+                let backward = archived.range(range.clone()).rev();
+                assert!(backward
+                    .map(|(k, _)| k.to_native())
+                    .eq(expected.iter().rev().copied()));
+
+                // Meet in the middle from both ends
+                let mut iter = archived.range(range);
+                let mut front = Vec::new();
+                let mut back = Vec::new();
+                while let Some((k, _)) = iter.next() {
+                    front.push(k.to_native());
+                    if let Some((k, _)) = iter.next_back() {
+                        back.push(k.to_native());
+                    }
+                }
+                front.extend(back.iter().rev());
+                assert_eq!(front, expected);
+            }
+        });
+    }
+
+    #[test]
+    fn range_iteration() {
+        check_ranges::<1>(0);
+        check_ranges::<1>(100);
+        check_ranges::<2>(100);
+        check_ranges::<5>(100);
+        check_ranges::<31>(100);
+    }
+
+    #[test]
+    fn sorted_iter_matches_btree_map() {
+        let value = sorted_pairs::<5>(1000);
+        let map = value.0.iter().cloned().collect::<BTreeMap<_, _>>();
+
+        let mut expected = Vec::new();
+        to_bytes(&map, |bytes| expected.extend_from_slice(bytes));
+        to_bytes(&value, |bytes| assert_eq!(bytes, expected.as_slice()));
+    }
+
+    #[test]
+    fn unsorted_iter() {
+        let unsorted = SortedPairs::<5>(vec![
+            (0, "a".to_string()),
+            (2, "b".to_string()),
+            (1, "c".to_string()),
+        ]);
+        crate::to_bytes::<Failure>(&unsorted).unwrap_err();
+
+        let duplicate =
+            SortedPairs::<5>(vec![(0, "a".to_string()), (0, "b".to_string())]);
+        crate::to_bytes::<Failure>(&duplicate).unwrap_err();
+    }
+
     #[test]
     fn roundtrip_btree_map() {
         let mut value = BTreeMap::new();
@@ -122,6 +269,79 @@ mod tests {
         roundtrip(&BTreeMap::<String, i32>::new());
     }
 
+    #[test]
+    fn get_with_unarchived_keys() {
+        let mut strings = BTreeMap::new();
+        strings.insert("foo".to_string(), 1u8);
+        strings.insert("bar".to_string(), 2);
+        to_archived(&strings, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert_eq!(archived.get(&"bar".to_string()), Some(&2));
+            assert!(archived.get("baz").is_none());
+            let range = archived.range::<str, _>((
+                Bound::Included("bar"),
+                Bound::Excluded("foo"),
+            ));
+            assert_eq!(
+                range.map(|(k, _)| k.as_str()).collect::<Vec<_>>(),
+                ["bar"]
+            );
+        });
+
+        let mut bytes = BTreeMap::new();
+        bytes.insert(vec![1u8, 2, 3], 1u8);
+        bytes.insert(vec![1u8, 3], 2);
+        to_archived(&bytes, |archived| {
+            assert_eq!(archived.get([1u8, 2, 3].as_slice()), Some(&1));
+            assert_eq!(archived.get(&vec![1u8, 3]), Some(&2));
+            assert!(archived.get([1u8, 2].as_slice()).is_none());
+        });
+
+        let mut numbers = BTreeMap::new();
+        for i in 0..100u32 {
+            numbers.insert(i * 5_000, i as u8);
+        }
+        to_archived(&numbers, |archived| {
+            assert_eq!(archived.get(&5_000u32), Some(&1));
+            assert_eq!(archived.get(&495_000u32), Some(&99));
+            assert!(!archived.contains_key(&6u32));
+            assert_eq!(archived.range(10_000u32..=20_000).count(), 3);
+        });
+    }
+
+    #[test]
+    fn get_with_borrowed_keys() {
+        let mut boxes = BTreeMap::new();
+        boxes.insert(Box::<str>::from("foo"), 1u8);
+        to_archived(&boxes, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert!(archived.get("bar").is_none());
+        });
+
+        let mut c_strings = BTreeMap::new();
+        c_strings.insert(CString::new("foo").unwrap(), 1u8);
+        to_archived(&c_strings, |archived| {
+            let foo = CStr::from_bytes_with_nul(b"foo\0").unwrap();
+            let bar = CStr::from_bytes_with_nul(b"bar\0").unwrap();
+            assert_eq!(archived.get(foo), Some(&1));
+            assert!(archived.get(bar).is_none());
+        });
+
+        let mut rcs = BTreeMap::new();
+        rcs.insert(Rc::<str>::from("foo"), 1u8);
+        to_archived(&rcs, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert!(archived.get("bar").is_none());
+        });
+
+        let mut arcs = BTreeMap::new();
+        arcs.insert(Arc::<str>::from("foo"), 1u8);
+        to_archived(&arcs, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert!(archived.get("bar").is_none());
+        });
+    }
+
     #[test]
     fn roundtrip_btree_map_zst() {
         let mut value = BTreeMap::new();
@@ -7,7 +7,9 @@ use std::collections::BTreeSet;
 use rancor::{Fallible, Source};
 
 use crate::{
-    collections::btree_set::{ArchivedBTreeSet, BTreeSetResolver},
+    collections::btree_set::{
+        is_sorted_subset, ArchivedBTreeSet, BTreeSetResolver,
+    },
     ser::{Allocator, Writer},
     Archive, Deserialize, Place, Serialize,
 };
@@ -88,10 +90,209 @@ impl<K, AK: PartialEq<K>> PartialEq<BTreeSet<K>> for ArchivedBTreeSet<AK> {
     }
 }
 
+impl<AK: Ord, const E: usize> ArchivedBTreeSet<AK, E> {
+    /// Returns `true` if every value in the set is also in the native set
+    /// `other`.
+    pub fn is_subset_native<K>(&self, other: &BTreeSet<K>) -> bool
+    where
+        AK: PartialEq<K>,
+    {
+        self.len() <= other.len()
+            && is_sorted_subset(self.iter(), other.iter(), |a, b| a == b)
+    }
+
+    /// Returns `true` if every value in the native set `other` is also in the
+    /// set.
+    pub fn is_superset_native<K>(&self, other: &BTreeSet<K>) -> bool
+    where
+        AK: PartialEq<K>,
+    {
+        other.len() <= self.len()
+            && is_sorted_subset(other.iter(), self.iter(), |b, a| a == b)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use core::ops::Bound;
+
     use super::BTreeSet;
-    use crate::test::roundtrip;
+    use crate::{
+        collections::btree_set::ArchivedBTreeSet,
+        test::{deserialize, roundtrip, to_archived},
+        Archived,
+    };
+
+    /// A small xorshift generator so that the randomized tests are
+    /// reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: u32) -> u32 {
+            (self.next() % bound as u64) as u32
+        }
+
+        fn bound(&mut self, value: u32) -> Bound<u32> {
+            match self.below(3) {
+                0 => Bound::Included(value),
+                1 => Bound::Excluded(value),
+                _ => Bound::Unbounded,
+            }
+        }
+    }
+
+    fn random_set(rng: &mut Rng, len: usize, max: u32) -> BTreeSet<u32> {
+        (0..len).map(|_| rng.below(max)).collect()
+    }
+
+    fn archived(bound: Bound<u32>) -> Bound<Archived<u32>> {
+        match bound {
+            Bound::Included(x) => {
+                Bound::Included(Archived::<u32>::from_native(x))
+            }
+            Bound::Excluded(x) => {
+                Bound::Excluded(Archived::<u32>::from_native(x))
+            }
+            Bound::Unbounded => Bound::Unbounded,
+        }
+    }
+
+    fn valid_range(start: Bound<u32>, end: Bound<u32>) -> bool {
+        match (start, end) {
+            (Bound::Excluded(s), Bound::Excluded(e)) => s < e,
+            (
+                Bound::Included(s) | Bound::Excluded(s),
+                Bound::Included(e) | Bound::Excluded(e),
+            ) => s <= e,
+            _ => true,
+        }
+    }
+
+    fn check_queries(
+        rng: &mut Rng,
+        set: &BTreeSet<u32>,
+        archived_set: &ArchivedBTreeSet<Archived<u32>>,
+        max: u32,
+    ) {
+        let values = archived_set
+            .iter()
+            .map(|x| x.to_native())
+            .collect::<Vec<_>>();
+        assert_eq!(values, set.iter().copied().collect::<Vec<_>>());
+        let reversed = archived_set
+            .iter()
+            .rev()
+            .map(|x| x.to_native())
+            .collect::<Vec<_>>();
+        assert_eq!(reversed, set.iter().rev().copied().collect::<Vec<_>>());
+        assert_eq!(archived_set.iter().len(), set.len());
+
+        assert_eq!(
+            archived_set.first().map(|x| x.to_native()),
+            set.first().copied()
+        );
+        assert_eq!(
+            archived_set.last().map(|x| x.to_native()),
+            set.last().copied()
+        );
+
+        for _ in 0..100 {
+            let x = rng.below(max);
+            assert_eq!(
+                archived_set
+                    .get_with(|k| k.to_native().cmp(&x))
+                    .map(|k| k.to_native()),
+                set.get(&x).copied(),
+            );
+            assert_eq!(
+                archived_set.contains_key(&Archived::<u32>::from_native(x)),
+                set.contains(&x),
+            );
+        }
+
+        for _ in 0..100 {
+            let a = rng.below(max);
+            let b = rng.below(max);
+            let start = rng.bound(a.min(b));
+            let end = rng.bound(a.max(b));
+            if !valid_range(start, end) {
+                continue;
+            }
+
+            let mut expected = set.range((start, end));
+            let mut actual =
+                archived_set.range((archived(start), archived(end)));
+            // Alternate between the ends of the range at random
+            loop {
+                let (e, a) = if rng.below(2) == 0 {
+                    (expected.next(), actual.next())
+                } else {
+                    (expected.next_back(), actual.next_back())
+                };
+                assert_eq!(a.map(|x| x.to_native()), e.copied());
+                if e.is_none() {
+                    break;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn randomized_btree_set_queries() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for len in [0, 1, 2, 5, 6, 7, 30, 36, 100, 1000] {
+            let max = (len as u32 * 2).max(1);
+            let set = random_set(&mut rng, len, max);
+            to_archived(&set, |archived_set| {
+                check_queries(&mut rng, &set, archived_set, max);
+                assert_eq!(deserialize::<BTreeSet<u32>>(archived_set), set);
+            });
+        }
+    }
+
+    #[test]
+    fn randomized_btree_set_algebra() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for len in [0, 1, 5, 6, 30, 100, 500] {
+            let max = (len as u32 * 2).max(1);
+            let a = random_set(&mut rng, len, max);
+            let b = match rng.below(3) {
+                // A subset of `a`
+                0 => a.iter().copied().filter(|_| rng.below(2) == 0).collect(),
+                // A superset of `a`
+                1 => {
+                    a.union(&random_set(&mut rng, len, max)).copied().collect()
+                }
+                // An unrelated set
+                _ => random_set(&mut rng, len, max),
+            };
+
+            let value = (a.clone(), b.clone());
+            to_archived(&value, |archived| {
+                let (archived_a, archived_b) = (&archived.0, &archived.1);
+                assert_eq!(archived_a.is_subset(archived_b), a.is_subset(&b));
+                assert_eq!(archived_b.is_subset(archived_a), b.is_subset(&a));
+                assert_eq!(
+                    archived_a.is_superset(archived_b),
+                    a.is_superset(&b)
+                );
+                assert_eq!(archived_a.is_subset_native(&b), a.is_subset(&b));
+                assert_eq!(
+                    archived_a.is_superset_native(&b),
+                    a.is_superset(&b)
+                );
+                assert!(archived_a.is_subset_native(&a));
+                assert!(archived_a.is_superset_native(&a));
+            });
+        }
+    }
 
     #[test]
     fn roundtrip_btree_set() {
@@ -298,6 +298,8 @@ where
 
 #[cfg(test)]
 mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::string::{String, ToString};
     use core::pin::Pin;
 
     use rancor::Panic;
@@ -540,4 +542,164 @@ mod tests {
         assert_eq!(Rc::weak_count(&deserialized.a), 1);
         assert_eq!(Weak::weak_count(&deserialized.b), 1);
     }
+
+    #[test]
+    fn pool_reset() {
+        #[cfg(not(feature = "std"))]
+        use alloc::{vec, vec::Vec};
+
+        use crate::util::AlignedVec;
+
+        let shared = Rc::new(10u32);
+        let value = vec![shared.clone(), shared];
+
+        let buf = to_bytes::<Panic>(&value).unwrap();
+        let mut copy = AlignedVec::<16>::new();
+        copy.extend_from_slice(&buf);
+
+        let mut pool = Pool::for_archive(&buf);
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Rc<u32>>>>(&buf) };
+        let first =
+            deserialize::<Vec<Rc<u32>>, _, Panic>(archived, &mut pool).unwrap();
+        assert!(Rc::ptr_eq(&first[0], &first[1]));
+        assert_eq!(Rc::strong_count(&first[0]), 3);
+
+        pool.reset(&copy);
+        assert_eq!(Rc::strong_count(&first[0]), 2);
+
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Rc<u32>>>>(&copy) };
+        let second =
+            deserialize::<Vec<Rc<u32>>, _, Panic>(archived, &mut pool).unwrap();
+        assert!(Rc::ptr_eq(&second[0], &second[1]));
+        assert!(!Rc::ptr_eq(&first[0], &second[0]));
+    }
+
+    #[test]
+    fn pool_many_shared() {
+        #[cfg(not(feature = "std"))]
+        use alloc::vec::Vec;
+
+        use crate::de::Unpool;
+
+        let shared = (0..10_000u32).map(Rc::new).collect::<Vec<_>>();
+        let value = shared
+            .iter()
+            .chain(shared.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let buf = to_bytes::<Panic>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<Archived<Vec<Rc<u32>>>>(buf.as_ref()) };
+
+        let deserialized =
+            deserialize::<Vec<Rc<u32>>, _, Panic>(archived, &mut Pool::new())
+                .unwrap();
+        assert_eq!(deserialized, value);
+        let (first, second) = deserialized.split_at(shared.len());
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert!(Rc::ptr_eq(a, b));
+            assert_eq!(Rc::strong_count(a), 2);
+        }
+
+        let deserialized =
+            deserialize::<Vec<Rc<u32>>, _, Panic>(archived, &mut Unpool)
+                .unwrap();
+        assert_eq!(deserialized, value);
+        let (first, second) = deserialized.split_at(shared.len());
+        for (a, b) in first.iter().zip(second.iter()) {
+            assert!(!Rc::ptr_eq(a, b));
+            assert_eq!(Rc::strong_count(a), 1);
+        }
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate)]
+    struct Config {
+        name: String,
+        limit: u32,
+    }
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(crate)]
+    struct SharedConfig {
+        config: super::sync::Arc<Config>,
+    }
+
+    #[test]
+    fn serialize_deny_shared() {
+        use rancor::Error;
+
+        use crate::{
+            ser::{allocator::Arena, sharing::DenyShared, SerializerBuilder},
+            util::{serialize_into, AlignedVec},
+        };
+
+        let config = Config {
+            name: "audited".to_string(),
+            limit: 10,
+        };
+        let mut arena = Arena::new();
+
+        let serializer = SerializerBuilder::new()
+            .writer(AlignedVec::<16>::new())
+            .allocator(arena.acquire())
+            .sharing(DenyShared)
+            .build::<Error>();
+        let bytes = serialize_into::<_, Error>(&config, serializer)
+            .unwrap()
+            .into_writer();
+        assert_eq!(
+            bytes.as_slice(),
+            to_bytes::<Error>(&config).unwrap().as_slice(),
+        );
+
+        let value = SharedConfig {
+            config: super::sync::Arc::new(config),
+        };
+        let serializer = SerializerBuilder::new()
+            .writer(AlignedVec::<16>::new())
+            .allocator(arena.acquire())
+            .sharing(DenyShared)
+            .build::<Error>();
+        let error = serialize_into::<_, Error>(&value, serializer)
+            .err()
+            .unwrap();
+        let message = error.to_string();
+        assert!(message.contains("sharing is denied"));
+        assert!(message.contains(core::any::type_name::<Config>()));
+    }
+
+    #[test]
+    fn deserialize_deny_pool() {
+        use rancor::Error;
+
+        use crate::de::DenyPool;
+
+        let config = Config {
+            name: "audited".to_string(),
+            limit: 10,
+        };
+        let bytes = to_bytes::<Error>(&config).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedConfig>(&bytes) };
+        let deserialized =
+            deserialize::<Config, _, Error>(archived, &mut DenyPool).unwrap();
+        assert_eq!(deserialized, config);
+
+        let value = SharedConfig {
+            config: super::sync::Arc::new(config),
+        };
+        let bytes = to_bytes::<Error>(&value).unwrap();
+        let archived =
+            unsafe { access_unchecked::<ArchivedSharedConfig>(&bytes) };
+        let error =
+            deserialize::<SharedConfig, _, Error>(archived, &mut DenyPool)
+                .err()
+                .unwrap();
+        let message = error.to_string();
+        assert!(message.contains("pooling is denied"));
+        assert!(message.contains(core::any::type_name::<Config>()));
+    }
 }
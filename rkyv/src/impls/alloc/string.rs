@@ -1,13 +1,20 @@
 #[cfg(not(feature = "std"))]
-use alloc::string::{String, ToString};
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+};
 use core::cmp::Ordering;
+#[cfg(feature = "std")]
+use std::borrow::Cow;
 
 use rancor::Fallible;
 
 use crate::{
+    collections::btree_map::LookupOrd,
+    hash::LookupKey,
     string::{ArchivedString, StringResolver},
-    Archive, Deserialize, DeserializeUnsized, Place, Serialize,
-    SerializeUnsized,
+    Archive, Deserialize, DeserializeInto, DeserializeUnsized, Place,
+    Serialize, SerializeUnsized,
 };
 
 impl Archive for String {
@@ -41,6 +48,23 @@ where
     }
 }
 
+impl<D: Fallible + ?Sized> DeserializeInto<String, D> for ArchivedString
+where
+    str: DeserializeUnsized<str, D>,
+{
+    /// The previous contents of `out` are cleared. `out` only reallocates if
+    /// its capacity is smaller than the length of the archived string.
+    fn deserialize_into(
+        &self,
+        out: &mut String,
+        _: &mut D,
+    ) -> Result<(), D::Error> {
+        out.clear();
+        out.push_str(self.as_str());
+        Ok(())
+    }
+}
+
 impl PartialEq<String> for ArchivedString {
     #[inline]
     fn eq(&self, other: &String) -> bool {
@@ -55,6 +79,20 @@ impl PartialEq<ArchivedString> for String {
     }
 }
 
+impl PartialEq<Cow<'_, str>> for ArchivedString {
+    #[inline]
+    fn eq(&self, other: &Cow<'_, str>) -> bool {
+        PartialEq::eq(self.as_str(), other.as_ref())
+    }
+}
+
+impl PartialEq<ArchivedString> for Cow<'_, str> {
+    #[inline]
+    fn eq(&self, other: &ArchivedString) -> bool {
+        PartialEq::eq(other.as_str(), self.as_ref())
+    }
+}
+
 impl PartialOrd<String> for ArchivedString {
     #[inline]
     fn partial_cmp(&self, other: &String) -> Option<Ordering> {
@@ -69,11 +107,34 @@ impl PartialOrd<ArchivedString> for String {
     }
 }
 
+impl LookupKey<ArchivedString> for String {
+    #[inline]
+    fn equivalent(&self, key: &ArchivedString) -> bool {
+        self.as_str() == key.as_str()
+    }
+}
+
+impl LookupOrd<ArchivedString> for String {
+    #[inline]
+    fn compare(&self, key: &ArchivedString) -> Ordering {
+        self.as_str().compare(key)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use rancor::Failure;
+    use core::borrow::Borrow;
+
+    use rancor::{Failure, Panic, Strategy};
 
-    use crate::{from_bytes, test::roundtrip, util::Align};
+    use crate::{
+        from_bytes,
+        hash::{hash_value, LookupKey},
+        string::ArchivedString,
+        test::{roundtrip, to_archived},
+        util::Align,
+        DeserializeInto,
+    };
 
     #[test]
     fn roundtrip_string() {
@@ -81,6 +142,31 @@ mod tests {
         roundtrip(&"hello world".to_string());
     }
 
+    #[test]
+    fn deserialize_into_reuses_capacity() {
+        let mut out = String::with_capacity(64);
+        out.push_str("previous contents which are longer than the next ones");
+        let ptr = out.as_ptr();
+
+        to_archived(&"short".to_string(), |archived| {
+            archived
+                .deserialize_into(&mut out, Strategy::<_, Panic>::wrap(&mut ()))
+                .unwrap();
+        });
+        assert_eq!(out, "short");
+        assert_eq!(out.as_ptr(), ptr);
+
+        let longer =
+            "a string which is longer than the previous one".to_string();
+        to_archived(&longer, |archived| {
+            archived
+                .deserialize_into(&mut out, Strategy::<_, Panic>::wrap(&mut ()))
+                .unwrap();
+        });
+        assert_eq!(out, longer);
+        assert_eq!(out.as_ptr(), ptr);
+    }
+
     #[test]
     fn roundtrip_option_string() {
         roundtrip(&Some("".to_string()));
@@ -101,4 +187,21 @@ mod tests {
         let data = Align([0x10; 16]);
         from_bytes::<String, Failure>(&*data).unwrap_err();
     }
+
+    #[test]
+    fn lookup_archived_string() {
+        // Long enough to be stored out-of-line
+        let value = "a string which is not stored inline".to_string();
+        to_archived(&value, |archived| {
+            let borrowed: &str = archived.borrow();
+            assert_eq!(borrowed, value.as_str());
+            assert_eq!(
+                hash_value::<str>(value.as_str()),
+                hash_value::<ArchivedString>(archived),
+            );
+            assert!(value.as_str().equivalent(archived));
+            assert!(value.equivalent(archived));
+            assert!(!"something else".equivalent(archived));
+        });
+    }
 }
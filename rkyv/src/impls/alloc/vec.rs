@@ -7,9 +7,12 @@ use ::std::alloc;
 use rancor::{Fallible, ResultExt as _, Source};
 
 use crate::{
+    collections::btree_map::LookupOrd,
+    hash::LookupKey,
     ser::{Allocator, Writer},
     vec::{ArchivedVec, VecResolver},
-    Archive, Deserialize, DeserializeUnsized, LayoutRaw, Place, Serialize,
+    Archive, Deserialize, DeserializeInto, DeserializeUnsized, LayoutRaw,
+    Place, Serialize,
 };
 
 impl<T: Archive> Archive for Vec<T> {
@@ -21,8 +24,11 @@ impl<T: Archive> Archive for Vec<T> {
     }
 }
 
-impl<T: Serialize<S>, S: Fallible + Allocator + Writer + ?Sized> Serialize<S>
-    for Vec<T>
+impl<T, S> Serialize<S> for Vec<T>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize(
         &self,
@@ -58,12 +64,49 @@ where
     }
 }
 
+impl<T, D> DeserializeInto<Vec<T>, D> for ArchivedVec<T::Archived>
+where
+    T: Archive,
+    T::Archived: DeserializeInto<T, D>,
+    [T::Archived]: DeserializeUnsized<[T], D>,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    /// The elements of `out` which have a corresponding archived element are
+    /// deserialized in place, and the rest are dropped. `out` only
+    /// reallocates if its capacity is smaller than the length of the archived
+    /// vec.
+    fn deserialize_into(
+        &self,
+        out: &mut Vec<T>,
+        deserializer: &mut D,
+    ) -> Result<(), D::Error> {
+        out.truncate(self.len());
+        for (value, out) in self.iter().zip(out.iter_mut()) {
+            value.deserialize_into(out, deserializer)?;
+        }
+
+        let reused = out.len();
+        out.reserve(self.len() - reused);
+        for value in self[reused..].iter() {
+            out.push(value.deserialize(deserializer)?);
+        }
+        Ok(())
+    }
+}
+
 impl<T: PartialEq<U>, U> PartialEq<Vec<U>> for ArchivedVec<T> {
     fn eq(&self, other: &Vec<U>) -> bool {
         self.as_slice().eq(other.as_slice())
     }
 }
 
+impl<T: PartialEq<U>, U> PartialEq<ArchivedVec<U>> for Vec<T> {
+    fn eq(&self, other: &ArchivedVec<U>) -> bool {
+        self.as_slice().eq(other.as_slice())
+    }
+}
+
 impl<T: PartialOrd<U>, U> PartialOrd<Vec<U>> for ArchivedVec<T> {
     fn partial_cmp(&self, other: &Vec<U>) -> Option<cmp::Ordering> {
         let min_len = self.len().min(other.len());
@@ -77,9 +120,234 @@ impl<T: PartialOrd<U>, U> PartialOrd<Vec<U>> for ArchivedVec<T> {
     }
 }
 
+impl<T: LookupKey<U>, U> LookupKey<ArchivedVec<U>> for Vec<T> {
+    #[inline]
+    fn equivalent(&self, key: &ArchivedVec<U>) -> bool {
+        self.as_slice().equivalent(key)
+    }
+}
+
+impl<T: LookupOrd<U>, U> LookupOrd<ArchivedVec<U>> for Vec<T> {
+    #[inline]
+    fn compare(&self, key: &ArchivedVec<U>) -> cmp::Ordering {
+        self.as_slice().compare(key)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::test::roundtrip;
+    #[cfg(not(feature = "std"))]
+    use alloc::{format, string::String, vec::Vec};
+
+    use rancor::{Failure, Fallible, Panic, Strategy};
+
+    use crate::{
+        ser::{Allocator, Writer},
+        string::StrAdapter,
+        test::{roundtrip, to_archived},
+        vec::{ArchivedVec, VecResolver},
+        Archive, Archived, DeserializeInto, Place, Serialize, SerializeUnsized,
+    };
+
+    struct Lines<'a>(&'a str);
+
+    impl Archive for Lines<'_> {
+        type Archived = Archived<Vec<String>>;
+        type Resolver = VecResolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            ArchivedVec::resolve_from_len(
+                self.0.lines().count(),
+                resolver,
+                out,
+            );
+        }
+    }
+
+    impl<S> Serialize<S> for Lines<'_>
+    where
+        S: Fallible + Allocator + Writer + ?Sized,
+        str: SerializeUnsized<S>,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedVec::serialize_from_iter::<StrAdapter, _, _>(
+                self.0.lines().map(StrAdapter),
+                serializer,
+            )
+        }
+    }
+
+    #[test]
+    fn serialize_from_unsized_iter() {
+        const LINES: usize = 1_000_000;
+
+        let text = (0..LINES)
+            .map(|i| format!("this is line number {}\n", i))
+            .collect::<String>();
+        to_archived(&Lines(&text), |archived| {
+            assert_eq!(archived.len(), LINES);
+            for (i, line) in archived.iter().enumerate() {
+                assert_eq!(line.as_str(), format!("this is line number {}", i));
+            }
+        });
+
+        to_archived(&Lines(""), |archived| assert!(archived.is_empty()));
+    }
+
+    #[test]
+    fn deserialize_into_reuses_capacity() {
+        let mut out = Vec::<String>::with_capacity(16);
+        out.extend((0..10).map(|i| format!("previous {i}")));
+        let ptr = out.as_ptr();
+
+        to_archived(&vec!["a".to_string(), "b".to_string()], |archived| {
+            archived
+                .deserialize_into(&mut out, Strategy::<_, Panic>::wrap(&mut ()))
+                .unwrap();
+        });
+        assert_eq!(out, ["a", "b"]);
+        assert_eq!(out.as_ptr(), ptr);
+
+        let longer = (0..16).map(|i| format!("next {i}")).collect::<Vec<_>>();
+        to_archived(&longer, |archived| {
+            archived
+                .deserialize_into(&mut out, Strategy::<_, Panic>::wrap(&mut ()))
+                .unwrap();
+        });
+        assert_eq!(out, longer);
+        assert_eq!(out.as_ptr(), ptr);
+
+        let value = (0..20).collect::<Vec<u32>>();
+        let mut out = Vec::new();
+        to_archived(&value, |archived| {
+            archived
+                .deserialize_into(&mut out, Strategy::<_, Panic>::wrap(&mut ()))
+                .unwrap();
+        });
+        assert_eq!(out, value);
+    }
+
+    #[test]
+    fn deserialize_into_reuses_elements() {
+        let mut out = (0..4)
+            .map(|i| format!("a long previous string {i}"))
+            .collect::<Vec<String>>();
+        let ptrs = out.iter().map(|s| s.as_ptr()).collect::<Vec<_>>();
+
+        let value = (0..6).map(|i| format!("next {i}")).collect::<Vec<_>>();
+        to_archived(&value, |archived| {
+            archived
+                .deserialize_into(&mut out, Strategy::<_, Panic>::wrap(&mut ()))
+                .unwrap();
+        });
+        assert_eq!(out, value);
+        // The strings which were already in `out` keep their allocations
+        for (s, ptr) in out.iter().zip(ptrs.iter()) {
+            assert_eq!(s.as_ptr(), *ptr);
+        }
+    }
+
+    #[test]
+    fn serialize_oversized_slice() {
+        struct Zst;
+
+        impl Archive for Zst {
+            type Archived = Archived<u32>;
+            type Resolver = ();
+
+            fn resolve(&self, _: Self::Resolver, _: Place<Self::Archived>) {}
+        }
+
+        impl<S: Fallible + ?Sized> Serialize<S> for Zst {
+            fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+                Ok(())
+            }
+        }
+
+        // SAFETY: Any number of zero-sized values can be read from a dangling
+        // pointer.
+        let slice: &'static [Zst] = unsafe {
+            core::slice::from_raw_parts(
+                core::ptr::NonNull::dangling().as_ptr(),
+                usize::MAX,
+            )
+        };
+        assert!(crate::to_bytes::<Failure>(&slice).is_err());
+    }
+
+    #[test]
+    fn bulk_update() {
+        #[derive(Archive, Serialize)]
+        #[rkyv(crate, check_bytes)]
+        struct Record {
+            id: u32,
+            secret: u64,
+        }
+
+        const LEN: u32 = 1_000_000;
+
+        let value = (0..LEN)
+            .map(|id| Record {
+                id,
+                secret: u64::from(id) * 3 + 1,
+            })
+            .collect::<Vec<_>>();
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+
+        let mut archived =
+            crate::access_mut::<Archived<Vec<Record>>, Failure>(&mut bytes)
+                .unwrap();
+        archived.as_mut().for_each_mut(|mut record| {
+            record.secret = Archived::<u64>::from_native(0);
+        });
+
+        let archived =
+            crate::access::<Archived<Vec<Record>>, Failure>(&bytes).unwrap();
+        assert_eq!(archived.len(), LEN as usize);
+        for (id, record) in (0..LEN).zip(archived.iter()) {
+            assert_eq!(record.id.to_native(), id);
+            assert_eq!(record.secret.to_native(), 0);
+        }
+    }
+
+    #[test]
+    fn fill() {
+        let mut bytes = crate::to_bytes::<Failure>(&vec![1u32; 100]).unwrap();
+        let mut archived =
+            crate::access_mut::<Archived<Vec<u32>>, Failure>(&mut bytes)
+                .unwrap();
+        archived.as_mut().fill(Archived::<u32>::from_native(7));
+
+        let archived =
+            crate::access::<Archived<Vec<u32>>, Failure>(&bytes).unwrap();
+        assert!(archived.iter().all(|x| x.to_native() == 7));
+    }
+
+    #[test]
+    fn for_each_mut_pinned() {
+        let value = vec!["hello".to_string(), "a much longer string".into()];
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let mut archived =
+            crate::access_mut::<Archived<Vec<String>>, Failure>(&mut bytes)
+                .unwrap();
+        // Archived strings contain relative pointers, so they can only be
+        // changed through their pinned methods.
+        archived
+            .as_mut()
+            .for_each_mut(|s| s.pin_mut_str().make_ascii_uppercase());
+
+        let archived =
+            crate::access::<Archived<Vec<String>>, Failure>(&bytes).unwrap();
+        assert_eq!(archived[0], "HELLO");
+        assert_eq!(archived[1], "A MUCH LONGER STRING");
+    }
 
     #[test]
     fn roundtrip_vec() {
@@ -4,31 +4,55 @@ use alloc::{
     boxed::Box,
     collections::{BTreeMap, BTreeSet},
     rc::Rc,
+    string::String,
     sync::Arc,
     vec::Vec,
 };
-use core::marker::PhantomData;
+use core::{fmt, hash::Hash, marker::PhantomData};
 #[cfg(feature = "std")]
 use std::{
     borrow::Cow,
-    collections::{BTreeMap, BTreeSet},
+    collections::{hash_set, BTreeMap, BTreeSet},
     rc::Rc,
     sync::Arc,
 };
 
+#[cfg(not(feature = "std"))]
+use hashbrown::hash_set;
 use ptr_meta::Pointee;
-use rancor::{Fallible, Source};
+use rancor::{fail, Fallible, Source};
 
 use crate::{
     boxed::{ArchivedBox, BoxResolver},
-    collections::util::{Entry, EntryAdapter},
+    collections::{
+        btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+        swiss_table::{ArchivedHashMap, HashMapResolver, DEFAULT_LOAD_FACTOR},
+        util::{Entry, EntryAdapter},
+    },
+    cow::{ArchivedCow, CowPointee, CowResolver},
+    extent::{
+        ArchivedExtent, OwnedArchivedField, OwnedArchivedFieldResolver,
+        Relocation,
+    },
+    impls::core::serialize_slice_aligned,
+    inline::{ArchivedInlineString, CapacityExceeded},
+    nested::{ArchivedBytesOf, BytesOfResolver},
     niche::option_box::{ArchivedOptionBox, OptionBoxResolver},
-    ser::{Allocator, Writer},
+    nullable::{
+        ArchivedNullableColumn, NullableColumnResolver, NullableLayout, Packed,
+        Slotted,
+    },
+    ser::{Allocator, Sharing, Writer, WriterExt as _},
+    soa::{ArchiveSoa, DeserializeSoa, SerializeSoa},
     string::{ArchivedString, StringResolver},
+    util::AlignedVec,
     vec::{ArchivedVec, VecResolver},
     with::{
-        ArchiveWith, AsBox, AsOwned, AsVec, DeserializeWith, InlineAsBox, Map,
-        Niche, SerializeWith, Unshare,
+        AlignElements, ArchiveWith, AsBTreeMap, AsBox, AsHashMap,
+        AsInlineString, AsNullableColumn, AsOwned, AsVec, BytesOf,
+        CowThreshold, DeserializeWith, ForceOutOfLine, InlineAsBox,
+        IterateList, KeepArchived, Leak, ListNode, Map, Niche, SerializeWith,
+        Unshare,
     },
     Archive, ArchiveUnsized, ArchivedMetadata, Deserialize, DeserializeUnsized,
     LayoutRaw, Place, Serialize, SerializeUnsized,
@@ -242,6 +266,7 @@ impl<'a, T, S> SerializeWith<Cow<'a, [T]>, S> for AsOwned
 where
     T: Serialize<S> + Clone,
     S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize_with(
         field: &Cow<'a, [T]>,
@@ -411,6 +436,219 @@ where
     }
 }
 
+// AsHashMap
+
+impl<K, V> ArchiveWith<BTreeMap<K, V>> for AsHashMap
+where
+    K: Archive + Hash + Eq,
+    V: Archive,
+{
+    type Archived = ArchivedHashMap<K::Archived, V::Archived>;
+    type Resolver = HashMapResolver;
+
+    fn resolve_with(
+        field: &BTreeMap<K, V>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedHashMap::resolve_from_btree(field, resolver, out);
+    }
+}
+
+impl<K, V, S> SerializeWith<BTreeMap<K, V>, S> for AsHashMap
+where
+    K: Serialize<S> + Hash + Eq,
+    V: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &BTreeMap<K, V>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_btree(
+            field, serializer,
+        )
+    }
+}
+
+impl<K, V, D>
+    DeserializeWith<
+        ArchivedHashMap<K::Archived, V::Archived>,
+        BTreeMap<K, V>,
+        D,
+    > for AsHashMap
+where
+    K: Archive + Ord,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedHashMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<BTreeMap<K, V>, D::Error> {
+        let mut result = BTreeMap::new();
+        for (key, value) in field.iter() {
+            result.insert(
+                key.deserialize(deserializer)?,
+                value.deserialize(deserializer)?,
+            );
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug)]
+struct DuplicateKey {
+    index: usize,
+}
+
+impl fmt::Display for DuplicateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the key at index {} of the vec is a duplicate of an earlier key",
+            self.index,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DuplicateKey {}
+
+impl<K, V> ArchiveWith<Vec<(K, V)>> for AsHashMap
+where
+    K: Archive + Hash + Eq,
+    V: Archive,
+{
+    type Archived = ArchivedHashMap<K::Archived, V::Archived>;
+    type Resolver = HashMapResolver;
+
+    fn resolve_with(
+        field: &Vec<(K, V)>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedHashMap::resolve_from_len(
+            field.len(),
+            DEFAULT_LOAD_FACTOR,
+            resolver,
+            out,
+        );
+    }
+}
+
+impl<K, V, S> SerializeWith<Vec<(K, V)>, S> for AsHashMap
+where
+    K: Serialize<S> + Hash + Eq,
+    V: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &Vec<(K, V)>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        let mut keys = hash_set::HashSet::with_capacity(field.len());
+        for (index, (key, _)) in field.iter().enumerate() {
+            if !keys.insert(key) {
+                fail!(DuplicateKey { index });
+            }
+        }
+        drop(keys);
+
+        ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_iter(
+            field.iter().map(|(key, value)| (key, value)),
+            DEFAULT_LOAD_FACTOR,
+            serializer,
+        )
+    }
+}
+
+impl<K, V, D>
+    DeserializeWith<ArchivedHashMap<K::Archived, V::Archived>, Vec<(K, V)>, D>
+    for AsHashMap
+where
+    K: Archive,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedHashMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<Vec<(K, V)>, D::Error> {
+        let mut result = Vec::with_capacity(field.len());
+        for (key, value) in field.iter() {
+            result.push((
+                key.deserialize(deserializer)?,
+                value.deserialize(deserializer)?,
+            ));
+        }
+        Ok(result)
+    }
+}
+
+// AsBTreeMap
+
+impl<K, V> ArchiveWith<BTreeMap<K, V>> for AsBTreeMap
+where
+    K: Archive + Ord,
+    K::Archived: Ord,
+    V: Archive,
+{
+    type Archived = ArchivedBTreeMap<K::Archived, V::Archived>;
+    type Resolver = BTreeMapResolver;
+
+    fn resolve_with(
+        field: &BTreeMap<K, V>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        field.resolve(resolver, out);
+    }
+}
+
+impl<K, V, S> SerializeWith<BTreeMap<K, V>, S> for AsBTreeMap
+where
+    K: Serialize<S> + Ord,
+    K::Archived: Ord,
+    V: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &BTreeMap<K, V>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        field.serialize(serializer)
+    }
+}
+
+impl<K, V, D>
+    DeserializeWith<
+        ArchivedBTreeMap<K::Archived, V::Archived>,
+        BTreeMap<K, V>,
+        D,
+    > for AsBTreeMap
+where
+    K: Archive + Ord,
+    K::Archived: Deserialize<K, D> + Ord,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedBTreeMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<BTreeMap<K, V>, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
 // Niche
 
 impl<T> ArchiveWith<Option<Box<T>>> for Niche
@@ -536,42 +774,1036 @@ where
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        test::roundtrip, with::Niche, Archive, Deserialize, Serialize,
+// CowThreshold
+
+macro_rules! impl_cow_threshold {
+    ($pointer:ident) => {
+        impl<T, const N: usize> ArchiveWith<$pointer<T>> for CowThreshold<N>
+        where
+            T: CowPointee + ?Sized,
+        {
+            type Archived = ArchivedCow<T>;
+            type Resolver = CowResolver;
+
+            fn resolve_with(
+                field: &$pointer<T>,
+                resolver: Self::Resolver,
+                out: Place<Self::Archived>,
+            ) {
+                ArchivedCow::resolve_from_ref(field.as_ref(), resolver, out);
+            }
+        }
+
+        impl<T, S, const N: usize> SerializeWith<$pointer<T>, S>
+            for CowThreshold<N>
+        where
+            T: CowPointee + ?Sized,
+            S: Fallible + Writer + Sharing + ?Sized,
+            S::Error: Source,
+        {
+            fn serialize_with(
+                field: &$pointer<T>,
+                serializer: &mut S,
+            ) -> Result<Self::Resolver, S::Error> {
+                ArchivedCow::serialize_from_ref(field.as_ref(), N, serializer)
+            }
+        }
+
+        impl<T, D, const N: usize>
+            DeserializeWith<ArchivedCow<T>, $pointer<T>, D> for CowThreshold<N>
+        where
+            T: CowPointee + ?Sized,
+            for<'a> $pointer<T>: From<&'a T>,
+            D: Fallible + ?Sized,
+        {
+            fn deserialize_with(
+                field: &ArchivedCow<T>,
+                _: &mut D,
+            ) -> Result<$pointer<T>, D::Error> {
+                Ok($pointer::from(field.get()))
+            }
+        }
     };
+}
 
-    #[derive(Debug, Archive, Deserialize, Serialize, PartialEq)]
-    #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
-    struct Test {
-        value: Option<Box<u128>>,
+impl_cow_threshold!(Rc);
+impl_cow_threshold!(Arc);
+
+// ForceOutOfLine
+
+impl ArchiveWith<String> for ForceOutOfLine {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    fn resolve_with(
+        field: &String,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedString::resolve_from_str_out_of_line(field, resolver, out);
     }
+}
 
-    #[test]
-    fn roundtrip_niche_none() {
-        roundtrip(&Test { value: None });
+impl<S> SerializeWith<String, S> for ForceOutOfLine
+where
+    S: Fallible + Sharing + ?Sized,
+    str: SerializeUnsized<S>,
+{
+    fn serialize_with(
+        field: &String,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str_out_of_line(field, serializer)
     }
+}
 
-    #[test]
-    fn roundtrip_niche_some() {
-        roundtrip(&Test {
-            value: Some(Box::new(128)),
-        });
+impl<D> DeserializeWith<ArchivedString, String, D> for ForceOutOfLine
+where
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedString,
+        _: &mut D,
+    ) -> Result<String, D::Error> {
+        Ok(String::from(field.as_str()))
     }
+}
 
-    #[test]
-    fn ambiguous_niched_archived_box() {
-        #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
-        #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
-        struct HasNiche {
-            #[with(Niche)]
-            inner: Option<Box<[u32]>>,
-        }
+// AlignElements
 
-        roundtrip(&HasNiche {
-            inner: Some(Box::<[u32]>::from([])),
-        });
-        roundtrip(&HasNiche { inner: None });
+impl<T: Archive, const N: usize> ArchiveWith<Vec<T>> for AlignElements<N> {
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve_with(
+        field: &Vec<T>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedVec::resolve_from_len(field.len(), resolver, out);
+    }
+}
+
+impl<T, S, const N: usize> SerializeWith<Vec<T>, S> for AlignElements<N>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &Vec<T>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        assert!(N.is_power_of_two(), "alignment must be a power of two");
+        let pos = serialize_slice_aligned(field.as_slice(), N, serializer)?;
+        Ok(VecResolver::from_pos(pos))
+    }
+}
+
+impl<T, D, const N: usize> DeserializeWith<ArchivedVec<T::Archived>, Vec<T>, D>
+    for AlignElements<N>
+where
+    T: Archive,
+    ArchivedVec<T::Archived>: Deserialize<Vec<T>, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedVec<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<Vec<T>, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
+// AsNullableColumn
+
+impl<T: Archive, L: NullableLayout> ArchiveWith<Vec<Option<T>>>
+    for AsNullableColumn<L>
+{
+    type Archived = ArchivedNullableColumn<T::Archived, L>;
+    type Resolver = NullableColumnResolver;
+
+    fn resolve_with(
+        field: &Vec<Option<T>>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedNullableColumn::resolve_from_slice(field, resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<Vec<Option<T>>, S> for AsNullableColumn<Packed>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &Vec<Option<T>>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedNullableColumn::<T::Archived, Packed>::serialize_from_slice(
+            field, serializer,
+        )
+    }
+}
+
+impl<T, S> SerializeWith<Vec<Option<T>>, S> for AsNullableColumn<Slotted>
+where
+    T: Default + Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &Vec<Option<T>>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedNullableColumn::<T::Archived, Slotted>::serialize_from_slice(
+            field, serializer,
+        )
+    }
+}
+
+impl<T, L, D>
+    DeserializeWith<ArchivedNullableColumn<T::Archived, L>, Vec<Option<T>>, D>
+    for AsNullableColumn<L>
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    L: NullableLayout,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedNullableColumn<T::Archived, L>,
+        deserializer: &mut D,
+    ) -> Result<Vec<Option<T>>, D::Error> {
+        let mut result = Vec::with_capacity(field.len());
+        for value in field.iter() {
+            result.push(match value {
+                Some(value) => Some(value.deserialize(deserializer)?),
+                None => None,
+            });
+        }
+        Ok(result)
+    }
+}
+
+// AsInlineString
+
+impl<const N: usize> ArchiveWith<String> for AsInlineString<N> {
+    type Archived = ArchivedInlineString<N>;
+    type Resolver = ();
+
+    fn resolve_with(field: &String, _: (), out: Place<Self::Archived>) {
+        ArchivedInlineString::resolve_from_str(field, out);
+    }
+}
+
+impl<S, const N: usize> SerializeWith<String, S> for AsInlineString<N>
+where
+    S: Fallible + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(field: &String, _: &mut S) -> Result<(), S::Error> {
+        if field.len() > N {
+            fail!(CapacityExceeded {
+                len: field.len(),
+                capacity: N,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<D, const N: usize> DeserializeWith<ArchivedInlineString<N>, String, D>
+    for AsInlineString<N>
+where
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedInlineString<N>,
+        _: &mut D,
+    ) -> Result<String, D::Error> {
+        Ok(String::from(field.as_str()))
+    }
+}
+
+// KeepArchived
+
+impl<T> ArchiveWith<OwnedArchivedField<T>> for KeepArchived
+where
+    T: Archive,
+    T::Archived: ArchivedExtent,
+{
+    type Archived = T::Archived;
+    type Resolver = OwnedArchivedFieldResolver;
+
+    fn resolve_with(
+        field: &OwnedArchivedField<T>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        let relocation =
+            Relocation::new(field.as_bytes().as_ptr(), resolver.pos);
+        field.get().resolve_relocated(relocation, out);
+    }
+}
+
+impl<T, S> SerializeWith<OwnedArchivedField<T>, S> for KeepArchived
+where
+    T: Archive,
+    T::Archived: ArchivedExtent,
+    S: Fallible + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &OwnedArchivedField<T>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        serializer.align(AlignedVec::<16>::ALIGNMENT)?;
+        let pos = serializer.pos();
+        serializer.write(field.as_bytes())?;
+        Ok(OwnedArchivedFieldResolver { pos })
+    }
+}
+
+impl<T, D> DeserializeWith<T::Archived, OwnedArchivedField<T>, D>
+    for KeepArchived
+where
+    T: Archive,
+    T::Archived: ArchivedExtent,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &T::Archived,
+        _: &mut D,
+    ) -> Result<OwnedArchivedField<T>, D::Error> {
+        Ok(OwnedArchivedField::new(field))
+    }
+}
+
+// BytesOf
+
+impl<T: Archive, const A: usize> ArchiveWith<AlignedVec<A>> for BytesOf<T> {
+    type Archived = ArchivedBytesOf<T>;
+    type Resolver = BytesOfResolver;
+
+    fn resolve_with(
+        field: &AlignedVec<A>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedBytesOf::resolve_from_len(field.len(), resolver, out);
+    }
+}
+
+impl<T, S, const A: usize> SerializeWith<AlignedVec<A>, S> for BytesOf<T>
+where
+    T: Archive,
+    S: Fallible + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &AlignedVec<A>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedBytesOf::<T>::serialize_from_bytes(
+            field,
+            AlignedVec::<A>::ALIGNMENT,
+            serializer,
+        )
+    }
+}
+
+impl<T, D, const A: usize> DeserializeWith<ArchivedBytesOf<T>, AlignedVec<A>, D>
+    for BytesOf<T>
+where
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedBytesOf<T>,
+        _: &mut D,
+    ) -> Result<AlignedVec<A>, D::Error> {
+        let mut result = AlignedVec::with_capacity(field.as_bytes().len());
+        result.extend_from_slice(field.as_bytes());
+        Ok(result)
+    }
+}
+
+impl<T: Archive> ArchiveWith<Vec<u8>> for BytesOf<T> {
+    type Archived = ArchivedBytesOf<T>;
+    type Resolver = BytesOfResolver;
+
+    fn resolve_with(
+        field: &Vec<u8>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedBytesOf::resolve_from_len(field.len(), resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<Vec<u8>, S> for BytesOf<T>
+where
+    T: Archive,
+    S: Fallible + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &Vec<u8>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedBytesOf::<T>::serialize_from_bytes(
+            field,
+            AlignedVec::<16>::ALIGNMENT,
+            serializer,
+        )
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedBytesOf<T>, Vec<u8>, D> for BytesOf<T>
+where
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedBytesOf<T>,
+        _: &mut D,
+    ) -> Result<Vec<u8>, D::Error> {
+        Ok(field.as_bytes().to_vec())
+    }
+}
+
+// SoA
+
+impl<T: ArchiveSoa> ArchiveWith<Vec<T>> for SoA {
+    type Archived = T::ArchivedSoa;
+    type Resolver = T::SoaResolver;
+
+    fn resolve_with(
+        field: &Vec<T>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        T::resolve_soa(field, resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<Vec<T>, S> for SoA
+where
+    T: SerializeSoa<S>,
+    S: Fallible + ?Sized,
+{
+    fn serialize_with(
+        field: &Vec<T>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        T::serialize_soa(field, serializer)
+    }
+}
+
+impl<T, D> DeserializeWith<T::ArchivedSoa, Vec<T>, D> for SoA
+where
+    T: DeserializeSoa<D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &T::ArchivedSoa,
+        deserializer: &mut D,
+    ) -> Result<Vec<T>, D::Error> {
+        let len = T::soa_len(field);
+        let mut result = Vec::with_capacity(len);
+        for i in 0..len {
+            result.push(T::deserialize_soa(field, i, deserializer)?);
+        }
+        Ok(result)
+    }
+}
+
+// IterateList
+
+/// Iterates over the values of a linked list without recursing.
+struct ListValues<'a, N>(Option<&'a N>);
+
+impl<'a, N: ListNode> Iterator for ListValues<'a, N> {
+    type Item = &'a N::Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.0?;
+        self.0 = node.next();
+        Some(node.value())
+    }
+}
+
+impl<N> ArchiveWith<Option<Box<N>>> for IterateList
+where
+    N: ListNode,
+    N::Value: Archive,
+{
+    type Archived = ArchivedVec<<N::Value as Archive>::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve_with(
+        field: &Option<Box<N>>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        let len = ListValues(field.as_deref()).count();
+        ArchivedVec::resolve_from_len(len, resolver, out);
+    }
+}
+
+impl<N, S> SerializeWith<Option<Box<N>>, S> for IterateList
+where
+    N: ListNode,
+    N::Value: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    fn serialize_with(
+        field: &Option<Box<N>>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::<<N::Value as Archive>::Archived>::serialize_from_iter::<
+            N::Value,
+            _,
+            _,
+        >(ListValues(field.as_deref()), serializer)
+    }
+}
+
+impl<N, D>
+    DeserializeWith<
+        ArchivedVec<<N::Value as Archive>::Archived>,
+        Option<Box<N>>,
+        D,
+    > for IterateList
+where
+    N: ListNode,
+    N::Value: Archive,
+    <N::Value as Archive>::Archived: Deserialize<N::Value, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedVec<<N::Value as Archive>::Archived>,
+        deserializer: &mut D,
+    ) -> Result<Option<Box<N>>, D::Error> {
+        // Build the list back to front so that each node can own the next.
+        let mut head = None;
+        for value in field.iter().rev() {
+            let value = value.deserialize(deserializer)?;
+            head = Some(Box::new(N::from_parts(value, head)));
+        }
+        Ok(head)
+    }
+}
+
+// Leak
+
+impl ArchiveWith<&'static str> for Leak {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    fn resolve_with(
+        field: &&'static str,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        field.resolve(resolver, out);
+    }
+}
+
+impl<S> SerializeWith<&'static str, S> for Leak
+where
+    S: Fallible + ?Sized,
+    str: SerializeUnsized<S>,
+{
+    fn serialize_with(
+        field: &&'static str,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        field.serialize(serializer)
+    }
+}
+
+impl<D> DeserializeWith<ArchivedString, &'static str, D> for Leak
+where
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedString,
+        _: &mut D,
+    ) -> Result<&'static str, D::Error> {
+        Ok(Box::leak(Box::<str>::from(field.as_str())))
+    }
+}
+
+impl<T: Archive> ArchiveWith<&'static [T]> for Leak {
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    fn resolve_with(
+        field: &&'static [T],
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        field.resolve(resolver, out);
+    }
+}
+
+impl<T, S> SerializeWith<&'static [T], S> for Leak
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &&'static [T],
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        field.serialize(serializer)
+    }
+}
+
+impl<T, D> DeserializeWith<ArchivedVec<T::Archived>, &'static [T], D> for Leak
+where
+    T: Archive,
+    T::Archived: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedVec<T::Archived>,
+        deserializer: &mut D,
+    ) -> Result<&'static [T], D::Error> {
+        let mut result = Vec::with_capacity(field.len());
+        for value in field.iter() {
+            result.push(value.deserialize(deserializer)?);
+        }
+        Ok(Box::leak(result.into_boxed_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use rancor::Failure;
+
+    use crate::{
+        string::ArchivedString,
+        test::{
+            access, deserialize, roundtrip, roundtrip_with, to_archived,
+            to_bytes,
+        },
+        util::{to_bytes_in, AlignedVec},
+        with::{
+            AlignElements, AsHashMap, DefaultOnNone, Fallback, ForceOutOfLine,
+            Identity, Inline, IterateList, Leak, ListNode, Map, Niche,
+        },
+        Archive, Deserialize, Serialize,
+    };
+
+    #[derive(Debug, Archive, Deserialize, Serialize, PartialEq)]
+    #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+    struct Test {
+        value: Option<Box<u128>>,
+    }
+
+    #[test]
+    fn align_elements() {
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes)]
+        struct Samples {
+            name: String,
+            #[with(AlignElements<64>)]
+            values: Vec<f32>,
+            #[with(AlignElements<32>)]
+            labels: Vec<String>,
+        }
+
+        let value = Samples {
+            name: "sensor readings".to_string(),
+            values: (0..100).map(|i| i as f32).collect(),
+            labels: vec!["a really long label".to_string(); 3],
+        };
+        let bytes =
+            to_bytes_in::<_, Failure>(&value, AlignedVec::<64>::new()).unwrap();
+        let archived =
+            crate::access::<ArchivedSamples, Failure>(&bytes).unwrap();
+
+        assert_eq!(archived.values.as_ptr() as usize % 64, 0);
+        assert!(archived.values.is_aligned_to(64));
+        assert!(archived.labels.is_aligned_to(32));
+        assert_eq!(deserialize::<Samples>(archived), value);
+    }
+
+    #[test]
+    fn vec_as_hash_map() {
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, derive(Debug))]
+        struct Rows {
+            #[with(AsHashMap)]
+            rows: Vec<(String, u32)>,
+        }
+
+        let value = Rows {
+            rows: ["alice", "bob", "carol"]
+                .iter()
+                .zip(0..)
+                .map(|(name, id)| (name.to_string(), id))
+                .collect(),
+        };
+
+        to_archived(&value, |archived| {
+            assert_eq!(archived.rows.len(), 3);
+            assert_eq!(archived.rows.get("bob").unwrap().to_native(), 1);
+            assert!(archived.rows.get("dave").is_none());
+
+            let mut deserialized = deserialize::<Rows>(archived);
+            deserialized.rows.sort();
+            assert_eq!(deserialized, value);
+        });
+
+        let duplicate = Rows {
+            rows: vec![
+                ("alice".to_string(), 0),
+                ("bob".to_string(), 1),
+                ("alice".to_string(), 2),
+            ],
+        };
+        let error = crate::to_bytes::<rancor::Error>(&duplicate).unwrap_err();
+        assert!(error.to_string().contains("index 2"), "{}", error);
+    }
+
+    #[test]
+    fn roundtrip_niche_none() {
+        roundtrip(&Test { value: None });
+    }
+
+    #[test]
+    fn roundtrip_niche_some() {
+        roundtrip(&Test {
+            value: Some(Box::new(128)),
+        });
+    }
+
+    #[test]
+    fn ambiguous_niched_archived_box() {
+        #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+        struct HasNiche {
+            #[with(Niche)]
+            inner: Option<Box<[u32]>>,
+        }
+
+        roundtrip(&HasNiche {
+            inner: Some(Box::<[u32]>::from([])),
+        });
+        roundtrip(&HasNiche { inner: None });
+    }
+
+    #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct OldConfig {
+        name: String,
+        retries: Option<u32>,
+    }
+
+    #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, derive(Debug))]
+    struct NewConfig {
+        name: String,
+        #[with(DefaultOnNone)]
+        retries: u32,
+    }
+
+    #[test]
+    fn default_on_none_reads_old_archives() {
+        let old = OldConfig {
+            name: "old".to_string(),
+            retries: None,
+        };
+        to_bytes(&old, |bytes| {
+            access::<ArchivedNewConfig>(bytes, |archived| {
+                assert!(archived.retries.is_none());
+                let new = deserialize::<NewConfig>(archived);
+                assert_eq!(new.name, "old");
+                assert_eq!(new.retries, 0);
+            });
+        });
+
+        let old = OldConfig {
+            name: "old".to_string(),
+            retries: Some(4),
+        };
+        to_bytes(&old, |bytes| {
+            access::<ArchivedNewConfig>(bytes, |archived| {
+                assert_eq!(deserialize::<NewConfig>(archived).retries, 4);
+            });
+        });
+    }
+
+    #[test]
+    fn default_on_none_writes_old_archives() {
+        let new = NewConfig {
+            name: "new".to_string(),
+            retries: 7,
+        };
+        to_bytes(&new, |bytes| {
+            access::<ArchivedOldConfig>(bytes, |archived| {
+                let old = deserialize::<OldConfig>(archived);
+                assert_eq!(old.name, "new");
+                assert_eq!(old.retries, Some(7));
+            });
+        });
+        roundtrip_with(&new, |_, archived| {
+            assert_eq!(archived.retries, Some(7));
+        });
+    }
+
+    #[test]
+    fn default_on_none_fallback() {
+        struct Three;
+
+        impl Fallback<u32> for Three {
+            fn fallback() -> u32 {
+                3
+            }
+        }
+
+        #[derive(Archive, Deserialize, Serialize)]
+        #[rkyv(crate, check_bytes)]
+        struct WithFallback {
+            #[with(DefaultOnNone<Identity, Three>)]
+            retries: u32,
+        }
+
+        to_bytes(&Option::<u32>::None, |bytes| {
+            access::<ArchivedWithFallback>(bytes, |archived| {
+                assert_eq!(deserialize::<WithFallback>(archived).retries, 3);
+            });
+        });
+    }
+
+    #[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Named {
+        #[with(ForceOutOfLine)]
+        name: String,
+        plain: String,
+    }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Many<'a> {
+        #[with(Map<Inline>)]
+        values: Vec<&'a Named>,
+    }
+
+    fn count(haystack: &[u8], needle: &[u8]) -> usize {
+        haystack
+            .windows(needle.len())
+            .filter(|w| *w == needle)
+            .count()
+    }
+
+    #[test]
+    fn force_out_of_line() {
+        let value = Named {
+            name: "short".to_string(),
+            plain: "plain".to_string(),
+        };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedNamed>(bytes, |archived| {
+                assert!(!archived.name.is_inline());
+                assert!(archived.plain.is_inline());
+                assert_eq!(archived.name, "short");
+                assert_eq!(deserialize::<Named>(archived), value);
+            });
+        });
+
+        let empty = Named {
+            name: String::new(),
+            plain: String::new(),
+        };
+        to_bytes(&empty, |bytes| {
+            access::<ArchivedNamed>(bytes, |archived| {
+                assert!(archived.name.is_inline());
+                assert!(archived.name.is_empty());
+            });
+        });
+    }
+
+    #[test]
+    fn force_out_of_line_shares_bytes() {
+        let name = "a".repeat(ArchivedString::INLINE_CAPACITY);
+        let named = Named {
+            name: name.clone(),
+            plain: "plain".to_string(),
+        };
+        let value = Many {
+            values: vec![&named; 8],
+        };
+
+        to_bytes(&value, |bytes| {
+            // The forced string is written once and the plain string is
+            // inlined in every element.
+            assert_eq!(count(bytes, name.as_bytes()), 1);
+            assert_eq!(count(bytes, b"plain"), 8);
+
+            access::<ArchivedMany>(bytes, |archived| {
+                let first = archived.values[0].name.as_ptr();
+                for value in archived.values.iter() {
+                    assert!(!value.name.is_inline());
+                    assert_eq!(value.name, name.as_str());
+                    assert_eq!(value.name.as_ptr(), first);
+                }
+            });
+        });
+    }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct StaticRefs {
+        name: &'static str,
+        values: &'static [u32],
+    }
+
+    #[test]
+    fn archive_static_refs() {
+        let value = StaticRefs {
+            name: "a name which is too long to be inlined",
+            values: &[1, 2, 3],
+        };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedStaticRefs>(bytes, |archived| {
+                assert_eq!(archived.name, value.name);
+                assert_eq!(archived.values, value.values);
+            });
+        });
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Leaked {
+        #[with(Leak)]
+        name: &'static str,
+        #[with(Leak)]
+        values: &'static [u32],
+    }
+
+    #[test]
+    fn leak_static_refs() {
+        let value = Leaked {
+            name: "leaked",
+            values: &[4, 5, 6],
+        };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedLeaked>(bytes, |archived| {
+                let deserialized = deserialize::<Leaked>(archived);
+                assert_eq!(deserialized, value);
+                assert_ne!(deserialized.name.as_ptr(), value.name.as_ptr());
+                assert_ne!(deserialized.values.as_ptr(), value.values.as_ptr());
+            });
+        });
+    }
+
+    struct Node {
+        value: u32,
+        next: Option<Box<Node>>,
+    }
+
+    impl ListNode for Node {
+        type Value = u32;
+
+        fn value(&self) -> &u32 {
+            &self.value
+        }
+
+        fn next(&self) -> Option<&Self> {
+            self.next.as_deref()
+        }
+
+        fn from_parts(value: u32, next: Option<Box<Self>>) -> Self {
+            Node { value, next }
+        }
+    }
+
+    // Dropping a long list recursively would overflow the stack.
+    impl Drop for Node {
+        fn drop(&mut self) {
+            let mut next = self.next.take();
+            while let Some(mut node) = next {
+                next = node.next.take();
+            }
+        }
+    }
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(crate, check_bytes)]
+    struct List {
+        #[with(IterateList)]
+        head: Option<Box<Node>>,
+    }
+
+    impl List {
+        fn new(len: u32) -> Self {
+            let mut head = None;
+            for value in (0..len).rev() {
+                head = Some(Box::new(Node { value, next: head }));
+            }
+            Self { head }
+        }
+
+        fn values(&self) -> Vec<u32> {
+            let mut values = Vec::new();
+            let mut node = self.head.as_deref();
+            while let Some(current) = node {
+                values.push(current.value);
+                node = current.next();
+            }
+            values
+        }
+    }
+
+    #[test]
+    fn iterate_list() {
+        for len in [0, 1, 5] {
+            let value = List::new(len);
+            to_bytes(&value, |bytes| {
+                access::<ArchivedList>(bytes, |archived| {
+                    assert_eq!(archived.head.len(), len as usize);
+                    for (i, value) in archived.head.iter().enumerate() {
+                        assert_eq!(value.to_native(), i as u32);
+                    }
+                    let deserialized = deserialize::<List>(archived);
+                    assert_eq!(deserialized.values(), value.values());
+                });
+            });
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn iterate_long_list() {
+        const LEN: u32 = 100_000;
+
+        std::thread::Builder::new()
+            .stack_size(1 << 20)
+            .spawn(|| {
+                let value = List::new(LEN);
+                to_bytes(&value, |bytes| {
+                    access::<ArchivedList>(bytes, |archived| {
+                        assert_eq!(archived.head.len(), LEN as usize);
+                        let deserialized = deserialize::<List>(archived);
+                        assert_eq!(deserialized.values(), value.values());
+                    });
+                });
+            })
+            .unwrap()
+            .join()
+            .unwrap();
     }
 }
@@ -1,5 +1,5 @@
 use arrayvec::ArrayVec;
-use rancor::Fallible;
+use rancor::{Fallible, Source};
 
 use crate::{
     ser::{Allocator, Writer},
@@ -23,6 +23,7 @@ impl<T, S, const CAP: usize> Serialize<S> for ArrayVec<T, CAP>
 where
     T: Serialize<S>,
     S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize(
         &self,
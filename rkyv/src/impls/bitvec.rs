@@ -39,6 +39,7 @@ where
     T: BitStore + Archive + Serialize<S>,
     O: BitOrder,
     S: Fallible + ?Sized + Allocator + Writer,
+    S::Error: Source,
     Archived<T>: BitStore,
 {
     fn serialize(
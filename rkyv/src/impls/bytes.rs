@@ -1,5 +1,5 @@
 use bytes::{Bytes, BytesMut};
-use rancor::Fallible;
+use rancor::{Fallible, Source};
 
 use crate::{
     ser::{Allocator, Writer},
@@ -17,7 +17,11 @@ impl Archive for Bytes {
     }
 }
 
-impl<S: Fallible + Allocator + Writer + ?Sized> Serialize<S> for Bytes {
+impl<S> Serialize<S> for Bytes
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
     fn serialize(
         &self,
         serializer: &mut S,
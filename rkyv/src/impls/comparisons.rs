@@ -0,0 +1,150 @@
+//! Checks every direction of the comparisons between archived and native
+//! containers, so that missing impls are caught when they regress.
+
+use std::{borrow::Cow, collections::HashMap};
+
+use crate::{
+    option::ArchivedOption, primitive::ArchivedU32, string::ArchivedString,
+    test::to_archived, vec::ArchivedVec,
+};
+
+fn check_eq<A, B>(a: &A, b: &B)
+where
+    A: PartialEq<B> + ?Sized,
+    B: PartialEq<A> + ?Sized,
+{
+    assert!(a == b);
+    assert!(b == a);
+}
+
+fn check_ne<A, B>(a: &A, b: &B)
+where
+    A: PartialEq<B> + ?Sized,
+    B: PartialEq<A> + ?Sized,
+{
+    assert!(a != b);
+    assert!(b != a);
+}
+
+#[test]
+fn vec_comparisons() {
+    let value = vec![1u32, 2, 3];
+    let other = vec![1u32, 2, 4];
+
+    to_archived(&value, |archived: &ArchivedVec<ArchivedU32>| {
+        check_eq(archived, &value);
+        check_eq(archived, value.as_slice());
+        check_eq(archived, &[1u32, 2, 3]);
+        check_ne(archived, &other);
+        check_ne(archived, other.as_slice());
+        check_ne(archived, &[1u32, 2, 4]);
+        check_ne(archived, &vec![1u32, 2]);
+
+        to_archived(&value, |same| check_eq(archived, same));
+        to_archived(&other, |different| check_ne(archived, different));
+    });
+}
+
+#[test]
+fn vec_of_strings_comparisons() {
+    let value = vec!["a".to_string(), "b".to_string()];
+
+    to_archived(&value, |archived| {
+        check_eq(archived, &value);
+        check_eq(archived, value.as_slice());
+        check_ne(archived, &vec!["a".to_string()]);
+
+        to_archived(&value, |same| check_eq(archived, same));
+    });
+}
+
+#[test]
+fn option_comparisons() {
+    let some = Some(42u32);
+    let none = None::<u32>;
+
+    to_archived(&some, |archived: &ArchivedOption<ArchivedU32>| {
+        assert!(*archived == some);
+        assert!(*archived != none);
+        assert!(*archived != Some(43u32));
+        #[cfg(feature = "extra_traits")]
+        {
+            check_eq(archived, &some);
+            check_ne(archived, &none);
+        }
+
+        // Archived options with different element types
+        check_eq(archived, &ArchivedOption::Some(42u32));
+        check_ne(archived, &ArchivedOption::Some(43u32));
+        check_ne(archived, &ArchivedOption::<u32>::None);
+
+        to_archived(&some, |same| check_eq(archived, same));
+        to_archived(&none, |different| check_ne(archived, different));
+    });
+
+    to_archived(&none, |archived| {
+        assert!(*archived == none);
+        assert!(*archived != some);
+        check_eq(archived, &ArchivedOption::<u32>::None);
+    });
+}
+
+#[test]
+fn string_comparisons() {
+    for value in ["", "short", "a string which is too long to be inline"] {
+        let string = value.to_string();
+        let borrowed = Cow::Borrowed(value);
+        let owned = Cow::<str>::Owned(string.clone());
+
+        to_archived(&string, |archived: &ArchivedString| {
+            check_eq(archived, &string);
+            check_eq(archived, value);
+            check_eq(archived, &value);
+            check_eq(archived, &borrowed);
+            check_eq(archived, &owned);
+
+            let other = "something else".to_string();
+            check_ne(archived, &other);
+            check_ne(archived, other.as_str());
+            check_ne(archived, &Cow::Borrowed(other.as_str()));
+
+            to_archived(&string, |same| check_eq(archived, same));
+            to_archived(&other, |different| check_ne(archived, different));
+        });
+    }
+}
+
+#[test]
+fn hash_map_comparisons() {
+    let mut value = HashMap::new();
+    value.insert("a".to_string(), 1u32);
+    value.insert("b".to_string(), 2u32);
+
+    let mut different_value = value.clone();
+    different_value.insert("b".to_string(), 3u32);
+
+    let mut different_key = value.clone();
+    different_key.remove("b");
+    different_key.insert("c".to_string(), 2u32);
+
+    let mut smaller = value.clone();
+    smaller.remove("b");
+
+    to_archived(&value, |archived| {
+        check_eq(archived, &value);
+        check_ne(archived, &different_value);
+        check_ne(archived, &different_key);
+        check_ne(archived, &smaller);
+
+        let hashbrown_value = value
+            .iter()
+            .map(|(k, v)| (k.clone(), *v))
+            .collect::<hashbrown::HashMap<_, _>>();
+        check_eq(archived, &hashbrown_value);
+
+        to_archived(&value, |same| check_eq(archived, same));
+        to_archived(&different_value, |different| {
+            check_ne(archived, different)
+        });
+    });
+}
@@ -1,18 +1,20 @@
 use core::{
     alloc::{Layout, LayoutError},
     cell::{Cell, UnsafeCell},
-    mem::ManuallyDrop,
+    mem::{align_of, ManuallyDrop},
     ptr::{self, addr_of_mut},
     str,
 };
 
 use ptr_meta::Pointee;
-use rancor::Fallible;
+use rancor::{Fallible, Source};
 
 use crate::{
     primitive::ArchivedUsize,
     ser::{Allocator, Writer, WriterExt as _},
+    string::{ArchivedString, StringResolver},
     tuple::*,
+    vec::{ArchivedVec, VecResolver},
     Archive, ArchivePointee, ArchiveUnsized, ArchivedMetadata,
     CopyOptimization, Deserialize, DeserializeUnsized, LayoutRaw, Place,
     Portable, Serialize, SerializeUnsized,
@@ -72,9 +74,13 @@ where
     S: Fallible + Writer + ?Sized,
 {
     fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
-        let resolver = self.serialize(serializer)?;
-        serializer.align_for::<T::Archived>()?;
-        unsafe { serializer.resolve_aligned(self, resolver) }
+        serializer.enter_nested(core::any::type_name::<T>())?;
+        let result = self.serialize(serializer).and_then(|resolver| {
+            serializer.align_for::<T::Archived>()?;
+            unsafe { serializer.resolve_aligned(self, resolver) }
+        });
+        serializer.exit_nested();
+        result
     }
 }
 
@@ -273,44 +279,64 @@ where
     S: Fallible + Allocator + Writer + ?Sized,
 {
     fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
-        if T::COPY_OPTIMIZATION.is_enabled() {
-            let result = serializer.align_for::<T::Archived>()?;
-            let as_bytes = unsafe {
-                core::slice::from_raw_parts(
-                    self.as_ptr().cast::<u8>(),
-                    core::mem::size_of_val(self),
-                )
-            };
-            serializer.write(as_bytes)?;
-
-            Ok(result)
-        } else {
-            use crate::util::SerVec;
-
-            SerVec::with_capacity(
-                serializer,
-                self.len(),
-                |resolvers, serializer| {
-                    for value in self.iter() {
-                        unsafe {
-                            resolvers
-                                .push_unchecked(value.serialize(serializer)?);
-                        }
+        serializer.enter_nested(core::any::type_name::<[T]>())?;
+        let result = serialize_slice_aligned(
+            self,
+            align_of::<T::Archived>(),
+            serializer,
+        );
+        serializer.exit_nested();
+        result
+    }
+}
+
+/// Serializes the elements of a slice, aligning the first element to at least
+/// `align` bytes. `align` must be a power of two.
+pub(crate) fn serialize_slice_aligned<T, S>(
+    slice: &[T],
+    align: usize,
+    serializer: &mut S,
+) -> Result<usize, S::Error>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    let align = align.max(align_of::<T::Archived>());
+    if T::COPY_OPTIMIZATION.is_enabled() {
+        let result = serializer.align(align)?;
+        let as_bytes = unsafe {
+            core::slice::from_raw_parts(
+                slice.as_ptr().cast::<u8>(),
+                core::mem::size_of_val(slice),
+            )
+        };
+        serializer.write(as_bytes)?;
+
+        Ok(result)
+    } else {
+        use crate::util::SerVec;
+
+        SerVec::with_capacity(
+            serializer,
+            slice.len(),
+            |resolvers, serializer| {
+                for value in slice.iter() {
+                    unsafe {
+                        resolvers.push_unchecked(value.serialize(serializer)?);
                     }
+                }
 
-                    let result = serializer.align_for::<T::Archived>()?;
+                let result = serializer.align(align)?;
 
-                    for (value, resolver) in self.iter().zip(resolvers.drain())
-                    {
-                        unsafe {
-                            serializer.resolve_aligned(value, resolver)?;
-                        }
+                for (value, resolver) in slice.iter().zip(resolvers.drain()) {
+                    unsafe {
+                        serializer.resolve_aligned(value, resolver)?;
                     }
+                }
 
-                    Ok(result)
-                },
-            )?
-        }
+                Ok(result)
+            },
+        )?
     }
 }
 
@@ -371,9 +397,12 @@ impl ArchivePointee for str {
 
 impl<S: Fallible + Writer + ?Sized> SerializeUnsized<S> for str {
     fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
+        serializer.enter_nested("str")?;
         let result = serializer.pos();
-        serializer.write(self.as_bytes())?;
-        Ok(result)
+        serializer.reserve_hint(self.len());
+        let written = serializer.write(self.as_bytes());
+        serializer.exit_nested();
+        written.map(|()| result)
     }
 }
 
@@ -404,6 +433,109 @@ impl<D: Fallible + ?Sized> DeserializeUnsized<str, D> for str {
     }
 }
 
+// `&'static str`
+
+impl Archive for &'static str {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedString::resolve_from_str(self, resolver, out);
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for &'static str
+where
+    str: SerializeUnsized<S>,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str(self, serializer)
+    }
+}
+
+// `&'static [T]`
+
+impl<T: Archive> Archive for &'static [T] {
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_slice(self, resolver, out);
+    }
+}
+
+impl<T, S> Serialize<S> for &'static [T]
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::<T::Archived>::serialize_from_slice(self, serializer)
+    }
+}
+
+// `ArchivedString`
+
+impl Archive for ArchivedString {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedString::resolve_from_str(self.as_str(), resolver, out);
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for ArchivedString
+where
+    str: SerializeUnsized<S>,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str(self.as_str(), serializer)
+    }
+}
+
+// `ArchivedVec`
+
+impl<T: Archive> Archive for ArchivedVec<T> {
+    type Archived = ArchivedVec<T::Archived>;
+    type Resolver = VecResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedVec::resolve_from_slice(self.as_slice(), resolver, out);
+    }
+}
+
+impl<T, S> Serialize<S> for ArchivedVec<T>
+where
+    T: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedVec::<T::Archived>::serialize_from_slice(
+            self.as_slice(),
+            serializer,
+        )
+    }
+}
+
 // `ManuallyDrop`
 
 unsafe impl<T: Portable> Portable for ManuallyDrop<T> {}
@@ -457,9 +589,16 @@ unsafe impl<T: Portable + ?Sized> Portable for UnsafeCell<T> {}
 mod tests {
     use core::mem::ManuallyDrop;
 
+    use bytecheck::CheckBytes;
+    use rancor::{Panic, Strategy};
+
     use crate::{
-        test::{roundtrip, roundtrip_with},
+        test::{
+            access, roundtrip, roundtrip_with, to_bytes, TestSerializer,
+            TestValidator,
+        },
         tuple::ArchivedTuple3,
+        Archive, Serialize,
     };
 
     #[test]
@@ -485,4 +624,36 @@ mod tests {
     fn roundtrip_manually_drop() {
         roundtrip(&ManuallyDrop::new(123i8));
     }
+
+    fn rearchive<T>(value: &T)
+    where
+        T: for<'a> Serialize<TestSerializer<'a>>,
+        T::Archived: Archive<Archived = T::Archived>
+            + for<'a> Serialize<TestSerializer<'a>>
+            + for<'a> CheckBytes<Strategy<TestValidator<'a>, Panic>>,
+    {
+        to_bytes(value, |bytes| {
+            access::<T::Archived>(bytes, |archived| {
+                to_bytes(archived, |rearchived| {
+                    assert_eq!(bytes, rearchived);
+                    access::<T::Archived>(rearchived, |_| ());
+                });
+            });
+        });
+    }
+
+    #[test]
+    fn rearchive_archived_types() {
+        rearchive(&1234567890u32);
+        rearchive(&'x');
+        rearchive(&"hello world");
+        rearchive(&"a string which is too long to be stored inline");
+        let ints: &'static [u32] = &[1, 2, 3, 4];
+        rearchive(&ints);
+        let strings: &'static [&str] =
+            &["foo", "bar", "a string which is stored out of line"];
+        rearchive(&strings);
+        rearchive(&Option::<&str>::None);
+        rearchive(&Some("a string stored out of line in an option"));
+    }
 }
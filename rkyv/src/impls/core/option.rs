@@ -4,8 +4,8 @@ use munge::munge;
 use rancor::Fallible;
 
 use crate::{
-    option::ArchivedOption, place::Initialized, Archive, Deserialize, Place,
-    Serialize,
+    option::ArchivedOption, place::Initialized, Archive, Deserialize,
+    DeserializeInto, Place, Serialize,
 };
 
 #[allow(dead_code)]
@@ -24,38 +24,44 @@ struct ArchivedOptionVariantNone(ArchivedOptionTag);
 #[repr(C)]
 struct ArchivedOptionVariantSome<T>(ArchivedOptionTag, T);
 
+fn resolve_option<T: Archive>(
+    value: Option<&T>,
+    resolver: Option<T::Resolver>,
+    out: Place<ArchivedOption<T::Archived>>,
+) {
+    match resolver {
+        None => {
+            let out =
+                unsafe { out.cast_unchecked::<ArchivedOptionVariantNone>() };
+            munge!(let ArchivedOptionVariantNone(tag) = out);
+            tag.write(ArchivedOptionTag::None);
+        }
+        Some(resolver) => {
+            let out = unsafe {
+                out.cast_unchecked::<ArchivedOptionVariantSome<T::Archived>>()
+            };
+            munge!(let ArchivedOptionVariantSome(tag, out_value) = out);
+            tag.write(ArchivedOptionTag::Some);
+
+            let value = if let Some(value) = value {
+                value
+            } else {
+                unsafe {
+                    unreachable_unchecked();
+                }
+            };
+
+            value.resolve(resolver, out_value);
+        }
+    }
+}
+
 impl<T: Archive> Archive for Option<T> {
     type Archived = ArchivedOption<T::Archived>;
     type Resolver = Option<T::Resolver>;
 
     fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
-        match resolver {
-            None => {
-                let out = unsafe {
-                    out.cast_unchecked::<ArchivedOptionVariantNone>()
-                };
-                munge!(let ArchivedOptionVariantNone(tag) = out);
-                tag.write(ArchivedOptionTag::None);
-            }
-            Some(resolver) => {
-                let out = unsafe {
-                    out
-                    .cast_unchecked::<ArchivedOptionVariantSome<T::Archived>>()
-                };
-                munge!(let ArchivedOptionVariantSome(tag, out_value) = out);
-                tag.write(ArchivedOptionTag::Some);
-
-                let value = if let Some(value) = self.as_ref() {
-                    value
-                } else {
-                    unsafe {
-                        unreachable_unchecked();
-                    }
-                };
-
-                value.resolve(resolver, out_value);
-            }
-        }
+        resolve_option(self.as_ref(), resolver, out);
     }
 }
 
@@ -70,6 +76,26 @@ impl<T: Serialize<S>, S: Fallible + ?Sized> Serialize<S> for Option<T> {
     }
 }
 
+impl<T: Archive> Archive for ArchivedOption<T> {
+    type Archived = ArchivedOption<T::Archived>;
+    type Resolver = Option<T::Resolver>;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        resolve_option(self.as_ref(), resolver, out);
+    }
+}
+
+impl<T: Serialize<S>, S: Fallible + ?Sized> Serialize<S> for ArchivedOption<T> {
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        self.as_ref()
+            .map(|value| value.serialize(serializer))
+            .transpose()
+    }
+}
+
 impl<T, D> Deserialize<Option<T>, D> for ArchivedOption<T::Archived>
 where
     T: Archive,
@@ -86,6 +112,29 @@ where
     }
 }
 
+impl<T, D> DeserializeInto<Option<T>, D> for ArchivedOption<T::Archived>
+where
+    T: Archive,
+    T::Archived: DeserializeInto<T, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_into(
+        &self,
+        out: &mut Option<T>,
+        deserializer: &mut D,
+    ) -> Result<(), D::Error> {
+        match (self, out) {
+            (ArchivedOption::Some(value), Some(out)) => {
+                value.deserialize_into(out, deserializer)
+            }
+            (archived, out) => {
+                *out = archived.deserialize(deserializer)?;
+                Ok(())
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::test::roundtrip;
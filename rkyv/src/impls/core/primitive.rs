@@ -19,7 +19,8 @@ use crate::{
         ArchivedNonZeroU32, ArchivedNonZeroU64, ArchivedNonZeroUsize,
         ArchivedU128, ArchivedU16, ArchivedU32, ArchivedU64, ArchivedUsize,
     },
-    Archive, CopyOptimization, Deserialize, Place, Portable, Serialize,
+    Archive, CopyOptimization, Deserialize, DeserializeInto, Place, Portable,
+    Serialize,
 };
 
 macro_rules! unsafe_impl_initialized_and_portable {
@@ -38,6 +39,10 @@ unsafe_impl_initialized_and_portable! {
     u8,
     NonZeroI8,
     NonZeroU8,
+    ArchivedI128,
+    ArchivedU128,
+    ArchivedNonZeroI128,
+    ArchivedNonZeroU128,
     rend::NonZeroI16_be,
     rend::NonZeroI16_le,
     rend::NonZeroI32_be,
@@ -116,6 +121,42 @@ unsafe_impl_initialized_and_portable! {
     rend::unaligned::u128_ule,
 }
 
+// Native multibyte primitives aren't `Portable` because their byte order
+// depends on the target, but they never have padding.
+macro_rules! unsafe_impl_initialized {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            unsafe impl Initialized for $ty {}
+        )*
+    };
+}
+
+unsafe_impl_initialized! {
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    f32,
+    f64,
+    char,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize,
+}
+
 unsafe impl<T: Portable, const N: usize> Portable for [T; N] {}
 unsafe impl<T: Portable> Portable for [T] {}
 
@@ -448,6 +489,75 @@ where
     }
 }
 
+// DeserializeInto
+
+macro_rules! impl_deserialize_into_by_value {
+    ($($archived:ty: $type:ty),* $(,)?) => {
+        $(
+            impl<D> DeserializeInto<$type, D> for $archived
+            where
+                D: Fallible + ?Sized,
+            {
+                #[inline]
+                fn deserialize_into(
+                    &self,
+                    out: &mut $type,
+                    deserializer: &mut D,
+                ) -> Result<(), D::Error> {
+                    *out = self.deserialize(deserializer)?;
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+impl_deserialize_into_by_value! {
+    (): (),
+    bool: bool,
+    i8: i8,
+    u8: u8,
+    NonZeroI8: NonZeroI8,
+    NonZeroU8: NonZeroU8,
+    ArchivedI16: i16,
+    ArchivedI32: i32,
+    ArchivedI64: i64,
+    ArchivedI128: i128,
+    ArchivedU16: u16,
+    ArchivedU32: u32,
+    ArchivedU64: u64,
+    ArchivedU128: u128,
+    ArchivedF32: f32,
+    ArchivedF64: f64,
+    ArchivedChar: char,
+    ArchivedNonZeroI16: NonZeroI16,
+    ArchivedNonZeroI32: NonZeroI32,
+    ArchivedNonZeroI64: NonZeroI64,
+    ArchivedNonZeroI128: NonZeroI128,
+    ArchivedNonZeroU16: NonZeroU16,
+    ArchivedNonZeroU32: NonZeroU32,
+    ArchivedNonZeroU64: NonZeroU64,
+    ArchivedNonZeroU128: NonZeroU128,
+    ArchivedUsize: usize,
+    ArchivedIsize: isize,
+    ArchivedNonZeroUsize: NonZeroUsize,
+    ArchivedNonZeroIsize: NonZeroIsize,
+    PhantomPinned: PhantomPinned,
+}
+
+impl<T: ?Sized, D: Fallible + ?Sized> DeserializeInto<PhantomData<T>, D>
+    for PhantomData<T>
+{
+    #[inline]
+    fn deserialize_into(
+        &self,
+        _: &mut PhantomData<T>,
+        _: &mut D,
+    ) -> Result<(), D::Error> {
+        Ok(())
+    }
+}
+
 // Atomics
 
 #[cfg(target_has_atomic = "8")]
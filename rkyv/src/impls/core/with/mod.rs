@@ -11,9 +11,12 @@ use core::{
 };
 
 use munge::munge;
-use rancor::Fallible;
+use rancor::{Fallible, ResultExt as _, Source};
 
 use crate::{
+    bounded::{ArchivedBounded, BoundedRepr},
+    de::Interning,
+    discriminant::{ArchivedDiscriminant, DiscriminantRepr, ToDiscriminant},
     niche::option_nonzero::{
         ArchivedOptionNonZeroI128, ArchivedOptionNonZeroI16,
         ArchivedOptionNonZeroI32, ArchivedOptionNonZeroI64,
@@ -25,11 +28,13 @@ use crate::{
     option::ArchivedOption,
     place::Initialized,
     primitive::{FixedNonZeroIsize, FixedNonZeroUsize},
+    string::{ArchivedString, StringResolver},
     with::{
-        ArchiveWith, DeserializeWith, Inline, Map, Niche, SerializeWith, Skip,
+        ArchiveWith, AsDiscriminant, Bounded, DefaultOnNone, DeserializeWith,
+        Fallback, Identity, Inline, Interned, Map, Niche, SerializeWith, Skip,
         Unsafe,
     },
-    Archive, Deserialize, Place, Serialize,
+    Archive, Deserialize, Place, Serialize, SerializeUnsized,
 };
 
 // Map
@@ -267,6 +272,102 @@ where
     }
 }
 
+// AsDiscriminant
+
+impl<F, R> ArchiveWith<F> for AsDiscriminant<R>
+where
+    F: ToDiscriminant<R>,
+    R: DiscriminantRepr,
+{
+    type Archived = ArchivedDiscriminant<F, R>;
+    type Resolver = ();
+
+    #[inline]
+    fn resolve_with(field: &F, _: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedDiscriminant::resolve_from_enum(field, out);
+    }
+}
+
+impl<F, R, S> SerializeWith<F, S> for AsDiscriminant<R>
+where
+    F: ToDiscriminant<R>,
+    R: DiscriminantRepr,
+    S: Fallible + ?Sized,
+{
+    #[inline]
+    fn serialize_with(_: &F, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+impl<F, R, D> DeserializeWith<ArchivedDiscriminant<F, R>, F, D>
+    for AsDiscriminant<R>
+where
+    F: ToDiscriminant<R>,
+    R: DiscriminantRepr,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedDiscriminant<F, R>,
+        _: &mut D,
+    ) -> Result<F, D::Error> {
+        field.try_to_native().into_error()
+    }
+}
+
+// Bounded
+
+impl<F, const MIN: u128, const MAX: u128> ArchiveWith<F> for Bounded<MIN, MAX>
+where
+    F: BoundedRepr,
+{
+    type Archived = ArchivedBounded<F, MIN, MAX>;
+    type Resolver = ();
+
+    #[inline]
+    fn resolve_with(field: &F, _: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedBounded::resolve_from_value(*field, out);
+    }
+}
+
+impl<F, S, const MIN: u128, const MAX: u128> SerializeWith<F, S>
+    for Bounded<MIN, MAX>
+where
+    F: BoundedRepr,
+    S: Fallible + ?Sized,
+{
+    #[inline]
+    fn serialize_with(
+        field: &F,
+        _: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        debug_assert!(
+            ArchivedBounded::<F, MIN, MAX>::in_bounds(*field),
+            "integer {} is out of bounds, expected a value in {}..={}",
+            Into::<u128>::into(*field),
+            MIN,
+            MAX,
+        );
+        Ok(())
+    }
+}
+
+impl<F, D, const MIN: u128, const MAX: u128>
+    DeserializeWith<ArchivedBounded<F, MIN, MAX>, F, D> for Bounded<MIN, MAX>
+where
+    F: BoundedRepr,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize_with(
+        field: &ArchivedBounded<F, MIN, MAX>,
+        _: &mut D,
+    ) -> Result<F, D::Error> {
+        field.try_get().into_error()
+    }
+}
+
 // Inline
 
 impl<F: Archive> ArchiveWith<&F> for Inline {
@@ -291,6 +392,46 @@ impl<F: Serialize<S>, S: Fallible + ?Sized> SerializeWith<&F, S> for Inline {
     }
 }
 
+// Interned
+
+impl ArchiveWith<&'static str> for Interned {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    fn resolve_with(
+        field: &&'static str,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        field.resolve(resolver, out);
+    }
+}
+
+impl<S> SerializeWith<&'static str, S> for Interned
+where
+    S: Fallible + ?Sized,
+    str: SerializeUnsized<S>,
+{
+    fn serialize_with(
+        field: &&'static str,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        field.serialize(serializer)
+    }
+}
+
+impl<D> DeserializeWith<ArchivedString, &'static str, D> for Interned
+where
+    D: Fallible + Interning + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedString,
+        deserializer: &mut D,
+    ) -> Result<&'static str, D::Error> {
+        deserializer.intern_str(field.as_str())
+    }
+}
+
 // Unsafe
 
 impl<F: Archive> ArchiveWith<UnsafeCell<F>> for Unsafe {
@@ -405,3 +546,100 @@ impl<F: Default, D: Fallible + ?Sized> DeserializeWith<(), F, D> for Skip {
         Ok(Default::default())
     }
 }
+
+// Identity
+
+impl<F: Archive> ArchiveWith<F> for Identity {
+    type Archived = F::Archived;
+    type Resolver = F::Resolver;
+
+    fn resolve_with(
+        field: &F,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        field.resolve(resolver, out);
+    }
+}
+
+impl<F: Serialize<S>, S: Fallible + ?Sized> SerializeWith<F, S> for Identity {
+    fn serialize_with(
+        field: &F,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        field.serialize(serializer)
+    }
+}
+
+impl<F, D> DeserializeWith<F::Archived, F, D> for Identity
+where
+    F: Archive,
+    F::Archived: Deserialize<F, D>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &F::Archived,
+        deserializer: &mut D,
+    ) -> Result<F, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
+// DefaultOnNone
+
+impl<W, F, T> ArchiveWith<T> for DefaultOnNone<W, F>
+where
+    W: ArchiveWith<T>,
+{
+    type Archived = ArchivedOption<<W as ArchiveWith<T>>::Archived>;
+    type Resolver = <W as ArchiveWith<T>>::Resolver;
+
+    fn resolve_with(
+        field: &T,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        let out = unsafe {
+            out.cast_unchecked::<ArchivedOptionVariantSome<
+                <W as ArchiveWith<T>>::Archived,
+            >>()
+        };
+        munge!(let ArchivedOptionVariantSome(tag, out_value) = out);
+        tag.write(ArchivedOptionTag::Some);
+        W::resolve_with(field, resolver, out_value);
+    }
+}
+
+impl<W, F, T, S> SerializeWith<T, S> for DefaultOnNone<W, F>
+where
+    W: SerializeWith<T, S>,
+    S: Fallible + ?Sized,
+{
+    fn serialize_with(
+        field: &T,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        W::serialize_with(field, serializer)
+    }
+}
+
+impl<W, F, T, D>
+    DeserializeWith<ArchivedOption<<W as ArchiveWith<T>>::Archived>, T, D>
+    for DefaultOnNone<W, F>
+where
+    W: ArchiveWith<T> + DeserializeWith<<W as ArchiveWith<T>>::Archived, T, D>,
+    F: Fallback<T>,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedOption<<W as ArchiveWith<T>>::Archived>,
+        deserializer: &mut D,
+    ) -> Result<T, D::Error> {
+        match field {
+            ArchivedOption::Some(value) => {
+                W::deserialize_with(value, deserializer)
+            }
+            ArchivedOption::None => Ok(F::fallback()),
+        }
+    }
+}
@@ -1,13 +1,13 @@
-use core::{
-    borrow::Borrow,
-    hash::{BuildHasher, Hash},
-};
+use core::hash::{BuildHasher, Hash, Hasher};
 
 use hashbrown::HashMap;
 use rancor::{Fallible, Source};
 
 use crate::{
-    collections::swiss_table::map::{ArchivedHashMap, HashMapResolver},
+    collections::swiss_table::{
+        ArchivedHashMap, HashMapResolver, DEFAULT_LOAD_FACTOR,
+    },
+    hash::LookupKey,
     ser::{Allocator, Writer},
     Archive, Deserialize, Place, Serialize,
 };
@@ -15,20 +15,23 @@ use crate::{
 impl<K, V: Archive, S> Archive for HashMap<K, V, S>
 where
     K: Archive + Hash + Eq,
-    K::Archived: Hash + Eq,
 {
     type Archived = ArchivedHashMap<K::Archived, V::Archived>;
     type Resolver = HashMapResolver;
 
     fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
-        ArchivedHashMap::resolve_from_len(self.len(), (7, 8), resolver, out);
+        ArchivedHashMap::resolve_from_len(
+            self.len(),
+            DEFAULT_LOAD_FACTOR,
+            resolver,
+            out,
+        );
     }
 }
 
 impl<K, V, S, RandomState> Serialize<S> for HashMap<K, V, RandomState>
 where
     K: Serialize<S> + Hash + Eq,
-    K::Archived: Hash + Eq,
     V: Serialize<S>,
     S: Fallible + Writer + Allocator + ?Sized,
     S::Error: Source,
@@ -39,7 +42,7 @@ where
     ) -> Result<Self::Resolver, S::Error> {
         ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_iter(
             self.iter(),
-            (7, 8),
+            DEFAULT_LOAD_FACTOR,
             serializer,
         )
     }
@@ -49,7 +52,7 @@ impl<K, V, D, S> Deserialize<HashMap<K, V, S>, D>
     for ArchivedHashMap<K::Archived, V::Archived>
 where
     K: Archive + Hash + Eq,
-    K::Archived: Deserialize<K, D> + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
     V: Archive,
     V::Archived: Deserialize<V, D>,
     D: Fallible + ?Sized,
@@ -71,31 +74,32 @@ where
     }
 }
 
-impl<K, V, AK, AV, S> PartialEq<HashMap<K, V, S>> for ArchivedHashMap<AK, AV>
+impl<K, V, AK, AV, H, S> PartialEq<HashMap<K, V, S>>
+    for ArchivedHashMap<AK, AV, H>
 where
-    K: Hash + Eq + Borrow<AK>,
-    AK: Hash + Eq,
+    K: LookupKey<AK>,
     AV: PartialEq<V>,
-    S: BuildHasher,
+    H: Default + Hasher,
 {
     fn eq(&self, other: &HashMap<K, V, S>) -> bool {
         if self.len() != other.len() {
             false
         } else {
-            self.iter().all(|(key, value)| {
-                other.get(key).map_or(false, |v| value.eq(v))
+            other.iter().all(|(key, value)| {
+                self.get(key).map_or(false, |v| v.eq(value))
             })
         }
     }
 }
 
-impl<K, V, AK, AV> PartialEq<ArchivedHashMap<AK, AV>> for HashMap<K, V>
+impl<K, V, AK, AV, H, S> PartialEq<ArchivedHashMap<AK, AV, H>>
+    for HashMap<K, V, S>
 where
-    K: Hash + Eq + Borrow<AK>,
-    AK: Hash + Eq,
+    K: LookupKey<AK>,
     AV: PartialEq<V>,
+    H: Default + Hasher,
 {
-    fn eq(&self, other: &ArchivedHashMap<AK, AV>) -> bool {
+    fn eq(&self, other: &ArchivedHashMap<AK, AV, H>) -> bool {
         other.eq(self)
     }
 }
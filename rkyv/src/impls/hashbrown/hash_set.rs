@@ -7,7 +7,9 @@ use hashbrown::HashSet;
 use rancor::{Fallible, Source};
 
 use crate::{
-    collections::swiss_table::set::{ArchivedHashSet, HashSetResolver},
+    collections::swiss_table::{
+        ArchivedHashSet, HashSetResolver, DEFAULT_LOAD_FACTOR,
+    },
     ser::{Allocator, Writer},
     Archive, Deserialize, Place, Serialize,
 };
@@ -15,7 +17,6 @@ use crate::{
 impl<K, S> Archive for HashSet<K, S>
 where
     K: Archive + Hash + Eq,
-    K::Archived: Hash + Eq,
 {
     type Archived = ArchivedHashSet<K::Archived>;
     type Resolver = HashSetResolver;
@@ -23,7 +24,7 @@ where
     fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
         ArchivedHashSet::<K::Archived>::resolve_from_len(
             self.len(),
-            (7, 8),
+            DEFAULT_LOAD_FACTOR,
             resolver,
             out,
         );
@@ -32,7 +33,6 @@ where
 
 impl<K, S, RS> Serialize<S> for HashSet<K, RS>
 where
-    K::Archived: Hash + Eq,
     K: Serialize<S> + Hash + Eq,
     S: Fallible + Allocator + Writer + ?Sized,
     S::Error: Source,
@@ -43,7 +43,7 @@ where
     ) -> Result<Self::Resolver, S::Error> {
         ArchivedHashSet::<K::Archived>::serialize_from_iter(
             self.iter(),
-            (7, 8),
+            DEFAULT_LOAD_FACTOR,
             serializer,
         )
     }
@@ -52,7 +52,7 @@ where
 impl<K, D, S> Deserialize<HashSet<K, S>, D> for ArchivedHashSet<K::Archived>
 where
     K: Archive + Hash + Eq,
-    K::Archived: Deserialize<K, D> + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
     D: Fallible + ?Sized,
     S: Default + BuildHasher,
 {
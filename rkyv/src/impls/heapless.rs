@@ -0,0 +1,211 @@
+use heapless::{String, Vec};
+use rancor::{fail, Fallible, Source};
+
+use crate::{
+    inline::{ArchivedInlineString, ArchivedInlineVec, CapacityExceeded},
+    Archive, Archived, Deserialize, Place, Serialize,
+};
+
+// String
+
+impl<const N: usize> Archive for String<N> {
+    type Archived = ArchivedInlineString<N>;
+    type Resolver = ();
+
+    #[inline]
+    fn resolve(&self, _: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedInlineString::resolve_from_str(self.as_str(), out);
+    }
+}
+
+impl<S: Fallible + ?Sized, const N: usize> Serialize<S> for String<N> {
+    #[inline]
+    fn serialize(&self, _: &mut S) -> Result<Self::Resolver, S::Error> {
+        Ok(())
+    }
+}
+
+impl<D, const N: usize> Deserialize<String<N>, D> for ArchivedInlineString<N>
+where
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, _: &mut D) -> Result<String<N>, D::Error> {
+        let mut result = String::new();
+        if self.exceeds_capacity() || result.push_str(self.as_str()).is_err() {
+            fail!(CapacityExceeded {
+                len: self.len(),
+                capacity: N,
+            });
+        }
+        Ok(result)
+    }
+}
+
+// Vec
+
+impl<T: Archive, const N: usize> Archive for Vec<T, N> {
+    type Archived = ArchivedInlineVec<Archived<T>, N>;
+    type Resolver = Vec<T::Resolver, N>;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedInlineVec::resolve_from_slice(self.as_slice(), resolver, out);
+    }
+}
+
+impl<T, S, const N: usize> Serialize<S> for Vec<T, N>
+where
+    T: Serialize<S>,
+    S: Fallible + ?Sized,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        self.iter().map(|item| item.serialize(serializer)).collect()
+    }
+}
+
+impl<T, D, const N: usize> Deserialize<Vec<T, N>, D>
+    for ArchivedInlineVec<Archived<T>, N>
+where
+    T: Archive,
+    Archived<T>: Deserialize<T, D>,
+    D: Fallible + ?Sized,
+    D::Error: Source,
+{
+    fn deserialize(&self, deserializer: &mut D) -> Result<Vec<T, N>, D::Error> {
+        if self.exceeds_capacity() {
+            fail!(CapacityExceeded {
+                len: self.len(),
+                capacity: N,
+            });
+        }
+
+        let mut result = Vec::new();
+        for item in self.as_slice() {
+            if result.push(item.deserialize(deserializer)?).is_err() {
+                fail!(CapacityExceeded {
+                    len: self.len(),
+                    capacity: N,
+                });
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::size_of;
+
+    use heapless::{String, Vec};
+    use rancor::Failure;
+
+    use crate::{
+        access_unchecked,
+        inline::{ArchivedInlineString, ArchivedInlineVec},
+        primitive::{ArchivedU32, ArchivedUsize},
+        test::{roundtrip_with, to_bytes},
+        util::Align,
+        Archive, Archived, Deserialize, Serialize,
+    };
+
+    #[test]
+    fn roundtrip_string() {
+        assert_eq!(size_of::<Archived<String<8>>>(), 9);
+
+        for value in ["", "a", "héllo", "12345678"] {
+            let mut string = String::<8>::new();
+            string.push_str(value).unwrap();
+            roundtrip_with(&string, |a, b| {
+                assert_eq!(b.as_str(), a.as_str());
+                assert_eq!(b.len(), a.len());
+            });
+        }
+    }
+
+    #[test]
+    fn roundtrip_vec() {
+        for len in 0..=4 {
+            let value =
+                Vec::<u32, 4>::from_slice(&[1, 2, 3, 4][..len]).unwrap();
+            roundtrip_with(&value, |a, b| {
+                assert_eq!(b.len(), a.len());
+                assert!(b
+                    .iter()
+                    .zip(a.iter())
+                    .all(|(b, a)| b.to_native() == *a));
+            });
+        }
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, derive(Debug))]
+    struct Reading {
+        sensor: String<16>,
+        samples: Vec<String<4>, 3>,
+    }
+
+    #[test]
+    fn roundtrip_nested() {
+        let mut value = Reading {
+            sensor: String::new(),
+            samples: Vec::new(),
+        };
+        value.sensor.push_str("thermometer").unwrap();
+        for sample in ["21.5", "22", "-4"] {
+            let mut string = String::new();
+            string.push_str(sample).unwrap();
+            value.samples.push(string).unwrap();
+        }
+
+        roundtrip_with(&value, |a, b| {
+            assert_eq!(b.sensor, a.sensor.as_str());
+            assert_eq!(b.samples.len(), a.samples.len());
+            for (b, a) in b.samples.iter().zip(a.samples.iter()) {
+                assert_eq!(b, a.as_str());
+            }
+        });
+    }
+
+    #[test]
+    fn deserialize_exceeded_capacity() {
+        let mut value = String::<4>::new();
+        value.push_str("abcd").unwrap();
+        to_bytes(&value, |bytes| {
+            let mut buffer = Align([0u8; 5]);
+            buffer.0.copy_from_slice(bytes);
+            // Claim that the string is longer than its capacity
+            buffer.0[0] = 5;
+
+            let archived = unsafe {
+                access_unchecked::<ArchivedInlineString<4>>(&buffer.0)
+            };
+            assert_eq!(archived.as_str(), "abcd");
+            crate::deserialize::<String<4>, _, Failure>(archived, &mut ())
+                .unwrap_err();
+        });
+
+        type ArchivedPair = ArchivedInlineVec<ArchivedU32, 2>;
+
+        let value = Vec::<u32, 2>::from_slice(&[1, 2]).unwrap();
+        to_bytes(&value, |bytes| {
+            let mut buffer = Align([0u8; size_of::<ArchivedPair>()]);
+            buffer.0.copy_from_slice(bytes);
+            // Claim that the vector is longer than its capacity
+            let low_byte = if cfg!(feature = "big_endian") {
+                size_of::<ArchivedUsize>() - 1
+            } else {
+                0
+            };
+            buffer.0[low_byte] = 3;
+
+            let archived =
+                unsafe { access_unchecked::<ArchivedPair>(&buffer.0) };
+            assert_eq!(archived.len(), 2);
+            crate::deserialize::<Vec<u32, 2>, _, Failure>(archived, &mut ())
+                .unwrap_err();
+        });
+    }
+}
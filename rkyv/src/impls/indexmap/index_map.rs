@@ -4,7 +4,9 @@ use indexmap::IndexMap;
 use rancor::{Fallible, Source};
 
 use crate::{
-    collections::swiss_table::{ArchivedIndexMap, IndexMapResolver},
+    collections::swiss_table::{
+        ArchivedIndexMap, IndexMapResolver, DEFAULT_LOAD_FACTOR,
+    },
     ser::{Allocator, Writer},
     Archive, Deserialize, Place, Serialize,
 };
@@ -14,7 +16,12 @@ impl<K: Archive, V: Archive, S> Archive for IndexMap<K, V, S> {
     type Resolver = IndexMapResolver;
 
     fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
-        ArchivedIndexMap::resolve_from_len(self.len(), (7, 8), resolver, out);
+        ArchivedIndexMap::resolve_from_len(
+            self.len(),
+            DEFAULT_LOAD_FACTOR,
+            resolver,
+            out,
+        );
     }
 }
 
@@ -31,7 +38,7 @@ where
     ) -> Result<IndexMapResolver, S::Error> {
         ArchivedIndexMap::<K::Archived, V::Archived>::serialize_from_iter(
             self.iter(),
-            (7, 8),
+            DEFAULT_LOAD_FACTOR,
             serializer,
         )
     }
@@ -79,12 +86,17 @@ where
 #[cfg(test)]
 mod tests {
     #[cfg(not(feature = "std"))]
-    use alloc::string::String;
+    use alloc::{string::String, vec::Vec};
     use core::hash::BuildHasherDefault;
 
     use indexmap::IndexMap;
+    use rancor::Failure;
 
-    use crate::{hash::FxHasher64, test::roundtrip_with};
+    use crate::{
+        access_unchecked, access_unchecked_mut,
+        collections::swiss_table::ArchivedIndexMap, hash::FxHasher64,
+        primitive::ArchivedU32, string::ArchivedString, test::roundtrip_with,
+    };
 
     #[test]
     fn index_map() {
@@ -105,15 +117,115 @@ mod tests {
         });
     }
 
+    type ArchivedMap = ArchivedIndexMap<ArchivedString, ArchivedU32>;
+
+    fn sorted_map() -> IndexMap<String, u32, BuildHasherDefault<FxHasher64>> {
+        let mut value =
+            IndexMap::with_hasher(BuildHasherDefault::<FxHasher64>::default());
+        for (i, key) in ["b", "d", "f", "h"].into_iter().enumerate() {
+            value.insert(String::from(key), i as u32);
+        }
+        value
+    }
+
+    #[test]
+    fn index_access() {
+        let value = sorted_map();
+        let bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let archived = unsafe { access_unchecked::<ArchivedMap>(&bytes) };
+
+        let native = |entry: Option<(&String, &u32)>| {
+            entry.map(|(k, v)| (k.as_str(), *v))
+        };
+        let archived_entry =
+            |entry: Option<(&ArchivedString, &ArchivedU32)>| {
+                entry.map(|(k, v)| (k.as_str(), v.to_native()))
+            };
+        assert_eq!(archived_entry(archived.first()), native(value.first()));
+        assert_eq!(archived_entry(archived.last()), native(value.last()));
+        for i in 0..=value.len() {
+            assert_eq!(
+                archived_entry(archived.get_index(i)),
+                native(value.get_index(i)),
+            );
+        }
+
+        for key in ["a", "b", "c", "d", "g", "h", "i"] {
+            assert_eq!(
+                archived.binary_search_keys(key),
+                value.binary_search_keys(&String::from(key)),
+            );
+        }
+        assert_eq!(
+            archived.binary_search_by(|_, v| v.to_native().cmp(&2)),
+            value.binary_search_by(|_, v| v.cmp(&2)),
+        );
+
+        let keys = archived.iter().rev().map(|(k, _)| k.as_str());
+        assert!(keys.eq(value.keys().rev().map(String::as_str)));
+        assert_eq!(archived.values().len(), value.len());
+    }
+
+    #[test]
+    fn iter_mut() {
+        let mut value = sorted_map();
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let mut archived =
+            unsafe { access_unchecked_mut::<ArchivedMap>(&mut bytes) };
+
+        let mut iter = archived.as_mut().iter_mut();
+        assert_eq!(iter.len(), 4);
+        let (last_key, mut last) = iter.next_back().unwrap();
+        assert_eq!(last_key, "h");
+        *last = 100.into();
+        // Hold on to every yielded value at once to make sure that the
+        // mutable references do not alias each other.
+        let mut entries = iter.collect::<Vec<_>>();
+        for (_, value) in entries.iter_mut() {
+            **value = (value.to_native() * 10).into();
+        }
+        drop(entries);
+
+        let last = archived.as_mut().values_mut().next_back().unwrap();
+        assert_eq!(last.to_native(), 100);
+        for (_, value) in value.iter_mut().take(3) {
+            *value *= 10;
+        }
+        *value.last_mut().unwrap().1 = 100;
+        assert!(archived
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.to_native()))
+            .eq(value.iter().map(|(k, v)| (k.as_str(), *v))));
+
+        *archived.as_mut().first_mut().unwrap().1 = 1.into();
+        *archived.as_mut().get_index_mut(1).unwrap().1 = 2.into();
+        assert!(archived.as_mut().get_index_mut(4).is_none());
+        let values = archived.values().map(|v| v.to_native());
+        assert!(values.eq([1, 2, 20, 100]));
+    }
+
+    #[test]
+    fn empty_index_access() {
+        let mut value = sorted_map();
+        value.clear();
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let mut archived =
+            unsafe { access_unchecked_mut::<ArchivedMap>(&mut bytes) };
+
+        assert!(archived.first().is_none());
+        assert!(archived.last().is_none());
+        assert!(archived.as_mut().first_mut().is_none());
+        assert!(archived.as_mut().last_mut().is_none());
+        assert_eq!(archived.binary_search_keys("a"), Err(0));
+        assert_eq!(archived.as_mut().iter_mut().len(), 0);
+    }
+
     #[cfg(feature = "bytecheck")]
     #[test]
     fn validate_index_map() {
         use rancor::Panic;
 
-        use crate::{
-            access, collections::swiss_table::ArchivedIndexMap,
-            primitive::ArchivedI32, string::ArchivedString,
-        };
+        use crate::{access, primitive::ArchivedI32};
 
         let mut value =
             IndexMap::with_hasher(BuildHasherDefault::<FxHasher64>::default());
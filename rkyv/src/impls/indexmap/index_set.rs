@@ -4,7 +4,9 @@ use indexmap::IndexSet;
 use rancor::{Fallible, Source};
 
 use crate::{
-    collections::swiss_table::{ArchivedIndexSet, IndexSetResolver},
+    collections::swiss_table::{
+        ArchivedIndexSet, IndexSetResolver, DEFAULT_LOAD_FACTOR,
+    },
     ser::{Allocator, Writer},
     Archive, Deserialize, Place, Serialize,
 };
@@ -14,7 +16,12 @@ impl<K: Archive, S> Archive for IndexSet<K, S> {
     type Resolver = IndexSetResolver;
 
     fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
-        ArchivedIndexSet::resolve_from_len(self.len(), (7, 8), resolver, out);
+        ArchivedIndexSet::resolve_from_len(
+            self.len(),
+            DEFAULT_LOAD_FACTOR,
+            resolver,
+            out,
+        );
     }
 }
 
@@ -30,7 +37,7 @@ where
     ) -> Result<IndexSetResolver, S::Error> {
         ArchivedIndexSet::<K::Archived>::serialize_from_iter(
             self.iter(),
-            (7, 8),
+            DEFAULT_LOAD_FACTOR,
             serializer,
         )
     }
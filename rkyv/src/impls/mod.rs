@@ -21,8 +21,12 @@ mod bitvec;
 mod bytes;
 #[cfg(feature = "hashbrown")]
 mod hashbrown;
+#[cfg(feature = "heapless")]
+mod heapless;
 #[cfg(feature = "indexmap")]
 mod indexmap;
+#[cfg(feature = "rayon")]
+mod rayon;
 #[cfg(feature = "smallvec")]
 mod smallvec;
 #[cfg(feature = "smol_str")]
@@ -36,6 +40,11 @@ mod triomphe;
 #[cfg(feature = "uuid")]
 mod uuid;
 
+#[cfg(all(test, feature = "std"))]
+mod comparisons;
+#[cfg(all(test, feature = "std"))]
+mod zst;
+
 #[cfg(test)]
 mod tests {
     use core::pin::Pin;
@@ -48,9 +57,9 @@ mod tests {
         boxed::ArchivedBox,
         option::ArchivedOption,
         primitive::{ArchivedI32, ArchivedU32},
-        ser::Writer,
+        ser::{Allocator, Writer},
         string::ArchivedString,
-        test::roundtrip,
+        test::{roundtrip, to_archived},
         to_bytes,
         validation::ArchiveContext,
         vec::ArchivedVec,
@@ -211,6 +220,136 @@ mod tests {
         ]);
     }
 
+    #[test]
+    fn roundtrip_wide_enum() {
+        macro_rules! define_wide {
+            ($($variant:ident)*) => {
+                #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+                #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+                enum Wide {
+                    $($variant,)*
+                    Last(String),
+                }
+            };
+        }
+
+        define_wide! {
+            V0 V1 V2 V3 V4 V5 V6 V7 V8 V9 V10 V11 V12 V13 V14 V15 V16
+            V17 V18 V19 V20 V21 V22 V23 V24 V25 V26 V27 V28 V29 V30 V31
+            V32 V33 V34 V35 V36 V37 V38 V39 V40 V41 V42 V43 V44 V45 V46
+            V47 V48 V49 V50 V51 V52 V53 V54 V55 V56 V57 V58 V59 V60 V61
+            V62 V63 V64 V65 V66 V67 V68 V69 V70 V71 V72 V73 V74 V75 V76
+            V77 V78 V79 V80 V81 V82 V83 V84 V85 V86 V87 V88 V89 V90 V91
+            V92 V93 V94 V95 V96 V97 V98 V99 V100 V101 V102 V103 V104
+            V105 V106 V107 V108 V109 V110 V111 V112 V113 V114 V115 V116
+            V117 V118 V119 V120 V121 V122 V123 V124 V125 V126 V127 V128
+            V129 V130 V131 V132 V133 V134 V135 V136 V137 V138 V139 V140
+            V141 V142 V143 V144 V145 V146 V147 V148 V149 V150 V151 V152
+            V153 V154 V155 V156 V157 V158 V159 V160 V161 V162 V163 V164
+            V165 V166 V167 V168 V169 V170 V171 V172 V173 V174 V175 V176
+            V177 V178 V179 V180 V181 V182 V183 V184 V185 V186 V187 V188
+            V189 V190 V191 V192 V193 V194 V195 V196 V197 V198 V199 V200
+            V201 V202 V203 V204 V205 V206 V207 V208 V209 V210 V211 V212
+            V213 V214 V215 V216 V217 V218 V219 V220 V221 V222 V223 V224
+            V225 V226 V227 V228 V229 V230 V231 V232 V233 V234 V235 V236
+            V237 V238 V239 V240 V241 V242 V243 V244 V245 V246 V247 V248
+            V249 V250 V251 V252 V253 V254 V255 V256 V257 V258 V259 V260
+            V261 V262 V263 V264 V265 V266 V267 V268 V269 V270 V271 V272
+            V273 V274 V275 V276 V277 V278 V279 V280 V281 V282 V283 V284
+            V285 V286 V287 V288 V289 V290 V291 V292 V293 V294 V295 V296
+            V297 V298
+        }
+
+        let bytes = to_bytes::<Panic>(&Wide::V256).unwrap();
+        if cfg!(feature = "big_endian") {
+            assert_eq!(&bytes[..2], &[0x01, 0x00]);
+        } else {
+            assert_eq!(&bytes[..2], &[0x00, 0x01]);
+        }
+        roundtrip(&Wide::V0);
+        roundtrip(&Wide::V1);
+        roundtrip(&Wide::V255);
+        roundtrip(&Wide::V256);
+        roundtrip(&Wide::V298);
+        roundtrip(&Wide::Last("hello world".to_string()));
+    }
+
+    #[test]
+    fn enum_forced_repr() {
+        use core::mem::size_of;
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(
+            crate,
+            check_bytes,
+            repr(u16),
+            compare(PartialEq),
+            derive(Debug)
+        )]
+        #[repr(u16)]
+        enum Small {
+            A,
+            B(u8),
+            C = 0x1234,
+        }
+
+        assert_eq!(size_of::<ArchivedSmall>(), 4);
+        roundtrip(&Small::A);
+        roundtrip(&Small::B(42));
+        roundtrip(&Small::C);
+
+        let bytes = to_bytes::<Panic>(&Small::B(42)).unwrap();
+        if cfg!(feature = "big_endian") {
+            assert_eq!(&bytes[..3], &[0x00, 0x01, 42]);
+        } else {
+            assert_eq!(&bytes[..3], &[0x01, 0x00, 42]);
+        }
+        let bytes = to_bytes::<Panic>(&Small::C).unwrap();
+        if cfg!(feature = "big_endian") {
+            assert_eq!(&bytes[..2], &[0x12, 0x34]);
+        } else {
+            assert_eq!(&bytes[..2], &[0x34, 0x12]);
+        }
+    }
+
+    #[test]
+    fn enum_as_ref() {
+        #[derive(Archive, Serialize)]
+        #[rkyv(crate, check_bytes, derive_as_ref)]
+        enum Test {
+            A,
+            B(String, u8),
+            C { a: i32, b: Option<String> },
+        }
+
+        to_archived(&Test::A, |archived| {
+            assert!(matches!(archived.as_ref(), ArchivedTestRef::A));
+        });
+        to_archived(
+            &Test::B("hello".to_string(), 1),
+            |archived| match archived.as_ref() {
+                ArchivedTestRef::B(s, n) => {
+                    assert_eq!(s.as_str(), "hello");
+                    assert_eq!(*n, 1);
+                }
+                _ => panic!("expected tuple variant"),
+            },
+        );
+        to_archived(
+            &Test::C {
+                a: 42,
+                b: Some("world".to_string()),
+            },
+            |archived| match archived.as_ref() {
+                ArchivedTestRef::C { a, b } => {
+                    assert_eq!(a.to_native(), 42);
+                    assert_eq!(b.as_ref().map(|b| b.as_str()), Some("world"));
+                }
+                _ => panic!("expected struct variant"),
+            },
+        );
+    }
+
     #[test]
     fn roundtrip_generic_enum() {
         use core::fmt;
@@ -442,6 +581,156 @@ mod tests {
         });
     }
 
+    #[test]
+    fn bound_skip() {
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(
+            crate,
+            check_bytes(bounds(__C: ArchiveContext)),
+            compare(PartialEq),
+            derive(Debug),
+        )]
+        #[rkyv(serialize_bounds(__S: Writer))]
+        #[rkyv(deserialize_bounds(__D::Error: Source))]
+        struct Tree {
+            value: u32,
+            #[rkyv(bound_skip)]
+            next: Option<Box<Tree>>,
+        }
+
+        roundtrip(&Tree {
+            value: 1,
+            next: Some(Box::new(Tree {
+                value: 2,
+                next: None,
+            })),
+        });
+    }
+
+    #[test]
+    fn recursive_tree_without_bounds() {
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+        struct Tree {
+            value: u32,
+            children: Vec<Tree>,
+            parent: Option<Box<Self>>,
+        }
+
+        roundtrip(&Tree {
+            value: 1,
+            children: vec![
+                Tree {
+                    value: 2,
+                    children: Vec::new(),
+                    parent: None,
+                },
+                Tree {
+                    value: 3,
+                    children: vec![Tree {
+                        value: 4,
+                        children: Vec::new(),
+                        parent: None,
+                    }],
+                    parent: None,
+                },
+            ],
+            parent: Some(Box::new(Tree {
+                value: 0,
+                children: Vec::new(),
+                parent: None,
+            })),
+        });
+    }
+
+    #[test]
+    fn recursive_enum_without_bounds() {
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+        enum Expr {
+            Value(i32),
+            Neg(Box<Expr>),
+            Sum(Vec<Self>),
+        }
+
+        roundtrip(&Expr::Sum(vec![
+            Expr::Value(1),
+            Expr::Neg(Box::new(Expr::Sum(vec![
+                Expr::Value(2),
+                Expr::Value(3),
+            ]))),
+        ]));
+    }
+
+    #[test]
+    fn mutually_recursive_types() {
+        // Recursion through another type can't be detected, so one of the
+        // fields in the cycle has to omit its bounds manually.
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(
+            crate,
+            check_bytes(bounds(__C: ArchiveContext)),
+            compare(PartialEq),
+            derive(Debug),
+        )]
+        #[rkyv(serialize_bounds(__S: Writer + Allocator, __S::Error: Source))]
+        #[rkyv(deserialize_bounds(__D::Error: Source))]
+        struct Parent {
+            name: u32,
+            #[rkyv(omit_bounds)]
+            child: Option<Box<Child>>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+        struct Child {
+            name: u32,
+            parents: Vec<Parent>,
+        }
+
+        roundtrip(&Parent {
+            name: 1,
+            child: Some(Box::new(Child {
+                name: 2,
+                parents: vec![Parent {
+                    name: 3,
+                    child: None,
+                }],
+            })),
+        });
+    }
+
+    #[test]
+    fn phantom_data_bounds() {
+        use core::marker::PhantomData;
+
+        use crate::test::deserialize;
+
+        trait Marker {}
+
+        // Neither `dyn Marker` nor `fn()` implement `Archive`
+        #[derive(Archive, Serialize, Deserialize)]
+        #[rkyv(crate, check_bytes, compare(PartialEq))]
+        struct Tagged<T: ?Sized> {
+            id: u64,
+            _marker: PhantomData<T>,
+        }
+
+        fn check<T: ?Sized>() {
+            let value = Tagged::<T> {
+                id: 42,
+                _marker: PhantomData,
+            };
+            to_archived(&value, |archived| {
+                assert!(*archived == value);
+                assert_eq!(deserialize::<Tagged<T>>(archived).id, 42);
+            });
+        }
+
+        check::<dyn Marker>();
+        check::<fn()>();
+    }
+
     #[test]
     fn complex_bounds() {
         use core::marker::PhantomData;
@@ -783,6 +1072,216 @@ mod tests {
         roundtrip(&value);
     }
 
+    #[test]
+    fn deserialize_other_variant() {
+        use crate::test::{deserialize, to_archived};
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes)]
+        enum Message {
+            Ping,
+            Text(String),
+            Move { x: i32, y: i32 },
+            Quit,
+        }
+
+        // A reader which only handles some of the variants of `Message`. The
+        // variants are matched by name, so they can be in any order.
+        #[derive(Archive, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, as = "ArchivedMessage")]
+        enum Reader {
+            Quit,
+            Text(String),
+            #[rkyv(other)]
+            Unknown,
+        }
+
+        let cases = [
+            (Message::Ping, Reader::Unknown),
+            (
+                Message::Text("hello world".to_string()),
+                Reader::Text("hello world".to_string()),
+            ),
+            (Message::Move { x: 1, y: 2 }, Reader::Unknown),
+            (Message::Quit, Reader::Quit),
+        ];
+        for (message, expected) in cases {
+            to_archived(&message, |archived| {
+                assert_eq!(deserialize::<Reader>(archived), expected);
+            });
+        }
+
+        // The fallback variant of an enum with its own archived type
+        // deserializes as itself.
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+        enum Local {
+            Value(u32),
+            #[rkyv(other)]
+            Other,
+        }
+
+        roundtrip(&Local::Value(42));
+        roundtrip(&Local::Other);
+    }
+
+    #[test]
+    fn archive_transparent() {
+        use core::{any::type_name, marker::PhantomData, mem};
+
+        use crate::{test::roundtrip_with, Archived};
+
+        // Tuple struct
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, transparent)]
+        struct UserId(u64);
+
+        assert_eq!(
+            type_name::<Archived<UserId>>(),
+            type_name::<Archived<u64>>()
+        );
+        assert_eq!(
+            mem::size_of::<Archived<UserId>>(),
+            mem::size_of::<Archived<u64>>(),
+        );
+        assert_eq!(
+            mem::align_of::<Archived<UserId>>(),
+            mem::align_of::<Archived<u64>>(),
+        );
+
+        roundtrip_with(&UserId(42), |a, b| assert_eq!(b.to_native(), a.0));
+
+        // Struct with marker fields
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, transparent)]
+        struct Name<T> {
+            #[rkyv(transparent)]
+            value: String,
+            _phantom: PhantomData<T>,
+            _unit: (),
+        }
+
+        assert_eq!(
+            type_name::<Archived<Name<u32>>>(),
+            type_name::<ArchivedString>(),
+        );
+
+        let value = Name::<u32> {
+            value: "hello world".to_string(),
+            _phantom: PhantomData,
+            _unit: (),
+        };
+        roundtrip_with(&value, |a, b| assert_eq!(*b, a.value));
+
+        // Fields with wrappers
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, transparent)]
+        struct Shared(#[with(crate::with::AsBox)] u32);
+
+        assert_eq!(
+            type_name::<Archived<Shared>>(),
+            type_name::<ArchivedBox<ArchivedU32>>(),
+        );
+
+        roundtrip_with(&Shared(7), |a, b| assert_eq!(b.get().to_native(), a.0));
+
+        // Generic fields
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, transparent)]
+        struct Wrapper<T>(T);
+
+        assert_eq!(
+            type_name::<Archived<Wrapper<u32>>>(),
+            type_name::<ArchivedU32>(),
+        );
+        assert_eq!(
+            type_name::<Archived<Wrapper<String>>>(),
+            type_name::<ArchivedString>(),
+        );
+
+        roundtrip_with(&Wrapper(7u32), |a, b| assert_eq!(b.to_native(), a.0));
+        roundtrip_with(&Wrapper("hello".to_string()), |a, b| {
+            assert_eq!(*b, a.0)
+        });
+    }
+
+    #[test]
+    fn resolver_default() {
+        use core::marker::PhantomData;
+
+        use crate::test::roundtrip_with;
+
+        #[derive(Archive, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, derive(Debug))]
+        struct Meters(f32);
+
+        impl<S: Fallible + ?Sized> Serialize<S> for Meters {
+            fn serialize(&self, _: &mut S) -> Result<MetersResolver, S::Error> {
+                Ok(Default::default())
+            }
+        }
+
+        roundtrip_with(&Meters(1.5), |a, b| assert_eq!(b.0.to_native(), a.0));
+
+        // The resolvers of fields which mention generic parameters must
+        // implement `Default` as well.
+        #[derive(Archive)]
+        #[rkyv(crate)]
+        struct Tagged<T> {
+            value: T,
+            tag: (u8, char),
+            _phantom: PhantomData<T>,
+        }
+
+        let _ = TaggedResolver::<u32>::default();
+
+        #[derive(Archive)]
+        #[rkyv(crate)]
+        struct Marker;
+
+        let _ = MarkerResolver::default();
+    }
+
+    #[test]
+    fn unit_resolver() {
+        use core::any::type_name;
+
+        use crate::test::roundtrip_with;
+
+        #[derive(Archive, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, unit_resolver, derive(Debug))]
+        struct Rgb(u8, u8, u8);
+
+        impl<S: Fallible + ?Sized> Serialize<S> for Rgb {
+            fn serialize(&self, _: &mut S) -> Result<RgbResolver, S::Error> {
+                Ok(())
+            }
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, unit_resolver, derive(Debug))]
+        struct Pixel {
+            color: Rgb,
+            alpha: u8,
+        }
+
+        assert_eq!(type_name::<RgbResolver>(), type_name::<()>());
+        assert_eq!(type_name::<PixelResolver>(), type_name::<()>());
+
+        let value = Pixel {
+            color: Rgb(1, 2, 3),
+            alpha: 4,
+        };
+        roundtrip_with(&value, |a, b| {
+            assert_eq!(b.color.2, a.color.2);
+            assert_eq!(b.alpha, a.alpha);
+        });
+    }
+
     #[test]
     fn archive_crate_path() {
         use crate as alt_path;
@@ -0,0 +1,114 @@
+use rayon::{
+    iter::{
+        plumbing::UnindexedConsumer, IntoParallelIterator, ParallelIterator,
+    },
+    slice::Iter,
+};
+
+use crate::{
+    collections::swiss_table::{map::ParIter, ArchivedHashMap},
+    vec::ArchivedVec,
+};
+
+impl<'data, T: Sync + 'data> IntoParallelIterator for &'data ArchivedVec<T> {
+    type Iter = Iter<'data, T>;
+    type Item = &'data T;
+
+    fn into_par_iter(self) -> Self::Iter {
+        self.as_slice().into_par_iter()
+    }
+}
+
+impl<'a, K, V, H> IntoParallelIterator for &'a ArchivedHashMap<K, V, H>
+where
+    K: Sync,
+    V: Sync,
+{
+    type Iter = ParIter<'a, K, V, H>;
+    type Item = (&'a K, &'a V);
+
+    fn into_par_iter(self) -> Self::Iter {
+        ParIter { map: self }
+    }
+}
+
+impl<'a, K, V, H> ParallelIterator for ParIter<'a, K, V, H>
+where
+    K: Sync,
+    V: Sync,
+{
+    type Item = (&'a K, &'a V);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let table = self.map.raw_table();
+        (0..table.capacity())
+            .into_par_iter()
+            .filter_map(move |index| {
+                table.bucket(index).map(|entry| (&entry.key, &entry.value))
+            })
+            .drive_unindexed(consumer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rayon::prelude::*;
+
+    use crate::{
+        collections::swiss_table::ArchivedHashMap, test::to_archived,
+        vec::ArchivedVec, Archived,
+    };
+
+    #[test]
+    fn par_iter_vec() {
+        const LEN: u32 = 10_000_000;
+
+        let value = (0..LEN).collect::<Vec<u32>>();
+        to_archived(&value, |archived| {
+            let sum = archived
+                .par_iter()
+                .map(|x| u64::from(x.to_native()))
+                .sum::<u64>();
+            assert_eq!(sum, u64::from(LEN) * u64::from(LEN - 1) / 2);
+
+            let chunks = archived.par_chunks(1024).count();
+            assert_eq!(chunks, (LEN as usize + 1023) / 1024);
+        });
+    }
+
+    #[test]
+    fn par_iter_hash_map() {
+        let value = (0..10_000u32)
+            .map(|i| (i.to_string(), i))
+            .collect::<HashMap<_, _>>();
+        to_archived(&value, |archived| {
+            let evens = archived
+                .par_iter()
+                .filter(|(_, value)| value.to_native() % 2 == 0)
+                .count();
+            assert_eq!(evens, 5_000);
+
+            let mut keys = archived
+                .par_iter()
+                .map(|(key, _)| key.as_str().parse::<u32>().unwrap())
+                .collect::<Vec<_>>();
+            keys.sort_unstable();
+            assert!(keys.into_iter().eq(0..10_000));
+        });
+    }
+
+    #[test]
+    fn par_iter_send_sync() {
+        fn assert_into_par_iter<T: IntoParallelIterator>() {}
+
+        type Map = ArchivedHashMap<Archived<String>, Archived<u32>>;
+
+        assert_into_par_iter::<&ArchivedVec<Archived<u32>>>();
+        assert_into_par_iter::<&Map>();
+    }
+}
@@ -1,4 +1,4 @@
-use rancor::Fallible;
+use rancor::{Fallible, Source};
 use smallvec::{Array, SmallVec};
 
 use crate::{
@@ -24,6 +24,7 @@ where
     A: Array,
     A::Item: Serialize<S>,
     S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize(
         &self,
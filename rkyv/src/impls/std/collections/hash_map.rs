@@ -1,34 +1,30 @@
-use core::{
-    borrow::Borrow,
-    hash::{BuildHasher, Hash},
-};
+use core::hash::{BuildHasher, Hash, Hasher};
 use std::collections::HashMap;
 
 use rancor::{Fallible, Source};
 
 use crate::{
     collections::swiss_table::map::{ArchivedHashMap, HashMapResolver},
+    hash::LookupKey,
     ser::{Allocator, Writer},
-    Archive, Deserialize, Place, Serialize,
+    Archive, Deserialize, DeserializeInto, Place, Serialize,
 };
 
 impl<K, V: Archive, S> Archive for HashMap<K, V, S>
 where
     K: Archive + Hash + Eq,
-    K::Archived: Hash + Eq,
 {
     type Archived = ArchivedHashMap<K::Archived, V::Archived>;
     type Resolver = HashMapResolver;
 
     fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
-        ArchivedHashMap::resolve_from_len(self.len(), (7, 8), resolver, out);
+        ArchivedHashMap::resolve_from_map(self, resolver, out);
     }
 }
 
 impl<K, V, S, RandomState> Serialize<S> for HashMap<K, V, RandomState>
 where
     K: Serialize<S> + Hash + Eq,
-    K::Archived: Hash + Eq,
     V: Serialize<S>,
     S: Fallible + Writer + Allocator + ?Sized,
     S::Error: Source,
@@ -37,10 +33,8 @@ where
         &self,
         serializer: &mut S,
     ) -> Result<Self::Resolver, S::Error> {
-        ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_iter(
-            self.iter(),
-            (7, 8),
-            serializer,
+        ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_map(
+            self, serializer,
         )
     }
 }
@@ -49,7 +43,7 @@ impl<K, V, D, S> Deserialize<HashMap<K, V, S>, D>
     for ArchivedHashMap<K::Archived, V::Archived>
 where
     K: Archive + Hash + Eq,
-    K::Archived: Deserialize<K, D> + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
     V: Archive,
     V::Archived: Deserialize<V, D>,
     D: Fallible + ?Sized,
@@ -71,45 +65,83 @@ where
     }
 }
 
-impl<K, V, AK, AV, S> PartialEq<HashMap<K, V, S>> for ArchivedHashMap<AK, AV>
+impl<K, V, D, S> DeserializeInto<HashMap<K, V, S>, D>
+    for ArchivedHashMap<K::Archived, V::Archived>
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    D: Fallible + ?Sized,
+    S: Default + BuildHasher,
+{
+    /// The previous entries of `out` are removed, and the entries of the
+    /// archived hash map are deserialized fresh. `out` only reallocates if its
+    /// capacity is smaller than the length of the archived hash map.
+    fn deserialize_into(
+        &self,
+        out: &mut HashMap<K, V, S>,
+        deserializer: &mut D,
+    ) -> Result<(), D::Error> {
+        out.clear();
+        out.reserve(self.len());
+        for (k, v) in self.iter() {
+            out.insert(
+                k.deserialize(deserializer)?,
+                v.deserialize(deserializer)?,
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<K, V, AK, AV, H, S> PartialEq<HashMap<K, V, S>>
+    for ArchivedHashMap<AK, AV, H>
 where
-    K: Hash + Eq + Borrow<AK>,
-    AK: Hash + Eq,
+    K: LookupKey<AK>,
     AV: PartialEq<V>,
-    S: BuildHasher,
+    H: Default + Hasher,
 {
     fn eq(&self, other: &HashMap<K, V, S>) -> bool {
         if self.len() != other.len() {
             false
         } else {
-            self.iter().all(|(key, value)| {
-                other.get(key).map_or(false, |v| value.eq(v))
+            other.iter().all(|(key, value)| {
+                self.get(key).map_or(false, |v| v.eq(value))
             })
         }
     }
 }
 
-impl<K, V, AK, AV> PartialEq<ArchivedHashMap<AK, AV>> for HashMap<K, V>
+impl<K, V, AK, AV, H, S> PartialEq<ArchivedHashMap<AK, AV, H>>
+    for HashMap<K, V, S>
 where
-    K: Hash + Eq + Borrow<AK>,
-    AK: Hash + Eq,
+    K: LookupKey<AK>,
     AV: PartialEq<V>,
+    H: Default + Hasher,
 {
-    fn eq(&self, other: &ArchivedHashMap<AK, AV>) -> bool {
+    fn eq(&self, other: &ArchivedHashMap<AK, AV, H>) -> bool {
         other.eq(self)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use core::{fmt::Debug, hash::BuildHasher};
-    use std::collections::HashMap;
+    use core::{borrow::Borrow, fmt::Debug, hash::BuildHasher};
+    use std::{
+        collections::HashMap,
+        ffi::{CStr, CString},
+        rc::Rc,
+        sync::Arc,
+    };
 
     use ahash::RandomState;
+    use rancor::{Failure, Strategy};
 
     use crate::{
+        hash::{hash_value, LookupKey},
         test::{roundtrip, roundtrip_with, to_archived},
-        Archive, Archived, Deserialize, Serialize,
+        Archive, Archived, Deserialize, DeserializeInto, Serialize,
     };
 
     fn assert_equal<V, S: BuildHasher>(
@@ -132,6 +164,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn deserialize_into_reuses_capacity() {
+        let mut out = HashMap::<String, u32>::with_capacity(32);
+        out.extend((0..20).map(|i| (format!("previous {i}"), i)));
+        let capacity = out.capacity();
+
+        let mut smaller = HashMap::new();
+        smaller.insert("a".to_string(), 1);
+        smaller.insert("b".to_string(), 2);
+        to_archived(&smaller, |archived| {
+            archived
+                .deserialize_into(
+                    &mut out,
+                    Strategy::<_, Failure>::wrap(&mut ()),
+                )
+                .unwrap();
+        });
+        assert_eq!(out, smaller);
+        assert_eq!(out.capacity(), capacity);
+
+        let larger = (0..capacity as u32)
+            .map(|i| (format!("next {i}"), i))
+            .collect::<HashMap<_, _>>();
+        to_archived(&larger, |archived| {
+            archived
+                .deserialize_into(
+                    &mut out,
+                    Strategy::<_, Failure>::wrap(&mut ()),
+                )
+                .unwrap();
+        });
+        assert_eq!(out, larger);
+        assert_eq!(out.capacity(), capacity);
+    }
+
     #[test]
     fn roundtrip_empty_hash_map() {
         roundtrip(&HashMap::<i8, i32>::default());
@@ -221,4 +288,388 @@ mod tests {
             assert_eq!(get_with.as_str(), "value");
         });
     }
+
+    #[test]
+    fn get_with_non_eq_query() {
+        // The archived key type implements neither `Hash` nor `Eq`
+        #[derive(Archive, Serialize, Deserialize, Eq, Hash, PartialEq)]
+        #[rkyv(crate, check_bytes)]
+        pub struct Name(String);
+
+        // Hashes the same as `Name`, but does not implement `Eq`
+        #[derive(Hash)]
+        struct Query<'a>(&'a str);
+
+        let mut hash_map = HashMap::new();
+        hash_map.insert(Name("foo".to_string()), 1u8);
+        hash_map.insert(Name("bar".to_string()), 2u8);
+
+        to_archived(&hash_map, |archived| {
+            let cmp = |q: &Query<'_>, k: &ArchivedName| q.0 == k.0.as_str();
+            assert_eq!(archived.get_with(&Query("foo"), cmp), Some(&1));
+            assert!(archived.get_with(&Query("baz"), cmp).is_none());
+
+            let hash = hash_value(&Query("bar"));
+            let value =
+                archived.get_with_hasher(hash, |k| k.0.as_str() == "bar");
+            assert_eq!(value, Some(&2));
+            assert!(archived.get_with_hasher(hash, |_| false).is_none());
+        });
+    }
+
+    #[test]
+    fn get_with_unarchived_keys() {
+        let mut strings = HashMap::new();
+        strings.insert("foo".to_string(), 1u8);
+        strings.insert("bar".to_string(), 2);
+        to_archived(&strings, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert_eq!(archived.get(&"bar".to_string()), Some(&2));
+            assert!(archived.get("baz").is_none());
+        });
+
+        let mut bytes = HashMap::new();
+        bytes.insert(vec![1u8, 2, 3], 1u8);
+        to_archived(&bytes, |archived| {
+            assert_eq!(archived.get([1u8, 2, 3].as_slice()), Some(&1));
+            assert_eq!(archived.get(&vec![1u8, 2, 3]), Some(&1));
+            assert!(archived.get([1u8, 2].as_slice()).is_none());
+        });
+
+        let mut numbers = HashMap::new();
+        numbers.insert(5u32, 1u8);
+        numbers.insert(500_000u32, 2);
+        to_archived(&numbers, |archived| {
+            assert_eq!(archived.get(&5u32), Some(&1));
+            assert_eq!(archived.get(&500_000u32), Some(&2));
+            assert!(!archived.contains_key(&6u32));
+        });
+
+        let mut nested = HashMap::new();
+        nested.insert(vec![5u64, 6], 1u8);
+        to_archived(&nested, |archived| {
+            assert_eq!(archived.get(&vec![5u64, 6]), Some(&1));
+            assert!(archived.get([5u64].as_slice()).is_none());
+        });
+    }
+
+    #[test]
+    fn get_with_borrowed_keys() {
+        let mut boxes = HashMap::new();
+        boxes.insert(Box::<str>::from("foo"), 1u8);
+        to_archived(&boxes, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert!(archived.get("bar").is_none());
+        });
+
+        let mut c_strings = HashMap::new();
+        c_strings.insert(CString::new("foo").unwrap(), 1u8);
+        to_archived(&c_strings, |archived| {
+            let foo = CStr::from_bytes_with_nul(b"foo\0").unwrap();
+            let bar = CStr::from_bytes_with_nul(b"bar\0").unwrap();
+            assert_eq!(archived.get(foo), Some(&1));
+            assert!(archived.get(bar).is_none());
+        });
+
+        let mut rcs = HashMap::new();
+        rcs.insert(Rc::<str>::from("foo"), 1u8);
+        to_archived(&rcs, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert!(archived.get("bar").is_none());
+        });
+
+        let mut arcs = HashMap::new();
+        arcs.insert(Arc::<str>::from("foo"), 1u8);
+        to_archived(&arcs, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert!(archived.get("bar").is_none());
+        });
+    }
+
+    #[test]
+    fn get_with_custom_borrow() {
+        #[derive(Archive, Serialize, Deserialize, Eq, Hash, PartialEq)]
+        #[rkyv(crate, check_bytes)]
+        pub struct Name(String);
+
+        impl Borrow<str> for ArchivedName {
+            fn borrow(&self) -> &str {
+                self.0.as_str()
+            }
+        }
+
+        // Archived maps look keys up through `LookupKey` instead of `Borrow`
+        impl LookupKey<ArchivedName> for str {
+            fn equivalent(&self, key: &ArchivedName) -> bool {
+                self == Borrow::<str>::borrow(key)
+            }
+        }
+
+        let mut hash_map = HashMap::new();
+        hash_map.insert(Name("foo".to_string()), 1u8);
+        hash_map.insert(Name("bar".to_string()), 2u8);
+        to_archived(&hash_map, |archived| {
+            assert_eq!(archived.get("foo"), Some(&1));
+            assert_eq!(archived.get("bar"), Some(&2));
+            assert!(archived.get("baz").is_none());
+        });
+    }
+
+    #[test]
+    fn get_many_mut() {
+        let mut value = HashMap::new();
+        value.insert("a".to_string(), 1u32);
+        value.insert("b".to_string(), 2);
+        value.insert("c".to_string(), 3);
+
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let mut archived = crate::access_mut::<
+            Archived<HashMap<String, u32>>,
+            Failure,
+        >(&mut bytes)
+        .unwrap();
+
+        assert!(archived.as_mut().get_many_mut(["a", "a"]).is_none());
+        assert!(archived.as_mut().get_many_mut(["a", "d"]).is_none());
+
+        let [mut a, mut c] =
+            archived.as_mut().get_many_mut(["a", "c"]).unwrap();
+        core::mem::swap(&mut *a, &mut *c);
+
+        assert_eq!(archived.get("a").map(|v| v.to_native()), Some(3));
+        assert_eq!(archived.get("b").map(|v| v.to_native()), Some(2));
+        assert_eq!(archived.get("c").map(|v| v.to_native()), Some(1));
+    }
+
+    // The mutable iteration tests are also run by Miri with aliasing checks
+    // enabled, which is slow, so they use fewer entries there.
+    #[cfg(not(miri))]
+    const ENTRIES: u32 = 100;
+    #[cfg(miri)]
+    const ENTRIES: u32 = 16;
+
+    #[test]
+    fn iter_mut() {
+        let mut value = HashMap::new();
+        for i in 0..ENTRIES {
+            value.insert(i.to_string(), i);
+        }
+
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let mut archived = crate::access_mut::<
+            Archived<HashMap<String, u32>>,
+            Failure,
+        >(&mut bytes)
+        .unwrap();
+
+        let mut iter = archived.as_mut().iter_mut();
+        assert_eq!(iter.len(), ENTRIES as usize);
+        // Hold on to every yielded value at once to make sure that the
+        // mutable references do not alias each other.
+        let mut entries = Vec::new();
+        for (key, value) in &mut iter {
+            entries.push((key, value));
+        }
+        assert_eq!(iter.len(), 0);
+        assert!(iter.next().is_none());
+        for (key, value) in entries.iter_mut() {
+            assert_eq!(key.as_str(), value.to_native().to_string());
+            **value = (value.to_native() * 2).into();
+        }
+        drop(entries);
+
+        for (key, value) in archived.iter() {
+            let key = key.as_str().parse::<u32>().unwrap();
+            assert_eq!(value.to_native(), key * 2);
+        }
+    }
+
+    #[test]
+    fn values_mut() {
+        let mut value = HashMap::new();
+        for i in 0..ENTRIES {
+            value.insert(i, i);
+        }
+
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let mut archived = crate::access_mut::<
+            Archived<HashMap<u32, u32>>,
+            Failure,
+        >(&mut bytes)
+        .unwrap();
+
+        let mut values = archived.as_mut().values_mut().collect::<Vec<_>>();
+        assert_eq!(values.len(), ENTRIES as usize);
+        for value in values.iter_mut() {
+            **value = (value.to_native() + 1).into();
+        }
+        drop(values);
+
+        for (key, value) in archived.iter() {
+            assert_eq!(value.to_native(), key.to_native() + 1);
+        }
+        assert_eq!(archived.as_mut().values_mut().count(), ENTRIES as usize);
+    }
+
+    #[test]
+    fn iter_mut_reborrowed() {
+        let mut value = HashMap::new();
+        for i in 0..ENTRIES {
+            value.insert(i, i);
+        }
+
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let mut archived = crate::access_mut::<
+            Archived<HashMap<u32, u32>>,
+            Failure,
+        >(&mut bytes)
+        .unwrap();
+
+        // Each pass reborrows the map, and writes through the references of
+        // the previous pass must not be invalidated by the next one.
+        for pass in 1..=3 {
+            for (_, mut value) in archived.as_mut().iter_mut() {
+                *value = (value.to_native() + 1).into();
+            }
+            for mut value in archived.as_mut().values_mut() {
+                *value = (value.to_native() + 1).into();
+            }
+            for (key, value) in archived.iter() {
+                assert_eq!(value.to_native(), key.to_native() + pass * 2);
+            }
+        }
+    }
+
+    #[test]
+    fn for_each_value_mut() {
+        #[derive(Archive, Serialize)]
+        #[rkyv(crate, check_bytes)]
+        struct Account {
+            balance: u32,
+            secret: u64,
+        }
+
+        let value = (0..10_000u32)
+            .map(|i| {
+                (
+                    i,
+                    Account {
+                        balance: i * 2,
+                        secret: u64::from(i) + 1,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut bytes = crate::to_bytes::<Failure>(&value).unwrap();
+        let mut archived = crate::access_mut::<
+            Archived<HashMap<u32, Account>>,
+            Failure,
+        >(&mut bytes)
+        .unwrap();
+        archived.as_mut().for_each_value_mut(|mut account| {
+            account.secret = Archived::<u64>::from_native(0);
+        });
+
+        let archived =
+            crate::access::<Archived<HashMap<u32, Account>>, Failure>(&bytes)
+                .unwrap();
+        assert_eq!(archived.len(), 10_000);
+        for (key, account) in archived.iter() {
+            assert_eq!(account.balance.to_native(), key.to_native() * 2);
+            assert_eq!(account.secret.to_native(), 0);
+        }
+    }
+
+    #[test]
+    fn iter_send_sync() {
+        use crate::collections::swiss_table::map::{
+            Iter, IterMut, Keys, Values, ValuesMut,
+        };
+
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        type K = Archived<String>;
+        type V = Archived<u32>;
+        assert_send_sync::<Iter<'_, K, V, RandomState>>();
+        assert_send_sync::<Keys<'_, K, V, RandomState>>();
+        assert_send_sync::<Values<'_, K, V, RandomState>>();
+        assert_send_sync::<IterMut<'_, K, V, RandomState>>();
+        assert_send_sync::<ValuesMut<'_, K, V, RandomState>>();
+    }
+
+    #[test]
+    fn serialize_filtered_from_archived() {
+        use rancor::{Fallible, Source};
+
+        use crate::{
+            collections::swiss_table::map::{
+                HashMapResolver, DEFAULT_LOAD_FACTOR,
+            },
+            ser::{Allocator, Writer},
+            Place,
+        };
+
+        type Map = Archived<HashMap<String, Vec<u32>>>;
+
+        struct Filtered<'a>(&'a Map);
+
+        fn keep(key: &Archived<String>, value: &Archived<Vec<u32>>) -> bool {
+            key.len() > 1 && !value.is_empty()
+        }
+
+        impl Archive for Filtered<'_> {
+            type Archived = Map;
+            type Resolver = HashMapResolver;
+
+            fn resolve(
+                &self,
+                resolver: Self::Resolver,
+                out: Place<Self::Archived>,
+            ) {
+                Map::resolve_filtered_from_archived(
+                    self.0,
+                    keep,
+                    DEFAULT_LOAD_FACTOR,
+                    resolver,
+                    out,
+                );
+            }
+        }
+
+        impl<S> Serialize<S> for Filtered<'_>
+        where
+            S: Fallible + Writer + Allocator + ?Sized,
+            S::Error: Source,
+        {
+            fn serialize(
+                &self,
+                serializer: &mut S,
+            ) -> Result<Self::Resolver, S::Error> {
+                Map::serialize_filtered_from_archived(
+                    self.0,
+                    keep,
+                    DEFAULT_LOAD_FACTOR,
+                    serializer,
+                )
+            }
+        }
+
+        let mut value = HashMap::new();
+        value.insert("a".to_string(), vec![1]);
+        value.insert("bb".to_string(), vec![2, 3]);
+        value.insert("cc".to_string(), Vec::new());
+        value.insert("ddd".to_string(), vec![4, 5, 6]);
+
+        let mut expected = value.clone();
+        expected.retain(|key, value| key.len() > 1 && !value.is_empty());
+
+        to_archived(&value, |archived| {
+            to_archived(&Filtered(archived), |filtered| {
+                assert_equal(&expected, filtered);
+                assert!(filtered.get("a").is_none());
+                assert!(filtered.get("cc").is_none());
+            });
+        });
+    }
 }
@@ -15,24 +15,17 @@ use crate::{
 impl<K, S> Archive for HashSet<K, S>
 where
     K: Archive + Hash + Eq,
-    K::Archived: Hash + Eq,
 {
     type Archived = ArchivedHashSet<K::Archived>;
     type Resolver = HashSetResolver;
 
     fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
-        ArchivedHashSet::<K::Archived>::resolve_from_len(
-            self.len(),
-            (7, 8),
-            resolver,
-            out,
-        );
+        ArchivedHashSet::<K::Archived>::resolve_from_set(self, resolver, out);
     }
 }
 
 impl<K, S, RS> Serialize<S> for HashSet<K, RS>
 where
-    K::Archived: Hash + Eq,
     K: Serialize<S> + Hash + Eq,
     S: Fallible + Allocator + Writer + ?Sized,
     S::Error: Source,
@@ -41,18 +34,14 @@ where
         &self,
         serializer: &mut S,
     ) -> Result<Self::Resolver, S::Error> {
-        ArchivedHashSet::<K::Archived>::serialize_from_iter(
-            self.iter(),
-            (7, 8),
-            serializer,
-        )
+        ArchivedHashSet::<K::Archived>::serialize_from_set(self, serializer)
     }
 }
 
 impl<K, D, S> Deserialize<HashSet<K, S>, D> for ArchivedHashSet<K::Archived>
 where
     K: Archive + Hash + Eq,
-    K::Archived: Deserialize<K, D> + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
     D: Fallible + ?Sized,
     S: Default + BuildHasher,
 {
@@ -92,7 +81,11 @@ impl<K: Hash + Eq + Borrow<AK>, AK: Hash + Eq, S: BuildHasher>
 mod tests {
     use std::collections::HashSet;
 
-    use crate::test::{roundtrip, roundtrip_with};
+    use crate::{
+        collections::swiss_table::ArchivedHashSet,
+        string::ArchivedString,
+        test::{access, roundtrip, roundtrip_with, to_bytes},
+    };
 
     #[test]
     fn roundtrip_hash_set() {
@@ -116,6 +109,35 @@ mod tests {
         });
     }
 
+    #[test]
+    fn get_interned() {
+        let words = ["apple", "banana", "cherry", "a much longer word"];
+        let value = words
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<HashSet<_>>();
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedHashSet<ArchivedString>>(bytes, |archived| {
+                let range = bytes.as_ptr_range();
+                for word in words {
+                    let query = word.to_string();
+                    let key = archived.get(query.as_str()).unwrap();
+                    assert_eq!(key, word);
+                    let key_ptr = (key as *const ArchivedString).cast::<u8>();
+                    assert!(range.contains(&key_ptr));
+                    assert!(range.contains(&key.as_ptr()));
+
+                    let key = archived
+                        .get_with(word, |q, k| q.eq_ignore_ascii_case(k))
+                        .unwrap();
+                    assert!(range.contains(&key.as_ptr()));
+                }
+                assert!(archived.get("durian").is_none());
+            });
+        });
+    }
+
     #[test]
     fn roundtrip_hash_set_zst() {
         let mut value = HashSet::new();
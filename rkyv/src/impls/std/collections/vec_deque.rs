@@ -21,6 +21,7 @@ impl<T, S> Serialize<S> for VecDeque<T>
 where
     T: Serialize<S>,
     S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize(
         &self,
@@ -54,9 +54,11 @@ impl ArchivePointee for CStr {
 
 impl<S: Fallible + Writer + ?Sized> SerializeUnsized<S> for CStr {
     fn serialize_unsized(&self, serializer: &mut S) -> Result<usize, S::Error> {
+        serializer.enter_nested("CStr")?;
         let result = serializer.pos();
-        serializer.write(self.to_bytes_with_nul())?;
-        Ok(result)
+        let written = serializer.write(self.to_bytes_with_nul());
+        serializer.exit_nested();
+        written.map(|()| result)
     }
 }
 
@@ -3,7 +3,7 @@ use std::{
     borrow::Cow,
     collections::{HashMap, HashSet},
     ffi::{CStr, OsString},
-    hash::Hash,
+    hash::{BuildHasher, Hash},
     path::{Path, PathBuf},
     str::FromStr,
     sync::{Mutex, RwLock},
@@ -13,15 +13,21 @@ use std::{
 use rancor::{Fallible, OptionExt, ResultExt, Source};
 
 use crate::{
-    collections::util::{Entry, EntryAdapter},
+    collections::{
+        btree_map::{ArchivedBTreeMap, BTreeMapResolver},
+        swiss_table::{ArchivedHashMap, HashMapResolver},
+        symbol_table::{ArchivedSymbolMap, SymbolMapResolver},
+        util::{Entry, EntryAdapter},
+    },
     ffi::{ArchivedCString, CStringResolver},
-    ser::{Allocator, Writer},
+    ser::{Allocator, Interning, Writer},
     string::{ArchivedString, StringResolver},
     time::ArchivedDuration,
+    util::SerVec,
     vec::{ArchivedVec, VecResolver},
     with::{
-        ArchiveWith, AsOwned, AsString, AsUnixTime, AsVec, DeserializeWith,
-        Lock, SerializeWith, Unsafe,
+        ArchiveWith, AsBTreeMap, AsHashMap, AsOwned, AsString, AsUnixTime,
+        AsVec, DeserializeWith, Lock, SerializeWith, SymbolKeys, Unsafe,
     },
     Archive, Deserialize, Place, Serialize, SerializeUnsized,
 };
@@ -369,6 +375,146 @@ where
     }
 }
 
+// AsHashMap
+
+impl<K, V, RS> ArchiveWith<HashMap<K, V, RS>> for AsHashMap
+where
+    K: Archive + Hash + Eq,
+    V: Archive,
+{
+    type Archived = ArchivedHashMap<K::Archived, V::Archived>;
+    type Resolver = HashMapResolver;
+
+    fn resolve_with(
+        field: &HashMap<K, V, RS>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedHashMap::resolve_from_map(field, resolver, out);
+    }
+}
+
+impl<K, V, RS, S> SerializeWith<HashMap<K, V, RS>, S> for AsHashMap
+where
+    K: Serialize<S> + Hash + Eq,
+    V: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &HashMap<K, V, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_map(
+            field, serializer,
+        )
+    }
+}
+
+impl<K, V, RS, D>
+    DeserializeWith<
+        ArchivedHashMap<K::Archived, V::Archived>,
+        HashMap<K, V, RS>,
+        D,
+    > for AsHashMap
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    RS: Default + BuildHasher,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedHashMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<K, V, RS>, D::Error> {
+        field.deserialize(deserializer)
+    }
+}
+
+// AsBTreeMap
+
+impl<K, V, RS> ArchiveWith<HashMap<K, V, RS>> for AsBTreeMap
+where
+    K: Archive + Ord,
+    K::Archived: Ord,
+    V: Archive,
+{
+    type Archived = ArchivedBTreeMap<K::Archived, V::Archived>;
+    type Resolver = BTreeMapResolver;
+
+    fn resolve_with(
+        field: &HashMap<K, V, RS>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedBTreeMap::resolve_from_len(field.len(), resolver, out);
+    }
+}
+
+impl<K, V, RS, S> SerializeWith<HashMap<K, V, RS>, S> for AsBTreeMap
+where
+    K: Serialize<S> + Ord,
+    K::Archived: Ord,
+    V: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &HashMap<K, V, RS>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        // B-tree maps must be serialized in order, so the entries are sorted
+        // by key first.
+        SerVec::with_capacity(
+            serializer,
+            field.len(),
+            |entries, serializer| {
+                for entry in field.iter() {
+                    entries.push(entry);
+                }
+                entries.as_mut_slice().sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+                ArchivedBTreeMap::<_, _>::serialize_from_ordered_iter(
+                    entries.as_slice().iter().map(|&(key, value)| (key, value)),
+                    serializer,
+                )
+            },
+        )?
+    }
+}
+
+impl<K, V, RS, D>
+    DeserializeWith<
+        ArchivedBTreeMap<K::Archived, V::Archived>,
+        HashMap<K, V, RS>,
+        D,
+    > for AsBTreeMap
+where
+    K: Archive + Hash + Eq,
+    K::Archived: Deserialize<K, D>,
+    V: Archive,
+    V::Archived: Deserialize<V, D>,
+    RS: Default + BuildHasher,
+    D: Fallible + ?Sized,
+{
+    fn deserialize_with(
+        field: &ArchivedBTreeMap<K::Archived, V::Archived>,
+        deserializer: &mut D,
+    ) -> Result<HashMap<K, V, RS>, D::Error> {
+        let mut result =
+            HashMap::with_capacity_and_hasher(field.len(), RS::default());
+        for (key, value) in field.iter() {
+            result.insert(
+                key.deserialize(deserializer)?,
+                value.deserialize(deserializer)?,
+            );
+        }
+        Ok(result)
+    }
+}
+
 // UnixTimestamp
 
 impl ArchiveWith<SystemTime> for AsUnixTime {
@@ -454,9 +600,42 @@ where
     }
 }
 
+// SymbolKeys
+
+impl<V: Archive> ArchiveWith<HashMap<String, V>> for SymbolKeys {
+    type Archived = ArchivedSymbolMap<V::Archived>;
+    type Resolver = SymbolMapResolver;
+
+    fn resolve_with(
+        field: &HashMap<String, V>,
+        resolver: Self::Resolver,
+        out: Place<Self::Archived>,
+    ) {
+        ArchivedSymbolMap::resolve_from_len(field.len(), resolver, out);
+    }
+}
+
+impl<V, S> SerializeWith<HashMap<String, V>, S> for SymbolKeys
+where
+    V: Serialize<S>,
+    S: Fallible + Interning + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize_with(
+        field: &HashMap<String, V>,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedSymbolMap::serialize_from_iter(
+            field.iter().map(|(key, value)| (key.as_str(), value)),
+            serializer,
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
+        collections::{BTreeMap, HashMap},
         ffi::OsString,
         path::PathBuf,
         sync::{Mutex, RwLock},
@@ -464,8 +643,8 @@ mod tests {
 
     use crate::{
         test::roundtrip_with,
-        with::{AsString, Lock, Unsafe},
-        Archive, Deserialize, Serialize,
+        with::{AsBTreeMap, AsHashMap, AsString, Lock, Unsafe},
+        Archive, Archived, Deserialize, Serialize,
     };
 
     #[test]
@@ -561,4 +740,64 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn roundtrip_map_containers() {
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, derive(Debug))]
+        struct Test {
+            #[with(AsHashMap)]
+            hash_as_hash: HashMap<u32, String>,
+            #[with(AsBTreeMap)]
+            hash_as_btree: HashMap<u32, String>,
+            #[with(AsHashMap)]
+            btree_as_hash: BTreeMap<u32, String>,
+            #[with(AsBTreeMap)]
+            btree_as_btree: BTreeMap<u32, String>,
+        }
+
+        fn archived(key: u32) -> Archived<u32> {
+            Archived::<u32>::from_native(key)
+        }
+
+        let keys = [5, 3, 9, 1, 7, 2, 8];
+        let entries = || keys.iter().map(|&k| (k, k.to_string()));
+        let value = Test {
+            hash_as_hash: entries().collect(),
+            hash_as_btree: entries().collect(),
+            btree_as_hash: entries().collect(),
+            btree_as_btree: entries().collect(),
+        };
+
+        roundtrip_with(&value, |_, b| {
+            let mut sorted = keys;
+            sorted.sort();
+
+            // B-tree maps iterate in order by key, whatever the type of the
+            // field.
+            for map in [&b.hash_as_btree, &b.btree_as_btree] {
+                let archived_keys =
+                    map.keys().map(|k| k.to_native()).collect::<Vec<_>>();
+                assert_eq!(archived_keys, sorted);
+                for &key in keys.iter() {
+                    assert_eq!(
+                        map.get(&archived(key)).unwrap(),
+                        &key.to_string()
+                    );
+                }
+                assert!(map.get(&archived(4)).is_none());
+            }
+
+            for map in [&b.hash_as_hash, &b.btree_as_hash] {
+                assert_eq!(map.len(), keys.len());
+                for &key in keys.iter() {
+                    assert_eq!(
+                        map.get(&archived(key)).unwrap(),
+                        &key.to_string()
+                    );
+                }
+                assert!(map.get(&archived(4)).is_none());
+            }
+        });
+    }
 }
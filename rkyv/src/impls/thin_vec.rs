@@ -1,4 +1,4 @@
-use rancor::Fallible;
+use rancor::{Fallible, Source};
 use thin_vec::ThinVec;
 
 use crate::{
@@ -23,6 +23,7 @@ impl<T, S> Serialize<S> for ThinVec<T>
 where
     T: Serialize<S>,
     S: Allocator + Writer + Fallible + ?Sized,
+    S::Error: Source,
 {
     fn serialize(
         &self,
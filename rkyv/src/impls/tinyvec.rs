@@ -1,4 +1,4 @@
-use rancor::Fallible;
+use rancor::{Fallible, Source};
 #[cfg(all(feature = "tinyvec", feature = "alloc"))]
 use tinyvec::TinyVec;
 use tinyvec::{Array, ArrayVec, SliceVec};
@@ -28,6 +28,7 @@ where
     A: Array,
     A::Item: Serialize<S>,
     S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize(
         &self,
@@ -71,6 +72,7 @@ impl<'s, T, S> Serialize<S> for SliceVec<'s, T>
 where
     T: Serialize<S>,
     S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize(
         &self,
@@ -103,6 +105,7 @@ where
     A: Array,
     A::Item: Serialize<S>,
     S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
 {
     fn serialize(
         &self,
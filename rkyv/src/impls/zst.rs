@@ -0,0 +1,171 @@
+//! Checks that zero-sized types can be archived in every collection, and that
+//! archives which claim huge numbers of them are validated quickly.
+
+use core::{marker::PhantomData, mem::size_of};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+use crate::{
+    primitive::{ArchivedUsize, FixedUsize},
+    test::{access, roundtrip, roundtrip_with, to_bytes},
+    util::AlignedVec,
+    vec::ArchivedVec,
+    with::Niche,
+    Archive, Deserialize, Serialize,
+};
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+#[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+struct Marker;
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+#[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+struct AllZst {
+    unit: (),
+    marker: Marker,
+    phantom: PhantomData<u32>,
+}
+
+const ALL_ZST: AllZst = AllZst {
+    unit: (),
+    marker: Marker,
+    phantom: PhantomData,
+};
+
+#[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+#[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+enum Tagged {
+    Empty,
+    Unit(()),
+    Fields { marker: Marker, all: AllZst },
+}
+
+#[test]
+fn derived_zsts_are_zero_sized() {
+    assert_eq!(size_of::<ArchivedMarker>(), 0);
+    assert_eq!(size_of::<ArchivedAllZst>(), 0);
+    assert_eq!(size_of::<ArchivedTagged>(), 1);
+
+    to_bytes(&ALL_ZST, |bytes| assert!(bytes.is_empty()));
+    roundtrip(&Marker);
+    roundtrip(&ALL_ZST);
+    roundtrip(&Tagged::Empty);
+    roundtrip(&Tagged::Unit(()));
+    roundtrip(&Tagged::Fields {
+        marker: Marker,
+        all: ALL_ZST,
+    });
+}
+
+#[test]
+fn vecs_of_zsts() {
+    // Only the vec itself is written, no matter how many elements it has.
+    to_bytes(&vec![(); 1000], |bytes| {
+        assert_eq!(bytes.len(), size_of::<ArchivedVec<()>>());
+    });
+
+    roundtrip(&Vec::<()>::new());
+    roundtrip(&vec![(); 1000]);
+    roundtrip(&vec![Marker, Marker, Marker]);
+    roundtrip(&(0..100).map(|_| ALL_ZST).collect::<Vec<_>>());
+    roundtrip(&vec![vec![(); 3], Vec::new(), vec![(); 5]]);
+    roundtrip(&VecDeque::from(vec![(); 10]));
+    roundtrip(&vec![Tagged::Empty, Tagged::Unit(())]);
+}
+
+#[test]
+fn boxed_zsts() {
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, derive(Debug))]
+    struct Boxes {
+        unit: Box<()>,
+        all: Box<AllZst>,
+        slice: Box<[Marker]>,
+        #[with(Niche)]
+        niched: Option<Box<()>>,
+    }
+
+    roundtrip(&Box::new(()));
+    roundtrip(&Box::new(ALL_ZST));
+    roundtrip(&vec![Box::new(()), Box::new(())]);
+    roundtrip_with(&Some(Box::new(())), |_, archived| {
+        assert!(archived.is_some());
+    });
+    roundtrip_with(
+        &Boxes {
+            unit: Box::new(()),
+            all: Box::new(ALL_ZST),
+            slice: vec![Marker, Marker].into_boxed_slice(),
+            niched: Some(Box::new(())),
+        },
+        |value, archived| {
+            assert_eq!(archived.slice.len(), 2);
+            assert_eq!(archived.niched, value.niched);
+        },
+    );
+    roundtrip_with(
+        &Boxes {
+            unit: Box::new(()),
+            all: Box::new(ALL_ZST),
+            slice: Box::new([]),
+            niched: None,
+        },
+        |_, archived| assert!(archived.niched.is_none()),
+    );
+}
+
+#[test]
+fn maps_and_sets_of_zsts() {
+    let map = (0..100u32).map(|i| (i, ())).collect::<HashMap<_, _>>();
+    roundtrip_with(&map, |value, archived| {
+        assert_eq!(archived.len(), value.len());
+        for key in value.keys() {
+            assert!(archived.contains_key(key));
+        }
+        assert!(!archived.contains_key(&100));
+    });
+
+    let mut map = HashMap::new();
+    map.insert((), ());
+    roundtrip(&map);
+    roundtrip(&HashMap::<(), ()>::new());
+    roundtrip_with(
+        &(0..10u32).map(|i| (i, Marker)).collect::<HashMap<_, _>>(),
+        |value, archived| assert_eq!(archived.len(), value.len()),
+    );
+
+    let mut set = HashSet::new();
+    set.insert(());
+    roundtrip(&set);
+    roundtrip(&HashSet::<()>::new());
+
+    roundtrip(&(0..100u32).map(|i| (i, ())).collect::<BTreeMap<_, _>>());
+    roundtrip(&[((), ())].into_iter().collect::<BTreeMap<_, _>>());
+    roundtrip(&[()].into_iter().collect::<BTreeSet<_>>());
+}
+
+#[test]
+fn huge_lengths_of_zsts() {
+    // Claim the largest possible number of elements by overwriting the length
+    // of the archived vec, which is stored at the end of the buffer.
+    fn with_max_len(len: usize, f: impl FnOnce(&[u8])) {
+        to_bytes(&vec![(); len], |bytes| {
+            let mut bytes_mut = AlignedVec::<16>::new();
+            bytes_mut.extend_from_slice(bytes);
+            let len_start = bytes_mut.len() - size_of::<ArchivedUsize>();
+            bytes_mut[len_start..].fill(0xff);
+            f(&bytes_mut);
+        });
+    }
+
+    with_max_len(4, |bytes| {
+        access::<ArchivedVec<()>>(bytes, |archived| {
+            assert_eq!(archived.len(), FixedUsize::MAX as usize);
+            assert!(archived.first().is_some());
+        });
+    });
+    with_max_len(0, |bytes| {
+        access::<ArchivedVec<()>>(bytes, |archived| {
+            assert_eq!(archived.len(), FixedUsize::MAX as usize);
+        });
+    });
+}
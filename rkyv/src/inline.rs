@@ -0,0 +1,497 @@
+//! Archived strings and vectors with fixed capacities.
+//!
+//! These types store their contents inline instead of behind a relative
+//! pointer, so they are always exactly as large as their capacity. They're the
+//! archived forms of fixed-capacity collections like
+//! [`heapless::String`](https://docs.rs/heapless) and can also be used through
+//! the [`AsInlineString`](crate::with::AsInlineString) wrapper.
+
+use core::{
+    borrow::Borrow,
+    cmp, fmt, hash,
+    mem::{size_of, MaybeUninit},
+    ops::Deref,
+    ptr, slice, str,
+};
+
+use munge::munge;
+
+use crate::{
+    primitive::{usize_from_archived, ArchivedUsize, FixedUsize},
+    Archive, Place, Portable,
+};
+
+/// An error which occurs when a value is too long for its fixed capacity.
+#[derive(Debug)]
+pub struct CapacityExceeded {
+    /// The length of the value.
+    pub len: usize,
+    /// The capacity which the value exceeded.
+    pub capacity: usize,
+}
+
+impl fmt::Display for CapacityExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "length {} exceeds the fixed capacity of {}",
+            self.len, self.capacity,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityExceeded {}
+
+/// An archived string with a fixed capacity of `N` bytes.
+///
+/// The bytes of the string are stored inline after a length byte, so `N` may
+/// be at most 255. Using a larger capacity is a compile-time error.
+#[derive(Portable)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+#[rkyv(crate)]
+#[repr(C)]
+pub struct ArchivedInlineString<const N: usize> {
+    len: u8,
+    bytes: [u8; N],
+}
+
+impl<const N: usize> ArchivedInlineString<N> {
+    /// The maximum length of the string in bytes.
+    pub const CAPACITY: usize = N;
+
+    const CAPACITY_FITS_IN_LEN: () = {
+        if N > u8::MAX as usize {
+            panic!(
+                "the capacity of an `ArchivedInlineString` must be at most \
+                 255 bytes"
+            );
+        }
+    };
+
+    /// Returns the length of the string in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        // Clamping the length keeps corrupted archives from reading out of
+        // bounds.
+        usize::min(self.len as usize, N)
+    }
+
+    /// Returns whether the string is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the archived length is greater than the capacity.
+    ///
+    /// This can only be true if the archive was accessed without validation.
+    #[inline]
+    pub fn exceeds_capacity(&self) -> bool {
+        self.len as usize > N
+    }
+
+    /// Extracts a string slice containing the entire `ArchivedInlineString`.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        // SAFETY: The first `len` bytes of an archived inline string are
+        // always valid UTF-8.
+        unsafe { str::from_utf8_unchecked(&self.bytes[..self.len()]) }
+    }
+
+    /// Resolves an archived inline string from a given `str`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is longer than `N` bytes.
+    pub fn resolve_from_str(value: &str, out: Place<Self>) {
+        let () = Self::CAPACITY_FITS_IN_LEN;
+
+        assert!(
+            value.len() <= N,
+            "string of length {} does not fit in an inline string with \
+             capacity {}",
+            value.len(),
+            N,
+        );
+
+        munge!(let ArchivedInlineString { len, bytes } = out);
+        len.write(value.len() as u8);
+        let mut inline = [0; N];
+        inline[..value.len()].copy_from_slice(value.as_bytes());
+        bytes.write(inline);
+    }
+}
+
+impl<const N: usize> AsRef<str> for ArchivedInlineString<N> {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> Borrow<str> for ArchivedInlineString<N> {
+    #[inline]
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Debug for ArchivedInlineString<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> Deref for ArchivedInlineString<N> {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<const N: usize> fmt::Display for ArchivedInlineString<N> {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+impl<const N: usize> Eq for ArchivedInlineString<N> {}
+
+impl<const N: usize> hash::Hash for ArchivedInlineString<N> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl<const N: usize> Ord for ArchivedInlineString<N> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl<const N: usize> PartialEq for ArchivedInlineString<N> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<const N: usize> PartialOrd for ArchivedInlineString<N> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const N: usize> PartialEq<&str> for ArchivedInlineString<N> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<const N: usize> PartialEq<str> for ArchivedInlineString<N> {
+    #[inline]
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<const N: usize> PartialEq<ArchivedInlineString<N>> for &str {
+    #[inline]
+    fn eq(&self, other: &ArchivedInlineString<N>) -> bool {
+        other == self
+    }
+}
+
+impl<const N: usize> PartialEq<ArchivedInlineString<N>> for str {
+    #[inline]
+    fn eq(&self, other: &ArchivedInlineString<N>) -> bool {
+        other == self
+    }
+}
+
+/// An archived vector with a fixed capacity of `N` items.
+///
+/// The items are stored inline after the length, and the unused capacity is
+/// zeroed.
+#[derive(Portable)]
+#[rkyv(crate)]
+#[repr(C)]
+pub struct ArchivedInlineVec<T, const N: usize> {
+    len: ArchivedUsize,
+    items: [MaybeUninit<T>; N],
+}
+
+impl<T, const N: usize> ArchivedInlineVec<T, N> {
+    /// The maximum number of items in the vector.
+    pub const CAPACITY: usize = N;
+
+    /// Returns the number of items in the vector.
+    #[inline]
+    pub fn len(&self) -> usize {
+        // Clamping the length keeps corrupted archives from reading out of
+        // bounds.
+        usize::min(usize_from_archived(self.len), N)
+    }
+
+    /// Returns whether the vector is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns whether the archived length is greater than the capacity.
+    ///
+    /// This can only be true if the archive was accessed without validation.
+    #[inline]
+    pub fn exceeds_capacity(&self) -> bool {
+        usize_from_archived(self.len) > N
+    }
+
+    /// Gets the items of the archived inline vector as a slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: The first `len` items of an archived inline vector are always
+        // initialized.
+        unsafe {
+            slice::from_raw_parts(self.items.as_ptr().cast::<T>(), self.len())
+        }
+    }
+
+    /// Resolves an archived inline vector from a slice of values and their
+    /// resolvers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slice` has more than `N` items, or if `resolvers` yields
+    /// fewer items than `slice`.
+    pub fn resolve_from_slice<U, I>(slice: &[U], resolvers: I, out: Place<Self>)
+    where
+        U: Archive<Archived = T>,
+        I: IntoIterator<Item = U::Resolver>,
+    {
+        assert!(
+            slice.len() <= N,
+            "slice of length {} does not fit in an inline vector with \
+             capacity {}",
+            slice.len(),
+            N,
+        );
+
+        munge!(let ArchivedInlineVec { len, items } = out);
+        len.write(ArchivedUsize::from_native(slice.len() as FixedUsize));
+
+        let mut resolvers = resolvers.into_iter();
+        for (i, value) in slice.iter().enumerate() {
+            let resolver = resolvers
+                .next()
+                .expect("missing resolver for item of inline vector");
+            // SAFETY: `i` is less than `slice.len()`, which is at most `N`.
+            let out_item = unsafe { items.index(i).cast_unchecked() };
+            value.resolve(resolver, out_item);
+        }
+
+        // Zero the unused capacity so that the output is deterministic.
+        // SAFETY: The items after the first `slice.len()` are in-bounds of
+        // `items`, and may be overwritten because they are `MaybeUninit`.
+        unsafe {
+            ptr::write_bytes(
+                items.ptr().cast::<T>().add(slice.len()).cast::<u8>(),
+                0,
+                (N - slice.len()) * size_of::<T>(),
+            );
+        }
+    }
+}
+
+impl<T, const N: usize> AsRef<[T]> for ArchivedInlineVec<T, N> {
+    #[inline]
+    fn as_ref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, const N: usize> Borrow<[T]> for ArchivedInlineVec<T, N> {
+    #[inline]
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for ArchivedInlineVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl<T, const N: usize> Deref for ArchivedInlineVec<T, N> {
+    type Target = [T];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.as_slice()
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for ArchivedInlineVec<T, N> {}
+
+impl<T: hash::Hash, const N: usize> hash::Hash for ArchivedInlineVec<T, N> {
+    fn hash<H: hash::Hasher>(&self, state: &mut H) {
+        self.as_slice().hash(state)
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a ArchivedInlineVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter()
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize, const M: usize>
+    PartialEq<ArchivedInlineVec<U, M>> for ArchivedInlineVec<T, N>
+{
+    #[inline]
+    fn eq(&self, other: &ArchivedInlineVec<U, M>) -> bool {
+        self.as_slice().eq(other.as_slice())
+    }
+}
+
+impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U]>
+    for ArchivedInlineVec<T, N>
+{
+    #[inline]
+    fn eq(&self, other: &[U]) -> bool {
+        self.as_slice().eq(other)
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::{ptr::addr_of, str};
+
+    use bytecheck::{CheckBytes, Verify};
+    use rancor::{fail, Fallible, Source};
+
+    use super::{ArchivedInlineString, ArchivedInlineVec, CapacityExceeded};
+    use crate::primitive::{usize_from_archived, ArchivedUsize};
+
+    unsafe impl<C, const N: usize> Verify<C> for ArchivedInlineString<N>
+    where
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+            if self.exceeds_capacity() {
+                fail!(CapacityExceeded {
+                    len: self.len as usize,
+                    capacity: N,
+                });
+            }
+
+            match str::from_utf8(&self.bytes[..self.len()]) {
+                Ok(_) => Ok(()),
+                Err(e) => fail!(e),
+            }
+        }
+    }
+
+    unsafe impl<T, C, const N: usize> CheckBytes<C> for ArchivedInlineVec<T, N>
+    where
+        T: CheckBytes<C>,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        unsafe fn check_bytes(
+            value: *const Self,
+            context: &mut C,
+        ) -> Result<(), C::Error> {
+            // SAFETY: The caller has guaranteed that `value` is aligned and
+            // points to enough bytes for `Self`.
+            let len = unsafe { addr_of!((*value).len) };
+            unsafe {
+                ArchivedUsize::check_bytes(len, context)?;
+            }
+            // SAFETY: We just checked that `len` is a valid `ArchivedUsize`.
+            let len = usize_from_archived(unsafe { *len });
+            if len > N {
+                fail!(CapacityExceeded { len, capacity: N });
+            }
+
+            let items = unsafe { addr_of!((*value).items).cast::<T>() };
+            for i in 0..len {
+                // SAFETY: `i` is less than `len`, which is at most `N`, so
+                // this points to one of the items of `value`.
+                unsafe {
+                    T::check_bytes(items.add(i), context)?;
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::string::{String, ToString};
+
+    use rancor::Failure;
+
+    use super::ArchivedInlineString;
+    use crate::{
+        access, test::roundtrip_with, to_bytes, util::Align,
+        with::AsInlineString, Archive, Deserialize, Serialize,
+    };
+
+    #[test]
+    fn reject_invalid_strings() {
+        let valid = Align([3, b'a', b'b', b'c', 0]);
+        let archived =
+            access::<ArchivedInlineString<4>, Failure>(&valid.0).unwrap();
+        assert_eq!(archived, "abc");
+
+        // Longer than the capacity
+        let too_long = Align([5, b'a', b'b', b'c', b'd']);
+        access::<ArchivedInlineString<4>, Failure>(&too_long.0).unwrap_err();
+
+        // Invalid UTF-8
+        let invalid = Align([2, b'a', 0xff, 0, 0]);
+        access::<ArchivedInlineString<4>, Failure>(&invalid.0).unwrap_err();
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, derive(Debug))]
+    struct Sensor {
+        #[with(AsInlineString<8>)]
+        id: String,
+    }
+
+    #[test]
+    fn with_wrapper() {
+        let value = Sensor {
+            id: "probe-01".to_string(),
+        };
+        roundtrip_with(&value, |a, b| assert_eq!(b.id, a.id.as_str()));
+
+        let value = Sensor {
+            id: "too long for an id".to_string(),
+        };
+        to_bytes::<Failure>(&value).unwrap_err();
+    }
+}
@@ -59,6 +59,10 @@
 //! build these features on top of rkyv. You can use other serialization
 //! frameworks like serde with the same types as rkyv conflict-free.
 //!
+//! Structs which only need to gain new fields over time can opt into an
+//! [`extensible`] layout with `#[rkyv(extensible)]`, which allows newer
+//! versions to read archives written by older versions and vice versa.
+//!
 //! ## Features
 //!
 //! - `alloc`: Enables types that require the `alloc` crate. Enabled by default.
@@ -76,7 +80,52 @@
 //!   intended to be used only for very large archives and may cause unnecessary
 //!   data bloat.
 //! - `std`: Enables standard library support. Enabled by default.
-//! - `bytecheck`: Enables validation support through `bytecheck`.
+//! - `locked_arena`: Guards the global arena allocator used without `std` with
+//!   a spinlock, so that threads serializing at the same time share one arena
+//!   instead of allocating their own. See [`with_arena`](util::with_arena).
+//! - `critical-section`: Acquires the `locked_arena` spinlock inside a critical
+//!   section from the [`critical-section`](https://docs.rs/critical-section)
+//!   crate, for targets without atomic compare-and-swap. The final binary must
+//!   provide a critical section implementation. Implies `locked_arena`.
+//! - `strict_alignment`: Panics in release builds as well as debug builds when
+//!   [`access_unchecked`] and related functions are called with a misaligned
+//!   buffer. Use [`access_or_copy_unchecked`](util::access_or_copy_unchecked)
+//!   for buffers which may not be aligned.
+//! - `bytecheck`: Enables validation support through `bytecheck`. Without
+//!   `alloc`, [`access`] tracks shared pointers in a fixed-size buffer on the
+//!   stack, and [`access_with_buffer`] can be used to provide a larger one.
+//!   [`from_bytes`] requires `alloc`.
+//! - `checksum`: Enables the [`checksum`] module, which frames archives with a
+//!   checksum trailer to detect torn writes. Implies `alloc` and `bytecheck`.
+//! - `xxh3`: Enables XXH3 as a checksum algorithm. Implies `checksum`.
+//! - `lz4_flex` and `zstd`: Enable a
+//!   [`CompressedWriter`](ser::writer::CompressedWriter) for the compression
+//!   format and helpers to decompress archives into an aligned buffer. Implies
+//!   `std`.
+//! - `shm`: Enables the [`shm`] module on Unix platforms, which shares archives
+//!   between processes through named shared memory mappings. Implies `std` and
+//!   `bytecheck`.
+//! - `cache`: Enables the [`cache`] module, which atomically writes archives to
+//!   cache files and memory-maps them back. Implies `std` and `checksum`.
+//! - `testing`: Enables the [`testing`] module, which provides test harnesses
+//!   for roundtripping and corrupting archived types. Implies `alloc` and
+//!   `bytecheck`.
+//! - `test_endian`: Enables the [`test_endian`] module, which provides golden
+//!   byte fixtures and harnesses for checking that archives don't depend on the
+//!   byte order of the host. Implies `testing`.
+//! - `golden`: Enables the [`golden`] module, [`assert_archive_snapshot!`], and
+//!   [`assert_archive_snapshot_diff!`], which check archives against snapshot
+//!   files to catch changes to their layout. Implies `std` and `testing`.
+//! - `ffi_describe`: Enables [`ffi::write_header`], which generates C headers
+//!   for archived structs with `#[rkyv(derive_describe)]`. Implies `std`.
+//! - `arrow`: Enables the [`arrow`] module, which exports archived numeric
+//!   vectors as Arrow buffers and arrays without copying them. Implies `std`.
+//! - `compat_0_7`: Enables the [`compat_0_7`] module, which provides deprecated
+//!   shims with the names of the 0.7 API to help migrate to the 0.8 API
+//!   gradually.
+//! - `validate_embedded`: Validates archives embedded with [`include_archive!`]
+//!   in release builds as well as debug builds. Implies `alloc` and
+//!   `bytecheck`.
 //!
 //! ## Crate support
 //!
@@ -88,7 +137,10 @@
 //!
 //! Crates supported by rkyv:
 //!
+//! - [`allocator-api2`](https://docs.rs/allocator-api2)
+//! - [`heapless`](https://docs.rs/heapless)
 //! - [`indexmap`](https://docs.rs/indexmap)
+//! - [`rayon`](https://docs.rs/rayon)
 //! - [`rend`](https://docs.rs/rend) *Enabled automatically when using
 //!   endian-specific archive features.*
 //! - [`tinyvec`](https://docs.rs/tinyvec)
@@ -153,36 +205,79 @@ pub use ::rkyv_derive::{Archive, Deserialize, Portable, Serialize};
 mod alias;
 #[macro_use]
 mod _macros;
+#[cfg(feature = "arrow")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arrow")))]
+pub mod arrow;
 #[cfg(feature = "bitvec")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bitvec")))]
 pub mod bitvec;
+pub mod bounded;
 pub mod boxed;
+#[cfg(feature = "cache")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cache")))]
+pub mod cache;
+#[cfg(feature = "checksum")]
+#[cfg_attr(docsrs, doc(cfg(feature = "checksum")))]
+pub mod checksum;
 pub mod collections;
+pub mod column;
+#[cfg(feature = "compat_0_7")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compat_0_7")))]
+pub mod compat_0_7;
+pub mod cow;
 pub mod de;
-mod fmt;
+#[cfg(feature = "alloc")]
+#[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+pub mod diff;
+pub mod discriminant;
+pub mod extensible;
+pub mod extent;
+pub mod fmt;
 // This is pretty unfortunate. CStr doesn't rely on the rest of std, but it's
 // not in core. If CStr ever gets moved into `core` then this module will no
 // longer need cfg(feature = "std")
 #[cfg(feature = "std")]
 #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
 pub mod ffi;
+#[cfg(feature = "golden")]
+#[cfg_attr(docsrs, doc(cfg(feature = "golden")))]
+pub mod golden;
 pub mod hash;
 mod impls;
+pub mod inline;
+#[cfg(all(feature = "std", feature = "bytecheck"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "std", feature = "bytecheck"))))]
+pub mod log;
+pub mod nested;
 pub mod net;
 pub mod niche;
+pub mod nullable;
 pub mod ops;
 pub mod option;
 pub mod place;
 mod polyfill;
 pub mod primitive;
 pub mod rc;
+#[cfg(all(feature = "alloc", feature = "bytecheck"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "alloc", feature = "bytecheck"))))]
+pub mod registry;
 pub mod rel_ptr;
 pub mod result;
 pub mod ser;
+#[cfg(all(feature = "shm", unix))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "shm", unix))))]
+pub mod shm;
 mod simd;
+pub mod soa;
 pub mod string;
 #[cfg(test)]
 mod test;
+#[cfg(feature = "test_endian")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test_endian")))]
+pub mod test_endian;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
 pub mod time;
 pub mod traits;
 pub mod tuple;
@@ -195,22 +290,35 @@ pub mod with;
 
 // Exports
 
+#[cfg(feature = "checksum")]
+#[doc(inline)]
+pub use checksum::{access_checksummed, to_bytes_checksummed};
+#[cfg(feature = "allocator-api2")]
+#[doc(inline)]
+pub use util::to_bytes_in_alloc;
 #[cfg(feature = "alloc")]
 #[doc(inline)]
-pub use util::{from_bytes_unchecked, to_bytes, to_bytes_in};
+pub use util::{
+    from_bytes_unchecked, to_bytes, to_bytes_in, to_bytes_padded_to,
+};
 #[cfg(all(feature = "bytecheck", feature = "alloc"))]
 #[doc(inline)]
 pub use validation::util::from_bytes;
 #[cfg(feature = "bytecheck")]
 #[doc(inline)]
-pub use validation::util::{access, access_mut};
+pub use validation::util::{
+    access, access_mut, access_with_budget, access_with_buffer,
+};
 
 #[doc(inline)]
 pub use crate::{
     alias::*,
     place::Place,
     traits::*,
-    util::{access_unchecked, access_unchecked_mut, deserialize, serialize},
+    util::{
+        access_unchecked, access_unchecked_const, access_unchecked_mut,
+        deserialize, deserialize_into, serialize,
+    },
 };
 
 // Check endianness feature flag settings
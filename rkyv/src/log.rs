@@ -0,0 +1,500 @@
+//! Append-only logs of archived records.
+//!
+//! [`ArchiveLogWriter`] appends archives to a [`Write`] one record at a time,
+//! and [`ArchiveLogReader`] reads them back from the log bytes with checked
+//! access.
+//!
+//! # Record layout
+//!
+//! Each record starts at a multiple of [`RECORD_ALIGN`] bytes from the start of
+//! the log. Any bytes between the end of one record and the start of the next
+//! are zero padding. A record consists of a [`HEADER_LEN`]-byte header followed
+//! by the archive bytes:
+//!
+//! | Offset | Size | Contents                                                |
+//! |--------|------|---------------------------------------------------------|
+//! | 0      | 4    | The magic bytes [`RECORD_MAGIC`].                       |
+//! | 4      | 4    | Reserved, must be zero.                                 |
+//! | 8      | 8    | The length of the archive bytes, as a little-endian     |
+//! |        |      | `u64`.                                                  |
+//!
+//! Because the header length is a multiple of [`RECORD_ALIGN`], the archive
+//! bytes of every record are aligned as long as the log bytes are. Load the log
+//! into an [`AlignedVec`] or memory map it before reading it.
+//!
+//! # Torn writes
+//!
+//! If the process writing the log crashes in the middle of an append, the log
+//! may end with an incomplete record. The reader stops cleanly before a final
+//! record whose header or archive bytes are incomplete, and
+//! [`ArchiveLogReader::pos`] reports where the last complete record ended.
+//! Truncate the log to that length before appending to it again.
+//!
+//! # Examples
+//!
+//! ```
+//! use rkyv::{
+//!     log::{ArchiveLogReader, ArchiveLogWriter},
+//!     rancor::Error,
+//!     util::AlignedVec,
+//! };
+//!
+//! let mut writer = ArchiveLogWriter::new(Vec::new());
+//! writer.append::<_, Error>(&"hello".to_string()).unwrap();
+//! writer.append::<_, Error>(&"world".to_string()).unwrap();
+//!
+//! let mut bytes = AlignedVec::<16>::new();
+//! bytes.extend_from_slice(&writer.into_inner());
+//!
+//! let mut reader = ArchiveLogReader::new(&bytes);
+//! let records = reader
+//!     .iter::<String, Error>()
+//!     .map(|record| record.unwrap().0.as_str())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(records, ["hello", "world"]);
+//! assert_eq!(reader.pos(), bytes.len());
+//! ```
+
+use core::{fmt, marker::PhantomData, ops::Range};
+use std::io::Write;
+
+use bytecheck::CheckBytes;
+use rancor::{fail, ResultExt as _, Source, Strategy};
+
+use crate::{
+    ser::DefaultSerializer, util::AlignedVec,
+    validation::validators::DefaultValidator, Archive, Portable, Serialize,
+};
+
+/// The alignment of each record in the log.
+pub const RECORD_ALIGN: usize = 16;
+
+/// The length of each record header in bytes.
+pub const HEADER_LEN: usize = 16;
+
+/// The magic bytes at the start of each record header.
+pub const RECORD_MAGIC: [u8; 4] = *b"rklg";
+
+const PADDING: [u8; RECORD_ALIGN] = [0; RECORD_ALIGN];
+
+fn align_up(pos: usize) -> Option<usize> {
+    Some(pos.checked_add(RECORD_ALIGN - 1)? & !(RECORD_ALIGN - 1))
+}
+
+/// An error which occurs when reading or writing a log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LogError {
+    /// A complete record header did not start with [`RECORD_MAGIC`], or its
+    /// reserved bytes were not zero.
+    InvalidHeader {
+        /// The position of the record header in the log.
+        pos: usize,
+    },
+    /// Appending a record would move the position in the log past
+    /// `usize::MAX`.
+    PositionOverflow {
+        /// The position in the log before the record.
+        pos: usize,
+    },
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader { pos } => {
+                write!(f, "invalid log record header at position {pos}")
+            }
+            Self::PositionOverflow { pos } => write!(
+                f,
+                "appending a log record at position {pos} would overflow the \
+                 log position",
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
+
+/// The location of a record appended to a log.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RecordHandle {
+    header_pos: usize,
+    range: Range<usize>,
+}
+
+impl RecordHandle {
+    /// Returns the position of the record header in the log.
+    pub fn header_pos(&self) -> usize {
+        self.header_pos
+    }
+
+    /// Returns the range of the archive bytes in the log.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
+/// Appends archived records to a [`Write`].
+///
+/// See the [module docs](self) for the layout of each record.
+#[derive(Debug)]
+pub struct ArchiveLogWriter<W> {
+    inner: W,
+    pos: usize,
+    buffer: AlignedVec,
+}
+
+impl<W> ArchiveLogWriter<W> {
+    /// Creates a new log writer which starts writing records at the beginning
+    /// of the log.
+    pub fn new(inner: W) -> Self {
+        Self::with_pos(inner, 0)
+    }
+
+    /// Creates a new log writer, and assumes that the underlying writer is
+    /// currently at the given position in the log.
+    ///
+    /// Use this to append to an existing log. The position should be the end
+    /// of the last complete record, as reported by [`ArchiveLogReader::pos`].
+    pub fn with_pos(inner: W, pos: usize) -> Self {
+        Self {
+            inner,
+            pos,
+            buffer: AlignedVec::new(),
+        }
+    }
+
+    /// Returns the current position in the log.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the log writer and returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> ArchiveLogWriter<W> {
+    /// Serializes the given value and appends it to the log as a new record.
+    ///
+    /// The value is serialized into a buffer which is reused between appends,
+    /// and then written after its header. If writing fails, the log may end
+    /// with an incomplete record.
+    pub fn append<T, E>(&mut self, value: &T) -> Result<RecordHandle, E>
+    where
+        T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, E>>,
+        E: Source,
+    {
+        let mut buffer = core::mem::take(&mut self.buffer);
+        buffer.clear();
+        let buffer = crate::to_bytes_in(value, buffer)?;
+
+        let Some(header_pos) = align_up(self.pos).filter(|header_pos| {
+            header_pos
+                .checked_add(HEADER_LEN)
+                .and_then(|start| start.checked_add(buffer.len()))
+                .is_some()
+        }) else {
+            fail!(LogError::PositionOverflow { pos: self.pos });
+        };
+        self.inner
+            .write_all(&PADDING[..header_pos - self.pos])
+            .into_error()?;
+        self.pos = header_pos;
+
+        let mut header = [0; HEADER_LEN];
+        header[0..4].copy_from_slice(&RECORD_MAGIC);
+        header[8..16].copy_from_slice(&(buffer.len() as u64).to_le_bytes());
+        self.inner.write_all(&header).into_error()?;
+        self.pos += HEADER_LEN;
+
+        self.inner.write_all(&buffer).into_error()?;
+        let start = self.pos;
+        self.pos += buffer.len();
+        self.buffer = buffer;
+
+        Ok(RecordHandle {
+            header_pos,
+            range: start..self.pos,
+        })
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush<E: Source>(&mut self) -> Result<(), E> {
+        self.inner.flush().into_error()
+    }
+}
+
+/// Reads archived records from the bytes of a log.
+///
+/// The log bytes must be aligned to at least [`RECORD_ALIGN`]. See the
+/// [module docs](self) for the layout of each record.
+#[derive(Clone, Debug)]
+pub struct ArchiveLogReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> ArchiveLogReader<'a> {
+    /// Creates a new log reader which starts reading records at the beginning
+    /// of the given log bytes.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    /// Returns the end of the last complete record read.
+    ///
+    /// After the reader stops, this is the length of the valid prefix of the
+    /// log.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// Reads the next record from the log.
+    ///
+    /// Returns `Ok(None)` at the end of the log, including if the final record
+    /// is incomplete. Returns an error if the next record has an invalid
+    /// header or its archive bytes fail validation. The reader does not advance
+    /// past records that it returns errors for.
+    pub fn read_next<T, E>(
+        &mut self,
+    ) -> Result<Option<(&'a T::Archived, Range<usize>)>, E>
+    where
+        T: Archive,
+        T::Archived:
+            Portable + for<'v> CheckBytes<Strategy<DefaultValidator<'v>, E>>,
+        E: Source,
+    {
+        let Some(header_pos) = align_up(self.pos) else {
+            return Ok(None);
+        };
+        let Some(header) = self
+            .bytes
+            .get(header_pos..)
+            .and_then(|rest| rest.get(..HEADER_LEN))
+        else {
+            return Ok(None);
+        };
+
+        if header[0..4] != RECORD_MAGIC || header[4..8] != [0; 4] {
+            fail!(LogError::InvalidHeader { pos: header_pos });
+        }
+
+        let mut len = [0; 8];
+        len.copy_from_slice(&header[8..16]);
+        let start = header_pos + HEADER_LEN;
+        let Some(end) = usize::try_from(u64::from_le_bytes(len))
+            .ok()
+            .and_then(|len| start.checked_add(len))
+            .filter(|&end| end <= self.bytes.len())
+        else {
+            return Ok(None);
+        };
+
+        let archived =
+            crate::access::<T::Archived, E>(&self.bytes[start..end])?;
+        self.pos = end;
+
+        Ok(Some((archived, start..end)))
+    }
+
+    /// Returns an iterator over the remaining records in the log.
+    ///
+    /// The iterator stops after the first error.
+    pub fn iter<T, E>(&mut self) -> Records<'_, 'a, T, E> {
+        Records {
+            reader: self,
+            done: false,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// An iterator over the records of a log.
+///
+/// This is created by [`ArchiveLogReader::iter`].
+#[derive(Debug)]
+pub struct Records<'r, 'a, T, E> {
+    reader: &'r mut ArchiveLogReader<'a>,
+    done: bool,
+    _phantom: PhantomData<fn() -> (T, E)>,
+}
+
+impl<'a, T, E> Iterator for Records<'_, 'a, T, E>
+where
+    T: Archive,
+    T::Archived:
+        Portable + for<'v> CheckBytes<Strategy<DefaultValidator<'v>, E>>,
+    E: Source,
+{
+    type Item = Result<(&'a T::Archived, Range<usize>), E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let result = self.reader.read_next::<T, E>().transpose();
+        self.done = !matches!(result, Some(Ok(_)));
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rancor::{Failure, Panic};
+
+    use super::{ArchiveLogReader, ArchiveLogWriter, HEADER_LEN, RECORD_ALIGN};
+    use crate::{util::AlignedVec, Archive, Serialize};
+
+    #[derive(Archive, Serialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, compare(PartialEq), derive(Debug))]
+    enum Record {
+        Insert { key: u32, value: String },
+        Remove { key: u32 },
+        Batch(Vec<u32>),
+    }
+
+    fn records() -> Vec<Record> {
+        vec![
+            Record::Insert {
+                key: 1,
+                value: String::from("a value which is stored out of line"),
+            },
+            Record::Remove { key: 2 },
+            Record::Batch((0..10).collect()),
+            Record::Insert {
+                key: 3,
+                value: String::from("short"),
+            },
+        ]
+    }
+
+    fn write_log(records: &[Record]) -> AlignedVec {
+        let mut writer = ArchiveLogWriter::new(Vec::new());
+        for (i, record) in records.iter().enumerate() {
+            let handle = writer.append::<_, Panic>(record).unwrap();
+            assert_eq!(handle.header_pos() % RECORD_ALIGN, 0);
+            assert_eq!(handle.range().start, handle.header_pos() + HEADER_LEN);
+            assert_eq!(handle.range().end, writer.pos());
+            if i == 0 {
+                assert_eq!(handle.header_pos(), 0);
+            }
+        }
+
+        let mut bytes = AlignedVec::new();
+        bytes.extend_from_slice(&writer.into_inner());
+        bytes
+    }
+
+    fn read_log(bytes: &[u8]) -> (Vec<&ArchivedRecord>, usize) {
+        let mut reader = ArchiveLogReader::new(bytes);
+        let records = reader
+            .iter::<Record, Failure>()
+            .map(|record| record.unwrap().0)
+            .collect();
+        (records, reader.pos())
+    }
+
+    #[test]
+    fn empty_log() {
+        let (records, pos) = read_log(&[]);
+        assert!(records.is_empty());
+        assert_eq!(pos, 0);
+
+        let writer = ArchiveLogWriter::new(Vec::new());
+        assert_eq!(writer.pos(), 0);
+        assert!(writer.into_inner().is_empty());
+    }
+
+    #[test]
+    fn mixed_records() {
+        let values = records();
+        let bytes = write_log(&values);
+
+        let mut reader = ArchiveLogReader::new(&bytes);
+        for value in values.iter() {
+            let (archived, range) =
+                reader.read_next::<Record, Failure>().unwrap().unwrap();
+            assert_eq!(archived, value);
+            assert_eq!(range.end, reader.pos());
+        }
+        assert!(reader.read_next::<Record, Failure>().unwrap().is_none());
+        assert_eq!(reader.pos(), bytes.len());
+    }
+
+    #[test]
+    fn torn_tail() {
+        let values = records();
+        let bytes = write_log(&values);
+
+        let mut ends = Vec::new();
+        let mut reader = ArchiveLogReader::new(&bytes);
+        for record in reader.iter::<Record, Failure>() {
+            ends.push(record.unwrap().1.end);
+        }
+
+        for len in 0..=bytes.len() {
+            let (records, pos) = read_log(&bytes[..len]);
+            let complete = ends.iter().filter(|&&end| end <= len).count();
+            assert_eq!(records.len(), complete);
+            assert_eq!(pos, ends[..complete].last().copied().unwrap_or(0));
+            for (archived, value) in records.iter().zip(values.iter()) {
+                assert_eq!(*archived, value);
+            }
+        }
+    }
+
+    #[test]
+    fn append_after_torn_tail() {
+        let values = records();
+        let bytes = write_log(&values);
+        let torn = &bytes[..bytes.len() - 1];
+
+        let (records, pos) = read_log(torn);
+        assert_eq!(records.len(), values.len() - 1);
+
+        let mut log = torn[..pos].to_vec();
+        let mut writer = ArchiveLogWriter::with_pos(&mut log, pos);
+        writer.append::<_, Panic>(&values[3]).unwrap();
+
+        let mut bytes = AlignedVec::<16>::new();
+        bytes.extend_from_slice(&log);
+        let (records, pos) = read_log(&bytes);
+        assert_eq!(records.len(), values.len());
+        assert_eq!(records[3], &values[3]);
+        assert_eq!(pos, bytes.len());
+    }
+
+    #[test]
+    fn append_position_overflow() {
+        let mut writer = ArchiveLogWriter::with_pos(Vec::new(), usize::MAX - 2);
+        assert!(writer.append::<_, Failure>(&records()[1]).is_err());
+        assert!(writer.inner().is_empty());
+
+        let pos = usize::MAX - HEADER_LEN - 4;
+        let mut writer = ArchiveLogWriter::with_pos(Vec::new(), pos);
+        assert!(writer.append::<_, Failure>(&records()[1]).is_err());
+        assert!(writer.inner().is_empty());
+        assert_eq!(writer.pos(), pos);
+    }
+
+    #[test]
+    fn invalid_header() {
+        let mut bytes = write_log(&records());
+        bytes[0] ^= 1;
+
+        let mut reader = ArchiveLogReader::new(&bytes);
+        let mut iter = reader.iter::<Record, Failure>();
+        assert!(iter.next().unwrap().is_err());
+        assert!(iter.next().is_none());
+        assert_eq!(reader.pos(), 0);
+    }
+}
@@ -0,0 +1,279 @@
+//! Archives embedded inside other archives.
+//!
+//! An already-serialized archive can be stored as a field of a bigger archive
+//! with [`BytesOf`](crate::with::BytesOf). The bytes of the inner archive are
+//! copied verbatim with enough alignment that the inner archive can be
+//! accessed in place, without deserializing or serializing it again.
+
+use core::{fmt, marker::PhantomData, mem};
+
+use munge::munge;
+use rancor::{fail, Fallible, Source};
+
+use crate::{
+    primitive::ArchivedUsize,
+    ser::{Writer, WriterExt as _},
+    vec::{ArchivedVec, VecResolver},
+    Archive, Place, Portable,
+};
+
+/// The bytes of an archived `T`, stored inside of another archive.
+///
+/// The inner archive is aligned so that its root can be accessed in place with
+/// [`access_inner`](ArchivedBytesOf::access_inner).
+#[derive(Portable)]
+#[rkyv(crate)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(C)]
+pub struct ArchivedBytesOf<T> {
+    bytes: ArchivedVec<u8>,
+    root: ArchivedUsize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> ArchivedBytesOf<T> {
+    /// Returns the bytes of the inner archive.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+
+    /// Returns the position of the root of the inner archive in its bytes.
+    #[inline]
+    pub fn root_pos(&self) -> usize {
+        self.root.to_native() as usize
+    }
+
+    /// Resolves an `ArchivedBytesOf` from the length of the inner archive.
+    pub fn resolve_from_len(
+        len: usize,
+        resolver: BytesOfResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedBytesOf { bytes, root, _phantom: _ } = out);
+        ArchivedVec::resolve_from_len(
+            len,
+            VecResolver::from_pos(resolver.pos),
+            bytes,
+        );
+        usize::resolve(&resolver.root, (), root);
+    }
+}
+
+impl<T: Archive> ArchivedBytesOf<T> {
+    /// Serializes the bytes of an archived `T`.
+    ///
+    /// The root of the archive must be at the end of the bytes, which is the
+    /// default for [`to_bytes`](crate::to_bytes). The bytes are aligned to
+    /// the greater of `align` and the alignment of the archived type. `align`
+    /// should be the alignment that the inner archive was serialized with.
+    pub fn serialize_from_bytes<S>(
+        inner: &[u8],
+        align: usize,
+        serializer: &mut S,
+    ) -> Result<BytesOfResolver, S::Error>
+    where
+        S: Fallible + Writer + ?Sized,
+        S::Error: Source,
+    {
+        #[derive(Debug)]
+        struct TooShortError {
+            len: usize,
+            root_size: usize,
+        }
+
+        impl fmt::Display for TooShortError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(
+                    f,
+                    "inner archive is {} bytes long but its root is {} bytes \
+                     long",
+                    self.len, self.root_size,
+                )
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl std::error::Error for TooShortError {}
+
+        let root_size = mem::size_of::<T::Archived>();
+        if inner.len() < root_size {
+            fail!(TooShortError {
+                len: inner.len(),
+                root_size,
+            });
+        }
+
+        serializer.align(align.max(mem::align_of::<T::Archived>()))?;
+        let pos = serializer.pos();
+        serializer.write(inner)?;
+        Ok(BytesOfResolver {
+            pos,
+            root: inner.len() - root_size,
+        })
+    }
+
+    /// Accesses the root of the inner archive without checking its validity.
+    ///
+    /// # Safety
+    ///
+    /// The inner bytes must contain a valid archived `T` at the root position.
+    #[inline]
+    pub unsafe fn access_inner_unchecked(&self) -> &T::Archived {
+        // SAFETY: The caller has guaranteed that a valid `T::Archived` is
+        // located at the root position of the inner bytes.
+        unsafe {
+            crate::util::access_pos_unchecked::<T::Archived>(
+                self.as_bytes(),
+                self.root_pos(),
+            )
+        }
+    }
+}
+
+#[cfg(all(feature = "bytecheck", feature = "alloc"))]
+impl<T: Archive> ArchivedBytesOf<T> {
+    /// Accesses the root of the inner archive after checking its validity.
+    ///
+    /// The inner archive is validated on its own, so it must not point to any
+    /// bytes outside of itself.
+    pub fn access_inner<E>(&self) -> Result<&T::Archived, E>
+    where
+        T::Archived: for<'a> bytecheck::CheckBytes<
+            rancor::Strategy<
+                crate::validation::validators::DefaultValidator<'a>,
+                E,
+            >,
+        >,
+        E: Source,
+    {
+        crate::validation::util::access_pos::<T::Archived, E>(
+            self.as_bytes(),
+            self.root_pos(),
+        )
+    }
+}
+
+impl<T> fmt::Debug for ArchivedBytesOf<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedBytesOf")
+            .field("len", &self.bytes.len())
+            .field("root", &self.root_pos())
+            .finish()
+    }
+}
+
+/// The resolver for [`ArchivedBytesOf`].
+pub struct BytesOfResolver {
+    pos: usize,
+    root: usize,
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    #[cfg(feature = "std")]
+    use std::collections::HashMap;
+
+    use rancor::Panic;
+
+    use crate::{
+        test::{access, deserialize, to_bytes},
+        util::AlignedVec,
+        vec::ArchivedVec,
+        with::BytesOf,
+        Archive, Archived, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Outer {
+        name: String,
+        #[with(BytesOf<Vec<u64>>)]
+        inner: AlignedVec,
+        #[with(BytesOf<Vec<String>>)]
+        unaligned: Vec<u8>,
+    }
+
+    #[test]
+    fn nested_vec_archive() {
+        let inner = crate::to_bytes::<Panic>(&vec![1u64, 2, 3]).unwrap();
+        let strings = vec!["first".to_string(), "second".to_string()];
+        let unaligned = crate::to_bytes::<Panic>(&strings).unwrap().to_vec();
+        let value = Outer {
+            name: "outer".to_string(),
+            inner,
+            unaligned,
+        };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedOuter>(bytes, |archived| {
+                assert_eq!(archived.name, "outer");
+
+                let inner = archived.inner.access_inner::<Panic>().unwrap();
+                assert_eq!(*inner, [1u64, 2, 3]);
+                let unchecked =
+                    unsafe { archived.inner.access_inner_unchecked() };
+                assert_eq!(*unchecked, [1u64, 2, 3]);
+
+                let strings: &ArchivedVec<Archived<String>> =
+                    archived.unaligned.access_inner::<Panic>().unwrap();
+                assert_eq!(strings.len(), 2);
+                assert_eq!(strings[1], "second");
+
+                let deserialized = deserialize::<Outer>(archived);
+                assert_eq!(deserialized.name, value.name);
+                assert_eq!(*deserialized.inner, *value.inner);
+                assert_eq!(deserialized.unaligned, value.unaligned);
+            });
+        });
+    }
+
+    #[cfg(feature = "std")]
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Index {
+        #[with(BytesOf<HashMap<String, u32>>)]
+        map: AlignedVec,
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn nested_map_archive() {
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), 1u32);
+        map.insert("b".to_string(), 2u32);
+        let value = Index {
+            map: crate::to_bytes::<Panic>(&map).unwrap(),
+        };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedIndex>(bytes, |archived| {
+                let inner = archived.map.access_inner::<Panic>().unwrap();
+                assert_eq!(inner.len(), 2);
+                assert_eq!(inner.get("a").map(|v| v.to_native()), Some(1));
+                assert_eq!(inner.get("b").map(|v| v.to_native()), Some(2));
+            });
+        });
+    }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate)]
+    struct TooShort {
+        #[with(BytesOf<u64>)]
+        bytes: Vec<u8>,
+    }
+
+    #[test]
+    fn too_short_fails() {
+        let value = TooShort {
+            bytes: vec![0u8; 4],
+        };
+        assert!(crate::to_bytes::<rancor::Failure>(&value).is_err());
+    }
+}
@@ -0,0 +1,596 @@
+//! Archived columns of optional values with a validity bitmap.
+//!
+//! Archiving a `Vec<Option<T>>` stores a tag next to every element, padded to
+//! the alignment of `T`. For an `Option<f64>`, that doubles the size of every
+//! element. The [`AsNullableColumn`](crate::with::AsNullableColumn) wrapper
+//! instead archives the column as:
+//!
+//! - A packed validity bitmap, with one bit for each element.
+//! - A dense column of archived values.
+//!
+//! How the values are stored is chosen by the layout parameter of the wrapper:
+//!
+//! - [`Packed`] stores only the values which are present. This is the most
+//!   compact layout, but getting an element has to count the set bits before
+//!   it.
+//! - [`Slotted`] stores a value for every element, and null elements hold the
+//!   archived default value. Getting an element is a single index.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{
+//!     nullable::Slotted, rancor::Error, with::AsNullableColumn, Archive,
+//!     Deserialize, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+//! struct Readings {
+//!     #[with(AsNullableColumn)]
+//!     temperatures: Vec<Option<f64>>,
+//!     #[with(AsNullableColumn<Slotted>)]
+//!     pressures: Vec<Option<u32>>,
+//! }
+//!
+//! let readings = Readings {
+//!     temperatures: vec![Some(21.5), None, None, Some(22.0)],
+//!     pressures: vec![None, Some(1013), Some(1009), None],
+//! };
+//!
+//! let bytes = rkyv::to_bytes::<Error>(&readings).unwrap();
+//! let archived = rkyv::access::<ArchivedReadings, Error>(&bytes).unwrap();
+//!
+//! assert_eq!(archived.temperatures.len(), 4);
+//! assert_eq!(archived.temperatures.get(3).unwrap().to_native(), 22.0);
+//! assert!(archived.temperatures.get(1).is_none());
+//! assert_eq!(archived.pressures.values().len(), 4);
+//! assert_eq!(
+//!     archived.pressures.iter().flatten().count(),
+//!     archived.pressures.len() - archived.pressures.null_count(),
+//! );
+//!
+//! let deserialized =
+//!     rkyv::deserialize::<Readings, _, Error>(archived, &mut ()).unwrap();
+//! assert_eq!(deserialized, readings);
+//! ```
+
+use core::{fmt, iter::FusedIterator, marker::PhantomData};
+
+use munge::munge;
+use rancor::Fallible;
+
+use crate::{
+    primitive::{usize_from_archived, ArchivedU64, ArchivedUsize},
+    ser::{Allocator, Writer},
+    vec::{ArchivedVec, VecResolver},
+    Archive as _, Place, Portable, Serialize,
+};
+
+const WORD_BITS: usize = 64;
+
+/// How the values of an [`ArchivedNullableColumn`] are stored.
+pub trait NullableLayout {
+    /// Whether only the present values are stored.
+    ///
+    /// If this is `false`, then a value is stored for every element.
+    const PACKED: bool;
+}
+
+/// A nullable column layout which only stores the present values.
+#[derive(Debug)]
+pub struct Packed;
+
+impl NullableLayout for Packed {
+    const PACKED: bool = true;
+}
+
+/// A nullable column layout which stores a value for every element.
+///
+/// Null elements are serialized from the default value of the type, and their
+/// archived values should not be relied on.
+#[derive(Debug)]
+pub struct Slotted;
+
+impl NullableLayout for Slotted {
+    const PACKED: bool = false;
+}
+
+/// An archived column of optional values.
+///
+/// This is the archived type of a `Vec<Option<T>>` archived with
+/// [`AsNullableColumn`](crate::with::AsNullableColumn).
+#[derive(Portable)]
+#[rkyv(crate)]
+#[cfg_attr(
+    feature = "bytecheck",
+    derive(bytecheck::CheckBytes),
+    check_bytes(verify)
+)]
+#[repr(C)]
+pub struct ArchivedNullableColumn<T, L = Packed> {
+    validity: ArchivedVec<ArchivedU64>,
+    values: ArchivedVec<T>,
+    len: ArchivedUsize,
+    _layout: PhantomData<L>,
+}
+
+impl<T, L: NullableLayout> ArchivedNullableColumn<T, L> {
+    /// Returns the number of elements, including null elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        usize_from_archived(self.len)
+    }
+
+    /// Returns whether there are no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of null elements.
+    #[inline]
+    pub fn null_count(&self) -> usize {
+        self.len() - count_ones(&self.validity)
+    }
+
+    /// Returns whether the element at the given index is present.
+    ///
+    /// Returns `false` if `index` is out of bounds.
+    #[inline]
+    pub fn is_valid(&self, index: usize) -> bool {
+        index < self.len() && bit(&self.validity, index)
+    }
+
+    /// Returns a reference to the value of the element at the given index.
+    ///
+    /// Returns `None` if the element is null or `index` is out of bounds.
+    /// With the [`Packed`] layout, this counts the present values before the
+    /// element and takes time linear in `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if !self.is_valid(index) {
+            return None;
+        }
+
+        if L::PACKED {
+            let word = index / WORD_BITS;
+            let mask = (1u64 << (index % WORD_BITS)) - 1;
+            let rank = count_ones(&self.validity[..word])
+                + (self.validity[word].to_native() & mask).count_ones()
+                    as usize;
+            self.values.get(rank)
+        } else {
+            self.values.get(index)
+        }
+    }
+
+    /// Returns an iterator over the elements of the column.
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            validity: self.validity.as_slice(),
+            values: self.values.as_slice(),
+            packed: L::PACKED,
+            index: 0,
+            value: 0,
+            len: self.len(),
+        }
+    }
+
+    /// Returns the words of the validity bitmap.
+    ///
+    /// Bit `i % 64` of word `i / 64` is set if element `i` is present. Unused
+    /// bits of the last word are always unset.
+    #[inline]
+    pub fn validity(&self) -> &[ArchivedU64] {
+        self.validity.as_slice()
+    }
+
+    /// Returns the stored values.
+    ///
+    /// With the [`Packed`] layout, these are only the present values. With
+    /// the [`Slotted`] layout, there is one value for every element.
+    #[inline]
+    pub fn values(&self) -> &[T] {
+        self.values.as_slice()
+    }
+
+    /// Resolves an archived nullable column from a slice of optional values.
+    pub fn resolve_from_slice<U>(
+        slice: &[Option<U>],
+        resolver: NullableColumnResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let Self { validity, values, len, _layout: _ } = out);
+        let values_len = if L::PACKED {
+            slice.iter().filter(|value| value.is_some()).count()
+        } else {
+            slice.len()
+        };
+        ArchivedVec::resolve_from_len(
+            words_for(slice.len()),
+            resolver.validity,
+            validity,
+        );
+        ArchivedVec::resolve_from_len(values_len, resolver.values, values);
+        usize::resolve(&slice.len(), (), len);
+    }
+}
+
+impl<T> ArchivedNullableColumn<T, Packed> {
+    /// Serializes the validity bitmap and present values of a slice of
+    /// optional values.
+    pub fn serialize_from_slice<U, S>(
+        slice: &[Option<U>],
+        serializer: &mut S,
+    ) -> Result<NullableColumnResolver, S::Error>
+    where
+        U: Serialize<S, Archived = T>,
+        S: Fallible + Allocator + Writer + ?Sized,
+    {
+        let validity = serialize_validity(slice, serializer)?;
+        let values = ArchivedVec::<T>::serialize_from_iter::<U, _, _>(
+            slice.iter().flatten(),
+            serializer,
+        )?;
+
+        Ok(NullableColumnResolver { validity, values })
+    }
+}
+
+impl<T> ArchivedNullableColumn<T, Slotted> {
+    /// Serializes the validity bitmap and a value for every element of a
+    /// slice of optional values.
+    ///
+    /// Null elements are serialized from `U::default()`.
+    pub fn serialize_from_slice<U, S>(
+        slice: &[Option<U>],
+        serializer: &mut S,
+    ) -> Result<NullableColumnResolver, S::Error>
+    where
+        U: Default + Serialize<S, Archived = T>,
+        S: Fallible + Allocator + Writer + ?Sized,
+    {
+        let validity = serialize_validity(slice, serializer)?;
+        let default = U::default();
+        let values = ArchivedVec::<T>::serialize_from_iter::<U, _, _>(
+            slice.iter().map(|value| value.as_ref().unwrap_or(&default)),
+            serializer,
+        )?;
+
+        Ok(NullableColumnResolver { validity, values })
+    }
+}
+
+impl<T: fmt::Debug, L: NullableLayout> fmt::Debug
+    for ArchivedNullableColumn<T, L>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a, T, L: NullableLayout> IntoIterator
+    for &'a ArchivedNullableColumn<T, L>
+{
+    type Item = Option<&'a T>;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, U, L> PartialEq<[Option<U>]> for ArchivedNullableColumn<T, L>
+where
+    T: PartialEq<U>,
+    L: NullableLayout,
+{
+    fn eq(&self, other: &[Option<U>]) -> bool {
+        self.len() == other.len()
+            && self.iter().zip(other.iter()).all(|(a, b)| match (a, b) {
+                (Some(a), Some(b)) => a == b,
+                (None, None) => true,
+                _ => false,
+            })
+    }
+}
+
+/// The resolver for [`ArchivedNullableColumn`].
+pub struct NullableColumnResolver {
+    validity: VecResolver,
+    values: VecResolver,
+}
+
+/// An iterator over the elements of an [`ArchivedNullableColumn`].
+pub struct Iter<'a, T> {
+    validity: &'a [ArchivedU64],
+    values: &'a [T],
+    packed: bool,
+    index: usize,
+    value: usize,
+    len: usize,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = Option<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index == self.len {
+            return None;
+        }
+
+        let index = self.index;
+        self.index += 1;
+        if !self.packed {
+            self.value = index;
+        }
+
+        if bit(self.validity, index) {
+            let value = self.values.get(self.value);
+            self.value += 1;
+            Some(value)
+        } else {
+            Some(None)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+impl<T> FusedIterator for Iter<'_, T> {}
+
+fn words_for(len: usize) -> usize {
+    len / WORD_BITS + usize::from(len % WORD_BITS != 0)
+}
+
+fn bit(words: &[ArchivedU64], index: usize) -> bool {
+    words[index / WORD_BITS].to_native() & (1 << (index % WORD_BITS)) != 0
+}
+
+fn count_ones(words: &[ArchivedU64]) -> usize {
+    words
+        .iter()
+        .map(|word| word.to_native().count_ones() as usize)
+        .sum()
+}
+
+fn serialize_validity<U, S>(
+    slice: &[Option<U>],
+    serializer: &mut S,
+) -> Result<VecResolver, S::Error>
+where
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    let words = slice.chunks(WORD_BITS).map(|chunk| {
+        chunk.iter().enumerate().fold(0u64, |word, (i, value)| {
+            word | (u64::from(value.is_some()) << i)
+        })
+    });
+    ArchivedVec::<ArchivedU64>::serialize_from_iter::<u64, _, _>(
+        words, serializer,
+    )
+}
+
+#[cfg(feature = "bytecheck")]
+mod verify {
+    use core::fmt;
+
+    use bytecheck::{
+        rancor::{Fallible, Source},
+        Verify,
+    };
+    use rancor::fail;
+
+    use super::{
+        count_ones, words_for, ArchivedNullableColumn, NullableLayout,
+        WORD_BITS,
+    };
+    use crate::primitive::native_len;
+
+    #[derive(Debug)]
+    enum NullableColumnError {
+        BitmapLength { len: usize, words: usize },
+        TrailingBits { len: usize },
+        ValuesLength { expected: usize, actual: usize },
+    }
+
+    impl fmt::Display for NullableColumnError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Self::BitmapLength { len, words } => write!(
+                    f,
+                    "nullable column of length {} has {} bitmap words",
+                    len, words,
+                ),
+                Self::TrailingBits { len } => write!(
+                    f,
+                    "nullable column of length {} has bits set past its end",
+                    len,
+                ),
+                Self::ValuesLength { expected, actual } => write!(
+                    f,
+                    "nullable column should have {} values but has {}",
+                    expected, actual,
+                ),
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for NullableColumnError {}
+
+    unsafe impl<T, L, C> Verify<C> for ArchivedNullableColumn<T, L>
+    where
+        L: NullableLayout,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        fn verify(&self, _: &mut C) -> Result<(), C::Error> {
+            let len = native_len(self.len)?;
+            let words = self.validity.len();
+            if words != words_for(len) {
+                fail!(NullableColumnError::BitmapLength { len, words });
+            }
+
+            let used = len % WORD_BITS;
+            if used != 0 && self.validity[words - 1].to_native() >> used != 0 {
+                fail!(NullableColumnError::TrailingBits { len });
+            }
+
+            let expected = if L::PACKED {
+                count_ones(&self.validity)
+            } else {
+                len
+            };
+            let actual = self.values.len();
+            if actual != expected {
+                fail!(NullableColumnError::ValuesLength { expected, actual });
+            }
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{vec, vec::Vec};
+
+    use rancor::{Failure, Panic};
+
+    use super::{ArchivedNullableColumn, NullableLayout, Packed, Slotted};
+    use crate::{
+        access, primitive::ArchivedU32, test::roundtrip_with, to_bytes,
+        with::AsNullableColumn, Archive, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, derive(Debug))]
+    struct Columns {
+        #[with(AsNullableColumn)]
+        packed: Vec<Option<u32>>,
+        #[with(AsNullableColumn<Slotted>)]
+        slotted: Vec<Option<u32>>,
+    }
+
+    fn check_column<L: NullableLayout>(
+        column: &ArchivedNullableColumn<ArchivedU32, L>,
+        values: &[Option<u32>],
+    ) {
+        let present = values.iter().flatten().count();
+        assert_eq!(column.len(), values.len());
+        assert_eq!(column.is_empty(), values.is_empty());
+        assert_eq!(column.null_count(), values.len() - present);
+        assert_eq!(column.iter().len(), values.len());
+        assert_eq!(*column, *values);
+
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(column.is_valid(i), value.is_some());
+            assert_eq!(column.get(i).map(|v| v.to_native()), *value);
+        }
+        assert!(!column.is_valid(values.len()));
+        assert!(column.get(values.len()).is_none());
+    }
+
+    fn check(values: Vec<Option<u32>>) {
+        let value = Columns {
+            packed: values.clone(),
+            slotted: values.clone(),
+        };
+
+        roundtrip_with(&value, |_, archived| {
+            check_column(&archived.packed, &values);
+            check_column(&archived.slotted, &values);
+
+            let present = values.iter().flatten().count();
+            assert_eq!(archived.packed.values().len(), present);
+            assert_eq!(archived.slotted.values().len(), values.len());
+        });
+    }
+
+    #[test]
+    fn empty_column() {
+        check(Vec::new());
+    }
+
+    #[test]
+    fn all_null_column() {
+        check(vec![None; 3]);
+        check(vec![None; 64]);
+        check(vec![None; 200]);
+    }
+
+    #[test]
+    fn none_null_column() {
+        check(vec![Some(7)]);
+        check((0..64).map(Some).collect());
+        check((0..200).map(Some).collect());
+    }
+
+    #[test]
+    fn mixed_column() {
+        check(vec![None, Some(1), None, Some(3)]);
+        check(
+            (0..300)
+                .map(|i| if i % 3 == 0 { None } else { Some(i * 10) })
+                .collect(),
+        );
+    }
+
+    // Has the same archived layout as an `ArchivedNullableColumn`, but can
+    // hold inconsistent bitmaps.
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate)]
+    struct RawColumn {
+        validity: Vec<u64>,
+        values: Vec<u32>,
+        len: usize,
+    }
+
+    fn raw(
+        validity: Vec<u64>,
+        values: Vec<u32>,
+        len: usize,
+    ) -> impl AsRef<[u8]> {
+        to_bytes::<Panic>(&RawColumn {
+            validity,
+            values,
+            len,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn reject_inconsistent_bitmap() {
+        type Column<L> = ArchivedNullableColumn<ArchivedU32, L>;
+
+        let valid = raw(vec![0b101], vec![1, 2], 3);
+        access::<Column<Packed>, Failure>(valid.as_ref()).unwrap();
+        access::<Column<Slotted>, Failure>(valid.as_ref()).unwrap_err();
+
+        let valid = raw(vec![0b101], vec![1, 0, 2], 3);
+        access::<Column<Slotted>, Failure>(valid.as_ref()).unwrap();
+
+        // Too few and too many bitmap words
+        let bytes = raw(vec![], vec![], 3);
+        access::<Column<Packed>, Failure>(bytes.as_ref()).unwrap_err();
+        let bytes = raw(vec![0, 0], vec![], 3);
+        access::<Column<Packed>, Failure>(bytes.as_ref()).unwrap_err();
+        let bytes = raw(vec![0, 0], vec![], 64);
+        access::<Column<Packed>, Failure>(bytes.as_ref()).unwrap_err();
+
+        // Bits set past the end of the column
+        let bytes = raw(vec![0b1001], vec![1, 2], 3);
+        access::<Column<Packed>, Failure>(bytes.as_ref()).unwrap_err();
+
+        // Values which don't match the bitmap
+        let bytes = raw(vec![0b101], vec![1], 3);
+        access::<Column<Packed>, Failure>(bytes.as_ref()).unwrap_err();
+    }
+}
@@ -56,7 +56,7 @@ impl<T> ArchivedOption<T> {
         }
     }
     /// Returns `true` if the option is a `None` value.
-    pub fn is_none(&self) -> bool {
+    pub const fn is_none(&self) -> bool {
         match self {
             ArchivedOption::None => true,
             ArchivedOption::Some(_) => false,
@@ -64,7 +64,7 @@ impl<T> ArchivedOption<T> {
     }
 
     /// Returns `true` if the option is a `Some` value.
-    pub fn is_some(&self) -> bool {
+    pub const fn is_some(&self) -> bool {
         match self {
             ArchivedOption::None => false,
             ArchivedOption::Some(_) => true,
@@ -168,9 +168,16 @@ impl<T: Ord> Ord for ArchivedOption<T> {
     }
 }
 
-impl<T: PartialEq> PartialEq for ArchivedOption<T> {
-    fn eq(&self, other: &Self) -> bool {
-        self.as_ref().eq(&other.as_ref())
+impl<T: PartialEq<U>, U> PartialEq<ArchivedOption<U>> for ArchivedOption<T> {
+    fn eq(&self, other: &ArchivedOption<U>) -> bool {
+        match (self, other) {
+            (ArchivedOption::None, ArchivedOption::None) => true,
+            (
+                ArchivedOption::Some(self_value),
+                ArchivedOption::Some(other_value),
+            ) => self_value.eq(other_value),
+            _ => false,
+        }
     }
 }
 
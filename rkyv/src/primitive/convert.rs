@@ -0,0 +1,305 @@
+//! Checked conversions from archived integers to native integers.
+
+use core::fmt;
+
+use rancor::{fail, Source};
+
+use super::{
+    ArchivedI128, ArchivedI16, ArchivedI32, ArchivedI64, ArchivedU128,
+    ArchivedU16, ArchivedU32, ArchivedU64, ArchivedUsize, FixedUsize,
+};
+
+/// An archived integer which can be converted to native integers without
+/// truncating.
+///
+/// Archived integers have a fixed size which doesn't depend on the target. For
+/// example, an archive created with the `pointer_width_64` feature stores its
+/// lengths as `u64`s, and `len.to_native() as usize` silently truncates them
+/// on 32-bit targets. These conversions return an error instead.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::primitive::{ArchivedInteger, ArchivedU64};
+///
+/// let small = ArchivedU64::from_native(42);
+/// assert_eq!(small.to_usize(), Some(42));
+/// assert_eq!(small.try_to::<u8>(), Ok(42));
+///
+/// let large = ArchivedU64::from_native(1 << 40);
+/// assert!(large.try_to::<u32>().is_err());
+/// ```
+pub trait ArchivedInteger: Copy {
+    /// The native integer type.
+    type Native: Copy;
+
+    /// Returns the native value of the archived integer.
+    fn into_native(self) -> Self::Native;
+
+    /// Converts the archived integer to the given native integer type,
+    /// returning an error if the value does not fit.
+    fn try_to<U>(&self) -> Result<U, U::Error>
+    where
+        U: TryFrom<Self::Native>,
+    {
+        U::try_from(self.into_native())
+    }
+
+    /// Converts the archived integer to a `usize`, returning `None` if the
+    /// value does not fit.
+    fn to_usize(&self) -> Option<usize>
+    where
+        usize: TryFrom<Self::Native>,
+    {
+        self.try_to().ok()
+    }
+
+    /// Converts the archived integer to an `isize`, returning `None` if the
+    /// value does not fit.
+    fn to_isize(&self) -> Option<isize>
+    where
+        isize: TryFrom<Self::Native>,
+    {
+        self.try_to().ok()
+    }
+}
+
+macro_rules! impl_archived_integer {
+    ($($archived:ty: $native:ty),* $(,)?) => {
+        $(
+            impl ArchivedInteger for $archived {
+                type Native = $native;
+
+                #[inline]
+                fn into_native(self) -> Self::Native {
+                    self.to_native()
+                }
+            }
+        )*
+    };
+}
+
+impl_archived_integer! {
+    ArchivedI16: i16,
+    ArchivedI32: i32,
+    ArchivedI64: i64,
+    ArchivedI128: i128,
+    ArchivedU16: u16,
+    ArchivedU32: u32,
+    ArchivedU64: u64,
+    ArchivedU128: u128,
+}
+
+macro_rules! impl_native_integer {
+    ($($native:ty),* $(,)?) => {
+        $(
+            impl ArchivedInteger for $native {
+                type Native = $native;
+
+                #[inline]
+                fn into_native(self) -> Self::Native {
+                    self
+                }
+            }
+        )*
+    };
+}
+
+impl_native_integer!(i8, u8);
+
+/// Converts an archived `usize` to a native `usize`.
+///
+/// Values which don't fit in a `usize` saturate to `usize::MAX`. This can only
+/// happen when reading an archive created with a larger `pointer_width_*`
+/// feature than the pointer width of the target. No valid archived collection
+/// can have a length of `usize::MAX`, so validation rejects a saturated length
+/// instead of accepting a truncated one.
+#[inline]
+pub const fn usize_from_archived(value: ArchivedUsize) -> usize {
+    let value = value.to_native();
+    if value as u128 > usize::MAX as u128 {
+        usize::MAX
+    } else {
+        value as usize
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct LengthOverflow {
+    len: usize,
+}
+
+impl fmt::Display for LengthOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the length {} overflowed the range of the archived `usize` type",
+            self.len,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LengthOverflow {}
+
+/// Converts a length to a `FixedUsize`, returning an error if it does not fit.
+#[inline]
+pub(crate) fn fixed_len<E: Source>(len: usize) -> Result<FixedUsize, E> {
+    match FixedUsize::try_from(len) {
+        Ok(len) => Ok(len),
+        Err(_) => fail!(LengthOverflow { len }),
+    }
+}
+
+#[cfg(feature = "bytecheck")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LengthTruncated {
+    len: FixedUsize,
+}
+
+#[cfg(feature = "bytecheck")]
+impl fmt::Display for LengthTruncated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the archived length {} does not fit in a `usize` on this target",
+            self.len,
+        )
+    }
+}
+
+#[cfg(all(feature = "bytecheck", feature = "std"))]
+impl std::error::Error for LengthTruncated {}
+
+/// Converts an archived length to a `usize`, returning an error if it does not
+/// fit.
+///
+/// Validation uses this instead of [`usize_from_archived`] so that lengths
+/// which don't fit are rejected explicitly.
+#[cfg(feature = "bytecheck")]
+#[inline]
+pub(crate) fn native_len<E: Source>(len: ArchivedUsize) -> Result<usize, E> {
+    let len = len.to_native();
+    if len as u128 > usize::MAX as u128 {
+        fail!(LengthTruncated { len });
+    }
+    Ok(len as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{usize_from_archived, ArchivedInteger};
+    use crate::primitive::{
+        ArchivedI64, ArchivedU128, ArchivedU16, ArchivedU32, ArchivedU64,
+        ArchivedUsize, FixedUsize,
+    };
+
+    #[test]
+    fn checked_conversions() {
+        let value = ArchivedU32::from_native(300);
+        assert_eq!(value.to_usize(), Some(300));
+        assert_eq!(value.to_isize(), Some(300));
+        assert_eq!(value.try_to::<u16>(), Ok(300));
+        assert!(value.try_to::<u8>().is_err());
+
+        let negative = ArchivedI64::from_native(-1);
+        assert_eq!(negative.to_isize(), Some(-1));
+        assert_eq!(negative.to_usize(), None);
+        assert!(negative.try_to::<u64>().is_err());
+
+        let wide = ArchivedU128::from_native(u128::MAX);
+        assert_eq!(wide.to_usize(), None);
+        assert_eq!(ArchivedU16::from_native(7).to_usize(), Some(7));
+        assert_eq!(7u8.to_usize(), Some(7));
+    }
+
+    // A length written by a 64-bit archive is read as a `u32` to simulate a
+    // 32-bit target, since the pointer width of the host can't be changed.
+    #[test]
+    fn wide_length_does_not_truncate() {
+        let len = ArchivedU64::from_native(u64::from(u32::MAX) + 2);
+        assert!(len.try_to::<u32>().is_err());
+        assert_eq!(len.into_native() as u32, 1);
+
+        #[cfg(target_pointer_width = "32")]
+        assert_eq!(len.to_usize(), None);
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(len.to_usize(), Some(u64::from(u32::MAX) as usize + 2));
+    }
+
+    #[test]
+    fn archived_usize() {
+        let len = ArchivedUsize::from_native(FixedUsize::MAX);
+        let native = usize_from_archived(len);
+        if FixedUsize::BITS > usize::BITS {
+            assert_eq!(native, usize::MAX);
+        } else {
+            assert_eq!(native as u128, FixedUsize::MAX as u128);
+        }
+        assert_eq!(usize_from_archived(ArchivedUsize::from_native(12)), 12);
+    }
+
+    /// Returns the bytes of an archived `Vec<()>` with the given length whose
+    /// elements start at the vec itself.
+    #[cfg(all(feature = "bytecheck", feature = "alloc"))]
+    fn vec_of_units(
+        len: FixedUsize,
+    ) -> crate::util::Align<[u8; 2 * core::mem::size_of::<FixedUsize>()]> {
+        let size = core::mem::size_of::<FixedUsize>();
+        let len = if cfg!(feature = "big_endian") {
+            len.to_be_bytes()
+        } else {
+            len.to_le_bytes()
+        };
+        let mut bytes =
+            crate::util::Align([0; 2 * core::mem::size_of::<FixedUsize>()]);
+        bytes.0[size..].copy_from_slice(&len);
+        bytes
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "bytecheck",
+        feature = "alloc",
+        feature = "pointer_width_64",
+    ))]
+    fn validate_wide_length() {
+        use rancor::Failure;
+
+        use crate::{access, vec::ArchivedVec};
+
+        // A 32-bit target would truncate this length to 1.
+        let len = (1 << 32) + 1;
+        let bytes = vec_of_units(len);
+        let result = access::<ArchivedVec<()>, Failure>(&bytes.0);
+        if usize::BITS < FixedUsize::BITS {
+            result.unwrap_err();
+            super::native_len::<Failure>(ArchivedUsize::from_native(len))
+                .unwrap_err();
+        } else {
+            assert_eq!(result.unwrap().len() as FixedUsize, len);
+        }
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "bytecheck",
+        feature = "alloc",
+        feature = "pointer_width_16",
+    ))]
+    fn validate_narrow_length() {
+        use rancor::Failure;
+
+        use crate::{access, vec::ArchivedVec};
+
+        // Every 16-bit length fits in a `usize`.
+        let bytes = vec_of_units(FixedUsize::MAX);
+        let archived = access::<ArchivedVec<()>, Failure>(&bytes.0).unwrap();
+        assert_eq!(archived.len(), FixedUsize::MAX as usize);
+        let len = ArchivedUsize::from_native(FixedUsize::MAX);
+        assert_eq!(
+            super::native_len::<Failure>(len).unwrap(),
+            FixedUsize::MAX as usize,
+        );
+    }
+}
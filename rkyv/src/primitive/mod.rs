@@ -5,38 +5,45 @@
 mod _macros;
 #[cfg(not(feature = "unaligned"))]
 mod atomic;
+mod convert;
+mod wide;
 
 // Aligned little-endian
 #[cfg(not(feature = "unaligned"))]
 pub use self::atomic::*;
+#[cfg(feature = "bytecheck")]
+pub(crate) use self::convert::native_len;
+pub(crate) use self::convert::{fixed_len, LengthOverflow};
+pub use self::{
+    convert::{usize_from_archived, ArchivedInteger},
+    wide::*,
+};
 // Unaligned big-endian
 #[cfg(all(feature = "unaligned", feature = "big_endian"))]
 use crate::rend::unaligned::{
-    char_ube, f32_ube, f64_ube, i128_ube, i16_ube, i32_ube, i64_ube, u128_ube,
-    u16_ube, u32_ube, u64_ube, NonZeroI128_ube, NonZeroI16_ube, NonZeroI32_ube,
-    NonZeroI64_ube, NonZeroU128_ube, NonZeroU16_ube, NonZeroU32_ube,
-    NonZeroU64_ube,
+    char_ube, f32_ube, f64_ube, i16_ube, i32_ube, i64_ube, u16_ube, u32_ube,
+    u64_ube, NonZeroI16_ube, NonZeroI32_ube, NonZeroI64_ube, NonZeroU16_ube,
+    NonZeroU32_ube, NonZeroU64_ube,
 };
 // Unaligned little-endian
 #[cfg(all(feature = "unaligned", not(feature = "big_endian")))]
 use crate::rend::unaligned::{
-    char_ule, f32_ule, f64_ule, i128_ule, i16_ule, i32_ule, i64_ule, u128_ule,
-    u16_ule, u32_ule, u64_ule, NonZeroI128_ule, NonZeroI16_ule, NonZeroI32_ule,
-    NonZeroI64_ule, NonZeroU128_ule, NonZeroU16_ule, NonZeroU32_ule,
-    NonZeroU64_ule,
+    char_ule, f32_ule, f64_ule, i16_ule, i32_ule, i64_ule, u16_ule, u32_ule,
+    u64_ule, NonZeroI16_ule, NonZeroI32_ule, NonZeroI64_ule, NonZeroU16_ule,
+    NonZeroU32_ule, NonZeroU64_ule,
 };
 // Aligned big-endian
 #[cfg(all(not(feature = "unaligned"), feature = "big_endian"))]
 use crate::rend::{
-    char_be, f32_be, f64_be, i128_be, i16_be, i32_be, i64_be, u128_be, u16_be,
-    u32_be, u64_be, NonZeroI128_be, NonZeroI16_be, NonZeroI32_be,
-    NonZeroI64_be, NonZeroU128_be, NonZeroU16_be, NonZeroU32_be, NonZeroU64_be,
+    char_be, f32_be, f64_be, i16_be, i32_be, i64_be, u16_be, u32_be, u64_be,
+    NonZeroI16_be, NonZeroI32_be, NonZeroI64_be, NonZeroU16_be, NonZeroU32_be,
+    NonZeroU64_be,
 };
 #[cfg(all(not(feature = "unaligned"), not(feature = "big_endian")))]
 use crate::rend::{
-    char_le, f32_le, f64_le, i128_le, i16_le, i32_le, i64_le, u128_le, u16_le,
-    u32_le, u64_le, NonZeroI128_le, NonZeroI16_le, NonZeroI32_le,
-    NonZeroI64_le, NonZeroU128_le, NonZeroU16_le, NonZeroU32_le, NonZeroU64_le,
+    char_le, f32_le, f64_le, i16_le, i32_le, i64_le, u16_le, u32_le, u64_le,
+    NonZeroI16_le, NonZeroI32_le, NonZeroI64_le, NonZeroU16_le, NonZeroU32_le,
+    NonZeroU64_le,
 };
 
 macro_rules! define_multibyte_primitive {
@@ -63,11 +70,9 @@ define_multibyte_primitives! {
     ArchivedI16: i16, i16_le, i16_ule, i16_be, i16_ube;
     ArchivedI32: i32, i32_le, i32_ule, i32_be, i32_ube;
     ArchivedI64: i64, i64_le, i64_ule, i64_be, i64_ube;
-    ArchivedI128: i128, i128_le, i128_ule, i128_be, i128_ube;
     ArchivedU16: u16, u16_le, u16_ule, u16_be, u16_ube;
     ArchivedU32: u32, u32_le, u32_ule, u32_be, u32_ube;
     ArchivedU64: u64, u64_le, u64_ule, u64_be, u64_ube;
-    ArchivedU128: u128, u128_le, u128_ule, u128_be, u128_ube;
     ArchivedF32: f32, f32_le, f32_ule, f32_be, f32_ube;
     ArchivedF64: f64, f64_le, f64_ule, f64_be, f64_ube;
     ArchivedChar: char, char_le, char_ule, char_be, char_ube;
@@ -119,13 +124,6 @@ define_multibyte_primitives! {
         NonZeroI64_be,
         NonZeroI64_ube;
 
-    ArchivedNonZeroI128:
-        NonZeroI128,
-        NonZeroI128_le,
-        NonZeroI128_ule,
-        NonZeroI128_be,
-        NonZeroI128_ube;
-
     ArchivedNonZeroU16:
         NonZeroU16,
         NonZeroU16_le,
@@ -146,13 +144,6 @@ define_multibyte_primitives! {
         NonZeroU64_ule,
         NonZeroU64_be,
         NonZeroU64_ube;
-
-    ArchivedNonZeroU128:
-        NonZeroU128,
-        NonZeroU128_le,
-        NonZeroU128_ule,
-        NonZeroU128_be,
-        NonZeroU128_ube;
 }
 
 /// The native type that `NonZeroIsize` is converted to for archiving.
@@ -194,3 +185,31 @@ pub type ArchivedNonZeroUsize = match_pointer_width!(
     ArchivedNonZeroU32,
     ArchivedNonZeroU64
 );
+
+/// Returns the native `u16` which has the same bytes as `value` archived.
+///
+/// This is used for the discriminants of archived enums with `u16` tags, so
+/// that their tags have the same endianness as other archived primitives.
+/// Calling it on its own result returns the original value.
+#[inline]
+pub const fn archived_tag_u16(value: u16) -> u16 {
+    if cfg!(feature = "big_endian") {
+        value.to_be()
+    } else {
+        value.to_le()
+    }
+}
+
+/// Returns the native `u32` which has the same bytes as `value` archived.
+///
+/// This is used for the discriminants of archived enums with `u32` tags, so
+/// that their tags have the same endianness as other archived primitives.
+/// Calling it on its own result returns the original value.
+#[inline]
+pub const fn archived_tag_u32(value: u32) -> u32 {
+    if cfg!(feature = "big_endian") {
+        value.to_be()
+    } else {
+        value.to_le()
+    }
+}
@@ -0,0 +1,415 @@
+//! Archived 128-bit integers with a fixed alignment.
+//!
+//! The alignment of the native `u128` and `i128` types differs between targets
+//! and compiler versions, so their archived versions have a fixed alignment
+//! of 8 instead. When the `unaligned` feature is enabled, they have an
+//! alignment of 1 like all other archived primitives. They are always 16 bytes
+//! long, and their bytes are ordered according to the `big_endian` feature.
+
+use core::{cmp, fmt, hash};
+
+macro_rules! define_archived_wide_integer {
+    ($archived:ident: $native:ty) => {
+        #[doc = concat!(
+            "The archived version of `",
+            stringify!($native),
+            "`.",
+        )]
+        ///
+        /// This has a size of 16 and an alignment of 8, or 1 when the
+        /// `unaligned` feature is enabled.
+        #[derive(Clone, Copy, Default)]
+        #[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+        #[cfg_attr(not(feature = "unaligned"), repr(C, align(8)))]
+        #[cfg_attr(feature = "unaligned", repr(C))]
+        pub struct $archived {
+            bytes: [u8; 16],
+        }
+
+        impl $archived {
+            #[doc = concat!(
+                "Returns an archived `",
+                stringify!($native),
+                "` containing the given value.",
+            )]
+            #[inline]
+            pub const fn from_native(value: $native) -> Self {
+                let bytes = if cfg!(feature = "big_endian") {
+                    value.to_be_bytes()
+                } else {
+                    value.to_le_bytes()
+                };
+                Self { bytes }
+            }
+
+            #[doc = concat!(
+                "Returns the value of the archived `",
+                stringify!($native),
+                "`.",
+            )]
+            #[inline]
+            pub const fn to_native(self) -> $native {
+                if cfg!(feature = "big_endian") {
+                    <$native>::from_be_bytes(self.bytes)
+                } else {
+                    <$native>::from_le_bytes(self.bytes)
+                }
+            }
+        }
+
+        impl From<$native> for $archived {
+            #[inline]
+            fn from(value: $native) -> Self {
+                Self::from_native(value)
+            }
+        }
+
+        impl From<$archived> for $native {
+            #[inline]
+            fn from(value: $archived) -> Self {
+                value.to_native()
+            }
+        }
+
+        impl_wide_traits!($archived: $native);
+    };
+}
+
+macro_rules! define_archived_wide_nonzero {
+    ($archived:ident: $nonzero:ty, $native:ty) => {
+        #[doc = concat!(
+            "The archived version of `",
+            stringify!($nonzero),
+            "`.",
+        )]
+        ///
+        /// This has a size of 16 and an alignment of 8, or 1 when the
+        /// `unaligned` feature is enabled.
+        #[derive(Clone, Copy)]
+        #[cfg_attr(not(feature = "unaligned"), repr(C, align(8)))]
+        #[cfg_attr(feature = "unaligned", repr(C))]
+        pub struct $archived {
+            bytes: [u8; 16],
+        }
+
+        impl $archived {
+            #[doc = concat!(
+                "Returns an archived `",
+                stringify!($nonzero),
+                "` if the given value is not zero.",
+            )]
+            #[inline]
+            pub const fn new(value: $native) -> Option<Self> {
+                if value != 0 {
+                    // SAFETY: We just checked that `value` is not zero.
+                    Some(unsafe { Self::new_unchecked(value) })
+                } else {
+                    None
+                }
+            }
+
+            #[doc = concat!(
+                "Returns an archived `",
+                stringify!($nonzero),
+                "` without checking whether the given value is zero.",
+            )]
+            ///
+            /// # Safety
+            ///
+            /// `value` must not be zero.
+            #[inline]
+            pub const unsafe fn new_unchecked(value: $native) -> Self {
+                let bytes = if cfg!(feature = "big_endian") {
+                    value.to_be_bytes()
+                } else {
+                    value.to_le_bytes()
+                };
+                Self { bytes }
+            }
+
+            #[doc = concat!(
+                "Returns an archived `",
+                stringify!($nonzero),
+                "` containing the given value.",
+            )]
+            #[inline]
+            pub const fn from_native(value: $nonzero) -> Self {
+                // SAFETY: `value` is not zero.
+                unsafe { Self::new_unchecked(value.get()) }
+            }
+
+            #[doc = concat!(
+                "Returns the value of the archived `",
+                stringify!($nonzero),
+                "`.",
+            )]
+            #[inline]
+            pub const fn to_native(self) -> $nonzero {
+                // SAFETY: Archived nonzero integers are never zero.
+                unsafe { <$nonzero>::new_unchecked(self.get()) }
+            }
+
+            /// Returns the value as a primitive type.
+            #[inline]
+            pub const fn get(self) -> $native {
+                if cfg!(feature = "big_endian") {
+                    <$native>::from_be_bytes(self.bytes)
+                } else {
+                    <$native>::from_le_bytes(self.bytes)
+                }
+            }
+        }
+
+        impl From<$nonzero> for $archived {
+            #[inline]
+            fn from(value: $nonzero) -> Self {
+                Self::from_native(value)
+            }
+        }
+
+        impl From<$archived> for $nonzero {
+            #[inline]
+            fn from(value: $archived) -> Self {
+                value.to_native()
+            }
+        }
+
+        #[cfg(feature = "bytecheck")]
+        // SAFETY: `check_bytes` only returns `Ok` if the value is not zero.
+        unsafe impl<C> bytecheck::CheckBytes<C> for $archived
+        where
+            C: rancor::Fallible + ?Sized,
+            C::Error: rancor::Source,
+        {
+            unsafe fn check_bytes(
+                value: *const Self,
+                _: &mut C,
+            ) -> Result<(), C::Error> {
+                // SAFETY: The caller has guaranteed that `value` is aligned
+                // and points to enough bytes for `Self`.
+                let bytes = unsafe { &(*value).bytes };
+                if *bytes == [0; 16] {
+                    rancor::fail!(ZeroError);
+                }
+                Ok(())
+            }
+        }
+
+        impl_wide_traits!($archived: $nonzero);
+    };
+}
+
+macro_rules! impl_wide_traits {
+    ($archived:ident : $native:ty) => {
+        impl fmt::Debug for $archived {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Debug::fmt(&self.to_native(), f)
+            }
+        }
+
+        impl fmt::Display for $archived {
+            #[inline]
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.to_native(), f)
+            }
+        }
+
+        impl hash::Hash for $archived {
+            #[inline]
+            fn hash<H: hash::Hasher>(&self, state: &mut H) {
+                self.to_native().hash(state)
+            }
+        }
+
+        impl Eq for $archived {}
+
+        impl PartialEq for $archived {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.bytes == other.bytes
+            }
+        }
+
+        impl PartialEq<$native> for $archived {
+            #[inline]
+            fn eq(&self, other: &$native) -> bool {
+                self.to_native() == *other
+            }
+        }
+
+        impl PartialEq<$archived> for $native {
+            #[inline]
+            fn eq(&self, other: &$archived) -> bool {
+                *self == other.to_native()
+            }
+        }
+
+        impl Ord for $archived {
+            #[inline]
+            fn cmp(&self, other: &Self) -> cmp::Ordering {
+                self.to_native().cmp(&other.to_native())
+            }
+        }
+
+        impl PartialOrd for $archived {
+            #[inline]
+            fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl PartialOrd<$native> for $archived {
+            #[inline]
+            fn partial_cmp(&self, other: &$native) -> Option<cmp::Ordering> {
+                Some(self.to_native().cmp(other))
+            }
+        }
+
+        impl PartialOrd<$archived> for $native {
+            #[inline]
+            fn partial_cmp(&self, other: &$archived) -> Option<cmp::Ordering> {
+                Some(self.cmp(&other.to_native()))
+            }
+        }
+    };
+}
+
+/// An error resulting from an archived nonzero integer which was zero.
+#[cfg(feature = "bytecheck")]
+#[derive(Debug)]
+struct ZeroError;
+
+#[cfg(feature = "bytecheck")]
+impl fmt::Display for ZeroError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nonzero integer is zero")
+    }
+}
+
+#[cfg(all(feature = "bytecheck", feature = "std"))]
+impl std::error::Error for ZeroError {}
+
+define_archived_wide_integer!(ArchivedI128: i128);
+define_archived_wide_integer!(ArchivedU128: u128);
+define_archived_wide_nonzero!(
+    ArchivedNonZeroI128: ::core::num::NonZeroI128, i128
+);
+define_archived_wide_nonzero!(
+    ArchivedNonZeroU128: ::core::num::NonZeroU128, u128
+);
+
+#[cfg(test)]
+mod tests {
+    use core::{
+        mem::{align_of, size_of},
+        num::{NonZeroI128, NonZeroU128},
+    };
+
+    use super::{
+        ArchivedI128, ArchivedNonZeroI128, ArchivedNonZeroU128, ArchivedU128,
+    };
+
+    #[cfg(not(feature = "unaligned"))]
+    const ALIGN: usize = 8;
+    #[cfg(feature = "unaligned")]
+    const ALIGN: usize = 1;
+
+    #[test]
+    fn layout() {
+        assert_eq!(size_of::<ArchivedI128>(), 16);
+        assert_eq!(size_of::<ArchivedU128>(), 16);
+        assert_eq!(size_of::<ArchivedNonZeroI128>(), 16);
+        assert_eq!(size_of::<ArchivedNonZeroU128>(), 16);
+
+        assert_eq!(align_of::<ArchivedI128>(), ALIGN);
+        assert_eq!(align_of::<ArchivedU128>(), ALIGN);
+        assert_eq!(align_of::<ArchivedNonZeroI128>(), ALIGN);
+        assert_eq!(align_of::<ArchivedNonZeroU128>(), ALIGN);
+    }
+
+    #[test]
+    fn round_trip() {
+        for value in
+            [0, 1, u128::MAX, 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10]
+        {
+            let archived = ArchivedU128::from_native(value);
+            assert_eq!(archived.to_native(), value);
+            assert_eq!(archived, value);
+            assert_eq!(value, archived);
+        }
+
+        for value in [0, -1, i128::MIN, i128::MAX] {
+            let archived = ArchivedI128::from_native(value);
+            assert_eq!(archived.to_native(), value);
+            assert_eq!(archived, value);
+        }
+
+        let value = NonZeroU128::new(u128::MAX).unwrap();
+        assert_eq!(ArchivedNonZeroU128::from_native(value).to_native(), value);
+        let value = NonZeroI128::new(i128::MIN).unwrap();
+        assert_eq!(ArchivedNonZeroI128::from_native(value).to_native(), value);
+
+        assert!(ArchivedNonZeroU128::new(0).is_none());
+        assert!(ArchivedNonZeroI128::new(0).is_none());
+    }
+
+    #[test]
+    fn comparisons() {
+        let a = ArchivedI128::from_native(-1);
+        let b = ArchivedI128::from_native(1);
+        assert!(a < b);
+        assert!(a < 0);
+        assert!(0 > a);
+        assert_eq!(a.max(b), b);
+
+        let a = ArchivedNonZeroU128::new(1 << 64).unwrap();
+        let b = ArchivedNonZeroU128::new(1).unwrap();
+        assert!(a > b);
+        assert!(a > NonZeroU128::new(2).unwrap());
+    }
+
+    #[test]
+    fn fixtures() {
+        const LITTLE_ENDIAN: [u8; 16] = [
+            0x10, 0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08, 0x07, 0x06,
+            0x05, 0x04, 0x03, 0x02, 0x01,
+        ];
+        const BIG_ENDIAN: [u8; 16] = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+        ];
+
+        let value = 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10u128;
+        let bytes = if cfg!(feature = "big_endian") {
+            BIG_ENDIAN
+        } else {
+            LITTLE_ENDIAN
+        };
+
+        assert_eq!(ArchivedU128::from_native(value).bytes, bytes);
+        assert_eq!(ArchivedI128::from_native(value as i128).bytes, bytes);
+        assert_eq!(ArchivedNonZeroU128::new(value).unwrap().bytes, bytes);
+        assert_eq!(
+            ArchivedNonZeroI128::new(value as i128).unwrap().bytes,
+            bytes,
+        );
+    }
+
+    #[cfg(all(feature = "bytecheck", feature = "alloc"))]
+    #[test]
+    fn check_nonzero() {
+        use rancor::Failure;
+
+        use crate::{access, util::Align};
+
+        let mut bytes = Align([0u8; 16]);
+        assert!(access::<ArchivedNonZeroU128, Failure>(&*bytes).is_err());
+        assert!(access::<ArchivedNonZeroI128, Failure>(&*bytes).is_err());
+
+        bytes[15] = 1;
+        assert!(access::<ArchivedNonZeroU128, Failure>(&*bytes).is_ok());
+        assert!(access::<ArchivedNonZeroI128, Failure>(&*bytes).is_ok());
+        assert!(access::<ArchivedU128, Failure>(&*bytes).is_ok());
+    }
+}
@@ -8,6 +8,8 @@ use munge::munge;
 use rancor::Fallible;
 
 use crate::{
+    collections::btree_map::LookupOrd,
+    hash::LookupKey,
     place::Initialized,
     ser::{Sharing, SharingExt, Writer, WriterExt as _},
     ArchivePointee, ArchiveUnsized, Place, Portable, RelPtr, SerializeUnsized,
@@ -99,6 +101,23 @@ impl<T: ArchivePointee + ?Sized, F> Borrow<T> for ArchivedRc<T, F> {
     }
 }
 
+impl<T, F> LookupKey<ArchivedRc<T, F>> for T
+where
+    T: ArchivePointee + hash::Hash + Eq + ?Sized,
+{
+    #[inline]
+    fn equivalent(&self, key: &ArchivedRc<T, F>) -> bool {
+        self == key.get()
+    }
+}
+
+impl<T: ArchivePointee + Ord + ?Sized, F> LookupOrd<ArchivedRc<T, F>> for T {
+    #[inline]
+    fn compare(&self, key: &ArchivedRc<T, F>) -> cmp::Ordering {
+        self.cmp(key.get())
+    }
+}
+
 impl<T: ArchivePointee + fmt::Debug + ?Sized, F> fmt::Debug
     for ArchivedRc<T, F>
 {
@@ -0,0 +1,347 @@
+//! Registries of archived types selected by name at runtime.
+//!
+//! Plugin systems often store archived payloads next to the name of the type
+//! they were serialized from, and only learn which concrete type to read at
+//! runtime. An [`ArchiveRegistry`] maps those names to functions which
+//! validate and deserialize each registered type, so payloads can be read
+//! without matching over every name by hand. Payloads are always validated
+//! before they are deserialized.
+//!
+//! # Examples
+//!
+//! ```
+//! use rkyv::{
+//!     rancor::Error, registry::ArchiveRegistry, Archive, Deserialize,
+//!     Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+//! struct Greeting {
+//!     message: String,
+//! }
+//!
+//! let mut registry = ArchiveRegistry::<Error>::new();
+//! registry
+//!     .register::<Greeting>("greeting")
+//!     .register::<Vec<u32>>("numbers");
+//!
+//! let value = Greeting {
+//!     message: "hello world".to_string(),
+//! };
+//! let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+//!
+//! let any = registry.deserialize_by_name("greeting", &bytes).unwrap();
+//! assert_eq!(any.downcast_ref::<Greeting>(), Some(&value));
+//! assert!(registry.validate_by_name("greeting", &bytes).is_ok());
+//! assert!(registry.validate_by_name("weather", &bytes).is_err());
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String};
+use core::{any::Any, fmt};
+#[cfg(feature = "std")]
+use std::collections::hash_map;
+
+use bytecheck::CheckBytes;
+#[cfg(not(feature = "std"))]
+use hashbrown::hash_map;
+use rancor::{fail, Source, Strategy};
+
+use crate::{
+    access,
+    de::Pool,
+    deserialize,
+    util::{align_or_copy, AlignedVec},
+    validation::validators::DefaultValidator,
+    Archive, Deserialize,
+};
+
+/// An error which occurs when no type is registered with a name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnregisteredName {
+    name: String,
+}
+
+impl UnregisteredName {
+    /// Returns the name which no type is registered with.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl fmt::Display for UnregisteredName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no type is registered with the name `{}`", self.name)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnregisteredName {}
+
+struct Entry<E> {
+    type_name: &'static str,
+    validate: fn(&[u8]) -> Result<(), E>,
+    deserialize: fn(&[u8]) -> Result<Box<dyn Any>, E>,
+}
+
+fn validate<T, E>(bytes: &[u8]) -> Result<(), E>
+where
+    T: Archive,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
+    E: Source,
+{
+    let mut scratch = AlignedVec::<16>::new();
+    access::<T::Archived, E>(align_or_copy(bytes, &mut scratch))?;
+    Ok(())
+}
+
+fn deserialize_boxed<T, E>(bytes: &[u8]) -> Result<Box<dyn Any>, E>
+where
+    T: Archive + 'static,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>
+        + Deserialize<T, Strategy<Pool, E>>,
+    E: Source,
+{
+    let mut scratch = AlignedVec::<16>::new();
+    let archived =
+        access::<T::Archived, E>(align_or_copy(bytes, &mut scratch))?;
+    let value = deserialize::<T, _, E>(archived, &mut Pool::new())?;
+    Ok(Box::new(value))
+}
+
+/// A registry of archived types keyed by name.
+///
+/// Each registered type gets a validation and a deserialization function,
+/// which are looked up by name with [`validate_by_name`] and
+/// [`deserialize_by_name`]. Payloads are validated with the
+/// [`DefaultValidator`] and deserialized with a [`Pool`], like
+/// [`from_bytes`](crate::from_bytes). Payloads which are not aligned are
+/// copied into aligned memory first.
+///
+/// The functions are monomorphized for the error type `E`, so all lookups on
+/// a registry return the same error type.
+///
+/// [`validate_by_name`]: ArchiveRegistry::validate_by_name
+/// [`deserialize_by_name`]: ArchiveRegistry::deserialize_by_name
+pub struct ArchiveRegistry<E> {
+    entries: hash_map::HashMap<String, Entry<E>>,
+}
+
+impl<E> Default for ArchiveRegistry<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> ArchiveRegistry<E> {
+    /// Returns a new registry with no types registered.
+    pub fn new() -> Self {
+        Self {
+            entries: hash_map::HashMap::new(),
+        }
+    }
+
+    /// Registers `T` with the given name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if another type is already registered with the same name.
+    pub fn register<T>(&mut self, name: impl Into<String>) -> &mut Self
+    where
+        T: Archive + 'static,
+        T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>
+            + Deserialize<T, Strategy<Pool, E>>,
+        E: Source,
+    {
+        match self.entries.entry(name.into()) {
+            hash_map::Entry::Occupied(e) => panic!(
+                "the name `{}` is already registered to `{}`",
+                e.key(),
+                e.get().type_name,
+            ),
+            hash_map::Entry::Vacant(e) => {
+                e.insert(Entry {
+                    type_name: core::any::type_name::<T>(),
+                    validate: validate::<T, E>,
+                    deserialize: deserialize_boxed::<T, E>,
+                });
+            }
+        }
+        self
+    }
+
+    /// Returns whether a type is registered with the given name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Returns the names of the registered types in an arbitrary order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    /// Returns the number of registered types.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether no types are registered.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<E: Source> ArchiveRegistry<E> {
+    fn entry(&self, name: &str) -> Result<&Entry<E>, E> {
+        match self.entries.get(name) {
+            Some(entry) => Ok(entry),
+            None => fail!(UnregisteredName {
+                name: String::from(name),
+            }),
+        }
+    }
+
+    /// Validates the given bytes as an archive of the type registered with
+    /// `name`.
+    ///
+    /// Returns an error if no type is registered with `name` or if the bytes
+    /// are not a valid archive of that type.
+    pub fn validate_by_name(&self, name: &str, bytes: &[u8]) -> Result<(), E> {
+        (self.entry(name)?.validate)(bytes)
+    }
+
+    /// Validates and deserializes the given bytes as an archive of the type
+    /// registered with `name`.
+    ///
+    /// The returned value can be downcast to the registered type. Returns an
+    /// error if no type is registered with `name`, or if the bytes are not a
+    /// valid archive of that type or can't be deserialized.
+    pub fn deserialize_by_name(
+        &self,
+        name: &str,
+        bytes: &[u8],
+    ) -> Result<Box<dyn Any>, E> {
+        (self.entry(name)?.deserialize)(bytes)
+    }
+}
+
+impl<E> fmt::Debug for ArchiveRegistry<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|(k, v)| (k, v.type_name)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    use core::{fmt::Debug, mem::size_of};
+
+    use rancor::{Error, Panic};
+
+    use super::ArchiveRegistry;
+    use crate::{
+        test::{to_bytes, TestSerializer},
+        util::AlignedVec,
+        Archive, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Config {
+        name: String,
+        retries: u32,
+        verbose: bool,
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    enum Command {
+        Start { delay: u64 },
+        Stop,
+    }
+
+    fn registry() -> ArchiveRegistry<Error> {
+        let mut registry = ArchiveRegistry::new();
+        registry
+            .register::<Config>("config")
+            .register::<Command>("command")
+            .register::<Vec<u32>>("numbers");
+        registry
+    }
+
+    fn roundtrip<T>(registry: &ArchiveRegistry<Error>, name: &str, value: T)
+    where
+        T: Debug + PartialEq + for<'a> Serialize<TestSerializer<'a>> + 'static,
+    {
+        to_bytes(&value, |bytes| {
+            registry.validate_by_name(name, bytes).unwrap();
+            let any = registry.deserialize_by_name(name, bytes).unwrap();
+            assert_eq!(any.downcast_ref::<T>(), Some(&value));
+
+            // Misaligned payloads are copied before they are accessed
+            let mut misaligned = AlignedVec::<16>::new();
+            misaligned.push(0);
+            misaligned.extend_from_slice(bytes);
+            let any = registry
+                .deserialize_by_name(name, &misaligned[1..])
+                .unwrap();
+            assert_eq!(any.downcast_ref::<T>(), Some(&value));
+        });
+    }
+
+    #[test]
+    fn roundtrip_by_name() {
+        let registry = registry();
+        assert_eq!(registry.len(), 3);
+        assert!(registry.contains("config"));
+
+        roundtrip(
+            &registry,
+            "config",
+            Config {
+                name: "a plugin with a long name".to_string(),
+                retries: 3,
+                verbose: true,
+            },
+        );
+        roundtrip(&registry, "command", Command::Start { delay: 1000 });
+        roundtrip(&registry, "command", Command::Stop);
+        roundtrip(&registry, "numbers", vec![1u32, 2, 3, 4]);
+    }
+
+    #[test]
+    fn unknown_name() {
+        let registry = registry();
+        let bytes = crate::to_bytes::<Panic>(&Command::Stop).unwrap();
+
+        let error = registry.validate_by_name("unknown", &bytes).unwrap_err();
+        assert!(error.to_string().contains("`unknown`"));
+        registry.deserialize_by_name("unknown", &bytes).unwrap_err();
+    }
+
+    #[test]
+    fn invalid_payload() {
+        let registry = registry();
+        let mut bytes = crate::to_bytes::<Panic>(&Command::Stop).unwrap();
+        // Overwrite the discriminant of the root
+        let root = bytes.len() - size_of::<ArchivedCommand>();
+        bytes[root] = 0xff;
+
+        registry.validate_by_name("command", &bytes).unwrap_err();
+        registry.deserialize_by_name("command", &bytes).unwrap_err();
+    }
+
+    #[test]
+    #[should_panic = "already registered"]
+    fn duplicate_name() {
+        let mut registry = registry();
+        registry.register::<Config>("numbers");
+    }
+}
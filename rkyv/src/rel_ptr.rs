@@ -19,7 +19,7 @@ use crate::{
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct IsizeOverflow;
+pub(crate) struct IsizeOverflow;
 
 impl fmt::Display for IsizeOverflow {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -30,6 +30,24 @@ impl fmt::Display for IsizeOverflow {
 #[cfg(feature = "std")]
 impl std::error::Error for IsizeOverflow {}
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct OffsetOverflow {
+    offset: isize,
+}
+
+impl fmt::Display for OffsetOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the offset {} overflowed the range of the archived offset type",
+            self.offset,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OffsetOverflow {}
+
 /// A offset that can be used with [`RawRelPtr`].
 pub trait Offset: Copy + Initialized {
     /// Creates a new offset between a `from` position and a `to` position.
@@ -47,7 +65,10 @@ macro_rules! impl_offset_single_byte {
                 // computed offsets cannot overflow an isize, which is why we're
                 // using signed_offset instead of `checked_sub` for unsized
                 // types.
-                Self::try_from(value).into_error()
+                match Self::try_from(value) {
+                    Ok(offset) => Ok(offset),
+                    Err(_) => fail!(OffsetOverflow { offset: value }),
+                }
             }
 
             #[inline]
@@ -71,9 +92,10 @@ macro_rules! impl_offset_multi_byte {
                 // computed offsets cannot overflow an isize, which is why we're
                 // using signed_offset instead of `checked_sub` for unsized
                 // types.
-                Ok(<$archived>::from_native(
-                    <$ty>::try_from(value).into_error()?,
-                ))
+                match <$ty>::try_from(value) {
+                    Ok(offset) => Ok(<$archived>::from_native(offset)),
+                    Err(_) => fail!(OffsetOverflow { offset: value }),
+                }
             }
 
             #[inline]
@@ -113,6 +135,13 @@ impl_offset_multi_byte!(u64, ArchivedU64);
 /// with the **pointee** without invalidating the pointer. However, if either
 /// the **pointer** or the **pointee** move independently, the pointer will be
 /// invalidated.
+///
+/// Because moving a relative pointer out of its archive invalidates it,
+/// `RawRelPtr` is `!Unpin`, and so is every archived type which contains one.
+/// This is what allows the mutation APIs to hand out `Pin<&mut T>` without
+/// letting callers move archived values. Relative pointers are always `Send`
+/// and `Sync`, so archived types which contain them are `Send` and `Sync`
+/// whenever their type parameters are.
 #[derive(Portable)]
 #[rkyv(crate)]
 #[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
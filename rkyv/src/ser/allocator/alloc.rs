@@ -1,51 +1,60 @@
 #[cfg(not(feature = "std"))]
-use alloc::{
-    alloc::{alloc, alloc_zeroed, dealloc},
-    boxed::Box,
-    vec::Vec,
-};
+use alloc::{boxed::Box, vec::Vec};
 use core::{
     alloc::Layout,
+    cmp::Reverse,
     marker::PhantomData,
-    mem::{align_of, size_of, ManuallyDrop},
+    mem::{align_of, size_of, swap, ManuallyDrop},
     ptr::{slice_from_raw_parts_mut, NonNull},
 };
 use std::alloc::handle_alloc_error;
-#[cfg(feature = "std")]
-use std::alloc::{alloc, dealloc};
 
-use crate::ser::Allocator;
+use crate::{
+    ser::Allocator,
+    util::allocator::{self, Global},
+};
 
 struct Block {
     next_ptr: NonNull<Block>,
     next_size: usize,
+    /// Whether this block was allocated for a single oversized allocation.
+    dedicated: bool,
 }
 
 impl Block {
-    fn alloc(size: usize) -> NonNull<Self> {
+    fn alloc<A: allocator::Allocator>(
+        size: usize,
+        dedicated: bool,
+        alloc: &A,
+    ) -> NonNull<Self> {
         debug_assert!(size >= size_of::<Self>());
         let layout = Layout::from_size_align(size, align_of::<Self>()).unwrap();
-        let ptr = unsafe { alloc(layout).cast::<Self>() };
-        let Some(ptr) = NonNull::new(ptr) else {
+        let Ok(ptr) = alloc.allocate(layout) else {
             handle_alloc_error(layout)
         };
+        let ptr = ptr.cast::<Self>();
 
         unsafe {
             ptr.as_ptr().write(Self {
                 next_ptr: ptr,
                 next_size: layout.size(),
+                dedicated,
             });
         }
 
         ptr
     }
 
-    unsafe fn dealloc(ptr: NonNull<Self>, size: usize) {
+    unsafe fn dealloc<A: allocator::Allocator>(
+        ptr: NonNull<Self>,
+        size: usize,
+        alloc: &A,
+    ) {
         let layout = unsafe {
             Layout::from_size_align(size, align_of::<Self>()).unwrap_unchecked()
         };
         unsafe {
-            dealloc(ptr.as_ptr().cast(), layout);
+            alloc.deallocate(ptr.cast(), layout);
         }
     }
 
@@ -75,16 +84,38 @@ impl Block {
 ///
 /// Reusing the same arena for multiple serializations will reduce the number of
 /// global allocations, which can save a considerable amount of time.
-pub struct Arena {
+///
+/// When an allocation doesn't fit in the current block, the arena allocates a
+/// new block twice as large. Allocations which wouldn't fit in that block
+/// either are given a dedicated block of exactly the requested size instead.
+/// Dedicated blocks are freed as soon as their allocation is popped, so very
+/// large allocations don't inflate the retained capacity of the arena. If you
+/// know the peak capacity you need in advance, create the arena with
+/// [`with_capacity`](Self::with_capacity) to avoid growing it at all.
+///
+/// Blocks are allocated with the global allocator by default. Use
+/// [`new_in`](Arena::new_in) to allocate them with a different
+/// [`Allocator`](crate::util::allocator::Allocator), which requires the
+/// `allocator-api2` feature.
+pub struct Arena<A: allocator::Allocator = Global> {
     head_ptr: NonNull<Block>,
+    alloc: A,
 }
 
-impl Drop for Arena {
+// SAFETY: `Arena` exclusively owns its blocks, and all of its methods which
+// modify them require `&mut self`.
+unsafe impl<A: allocator::Allocator + Send> Send for Arena<A> {}
+
+// SAFETY: Shared references to an `Arena` can only be used to read the sizes
+// of its blocks.
+unsafe impl<A: allocator::Allocator + Sync> Sync for Arena<A> {}
+
+impl<A: allocator::Allocator> Drop for Arena<A> {
     fn drop(&mut self) {
         self.shrink();
         let head_size = unsafe { self.head_ptr.as_ref().next_size };
         unsafe {
-            Block::dealloc(self.head_ptr, head_size);
+            Block::dealloc(self.head_ptr, head_size, &self.alloc);
         }
     }
 }
@@ -95,59 +126,116 @@ impl Arena {
 
     /// Creates a new `Arena` with the default capacity.
     pub fn new() -> Self {
-        Self::with_capacity(Self::DEFAULT_CAPACITY)
+        Self::new_in(Global)
     }
 
     /// Creates a new `Arena` with at least the requested capacity.
+    ///
+    /// Allocations which fit in the requested capacity never cause the arena
+    /// to grow.
     pub fn with_capacity(cap: usize) -> Self {
+        Self::with_capacity_in(cap, Global)
+    }
+
+    /// Consumes the `Arena`, returning a raw pointer.
+    pub fn into_raw(self) -> NonNull<()> {
+        let this = ManuallyDrop::new(self);
+        this.head_ptr.cast()
+    }
+
+    /// Constructs an arena from a raw pointer.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must have been returned from `into_raw`. `from_raw` takes
+    /// ownership over the pointer, and so `from_raw` must not be called on the
+    /// same pointer more than once.
+    pub unsafe fn from_raw(raw: NonNull<()>) -> Self {
+        Self {
+            head_ptr: raw.cast(),
+            alloc: Global,
+        }
+    }
+}
+
+impl<A: allocator::Allocator> Arena<A> {
+    /// Creates a new `Arena` with the default capacity which allocates its
+    /// blocks with `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        Self::with_capacity_in(Arena::DEFAULT_CAPACITY, alloc)
+    }
+
+    /// Creates a new `Arena` with at least the requested capacity which
+    /// allocates its blocks with `alloc`.
+    ///
+    /// Allocations which fit in the requested capacity never cause the arena
+    /// to grow.
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
         let head_size = (cap + size_of::<Block>()).next_power_of_two();
-        let head_ptr = Block::alloc(head_size);
-        Self { head_ptr }
+        let head_ptr = Block::alloc(head_size, false, &alloc);
+        Self { head_ptr, alloc }
+    }
+
+    /// Returns a reference to the allocator of the arena.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
     }
 
     /// Cleans up allocated blocks which are no longer in use.
     ///
+    /// Only the largest block which is not dedicated to an oversized
+    /// allocation is kept. Returns the capacity of that block.
+    ///
     /// The arena is automatically shrunk by [`acquire`](Self::acquire).
     pub fn shrink(&mut self) -> usize {
-        let (mut current_ptr, mut current_size) = {
+        let (first_ptr, first_size) = {
             let head = unsafe { self.head_ptr.as_ref() };
             (head.next_ptr, head.next_size)
         };
 
+        // The head block is never dedicated, so there is always a block to
+        // keep.
+        let (mut keep_ptr, mut keep_size) = (self.head_ptr, 0);
+        let (mut current_ptr, mut current_size) = (first_ptr, first_size);
         loop {
-            let current = unsafe { current_ptr.as_mut() };
-
-            if current.next_ptr == current_ptr {
-                // There was only one block in the loop. No deallocating needed.
+            // SAFETY: Every block in the loop starting at `head_ptr` is valid,
+            // and none of them have been freed yet.
+            let current = unsafe { current_ptr.as_ref() };
+            if !current.dedicated && current_size >= keep_size {
+                keep_ptr = current_ptr;
+                keep_size = current_size;
+            }
+            if current_ptr == self.head_ptr {
                 break;
             }
+            current_ptr = current.next_ptr;
+            current_size = current.next_size;
+        }
 
-            let next_ptr = current.next_ptr;
-            let next_size = current.next_size;
-
-            if next_ptr == self.head_ptr {
-                // End of the loop. Free the head block.
+        let (mut current_ptr, mut current_size) = (first_ptr, first_size);
+        loop {
+            let current = unsafe { current_ptr.as_ref() };
+            let (next_ptr, next_size) = (current.next_ptr, current.next_size);
+            let is_head = current_ptr == self.head_ptr;
+            if current_ptr != keep_ptr {
                 unsafe {
-                    Block::dealloc(next_ptr, next_size);
+                    Block::dealloc(current_ptr, current_size, &self.alloc);
                 }
-
-                // Loop the head back on itself.
-                current.next_ptr = current_ptr;
-                current.next_size = current_size;
-                self.head_ptr = current_ptr;
-
-                break;
             }
-
-            unsafe {
-                Block::dealloc(current_ptr, current_size);
+            if is_head {
+                break;
             }
-
             current_ptr = next_ptr;
             current_size = next_size;
         }
 
-        current_size - size_of::<Block>()
+        // Loop the kept block back on itself.
+        let keep = unsafe { keep_ptr.as_mut() };
+        keep.next_ptr = keep_ptr;
+        keep.next_size = keep_size;
+        self.head_ptr = keep_ptr;
+
+        keep_size - size_of::<Block>()
     }
 
     /// Returns the available capacity of the arena.
@@ -164,35 +252,157 @@ impl Arena {
 
     /// Acquires a handle to the arena.
     ///
-    /// The returned handle has exclusive allocation rights in the arena.
-    pub fn acquire(&mut self) -> ArenaHandle<'_> {
+    /// The returned handle has exclusive allocation rights in the arena. Use
+    /// [`acquire_n`](Self::acquire_n) to allocate from multiple handles at the
+    /// same time.
+    pub fn acquire(&mut self) -> ArenaHandle<'_, A> {
         self.shrink();
 
         ArenaHandle {
             tail_ptr: self.head_ptr,
             tail_size: unsafe { self.head_ptr.as_ref().next_size },
             used: size_of::<Block>(),
+            alloc: &self.alloc,
             _phantom: PhantomData,
         }
     }
 
-    /// Consumes the `Arena`, returning a raw pointer.
-    pub fn into_raw(self) -> NonNull<()> {
-        let this = ManuallyDrop::new(self);
-        this.head_ptr.cast()
-    }
-
-    /// Constructs an arena from a raw pointer.
+    /// Acquires `N` independent handles to the arena and calls `f` with them.
     ///
-    /// # Safety
+    /// Each handle has exclusive allocation rights in its own part of the
+    /// arena, so the handles can be used at the same time to serialize
+    /// independent values, for example on scoped threads. The handles can't
+    /// outlive `f`. After `f` returns, the blocks allocated by every handle are
+    /// returned to the arena, and so [`shrink`](Self::shrink) and later
+    /// acquisitions can reuse them.
     ///
-    /// `raw` must have been returned from `into_raw`. `from_raw` takes
-    /// ownership over the pointer, and so `from_raw` must not be called on the
-    /// same pointer more than once.
-    pub unsafe fn from_raw(raw: NonNull<()>) -> Self {
-        Self {
-            head_ptr: raw.cast(),
+    /// The largest `N` blocks in the arena are kept, one for each handle, and
+    /// the rest are freed. If the arena has fewer than `N` blocks, new blocks
+    /// of the same size as the largest one are allocated for the remaining
+    /// handles.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rkyv::{
+    ///     access,
+    ///     rancor::Error,
+    ///     ser::{allocator::Arena, sharing::Share, Serializer},
+    ///     util::{serialize_into, AlignedVec},
+    ///     Archived,
+    /// };
+    ///
+    /// let mut arena = Arena::new();
+    /// let (left, right) = arena.acquire_n(|[a, b]| {
+    ///     std::thread::scope(|s| {
+    ///         let left = s.spawn(|| {
+    ///             let writer = AlignedVec::<16>::new();
+    ///             let serializer = Serializer::new(writer, a, Share::new());
+    ///             serialize_into::<_, Error>(&vec![1, 2, 3], serializer)
+    ///         });
+    ///         let right = s.spawn(|| {
+    ///             let writer = AlignedVec::<16>::new();
+    ///             let serializer = Serializer::new(writer, b, Share::new());
+    ///             serialize_into::<_, Error>(&vec![4, 5, 6], serializer)
+    ///         });
+    ///         (left.join().unwrap(), right.join().unwrap())
+    ///     })
+    /// });
+    ///
+    /// let left = left.unwrap().into_writer();
+    /// let archived = access::<Archived<Vec<i32>>, Error>(&left).unwrap();
+    /// assert_eq!(archived.as_slice(), [1, 2, 3]);
+    ///
+    /// let right = right.unwrap().into_writer();
+    /// let archived = access::<Archived<Vec<i32>>, Error>(&right).unwrap();
+    /// assert_eq!(archived.as_slice(), [4, 5, 6]);
+    /// ```
+    pub fn acquire_n<const N: usize, R, F>(&mut self, f: F) -> R
+    where
+        F: for<'h> FnOnce([ArenaHandle<'h, A>; N]) -> R,
+    {
+        if N == 0 {
+            return f(core::array::from_fn(|_| unreachable!()));
         }
+
+        // Each handle gets a separate loop of blocks, so handles never read or
+        // write the headers of blocks which other handles own. The loops are
+        // joined back together when `f` returns, even if it panics.
+        let blocks = Self::detach_blocks(self.head_ptr, &self.alloc);
+        let _rejoin = Rejoin {
+            head_ptr: &mut self.head_ptr,
+            blocks,
+        };
+        let handles = blocks.map(|(tail_ptr, tail_size)| ArenaHandle {
+            tail_ptr,
+            tail_size,
+            used: size_of::<Block>(),
+            alloc: &self.alloc,
+            _phantom: PhantomData,
+        });
+
+        f(handles)
+    }
+
+    /// Splits the loop of blocks starting at `head_ptr` into `N` loops of one
+    /// block each, freeing the blocks which are not kept.
+    fn detach_blocks<const N: usize>(
+        head_ptr: NonNull<Block>,
+        alloc: &A,
+    ) -> [(NonNull<Block>, usize); N] {
+        let mut blocks = Vec::new();
+        let (mut current_ptr, mut current_size) = {
+            // SAFETY: `head_ptr` is the head block of an arena, which is always
+            // a valid block.
+            let head = unsafe { head_ptr.as_ref() };
+            (head.next_ptr, head.next_size)
+        };
+        loop {
+            let current = unsafe { current_ptr.as_ref() };
+            blocks.push((current_ptr, current_size, current.dedicated));
+            if current_ptr == head_ptr {
+                break;
+            }
+            current_ptr = current.next_ptr;
+            current_size = current.next_size;
+        }
+
+        // Sort the blocks which are not dedicated first, from largest to
+        // smallest. The head block is never dedicated, so the first block is
+        // always one which can be kept.
+        blocks.sort_unstable_by_key(|&(_, size, dedicated)| {
+            Reverse((!dedicated, size))
+        });
+        let keep = blocks
+            .iter()
+            .take(N)
+            .take_while(|(_, _, dedicated)| !dedicated)
+            .count();
+        for &(ptr, size, _) in &blocks[keep..] {
+            // SAFETY: Each block appears in `blocks` exactly once and was
+            // allocated by `alloc` with `size` bytes. Blocks which are not kept
+            // are never accessed again, even though they are still linked to
+            // by other blocks: the blocks which are kept are relinked below.
+            unsafe {
+                Block::dealloc(ptr, size, alloc);
+            }
+        }
+
+        let largest_size = blocks[0].1;
+        core::array::from_fn(|i| {
+            if i < keep {
+                let (mut ptr, size, _) = blocks[i];
+                // Loop the kept block back on itself.
+                // SAFETY: `ptr` is a kept block, so it has not been freed, and
+                // nothing else references it while the loops are being split.
+                let block = unsafe { ptr.as_mut() };
+                block.next_ptr = ptr;
+                block.next_size = size;
+                (ptr, size)
+            } else {
+                (Block::alloc(largest_size, false, alloc), largest_size)
+            }
+        })
     }
 }
 
@@ -202,15 +412,48 @@ impl Default for Arena {
     }
 }
 
+/// Joins the separate loops of blocks used by the handles from
+/// [`Arena::acquire_n`] back into a single loop when dropped.
+struct Rejoin<'a, const N: usize> {
+    head_ptr: &'a mut NonNull<Block>,
+    blocks: [(NonNull<Block>, usize); N],
+}
+
+impl<const N: usize> Drop for Rejoin<'_, N> {
+    fn drop(&mut self) {
+        // Handles never unlink the block they started with, so each of these
+        // blocks is still part of a separate loop. Swapping the next pointers
+        // of blocks in two separate loops joins them into a single loop.
+        let (mut first_ptr, _) = self.blocks[0];
+        for &(mut other_ptr, _) in &self.blocks[1..] {
+            // SAFETY: The handles have been dropped by the time `Rejoin` is
+            // dropped, so nothing else references these blocks. They are
+            // distinct blocks, so the two mutable references don't alias.
+            let first = unsafe { first_ptr.as_mut() };
+            // SAFETY: See above.
+            let other = unsafe { other_ptr.as_mut() };
+            swap(&mut first.next_ptr, &mut other.next_ptr);
+            swap(&mut first.next_size, &mut other.next_size);
+        }
+        *self.head_ptr = first_ptr;
+    }
+}
+
 /// A handle which can allocate within an arena.
-pub struct ArenaHandle<'a> {
+pub struct ArenaHandle<'a, A: allocator::Allocator = Global> {
     tail_ptr: NonNull<Block>,
     tail_size: usize,
     used: usize,
-    _phantom: PhantomData<&'a mut Arena>,
+    alloc: &'a A,
+    _phantom: PhantomData<&'a mut Arena<A>>,
 }
 
-unsafe impl<E> Allocator<E> for ArenaHandle<'_> {
+// SAFETY: An `ArenaHandle` only ever accesses the blocks which it has exclusive
+// allocation rights over, so it can be sent to other threads as long as the
+// allocator can be shared between them.
+unsafe impl<A: allocator::Allocator + Sync> Send for ArenaHandle<'_, A> {}
+
+unsafe impl<E, A: allocator::Allocator> Allocator<E> for ArenaHandle<'_, A> {
     unsafe fn push_alloc(
         &mut self,
         layout: Layout,
@@ -219,14 +462,27 @@ unsafe impl<E> Allocator<E> for ArenaHandle<'_> {
         let pad = 0usize.wrapping_sub(pos) % layout.align();
         if pad + layout.size() <= self.tail_size - self.used {
             self.used += pad;
+        } else if size_of::<Block>() + layout.size() + layout.align()
+            > 2 * self.tail_size
+        {
+            // Allocation request is too large to fit in a doubled block, so
+            // allocate a dedicated block for it. The tail block stays the same
+            // so that later allocations can keep using it.
+            let size = size_of::<Block>() + layout.size() + layout.align();
+            let block = Block::alloc(size, true, self.alloc);
+            unsafe {
+                Block::push_next(self.tail_ptr, block);
+            }
+            let pos = block.as_ptr() as usize + size_of::<Block>();
+            let pad = 0usize.wrapping_sub(pos) % layout.align();
+            let ptr = (pos + pad) as *mut u8;
+            let slice_ptr = slice_from_raw_parts_mut(ptr, layout.size());
+            // SAFETY: `slice_ptr` is offset from `block`, which is never null.
+            return Ok(unsafe { NonNull::new_unchecked(slice_ptr) });
         } else {
             // Allocation request is too large, allocate a new block
-            let size = usize::max(
-                2 * self.tail_size,
-                (size_of::<Block>() + layout.size() + layout.align())
-                    .next_power_of_two(),
-            );
-            let next = Block::alloc(size);
+            let size = 2 * self.tail_size;
+            let next = Block::alloc(size, false, self.alloc);
             unsafe {
                 Block::push_next(self.tail_ptr, next);
             }
@@ -253,8 +509,50 @@ unsafe impl<E> Allocator<E> for ArenaHandle<'_> {
         ptr: NonNull<u8>,
         _: Layout,
     ) -> Result<(), E> {
-        let bytes = self.tail_ptr.as_ptr().cast::<u8>();
-        self.used = ptr.as_ptr() as usize - bytes as usize;
+        let addr = ptr.as_ptr() as usize;
+        // Allocations never start at the beginning of a block because the
+        // block header is stored there, but zero-sized allocations may start
+        // at the end of one.
+        let contains = |block: NonNull<Block>, size: usize| {
+            let start = block.as_ptr() as usize;
+            start < addr && addr <= start + size
+        };
+
+        if !contains(self.tail_ptr, self.tail_size) {
+            let mut prev_ptr = self.tail_ptr;
+            let (mut block_ptr, mut block_size) = {
+                let tail = unsafe { self.tail_ptr.as_ref() };
+                (tail.next_ptr, tail.next_size)
+            };
+            while !contains(block_ptr, block_size) {
+                let block = unsafe { block_ptr.as_ref() };
+                prev_ptr = block_ptr;
+                block_ptr = block.next_ptr;
+                block_size = block.next_size;
+            }
+
+            let block = unsafe { block_ptr.as_ref() };
+            if block.dedicated {
+                // The allocation had a dedicated block, which is no longer in
+                // use. Dedicated blocks are never the head or tail block, so
+                // it can be unlinked and freed right away.
+                let prev = unsafe { prev_ptr.as_mut() };
+                prev.next_ptr = block.next_ptr;
+                prev.next_size = block.next_size;
+                unsafe {
+                    Block::dealloc(block_ptr, block_size, self.alloc);
+                }
+                return Ok(());
+            }
+
+            // The allocation was made in an earlier block, so make that block
+            // the tail again. Later blocks stay in the loop and are deallocated
+            // when the arena is shrunk.
+            self.tail_ptr = block_ptr;
+            self.tail_size = block_size;
+        }
+
+        self.used = addr - self.tail_ptr.as_ptr() as usize;
 
         Ok(())
     }
@@ -262,13 +560,41 @@ unsafe impl<E> Allocator<E> for ArenaHandle<'_> {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    use core::alloc::Layout;
+
     use rancor::Panic;
 
     use crate::{
-        ser::{allocator::Arena, sharing::Share, Serializer},
+        ser::{
+            allocator::Arena, sharing::Share, Allocator, DefaultSerializer,
+            Serializer,
+        },
         util::{serialize_into, AlignedVec},
+        Serialize,
     };
 
+    fn serialize_with_arena<T>(value: &T, arena: &mut Arena)
+    where
+        T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Panic>>,
+    {
+        serialize_into::<_, Panic>(
+            value,
+            Serializer::new(
+                AlignedVec::<16>::new(),
+                arena.acquire(),
+                Share::new(),
+            ),
+        )
+        .unwrap();
+    }
+
     #[test]
     fn reuse_arena() {
         let mut bytes = AlignedVec::<16>::with_capacity(1024);
@@ -293,4 +619,286 @@ mod tests {
             .unwrap();
         }
     }
+
+    #[test]
+    fn oversized_allocations() {
+        let mut arena = Arena::new();
+        let capacity = arena.capacity();
+
+        let mut handle = arena.acquire();
+        let small = Layout::new::<[u8; 64]>();
+        let huge = Layout::from_size_align(1 << 20, 16).unwrap();
+        unsafe {
+            let a = Allocator::<Panic>::push_alloc(&mut handle, small).unwrap();
+            let b = Allocator::<Panic>::push_alloc(&mut handle, huge).unwrap();
+            let c = Allocator::<Panic>::push_alloc(&mut handle, small).unwrap();
+            assert_eq!(b.cast::<u8>().as_ptr() as usize % 16, 0);
+
+            a.cast::<u8>().as_ptr().write_bytes(1, small.size());
+            b.cast::<u8>().as_ptr().write_bytes(2, huge.size());
+            c.cast::<u8>().as_ptr().write_bytes(3, small.size());
+            assert!(a.as_ref().iter().all(|&x| x == 1));
+            assert!(b.as_ref().iter().all(|&x| x == 2));
+            assert!(c.as_ref().iter().all(|&x| x == 3));
+
+            Allocator::<Panic>::pop_alloc(&mut handle, c.cast(), small)
+                .unwrap();
+            Allocator::<Panic>::pop_alloc(&mut handle, b.cast(), huge).unwrap();
+            Allocator::<Panic>::pop_alloc(&mut handle, a.cast(), small)
+                .unwrap();
+
+            // The tail block is reused after the dedicated block is freed
+            let d = Allocator::<Panic>::push_alloc(&mut handle, small).unwrap();
+            assert_eq!(d.cast::<u8>(), a.cast::<u8>());
+        }
+
+        assert_eq!(arena.shrink(), capacity);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn acquire_n_scoped_threads() {
+        use crate::{access, rancor::Failure, Archived};
+
+        let left = (0..1000).map(|i| i.to_string()).collect::<Vec<_>>();
+        let right = (0..1000)
+            .map(|i| format!("a string which is not inline {}", i))
+            .collect::<Vec<_>>();
+
+        let mut arena = Arena::new();
+        let capacity = arena.capacity();
+        for _ in 0..4 {
+            let (left_bytes, right_bytes) = arena.acquire_n(|[a, b]| {
+                std::thread::scope(|s| {
+                    let left_thread = s.spawn(|| {
+                        serialize_into::<_, Panic>(
+                            &left,
+                            Serializer::new(
+                                AlignedVec::<16>::new(),
+                                a,
+                                Share::new(),
+                            ),
+                        )
+                        .unwrap()
+                        .into_writer()
+                    });
+                    let right_thread = s.spawn(|| {
+                        serialize_into::<_, Panic>(
+                            &right,
+                            Serializer::new(
+                                AlignedVec::<16>::new(),
+                                b,
+                                Share::new(),
+                            ),
+                        )
+                        .unwrap()
+                        .into_writer()
+                    });
+                    (left_thread.join().unwrap(), right_thread.join().unwrap())
+                })
+            });
+
+            let archived =
+                access::<Archived<Vec<String>>, Failure>(&left_bytes).unwrap();
+            assert_eq!(archived, &left);
+            let archived =
+                access::<Archived<Vec<String>>, Failure>(&right_bytes).unwrap();
+            assert_eq!(archived, &right);
+        }
+
+        // Every block was returned to the arena, and shrinking keeps the
+        // largest one.
+        assert!(arena.shrink() >= capacity);
+        serialize_with_arena(&left, &mut arena);
+    }
+
+    #[test]
+    fn acquire_n_reuses_blocks() {
+        let mut arena = Arena::new();
+        let capacity = arena.capacity();
+
+        let small = Layout::new::<[u8; 64]>();
+        let ptrs = arena.acquire_n::<3, _, _>(|handles| {
+            handles.map(|mut handle| unsafe {
+                Allocator::<Panic>::push_alloc(&mut handle, small)
+                    .unwrap()
+                    .cast::<u8>()
+            })
+        });
+        assert_ne!(ptrs[0], ptrs[1]);
+        assert_ne!(ptrs[1], ptrs[2]);
+        assert_ne!(ptrs[0], ptrs[2]);
+
+        // The same three blocks are handed out again
+        let again = arena.acquire_n::<3, _, _>(|handles| {
+            handles.map(|mut handle| unsafe {
+                Allocator::<Panic>::push_alloc(&mut handle, small)
+                    .unwrap()
+                    .cast::<u8>()
+            })
+        });
+        let mut sorted = ptrs;
+        sorted.sort();
+        let mut again_sorted = again;
+        again_sorted.sort();
+        assert_eq!(sorted, again_sorted);
+
+        assert_eq!(arena.acquire_n(|[]| 0), 0);
+        assert_eq!(arena.shrink(), capacity);
+    }
+
+    #[test]
+    fn huge_vec_retained_capacity() {
+        let value = (0..100_000)
+            .map(|i| format!("a string which is not inline {}", i))
+            .collect::<Vec<_>>();
+
+        // The resolvers for the strings need a single scratch allocation which
+        // is much larger than the arena, so it gets a dedicated block.
+        let mut arena = Arena::new();
+        let capacity = arena.capacity();
+        serialize_with_arena(&value, &mut arena);
+        assert_eq!(arena.shrink(), capacity);
+
+        let nested = vec![value.clone(), value.clone(), value];
+        serialize_with_arena(&nested, &mut arena);
+        assert_eq!(arena.shrink(), capacity);
+
+        // A pre-sized arena never needs to grow
+        let mut arena = Arena::with_capacity(1 << 21);
+        let capacity = arena.capacity();
+        assert!(capacity >= 1 << 21);
+        for _ in 0..4 {
+            serialize_with_arena(&nested, &mut arena);
+            assert_eq!(arena.shrink(), capacity);
+        }
+    }
+
+    #[cfg(feature = "allocator-api2")]
+    mod custom_allocator {
+        #[cfg(not(feature = "std"))]
+        use alloc::{format, string::String, vec::Vec};
+        use core::{
+            alloc::Layout,
+            mem::size_of,
+            ptr::NonNull,
+            sync::atomic::{AtomicUsize, Ordering},
+        };
+
+        use rancor::{Failure, Panic};
+
+        use super::super::Block;
+        use crate::{
+            access,
+            ser::{allocator::Arena, sharing::Share, Serializer},
+            to_bytes_in_alloc,
+            util::{
+                allocator::{self, AllocError, Allocator as _, Global},
+                serialize_into, AlignedVec,
+            },
+            Archived,
+        };
+
+        /// Wraps the global allocator and counts the bytes allocated with it.
+        #[derive(Default)]
+        struct CountingAllocator {
+            live: AtomicUsize,
+            peak: AtomicUsize,
+        }
+
+        impl CountingAllocator {
+            fn live(&self) -> usize {
+                self.live.load(Ordering::Relaxed)
+            }
+
+            fn peak(&self) -> usize {
+                self.peak.load(Ordering::Relaxed)
+            }
+        }
+
+        unsafe impl allocator::Allocator for CountingAllocator {
+            fn allocate(
+                &self,
+                layout: Layout,
+            ) -> Result<NonNull<[u8]>, AllocError> {
+                let ptr = Global.allocate(layout)?;
+                let live =
+                    self.live.fetch_add(layout.size(), Ordering::Relaxed)
+                        + layout.size();
+                self.peak.fetch_max(live, Ordering::Relaxed);
+                Ok(ptr)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                self.live.fetch_sub(layout.size(), Ordering::Relaxed);
+                unsafe {
+                    Global.deallocate(ptr, layout);
+                }
+            }
+        }
+
+        fn strings() -> Vec<String> {
+            (0..100_000)
+                .map(|i| format!("a string which is not inline {}", i))
+                .collect()
+        }
+
+        #[test]
+        fn arena_and_writer_in_allocator() {
+            let counter = CountingAllocator::default();
+            let value = strings();
+
+            let mut arena = Arena::new_in(&counter);
+            let block_size = arena.capacity() + size_of::<Block>();
+            assert_eq!(counter.live(), block_size);
+
+            let bytes = serialize_into::<_, Panic>(
+                &value,
+                Serializer::new(
+                    AlignedVec::<16, _>::new_in(&counter),
+                    arena.acquire(),
+                    Share::new(),
+                ),
+            )
+            .unwrap()
+            .into_writer();
+
+            // The dedicated block for the resolvers was allocated and freed
+            // with the counting allocator.
+            assert_eq!(counter.live(), bytes.capacity() + block_size);
+            assert!(counter.peak() > counter.live());
+
+            let archived =
+                access::<Archived<Vec<String>>, Failure>(&bytes).unwrap();
+            assert_eq!(archived, &value);
+
+            drop(bytes);
+            assert_eq!(counter.live(), block_size);
+            drop(arena);
+            assert_eq!(counter.live(), 0);
+        }
+
+        #[test]
+        fn to_bytes_in_counting_allocator() {
+            let counter = CountingAllocator::default();
+            let value = strings();
+
+            let bytes =
+                to_bytes_in_alloc::<_, Panic>(&value, &counter).unwrap();
+            // The arena is freed once serialization finishes, so only the
+            // output buffer is left.
+            assert_eq!(counter.live(), bytes.capacity());
+            assert!(counter.peak() > bytes.capacity());
+
+            let cloned = bytes.clone();
+            assert_eq!(counter.live(), bytes.capacity() + cloned.capacity());
+            let archived =
+                access::<Archived<Vec<String>>, Failure>(&cloned).unwrap();
+            assert_eq!(archived, &value);
+
+            drop(bytes);
+            drop(cloned);
+            assert_eq!(counter.live(), 0);
+        }
+    }
 }
@@ -11,7 +11,7 @@ use rancor::{fail, Source};
 use crate::ser::Allocator;
 
 #[derive(Debug)]
-struct OutOfSpaceError {
+pub(crate) struct OutOfSpaceError {
     layout: Layout,
 }
 
@@ -76,6 +76,18 @@ pub struct AllocationStats {
 }
 
 impl AllocationStats {
+    /// Returns the number of bytes which are currently allocated.
+    #[inline]
+    pub fn bytes_allocated(&self) -> usize {
+        self.bytes_allocated
+    }
+
+    /// Returns the number of allocations which have not been returned yet.
+    #[inline]
+    pub fn allocations(&self) -> usize {
+        self.allocations
+    }
+
     /// Returns the minimum arena capacity required to serialize the same data.
     ///
     /// This calculation takes into account packing efficiency for slab
@@ -137,6 +149,11 @@ impl<T> AllocationTracker<T> {
         }
     }
 
+    /// Returns the allocation stats accumulated so far.
+    pub fn stats(&self) -> &AllocationStats {
+        &self.stats
+    }
+
     /// Returns the allocation stats accumulated during serialization.
     pub fn into_stats(self) -> AllocationStats {
         self.stats
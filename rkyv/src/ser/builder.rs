@@ -0,0 +1,341 @@
+use core::mem::MaybeUninit;
+
+use crate::ser::{
+    allocator::SubAllocator, sharing::Unshare, writer::Buffer, Allocator,
+    Serializer, Sharing, Writer,
+};
+#[cfg(feature = "alloc")]
+use crate::{
+    ser::{
+        allocator::{Arena, ArenaHandle},
+        sharing::Share,
+    },
+    util::AlignedVec,
+};
+
+/// A builder for [`Serializer`]s.
+///
+/// Each method sets one of the writer, allocator, and pointer sharing of the
+/// serializer, so the pieces can be provided in any order and the type of the
+/// built serializer is inferred from them. [`build`](Self::build) checks that
+/// every piece supports the error type that the serializer will be used with.
+///
+/// The [`core`](SerializerBuilder::core) and
+/// [`high_level`](SerializerBuilder::high_level) presets create builders for
+/// the same serializers as [`CoreSerializer`](crate::ser::CoreSerializer) and
+/// [`DefaultSerializer`](crate::ser::DefaultSerializer). Their pieces can
+/// still be replaced afterward.
+///
+/// # Examples
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::{allocator::Arena, sharing::Unshare, SerializerBuilder},
+///     util::{serialize_into, AlignedVec},
+/// };
+///
+/// let mut arena = Arena::new();
+/// let serializer = SerializerBuilder::new()
+///     .writer(AlignedVec::<16>::new())
+///     .allocator(arena.acquire())
+///     .sharing(Unshare)
+///     .build::<Error>();
+///
+/// let value = vec!["hello".to_string(), "world".to_string()];
+/// let bytes = serialize_into::<_, Error>(&value, serializer)
+///     .unwrap()
+///     .into_writer();
+/// # let _ = bytes;
+/// ```
+#[derive(Debug, Default)]
+pub struct SerializerBuilder<W = (), A = (), S = ()> {
+    writer: W,
+    allocator: A,
+    sharing: S,
+}
+
+/// A [`SerializerBuilder`] for a serializer which writes to and allocates from
+/// fixed-size buffers.
+///
+/// This is returned by [`SerializerBuilder::core`].
+pub type CoreSerializerBuilder<'a> =
+    SerializerBuilder<Buffer<'a>, SubAllocator<'a>, Unshare>;
+
+/// A [`SerializerBuilder`] for a general-purpose serializer.
+///
+/// This is returned by [`SerializerBuilder::high_level`].
+#[cfg(feature = "alloc")]
+pub type HighSerializerBuilder<'a> =
+    SerializerBuilder<AlignedVec, ArenaHandle<'a>, Share>;
+
+impl SerializerBuilder {
+    /// Returns a new builder with none of the pieces of the serializer set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a builder for a serializer which writes to `buffer` and
+    /// allocates scratch space from `scratch`.
+    ///
+    /// The built serializer does not support pointer sharing, and is suitable
+    /// for environments where allocations cannot be made.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use core::mem::MaybeUninit;
+    ///
+    /// use rkyv::{
+    ///     rancor::Error,
+    ///     ser::SerializerBuilder,
+    ///     util::{serialize_into, Align},
+    /// };
+    ///
+    /// let mut buffer = Align([MaybeUninit::<u8>::uninit(); 256]);
+    /// let mut scratch = [MaybeUninit::<u8>::uninit(); 256];
+    /// let builder = SerializerBuilder::core(&mut *buffer, &mut scratch);
+    /// let serializer = builder.build::<Error>();
+    ///
+    /// let bytes = serialize_into::<_, Error>(&[1u32, 2, 3], serializer)
+    ///     .unwrap()
+    ///     .into_writer();
+    /// assert_eq!(bytes.len(), 12);
+    /// ```
+    pub fn core<'a>(
+        buffer: &'a mut [MaybeUninit<u8>],
+        scratch: &'a mut [MaybeUninit<u8>],
+    ) -> CoreSerializerBuilder<'a> {
+        SerializerBuilder {
+            writer: Buffer::from(buffer),
+            allocator: SubAllocator::new(scratch),
+            sharing: Unshare,
+        }
+    }
+
+    /// Returns a builder for a serializer which writes to an [`AlignedVec`],
+    /// allocates scratch space from `arena`, and supports pointer sharing.
+    ///
+    /// This is the same serializer that [`to_bytes`](crate::to_bytes) uses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rkyv::{
+    ///     rancor::Error,
+    ///     ser::{allocator::Arena, SerializerBuilder},
+    ///     util::serialize_into,
+    /// };
+    ///
+    /// let mut arena = Arena::new();
+    /// let value = vec!["hello".to_string(), "world".to_string()];
+    /// for _ in 0..10 {
+    ///     let serializer =
+    ///         SerializerBuilder::high_level(&mut arena).build::<Error>();
+    ///     let bytes = serialize_into::<_, Error>(&value, serializer)
+    ///         .unwrap()
+    ///         .into_writer();
+    ///     let expected = rkyv::to_bytes::<Error>(&value).unwrap();
+    ///     assert_eq!(&*bytes, &*expected);
+    /// }
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn high_level(arena: &mut Arena) -> HighSerializerBuilder<'_> {
+        SerializerBuilder {
+            writer: AlignedVec::new(),
+            allocator: arena.acquire(),
+            sharing: Share::new(),
+        }
+    }
+}
+
+impl<W, A, S> SerializerBuilder<W, A, S> {
+    /// Sets the writer of the serializer.
+    pub fn writer<T>(self, writer: T) -> SerializerBuilder<T, A, S> {
+        SerializerBuilder {
+            writer,
+            allocator: self.allocator,
+            sharing: self.sharing,
+        }
+    }
+
+    /// Sets the allocator of the serializer.
+    pub fn allocator<T>(self, allocator: T) -> SerializerBuilder<W, T, S> {
+        SerializerBuilder {
+            writer: self.writer,
+            allocator,
+            sharing: self.sharing,
+        }
+    }
+
+    /// Sets the pointer sharing of the serializer.
+    ///
+    /// # Examples
+    ///
+    /// [`BloomShare`](crate::ser::sharing::BloomShare) shares the same
+    /// pointers as the default [`Share`](crate::ser::sharing::Share), but is
+    /// faster for large graphs where few pointers are shared:
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    ///
+    /// use rkyv::{
+    ///     rancor::Error,
+    ///     ser::{allocator::Arena, sharing::BloomShare, SerializerBuilder},
+    ///     util::serialize_into,
+    /// };
+    ///
+    /// let shared = Rc::new("a shared string".to_string());
+    /// let unshared = Rc::new("an unshared string".to_string());
+    /// let value = vec![shared.clone(), unshared, shared];
+    ///
+    /// let mut arena = Arena::new();
+    /// let serializer = SerializerBuilder::high_level(&mut arena)
+    ///     .sharing(BloomShare::with_capacity(value.len()))
+    ///     .build::<Error>();
+    /// let (bytes, _, sharing) = serialize_into::<_, Error>(&value, serializer)
+    ///     .unwrap()
+    ///     .into_raw_parts();
+    ///
+    /// assert_eq!(&*bytes, &*rkyv::to_bytes::<Error>(&value).unwrap());
+    /// assert_eq!(sharing.len(), 2);
+    /// ```
+    pub fn sharing<T>(self, sharing: T) -> SerializerBuilder<W, A, T> {
+        SerializerBuilder {
+            writer: self.writer,
+            allocator: self.allocator,
+            sharing,
+        }
+    }
+
+    /// Builds a serializer which can be used with the error type `E`.
+    pub fn build<E>(self) -> Serializer<W, A, S>
+    where
+        W: Writer<E>,
+        A: Allocator<E>,
+        S: Sharing<E>,
+    {
+        Serializer::new(self.writer, self.allocator, self.sharing)
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    use core::mem::MaybeUninit;
+
+    use rancor::Panic;
+
+    use super::SerializerBuilder;
+    use crate::{
+        ser::{
+            allocator::{AllocationTracker, Arena},
+            sharing::{BloomShare, CountLookups, Share, Unshare},
+        },
+        test::{access, deserialize},
+        util::{serialize_into, Align, AlignedVec},
+        Archive, Archived, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Example {
+        name: String,
+        values: Vec<u32>,
+        tags: Vec<String>,
+    }
+
+    fn example() -> Example {
+        Example {
+            name: "a name which is too long to be inline".to_string(),
+            values: vec![1, 2, 3, 4],
+            tags: vec!["foo".to_string(), "bar".to_string()],
+        }
+    }
+
+    fn check(bytes: &[u8]) {
+        access::<Archived<Example>>(bytes, |archived| {
+            assert_eq!(deserialize::<Example>(archived), example());
+        });
+    }
+
+    #[test]
+    fn core_builder() {
+        let mut buffer = Align([MaybeUninit::<u8>::uninit(); 256]);
+        let mut scratch = [MaybeUninit::<u8>::uninit(); 256];
+        let serializer = SerializerBuilder::core(&mut *buffer, &mut scratch)
+            .build::<Panic>();
+
+        let bytes = serialize_into::<_, Panic>(&example(), serializer)
+            .unwrap()
+            .into_writer();
+        check(&bytes);
+    }
+
+    #[test]
+    fn high_level_builder() {
+        let mut arena = Arena::new();
+        let serializer =
+            SerializerBuilder::high_level(&mut arena).build::<Panic>();
+
+        let bytes = serialize_into::<_, Panic>(&example(), serializer)
+            .unwrap()
+            .into_writer();
+        check(&bytes);
+    }
+
+    #[test]
+    fn custom_builder() {
+        let mut arena = Arena::new();
+        let serializer = SerializerBuilder::new()
+            .sharing(Unshare)
+            .allocator(AllocationTracker::new(arena.acquire()))
+            .writer(AlignedVec::<16>::new())
+            .build::<Panic>();
+
+        let (bytes, tracker, _) =
+            serialize_into::<_, Panic>(&example(), serializer)
+                .unwrap()
+                .into_raw_parts();
+        check(&bytes);
+        assert!(tracker.into_stats().max_allocations > 0);
+    }
+
+    #[test]
+    fn sharing_builder() {
+        #[cfg(not(feature = "std"))]
+        use alloc::rc::Rc;
+        #[cfg(feature = "std")]
+        use std::rc::Rc;
+
+        let shared = Rc::new(example());
+        let value = vec![shared.clone(), Rc::new(example()), shared];
+        let mut arena = Arena::new();
+
+        let serializer = SerializerBuilder::high_level(&mut arena)
+            .sharing(CountLookups::new(Share::with_capacity(3)))
+            .build::<Panic>();
+        let (expected, _, share) =
+            serialize_into::<_, Panic>(&value, serializer)
+                .unwrap()
+                .into_raw_parts();
+
+        let serializer = SerializerBuilder::high_level(&mut arena)
+            .sharing(CountLookups::new(BloomShare::with_capacity(3)))
+            .build::<Panic>();
+        let (bytes, _, bloom) = serialize_into::<_, Panic>(&value, serializer)
+            .unwrap()
+            .into_raw_parts();
+
+        assert_eq!(&*bytes, &*expected);
+        assert_eq!(share.inner().len(), 2);
+        assert_eq!(bloom.inner().len(), 2);
+        assert_eq!(share.stats().hits, 1);
+        assert_eq!(bloom.stats().hits, 1);
+    }
+}
@@ -0,0 +1,217 @@
+//! Serialization into a buffer which is written out later.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::{alloc::Layout, any::TypeId, ptr::NonNull};
+
+use rancor::Fallible;
+
+use crate::ser::{
+    sharing::RewindSharing,
+    writer::{Checkpoint, Rewind},
+    Allocator, Interning, Positional, Sharing, Writer,
+};
+
+/// A serializer which buffers its writes so they can be written out after
+/// other data.
+///
+/// The buffered bytes are positioned as if they were written starting at
+/// `start`, so they must be written to the inner serializer once its position
+/// reaches `start`. Scratch space, pointer sharing, string interning, and the
+/// nesting hooks of [`Writer`] are forwarded to the inner serializer.
+///
+/// This is used to serialize values which must come after some data that
+/// depends on their resolvers.
+pub struct Deferred<'a, S: ?Sized> {
+    inner: &'a mut S,
+    start: usize,
+    bytes: Vec<u8>,
+}
+
+impl<'a, S: ?Sized> Deferred<'a, S> {
+    /// Returns a new deferred serializer which buffers writes starting at
+    /// `start`.
+    pub fn new(inner: &'a mut S, start: usize) -> Self {
+        Self {
+            inner,
+            start,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Returns the position that the buffered bytes start at.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the buffered bytes.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Returns the inner serializer.
+    pub fn inner(&mut self) -> &mut S {
+        self.inner
+    }
+
+    /// Consumes the deferred serializer and returns the buffered bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl<S: Fallible + ?Sized> Fallible for Deferred<'_, S> {
+    type Error = S::Error;
+}
+
+impl<S: ?Sized> Positional for Deferred<'_, S> {
+    fn pos(&self) -> usize {
+        self.start + self.bytes.len()
+    }
+}
+
+impl<S: ?Sized> Rewind for Deferred<'_, S> {
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        // Bytes which were written to the inner serializer before `start` are
+        // not buffered, so they can't be discarded.
+        let len = checkpoint.pos().saturating_sub(self.start);
+        self.bytes.truncate(len);
+    }
+}
+
+impl<S: Writer<E> + ?Sized, E> Writer<E> for Deferred<'_, S> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.bytes.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn reserve_hint(&mut self, additional: usize) {
+        self.bytes.reserve(additional);
+    }
+
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), E> {
+        self.bytes.reserve(bufs.iter().map(|buf| buf.len()).sum());
+        for buf in bufs {
+            self.bytes.extend_from_slice(buf);
+        }
+        Ok(())
+    }
+
+    fn enter_nested(&mut self, type_name: &'static str) -> Result<(), E> {
+        self.inner.enter_nested(type_name)
+    }
+
+    fn exit_nested(&mut self) {
+        self.inner.exit_nested();
+    }
+}
+
+unsafe impl<S: Allocator<E> + ?Sized, E> Allocator<E> for Deferred<'_, S> {
+    unsafe fn push_alloc(
+        &mut self,
+        layout: Layout,
+    ) -> Result<NonNull<[u8]>, E> {
+        // SAFETY: The safety requirements for `push_alloc` are the same as the
+        // requirements for the inner serializer.
+        unsafe { self.inner.push_alloc(layout) }
+    }
+
+    unsafe fn pop_alloc(
+        &mut self,
+        ptr: NonNull<u8>,
+        layout: Layout,
+    ) -> Result<(), E> {
+        // SAFETY: The safety requirements for `pop_alloc` are the same as the
+        // requirements for the inner serializer.
+        unsafe { self.inner.pop_alloc(ptr, layout) }
+    }
+}
+
+impl<S: Sharing<E> + ?Sized, E> Sharing<E> for Deferred<'_, S> {
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        self.inner.get_shared_ptr(address)
+    }
+
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.inner.add_shared_ptr(address, pos)
+    }
+
+    fn start_shared_ptr(&mut self, address: usize) -> Result<(), E> {
+        self.inner.start_shared_ptr(address)
+    }
+
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        self.inner.check_shared_type(type_name)
+    }
+
+    fn get_typed_shared_ptr(
+        &self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Option<usize> {
+        self.inner.get_typed_shared_ptr(address, type_id)
+    }
+
+    fn add_typed_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+        pos: usize,
+    ) -> Result<(), E> {
+        self.inner.add_typed_shared_ptr(address, type_id, pos)
+    }
+}
+
+impl<S: RewindSharing + ?Sized> RewindSharing for Deferred<'_, S> {
+    fn rewind_shared(&mut self, pos: usize) {
+        self.inner.rewind_shared(pos);
+    }
+}
+
+impl<S: Interning<E> + ?Sized, E> Interning<E> for Deferred<'_, S> {
+    fn intern(&mut self, value: &str) -> Result<u32, E> {
+        self.inner.intern(value)
+    }
+
+    fn take_interned(&mut self) -> Result<Vec<String>, E> {
+        self.inner.take_interned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::vec::Vec;
+
+    use rancor::Failure;
+
+    use super::Deferred;
+    use crate::ser::{
+        writer::{Rewind, WithDepthLimit},
+        Positional, Writer,
+    };
+
+    #[test]
+    fn forwards_nesting() {
+        let mut inner = WithDepthLimit::new(Vec::<u8>::new(), 1);
+        let mut deferred = Deferred::new(&mut inner, 0);
+        Writer::<Failure>::enter_nested(&mut deferred, "outer").unwrap();
+        Writer::<Failure>::enter_nested(&mut deferred, "inner").unwrap_err();
+        Writer::<Failure>::exit_nested(&mut deferred);
+        assert_eq!(inner.depth(), 0);
+    }
+
+    #[test]
+    fn restore() {
+        let mut inner = Vec::<u8>::new();
+        let mut deferred = Deferred::new(&mut inner, 8);
+        Writer::<Failure>::write(&mut deferred, &[1, 2]).unwrap();
+        let checkpoint = deferred.checkpoint();
+        Writer::<Failure>::write(&mut deferred, &[3, 4]).unwrap();
+        assert_eq!(deferred.pos(), 12);
+
+        deferred.restore(checkpoint);
+        assert_eq!(deferred.bytes(), [1, 2]);
+        assert_eq!(deferred.pos(), 10);
+    }
+}
@@ -0,0 +1,318 @@
+//! Typed kinds for serialization failures.
+//!
+//! The built-in serializers and writers report errors through generic
+//! [`Source`] errors. [`SerializeError`] is a `Source` which records the
+//! [`SerializeErrorKind`] of the crate's own failure points, so they can be
+//! distinguished without matching on error messages.
+//!
+//! # Examples
+//!
+//! ```
+//! use core::mem::MaybeUninit;
+//!
+//! use rkyv::{
+//!     ser::{
+//!         allocator::SubAllocator,
+//!         error::{is_out_of_space, SerializeError, SerializeErrorKind},
+//!         sharing::Unshare,
+//!         writer::Buffer,
+//!         Serializer,
+//!     },
+//!     util::serialize_into,
+//! };
+//!
+//! let mut bytes = [MaybeUninit::<u8>::uninit(); 4];
+//! let value = "a string which doesn't fit in four bytes".to_string();
+//! let writer = Buffer::from(&mut bytes);
+//! let serializer = Serializer::new(writer, SubAllocator::empty(), Unshare);
+//! let error =
+//!     serialize_into::<_, SerializeError>(&value, serializer).unwrap_err();
+//!
+//! assert_eq!(error.kind(), Some(SerializeErrorKind::OutOfSpace));
+//! assert!(is_out_of_space(&error));
+//! ```
+
+use core::{any::TypeId, fmt};
+
+use rancor::{Source, Trace};
+
+/// The kind of a failure raised by one of the crate's serializers or writers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SerializeErrorKind {
+    /// A writer ran out of space for the bytes being written.
+    OutOfSpace,
+    /// A scratch space allocator ran out of space.
+    ScratchExhausted,
+    /// An alignment was not a power of two.
+    InvalidAlignment,
+    /// A length or offset was too large for its archived integer type.
+    ///
+    /// This is most common with small pointer widths, for example when the
+    /// archive is larger than a 16-bit offset can address.
+    LengthOverflow,
+    /// A shared pointer was reached again while it was being serialized.
+    SharingCycle,
+}
+
+impl SerializeErrorKind {
+    /// Returns the kind of the given source error, if it's one of the crate's
+    /// own failures.
+    pub fn of<T: 'static>(_: &T) -> Option<Self> {
+        let id = TypeId::of::<T>();
+        if id == TypeId::of::<crate::ser::writer::BufferOverflow>() {
+            Some(Self::OutOfSpace)
+        } else if id == TypeId::of::<crate::ser::allocator::OutOfSpaceError>() {
+            Some(Self::ScratchExhausted)
+        } else if id == TypeId::of::<crate::util::InvalidPadAlignment>() {
+            Some(Self::InvalidAlignment)
+        } else if id == TypeId::of::<crate::rel_ptr::IsizeOverflow>()
+            || id == TypeId::of::<crate::rel_ptr::OffsetOverflow>()
+            || id == TypeId::of::<crate::primitive::LengthOverflow>()
+        {
+            Some(Self::LengthOverflow)
+        } else if id == TypeId::of::<crate::ser::sharing::SharedPointerCycle>()
+        {
+            Some(Self::SharingCycle)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for SerializeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfSpace => write!(f, "writer out of space"),
+            Self::ScratchExhausted => write!(f, "scratch space exhausted"),
+            Self::InvalidAlignment => write!(f, "invalid alignment"),
+            Self::LengthOverflow => write!(f, "length overflow"),
+            Self::SharingCycle => write!(f, "shared pointer cycle"),
+        }
+    }
+}
+
+/// A [`Source`] error which records the [`SerializeErrorKind`] of the failure
+/// that caused it.
+///
+/// The kind is kept when traces are added to the error.
+#[derive(Debug)]
+pub struct SerializeError {
+    kind: Option<SerializeErrorKind>,
+    inner: rancor::Error,
+}
+
+impl SerializeError {
+    /// Returns the kind of the failure, if it was one of the crate's own.
+    #[inline]
+    pub fn kind(&self) -> Option<SerializeErrorKind> {
+        self.kind
+    }
+
+    /// Consumes the error and returns the underlying error.
+    #[inline]
+    pub fn into_inner(self) -> rancor::Error {
+        self.inner
+    }
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.inner, f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SerializeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.inner)
+    }
+}
+
+impl Trace for SerializeError {
+    fn trace<R>(self, trace: R) -> Self
+    where
+        R: fmt::Debug + fmt::Display + Send + Sync + 'static,
+    {
+        Self {
+            kind: self.kind,
+            inner: self.inner.trace(trace),
+        }
+    }
+}
+
+impl Source for SerializeError {
+    #[cfg(feature = "std")]
+    fn new<T: std::error::Error + Send + Sync + 'static>(source: T) -> Self {
+        Self {
+            kind: SerializeErrorKind::of(&source),
+            inner: rancor::Error::new(source),
+        }
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn new<T: fmt::Debug + fmt::Display + Send + Sync + 'static>(
+        source: T,
+    ) -> Self {
+        Self {
+            kind: SerializeErrorKind::of(&source),
+            inner: rancor::Error::new(source),
+        }
+    }
+}
+
+/// Returns whether the error was caused by running out of space in a
+/// fixed-size writer or scratch space.
+#[inline]
+pub fn is_out_of_space(error: &SerializeError) -> bool {
+    matches!(
+        error.kind,
+        Some(SerializeErrorKind::OutOfSpace)
+            | Some(SerializeErrorKind::ScratchExhausted)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{string::String, vec};
+    use core::mem::MaybeUninit;
+
+    use rancor::{Source as _, Trace as _};
+
+    use super::{is_out_of_space, SerializeError, SerializeErrorKind};
+    use crate::{
+        primitive::ArchivedI16,
+        rel_ptr::{signed_offset, Offset as _},
+        ser::{
+            allocator::SubAllocator, sharing::Unshare, writer::Buffer,
+            Serializer,
+        },
+        to_bytes_padded_to,
+        util::{serialize_into, Align},
+    };
+
+    fn serialize_in<T>(
+        value: &T,
+        output: &mut [MaybeUninit<u8>],
+        scratch: &mut [MaybeUninit<u8>],
+    ) -> Result<(), SerializeError>
+    where
+        T: for<'a> crate::Serialize<
+            rancor::Strategy<
+                Serializer<Buffer<'a>, SubAllocator<'a>, Unshare>,
+                SerializeError,
+            >,
+        >,
+    {
+        serialize_into(
+            value,
+            Serializer::new(
+                Buffer::from(output),
+                SubAllocator::new(scratch),
+                Unshare,
+            ),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn writer_out_of_space() {
+        let mut output = Align([MaybeUninit::<u8>::uninit(); 8]);
+        let value = String::from("a string which does not fit in 8 bytes");
+
+        let error = serialize_in(&value, &mut *output, &mut []).unwrap_err();
+        assert_eq!(error.kind(), Some(SerializeErrorKind::OutOfSpace));
+        assert!(is_out_of_space(&error));
+    }
+
+    #[test]
+    fn scratch_exhausted() {
+        let mut output = Align([MaybeUninit::<u8>::uninit(); 256]);
+        let mut scratch = [MaybeUninit::<u8>::uninit(); 4];
+        let value = vec![String::from("a"), String::from("b")];
+
+        let error =
+            serialize_in(&value, &mut *output, &mut scratch).unwrap_err();
+        assert_eq!(error.kind(), Some(SerializeErrorKind::ScratchExhausted));
+        assert!(is_out_of_space(&error));
+    }
+
+    #[test]
+    fn invalid_alignment() {
+        let error = to_bytes_padded_to::<SerializeError>(&0u32, 3).unwrap_err();
+        assert_eq!(error.kind(), Some(SerializeErrorKind::InvalidAlignment));
+        assert!(!is_out_of_space(&error));
+    }
+
+    #[test]
+    fn length_overflow() {
+        let error = signed_offset::<SerializeError>(0, isize::MAX as usize + 2)
+            .unwrap_err();
+        assert_eq!(error.kind(), Some(SerializeErrorKind::LengthOverflow));
+
+        let error =
+            ArchivedI16::from_isize::<SerializeError>(1 << 20).unwrap_err();
+        assert_eq!(error.kind(), Some(SerializeErrorKind::LengthOverflow));
+        assert!(!is_out_of_space(&error));
+    }
+
+    #[test]
+    fn unrelated_conversions_have_no_kind() {
+        let error = SerializeError::new(u8::try_from(300u32).unwrap_err());
+        assert_eq!(error.kind(), None);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn sharing_cycle() {
+        use crate::ser::sharing::{Share, Sharing as _};
+
+        let mut share = Share::new();
+        Sharing::<SerializeError>::start_shared_ptr(&mut share, 0x1000)
+            .unwrap();
+        let error =
+            Sharing::<SerializeError>::start_shared_ptr(&mut share, 0x1000)
+                .unwrap_err();
+        assert_eq!(error.kind(), Some(SerializeErrorKind::SharingCycle));
+
+        // Adding the position ends the serialization
+        Sharing::<SerializeError>::add_shared_ptr(&mut share, 0x1000, 8)
+            .unwrap();
+        Sharing::<SerializeError>::start_shared_ptr(&mut share, 0x2000)
+            .unwrap();
+    }
+
+    #[test]
+    fn kind_survives_traces() {
+        let mut output = Align([MaybeUninit::<u8>::uninit(); 8]);
+        let value = String::from("a string which does not fit in 8 bytes");
+
+        let error = serialize_in(&value, &mut *output, &mut [])
+            .unwrap_err()
+            .trace("while serializing a test value");
+        assert_eq!(error.kind(), Some(SerializeErrorKind::OutOfSpace));
+    }
+
+    #[test]
+    fn other_errors_have_no_kind() {
+        #[derive(Debug)]
+        struct Other;
+
+        impl core::fmt::Display for Other {
+            fn fmt(
+                &self,
+                f: &mut core::fmt::Formatter<'_>,
+            ) -> core::fmt::Result {
+                write!(f, "some other error")
+            }
+        }
+
+        #[cfg(feature = "std")]
+        impl std::error::Error for Other {}
+
+        let error = SerializeError::new(Other);
+        assert_eq!(error.kind(), None);
+        assert!(!is_out_of_space(&error));
+    }
+}
@@ -1,23 +1,73 @@
 //! Serialization traits and adapters.
 
 pub mod allocator;
+mod builder;
+#[cfg(feature = "alloc")]
+mod deferred;
+#[cfg(feature = "alloc")]
+pub mod error;
 pub mod sharing;
 pub mod writer;
 
-use ::core::{alloc::Layout, ptr::NonNull};
+use ::core::{alloc::Layout, any::TypeId, ptr::NonNull};
 use rancor::Strategy;
 
+#[doc(inline)]
+#[cfg(feature = "alloc")]
+pub use self::sharing::Interning;
 #[doc(inline)]
 pub use self::{
     allocator::Allocator,
-    sharing::{Sharing, SharingExt},
-    writer::{Positional, Writer, WriterExt},
+    builder::{CoreSerializerBuilder, SerializerBuilder},
+    sharing::{RewindSharing, Sharing, SharingExt},
+    writer::{Positional, Rewind, Writer, WriterExt},
 };
 #[cfg(feature = "alloc")]
+pub use self::{builder::HighSerializerBuilder, deferred::Deferred};
+#[cfg(feature = "alloc")]
 use crate::ser::{allocator::ArenaHandle, sharing::Share};
-use crate::ser::{allocator::SubAllocator, sharing::Unshare};
+use crate::ser::{
+    allocator::SubAllocator, sharing::Unshare, writer::Checkpoint,
+};
 
 /// A serializer built from composeable pieces.
+///
+/// If serialization fails partway through, the writer may contain part of an
+/// archive and the pointer sharing may refer to values in it. Using the
+/// serializer again without resetting it may produce corrupt archives. To
+/// reuse a serializer after an error, take a [`checkpoint`](Self::checkpoint)
+/// before serializing and [`reset`](Self::reset) to it after the error.
+/// The allocator doesn't need to be reset, since scratch space is always
+/// returned to it, even when serialization fails or a `Serialize`
+/// implementation panics.
+///
+/// # Examples
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::{allocator::Arena, sharing::Share, writer::Buffer, Serializer},
+///     util::{serialize, Align},
+/// };
+///
+/// let value = vec!["a string which is too long to be inlined".to_string()];
+///
+/// let mut arena = Arena::new();
+/// let mut small = Align([0u8; 16]);
+/// let mut large = Align([0u8; 256]);
+/// let mut serializer = Serializer::new(
+///     Buffer::from(&mut *small),
+///     arena.acquire(),
+///     Share::new(),
+/// );
+///
+/// let checkpoint = serializer.checkpoint();
+/// assert!(serialize::<_, Error>(&value, &mut serializer).is_err());
+/// serializer.reset(checkpoint);
+///
+/// serializer.writer = Buffer::from(&mut *large);
+/// serialize::<_, Error>(&value, &mut serializer).unwrap();
+/// ```
 #[derive(Debug, Default)]
 pub struct Serializer<W, A, S> {
     /// The writer of the serializer.
@@ -51,6 +101,23 @@ impl<W, A, S> Serializer<W, A, S> {
     }
 }
 
+impl<W: Rewind, A, S: RewindSharing> Serializer<W, A, S> {
+    /// Returns a checkpoint of the current position of the writer.
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.writer.checkpoint()
+    }
+
+    /// Resets the serializer to the given checkpoint.
+    ///
+    /// This discards the bytes written after the checkpoint and forgets the
+    /// shared pointers which were serialized after it, so the serializer can
+    /// be used again after a serialization fails.
+    pub fn reset(&mut self, checkpoint: Checkpoint) {
+        self.writer.restore(checkpoint);
+        self.sharing.rewind_shared(checkpoint.pos());
+    }
+}
+
 impl<W: Positional, A, S> Positional for Serializer<W, A, S> {
     fn pos(&self) -> usize {
         self.writer.pos()
@@ -61,6 +128,25 @@ impl<W: Writer<E>, A, S, E> Writer<E> for Serializer<W, A, S> {
     fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
         self.writer.write(bytes)
     }
+
+    #[inline]
+    fn reserve_hint(&mut self, additional: usize) {
+        self.writer.reserve_hint(additional)
+    }
+
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), E> {
+        self.writer.write_vectored(bufs)
+    }
+
+    #[inline]
+    fn enter_nested(&mut self, type_name: &'static str) -> Result<(), E> {
+        self.writer.enter_nested(type_name)
+    }
+
+    #[inline]
+    fn exit_nested(&mut self) {
+        self.writer.exit_nested()
+    }
 }
 
 unsafe impl<W, A: Allocator<E>, S, E> Allocator<E> for Serializer<W, A, S> {
@@ -92,6 +178,44 @@ impl<W, A, S: Sharing<E>, E> Sharing<E> for Serializer<W, A, S> {
     fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
         self.sharing.add_shared_ptr(address, pos)
     }
+
+    fn start_shared_ptr(&mut self, address: usize) -> Result<(), E> {
+        self.sharing.start_shared_ptr(address)
+    }
+
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        self.sharing.check_shared_type(type_name)
+    }
+
+    fn get_typed_shared_ptr(
+        &self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Option<usize> {
+        self.sharing.get_typed_shared_ptr(address, type_id)
+    }
+
+    fn add_typed_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+        pos: usize,
+    ) -> Result<(), E> {
+        self.sharing.add_typed_shared_ptr(address, type_id, pos)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<W, A, S: Interning<E>, E> Interning<E> for Serializer<W, A, S> {
+    fn intern(&mut self, value: &str) -> Result<u32, E> {
+        self.sharing.intern(value)
+    }
+
+    fn take_interned(
+        &mut self,
+    ) -> Result<::alloc::vec::Vec<::alloc::string::String>, E> {
+        self.sharing.take_interned()
+    }
 }
 
 /// A serializer suitable for environments where allocations cannot be made.
@@ -103,3 +227,191 @@ pub type CoreSerializer<'a, W, E> =
 #[cfg(feature = "alloc")]
 pub type DefaultSerializer<'a, W, E> =
     Strategy<Serializer<W, ArenaHandle<'a>, Share>, E>;
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        rc::Rc,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    #[cfg(feature = "std")]
+    use std::rc::Rc;
+
+    use rancor::Failure;
+
+    use crate::{
+        ser::{
+            allocator::Arena,
+            sharing::{
+                BloomShare, CountLookups, RewindSharing, Share, SharingStats,
+            },
+            writer::{Buffer, Rewind},
+            Positional, Serializer, Sharing, SharingExt, Writer,
+        },
+        to_bytes,
+        util::{serialize, Align, AlignedVec},
+    };
+
+    #[test]
+    fn reset_after_error() {
+        let shared =
+            Rc::new(String::from("a shared string which is out of line"));
+        let value = vec![
+            shared.clone(),
+            shared,
+            Rc::new(String::from("another string which is out of line")),
+        ];
+        let expected = to_bytes::<Failure>(&value).unwrap();
+
+        let mut arena = Arena::new();
+        let mut small = Align([0u8; 64]);
+        let mut large = Align([0u8; 256]);
+        let mut serializer = Serializer::new(
+            Buffer::from(&mut *small),
+            arena.acquire(),
+            Share::new(),
+        );
+
+        let checkpoint = serializer.checkpoint();
+        assert!(serialize::<_, Failure>(&value, &mut serializer).is_err());
+        // The first string was shared before the buffer ran out of space
+        assert!(SharingExt::<Failure>::get_shared(
+            &serializer.sharing,
+            &*value[0]
+        )
+        .is_some());
+
+        serializer.reset(checkpoint);
+        assert_eq!(serializer.pos(), 0);
+        assert!(SharingExt::<Failure>::get_shared(
+            &serializer.sharing,
+            &*value[0]
+        )
+        .is_none());
+
+        serializer.writer = Buffer::from(&mut *large);
+        serialize::<_, Failure>(&value, &mut serializer).unwrap();
+        assert_eq!(&*serializer.writer, expected.as_slice());
+    }
+
+    #[test]
+    fn rewind_writers() {
+        fn check<W: Writer<Failure> + Rewind>(mut writer: W) {
+            writer.write(&[1, 2, 3]).unwrap();
+            let checkpoint = writer.checkpoint();
+            assert_eq!(checkpoint.pos(), 3);
+
+            writer.write(&[4, 5]).unwrap();
+            writer.restore(checkpoint);
+            assert_eq!(writer.pos(), 3);
+
+            // Restoring a checkpoint which the writer isn't past does nothing
+            writer.restore(checkpoint);
+            assert_eq!(writer.pos(), 3);
+        }
+
+        check(Vec::new());
+        check(AlignedVec::<16>::new());
+        check(Buffer::from(&mut [0u8; 8]));
+    }
+
+    #[test]
+    fn rewind_sharing() {
+        let mut share = Share::new();
+        Sharing::<Failure>::add_shared_ptr(&mut share, 1, 0).unwrap();
+        Sharing::<Failure>::add_shared_ptr(&mut share, 2, 8).unwrap();
+        Sharing::<Failure>::add_shared_ptr(&mut share, 3, 16).unwrap();
+
+        share.rewind_shared(8);
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 1), Some(0));
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 2), None);
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 3), None);
+    }
+
+    #[test]
+    fn rewind_bloom_sharing() {
+        let mut share = BloomShare::new();
+        Sharing::<Failure>::add_shared_ptr(&mut share, 1, 0).unwrap();
+        Sharing::<Failure>::add_shared_ptr(&mut share, 2, 8).unwrap();
+        Sharing::<Failure>::add_shared_ptr(&mut share, 3, 16).unwrap();
+        Sharing::<Failure>::add_shared_ptr(&mut share, 2, 24).unwrap_err();
+
+        share.rewind_shared(8);
+        assert_eq!(share.len(), 1);
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 1), Some(0));
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 2), None);
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 3), None);
+    }
+
+    #[test]
+    fn sharing_stats() {
+        let mut share = CountLookups::new(Share::with_capacity(4));
+        assert!(share.inner().is_empty());
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 1), None);
+        Sharing::<Failure>::add_shared_ptr(&mut share, 1, 0).unwrap();
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 1), Some(0));
+        assert_eq!(Sharing::<Failure>::get_shared_ptr(&share, 2), None);
+
+        assert_eq!(share.inner().len(), 1);
+        assert_eq!(
+            share.stats(),
+            SharingStats {
+                lookups: 3,
+                hits: 1
+            }
+        );
+
+        share.reset_stats();
+        assert_eq!(share.stats(), SharingStats::default());
+        assert_eq!(share.inner().len(), 1);
+    }
+
+    #[test]
+    fn sharing_is_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<Share>();
+        assert_sync::<BloomShare>();
+    }
+
+    #[test]
+    fn bloom_share_dedup() {
+        let shared = Rc::new(String::from("a shared string which is long"));
+        let mut value = (0..1000)
+            .map(|i| Rc::new(i.to_string()))
+            .collect::<Vec<_>>();
+        for i in 0..10 {
+            value.insert(i * 100, shared.clone());
+        }
+        let expected = to_bytes::<Failure>(&value).unwrap();
+
+        // Both serializers are alive at the same time, so each needs its own
+        // arena.
+        let mut share_arena = Arena::new();
+        let mut share = Serializer::new(
+            AlignedVec::<16>::new(),
+            share_arena.acquire(),
+            CountLookups::new(Share::new()),
+        );
+        serialize::<_, Failure>(&value, &mut share).unwrap();
+        let mut bloom_arena = Arena::new();
+        let mut bloom = Serializer::new(
+            AlignedVec::<16>::new(),
+            bloom_arena.acquire(),
+            CountLookups::new(BloomShare::new()),
+        );
+        serialize::<_, Failure>(&value, &mut bloom).unwrap();
+
+        assert_eq!(&*share.writer, expected.as_slice());
+        assert_eq!(&*bloom.writer, expected.as_slice());
+        assert_eq!(share.sharing.inner().len(), 1001);
+        assert_eq!(bloom.sharing.inner().len(), 1001);
+
+        let share_stats = share.sharing.stats();
+        assert_eq!(share_stats.lookups, 1010);
+        assert_eq!(share_stats.hits, 9);
+        assert_eq!(bloom.sharing.stats(), share_stats);
+    }
+}
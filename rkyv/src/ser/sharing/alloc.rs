@@ -1,12 +1,17 @@
-use core::{fmt, mem::size_of};
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+use core::{any::TypeId, fmt, mem::size_of};
 #[cfg(feature = "std")]
-use std::collections::hash_map;
+use std::collections::{hash_map, hash_set};
 
 #[cfg(not(feature = "std"))]
-use hashbrown::hash_map;
+use hashbrown::{hash_map, hash_set};
 use rancor::{fail, Source};
 
-use crate::ser::Sharing;
+use crate::ser::{
+    sharing::{RewindSharing, SharedPointerCycle},
+    Sharing,
+};
 
 #[derive(Debug)]
 struct DuplicateSharedPointer {
@@ -29,18 +34,24 @@ impl std::error::Error for DuplicateSharedPointer {}
 
 /// A shared pointer strategy that shares serializations of the same shared
 /// pointer.
+///
+/// Every shared pointer serialized is kept until the strategy is dropped or
+/// [`clear`](Share::clear)ed, so serializing large graphs may make the map of
+/// shared pointers large. [`len`](Share::len) can be used to inspect it, and
+/// [`CountLookups`](crate::ser::sharing::CountLookups) can count how often it
+/// is searched.
 #[derive(Debug, Default)]
 pub struct Share {
     shared_address_to_pos: hash_map::HashMap<usize, usize>,
+    typed_address_to_pos: hash_map::HashMap<(usize, TypeId), usize>,
+    in_progress: hash_set::HashSet<usize>,
 }
 
 impl Share {
     /// Creates a new shared pointer unifier.
     #[inline]
     pub fn new() -> Self {
-        Self {
-            shared_address_to_pos: hash_map::HashMap::new(),
-        }
+        Self::default()
     }
 
     /// Creates a new shared pointer unifier with initial capacity.
@@ -48,8 +59,30 @@ impl Share {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
             shared_address_to_pos: hash_map::HashMap::with_capacity(capacity),
+            ..Self::default()
         }
     }
+
+    /// Returns the number of shared pointers which have been added.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.shared_address_to_pos.len() + self.typed_address_to_pos.len()
+    }
+
+    /// Returns whether no shared pointers have been added.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.shared_address_to_pos.is_empty()
+            && self.typed_address_to_pos.is_empty()
+    }
+
+    /// Forgets all shared pointers, keeping the allocated capacity so the
+    /// strategy can be reused for another serialization.
+    pub fn clear(&mut self) {
+        self.shared_address_to_pos.clear();
+        self.typed_address_to_pos.clear();
+        self.in_progress.clear();
+    }
 }
 
 impl<E: Source> Sharing<E> for Share {
@@ -57,7 +90,15 @@ impl<E: Source> Sharing<E> for Share {
         self.shared_address_to_pos.get(&address).copied()
     }
 
+    fn start_shared_ptr(&mut self, address: usize) -> Result<(), E> {
+        if !self.in_progress.insert(address) {
+            fail!(SharedPointerCycle { address });
+        }
+        Ok(())
+    }
+
     fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.in_progress.remove(&address);
         match self.shared_address_to_pos.entry(address) {
             hash_map::Entry::Occupied(_) => {
                 fail!(DuplicateSharedPointer { address });
@@ -68,4 +109,197 @@ impl<E: Source> Sharing<E> for Share {
             }
         }
     }
+
+    fn get_typed_shared_ptr(
+        &self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Option<usize> {
+        self.typed_address_to_pos.get(&(address, type_id)).copied()
+    }
+
+    fn add_typed_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+        pos: usize,
+    ) -> Result<(), E> {
+        match self.typed_address_to_pos.entry((address, type_id)) {
+            hash_map::Entry::Occupied(_) => {
+                fail!(DuplicateSharedPointer { address });
+            }
+            hash_map::Entry::Vacant(e) => {
+                e.insert(pos);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl RewindSharing for Share {
+    fn rewind_shared(&mut self, pos: usize) {
+        self.shared_address_to_pos.retain(|_, shared| *shared < pos);
+        self.typed_address_to_pos.retain(|_, shared| *shared < pos);
+        // Rewinding abandons every serialization which was in progress.
+        self.in_progress.clear();
+    }
+}
+
+/// The minimum number of filter bits per shared pointer before the filter of a
+/// [`BloomShare`] grows.
+const BLOOM_BITS_PER_ENTRY: usize = 8;
+const BLOOM_MIN_BITS: usize = 64;
+
+/// A shared pointer strategy like [`Share`] which checks a bloom filter before
+/// searching its map of shared pointers.
+///
+/// Most lookups for pointers which have not been added are answered by the
+/// filter alone, which makes serializing graphs where few pointers are shared
+/// faster. Lookups which the filter can't rule out still search the map, so
+/// the same pointers are shared as with `Share`. The filter costs about one
+/// byte per shared pointer. Lookups of typed shared values always search the
+/// map.
+#[derive(Debug)]
+pub struct BloomShare {
+    share: Share,
+    filter: Vec<u64>,
+    shift: u32,
+}
+
+impl Default for BloomShare {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BloomShare {
+    /// Creates a new shared pointer unifier with a bloom filter.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_share(Share::new(), 0)
+    }
+
+    /// Creates a new shared pointer unifier with a bloom filter and initial
+    /// capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_share(Share::with_capacity(capacity), capacity)
+    }
+
+    fn with_share(share: Share, capacity: usize) -> Self {
+        let bits = Self::filter_bits(capacity);
+        Self {
+            share,
+            filter: vec![0; bits / 64],
+            shift: 64 - bits.trailing_zeros(),
+        }
+    }
+
+    fn filter_bits(entries: usize) -> usize {
+        entries
+            .saturating_mul(BLOOM_BITS_PER_ENTRY)
+            .max(BLOOM_MIN_BITS)
+            .next_power_of_two()
+    }
+
+    /// Returns the number of shared pointers which have been added.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.share.len()
+    }
+
+    /// Returns whether no shared pointers have been added.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.share.is_empty()
+    }
+
+    /// Forgets all shared pointers, keeping the allocated capacity of the map
+    /// and the filter.
+    pub fn clear(&mut self) {
+        self.share.clear();
+        self.filter.fill(0);
+    }
+
+    #[inline]
+    fn probes(shift: u32, address: usize) -> [usize; 2] {
+        let address = address as u64;
+        [
+            (address.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> shift) as usize,
+            (address.wrapping_mul(0xbf58_476d_1ce4_e5b9) >> shift) as usize,
+        ]
+    }
+
+    #[inline]
+    fn may_contain(&self, address: usize) -> bool {
+        Self::probes(self.shift, address)
+            .iter()
+            .all(|&bit| self.filter[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    #[inline]
+    fn insert(filter: &mut [u64], shift: u32, address: usize) {
+        for bit in Self::probes(shift, address) {
+            filter[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    fn grow(&mut self) {
+        let bits = Self::filter_bits(self.share.len() * 2);
+        self.filter.clear();
+        self.filter.resize(bits / 64, 0);
+        self.shift = 64 - bits.trailing_zeros();
+        for &address in self.share.shared_address_to_pos.keys() {
+            Self::insert(&mut self.filter, self.shift, address);
+        }
+    }
+}
+
+impl<E: Source> Sharing<E> for BloomShare {
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        if self.may_contain(address) {
+            Sharing::<E>::get_shared_ptr(&self.share, address)
+        } else {
+            None
+        }
+    }
+
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        Sharing::<E>::add_shared_ptr(&mut self.share, address, pos)?;
+        if self.share.len() * BLOOM_BITS_PER_ENTRY > self.filter.len() * 64 {
+            self.grow();
+        } else {
+            Self::insert(&mut self.filter, self.shift, address);
+        }
+        Ok(())
+    }
+
+    fn start_shared_ptr(&mut self, address: usize) -> Result<(), E> {
+        Sharing::<E>::start_shared_ptr(&mut self.share, address)
+    }
+
+    fn get_typed_shared_ptr(
+        &self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Option<usize> {
+        Sharing::<E>::get_typed_shared_ptr(&self.share, address, type_id)
+    }
+
+    fn add_typed_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+        pos: usize,
+    ) -> Result<(), E> {
+        self.share.add_typed_shared_ptr(address, type_id, pos)
+    }
+}
+
+impl RewindSharing for BloomShare {
+    fn rewind_shared(&mut self, pos: usize) {
+        // The filter bits of forgotten pointers stay set. They only cause
+        // extra searches of the map, which will not find them.
+        self.share.rewind_shared(pos);
+    }
 }
@@ -1,4 +1,8 @@
-use crate::ser::Sharing;
+use core::{any::TypeId, cell::Cell, fmt};
+
+use rancor::{fail, Source};
+
+use crate::ser::{sharing::RewindSharing, Sharing};
 
 /// A shared pointer strategy that duplicates serializations of the same shared
 /// pointer.
@@ -14,3 +18,231 @@ impl<E> Sharing<E> for Unshare {
         Ok(())
     }
 }
+
+impl RewindSharing for Unshare {
+    fn rewind_shared(&mut self, _: usize) {}
+}
+
+/// An error which occurs when a shared pointer is serialized with
+/// [`DenyShared`].
+#[derive(Debug)]
+pub struct SharingDenied {
+    type_name: &'static str,
+}
+
+impl SharingDenied {
+    /// Returns the name of the type of the shared value.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl fmt::Display for SharingDenied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "attempted to serialize a shared pointer to `{}` while sharing is \
+             denied",
+            self.type_name,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SharingDenied {}
+
+/// A shared pointer strategy that returns an error whenever a shared pointer
+/// is serialized.
+///
+/// This guarantees that an archive contains no shared pointers. Serializing a
+/// shared pointer like `Rc` or `Arc` fails with a [`SharingDenied`] error
+/// which names the type of the shared value.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use rkyv::{
+///     rancor::Error,
+///     ser::{allocator::Arena, sharing::DenyShared, SerializerBuilder},
+///     util::{serialize_into, AlignedVec},
+/// };
+///
+/// let mut arena = Arena::new();
+/// let serializer = SerializerBuilder::new()
+///     .writer(AlignedVec::<16>::new())
+///     .allocator(arena.acquire())
+///     .sharing(DenyShared)
+///     .build::<Error>();
+///
+/// let result = serialize_into::<_, Error>(&Arc::new(42u32), serializer);
+/// assert!(result.is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct DenyShared;
+
+impl<E: Source> Sharing<E> for DenyShared {
+    fn get_shared_ptr(&self, _: usize) -> Option<usize> {
+        None
+    }
+
+    fn add_shared_ptr(&mut self, _: usize, _: usize) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        fail!(SharingDenied { type_name });
+    }
+}
+
+impl RewindSharing for DenyShared {
+    fn rewind_shared(&mut self, _: usize) {}
+}
+
+/// Statistics for the shared pointer lookups which occurred during
+/// serialization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SharingStats {
+    /// The number of times a shared pointer was looked up.
+    pub lookups: usize,
+    /// The number of lookups which found a shared pointer.
+    pub hits: usize,
+}
+
+/// A shared pointer strategy which counts the lookups of another strategy.
+///
+/// Lookups take `&self`, so the counts are kept in [`Cell`]s and a
+/// `CountLookups` is not `Sync` even if the strategy it wraps is. Wrap a
+/// strategy in it only when the statistics are needed.
+///
+/// # Examples
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use rkyv::{
+///     rancor::Error,
+///     ser::{
+///         allocator::Arena,
+///         sharing::{CountLookups, Share},
+///         SerializerBuilder,
+///     },
+///     util::serialize_into,
+/// };
+///
+/// let shared = Rc::new("a shared string".to_string());
+/// let value = vec![shared.clone(), shared];
+///
+/// let mut arena = Arena::new();
+/// let serializer = SerializerBuilder::high_level(&mut arena)
+///     .sharing(CountLookups::new(Share::new()))
+///     .build::<Error>();
+/// let (_, _, sharing) = serialize_into::<_, Error>(&value, serializer)
+///     .unwrap()
+///     .into_raw_parts();
+///
+/// assert_eq!(sharing.stats().lookups, 2);
+/// assert_eq!(sharing.stats().hits, 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct CountLookups<S> {
+    inner: S,
+    lookups: Cell<usize>,
+    hits: Cell<usize>,
+}
+
+impl<S> CountLookups<S> {
+    /// Wraps the given strategy to count its lookups.
+    #[inline]
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            lookups: Cell::new(0),
+            hits: Cell::new(0),
+        }
+    }
+
+    /// Returns a reference to the wrapped strategy.
+    #[inline]
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped strategy.
+    #[inline]
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped strategy.
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    /// Returns the lookup statistics accumulated so far.
+    #[inline]
+    pub fn stats(&self) -> SharingStats {
+        SharingStats {
+            lookups: self.lookups.get(),
+            hits: self.hits.get(),
+        }
+    }
+
+    /// Resets the lookup statistics.
+    #[inline]
+    pub fn reset_stats(&mut self) {
+        self.lookups.set(0);
+        self.hits.set(0);
+    }
+
+    fn count(&self, result: Option<usize>) -> Option<usize> {
+        self.lookups.set(self.lookups.get() + 1);
+        if result.is_some() {
+            self.hits.set(self.hits.get() + 1);
+        }
+        result
+    }
+}
+
+impl<S: Sharing<E>, E> Sharing<E> for CountLookups<S> {
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        self.count(self.inner.get_shared_ptr(address))
+    }
+
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.inner.add_shared_ptr(address, pos)
+    }
+
+    fn start_shared_ptr(&mut self, address: usize) -> Result<(), E> {
+        self.inner.start_shared_ptr(address)
+    }
+
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        self.inner.check_shared_type(type_name)
+    }
+
+    fn get_typed_shared_ptr(
+        &self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Option<usize> {
+        self.count(self.inner.get_typed_shared_ptr(address, type_id))
+    }
+
+    fn add_typed_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+        pos: usize,
+    ) -> Result<(), E> {
+        self.inner.add_typed_shared_ptr(address, type_id, pos)
+    }
+}
+
+impl<S: RewindSharing> RewindSharing for CountLookups<S> {
+    fn rewind_shared(&mut self, pos: usize) {
+        self.inner.rewind_shared(pos);
+    }
+}
@@ -3,14 +3,41 @@
 #[cfg(feature = "alloc")]
 mod alloc;
 mod core;
+#[cfg(feature = "alloc")]
+mod symbols;
 
+use ::core::{any::TypeId, fmt, mem::size_of};
 use rancor::{Fallible, Strategy};
 
 #[cfg(feature = "alloc")]
 pub use self::alloc::*;
 pub use self::core::*;
+#[cfg(feature = "alloc")]
+pub use self::symbols::*;
 use crate::SerializeUnsized;
 
+/// An error which occurs when a shared pointer is reached again while it is
+/// still being serialized.
+#[derive(Debug)]
+pub(crate) struct SharedPointerCycle {
+    pub(crate) address: usize,
+}
+
+impl fmt::Display for SharedPointerCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "shared pointer cycle: {:#.*x} was reached while it was being \
+             serialized",
+            size_of::<usize>() * 2,
+            self.address
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SharedPointerCycle {}
+
 /// A shared pointer serialization strategy.
 ///
 /// This trait is required to serialize `Rc` and `Arc`.
@@ -22,6 +49,58 @@ pub trait Sharing<E = <Self as Fallible>::Error> {
 
     /// Adds the serialized position of a shared pointer.
     fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E>;
+
+    /// Marks the shared pointer with the given address as being serialized.
+    ///
+    /// This is called before a shared value is serialized, and the mark is
+    /// removed when its position is added. Strategies which track marks should
+    /// return an error if the pointer is already marked, since the value
+    /// contains itself and serializing it would never finish. By default,
+    /// shared pointers are not marked.
+    fn start_shared_ptr(&mut self, address: usize) -> Result<(), E> {
+        let _ = address;
+        Ok(())
+    }
+
+    /// Checks whether a value with the given type name may be shared.
+    ///
+    /// This is called once for each shared pointer which is serialized, even
+    /// if the value it points to has already been serialized. By default,
+    /// values of every type may be shared.
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        let _ = type_name;
+        Ok(())
+    }
+
+    /// Gets the position of a serialized shared value by address and the type
+    /// it was serialized as.
+    ///
+    /// Values which are serialized in different formats may have the same
+    /// address (e.g. an `Rc<str>` which is also serialized as an
+    /// [`ArchivedCow`](crate::cow::ArchivedCow)), so they are kept apart from
+    /// each other and from untyped shared pointers. Returns `None` if no value
+    /// with the address and type has been added. By default, typed values are
+    /// never shared.
+    fn get_typed_shared_ptr(
+        &self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Option<usize> {
+        let _ = (address, type_id);
+        None
+    }
+
+    /// Adds the serialized position of a shared value with the type it was
+    /// serialized as.
+    fn add_typed_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+        pos: usize,
+    ) -> Result<(), E> {
+        let _ = (address, type_id, pos);
+        Ok(())
+    }
 }
 
 impl<T, E> Sharing<E> for Strategy<T, E>
@@ -35,6 +114,51 @@ where
     fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
         T::add_shared_ptr(self, address, pos)
     }
+
+    fn start_shared_ptr(&mut self, address: usize) -> Result<(), E> {
+        T::start_shared_ptr(self, address)
+    }
+
+    fn check_shared_type(&mut self, type_name: &'static str) -> Result<(), E> {
+        T::check_shared_type(self, type_name)
+    }
+
+    fn get_typed_shared_ptr(
+        &self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Option<usize> {
+        T::get_typed_shared_ptr(self, address, type_id)
+    }
+
+    fn add_typed_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+        pos: usize,
+    ) -> Result<(), E> {
+        T::add_typed_shared_ptr(self, address, type_id, pos)
+    }
+}
+
+/// A shared pointer strategy that can forget shared pointers.
+///
+/// This is used to reset a serializer to a
+/// [`Checkpoint`](crate::ser::writer::Checkpoint) after a serialization fails
+/// partway through. See [`Serializer::reset`](crate::ser::Serializer::reset).
+pub trait RewindSharing {
+    /// Removes all of the shared pointers which were added at or after the
+    /// given position.
+    fn rewind_shared(&mut self, pos: usize);
+}
+
+impl<T, E> RewindSharing for Strategy<T, E>
+where
+    T: RewindSharing + ?Sized,
+{
+    fn rewind_shared(&mut self, pos: usize) {
+        T::rewind_shared(self, pos)
+    }
 }
 
 /// Helper methods for [`Sharing`].
@@ -65,9 +189,11 @@ pub trait SharingExt<E>: Sharing<E> {
     where
         Self: Fallible<Error = E>,
     {
+        self.check_shared_type(::core::any::type_name::<T>())?;
         if let Some(pos) = self.get_shared(value) {
             Ok(pos)
         } else {
+            self.start_shared_ptr(value as *const T as *const () as usize)?;
             let pos = value.serialize_unsized(self)?;
             self.add_shared(value, pos)?;
             Ok(pos)
@@ -0,0 +1,191 @@
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+use core::{any::TypeId, fmt};
+#[cfg(feature = "std")]
+use std::collections::hash_map;
+
+#[cfg(not(feature = "std"))]
+use hashbrown::hash_map;
+use rancor::{fail, Fallible, Source, Strategy};
+
+use crate::ser::{
+    sharing::{RewindSharing, Share},
+    Sharing,
+};
+
+/// A strategy which assigns symbols to strings.
+///
+/// This trait is required to serialize fields with
+/// [`SymbolKeys`](crate::with::SymbolKeys) and to serialize
+/// [`Symbols`](crate::collections::symbol_table::Symbols).
+pub trait Interning<E = <Self as Fallible>::Error> {
+    /// Returns the symbol for the given string, assigning it the next unused
+    /// symbol if it has not been interned yet.
+    fn intern(&mut self, value: &str) -> Result<u32, E>;
+
+    /// Takes the interned strings in order of their symbols.
+    ///
+    /// After the strings have been taken, no more strings can be interned.
+    fn take_interned(&mut self) -> Result<Vec<String>, E>;
+}
+
+impl<T, E> Interning<E> for Strategy<T, E>
+where
+    T: Interning<E> + ?Sized,
+{
+    fn intern(&mut self, value: &str) -> Result<u32, E> {
+        T::intern(self, value)
+    }
+
+    fn take_interned(&mut self) -> Result<Vec<String>, E> {
+        T::take_interned(self)
+    }
+}
+
+#[derive(Debug)]
+struct TooManySymbols;
+
+impl fmt::Display for TooManySymbols {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "more than {} strings were interned", u32::MAX)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TooManySymbols {}
+
+#[derive(Debug)]
+struct SymbolTableTaken;
+
+impl fmt::Display for SymbolTableTaken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the interned strings were already taken, strings must be \
+             interned before the symbol table is serialized"
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SymbolTableTaken {}
+
+/// A shared pointer strategy which also interns strings.
+///
+/// Shared pointers are shared the same way as [`Share`], and each distinct
+/// interned string is assigned a `u32` symbol in the order that it was first
+/// interned.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    share: Share,
+    symbols: hash_map::HashMap<String, u32>,
+    taken: bool,
+}
+
+impl SymbolTable {
+    /// Creates a new, empty symbol table.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new, empty symbol table which shares pointers with the given
+    /// [`Share`].
+    #[inline]
+    pub fn with_share(share: Share) -> Self {
+        Self {
+            share,
+            symbols: hash_map::HashMap::new(),
+            taken: false,
+        }
+    }
+
+    /// Returns the number of interned strings which have not been taken.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns whether there are no interned strings which have not been
+    /// taken.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Returns the symbol of the given string, or `None` if it has not been
+    /// interned.
+    #[inline]
+    pub fn get(&self, value: &str) -> Option<u32> {
+        self.symbols.get(value).copied()
+    }
+}
+
+impl<E: Source> Sharing<E> for SymbolTable {
+    fn get_shared_ptr(&self, address: usize) -> Option<usize> {
+        Sharing::<E>::get_shared_ptr(&self.share, address)
+    }
+
+    fn add_shared_ptr(&mut self, address: usize, pos: usize) -> Result<(), E> {
+        self.share.add_shared_ptr(address, pos)
+    }
+
+    fn start_shared_ptr(&mut self, address: usize) -> Result<(), E> {
+        self.share.start_shared_ptr(address)
+    }
+
+    fn get_typed_shared_ptr(
+        &self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Option<usize> {
+        Sharing::<E>::get_typed_shared_ptr(&self.share, address, type_id)
+    }
+
+    fn add_typed_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+        pos: usize,
+    ) -> Result<(), E> {
+        self.share.add_typed_shared_ptr(address, type_id, pos)
+    }
+}
+
+impl RewindSharing for SymbolTable {
+    fn rewind_shared(&mut self, pos: usize) {
+        // Interned strings are kept, since their symbols may have already been
+        // written before the given position.
+        self.share.rewind_shared(pos);
+    }
+}
+
+impl<E: Source> Interning<E> for SymbolTable {
+    fn intern(&mut self, value: &str) -> Result<u32, E> {
+        if self.taken {
+            fail!(SymbolTableTaken);
+        }
+
+        if let Some(symbol) = self.symbols.get(value) {
+            return Ok(*symbol);
+        }
+
+        let symbol = match u32::try_from(self.symbols.len()) {
+            Ok(symbol) => symbol,
+            Err(_) => fail!(TooManySymbols),
+        };
+        self.symbols.insert(String::from(value), symbol);
+        Ok(symbol)
+    }
+
+    fn take_interned(&mut self) -> Result<Vec<String>, E> {
+        if self.taken {
+            fail!(SymbolTableTaken);
+        }
+        self.taken = true;
+
+        let mut symbols = self.symbols.drain().collect::<Vec<_>>();
+        symbols.sort_unstable_by_key(|(_, symbol)| *symbol);
+        Ok(symbols.into_iter().map(|(value, _)| value).collect())
+    }
+}
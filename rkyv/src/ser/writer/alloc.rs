@@ -2,8 +2,11 @@
 use alloc::vec::Vec;
 
 use crate::{
-    ser::{Positional, Writer},
-    util::AlignedVec,
+    ser::{
+        writer::{Checkpoint, Rewind},
+        Positional, Writer,
+    },
+    util::{allocator::Allocator, AlignedVec},
 };
 
 impl Positional for Vec<u8> {
@@ -18,18 +21,48 @@ impl<E> Writer<E> for Vec<u8> {
         self.extend_from_slice(bytes);
         Ok(())
     }
+
+    #[inline]
+    fn reserve_hint(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+}
+
+impl Rewind for Vec<u8> {
+    #[inline]
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.truncate(checkpoint.pos());
+    }
 }
 
-impl<const A: usize> Positional for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> Positional for AlignedVec<A, Alloc> {
     #[inline]
     fn pos(&self) -> usize {
         self.len()
     }
 }
 
-impl<E, const A: usize> Writer<E> for AlignedVec<A> {
+impl<E, const A: usize, Alloc: Allocator> Writer<E> for AlignedVec<A, Alloc> {
     fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
         self.extend_from_slice(bytes);
         Ok(())
     }
+
+    #[inline]
+    fn reserve_hint(&mut self, additional: usize) {
+        self.reserve(additional);
+    }
+}
+
+impl<const A: usize, Alloc: Allocator> Rewind for AlignedVec<A, Alloc> {
+    #[inline]
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        if checkpoint.pos() < self.len() {
+            // SAFETY: The new length is less than the current length, so all of
+            // the bytes up to it are initialized.
+            unsafe {
+                self.set_len(checkpoint.pos());
+            }
+        }
+    }
 }
@@ -0,0 +1,228 @@
+use std::io;
+
+use rancor::{ResultExt as _, Source};
+
+use crate::ser::{Positional, Writer};
+
+/// A writer which compresses the bytes written to it.
+///
+/// Positions are offsets into the uncompressed archive, so serializers work
+/// the same as they do with an uncompressed writer. The compressor buffers its
+/// input internally and writes compressed frames to the inner writer as it
+/// goes, so archives can be streamed to storage without holding the whole
+/// archive in memory.
+///
+/// Compressed archives can't be accessed in place. They must be decompressed
+/// into an aligned buffer first, for example with
+/// [`decompress_lz4_to_aligned`](crate::util::decompress_lz4_to_aligned) or
+/// [`decompress_zstd_to_aligned`](crate::util::decompress_zstd_to_aligned).
+/// Compression is a convenience for storage and transport, and doesn't change
+/// how archives are accessed.
+///
+/// Call `finish` after serializing to flush the compressor and get the inner
+/// writer back. Dropping a `CompressedWriter` without finishing it may produce
+/// a truncated stream.
+#[derive(Debug)]
+pub struct CompressedWriter<C> {
+    compressor: C,
+    pos: usize,
+}
+
+impl<C> CompressedWriter<C> {
+    /// Returns a reference to the underlying compressor.
+    pub fn compressor(&self) -> &C {
+        &self.compressor
+    }
+
+    /// Returns the underlying compressor without finishing the stream.
+    pub fn into_compressor(self) -> C {
+        self.compressor
+    }
+}
+
+#[cfg(feature = "lz4_flex")]
+impl<W: io::Write> CompressedWriter<lz4_flex::frame::FrameEncoder<W>> {
+    /// Creates a new writer which compresses to the LZ4 frame format.
+    ///
+    /// LZ4 does not have compression levels.
+    pub fn lz4(inner: W) -> Self {
+        Self {
+            compressor: lz4_flex::frame::FrameEncoder::new(inner),
+            pos: 0,
+        }
+    }
+
+    /// Finishes the LZ4 frame and returns the inner writer.
+    pub fn finish<E: Source>(self) -> Result<W, E> {
+        self.compressor.finish().into_error()
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<W: io::Write> CompressedWriter<zstd::stream::Encoder<'static, W>> {
+    /// Creates a new writer which compresses to the Zstandard format with the
+    /// given compression level.
+    ///
+    /// Levels range from 1 to 22, and 0 uses the default level. Negative levels
+    /// trade compression ratio for speed.
+    ///
+    /// # Examples
+    /// ```
+    /// use rkyv::{
+    ///     rancor::Error,
+    ///     ser::{
+    ///         allocator::Arena, sharing::Share, writer::CompressedWriter,
+    ///         Serializer,
+    ///     },
+    ///     util::{decompress_zstd_to_aligned, serialize_into},
+    ///     Archived,
+    /// };
+    ///
+    /// let value = vec![42u32; 1024];
+    ///
+    /// let mut arena = Arena::new();
+    /// let writer = CompressedWriter::zstd::<Error>(Vec::new(), 3).unwrap();
+    /// let serializer = Serializer::new(writer, arena.acquire(), Share::new());
+    /// let compressed = serialize_into::<_, Error>(&value, serializer)
+    ///     .unwrap()
+    ///     .into_writer()
+    ///     .finish::<Error>()
+    ///     .unwrap();
+    /// assert!(compressed.len() < 1024);
+    ///
+    /// let bytes = decompress_zstd_to_aligned::<Error>(&compressed).unwrap();
+    /// let values = rkyv::access::<Archived<Vec<u32>>, Error>(&bytes).unwrap();
+    /// assert_eq!(values.len(), 1024);
+    /// ```
+    pub fn zstd<E: Source>(inner: W, level: i32) -> Result<Self, E> {
+        Ok(Self {
+            compressor: zstd::stream::Encoder::new(inner, level)
+                .into_error()?,
+            pos: 0,
+        })
+    }
+
+    /// Finishes the Zstandard frame and returns the inner writer.
+    pub fn finish<E: Source>(self) -> Result<W, E> {
+        self.compressor.finish().into_error()
+    }
+}
+
+impl<C> Positional for CompressedWriter<C> {
+    fn pos(&self) -> usize {
+        self.pos
+    }
+}
+
+impl<C: io::Write, E: Source> Writer<E> for CompressedWriter<C> {
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.compressor.write_all(bytes).into_error()?;
+        self.pos += bytes.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rancor::Panic;
+
+    use crate::{
+        ser::{
+            allocator::Arena, sharing::Share, writer::CompressedWriter,
+            Serializer,
+        },
+        to_bytes,
+        util::serialize_into,
+        Archive, Serialize,
+    };
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Log {
+        lines: Vec<String>,
+        counts: Vec<u64>,
+    }
+
+    fn log() -> Log {
+        Log {
+            lines: (0..200)
+                .map(|i| format!("request {} completed successfully", i % 7))
+                .collect(),
+            counts: (0..1000).map(|i| i % 4).collect(),
+        }
+    }
+
+    fn check(
+        value: &Log,
+        compressed: &[u8],
+        decompressed: &[u8],
+        expected: &[u8],
+    ) {
+        assert!(compressed.len() * 2 < expected.len());
+        assert_eq!(decompressed, expected);
+
+        let archived =
+            crate::access::<ArchivedLog, Panic>(decompressed).unwrap();
+        assert_eq!(archived.lines.len(), value.lines.len());
+        assert_eq!(archived.lines[8].as_str(), value.lines[8]);
+        assert_eq!(archived.counts[999].to_native(), 3);
+    }
+
+    #[cfg(feature = "lz4_flex")]
+    #[test]
+    fn lz4_round_trip() {
+        use crate::util::decompress_lz4_to_aligned;
+
+        let value = log();
+        let expected = to_bytes::<Panic>(&value).unwrap();
+
+        let mut arena = Arena::new();
+        let writer = CompressedWriter::lz4(Vec::new());
+        let serializer = Serializer::new(writer, arena.acquire(), Share::new());
+        let compressed = serialize_into::<_, Panic>(&value, serializer)
+            .unwrap()
+            .into_writer()
+            .finish::<Panic>()
+            .unwrap();
+
+        let decompressed =
+            decompress_lz4_to_aligned::<Panic>(&compressed).unwrap();
+        check(&value, &compressed, &decompressed, &expected);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip() {
+        use crate::util::decompress_zstd_to_aligned;
+
+        let value = log();
+        let expected = to_bytes::<Panic>(&value).unwrap();
+
+        for level in [-5, 0, 1, 3, 9, 19] {
+            let mut arena = Arena::new();
+            let writer =
+                CompressedWriter::zstd::<Panic>(Vec::new(), level).unwrap();
+            let serializer =
+                Serializer::new(writer, arena.acquire(), Share::new());
+            let compressed = serialize_into::<_, Panic>(&value, serializer)
+                .unwrap()
+                .into_writer()
+                .finish::<Panic>()
+                .unwrap();
+
+            let decompressed =
+                decompress_zstd_to_aligned::<Panic>(&compressed).unwrap();
+            check(&value, &compressed, &decompressed, &expected);
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn invalid_compressed_bytes() {
+        use rancor::Failure;
+
+        use crate::util::decompress_zstd_to_aligned;
+
+        assert!(decompress_zstd_to_aligned::<Failure>(b"not zstd").is_err());
+    }
+}
@@ -9,10 +9,13 @@ use core::{
 
 use rancor::{fail, Source};
 
-use crate::ser::{Positional, Writer};
+use crate::ser::{
+    writer::{Checkpoint, Rewind},
+    Positional, Writer,
+};
 
 #[derive(Debug)]
-struct BufferOverflow {
+pub(crate) struct BufferOverflow {
     write_len: usize,
     cap: usize,
     len: usize,
@@ -145,6 +148,13 @@ impl Positional for Buffer<'_> {
     }
 }
 
+impl Rewind for Buffer<'_> {
+    #[inline]
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.len = usize::min(self.len, checkpoint.pos());
+    }
+}
+
 impl<E: Source> Writer<E> for Buffer<'_> {
     fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
         if bytes.len() > self.cap - self.len {
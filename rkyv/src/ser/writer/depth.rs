@@ -0,0 +1,246 @@
+use core::fmt;
+
+use rancor::{fail, Source};
+
+use crate::ser::{
+    writer::{Checkpoint, Rewind},
+    Positional, Writer,
+};
+
+#[derive(Debug)]
+struct DepthLimitExceeded {
+    max_depth: usize,
+}
+
+impl fmt::Display for DepthLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "exceeded the maximum serialization depth of {}",
+            self.max_depth,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+const _: () = {
+    use std::error::Error;
+
+    impl Error for DepthLimitExceeded {}
+};
+
+/// Wraps a writer and limits how deeply values may be nested while
+/// serializing.
+///
+/// Serializing a value recurses into every value it points to, so a deeply
+/// nested structure (like a long linked list of boxes) can overflow the stack.
+/// `WithDepthLimit` counts how many out-of-line values are being serialized at
+/// once, and returns an error instead of recursing past `max_depth` of them.
+///
+/// The depth is tracked with the [`enter_nested`](Writer::enter_nested) and
+/// [`exit_nested`](Writer::exit_nested) hooks, so `WithDepthLimit` must be the
+/// writer that the serializer is created with.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::{
+///         allocator::Arena, sharing::Share, writer::WithDepthLimit,
+///         Serializer,
+///     },
+///     util::{serialize_into, AlignedVec},
+///     Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[rkyv(serialize_bounds(__S: rkyv::ser::Writer))]
+/// struct Node {
+///     #[rkyv(omit_bounds)]
+///     next: Option<Box<Node>>,
+/// }
+///
+/// // The root and both boxed nodes are each serialized out-of-line.
+/// let list = Node {
+///     next: Some(Box::new(Node {
+///         next: Some(Box::new(Node { next: None })),
+///     })),
+/// };
+///
+/// let mut arena = Arena::new();
+/// let serialize = |max_depth| {
+///     let writer = WithDepthLimit::new(AlignedVec::<16>::new(), max_depth);
+///     let serializer = Serializer::new(writer, arena.acquire(), Share::new());
+///     serialize_into::<_, Error>(&list, serializer).map(|_| ())
+/// };
+/// assert!(serialize(3).is_ok());
+/// assert!(serialize(2).is_err());
+/// ```
+#[derive(Debug)]
+pub struct WithDepthLimit<W> {
+    inner: W,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<W> WithDepthLimit<W> {
+    /// Wraps the given writer, allowing at most `max_depth` out-of-line values
+    /// to be nested inside of each other.
+    pub fn new(inner: W, max_depth: usize) -> Self {
+        Self {
+            inner,
+            depth: 0,
+            max_depth,
+        }
+    }
+
+    /// Returns the current nesting depth.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the maximum nesting depth.
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the `WithDepthLimit` and returns the wrapped writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Positional> Positional for WithDepthLimit<W> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<W: Writer<E>, E: Source> Writer<E> for WithDepthLimit<W> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn reserve_hint(&mut self, additional: usize) {
+        self.inner.reserve_hint(additional)
+    }
+
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), E> {
+        self.inner.write_vectored(bufs)
+    }
+
+    fn enter_nested(&mut self, type_name: &'static str) -> Result<(), E> {
+        if self.depth >= self.max_depth {
+            fail!(DepthLimitExceeded {
+                max_depth: self.max_depth,
+            });
+        }
+        self.inner.enter_nested(type_name)?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn exit_nested(&mut self) {
+        self.inner.exit_nested();
+        self.depth -= 1;
+    }
+}
+
+impl<W: Rewind> Rewind for WithDepthLimit<W> {
+    fn checkpoint(&self) -> Checkpoint {
+        self.inner.checkpoint()
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        self.inner.restore(checkpoint)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use rancor::Error;
+
+    use crate::{
+        ser::{
+            allocator::Arena, sharing::Share, writer::WithDepthLimit,
+            Serializer, Writer,
+        },
+        util::{serialize_into, AlignedVec},
+        Archive, Serialize,
+    };
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate)]
+    #[rkyv(serialize_bounds(__S: Writer))]
+    struct Node {
+        value: u32,
+        #[omit_bounds]
+        next: Option<Box<Node>>,
+    }
+
+    impl Node {
+        fn list(len: u32) -> Option<Box<Node>> {
+            let mut head = None;
+            for value in (0..len).rev() {
+                head = Some(Box::new(Node { value, next: head }));
+            }
+            head
+        }
+    }
+
+    // Dropping a long list recursively would overflow the stack.
+    impl Drop for Node {
+        fn drop(&mut self) {
+            let mut next = self.next.take();
+            while let Some(mut node) = next {
+                next = node.next.take();
+            }
+        }
+    }
+
+    fn serialize_with_limit(
+        value: &Option<Box<Node>>,
+        max_depth: usize,
+    ) -> Result<WithDepthLimit<AlignedVec>, Error> {
+        let mut arena = Arena::new();
+        let writer = WithDepthLimit::new(AlignedVec::<16>::new(), max_depth);
+        serialize_into::<_, Error>(
+            value,
+            Serializer::new(writer, arena.acquire(), Share::new()),
+        )
+        .map(|serializer| serializer.into_writer())
+    }
+
+    #[test]
+    fn shallow_values_are_serialized() {
+        let list = Node::list(10);
+        let writer = serialize_with_limit(&list, 100).unwrap();
+        assert_eq!(writer.depth(), 0);
+        assert!(!writer.inner().is_empty());
+    }
+
+    #[test]
+    fn deep_values_fail_before_overflowing() {
+        let result = std::thread::Builder::new()
+            .stack_size(1 << 20)
+            .spawn(|| {
+                let list = Node::list(100_000);
+                serialize_with_limit(&list, 100).map(|_| ())
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("maximum serialization depth of 100"));
+    }
+}
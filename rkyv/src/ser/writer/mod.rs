@@ -2,16 +2,25 @@
 
 #[cfg(feature = "alloc")]
 mod alloc;
+#[cfg(any(feature = "lz4_flex", feature = "zstd"))]
+mod compress;
 mod core;
+mod depth;
 #[cfg(feature = "std")]
 mod std;
+#[cfg(feature = "alloc")]
+mod trace;
 
 use ::core::mem;
 use rancor::{Fallible, Strategy};
 
-pub use self::core::*;
+#[cfg(any(feature = "lz4_flex", feature = "zstd"))]
+pub use self::compress::*;
 #[cfg(feature = "std")]
 pub use self::std::*;
+#[cfg(feature = "alloc")]
+pub use self::trace::*;
+pub use self::{core::*, depth::*};
 use crate::{Archive, ArchiveUnsized, Place, RelPtr};
 
 /// A writer that knows its current position.
@@ -29,6 +38,45 @@ where
     }
 }
 
+/// A position which a [`Rewind`] writer can be restored to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    pos: usize,
+}
+
+impl Checkpoint {
+    /// Returns the position of the writer when the checkpoint was taken.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+}
+
+/// A writer that can discard the bytes written after an earlier position.
+pub trait Rewind: Positional {
+    /// Returns a checkpoint of the current position of the writer.
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint { pos: self.pos() }
+    }
+
+    /// Discards all of the bytes written after the given checkpoint.
+    ///
+    /// Does nothing if the writer is not past the checkpoint.
+    fn restore(&mut self, checkpoint: Checkpoint);
+}
+
+impl<T, E> Rewind for Strategy<T, E>
+where
+    T: Rewind + ?Sized,
+{
+    fn checkpoint(&self) -> Checkpoint {
+        T::checkpoint(self)
+    }
+
+    fn restore(&mut self, checkpoint: Checkpoint) {
+        T::restore(self, checkpoint)
+    }
+}
+
 /// A type that writes bytes to some output.
 ///
 /// A type that is [`Write`](::std::io::Write) can be wrapped in an [`IoWriter`]
@@ -41,6 +89,47 @@ where
 pub trait Writer<E = <Self as Fallible>::Error>: Positional {
     /// Attempts to write the given bytes to the serializer.
     fn write(&mut self, bytes: &[u8]) -> Result<(), E>;
+
+    /// Hints that at least `additional` more bytes are about to be written.
+    ///
+    /// Writers which buffer their output can use this to reserve space for the
+    /// bytes ahead of time. The default implementation does nothing.
+    #[inline]
+    fn reserve_hint(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Attempts to write the given buffers to the serializer, in order.
+    ///
+    /// The default implementation writes each buffer with
+    /// [`write`](Writer::write).
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), E> {
+        for buf in bufs {
+            self.write(buf)?;
+        }
+        Ok(())
+    }
+
+    /// Called before a value is serialized out-of-line, which may recurse into
+    /// the values it contains. `type_name` is the name of the type of the
+    /// value.
+    ///
+    /// Writers can return an error to stop serializing values which are nested
+    /// too deeply, or record which types the following bytes belong to. Every
+    /// successful call is followed by a call to
+    /// [`exit_nested`](Writer::exit_nested). The default implementation does
+    /// nothing.
+    #[inline]
+    fn enter_nested(&mut self, type_name: &'static str) -> Result<(), E> {
+        let _ = type_name;
+        Ok(())
+    }
+
+    /// Called after a value entered with
+    /// [`enter_nested`](Writer::enter_nested) has been serialized, whether or
+    /// not serializing it succeeded. The default implementation does nothing.
+    #[inline]
+    fn exit_nested(&mut self) {}
 }
 
 impl<T, E> Writer<E> for Strategy<T, E>
@@ -50,16 +139,40 @@ where
     fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
         T::write(self, bytes)
     }
+
+    #[inline]
+    fn reserve_hint(&mut self, additional: usize) {
+        T::reserve_hint(self, additional)
+    }
+
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), E> {
+        T::write_vectored(self, bufs)
+    }
+
+    #[inline]
+    fn enter_nested(&mut self, type_name: &'static str) -> Result<(), E> {
+        T::enter_nested(self, type_name)
+    }
+
+    #[inline]
+    fn exit_nested(&mut self) {
+        T::exit_nested(self)
+    }
 }
 
 /// Helper methods for [`Writer`].
 pub trait WriterExt<E>: Writer<E> {
     /// Advances the given number of bytes as padding.
-    fn pad(&mut self, padding: usize) -> Result<(), E> {
+    fn pad(&mut self, mut padding: usize) -> Result<(), E> {
         const MAX_ZEROES: usize = 32;
         const ZEROES: [u8; MAX_ZEROES] = [0; MAX_ZEROES];
-        debug_assert!(padding < MAX_ZEROES);
 
+        // Alignments larger than `MAX_ZEROES` can require more padding than
+        // fits in a single write.
+        while padding > MAX_ZEROES {
+            self.write(&ZEROES)?;
+            padding -= MAX_ZEROES;
+        }
         self.write(&ZEROES[0..padding])
     }
 
@@ -136,3 +249,61 @@ pub trait WriterExt<E>: Writer<E> {
 }
 
 impl<T, E> WriterExt<E> for T where T: Writer<E> + ?Sized {}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{string::ToString, vec, vec::Vec};
+
+    use rancor::Panic;
+
+    use crate::{
+        ser::{sharing::Share, Positional, Serializer, Writer},
+        util::{serialize_into, with_arena},
+    };
+
+    #[derive(Default)]
+    struct HintWriter {
+        bytes: Vec<u8>,
+        hinted: usize,
+    }
+
+    impl Positional for HintWriter {
+        fn pos(&self) -> usize {
+            self.bytes.len()
+        }
+    }
+
+    impl<E> Writer<E> for HintWriter {
+        fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+            self.bytes.extend_from_slice(bytes);
+            Ok(())
+        }
+
+        fn reserve_hint(&mut self, additional: usize) {
+            self.hinted += additional;
+        }
+    }
+
+    #[test]
+    fn reserve_hints() {
+        let value = (
+            vec![1u32; 100],
+            "a string which is too long to be inline".to_string(),
+        );
+
+        let writer = with_arena(|arena| {
+            serialize_into::<_, Panic>(
+                &value,
+                Serializer::new(
+                    HintWriter::default(),
+                    arena.acquire(),
+                    Share::new(),
+                ),
+            )
+            .unwrap()
+            .into_writer()
+        });
+        assert!(writer.hinted >= 100 * 4 + value.1.len());
+    }
+}
@@ -59,16 +59,137 @@ impl<W: io::Write, E: Source> Writer<E> for IoWriter<W> {
         self.pos += bytes.len();
         Ok(())
     }
+
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), E> {
+        // Buffers are passed to the inner writer in batches so that the
+        // `IoSlice`s can live on the stack.
+        const MAX_SLICES: usize = 32;
+
+        let mut slices = [io::IoSlice::new(&[]); MAX_SLICES];
+        for chunk in bufs.chunks(MAX_SLICES) {
+            for (slice, buf) in slices.iter_mut().zip(chunk) {
+                *slice = io::IoSlice::new(buf);
+            }
+            self.write_io_slices(&slices[..chunk.len()])?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: io::Write> IoWriter<W> {
+    fn write_io_slices<E: Source>(
+        &mut self,
+        mut bufs: &[io::IoSlice<'_>],
+    ) -> Result<(), E> {
+        let mut written = 0;
+        loop {
+            // Skip past empty buffers and buffers which were written completely
+            while let Some((first, rest)) = bufs.split_first() {
+                if written < first.len() {
+                    break;
+                }
+                written -= first.len();
+                bufs = rest;
+            }
+
+            if bufs.is_empty() {
+                return Ok(());
+            }
+
+            // Finish writing a buffer which was written partially
+            if written > 0 {
+                Writer::<E>::write(self, &bufs[0][written..])?;
+                bufs = &bufs[1..];
+                written = 0;
+                continue;
+            }
+
+            written = match self.inner.write_vectored(bufs) {
+                Ok(0) => {
+                    return Err(io::Error::from(io::ErrorKind::WriteZero))
+                        .into_error();
+                }
+                Ok(written) => written,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => 0,
+                Err(e) => return Err(e).into_error(),
+            };
+            self.pos += written;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use rancor::Failure;
+    use std::io;
+
+    use rancor::{Failure, Panic, Strategy};
 
     use crate::{
-        ser::writer::IoWriter, serialize, util::Align, Archive, Serialize,
+        ser::{writer::IoWriter, Positional as _, Writer as _},
+        serialize,
+        util::Align,
+        Archive, Serialize,
     };
 
+    /// A writer which writes at most a few bytes at a time.
+    struct Trickle(Vec<u8>);
+
+    impl io::Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let len = buf.len().min(3);
+            self.0.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn write_vectored(
+            &mut self,
+            bufs: &[io::IoSlice<'_>],
+        ) -> io::Result<usize> {
+            let mut written = 0;
+            for buf in bufs {
+                let len = buf.len().min(5 - written);
+                self.0.extend_from_slice(&buf[..len]);
+                written += len;
+                if written == 5 {
+                    break;
+                }
+            }
+            Ok(written)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_vectored() {
+        let bufs: [&[u8]; 4] = [b"hello", b"", b" vectored", b" world"];
+
+        let mut io_writer = IoWriter::new(Trickle(Vec::new()));
+        let mut writer = Strategy::<_, Panic>::wrap(&mut io_writer);
+        writer.write_vectored(&[b""]).unwrap();
+        writer.write_vectored(&bufs).unwrap();
+        assert_eq!(writer.pos(), 20);
+        assert_eq!(io_writer.into_inner().0, b"hello vectored world");
+
+        // More buffers than fit in a single batch
+        let bytes = (0..100u8).collect::<Vec<_>>();
+        let many = bytes.chunks(1).collect::<Vec<_>>();
+        let mut io_writer = IoWriter::new(Trickle(Vec::new()));
+        let mut writer = Strategy::<_, Panic>::wrap(&mut io_writer);
+        writer.write_vectored(&many).unwrap();
+        assert_eq!(writer.pos(), 100);
+        assert_eq!(io_writer.into_inner().0, bytes);
+
+        let mut buf = [0u8; 8];
+        let mut io_writer = IoWriter::new(&mut buf[..]);
+        let mut writer = Strategy::<_, Failure>::wrap(&mut io_writer);
+        writer
+            .write_vectored(&bufs)
+            .expect_err("wrote to an undersized buffer");
+    }
+
     #[test]
     fn write_serializer() {
         #[derive(Archive, Serialize)]
@@ -0,0 +1,417 @@
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::ser::{Positional, Writer};
+
+/// A record of the bytes written while serializing one out-of-line value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceEvent {
+    type_name: &'static str,
+    parent: Option<usize>,
+    start: usize,
+    len: usize,
+    self_len: usize,
+}
+
+impl TraceEvent {
+    /// Returns the name of the type of the value.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the index of the event for the value that this value was
+    /// serialized inside of, if any.
+    pub fn parent(&self) -> Option<usize> {
+        self.parent
+    }
+
+    /// Returns the position of the writer when the value started serializing.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the number of bytes written while serializing the value,
+    /// including the bytes of the values nested inside of it.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether no bytes were written while serializing the value.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of bytes written while serializing the value,
+    /// excluding the bytes of the values nested inside of it.
+    pub fn self_len(&self) -> usize {
+        self.self_len
+    }
+}
+
+/// Wraps a writer and records which types the written bytes belong to.
+///
+/// Each value which is serialized out-of-line (like the root value and the
+/// contents of boxes, vectors, and strings) produces a [`TraceEvent`]. The
+/// bytes written while serializing a value are attributed to the innermost
+/// value being serialized, so that the sizes in a [`SizeReport`] add up to the
+/// number of bytes written.
+///
+/// Events are recorded with the [`enter_nested`](Writer::enter_nested) and
+/// [`exit_nested`](Writer::exit_nested) hooks, so `TracingWriter` must be the
+/// writer that the serializer is created with.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::{
+///         allocator::Arena, sharing::Share, writer::TracingWriter, Serializer,
+///     },
+///     util::serialize_into,
+/// };
+///
+/// let value = vec!["a string which is too long to be inline".to_string()];
+///
+/// let mut arena = Arena::new();
+/// let writer = TracingWriter::new(Vec::new());
+/// let serializer = Serializer::new(writer, arena.acquire(), Share::new());
+/// let writer = serialize_into::<_, Error>(&value, serializer)
+///     .unwrap()
+///     .into_writer();
+///
+/// let report = writer.report();
+/// assert_eq!(report.total(), writer.inner().len());
+/// assert!(report.by_type().iter().any(|entry| entry.name() == "str"));
+/// ```
+#[derive(Debug)]
+pub struct TracingWriter<W> {
+    inner: W,
+    events: Vec<TraceEvent>,
+    // The index of each event which is being serialized, along with the
+    // number of bytes written by the values nested inside of it so far.
+    stack: Vec<(usize, usize)>,
+}
+
+impl<W> TracingWriter<W> {
+    /// Wraps the given writer.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            events: Vec::new(),
+            stack: Vec::new(),
+        }
+    }
+
+    /// Returns the events recorded so far, in the order that their values
+    /// started serializing.
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// Returns a report of the sizes of the values serialized so far.
+    pub fn report(&self) -> SizeReport {
+        SizeReport {
+            events: self.events.clone(),
+        }
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn inner(&self) -> &W {
+        &self.inner
+    }
+
+    /// Consumes the `TracingWriter` and returns the wrapped writer and a
+    /// report of the sizes of the values which were serialized.
+    pub fn into_parts(self) -> (W, SizeReport) {
+        (
+            self.inner,
+            SizeReport {
+                events: self.events,
+            },
+        )
+    }
+}
+
+impl<W: Positional> Positional for TracingWriter<W> {
+    #[inline]
+    fn pos(&self) -> usize {
+        self.inner.pos()
+    }
+}
+
+impl<W: Writer<E>, E> Writer<E> for TracingWriter<W> {
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> Result<(), E> {
+        self.inner.write(bytes)
+    }
+
+    #[inline]
+    fn reserve_hint(&mut self, additional: usize) {
+        self.inner.reserve_hint(additional)
+    }
+
+    fn write_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), E> {
+        self.inner.write_vectored(bufs)
+    }
+
+    fn enter_nested(&mut self, type_name: &'static str) -> Result<(), E> {
+        self.inner.enter_nested(type_name)?;
+        self.stack.push((self.events.len(), 0));
+        self.events.push(TraceEvent {
+            type_name,
+            parent: self.stack.iter().rev().nth(1).map(|&(index, _)| index),
+            start: self.inner.pos(),
+            len: 0,
+            self_len: 0,
+        });
+        Ok(())
+    }
+
+    fn exit_nested(&mut self) {
+        self.inner.exit_nested();
+        let (index, nested_len) = self.stack.pop().unwrap();
+        let event = &mut self.events[index];
+        event.len = self.inner.pos().saturating_sub(event.start);
+        event.self_len = event.len.saturating_sub(nested_len);
+        if let Some((_, parent_nested_len)) = self.stack.last_mut() {
+            *parent_nested_len += event.len;
+        }
+    }
+}
+
+/// The number of bytes attributed to a type or callsite in a [`SizeReport`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SizeEntry {
+    name: String,
+    count: usize,
+    bytes: usize,
+}
+
+impl SizeEntry {
+    /// Returns the name of the type or callsite.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the number of values which were serialized.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the number of bytes attributed to the values, excluding the
+    /// bytes of the values nested inside of them.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+impl fmt::Display for SizeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>10} bytes {:>8}x  {}",
+            self.bytes, self.count, self.name
+        )
+    }
+}
+
+/// A breakdown of the bytes written by a [`TracingWriter`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SizeReport {
+    events: Vec<TraceEvent>,
+}
+
+impl SizeReport {
+    /// Returns the recorded events, in the order that their values started
+    /// serializing.
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// Returns the total number of bytes attributed to the recorded events.
+    pub fn total(&self) -> usize {
+        self.events.iter().map(TraceEvent::self_len).sum()
+    }
+
+    /// Returns the number of bytes attributed to each type, largest first.
+    pub fn by_type(&self) -> Vec<SizeEntry> {
+        group(
+            self.events
+                .iter()
+                .map(|event| (String::from(event.type_name), event.self_len)),
+        )
+    }
+
+    /// Returns the number of bytes attributed to each callsite, largest first.
+    ///
+    /// The callsite of a value is the path of types which were being serialized
+    /// when it was serialized, like `Document > [Section] > str`.
+    pub fn by_callsite(&self) -> Vec<SizeEntry> {
+        group(
+            self.events
+                .iter()
+                .map(|event| (self.callsite(event), event.self_len)),
+        )
+    }
+
+    fn callsite(&self, event: &TraceEvent) -> String {
+        let mut names = Vec::new();
+        let mut current = Some(event);
+        while let Some(event) = current {
+            names.push(event.type_name);
+            current = event.parent.map(|index| &self.events[index]);
+        }
+
+        let mut result = String::new();
+        for (i, name) in names.iter().rev().enumerate() {
+            if i != 0 {
+                result.push_str(" > ");
+            }
+            result.push_str(name);
+        }
+        result
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in self.by_type() {
+            writeln!(f, "{}", entry)?;
+        }
+        write!(f, "{:>10} bytes total", self.total())
+    }
+}
+
+fn group(sizes: impl Iterator<Item = (String, usize)>) -> Vec<SizeEntry> {
+    let mut entries = BTreeMap::<String, (usize, usize)>::new();
+    for (name, bytes) in sizes {
+        let entry = entries.entry(name).or_default();
+        entry.0 += 1;
+        entry.1 += bytes;
+    }
+
+    let mut result = entries
+        .into_iter()
+        .map(|(name, (count, bytes))| SizeEntry { name, count, bytes })
+        .collect::<Vec<_>>();
+    result.sort_by(|a, b| {
+        b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name))
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{format, string::String, vec, vec::Vec};
+
+    use rancor::{Error, Source, Strategy};
+
+    use super::{SizeReport, TracingWriter};
+    use crate::{
+        ser::{
+            allocator::{Arena, ArenaHandle},
+            sharing::Share,
+            Allocator, Serializer, Writer,
+        },
+        util::{serialize_into, AlignedVec},
+        Archive, Serialize,
+    };
+
+    type TracingSerializer<'a> = Strategy<
+        Serializer<TracingWriter<AlignedVec>, ArenaHandle<'a>, Share>,
+        Error,
+    >;
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate)]
+    #[rkyv(serialize_bounds(__S: Writer + Allocator, __S::Error: Source))]
+    struct Section {
+        title: String,
+        #[omit_bounds]
+        children: Vec<Section>,
+    }
+
+    fn trace<T>(value: &T) -> (AlignedVec, SizeReport)
+    where
+        T: for<'a> Serialize<TracingSerializer<'a>>,
+    {
+        let mut arena = Arena::new();
+        let writer = TracingWriter::new(AlignedVec::new());
+        serialize_into::<_, Error>(
+            value,
+            Serializer::new(writer, arena.acquire(), Share::new()),
+        )
+        .unwrap()
+        .into_writer()
+        .into_parts()
+    }
+
+    fn document() -> Section {
+        let leaf = |title: &str| Section {
+            title: title.repeat(16),
+            children: Vec::new(),
+        };
+        Section {
+            title: "the title of the document".into(),
+            children: vec![
+                Section {
+                    title: "the first section".into(),
+                    children: vec![leaf("a"), leaf("b")],
+                },
+                leaf("c"),
+            ],
+        }
+    }
+
+    #[test]
+    fn totals_match_buffer_len() {
+        let (bytes, report) = trace(&document());
+        assert_eq!(report.total(), bytes.len());
+
+        let by_type = report.by_type().iter().map(|e| e.bytes()).sum::<usize>();
+        assert_eq!(by_type, bytes.len());
+        let by_callsite = report
+            .by_callsite()
+            .iter()
+            .map(|e| e.bytes())
+            .sum::<usize>();
+        assert_eq!(by_callsite, bytes.len());
+
+        // The root event contains every byte.
+        let root = &report.events()[0];
+        assert_eq!(root.parent(), None);
+        assert_eq!(root.start(), 0);
+        assert_eq!(root.len(), bytes.len());
+    }
+
+    #[test]
+    fn entries_are_grouped() {
+        let (_, report) = trace(&document());
+        let section = core::any::type_name::<Section>();
+        let sections = core::any::type_name::<[Section]>();
+
+        let by_type = report.by_type();
+        let find = |name: &str| {
+            by_type.iter().find(|entry| entry.name() == name).unwrap()
+        };
+        assert_eq!(find(section).count(), 1);
+        // Every section has children, even if there are none of them.
+        assert_eq!(find(sections).count(), 5);
+        // Every title is too long to be inline.
+        assert_eq!(find("str").count(), 5);
+
+        let by_callsite = report.by_callsite();
+        let nested = format!("{section} > {sections} > {sections} > str");
+        let entry = by_callsite
+            .iter()
+            .find(|entry| entry.name() == nested)
+            .unwrap();
+        assert_eq!(entry.count(), 2);
+        assert_eq!(entry.bytes(), 32);
+    }
+}
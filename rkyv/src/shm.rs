@@ -0,0 +1,578 @@
+//! Archives in shared memory.
+//!
+//! [`ShmArchive`] serializes a value directly into a named shared memory
+//! mapping, and lets other processes open the same mapping by name and access
+//! the archived value after validating it.
+//!
+//! On Linux, shared memory mappings are backed by files in `/dev/shm`. On other
+//! Unix platforms they are backed by files in the temporary directory.
+//!
+//! # Mapping layout
+//!
+//! A mapping consists of a [`HEADER_LEN`]-byte header followed by the archive
+//! bytes:
+//!
+//! | Offset | Size | Contents                                                |
+//! |--------|------|---------------------------------------------------------|
+//! | 0      | 4    | A native-endian magic number, written after the archive |
+//! |        |      | is complete.                                            |
+//! | 4      | 4    | A native-endian lock word.                              |
+//! | 8      | 8    | The length of the archive bytes, as a little-endian     |
+//! |        |      | `u64`.                                                  |
+//!
+//! Mappings are page-aligned, so the archive bytes are always aligned.
+//!
+//! # Mutation
+//!
+//! Every modification must be made while holding the advisory lock returned by
+//! [`ShmArchive::lock`]. This includes modifications of fields which are
+//! archived as atomics (for example with [`AsAtomic`](crate::with::AsAtomic)),
+//! because [`ShmGuard::archived_mut`] hands out a mutable reference to the
+//! whole archived value. The lock is a spinlock stored in the mapping header,
+//! so it is shared by every process which has the mapping open. A process which
+//! exits while holding the lock leaves it locked.
+//!
+//! The archived value can always be read through the [`ShmGuard`] returned by
+//! the lock. [`ShmArchive::archived`] returns a reference without taking the
+//! lock, which is only sound while no process holds the lock.
+//!
+//! # Examples
+//!
+//! ```
+//! use core::sync::atomic::{AtomicU32, Ordering};
+//!
+//! use rkyv::{
+//!     rancor::Error,
+//!     shm::ShmArchive,
+//!     with::{AsAtomic, Relaxed},
+//!     Archive, Serialize,
+//! };
+//!
+//! #[derive(Archive, Serialize)]
+//! #[rkyv(check_bytes)]
+//! struct Stats {
+//!     #[with(AsAtomic<Relaxed, Relaxed>)]
+//!     requests: AtomicU32,
+//!     generation: u32,
+//! }
+//!
+//! let name = format!("rkyv-doc-stats-{}", std::process::id());
+//! let stats = Stats {
+//!     requests: AtomicU32::new(0),
+//!     generation: 1,
+//! };
+//!
+//! // SAFETY: Every process opening this mapping uses `Stats` and only
+//! // modifies it through a `ShmGuard`.
+//! let _owner =
+//!     unsafe { ShmArchive::<Stats>::create::<Error>(&name, 4096, &stats) }
+//!         .unwrap();
+//!
+//! // This would usually happen in another process.
+//! let mut shm = unsafe { ShmArchive::<Stats>::open::<Error>(&name) }.unwrap();
+//! {
+//!     let mut guard = shm.lock();
+//!     guard.requests.fetch_add(1, Ordering::Relaxed);
+//!     let stats = guard.archived_mut().get_mut();
+//!     stats.generation = (stats.generation.to_native() + 1).into();
+//! }
+//!
+//! let guard = shm.lock();
+//! assert_eq!(guard.requests.load(Ordering::Relaxed), 1);
+//! assert_eq!(guard.generation, 2);
+//! drop(guard);
+//!
+//! ShmArchive::<Stats>::unlink::<Error>(&name).unwrap();
+//! ```
+
+use core::{
+    fmt,
+    marker::PhantomData,
+    ops::Deref,
+    pin::Pin,
+    slice,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use std::{
+    fs::{self, OpenOptions},
+    path::PathBuf,
+};
+
+use bytecheck::CheckBytes;
+use memmap2::MmapMut;
+use rancor::{fail, ResultExt as _, Source, Strategy};
+
+use crate::{
+    access_unchecked, access_unchecked_mut,
+    ser::{writer::Buffer, DefaultSerializer},
+    validation::validators::DefaultValidator,
+    Archive, Serialize,
+};
+
+/// The length of the mapping header in bytes.
+pub const HEADER_LEN: usize = 16;
+
+const MAGIC: u32 = u32::from_ne_bytes(*b"rksm");
+const UNLOCKED: u32 = 0;
+const LOCKED: u32 = 1;
+
+/// An error which occurs when creating or opening a shared memory archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShmError {
+    /// The mapping name was empty or contained a `/`.
+    InvalidName,
+    /// The mapping has not finished being created.
+    NotInitialized,
+    /// The mapping header was corrupted.
+    InvalidHeader,
+    /// The capacity was too large for a mapping, or the archive length in the
+    /// mapping header was larger than the capacity of the mapping.
+    CapacityOverflow,
+}
+
+impl fmt::Display for ShmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidName => write!(
+                f,
+                "shared memory names must be nonempty and must not contain '/'",
+            ),
+            Self::NotInitialized => {
+                write!(f, "shared memory archive is not initialized")
+            }
+            Self::InvalidHeader => {
+                write!(f, "invalid shared memory archive header")
+            }
+            Self::CapacityOverflow => {
+                write!(f, "shared memory archive capacity overflowed")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShmError {}
+
+fn shm_path(name: &str) -> Result<PathBuf, ShmError> {
+    if name.is_empty() || name.contains('/') {
+        return Err(ShmError::InvalidName);
+    }
+
+    #[cfg(target_os = "linux")]
+    let dir = PathBuf::from("/dev/shm");
+    #[cfg(not(target_os = "linux"))]
+    let dir = std::env::temp_dir();
+
+    Ok(dir.join(name))
+}
+
+/// An archived `T` in a named shared memory mapping.
+///
+/// See the [module docs](self) for the layout of the mapping and the rules for
+/// modifying it.
+pub struct ShmArchive<T: Archive> {
+    map: MmapMut,
+    len: usize,
+    _phantom: PhantomData<T::Archived>,
+}
+
+impl<T: Archive> ShmArchive<T> {
+    /// Creates a new shared memory mapping with the given name, and serializes
+    /// `value` into it.
+    ///
+    /// `capacity` is the maximum number of archive bytes. Returns an error if a
+    /// mapping with the given name already exists, or if the archive does not
+    /// fit in `capacity` bytes.
+    ///
+    /// The mapping is not removed when the returned archive is dropped. Call
+    /// [`unlink`](Self::unlink) to remove it.
+    ///
+    /// # Safety
+    ///
+    /// Every process which opens the mapping must use the same `T`, and must
+    /// only modify the mapping through a [`ShmGuard`] while holding the lock.
+    pub unsafe fn create<E>(
+        name: &str,
+        capacity: usize,
+        value: &T,
+    ) -> Result<Self, E>
+    where
+        T: for<'a, 'b> Serialize<DefaultSerializer<'a, Buffer<'b>, E>>,
+        E: Source,
+    {
+        let path = shm_path(name).into_error()?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .into_error()?;
+
+        let result = (|| -> Result<Self, E> {
+            let Some(map_len) = capacity.checked_add(HEADER_LEN) else {
+                fail!(ShmError::CapacityOverflow);
+            };
+            file.set_len(map_len as u64).into_error()?;
+            // SAFETY: The caller has guaranteed that the mapping is only
+            // modified through a `ShmArchive`.
+            let mut map = unsafe { MmapMut::map_mut(&file) }.into_error()?;
+
+            // SAFETY: The mapping is at least `HEADER_LEN` bytes long, and no
+            // other process can access the archive bytes until the magic
+            // number has been written.
+            let data = unsafe {
+                slice::from_raw_parts_mut(
+                    map.as_mut_ptr().add(HEADER_LEN),
+                    capacity,
+                )
+            };
+            let len = crate::to_bytes_in(value, Buffer::from(data))?.len();
+            // SAFETY: The length is in the header, which is at least 16 bytes
+            // long.
+            unsafe {
+                map.as_mut_ptr()
+                    .add(8)
+                    .cast::<[u8; 8]>()
+                    .write((len as u64).to_le_bytes());
+            }
+
+            let result = Self {
+                map,
+                len,
+                _phantom: PhantomData,
+            };
+            result.magic().store(MAGIC, Ordering::Release);
+            Ok(result)
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&path);
+        }
+        result
+    }
+
+    /// Opens the shared memory mapping with the given name, and validates the
+    /// archive in it.
+    ///
+    /// The archive is validated while holding the mapping lock.
+    ///
+    /// # Safety
+    ///
+    /// Every process which opens the mapping must use the same `T`, and must
+    /// only modify the mapping through a [`ShmGuard`] while holding the lock.
+    pub unsafe fn open<E>(name: &str) -> Result<Self, E>
+    where
+        T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
+        E: Source,
+    {
+        let path = shm_path(name).into_error()?;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .into_error()?;
+        // SAFETY: The caller has guaranteed that the mapping is only modified
+        // through a `ShmArchive`.
+        let map = unsafe { MmapMut::map_mut(&file) }.into_error()?;
+        if map.len() < HEADER_LEN {
+            fail!(ShmError::InvalidHeader);
+        }
+
+        let mut result = Self {
+            map,
+            len: 0,
+            _phantom: PhantomData,
+        };
+        if result.magic().load(Ordering::Acquire) != MAGIC {
+            fail!(ShmError::NotInitialized);
+        }
+        // SAFETY: The length is in the header, which is at least 16 bytes
+        // long. It is never modified after the magic number is written.
+        let len =
+            unsafe { result.map.as_ptr().add(8).cast::<[u8; 8]>().read() };
+        let len = u64::from_le_bytes(len);
+        if len > (result.map.len() - HEADER_LEN) as u64 {
+            fail!(ShmError::CapacityOverflow);
+        }
+        result.len = len as usize;
+
+        let guard = result.lock();
+        crate::access::<T::Archived, E>(guard.archive.bytes())?;
+        drop(guard);
+
+        Ok(result)
+    }
+
+    /// Removes the shared memory mapping with the given name.
+    ///
+    /// Processes which already have the mapping open can continue to use it.
+    pub fn unlink<E: Source>(name: &str) -> Result<(), E> {
+        fs::remove_file(shm_path(name).into_error()?).into_error()
+    }
+
+    /// Returns the number of archive bytes in the mapping.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the mapping contains no archive bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the archived value without taking the lock.
+    ///
+    /// Use [`lock`](Self::lock) to read the archived value safely.
+    ///
+    /// # Safety
+    ///
+    /// While the returned reference is alive, no process may hold the lock.
+    /// The returned reference must not be used to modify the archived value,
+    /// including any atomics in it.
+    pub unsafe fn archived(&self) -> &T::Archived {
+        // SAFETY: The archive was validated when the mapping was opened, or
+        // was serialized into the mapping when it was created. Every
+        // modification since then has kept it valid. The caller has
+        // guaranteed that no process holds the lock, and so that nothing
+        // modifies the archive while the reference is alive.
+        unsafe { access_unchecked::<T::Archived>(self.bytes()) }
+    }
+
+    /// Acquires the advisory lock on the mapping, spinning until it is
+    /// available.
+    ///
+    /// The lock is shared by every process which has the mapping open.
+    pub fn lock(&mut self) -> ShmGuard<'_, T> {
+        while self
+            .lock_word()
+            .compare_exchange_weak(
+                UNLOCKED,
+                LOCKED,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            std::thread::yield_now();
+        }
+        ShmGuard { archive: self }
+    }
+
+    fn magic(&self) -> &AtomicU32 {
+        // SAFETY: The magic number is at the start of the header, which is
+        // page-aligned and at least 16 bytes long.
+        unsafe { &*self.map.as_ptr().cast::<AtomicU32>() }
+    }
+
+    fn lock_word(&self) -> &AtomicU32 {
+        // SAFETY: The lock word is at offset 4 in the header, which is
+        // page-aligned and at least 16 bytes long.
+        unsafe { &*self.map.as_ptr().add(4).cast::<AtomicU32>() }
+    }
+
+    fn bytes(&self) -> &[u8] {
+        // SAFETY: The archive bytes follow the header and are `len` bytes
+        // long.
+        unsafe {
+            slice::from_raw_parts(self.map.as_ptr().add(HEADER_LEN), self.len)
+        }
+    }
+}
+
+impl<T: Archive> fmt::Debug for ShmArchive<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShmArchive")
+            .field("len", &self.len)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A guard which holds the advisory lock on a [`ShmArchive`].
+///
+/// The lock is released when the guard is dropped.
+pub struct ShmGuard<'a, T: Archive> {
+    archive: &'a mut ShmArchive<T>,
+}
+
+impl<T: Archive> ShmGuard<'_, T> {
+    /// Returns a mutable reference to the archived value.
+    pub fn archived_mut(&mut self) -> Pin<&mut T::Archived> {
+        let len = self.archive.len;
+        // SAFETY: The archive bytes follow the header and are `len` bytes
+        // long. Every modification of them, including of atomics, is made
+        // while holding the lock, and references returned by
+        // `ShmArchive::archived` may not be alive while any process holds it.
+        // So holding the lock gives us exclusive access to them.
+        let bytes = unsafe {
+            slice::from_raw_parts_mut(
+                self.archive.map.as_mut_ptr().add(HEADER_LEN),
+                len,
+            )
+        };
+        // SAFETY: The archive is valid, and is only modified through pinned
+        // references which keep it valid.
+        unsafe { access_unchecked_mut::<T::Archived>(bytes) }
+    }
+}
+
+impl<T: Archive> Deref for ShmGuard<'_, T> {
+    type Target = T::Archived;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY: The archive is valid, and every modification of it is made
+        // while holding the lock. This guard holds the lock until the
+        // reference is dropped, and only modifies the archive through
+        // `archived_mut`, which can't be called while the reference is alive.
+        unsafe { access_unchecked::<T::Archived>(self.archive.bytes()) }
+    }
+}
+
+impl<T: Archive> Drop for ShmGuard<'_, T> {
+    fn drop(&mut self) {
+        self.archive.lock_word().store(UNLOCKED, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::sync::atomic::{AtomicU32, Ordering};
+    use std::thread;
+
+    use rancor::{Error, Failure};
+
+    use super::ShmArchive;
+    use crate::{
+        with::{AsAtomic, Relaxed},
+        Archive, Serialize,
+    };
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Counters {
+        #[with(AsAtomic<Relaxed, Relaxed>)]
+        hits: AtomicU32,
+        total: u32,
+        label: String,
+    }
+
+    fn test_name(name: &str) -> String {
+        format!("rkyv-test-{name}-{}", std::process::id())
+    }
+
+    #[test]
+    fn shared_counter() {
+        const THREADS: u32 = 4;
+        const ITERATIONS: u32 = 100;
+
+        let name = test_name("shared-counter");
+        let value = Counters {
+            hits: AtomicU32::new(0),
+            total: 0,
+            label: "counter".to_string(),
+        };
+        let owner = unsafe {
+            ShmArchive::<Counters>::create::<Error>(&name, 256, &value)
+        }
+        .unwrap();
+
+        let threads = (0..THREADS)
+            .map(|_| {
+                let name = name.clone();
+                thread::spawn(move || {
+                    // Each thread maps the shared memory at its own address.
+                    let mut shm =
+                        unsafe { ShmArchive::<Counters>::open::<Error>(&name) }
+                            .unwrap();
+                    for _ in 0..ITERATIONS {
+                        let mut guard = shm.lock();
+                        guard.hits.fetch_add(1, Ordering::Relaxed);
+                        let mut total = unsafe {
+                            guard
+                                .archived_mut()
+                                .map_unchecked_mut(|c| &mut c.total)
+                        };
+                        *total = (total.to_native() + 1).into();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // SAFETY: Every thread which locked the mapping has exited.
+        let archived = unsafe { owner.archived() };
+        assert_eq!(archived.hits.load(Ordering::Relaxed), THREADS * ITERATIONS);
+        assert_eq!(archived.total, THREADS * ITERATIONS);
+        assert_eq!(archived.label.as_str(), "counter");
+
+        ShmArchive::<Counters>::unlink::<Error>(&name).unwrap();
+    }
+
+    #[test]
+    fn create_errors() {
+        let value = Counters {
+            hits: AtomicU32::new(0),
+            total: 0,
+            label: "a label which is too long to fit".to_string(),
+        };
+
+        unsafe {
+            assert!(ShmArchive::<Counters>::create::<Failure>("", 256, &value)
+                .is_err());
+            assert!(ShmArchive::<Counters>::create::<Failure>(
+                "a/b", 256, &value
+            )
+            .is_err());
+        }
+
+        let name = test_name("too-small");
+        unsafe {
+            assert!(ShmArchive::<Counters>::create::<Failure>(
+                &name, 16, &value
+            )
+            .is_err());
+        }
+        // The mapping is removed if the value does not fit.
+        assert!(
+            unsafe { ShmArchive::<Counters>::open::<Failure>(&name) }.is_err()
+        );
+
+        let name = test_name("overflow");
+        unsafe {
+            assert!(ShmArchive::<Counters>::create::<Failure>(
+                &name,
+                usize::MAX,
+                &value
+            )
+            .is_err());
+        }
+        assert!(
+            unsafe { ShmArchive::<Counters>::open::<Failure>(&name) }.is_err()
+        );
+
+        let name = test_name("exists");
+        let _owner = unsafe {
+            ShmArchive::<Counters>::create::<Failure>(&name, 256, &value)
+        }
+        .unwrap();
+        assert!(unsafe {
+            ShmArchive::<Counters>::create::<Failure>(&name, 256, &value)
+        }
+        .is_err());
+        ShmArchive::<Counters>::unlink::<Failure>(&name).unwrap();
+    }
+
+    #[test]
+    fn open_uninitialized() {
+        let name = test_name("uninitialized");
+        let path = super::shm_path(&name).unwrap();
+        std::fs::write(&path, [0; 64]).unwrap();
+
+        assert!(
+            unsafe { ShmArchive::<Counters>::open::<Failure>(&name) }.is_err()
+        );
+
+        ShmArchive::<Counters>::unlink::<Failure>(&name).unwrap();
+    }
+}
@@ -0,0 +1,452 @@
+//! Struct-of-arrays archives for slices of enums.
+//!
+//! Archiving a `Vec` of enums stores every element with the size and
+//! alignment of the largest variant. When most elements are small variants,
+//! much of the archive is padding. Enums which derive `Archive` with
+//! `#[rkyv(soa)]` can instead be archived with the [`SoA`](crate::with::SoA)
+//! wrapper, which stores:
+//!
+//! - A compact stream of tags and indices in an [`ArchivedSoaIndex`].
+//! - One column with the archived values of each field of each variant.
+//!
+//! The archived struct of arrays has `len`, `get`, and `iter` methods which
+//! return references to the fields of each element in their original order.
+//!
+//! # Example
+//!
+//! ```
+//! use rkyv::{rancor::Error, with::SoA, Archive, Deserialize, Serialize};
+//!
+//! #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+//! #[rkyv(soa)]
+//! enum Event {
+//!     Tick,
+//!     Move { x: i32, y: i32 },
+//!     Message(String),
+//! }
+//!
+//! #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+//! struct Log {
+//!     #[with(SoA)]
+//!     events: Vec<Event>,
+//! }
+//!
+//! let log = Log {
+//!     events: vec![
+//!         Event::Tick,
+//!         Event::Move { x: 1, y: 2 },
+//!         Event::Tick,
+//!         Event::Message("hello".to_string()),
+//!     ],
+//! };
+//!
+//! let bytes = rkyv::to_bytes::<Error>(&log).unwrap();
+//! let archived = unsafe { rkyv::access_unchecked::<ArchivedLog>(&bytes) };
+//!
+//! assert_eq!(archived.events.len(), 4);
+//! match archived.events.get(1) {
+//!     Some(ArchivedEventRef::Move { x, y }) => {
+//!         assert_eq!(*x, 1);
+//!         assert_eq!(*y, 2);
+//!     }
+//!     _ => panic!("expected a move event"),
+//! }
+//! assert!(matches!(
+//!     archived.events.iter().last(),
+//!     Some(ArchivedEventRef::Message(message)) if message == "hello",
+//! ));
+//!
+//! let deserialized =
+//!     rkyv::deserialize::<Log, _, Error>(archived, &mut ()).unwrap();
+//! assert_eq!(deserialized, log);
+//! ```
+
+use core::{fmt, mem::MaybeUninit};
+
+use munge::munge;
+use rancor::{fail, Fallible, Source};
+
+use crate::{
+    primitive::{ArchivedUsize, FixedUsize},
+    ser::{Allocator, Writer, WriterExt as _},
+    util::SerVec,
+    vec::{ArchivedVec, VecResolver},
+    Place, Portable,
+};
+
+/// An enum which can archive a slice of its values as a struct of arrays.
+///
+/// This is implemented by deriving `Archive` with `#[rkyv(soa)]`.
+pub trait ArchiveSoa: Sized {
+    /// The archived struct of arrays.
+    type ArchivedSoa: Portable;
+    /// The resolver for the archived struct of arrays.
+    type SoaResolver;
+
+    /// Returns the number of elements in an archived struct of arrays.
+    fn soa_len(archived: &Self::ArchivedSoa) -> usize;
+
+    /// Creates the archived struct of arrays for a slice of values at the
+    /// given output location.
+    fn resolve_soa(
+        values: &[Self],
+        resolver: Self::SoaResolver,
+        out: Place<Self::ArchivedSoa>,
+    );
+}
+
+/// An enum which can serialize a slice of its values as a struct of arrays.
+pub trait SerializeSoa<S: Fallible + ?Sized>: ArchiveSoa {
+    /// Writes the columns of a slice of values to the serializer.
+    fn serialize_soa(
+        values: &[Self],
+        serializer: &mut S,
+    ) -> Result<Self::SoaResolver, S::Error>;
+}
+
+/// An enum which can deserialize its values from a struct of arrays.
+pub trait DeserializeSoa<D: Fallible + ?Sized>: ArchiveSoa {
+    /// Deserializes the element at the given index of an archived struct of
+    /// arrays.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    fn deserialize_soa(
+        archived: &Self::ArchivedSoa,
+        index: usize,
+        deserializer: &mut D,
+    ) -> Result<Self, D::Error>;
+}
+
+/// The tags and column indices of an archived struct of arrays.
+///
+/// Each element has a one-byte tag which identifies its variant, and the index
+/// of its fields in the columns of that variant.
+#[derive(Portable)]
+#[rkyv(crate)]
+#[cfg_attr(feature = "bytecheck", derive(bytecheck::CheckBytes))]
+#[repr(C)]
+pub struct ArchivedSoaIndex {
+    tags: ArchivedVec<u8>,
+    indices: ArchivedVec<ArchivedUsize>,
+}
+
+impl ArchivedSoaIndex {
+    /// Returns the number of elements.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tags.len()
+    }
+
+    /// Returns whether there are no elements.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+    }
+
+    /// Returns the tag of the element at the given index and the index of its
+    /// fields in the columns of its variant.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<(u8, usize)> {
+        let tag = *self.tags.get(index)?;
+        let column_index = self.indices.get(index)?.to_native() as usize;
+        Some((tag, column_index))
+    }
+
+    /// Serializes the tags and column indices for the given tags.
+    ///
+    /// Column indices are assigned in order, so the `n`th element with a tag
+    /// has index `n` in the columns of its variant.
+    pub fn serialize_from_tags<I, S>(
+        tags: I,
+        serializer: &mut S,
+    ) -> Result<SoaIndexResolver, S::Error>
+    where
+        I: Iterator<Item = u8> + Clone,
+        S: Fallible + Allocator + Writer + ?Sized,
+    {
+        let tags_resolver = ArchivedVec::<u8>::serialize_from_iter::<u8, _, _>(
+            tags.clone(),
+            serializer,
+        )?;
+        let mut counts = [0 as FixedUsize; 256];
+        let indices = tags.map(|tag| {
+            let index = counts[tag as usize];
+            counts[tag as usize] += 1;
+            index
+        });
+        let indices_resolver =
+            ArchivedVec::<ArchivedUsize>::serialize_from_iter::<
+                FixedUsize,
+                _,
+                _,
+            >(indices, serializer)?;
+
+        Ok(SoaIndexResolver {
+            tags: tags_resolver,
+            indices: indices_resolver,
+        })
+    }
+
+    /// Resolves an `ArchivedSoaIndex` from the number of elements.
+    pub fn resolve_from_len(
+        len: usize,
+        resolver: SoaIndexResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedSoaIndex { tags, indices } = out);
+        ArchivedVec::resolve_from_len(len, resolver.tags, tags);
+        ArchivedVec::resolve_from_len(len, resolver.indices, indices);
+    }
+
+    /// Checks that the tags and indices are consistent with the lengths of the
+    /// columns of each variant.
+    ///
+    /// `columns` has one entry for each variant, which contains the lengths of
+    /// all of the columns of that variant. Variants without any fields have an
+    /// empty entry.
+    pub fn check_columns<E: Source>(
+        &self,
+        columns: &[&[usize]],
+    ) -> Result<(), E> {
+        for (variant, lens) in columns.iter().enumerate() {
+            if let Some((&first, rest)) = lens.split_first() {
+                if rest.iter().any(|&len| len != first) {
+                    fail!(SoaError::MismatchedColumns { variant });
+                }
+            }
+        }
+
+        if self.indices.len() != self.tags.len() {
+            fail!(SoaError::MismatchedIndices {
+                tags: self.tags.len(),
+                indices: self.indices.len(),
+            });
+        }
+
+        for (position, (&tag, index)) in
+            self.tags.iter().zip(self.indices.iter()).enumerate()
+        {
+            let index = index.to_native() as usize;
+            match columns.get(tag as usize) {
+                None => fail!(SoaError::InvalidTag { position, tag }),
+                Some(lens) => {
+                    if lens.first().map_or(false, |&len| index >= len) {
+                        fail!(SoaError::IndexOutOfBounds { position, index });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for ArchivedSoaIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchivedSoaIndex")
+            .field("tags", &self.tags.as_slice())
+            .field("indices", &self.indices.as_slice())
+            .finish()
+    }
+}
+
+/// The resolver for [`ArchivedSoaIndex`].
+pub struct SoaIndexResolver {
+    tags: VecResolver,
+    indices: VecResolver,
+}
+
+#[derive(Debug)]
+enum SoaError {
+    MismatchedColumns { variant: usize },
+    MismatchedIndices { tags: usize, indices: usize },
+    InvalidTag { position: usize, tag: u8 },
+    IndexOutOfBounds { position: usize, index: usize },
+}
+
+impl fmt::Display for SoaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MismatchedColumns { variant } => write!(
+                f,
+                "the columns of variant {} have different lengths",
+                variant,
+            ),
+            Self::MismatchedIndices { tags, indices } => write!(
+                f,
+                "struct of arrays has {} tags but {} indices",
+                tags, indices,
+            ),
+            Self::InvalidTag { position, tag } => {
+                write!(f, "invalid tag {} at position {}", tag, position)
+            }
+            Self::IndexOutOfBounds { position, index } => write!(
+                f,
+                "column index {} at position {} is out of bounds",
+                index, position,
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SoaError {}
+
+/// Serializes one column of an archived struct of arrays.
+///
+/// `serialize` and `resolve` are called with each field in `fields`, and the
+/// resolved fields are written contiguously. The column must be resolved with
+/// the number of fields.
+pub fn serialize_column<'a, F, A, R, I, S>(
+    fields: I,
+    mut serialize: impl FnMut(&F, &mut S) -> Result<R, S::Error>,
+    mut resolve: impl FnMut(&F, R, Place<A>),
+    serializer: &mut S,
+) -> Result<VecResolver, S::Error>
+where
+    F: 'a,
+    A: Portable,
+    I: Iterator<Item = &'a F> + Clone,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    let len = fields.clone().count();
+    SerVec::with_capacity(serializer, len, |resolvers, serializer| {
+        for field in fields.clone() {
+            // SAFETY: `fields` yields `len` fields, which is the capacity of
+            // `resolvers`.
+            unsafe {
+                resolvers.push_unchecked(serialize(field, serializer)?);
+            }
+        }
+
+        let pos = serializer.align_for::<A>()?;
+        for (field, resolver) in fields.zip(resolvers.drain()) {
+            let mut resolved = MaybeUninit::<A>::zeroed();
+            // SAFETY: `resolved.as_mut_ptr()` points to a local zeroed
+            // `MaybeUninit`, and so is properly aligned, dereferenceable, and
+            // all of its bytes are initialized.
+            let out = unsafe {
+                Place::new_unchecked(serializer.pos(), resolved.as_mut_ptr())
+            };
+            resolve(field, resolver, out);
+            serializer.write(out.as_slice())?;
+        }
+
+        Ok(VecResolver::from_pos(pos))
+    })?
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use rancor::Panic;
+
+    use crate::{
+        test::{access, deserialize, to_bytes},
+        with::SoA,
+        Archive, Deserialize, Serialize,
+    };
+
+    #[derive(Archive, Serialize, Deserialize, Clone, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes, soa)]
+    enum Event {
+        Tick,
+        Move { x: i32, y: i32 },
+        Message(String),
+        Data([u64; 8]),
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Columns {
+        #[with(SoA)]
+        events: Vec<Event>,
+    }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate)]
+    struct Rows {
+        events: Vec<Event>,
+    }
+
+    fn events() -> Vec<Event> {
+        let mut events = Vec::new();
+        for i in 0..100 {
+            events.push(Event::Tick);
+            if i % 10 == 0 {
+                events.push(Event::Move { x: i, y: -i });
+            }
+        }
+        events.push(Event::Message("hello".to_string()));
+        events.push(Event::Data([1, 2, 3, 4, 5, 6, 7, 8]));
+        events
+    }
+
+    #[test]
+    fn soa_round_trip() {
+        let value = Columns { events: events() };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedColumns>(bytes, |archived| {
+                assert_eq!(archived.events.len(), value.events.len());
+                assert!(matches_events(&archived.events, &value.events));
+
+                let deserialized = deserialize::<Columns>(archived);
+                assert_eq!(deserialized, value);
+            });
+        });
+    }
+
+    fn matches_events(archived: &ArchivedEventSoa, events: &[Event]) -> bool {
+        archived.iter().zip(events.iter()).all(|pair| match pair {
+            (ArchivedEventRef::Tick, Event::Tick) => true,
+            (ArchivedEventRef::Move { x, y }, Event::Move { x: ex, y: ey }) => {
+                *x == *ex && *y == *ey
+            }
+            (ArchivedEventRef::Message(a), Event::Message(b)) => {
+                a.as_str() == b
+            }
+            (ArchivedEventRef::Data(a), Event::Data(b)) => {
+                a.iter().zip(b.iter()).all(|(a, b)| *a == *b)
+            }
+            _ => false,
+        })
+    }
+
+    #[test]
+    fn soa_out_of_bounds() {
+        let value = Columns {
+            events: vec![Event::Tick],
+        };
+
+        to_bytes(&value, |bytes| {
+            access::<ArchivedColumns>(bytes, |archived| {
+                assert!(matches!(
+                    archived.events.get(0),
+                    Some(ArchivedEventRef::Tick)
+                ));
+                assert!(archived.events.get(1).is_none());
+            });
+        });
+    }
+
+    #[test]
+    fn soa_is_smaller_than_rows() {
+        let events = events();
+        let columns = crate::to_bytes::<Panic>(&Columns {
+            events: events.clone(),
+        })
+        .unwrap();
+        let rows = crate::to_bytes::<Panic>(&Rows { events }).unwrap();
+
+        assert!(columns.len() < rows.len() / 4);
+    }
+}
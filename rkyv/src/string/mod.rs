@@ -17,7 +17,12 @@ use munge::munge;
 use rancor::Fallible;
 use repr::{ArchivedStringRepr, INLINE_CAPACITY};
 
-use crate::{Place, Portable, SerializeUnsized};
+use crate::{
+    collections::btree_map::LookupOrd,
+    hash::LookupKey,
+    ser::{Sharing, SharingExt as _},
+    Archive, Place, Portable, Serialize, SerializeUnsized,
+};
 
 /// An archived [`String`].
 ///
@@ -37,12 +42,45 @@ pub struct ArchivedString {
 }
 
 impl ArchivedString {
+    /// The maximum length in bytes of a string which can be stored inline.
+    ///
+    /// Strings which are at most this long are stored inline unless they are
+    /// serialized with [`ForceOutOfLine`](crate::with::ForceOutOfLine).
+    pub const INLINE_CAPACITY: usize = INLINE_CAPACITY;
+
+    /// Returns whether the bytes of the string are stored inline.
+    ///
+    /// Strings which are not inline store a relative pointer to their bytes
+    /// elsewhere in the buffer.
+    #[inline]
+    pub const fn is_inline(&self) -> bool {
+        self.repr.is_inline()
+    }
+
+    /// Returns the length of the string in bytes.
+    #[inline]
+    pub const fn len(&self) -> usize {
+        self.repr.len()
+    }
+
+    /// Returns whether the string is empty.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.repr.is_empty()
+    }
+
     /// Extracts a string slice containing the entire `ArchivedString`.
     #[inline]
     pub fn as_str(&self) -> &str {
         self.repr.as_str()
     }
 
+    /// Returns whether the string has the same bytes as `other`.
+    #[inline]
+    fn eq_bytes(&self, other: &[u8]) -> bool {
+        self.repr.bytes() == other
+    }
+
     /// Extracts a pinned mutable string slice containing the entire
     /// `ArchivedString`.
     #[inline]
@@ -89,6 +127,56 @@ impl ArchivedString {
             })
         }
     }
+
+    /// Resolves an archived string from a given `str` which was serialized
+    /// with [`serialize_from_str_out_of_line`].
+    ///
+    /// Nonempty strings are always resolved out-of-line. Empty strings have no
+    /// bytes to point to, so they are always inline.
+    ///
+    /// [`serialize_from_str_out_of_line`]: Self::serialize_from_str_out_of_line
+    #[inline]
+    pub fn resolve_from_str_out_of_line(
+        value: &str,
+        resolver: StringResolver,
+        out: Place<Self>,
+    ) {
+        munge!(let ArchivedString { repr } = out);
+        if value.is_empty() {
+            unsafe {
+                ArchivedStringRepr::emplace_inline(value, repr.ptr());
+            }
+        } else {
+            unsafe {
+                ArchivedStringRepr::emplace_out_of_line(
+                    value,
+                    resolver.pos,
+                    repr,
+                );
+            }
+        }
+    }
+
+    /// Serializes the bytes of a `str` out-of-line regardless of its length.
+    ///
+    /// The bytes are shared with any previous serialization of the same `str`
+    /// through the serializer's [`Sharing`].
+    pub fn serialize_from_str_out_of_line<S>(
+        value: &str,
+        serializer: &mut S,
+    ) -> Result<StringResolver, S::Error>
+    where
+        S: Fallible + Sharing + ?Sized,
+        str: SerializeUnsized<S>,
+    {
+        if value.is_empty() {
+            Ok(StringResolver { pos: 0 })
+        } else {
+            Ok(StringResolver {
+                pos: serializer.serialize_shared(value)?,
+            })
+        }
+    }
 }
 
 impl AsRef<str> for ArchivedString {
@@ -105,6 +193,20 @@ impl Borrow<str> for ArchivedString {
     }
 }
 
+impl LookupKey<ArchivedString> for str {
+    #[inline]
+    fn equivalent(&self, key: &ArchivedString) -> bool {
+        key.eq_bytes(self.as_bytes())
+    }
+}
+
+impl LookupOrd<ArchivedString> for str {
+    #[inline]
+    fn compare(&self, key: &ArchivedString) -> cmp::Ordering {
+        self.cmp(key.as_str())
+    }
+}
+
 impl fmt::Debug for ArchivedString {
     #[inline]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -131,6 +233,9 @@ impl fmt::Display for ArchivedString {
 impl Eq for ArchivedString {}
 
 impl hash::Hash for ArchivedString {
+    // This must hash the same way as `str` because `ArchivedString` implements
+    // `Borrow<str>`.
+    #[inline]
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
         self.as_str().hash(state)
     }
@@ -159,14 +264,16 @@ impl_index!(RangeToInclusive<usize>);
 impl Ord for ArchivedString {
     #[inline]
     fn cmp(&self, other: &Self) -> cmp::Ordering {
-        self.as_str().cmp(other.as_str())
+        // Strings are ordered by their bytes, which compares as a single
+        // `memcmp` followed by the lengths.
+        self.repr.bytes().cmp(other.repr.bytes())
     }
 }
 
 impl PartialEq for ArchivedString {
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        self.as_str() == other.as_str()
+        self.repr.bytes() == other.repr.bytes()
     }
 }
 
@@ -180,28 +287,28 @@ impl PartialOrd for ArchivedString {
 impl PartialEq<&str> for ArchivedString {
     #[inline]
     fn eq(&self, other: &&str) -> bool {
-        PartialEq::eq(self.as_str(), *other)
+        self.eq_bytes(other.as_bytes())
     }
 }
 
 impl PartialEq<str> for ArchivedString {
     #[inline]
     fn eq(&self, other: &str) -> bool {
-        PartialEq::eq(self.as_str(), other)
+        self.eq_bytes(other.as_bytes())
     }
 }
 
 impl PartialEq<ArchivedString> for &str {
     #[inline]
     fn eq(&self, other: &ArchivedString) -> bool {
-        PartialEq::eq(other.as_str(), *self)
+        other.eq_bytes(self.as_bytes())
     }
 }
 
 impl PartialEq<ArchivedString> for str {
     #[inline]
     fn eq(&self, other: &ArchivedString) -> bool {
-        PartialEq::eq(other.as_str(), self)
+        other.eq_bytes(self.as_bytes())
     }
 }
 
@@ -238,6 +345,102 @@ pub struct StringResolver {
     pos: usize,
 }
 
+impl StringResolver {
+    /// Creates a new `StringResolver` from a position in the output buffer
+    /// where the bytes of the archived string are stored.
+    ///
+    /// The position is ignored when the string is resolved inline.
+    pub fn from_pos(pos: usize) -> Self {
+        Self { pos }
+    }
+}
+
+/// An adapter which serializes and resolves a `str` as an [`ArchivedString`].
+///
+/// This can be used to serialize an
+/// [`ArchivedVec<ArchivedString>`](crate::vec::ArchivedVec) from an iterator
+/// of `&str`:
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error, string::StrAdapter, to_bytes, vec::ArchivedVec, Archive,
+///     Archived, Place, Serialize,
+/// };
+///
+/// struct Words<'a>(&'a str);
+///
+/// impl Archive for Words<'_> {
+///     type Archived = Archived<Vec<String>>;
+///     type Resolver = rkyv::vec::VecResolver;
+///
+///     fn resolve(
+///         &self,
+///         resolver: Self::Resolver,
+///         out: Place<Self::Archived>,
+///     ) {
+///         let len = self.0.split_whitespace().count();
+///         ArchivedVec::resolve_from_len(len, resolver, out);
+///     }
+/// }
+///
+/// impl<S> Serialize<S> for Words<'_>
+/// where
+///     S: rkyv::rancor::Fallible
+///         + rkyv::ser::Allocator
+///         + rkyv::ser::Writer
+///         + ?Sized,
+///     str: rkyv::SerializeUnsized<S>,
+/// {
+///     fn serialize(
+///         &self,
+///         serializer: &mut S,
+///     ) -> Result<Self::Resolver, S::Error> {
+///         ArchivedVec::serialize_from_iter::<StrAdapter, _, _>(
+///             self.0.split_whitespace().map(StrAdapter),
+///             serializer,
+///         )
+///     }
+/// }
+///
+/// let bytes = to_bytes::<Error>(&Words("the quick brown fox")).unwrap();
+/// let archived =
+///     unsafe { rkyv::access_unchecked::<Archived<Vec<String>>>(&bytes) };
+/// assert_eq!(archived.len(), 4);
+/// assert_eq!(archived[2], "brown");
+/// ```
+///
+/// `StrAdapter` hashes and compares the same as the `str` it wraps, so it can
+/// also be used as the key of an archived hash map. See
+/// the `serialize_from_borrowed_iter` method of [`ArchivedHashMap`] for an
+/// example.
+///
+/// [`ArchivedHashMap`]: crate::collections::swiss_table::ArchivedHashMap
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct StrAdapter<'a>(pub &'a str);
+
+impl Archive for StrAdapter<'_> {
+    type Archived = ArchivedString;
+    type Resolver = StringResolver;
+
+    #[inline]
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedString::resolve_from_str(self.0, resolver, out);
+    }
+}
+
+impl<S: Fallible + ?Sized> Serialize<S> for StrAdapter<'_>
+where
+    str: SerializeUnsized<S>,
+{
+    #[inline]
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedString::serialize_from_str(self.0, serializer)
+    }
+}
+
 #[cfg(feature = "bytecheck")]
 mod verify {
     use bytecheck::{
@@ -275,6 +478,7 @@ mod verify {
                     // the pointed-to `str`.
                     unsafe { str::check_bytes(ptr, context) }
                 })?;
+                context.report_progress(metadata)?;
             }
 
             Ok(())
@@ -6,7 +6,7 @@ use munge::munge;
 use rancor::{Panic, ResultExt as _, Source};
 
 use crate::{
-    primitive::{ArchivedUsize, FixedIsize},
+    primitive::{fixed_len, usize_from_archived, ArchivedUsize, FixedIsize},
     Place, Portable,
 };
 
@@ -47,7 +47,7 @@ pub union ArchivedStringRepr {
 impl ArchivedStringRepr {
     /// Returns whether the representation is inline.
     #[inline]
-    pub fn is_inline(&self) -> bool {
+    pub const fn is_inline(&self) -> bool {
         unsafe { self.inline.len & 0x80 == 0 }
     }
 
@@ -95,19 +95,19 @@ impl ArchivedStringRepr {
 
     /// Returns the length of the string.
     #[inline]
-    pub fn len(&self) -> usize {
+    pub const fn len(&self) -> usize {
         unsafe {
             if self.is_inline() {
                 self.inline.len as usize
             } else {
-                self.out_of_line.len.to_native() as usize
+                usize_from_archived(self.out_of_line.len)
             }
         }
     }
 
     /// Returns whether the string is empty.
     #[inline]
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
@@ -184,7 +184,8 @@ impl ArchivedStringRepr {
     ///
     /// # Safety
     ///
-    /// The length of `str` must be greater than [`INLINE_CAPACITY`].
+    /// - `value` must not be empty.
+    /// - `target` must be less than the position of `out`.
     pub unsafe fn try_emplace_out_of_line<E: Source>(
         value: &str,
         target: usize,
@@ -195,9 +196,7 @@ impl ArchivedStringRepr {
                 out_of_line: OutOfLineRepr { len, offset, _phantom: _ }
             } = out;
         }
-        len.write(ArchivedUsize::from_native(
-            value.len().try_into().into_error()?,
-        ));
+        len.write(ArchivedUsize::from_native(fixed_len(value.len())?));
 
         let off = crate::rel_ptr::signed_offset(out.pos(), target)?;
         offset.write((off as FixedIsize).to_le_bytes());
@@ -214,7 +213,8 @@ impl ArchivedStringRepr {
     ///
     /// # Safety
     ///
-    /// The length of `str` must be greater than [`INLINE_CAPACITY`].
+    /// - `value` must not be empty.
+    /// - `target` must be less than the position of `out`.
     #[inline]
     pub unsafe fn emplace_out_of_line(
         value: &str,
@@ -2,19 +2,24 @@
 mod detail {
     use core::mem::MaybeUninit;
 
-    use rancor::Panic;
+    use bytecheck::CheckBytes;
+    use rancor::{Panic, Strategy};
 
     use crate::{
+        access_with_buffer,
         de::{CoreDeserializer, Unpool},
         ser::{
             allocator::SubAllocator, sharing::Unshare, writer::Buffer,
-            CoreSerializer,
+            CoreSerializer, Serializer,
         },
         util::{serialize_into, Align},
+        validation::validators::{SharedEntry, ValidatorWithBuffer},
+        Archive, Deserialize, Portable, Serialize,
     };
 
     pub type TestSerializer<'a> = CoreSerializer<'a, Buffer<'a>, Panic>;
     pub type TestDeserializer = CoreDeserializer<Panic>;
+    pub type TestValidator<'a> = ValidatorWithBuffer<'a>;
 
     pub fn to_bytes<T>(value: &T, f: impl FnOnce(&[u8]))
     where
@@ -37,6 +42,15 @@ mod detail {
         f(&buffer);
     }
 
+    pub fn access<T>(bytes: &[u8], f: impl FnOnce(&T))
+    where
+        T: Portable + for<'a> CheckBytes<Strategy<TestValidator<'a>, Panic>>,
+    {
+        let mut shared = [MaybeUninit::<SharedEntry>::uninit(); 64];
+        f(access_with_buffer::<T, Panic>(bytes, &mut shared)
+            .expect("failed to validate archived value"));
+    }
+
     pub fn deserialize<T>(value: &T::Archived) -> T
     where
         T: Archive,
@@ -49,17 +63,20 @@ mod detail {
 
 #[cfg(feature = "alloc")]
 mod detail {
-    use rancor::Panic;
+    use bytecheck::CheckBytes;
+    use rancor::{Panic, Strategy};
 
     use crate::{
         de::{DefaultDeserializer, Pool},
         ser::DefaultSerializer,
         util::AlignedVec,
-        Archive, Deserialize, Serialize,
+        validation::validators::DefaultValidator,
+        Archive, Deserialize, Portable, Serialize,
     };
 
     pub type TestSerializer<'a> = DefaultSerializer<'a, AlignedVec, Panic>;
     pub type TestDeserializer = DefaultDeserializer<Panic>;
+    pub type TestValidator<'a> = DefaultValidator<'a>;
 
     pub fn to_bytes<T>(value: &T, f: impl FnOnce(&[u8]))
     where
@@ -68,6 +85,14 @@ mod detail {
         f(&crate::to_bytes(value).expect("failed to serialize value"));
     }
 
+    pub fn access<T>(bytes: &[u8], f: impl FnOnce(&T))
+    where
+        T: Portable + for<'a> CheckBytes<Strategy<TestValidator<'a>, Panic>>,
+    {
+        f(crate::access::<T, Panic>(bytes)
+            .expect("failed to validate archived value"));
+    }
+
     pub fn deserialize<T>(value: &T::Archived) -> T
     where
         T: Archive,
@@ -83,20 +108,18 @@ use core::fmt::Debug;
 use bytecheck::CheckBytes;
 use rancor::{Panic, Strategy};
 
-use self::detail::{deserialize, to_bytes, TestDeserializer, TestSerializer};
-use crate::{
-    access, validation::validators::DefaultValidator, Deserialize, Serialize,
+pub use self::detail::{
+    access, deserialize, to_bytes, TestDeserializer, TestSerializer,
+    TestValidator,
 };
+use crate::{Deserialize, Serialize};
 
 pub fn to_archived<T>(value: &T, f: impl FnOnce(&T::Archived))
 where
     T: for<'a> Serialize<TestSerializer<'a>>,
-    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Panic>>,
+    T::Archived: for<'a> CheckBytes<Strategy<TestValidator<'a>, Panic>>,
 {
-    to_bytes(value, |bytes| {
-        let archived_value = access::<T::Archived, Panic>(bytes).unwrap();
-        f(archived_value);
-    });
+    to_bytes(value, |bytes| access(bytes, f));
 }
 
 pub fn roundtrip_with<T>(value: &T, cmp: impl Fn(&T, &T::Archived))
@@ -104,7 +127,7 @@ where
     T: Debug + PartialEq + for<'a> Serialize<TestSerializer<'a>>,
     T::Archived: Debug
         + Deserialize<T, TestDeserializer>
-        + for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Panic>>,
+        + for<'a> CheckBytes<Strategy<TestValidator<'a>, Panic>>,
 {
     to_archived(value, |archived_value| {
         cmp(value, archived_value);
@@ -119,7 +142,7 @@ where
     T::Archived: Debug
         + PartialEq<T>
         + Deserialize<T, TestDeserializer>
-        + for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Panic>>,
+        + for<'a> CheckBytes<Strategy<TestValidator<'a>, Panic>>,
 {
     roundtrip_with(value, |a, b| assert_eq!(b, a));
 }
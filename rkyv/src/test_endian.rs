@@ -0,0 +1,704 @@
+//! Golden fixtures and harnesses for checking that archives don't depend on the
+//! byte order of the host.
+//!
+//! An archive serialized with the `little_endian` or `big_endian` feature must
+//! contain exactly the same bytes no matter which architecture wrote it. Each
+//! [`Fixture`] records the expected bytes of an archived value for both
+//! features, and [`check_fixture`] compares a freshly serialized value against
+//! them. Running these checks on both little- and big-endian targets catches
+//! types whose layout accidentally depends on the endianness of the host.
+//!
+//! [`check_byte_order`] complements the fixtures by checking the archived bytes
+//! of randomly generated primitives against their `to_le_bytes` and
+//! `to_be_bytes` representations.
+//!
+//! # Examples
+//!
+//! ```
+//! use rkyv::test_endian::{check_byte_order, to_native};
+//!
+//! check_byte_order(1000, 0x1234);
+//!
+//! let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&0x0102_0304u32).unwrap();
+//! assert_eq!(to_native::<u32>(&bytes), 0x0102_0304);
+//! ```
+
+use core::fmt::Debug;
+
+use bytecheck::CheckBytes;
+use rancor::{Error, Strategy};
+
+use crate::{
+    access, de::Pool, deserialize, primitive::FixedUsize,
+    ser::DefaultSerializer, testing::SplitMix64, to_bytes, util::AlignedVec,
+    validation::validators::DefaultValidator, Archive, Deserialize, Serialize,
+};
+
+/// A default seed for [`check_byte_order`].
+pub const DEFAULT_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// The expected bytes of an archived value for each endianness feature.
+#[derive(Clone, Copy, Debug)]
+pub struct Fixture {
+    /// The name of the fixture, used in failure messages.
+    pub name: &'static str,
+    /// The expected bytes when the `little_endian` feature is enabled.
+    pub little_endian: &'static [u8],
+    /// The expected bytes when the `big_endian` feature is enabled.
+    pub big_endian: &'static [u8],
+}
+
+impl Fixture {
+    /// Returns the expected bytes for the enabled endianness feature.
+    pub const fn expected(&self) -> &'static [u8] {
+        if cfg!(feature = "big_endian") {
+            self.big_endian
+        } else {
+            self.little_endian
+        }
+    }
+}
+
+/// Serializes the given value and asserts that the resulting bytes are equal
+/// to the expected bytes of the fixture.
+///
+/// # Panics
+///
+/// Panics if the value cannot be serialized or if the serialized bytes differ
+/// from the fixture.
+pub fn check_fixture<T>(value: &T, fixture: &Fixture)
+where
+    T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+{
+    let bytes = to_bytes::<Error>(value).unwrap_or_else(|e| {
+        panic!("failed to serialize fixture `{}`: {}", fixture.name, e)
+    });
+    let expected = fixture.expected();
+
+    if let Some(index) = bytes
+        .iter()
+        .zip(expected)
+        .position(|(found, exp)| found != exp)
+    {
+        panic!(
+            "fixture `{}` differs at byte {}: expected {:#04x} but found \
+             {:#04x}\nexpected: {:02x?}\n   found: {:02x?}",
+            fixture.name,
+            index,
+            expected[index],
+            bytes[index],
+            expected,
+            &bytes[..],
+        );
+    }
+    assert_eq!(
+        bytes.len(),
+        expected.len(),
+        "fixture `{}` has the wrong length",
+        fixture.name,
+    );
+}
+
+/// Validates and deserializes the archived value in the given bytes.
+///
+/// The bytes are copied into an aligned buffer first, so they don't need to be
+/// aligned themselves.
+///
+/// # Panics
+///
+/// Panics if the bytes fail validation or if the value cannot be deserialized.
+pub fn to_native<T>(bytes: &[u8]) -> T
+where
+    T: Archive,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Error>>
+        + Deserialize<T, Strategy<Pool, Error>>,
+{
+    let mut aligned = AlignedVec::<16>::with_capacity(bytes.len());
+    aligned.extend_from_slice(bytes);
+
+    let archived = access::<T::Archived, Error>(&aligned)
+        .unwrap_or_else(|e| panic!("failed to validate archived value: {}", e));
+    deserialize::<T, _, Error>(archived, &mut Pool::new())
+        .unwrap_or_else(|e| panic!("failed to deserialize value: {}", e))
+}
+
+fn check_primitive<T, const N: usize>(value: T, le: [u8; N], be: [u8; N])
+where
+    T: Debug
+        + PartialEq
+        + for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Error>>
+        + Deserialize<T, Strategy<Pool, Error>>,
+{
+    let expected = if cfg!(feature = "big_endian") { be } else { le };
+    let bytes = to_bytes::<Error>(&value)
+        .unwrap_or_else(|e| panic!("failed to serialize value: {}", e));
+    assert_eq!(
+        &bytes[..],
+        &expected[..],
+        "archived `{:?}` has the wrong byte order",
+        value,
+    );
+    assert_eq!(to_native::<T>(&bytes), value);
+}
+
+/// Checks the byte order of randomly generated archived primitives, using the
+/// given seed to choose the values.
+///
+/// Each primitive is serialized and its bytes are compared against the
+/// `to_le_bytes` or `to_be_bytes` representation of the value, and then read
+/// back with [`to_native`]. The same `seed` always generates the same values,
+/// so failures can be reproduced.
+///
+/// # Panics
+///
+/// Panics if any primitive is archived with the wrong byte order or does not
+/// read back as the same value.
+pub fn check_byte_order(iterations: usize, seed: u64) {
+    let mut rng = SplitMix64::new(seed);
+    for _ in 0..iterations {
+        let x = rng.next_u64();
+        let y = rng.next_u64();
+
+        macro_rules! check {
+            ($($value:expr),* $(,)?) => {
+                $({
+                    let value = $value;
+                    check_primitive(
+                        value,
+                        value.to_le_bytes(),
+                        value.to_be_bytes(),
+                    );
+                })*
+            };
+        }
+
+        check!(
+            x as u16,
+            x as i16,
+            x as u32,
+            x as i32,
+            x,
+            x as i64,
+            (u128::from(x) << 64) | u128::from(y),
+            (i128::from(x) << 64) | i128::from(y),
+            f32::from_bits(x as u32 & 0x7f7f_ffff),
+            f64::from_bits(x & 0x7fef_ffff_ffff_ffff),
+        );
+
+        let size = x as FixedUsize;
+        check_primitive(size as usize, size.to_le_bytes(), size.to_be_bytes());
+
+        let c = char::from_u32(x as u32 % 0x11_0000).unwrap_or('\u{fffd}');
+        let c_le = u32::from(c).to_le_bytes();
+        let c_be = u32::from(c).to_be_bytes();
+        check_primitive(c, c_le, c_be);
+    }
+}
+
+/// Fixtures for primitives and standard library types.
+///
+/// These fixtures assume the default archive layout with 32-bit `usize` and
+/// aligned primitives, so they aren't available when the `unaligned`,
+/// `pointer_width_16`, or `pointer_width_64` features are enabled.
+#[cfg(not(any(
+    feature = "unaligned",
+    feature = "pointer_width_16",
+    feature = "pointer_width_64",
+)))]
+pub mod fixtures {
+    use super::Fixture;
+
+    /// `0x0102u16`
+    pub const U16: Fixture = Fixture {
+        name: "u16",
+        little_endian: &[0x02, 0x01],
+        big_endian: &[0x01, 0x02],
+    };
+
+    /// `-2i16`
+    pub const I16: Fixture = Fixture {
+        name: "i16",
+        little_endian: &[0xfe, 0xff],
+        big_endian: &[0xff, 0xfe],
+    };
+
+    /// `0x0102_0304u32`
+    pub const U32: Fixture = Fixture {
+        name: "u32",
+        little_endian: &[0x04, 0x03, 0x02, 0x01],
+        big_endian: &[0x01, 0x02, 0x03, 0x04],
+    };
+
+    /// `0x0102_0304_0506_0708u64`
+    pub const U64: Fixture = Fixture {
+        name: "u64",
+        little_endian: &[0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01],
+        big_endian: &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08],
+    };
+
+    /// `0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10u128`
+    pub const U128: Fixture = Fixture {
+        name: "u128",
+        little_endian: &[
+            0x10, 0x0f, 0x0e, 0x0d, 0x0c, 0x0b, 0x0a, 0x09, 0x08, 0x07, 0x06,
+            0x05, 0x04, 0x03, 0x02, 0x01,
+        ],
+        big_endian: &[
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b,
+            0x0c, 0x0d, 0x0e, 0x0f, 0x10,
+        ],
+    };
+
+    /// `0x0102usize`
+    pub const USIZE: Fixture = Fixture {
+        name: "usize",
+        little_endian: &[0x02, 0x01, 0x00, 0x00],
+        big_endian: &[0x00, 0x00, 0x01, 0x02],
+    };
+
+    /// `1.5f32`
+    pub const F32: Fixture = Fixture {
+        name: "f32",
+        little_endian: &[0x00, 0x00, 0xc0, 0x3f],
+        big_endian: &[0x3f, 0xc0, 0x00, 0x00],
+    };
+
+    /// `-2.0f64`
+    pub const F64: Fixture = Fixture {
+        name: "f64",
+        little_endian: &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xc0],
+        big_endian: &[0xc0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    };
+
+    /// `'é'`
+    pub const CHAR: Fixture = Fixture {
+        name: "char",
+        little_endian: &[0xe9, 0x00, 0x00, 0x00],
+        big_endian: &[0x00, 0x00, 0x00, 0xe9],
+    };
+
+    /// `Some(0x0102_0304u32)`
+    pub const OPTION_SOME: Fixture = Fixture {
+        name: "Option::Some",
+        little_endian: &[
+            0x01, 0x00, 0x00, 0x00, // Some + padding
+            0x04, 0x03, 0x02, 0x01, // value
+        ],
+        big_endian: &[
+            0x01, 0x00, 0x00, 0x00, // Some + padding
+            0x01, 0x02, 0x03, 0x04, // value
+        ],
+    };
+
+    /// `None::<u32>`
+    pub const OPTION_NONE: Fixture = Fixture {
+        name: "Option::None",
+        little_endian: &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+        big_endian: &[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    };
+
+    /// `"hi"`, which is short enough to be inlined
+    pub const STRING_INLINE: Fixture = Fixture {
+        name: "String (inline)",
+        little_endian: &[b'h', b'i', 0x00, 0x00, 0x00, 0x00, 0x00, 0x02],
+        big_endian: &[b'h', b'i', 0x00, 0x00, 0x00, 0x00, 0x00, 0x02],
+    };
+
+    /// `"hello, world!"`, which is too long to be inlined
+    ///
+    /// The offset of an out-of-line string is always little-endian so that its
+    /// sign bit is in the last byte of the representation.
+    pub const STRING_OUT_OF_LINE: Fixture = Fixture {
+        name: "String (out-of-line)",
+        little_endian: &[
+            b'h', b'e', b'l', b'l', b'o', b',', b' ', b'w', b'o', b'r', b'l',
+            b'd', b'!', // string bytes
+            0x00, 0x00, 0x00, // padding to 4-alignment
+            0x0d, 0x00, 0x00, 0x00, // string is 13 bytes long
+            0xf0, 0xff, 0xff, 0xff, // points 16 bytes backward
+        ],
+        big_endian: &[
+            b'h', b'e', b'l', b'l', b'o', b',', b' ', b'w', b'o', b'r', b'l',
+            b'd', b'!', // string bytes
+            0x00, 0x00, 0x00, // padding to 4-alignment
+            0x00, 0x00, 0x00, 0x0d, // string is 13 bytes long
+            0xf0, 0xff, 0xff, 0xff, // points 16 bytes backward
+        ],
+    };
+
+    /// `vec![1u16, 2, 3]`
+    pub const VEC: Fixture = Fixture {
+        name: "Vec",
+        little_endian: &[
+            0x01, 0x00, 0x02, 0x00, 0x03, 0x00, // elements
+            0x00, 0x00, // padding to 4-alignment
+            0xf8, 0xff, 0xff, 0xff, // points 8 bytes backward
+            0x03, 0x00, 0x00, 0x00, // vec has 3 elements
+        ],
+        big_endian: &[
+            0x00, 0x01, 0x00, 0x02, 0x00, 0x03, // elements
+            0x00, 0x00, // padding to 4-alignment
+            0xff, 0xff, 0xff, 0xf8, // points 8 bytes backward
+            0x00, 0x00, 0x00, 0x03, // vec has 3 elements
+        ],
+    };
+
+    /// An empty `HashMap<u32, u32>`
+    pub const HASH_MAP_EMPTY: Fixture = Fixture {
+        name: "HashMap (empty)",
+        little_endian: &[
+            0x01, 0x00, 0x00, 0x00, // invalid pointer
+            0x00, 0x00, 0x00, 0x00, // len
+            0x00, 0x00, 0x00, 0x00, // capacity
+        ],
+        big_endian: &[
+            0x00, 0x00, 0x00, 0x01, // invalid pointer
+            0x00, 0x00, 0x00, 0x00, // len
+            0x00, 0x00, 0x00, 0x00, // capacity
+        ],
+    };
+
+    /// A `HashMap<u32, u32>` mapping `1` to `2`
+    ///
+    /// The hash of `1` places its entry in bucket `1` with a control byte of
+    /// `0x28`. Buckets are stored in reverse before the control bytes.
+    pub const HASH_MAP: Fixture = Fixture {
+        name: "HashMap",
+        little_endian: &[
+            0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, // bucket 1
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // bucket 0
+            0xff, 0x28, 0xff, 0x28, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // control bytes
+            0x00, 0x00, 0x00, // padding to 4-alignment
+            0xec, 0xff, 0xff, 0xff, // points 20 bytes backward
+            0x01, 0x00, 0x00, 0x00, // len
+            0x02, 0x00, 0x00, 0x00, // capacity
+        ],
+        big_endian: &[
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, // bucket 1
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // bucket 0
+            0xff, 0x28, 0xff, 0x28, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, // control bytes
+            0x00, 0x00, 0x00, // padding to 4-alignment
+            0xff, 0xff, 0xff, 0xec, // points 20 bytes backward
+            0x00, 0x00, 0x00, 0x01, // len
+            0x00, 0x00, 0x00, 0x02, // capacity
+        ],
+    };
+
+    /// An empty `BTreeMap<u32, u16>`
+    pub const BTREE_MAP_EMPTY: Fixture = Fixture {
+        name: "BTreeMap (empty)",
+        little_endian: &[
+            0x01, 0x00, 0x00, 0x00, // invalid pointer
+            0x00, 0x00, 0x00, 0x00, // len
+        ],
+        big_endian: &[
+            0x00, 0x00, 0x00, 0x01, // invalid pointer
+            0x00, 0x00, 0x00, 0x00, // len
+        ],
+    };
+
+    /// A `BTreeMap<u32, u16>` mapping `0x0102_0304` to `0x0506`
+    pub const BTREE_MAP: Fixture = Fixture {
+        name: "BTreeMap",
+        little_endian: &[
+            0x00, 0x00, 0x00, 0x00, // leaf + padding
+            0x01, 0x00, 0x00, 0x00, // node has 1 entry
+            0x04, 0x03, 0x02, 0x01, // key 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, // keys 1-4
+            0x06, 0x05, // value 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // values 1-4
+            0x00, 0x00, // padding to 4-alignment
+            0xd8, 0xff, 0xff, 0xff, // points 40 bytes backward
+            0x01, 0x00, 0x00, 0x00, // len
+        ],
+        big_endian: &[
+            0x00, 0x00, 0x00, 0x00, // leaf + padding
+            0x00, 0x00, 0x00, 0x01, // node has 1 entry
+            0x01, 0x02, 0x03, 0x04, // key 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, // keys 1-4
+            0x05, 0x06, // value 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // values 1-4
+            0x00, 0x00, // padding to 4-alignment
+            0xff, 0xff, 0xff, 0xd8, // points 40 bytes backward
+            0x00, 0x00, 0x00, 0x01, // len
+        ],
+    };
+
+    /// A `BTreeMap<u32, u16>` mapping each of `1..=6` to `0x10` plus itself
+    ///
+    /// The first entry is in a leaf node, and the rest are in the root inner
+    /// node which points to that leaf as the lesser node of its first entry.
+    pub const BTREE_MAP_INNER: Fixture = Fixture {
+        name: "BTreeMap (inner node)",
+        little_endian: &[
+            0x00, 0x00, 0x00, 0x00, // leaf + padding
+            0x01, 0x00, 0x00, 0x00, // node has 1 entry
+            0x01, 0x00, 0x00, 0x00, // key 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, // keys 1-4
+            0x11, 0x00, // value 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // values 1-4
+            0x00, 0x00, // padding to 4-alignment
+            0x01, 0x00, 0x00, 0x00, // inner + padding
+            0x05, 0x00, 0x00, 0x00, // node has 5 entries
+            0x02, 0x00, 0x00, 0x00, // key 0
+            0x03, 0x00, 0x00, 0x00, // key 1
+            0x04, 0x00, 0x00, 0x00, // key 2
+            0x05, 0x00, 0x00, 0x00, // key 3
+            0x06, 0x00, 0x00, 0x00, // key 4
+            0x12, 0x00, 0x13, 0x00, 0x14, 0x00, // values 0-2
+            0x15, 0x00, 0x16, 0x00, // values 3-4
+            0x00, 0x00, // padding to 4-alignment
+            0xb0, 0xff, 0xff, 0xff, // lesser node 0 is 80 bytes back
+            0x01, 0x00, 0x00, 0x00, // lesser node 1 is invalid
+            0x01, 0x00, 0x00, 0x00, // lesser node 2 is invalid
+            0x01, 0x00, 0x00, 0x00, // lesser node 3 is invalid
+            0x01, 0x00, 0x00, 0x00, // lesser node 4 is invalid
+            0x01, 0x00, 0x00, 0x00, // greater node is invalid
+            0xc0, 0xff, 0xff, 0xff, // points 64 bytes backward
+            0x06, 0x00, 0x00, 0x00, // len
+        ],
+        big_endian: &[
+            0x00, 0x00, 0x00, 0x00, // leaf + padding
+            0x00, 0x00, 0x00, 0x01, // node has 1 entry
+            0x00, 0x00, 0x00, 0x01, // key 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, // keys 1-4
+            0x00, 0x11, // value 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // values 1-4
+            0x00, 0x00, // padding to 4-alignment
+            0x01, 0x00, 0x00, 0x00, // inner + padding
+            0x00, 0x00, 0x00, 0x05, // node has 5 entries
+            0x00, 0x00, 0x00, 0x02, // key 0
+            0x00, 0x00, 0x00, 0x03, // key 1
+            0x00, 0x00, 0x00, 0x04, // key 2
+            0x00, 0x00, 0x00, 0x05, // key 3
+            0x00, 0x00, 0x00, 0x06, // key 4
+            0x00, 0x12, 0x00, 0x13, 0x00, 0x14, // values 0-2
+            0x00, 0x15, 0x00, 0x16, // values 3-4
+            0x00, 0x00, // padding to 4-alignment
+            0xff, 0xff, 0xff, 0xb0, // lesser node 0 is 80 bytes back
+            0x00, 0x00, 0x00, 0x01, // lesser node 1 is invalid
+            0x00, 0x00, 0x00, 0x01, // lesser node 2 is invalid
+            0x00, 0x00, 0x00, 0x01, // lesser node 3 is invalid
+            0x00, 0x00, 0x00, 0x01, // lesser node 4 is invalid
+            0x00, 0x00, 0x00, 0x01, // greater node is invalid
+            0xff, 0xff, 0xff, 0xc0, // points 64 bytes backward
+            0x00, 0x00, 0x00, 0x06, // len
+        ],
+    };
+
+    #[cfg(test)]
+    mod tests {
+        #[cfg(not(feature = "std"))]
+        use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+        use core::fmt::Debug;
+        #[cfg(feature = "std")]
+        use std::collections::{BTreeMap, HashMap};
+
+        use bytecheck::CheckBytes;
+        use rancor::{Error, Strategy};
+
+        use crate::{
+            de::Pool,
+            ser::DefaultSerializer,
+            test_endian::{check_fixture, fixtures::*, to_native, Fixture},
+            util::AlignedVec,
+            validation::validators::DefaultValidator,
+            Archive, Deserialize, Serialize,
+        };
+
+        type Validator<'a> = Strategy<DefaultValidator<'a>, Error>;
+
+        fn check<T>(value: T, fixture: &Fixture)
+        where
+            T: Debug
+                + PartialEq
+                + for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+            T::Archived: for<'a> CheckBytes<Validator<'a>>
+                + Deserialize<T, Strategy<Pool, Error>>,
+        {
+            check_fixture(&value, fixture);
+            assert_eq!(to_native::<T>(fixture.expected()), value);
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes)]
+        struct Point {
+            x: i16,
+            y: u32,
+            flag: bool,
+        }
+
+        const POINT: Fixture = Fixture {
+            name: "Point",
+            little_endian: &[
+                0xfe, 0xff, 0x00, 0x00, // x + padding
+                0x04, 0x03, 0x02, 0x01, // y
+                0x01, 0x00, 0x00, 0x00, // flag + padding
+            ],
+            big_endian: &[
+                0xff, 0xfe, 0x00, 0x00, // x + padding
+                0x01, 0x02, 0x03, 0x04, // y
+                0x01, 0x00, 0x00, 0x00, // flag + padding
+            ],
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes)]
+        enum Shape {
+            Circle(u16),
+            Rect { w: u32, h: u8 },
+        }
+
+        const SHAPE: Fixture = Fixture {
+            name: "Shape",
+            little_endian: &[
+                0x01, 0x00, 0x00, 0x00, // Rect + padding
+                0x04, 0x03, 0x02, 0x01, // w
+                0x05, 0x00, 0x00, 0x00, // h + padding
+            ],
+            big_endian: &[
+                0x01, 0x00, 0x00, 0x00, // Rect + padding
+                0x01, 0x02, 0x03, 0x04, // w
+                0x05, 0x00, 0x00, 0x00, // h + padding
+            ],
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes)]
+        struct Record {
+            id: u64,
+            name: String,
+            scores: Vec<u32>,
+            parent: Option<u16>,
+        }
+
+        const RECORD: Fixture = Fixture {
+            name: "Record",
+            little_endian: &[
+                b'h', b'e', b'l', b'l', b'o', b',', b' ', b'w', b'o', b'r',
+                b'l', b'd', b'!', // name bytes
+                0x00, 0x00, 0x00, // padding to 4-alignment
+                0x07, 0x00, 0x00, 0x00, // scores
+                0x00, 0x00, 0x00, 0x00, // padding to 8-alignment
+                0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, // id
+                0x0d, 0x00, 0x00, 0x00, // name is 13 bytes long
+                0xe0, 0xff, 0xff, 0xff, // points 32 bytes backward
+                0xe8, 0xff, 0xff, 0xff, // points 24 bytes backward
+                0x01, 0x00, 0x00, 0x00, // scores has 1 element
+                0x01, 0x00, 0x06, 0x05, // Some + padding + parent
+                0x00, 0x00, 0x00, 0x00, // padding to 8-alignment
+            ],
+            big_endian: &[
+                b'h', b'e', b'l', b'l', b'o', b',', b' ', b'w', b'o', b'r',
+                b'l', b'd', b'!', // name bytes
+                0x00, 0x00, 0x00, // padding to 4-alignment
+                0x00, 0x00, 0x00, 0x07, // scores
+                0x00, 0x00, 0x00, 0x00, // padding to 8-alignment
+                0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, // id
+                0x00, 0x00, 0x00, 0x0d, // name is 13 bytes long
+                0xe0, 0xff, 0xff, 0xff, // points 32 bytes backward
+                0xff, 0xff, 0xff, 0xe8, // points 24 bytes backward
+                0x00, 0x00, 0x00, 0x01, // scores has 1 element
+                0x01, 0x00, 0x05, 0x06, // Some + padding + parent
+                0x00, 0x00, 0x00, 0x00, // padding to 8-alignment
+            ],
+        };
+
+        #[test]
+        fn primitives() {
+            check(0x0102u16, &U16);
+            check(-2i16, &I16);
+            check(0x0102_0304u32, &U32);
+            check(0x0102_0304_0506_0708u64, &U64);
+            check(0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10u128, &U128);
+            check(0x0102usize, &USIZE);
+            check(1.5f32, &F32);
+            check(-2.0f64, &F64);
+            check('é', &CHAR);
+        }
+
+        #[test]
+        fn options() {
+            check(Some(0x0102_0304u32), &OPTION_SOME);
+            check(None::<u32>, &OPTION_NONE);
+        }
+
+        #[test]
+        fn strings() {
+            check(String::from("hi"), &STRING_INLINE);
+            check(String::from("hello, world!"), &STRING_OUT_OF_LINE);
+        }
+
+        #[test]
+        fn vecs() {
+            check(vec![1u16, 2, 3], &VEC);
+        }
+
+        #[cfg(feature = "std")]
+        #[test]
+        fn hash_maps() {
+            check(HashMap::<u32, u32>::new(), &HASH_MAP_EMPTY);
+
+            let mut map = HashMap::new();
+            map.insert(1u32, 2u32);
+            check(map, &HASH_MAP);
+        }
+
+        #[test]
+        fn btree_maps() {
+            check(BTreeMap::<u32, u16>::new(), &BTREE_MAP_EMPTY);
+
+            let mut map = BTreeMap::new();
+            map.insert(0x0102_0304u32, 0x0506u16);
+            check(map, &BTREE_MAP);
+
+            let map = (1..=6u32).map(|i| (i, 0x10 + i as u16)).collect();
+            check::<BTreeMap<u32, u16>>(map, &BTREE_MAP_INNER);
+        }
+
+        #[test]
+        fn derived() {
+            check(
+                Point {
+                    x: -2,
+                    y: 0x0102_0304,
+                    flag: true,
+                },
+                &POINT,
+            );
+            check(
+                Shape::Rect {
+                    w: 0x0102_0304,
+                    h: 5,
+                },
+                &SHAPE,
+            );
+            check(
+                Record {
+                    id: 0x0102_0304_0506_0708,
+                    name: String::from("hello, world!"),
+                    scores: vec![7],
+                    parent: Some(0x0506),
+                },
+                &RECORD,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_byte_order, DEFAULT_SEED};
+
+    #[test]
+    fn byte_order() {
+        check_byte_order(1000, DEFAULT_SEED);
+    }
+}
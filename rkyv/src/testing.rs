@@ -0,0 +1,275 @@
+//! Test harnesses for archived types.
+//!
+//! These functions take care of the boilerplate involved in testing that a
+//! type serializes, validates, and deserializes correctly, and that validation
+//! rejects corrupted archives instead of exhibiting undefined behavior. They
+//! panic on failure, and so are intended to be called from tests and fuzzers.
+//!
+//! # Examples
+//!
+//! ```
+//! use rkyv::{testing, Archive, Deserialize, Serialize};
+//!
+//! #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+//! #[rkyv(check_bytes)]
+//! struct Example {
+//!     name: String,
+//!     values: Vec<u32>,
+//! }
+//!
+//! let value = Example {
+//!     name: "pi".to_string(),
+//!     values: vec![3, 1, 4, 1, 5],
+//! };
+//!
+//! testing::roundtrip(&value);
+//! testing::check_corruption_resilience(&value, 1000);
+//! ```
+
+use core::fmt::Debug;
+
+use bytecheck::CheckBytes;
+use rancor::{Error, Failure, Strategy};
+
+use crate::{
+    access, de::Pool, deserialize, ser::DefaultSerializer, to_bytes,
+    util::AlignedVec, validation::validators::DefaultValidator, Archive,
+    Deserialize, Serialize,
+};
+
+/// The seed used by [`check_corruption_resilience`].
+pub const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Serializes, validates, and deserializes the given value, asserting that the
+/// deserialized value is equal to the original.
+///
+/// # Panics
+///
+/// Panics if any step fails or if the deserialized value is not equal to the
+/// original value.
+pub fn roundtrip<T>(value: &T)
+where
+    T: Debug
+        + PartialEq
+        + for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Error>>
+        + Deserialize<T, Strategy<Pool, Error>>,
+{
+    let bytes = to_bytes::<Error>(value)
+        .unwrap_or_else(|e| panic!("failed to serialize value: {}", e));
+    let archived = access::<T::Archived, Error>(&bytes)
+        .unwrap_or_else(|e| panic!("failed to validate archived value: {}", e));
+    let deserialized = deserialize::<T, _, Error>(archived, &mut Pool::new())
+        .unwrap_or_else(|e| panic!("failed to deserialize value: {}", e));
+    assert_eq!(value, &deserialized, "roundtripped value was not equal");
+}
+
+/// Checks that validation catches random single-byte corruptions of the
+/// serialized value.
+///
+/// This is equivalent to [`check_corruption_resilience_with_seed`] with the
+/// [`DEFAULT_SEED`].
+///
+/// # Panics
+///
+/// Panics if the value cannot be serialized or validated, or if deserializing a
+/// corrupted archive which passed validation panics.
+pub fn check_corruption_resilience<T>(value: &T, mutations: usize)
+where
+    T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Failure>>
+        + Deserialize<T, Strategy<Pool, Failure>>,
+{
+    check_corruption_resilience_with_seed(value, mutations, DEFAULT_SEED);
+}
+
+/// Checks that validation catches random single-byte corruptions of the
+/// serialized value, using the given seed to choose the corruptions.
+///
+/// Each corrupted archive which passes validation is deserialized, which walks
+/// all of the archived data through its accessors. Deserialization is allowed
+/// to fail, but must not panic. See [`check_corruption_resilience_with`] to use
+/// a different probe.
+///
+/// # Panics
+///
+/// Panics if the value cannot be serialized or validated, or if deserializing a
+/// corrupted archive which passed validation panics.
+pub fn check_corruption_resilience_with_seed<T>(
+    value: &T,
+    mutations: usize,
+    seed: u64,
+) where
+    T: for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Failure>>
+        + Deserialize<T, Strategy<Pool, Failure>>,
+{
+    check_corruption_resilience_with(value, mutations, seed, |archived| {
+        let _ = deserialize::<T, _, Failure>(archived, &mut Pool::new());
+    });
+}
+
+/// Checks that validation catches random single-byte corruptions of the
+/// serialized value, calling `probe` on each corrupted archive which passes
+/// validation.
+///
+/// The same `seed` always produces the same sequence of corruptions, so
+/// failures can be reproduced. The uncorrupted archive must pass validation.
+///
+/// Returns the number of corrupted archives which passed validation.
+///
+/// # Panics
+///
+/// Panics if the value cannot be serialized or validated. Panics raised by
+/// `probe` are propagated.
+pub fn check_corruption_resilience_with<T>(
+    value: &T,
+    mutations: usize,
+    seed: u64,
+    mut probe: impl FnMut(&T::Archived),
+) -> usize
+where
+    T: Archive + for<'a> Serialize<DefaultSerializer<'a, AlignedVec, Error>>,
+    T::Archived: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, Failure>>,
+{
+    let bytes = to_bytes::<Error>(value)
+        .unwrap_or_else(|e| panic!("failed to serialize value: {}", e));
+    if access::<T::Archived, Failure>(&bytes).is_err() {
+        panic!("failed to validate uncorrupted archived value");
+    }
+
+    if bytes.is_empty() {
+        return 0;
+    }
+
+    let mut rng = SplitMix64::new(seed);
+    let mut corrupted = AlignedVec::<16>::with_capacity(bytes.len());
+    let mut accepted = 0;
+    for _ in 0..mutations {
+        corrupted.clear();
+        corrupted.extend_from_slice(&bytes);
+
+        let index = rng.next_below(bytes.len());
+        // The mask is never zero, so every mutation changes the archive
+        let mask = rng.next_below(255) as u8 + 1;
+        corrupted[index] ^= mask;
+
+        if let Ok(archived) = access::<T::Archived, Failure>(&corrupted) {
+            accepted += 1;
+            probe(archived);
+        }
+    }
+
+    accepted
+}
+
+/// A small, deterministic pseudorandom number generator.
+///
+/// See <https://prng.di.unimi.it/splitmix64.c>.
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub(crate) fn next_below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        boxed::Box,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use super::{
+        check_corruption_resilience, check_corruption_resilience_with,
+        roundtrip, SplitMix64,
+    };
+    use crate::{Archive, Deserialize, Serialize};
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    struct Example {
+        id: u64,
+        name: String,
+        tags: Vec<String>,
+        data: Option<Box<[u8]>>,
+        kind: Kind,
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    #[rkyv(crate, check_bytes)]
+    enum Kind {
+        A,
+        B(u32),
+        C { value: String },
+    }
+
+    fn example() -> Example {
+        Example {
+            id: 0x1234_5678_9abc_def0,
+            name: "a name which is too long to be inline".to_string(),
+            tags: vec!["foo".to_string(), "bar".to_string()],
+            data: Some(vec![1, 2, 3, 4].into_boxed_slice()),
+            kind: Kind::C {
+                value: "another long string".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut a = SplitMix64::new(42);
+        let mut b = SplitMix64::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+        assert_ne!(
+            SplitMix64::new(1).next_u64(),
+            SplitMix64::new(2).next_u64()
+        );
+    }
+
+    #[test]
+    fn roundtrip_values() {
+        roundtrip(&42u32);
+        roundtrip(&"hello world".to_string());
+        roundtrip(&vec![Kind::A, Kind::B(1)]);
+        roundtrip(&example());
+    }
+
+    #[test]
+    fn corruption_resilience() {
+        check_corruption_resilience(&42u32, 100);
+        check_corruption_resilience(&vec![Kind::A, Kind::B(1)], 1000);
+        check_corruption_resilience(&example(), 5000);
+    }
+
+    #[test]
+    fn corruption_is_reproducible() {
+        let value = example();
+        let first = check_corruption_resilience_with(&value, 1000, 7, |_| ());
+        let second = check_corruption_resilience_with(&value, 1000, 7, |_| ());
+        assert_eq!(first, second);
+        // Some corrupted bytes (e.g. string contents) are still valid
+        assert_ne!(first, 0);
+        assert_ne!(first, 1000);
+    }
+}
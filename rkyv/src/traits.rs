@@ -21,6 +21,52 @@ use crate::{
 /// they must be `#[repr(C)]`, `#[repr(int)]`, or `#[repr(C, int)]`.
 /// Additionally, all fields that the type may contain or produce relative
 /// pointers to must also be `Portable`.
+///
+/// # Examples
+///
+/// `Portable` can be derived for types with a well-defined layout whose fields
+/// are all `Portable`. Every archived type must be `Portable`, including
+/// hand-written archived types named with `#[rkyv(as = "...")]`:
+///
+/// ```
+/// use rkyv::{Archive, Archived, Portable};
+///
+/// #[derive(Portable)]
+/// #[repr(C)]
+/// struct ArchivedPoint {
+///     x: Archived<f32>,
+///     y: Archived<f32>,
+/// }
+///
+/// #[derive(Archive)]
+/// #[rkyv(as = "ArchivedPoint")]
+/// struct Point {
+///     x: f32,
+///     y: f32,
+/// }
+/// ```
+///
+/// Hand-written archived types which aren't `Portable` are rejected at compile
+/// time instead of being reinterpreted from bytes:
+///
+/// ```compile_fail
+/// use rkyv::{Archive, Archived};
+///
+/// struct ArchivedPoint {
+///     x: Archived<f32>,
+///     y: Archived<f32>,
+/// }
+///
+/// #[derive(Archive)]
+/// #[rkyv(as = "ArchivedPoint")]
+/// struct Point {
+///     x: f32,
+///     y: f32,
+/// }
+/// ```
+///
+/// Types which meet these requirements but can't derive `Portable` may
+/// implement it manually with `unsafe impl Portable`.
 pub unsafe trait Portable {}
 
 /// Returns the layout of a type from its metadata.
@@ -125,17 +171,15 @@ impl<T: ?Sized> CopyOptimization<T> {
 /// // Or you can customize your serialization for better performance
 /// // and compatibility with #![no_std] environments
 /// use rkyv::{
-///     ser::{allocator::Arena, sharing::Share, Serializer},
-///     util::{serialize_into, AlignedVec},
+///     ser::{allocator::Arena, SerializerBuilder},
+///     util::serialize_into,
 /// };
 ///
 /// let mut arena = Arena::new();
-/// let serializer = serialize_into::<_, Error>(
-///     &value,
-///     Serializer::new(AlignedVec::<16>::new(), arena.acquire(), Share::new()),
-/// )
-/// .unwrap();
-/// let bytes = serializer.into_writer();
+/// let serializer = SerializerBuilder::high_level(&mut arena).build::<Error>();
+/// let bytes = serialize_into::<_, Error>(&value, serializer)
+///     .unwrap()
+///     .into_writer();
 ///
 /// // You can use the safe API with the `bytecheck` feature enabled,
 /// // or you can use the unsafe API (shown here) for maximum performance
@@ -316,6 +360,64 @@ pub trait Deserialize<T, D: Fallible + ?Sized> {
     fn deserialize(&self, deserializer: &mut D) -> Result<T, D::Error>;
 }
 
+/// Deserializes an archived value into an existing value, reusing its
+/// resources.
+///
+/// Collections reuse their allocations, and elements which are already present
+/// are deserialized in place so that their own allocations are reused too.
+/// Primitives are simply overwritten. If deserialization fails, `out` is left
+/// in a valid but unspecified state.
+///
+/// This can be derived for structs with the `deserialize_into` argument of
+/// [`Deserialize`](macro@crate::Deserialize), which deserializes each field
+/// into the corresponding field of `out`.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     access, rancor::Error, Archive, Archived, Deserialize, DeserializeInto,
+///     Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize, Deserialize)]
+/// #[rkyv(deserialize_into)]
+/// struct Frame {
+///     id: u32,
+///     samples: Vec<f32>,
+/// }
+///
+/// let mut out = Frame {
+///     id: 0,
+///     samples: Vec::with_capacity(64),
+/// };
+/// let ptr = out.samples.as_ptr();
+///
+/// for id in 0..4 {
+///     let frame = Frame {
+///         id,
+///         samples: vec![1.0; 32],
+///     };
+///     let bytes = rkyv::to_bytes::<Error>(&frame).unwrap();
+///     let archived = access::<Archived<Frame>, Error>(&bytes).unwrap();
+///     rkyv::deserialize_into::<_, _, Error>(archived, &mut out, &mut ())
+///         .unwrap();
+/// }
+///
+/// assert_eq!(out.id, 3);
+/// assert_eq!(out.samples.len(), 32);
+/// // The samples were deserialized into the same allocation every time.
+/// assert_eq!(out.samples.as_ptr(), ptr);
+/// ```
+pub trait DeserializeInto<T, D: Fallible + ?Sized>: Deserialize<T, D> {
+    /// Deserializes into `out` using the given deserializer.
+    fn deserialize_into(
+        &self,
+        out: &mut T,
+        deserializer: &mut D,
+    ) -> Result<(), D::Error>;
+}
+
 /// A counterpart of [`Archive`] that's suitable for unsized types.
 ///
 /// Unlike `Archive`, types that implement `ArchiveUnsized` must be serialized
@@ -522,3 +624,146 @@ pub trait DeserializeUnsized<T: Pointee + ?Sized, D: Fallible + ?Sized>:
     /// Deserializes the metadata for the given type.
     fn deserialize_metadata(&self) -> T::Metadata;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        boxed::ArchivedBox,
+        collections::swiss_table::{ArchivedHashMap, ArchivedHashSet},
+        rc::{ArcFlavor, ArchivedRc, RcFlavor},
+        string::ArchivedString,
+        vec::ArchivedVec,
+        Archived,
+    };
+
+    fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+
+    // Fails to compile if `$ty` implements `Unpin`.
+    macro_rules! assert_not_unpin {
+        ($ty:ty) => {{
+            trait AmbiguousIfUnpin<A> {
+                fn check() {}
+            }
+            impl<T: ?Sized> AmbiguousIfUnpin<()> for T {}
+            impl<T: ?Sized + Unpin> AmbiguousIfUnpin<u8> for T {}
+            <$ty as AmbiguousIfUnpin<_>>::check();
+        }};
+    }
+
+    #[test]
+    fn archived_types_are_send_sync() {
+        type Int = Archived<u32>;
+
+        assert_send_sync::<ArchivedString>();
+        assert_send_sync::<ArchivedVec<Int>>();
+        assert_send_sync::<ArchivedVec<ArchivedString>>();
+        assert_send_sync::<ArchivedBox<Int>>();
+        assert_send_sync::<ArchivedBox<[Int]>>();
+        assert_send_sync::<ArchivedBox<str>>();
+        assert_send_sync::<ArchivedRc<Int, RcFlavor>>();
+        assert_send_sync::<ArchivedRc<Int, ArcFlavor>>();
+        assert_send_sync::<ArchivedHashMap<ArchivedString, Int>>();
+        assert_send_sync::<ArchivedHashSet<ArchivedString>>();
+    }
+
+    #[test]
+    fn hasher_does_not_affect_auto_traits() {
+        // A hasher which is neither `Send` nor `Sync`.
+        struct LocalHasher(*const ());
+
+        assert_send_sync::<ArchivedHashMap<Archived<u32>, (), LocalHasher>>();
+        assert_send_sync::<ArchivedHashSet<Archived<u32>, LocalHasher>>();
+    }
+
+    #[test]
+    fn archived_types_with_relative_pointers_are_not_unpin() {
+        assert_not_unpin!(ArchivedString);
+        assert_not_unpin!(ArchivedVec<Archived<u32>>);
+        assert_not_unpin!(ArchivedBox<Archived<u32>>);
+        assert_not_unpin!(ArchivedRc<Archived<u32>, RcFlavor>);
+        assert_not_unpin!(ArchivedHashMap<ArchivedString, Archived<u32>>);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn derive_deserialize_into() {
+        #[cfg(not(feature = "std"))]
+        use alloc::{
+            string::{String, ToString},
+            vec,
+            vec::Vec,
+        };
+        use core::marker::PhantomData;
+
+        use rancor::{Panic, Strategy};
+
+        use crate::{
+            test::to_archived, with::AsBox, Archive, Deserialize,
+            DeserializeInto, Serialize,
+        };
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, deserialize_into)]
+        struct Record {
+            id: u32,
+            name: String,
+            tags: Vec<String>,
+            #[rkyv(with = AsBox)]
+            boxed: u32,
+            phantom: PhantomData<u8>,
+        }
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, deserialize_into)]
+        struct Pair(String, u32);
+
+        #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+        #[rkyv(crate, check_bytes, deserialize_into)]
+        struct Unit;
+
+        let mut out = Record {
+            id: 0,
+            name: "a name which is longer than the next one".to_string(),
+            tags: vec!["a previous tag".to_string()],
+            boxed: 0,
+            phantom: PhantomData,
+        };
+        let name = out.name.as_ptr();
+        let tag = out.tags[0].as_ptr();
+
+        let value = Record {
+            id: 1,
+            name: "short".to_string(),
+            tags: vec!["tag".to_string(), "another tag".to_string()],
+            boxed: 2,
+            phantom: PhantomData,
+        };
+        to_archived(&value, |archived| {
+            archived
+                .deserialize_into(&mut out, Strategy::<_, Panic>::wrap(&mut ()))
+                .unwrap();
+        });
+        assert_eq!(out, value);
+        assert_eq!(out.name.as_ptr(), name);
+        assert_eq!(out.tags[0].as_ptr(), tag);
+
+        let mut out = Pair(String::with_capacity(16), 0);
+        let ptr = out.0.as_ptr();
+        to_archived(&Pair("hello".to_string(), 3), |archived| {
+            archived
+                .deserialize_into(&mut out, Strategy::<_, Panic>::wrap(&mut ()))
+                .unwrap();
+        });
+        assert_eq!(out, Pair("hello".to_string(), 3));
+        assert_eq!(out.0.as_ptr(), ptr);
+
+        to_archived(&Unit, |archived| {
+            archived
+                .deserialize_into(
+                    &mut Unit,
+                    Strategy::<_, Panic>::wrap(&mut ()),
+                )
+                .unwrap();
+        });
+    }
+}
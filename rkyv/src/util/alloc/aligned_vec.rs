@@ -10,10 +10,11 @@ use std::{alloc, io};
 
 #[cfg(not(feature = "std"))]
 use ::alloc::{alloc, boxed::Box, vec::Vec};
-use rancor::Fallible;
+use rancor::{Fallible, Source};
 
+use super::allocator::{Allocator, Global};
 use crate::{
-    ser::{Allocator, Writer},
+    ser::{self, Writer},
     vec::{ArchivedVec, VecResolver},
     with::{ArchiveWith, AsVec, DeserializeWith, SerializeWith},
     Place,
@@ -33,33 +34,29 @@ use crate::{
 /// let bytes = AlignedVec::<4096>::with_capacity(1);
 /// assert_eq!(bytes.as_ptr() as usize % 4096, 0);
 /// ```
-pub struct AlignedVec<const ALIGNMENT: usize = 16> {
+///
+/// The memory is allocated with the global allocator by default. Use
+/// [`new_in`](AlignedVec::new_in) to allocate with a different
+/// [`Allocator`](crate::util::allocator::Allocator), which requires the
+/// `allocator-api2` feature.
+pub struct AlignedVec<const ALIGNMENT: usize = 16, Alloc: Allocator = Global> {
     ptr: NonNull<u8>,
     cap: usize,
     len: usize,
+    alloc: Alloc,
 }
 
-impl<const A: usize> Drop for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> Drop for AlignedVec<A, Alloc> {
     fn drop(&mut self) {
         if self.cap != 0 {
             unsafe {
-                alloc::dealloc(self.ptr.as_ptr(), self.layout());
+                self.alloc.deallocate(self.ptr, self.layout());
             }
         }
     }
 }
 
 impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
-    /// The alignment of the vector
-    pub const ALIGNMENT: usize = ALIGNMENT;
-
-    /// Maximum capacity of the vector.
-    ///
-    /// Dictated by the requirements of [`alloc::Layout`]. "`size`, when rounded
-    /// up to the nearest multiple of `align`, must not overflow `isize` (i.e.
-    /// the rounded value must be less than or equal to `isize::MAX`)".
-    pub const MAX_CAPACITY: usize = isize::MAX as usize - (Self::ALIGNMENT - 1);
-
     /// Constructs a new, empty `AlignedVec`.
     ///
     /// The vector will not allocate until elements are pushed into it.
@@ -100,6 +97,40 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
     /// assert!(vec.capacity() >= 11);
     /// ```
     pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_in(capacity, Global)
+    }
+}
+
+impl<const ALIGNMENT: usize, Alloc: Allocator> AlignedVec<ALIGNMENT, Alloc> {
+    /// The alignment of the vector
+    pub const ALIGNMENT: usize = ALIGNMENT;
+
+    /// Maximum capacity of the vector.
+    ///
+    /// Dictated by the requirements of [`alloc::Layout`]. "`size`, when rounded
+    /// up to the nearest multiple of `align`, must not overflow `isize` (i.e.
+    /// the rounded value must be less than or equal to `isize::MAX`)".
+    pub const MAX_CAPACITY: usize = isize::MAX as usize - (Self::ALIGNMENT - 1);
+
+    /// Constructs a new, empty `AlignedVec` which allocates with `alloc`.
+    ///
+    /// The vector will not allocate until elements are pushed into it.
+    ///
+    /// # Examples
+    /// ```
+    /// # use rkyv::util::{allocator::Global, AlignedVec};
+    /// let mut vec = AlignedVec::<16, _>::new_in(Global);
+    /// ```
+    pub fn new_in(alloc: Alloc) -> Self {
+        Self::with_capacity_in(0, alloc)
+    }
+
+    /// Constructs a new, empty `AlignedVec` with the specified capacity which
+    /// allocates with `alloc`.
+    ///
+    /// The vector will be able to hold exactly `capacity` bytes without
+    /// reallocating. If `capacity` is 0, the vector will not allocate.
+    pub fn with_capacity_in(capacity: usize, alloc: Alloc) -> Self {
         assert!(ALIGNMENT > 0, "ALIGNMENT must be 1 or more");
         assert!(
             ALIGNMENT.is_power_of_two(),
@@ -117,6 +148,7 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
                 ptr: NonNull::dangling(),
                 cap: 0,
                 len: 0,
+                alloc,
             }
         } else {
             assert!(
@@ -124,26 +156,30 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
                 "`capacity` cannot exceed `Self::MAX_CAPACITY`"
             );
 
-            let ptr = unsafe {
-                let layout = alloc::Layout::from_size_align_unchecked(
+            let layout = unsafe {
+                alloc::Layout::from_size_align_unchecked(
                     capacity,
                     Self::ALIGNMENT,
-                );
-                let ptr = alloc::alloc(layout);
-                if ptr.is_null() {
-                    alloc::handle_alloc_error(layout);
-                }
-                NonNull::new_unchecked(ptr)
+                )
+            };
+            let Ok(ptr) = alloc.allocate(layout) else {
+                alloc::handle_alloc_error(layout);
             };
 
             Self {
-                ptr,
+                ptr: ptr.cast(),
                 cap: capacity,
                 len: 0,
+                alloc,
             }
         }
     }
 
+    /// Returns a reference to the allocator of the vector.
+    pub fn allocator(&self) -> &Alloc {
+        &self.alloc
+    }
+
     fn layout(&self) -> alloc::Layout {
         unsafe {
             alloc::Layout::from_size_align_unchecked(self.cap, Self::ALIGNMENT)
@@ -191,58 +227,43 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
         debug_assert!(new_cap >= self.len);
 
         if new_cap > 0 {
-            let new_ptr = if self.cap > 0 {
+            // SAFETY:
+            // - `ALIGNMENT` is always guaranteed to be a nonzero power of two.
+            // - The caller has guaranteed that `new_cap` doesn't overflow
+            //   `isize` when rounded up to the nearest power of two.
+            let new_layout = unsafe {
+                alloc::Layout::from_size_align_unchecked(
+                    new_cap,
+                    Self::ALIGNMENT,
+                )
+            };
+            let result = if self.cap == 0 {
+                self.alloc.allocate(new_layout)
+            } else if new_cap > self.cap {
                 // SAFETY:
                 // - `self.ptr` is currently allocated because `self.cap` is
                 //   greater than zero.
                 // - `self.layout()` always matches the layout used to allocate
                 //   the current block of memory.
-                // - We checked that `new_cap` is greater than zero.
-                let new_ptr = unsafe {
-                    alloc::realloc(self.ptr.as_ptr(), self.layout(), new_cap)
-                };
-                if new_ptr.is_null() {
-                    // SAFETY:
-                    // - `ALIGNMENT` is always guaranteed to be a nonzero power
-                    //   of two.
-                    // - We checked that `new_cap` doesn't overflow `isize` when
-                    //   rounded up to the nearest power of two.
-                    let layout = unsafe {
-                        alloc::Layout::from_size_align_unchecked(
-                            new_cap,
-                            Self::ALIGNMENT,
-                        )
-                    };
-                    alloc::handle_alloc_error(layout);
-                }
-                new_ptr
+                // - We checked that `new_cap` is greater than the current
+                //   capacity.
+                unsafe { self.alloc.grow(self.ptr, self.layout(), new_layout) }
             } else {
-                // SAFETY:
-                // - `ALIGNMENT` is always guaranteed to be a nonzero power of
-                //   two.
-                // - We checked that `new_cap` doesn't overflow `isize` when
-                //   rounded up to the nearest power of two.
-                let layout = unsafe {
-                    alloc::Layout::from_size_align_unchecked(
-                        new_cap,
-                        Self::ALIGNMENT,
-                    )
-                };
-                // SAFETY: We checked that `new_cap` has non-zero size.
-                let new_ptr = unsafe { alloc::alloc(layout) };
-                if new_ptr.is_null() {
-                    alloc::handle_alloc_error(layout);
+                // SAFETY: The same as for `grow`, except that we checked that
+                // `new_cap` is less than or equal to the current capacity.
+                unsafe {
+                    self.alloc.shrink(self.ptr, self.layout(), new_layout)
                 }
-                new_ptr
             };
-            // SAFETY: We checked that `new_ptr` is non-null in each of the
-            // branches.
-            self.ptr = unsafe { NonNull::new_unchecked(new_ptr) };
+            let Ok(new_ptr) = result else {
+                alloc::handle_alloc_error(new_layout);
+            };
+            self.ptr = new_ptr.cast();
             self.cap = new_cap;
         } else if self.cap > 0 {
             //
             unsafe {
-                alloc::dealloc(self.ptr.as_ptr(), self.layout());
+                self.alloc.deallocate(self.ptr, self.layout());
             }
             self.ptr = NonNull::dangling();
             self.cap = 0;
@@ -773,7 +794,7 @@ impl<const ALIGNMENT: usize> AlignedVec<ALIGNMENT> {
 const _: () = {
     use std::io::{ErrorKind, Read};
 
-    impl<const A: usize> AlignedVec<A> {
+    impl<const A: usize, Alloc: Allocator> AlignedVec<A, Alloc> {
         /// Reads all bytes until EOF from `r` and appends them to this
         /// `AlignedVec`.
         ///
@@ -879,40 +900,43 @@ const _: () = {
     }
 };
 
-impl<const A: usize> From<AlignedVec<A>> for Vec<u8> {
-    fn from(aligned: AlignedVec<A>) -> Self {
+impl<const A: usize, Alloc: Allocator> From<AlignedVec<A, Alloc>> for Vec<u8> {
+    fn from(aligned: AlignedVec<A, Alloc>) -> Self {
         aligned.to_vec()
     }
 }
 
-impl<const A: usize> AsMut<[u8]> for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> AsMut<[u8]> for AlignedVec<A, Alloc> {
     fn as_mut(&mut self) -> &mut [u8] {
         self.as_mut_slice()
     }
 }
 
-impl<const A: usize> AsRef<[u8]> for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> AsRef<[u8]> for AlignedVec<A, Alloc> {
     fn as_ref(&self) -> &[u8] {
         self.as_slice()
     }
 }
 
-impl<const A: usize> Borrow<[u8]> for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> Borrow<[u8]> for AlignedVec<A, Alloc> {
     fn borrow(&self) -> &[u8] {
         self.as_slice()
     }
 }
 
-impl<const A: usize> BorrowMut<[u8]> for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> BorrowMut<[u8]>
+    for AlignedVec<A, Alloc>
+{
     fn borrow_mut(&mut self) -> &mut [u8] {
         self.as_mut_slice()
     }
 }
 
-impl<const A: usize> Clone for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator + Clone> Clone for AlignedVec<A, Alloc> {
     fn clone(&self) -> Self {
         unsafe {
-            let mut result = Self::with_capacity(self.len);
+            let mut result =
+                Self::with_capacity_in(self.len, self.alloc.clone());
             result.len = self.len;
             core::ptr::copy_nonoverlapping(
                 self.as_ptr(),
@@ -924,19 +948,21 @@ impl<const A: usize> Clone for AlignedVec<A> {
     }
 }
 
-impl<const A: usize> fmt::Debug for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> fmt::Debug for AlignedVec<A, Alloc> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.as_slice().fmt(f)
     }
 }
 
-impl<const A: usize> Default for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator + Default> Default
+    for AlignedVec<A, Alloc>
+{
     fn default() -> Self {
-        Self::new()
+        Self::new_in(Alloc::default())
     }
 }
 
-impl<const A: usize> Deref for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> Deref for AlignedVec<A, Alloc> {
     type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
@@ -944,13 +970,22 @@ impl<const A: usize> Deref for AlignedVec<A> {
     }
 }
 
-impl<const A: usize> DerefMut for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> DerefMut for AlignedVec<A, Alloc> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.as_mut_slice()
     }
 }
 
-impl<const A: usize, I: slice::SliceIndex<[u8]>> Index<I> for AlignedVec<A> {
+// SAFETY: The bytes are stored in a separate allocation, so moving the
+// `AlignedVec` doesn't move them.
+unsafe impl<const A: usize, Alloc: Allocator> stable_deref_trait::StableDeref
+    for AlignedVec<A, Alloc>
+{
+}
+
+impl<const A: usize, Alloc: Allocator, I: slice::SliceIndex<[u8]>> Index<I>
+    for AlignedVec<A, Alloc>
+{
     type Output = <I as slice::SliceIndex<[u8]>>::Output;
 
     fn index(&self, index: I) -> &Self::Output {
@@ -958,14 +993,16 @@ impl<const A: usize, I: slice::SliceIndex<[u8]>> Index<I> for AlignedVec<A> {
     }
 }
 
-impl<const A: usize, I: slice::SliceIndex<[u8]>> IndexMut<I> for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator, I: slice::SliceIndex<[u8]>> IndexMut<I>
+    for AlignedVec<A, Alloc>
+{
     fn index_mut(&mut self, index: I) -> &mut Self::Output {
         &mut self.as_mut_slice()[index]
     }
 }
 
 #[cfg(feature = "std")]
-impl<const A: usize> io::Write for AlignedVec<A> {
+impl<const A: usize, Alloc: Allocator> io::Write for AlignedVec<A, Alloc> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.extend_from_slice(buf);
         Ok(buf.len())
@@ -994,19 +1031,27 @@ impl<const A: usize> io::Write for AlignedVec<A> {
 }
 
 // SAFETY: AlignedVec is safe to send to another thread
-unsafe impl<const A: usize> Send for AlignedVec<A> {}
+unsafe impl<const A: usize, Alloc: Allocator + Send> Send
+    for AlignedVec<A, Alloc>
+{
+}
 
 // SAFETY: AlignedVec is safe to share between threads
-unsafe impl<const A: usize> Sync for AlignedVec<A> {}
+unsafe impl<const A: usize, Alloc: Allocator + Sync> Sync
+    for AlignedVec<A, Alloc>
+{
+}
 
-impl<const A: usize> Unpin for AlignedVec<A> {}
+impl<const A: usize, Alloc: Allocator> Unpin for AlignedVec<A, Alloc> {}
 
-impl<const A: usize> ArchiveWith<AlignedVec<A>> for AsVec {
+impl<const A: usize, Alloc: Allocator> ArchiveWith<AlignedVec<A, Alloc>>
+    for AsVec
+{
     type Archived = ArchivedVec<u8>;
     type Resolver = VecResolver;
 
     fn resolve_with(
-        field: &AlignedVec<A>,
+        field: &AlignedVec<A, Alloc>,
         resolver: Self::Resolver,
         out: Place<Self::Archived>,
     ) {
@@ -1014,12 +1059,14 @@ impl<const A: usize> ArchiveWith<AlignedVec<A>> for AsVec {
     }
 }
 
-impl<S, const A: usize> SerializeWith<AlignedVec<A>, S> for AsVec
+impl<S, const A: usize, Alloc> SerializeWith<AlignedVec<A, Alloc>, S> for AsVec
 where
-    S: Allocator + Fallible + Writer + ?Sized,
+    S: ser::Allocator + Fallible + Writer + ?Sized,
+    S::Error: Source,
+    Alloc: Allocator,
 {
     fn serialize_with(
-        field: &AlignedVec<A>,
+        field: &AlignedVec<A, Alloc>,
         serializer: &mut S,
     ) -> Result<Self::Resolver, S::Error> {
         ArchivedVec::serialize_from_slice(field.as_slice(), serializer)
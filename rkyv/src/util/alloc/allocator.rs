@@ -0,0 +1,199 @@
+//! Memory allocators for [`AlignedVec`](crate::util::AlignedVec) and
+//! [`Arena`](crate::ser::allocator::Arena).
+//!
+//! With the `allocator-api2` feature enabled, these are re-exported from
+//! [`allocator-api2`](https://docs.rs/allocator-api2) so that any allocator
+//! implementing its `Allocator` trait can be used. Otherwise, only the global
+//! allocator is available.
+
+#[cfg(feature = "allocator-api2")]
+pub use allocator_api2::alloc::{AllocError, Allocator, Global};
+
+#[cfg(not(feature = "allocator-api2"))]
+pub use self::fallback::{AllocError, Allocator, Global};
+
+#[cfg(not(feature = "allocator-api2"))]
+mod fallback {
+    #[cfg(not(feature = "std"))]
+    use alloc::alloc::{alloc, dealloc, realloc};
+    use core::{
+        alloc::Layout,
+        fmt,
+        ptr::{self, slice_from_raw_parts_mut, NonNull},
+    };
+    #[cfg(feature = "std")]
+    use std::alloc::{alloc, dealloc, realloc};
+
+    use crate::polyfill;
+
+    /// The error returned when an allocation fails.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct AllocError;
+
+    impl fmt::Display for AllocError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "memory allocation failed")
+        }
+    }
+
+    #[cfg(feature = "std")]
+    impl std::error::Error for AllocError {}
+
+    /// A memory allocator.
+    ///
+    /// This is a subset of the `Allocator` trait from `allocator-api2`, and is
+    /// only implemented for [`Global`]. Enable the `allocator-api2` feature to
+    /// use other allocators.
+    ///
+    /// # Safety
+    ///
+    /// Memory blocks returned from an allocator must point to valid memory and
+    /// retain their validity until they are deallocated, and cloning or moving
+    /// the allocator must not invalidate them.
+    pub unsafe trait Allocator {
+        /// Attempts to allocate a block of memory which fits `layout`.
+        fn allocate(&self, layout: Layout)
+            -> Result<NonNull<[u8]>, AllocError>;
+
+        /// Deallocates the memory referenced by `ptr`.
+        ///
+        /// # Safety
+        ///
+        /// `ptr` must have been allocated by this allocator with `layout`.
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+        /// Attempts to extend the memory block referenced by `ptr`.
+        ///
+        /// # Safety
+        ///
+        /// `ptr` must have been allocated by this allocator with `old_layout`,
+        /// and the size of `new_layout` must be greater than or equal to the
+        /// size of `old_layout`.
+        unsafe fn grow(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError>;
+
+        /// Attempts to shrink the memory block referenced by `ptr`.
+        ///
+        /// # Safety
+        ///
+        /// `ptr` must have been allocated by this allocator with `old_layout`,
+        /// and the size of `new_layout` must be less than or equal to the size
+        /// of `old_layout`.
+        unsafe fn shrink(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError>;
+    }
+
+    /// The global memory allocator.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct Global;
+
+    impl Global {
+        fn slice(
+            ptr: *mut u8,
+            len: usize,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            NonNull::new(slice_from_raw_parts_mut(ptr, len)).ok_or(AllocError)
+        }
+
+        /// # Safety
+        ///
+        /// `ptr` must have been allocated by this allocator with `old_layout`.
+        unsafe fn realloc(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            if old_layout.size() == 0 {
+                return self.allocate(new_layout);
+            }
+            if new_layout.size() == 0 {
+                // SAFETY: The caller has guaranteed that `ptr` was allocated
+                // by this allocator with `old_layout`.
+                unsafe {
+                    self.deallocate(ptr, old_layout);
+                }
+                return self.allocate(new_layout);
+            }
+            if old_layout.align() == new_layout.align() {
+                // SAFETY: Both sizes are nonzero and the alignment is the same,
+                // and the caller has guaranteed that `ptr` was allocated with
+                // `old_layout`.
+                let new_ptr = unsafe {
+                    realloc(ptr.as_ptr(), old_layout, new_layout.size())
+                };
+                return Self::slice(new_ptr, new_layout.size());
+            }
+
+            let new_ptr = self.allocate(new_layout)?;
+            let len = old_layout.size().min(new_layout.size());
+            // SAFETY: Both blocks are valid for at least `len` bytes and are
+            // separate allocations.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    ptr.as_ptr(),
+                    new_ptr.as_ptr().cast::<u8>(),
+                    len,
+                );
+                self.deallocate(ptr, old_layout);
+            }
+            Ok(new_ptr)
+        }
+    }
+
+    unsafe impl Allocator for Global {
+        fn allocate(
+            &self,
+            layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            if layout.size() == 0 {
+                let ptr = polyfill::dangling(&layout).as_ptr();
+                return Self::slice(ptr, 0);
+            }
+            // SAFETY: We checked that `layout` has a nonzero size.
+            Self::slice(unsafe { alloc(layout) }, layout.size())
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            if layout.size() != 0 {
+                // SAFETY: The caller has guaranteed that `ptr` was allocated
+                // by this allocator with `layout`, which has a nonzero size.
+                unsafe {
+                    dealloc(ptr.as_ptr(), layout);
+                }
+            }
+        }
+
+        unsafe fn grow(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            debug_assert!(new_layout.size() >= old_layout.size());
+            // SAFETY: The safety requirements of `grow` include those of
+            // `realloc`.
+            unsafe { self.realloc(ptr, old_layout, new_layout) }
+        }
+
+        unsafe fn shrink(
+            &self,
+            ptr: NonNull<u8>,
+            old_layout: Layout,
+            new_layout: Layout,
+        ) -> Result<NonNull<[u8]>, AllocError> {
+            debug_assert!(new_layout.size() <= old_layout.size());
+            // SAFETY: The safety requirements of `shrink` include those of
+            // `realloc`.
+            unsafe { self.realloc(ptr, old_layout, new_layout) }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use rancor::{ResultExt as _, Source};
+
+use crate::util::AlignedVec;
+
+/// Decompresses an LZ4 frame into an [`AlignedVec`].
+///
+/// This restores an archive which was serialized with an LZ4
+/// [`CompressedWriter`](crate::ser::writer::CompressedWriter) so that it can be
+/// accessed.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     ser::{
+///         allocator::Arena, sharing::Share, writer::CompressedWriter,
+///         Serializer,
+///     },
+///     util::{decompress_lz4_to_aligned, serialize_into},
+///     Archived,
+/// };
+///
+/// let value = "hello world ".repeat(64);
+///
+/// let mut arena = Arena::new();
+/// let writer = CompressedWriter::lz4(Vec::new());
+/// let serializer = Serializer::new(writer, arena.acquire(), Share::new());
+/// let compressed = serialize_into::<_, Error>(&value, serializer)
+///     .unwrap()
+///     .into_writer()
+///     .finish::<Error>()
+///     .unwrap();
+///
+/// let bytes = decompress_lz4_to_aligned::<Error>(&compressed).unwrap();
+/// let archived = rkyv::access::<Archived<String>, Error>(&bytes).unwrap();
+/// assert_eq!(archived, value.as_str());
+/// ```
+#[cfg(feature = "lz4_flex")]
+pub fn decompress_lz4_to_aligned<E: Source>(
+    bytes: &[u8],
+) -> Result<AlignedVec, E> {
+    let mut decoder = lz4_flex::frame::FrameDecoder::new(bytes);
+    let mut result = AlignedVec::new();
+    result.extend_from_reader(&mut decoder).into_error()?;
+    Ok(result)
+}
+
+/// Decompresses a Zstandard frame into an [`AlignedVec`].
+///
+/// This restores an archive which was serialized with a Zstandard
+/// [`CompressedWriter`](crate::ser::writer::CompressedWriter) so that it can be
+/// accessed. See [`CompressedWriter::zstd`] for an example.
+///
+/// [`CompressedWriter::zstd`]: crate::ser::writer::CompressedWriter::zstd
+#[cfg(feature = "zstd")]
+pub fn decompress_zstd_to_aligned<E: Source>(
+    bytes: &[u8],
+) -> Result<AlignedVec, E> {
+    let mut decoder = zstd::stream::Decoder::new(bytes).into_error()?;
+    let mut result = AlignedVec::new();
+    result.extend_from_reader(&mut decoder).into_error()?;
+    Ok(result)
+}
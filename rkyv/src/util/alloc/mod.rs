@@ -1,8 +1,17 @@
 mod aligned_vec;
+pub mod allocator;
+#[cfg(any(feature = "lz4_flex", feature = "zstd"))]
+mod compress;
 
-use rancor::Strategy;
+use core::{fmt, mem};
+
+use rancor::{fail, Source, Strategy};
 
 pub use self::aligned_vec::*;
+#[cfg(any(feature = "lz4_flex", feature = "zstd"))]
+pub use self::compress::*;
+#[cfg(feature = "allocator-api2")]
+use crate::ser::allocator::ArenaHandle;
 use crate::{
     access_unchecked,
     de::pooling::Pool,
@@ -10,111 +19,382 @@ use crate::{
     ser::{
         allocator::Arena, sharing::Share, DefaultSerializer, Serializer, Writer,
     },
-    util::serialize_into,
-    Archive, Deserialize, Serialize,
+    util::{root_alignment, serialize},
+    Archive, Deserialize, Portable, Serialize,
 };
 
 #[cfg(feature = "std")]
 mod arena {
-    use core::cell::Cell;
+    use core::cell::RefCell;
 
     use crate::ser::allocator::Arena;
 
     thread_local! {
-        static THREAD_ARENA: Cell<Option<Arena>> = const { Cell::new(None) };
+        static THREAD_ARENA: RefCell<Option<Arena>> =
+            const { RefCell::new(None) };
     }
 
-    pub fn with_arena<T>(f: impl FnOnce(&mut Arena) -> T) -> T {
+    pub fn try_with_arena<T>(f: impl FnOnce(&mut Arena) -> T) -> Option<T> {
         THREAD_ARENA.with(|thread_arena| {
-            let mut arena = thread_arena.take().unwrap_or_default();
+            // The arena stays in the thread-local while it's borrowed, so it
+            // isn't lost if `f` panics.
+            let mut arena = thread_arena.try_borrow_mut().ok()?;
+            let mut guard = ShrinkGuard {
+                arena: arena.get_or_insert_with(Arena::new),
+            };
+            Some(f(&mut *guard.arena))
+        })
+    }
 
-            let result = f(&mut arena);
-            let capacity = arena.shrink();
+    /// Shrinks the thread-local arena when dropped, even if the function using
+    /// it panicked.
+    struct ShrinkGuard<'a> {
+        arena: &'a mut Arena,
+    }
 
-            if let Some(other) = thread_arena.take() {
-                if other.capacity() > capacity {
-                    arena = other;
-                }
-            }
-            thread_arena.set(Some(arena));
+    impl Drop for ShrinkGuard<'_> {
+        fn drop(&mut self) {
+            self.arena.shrink();
+        }
+    }
 
-            result
-        })
+    pub fn with_arena<T>(f: impl FnOnce(&mut Arena) -> T) -> T {
+        let mut f = Some(f);
+        try_with_arena(|arena| (f.take().unwrap())(arena))
+            .unwrap_or_else(|| (f.take().unwrap())(&mut Arena::new()))
     }
 
     #[inline]
     pub fn clear_arena() {
-        THREAD_ARENA.take();
+        THREAD_ARENA.with(|thread_arena| {
+            if let Ok(mut arena) = thread_arena.try_borrow_mut() {
+                *arena = None;
+            }
+        });
     }
 }
 
-#[cfg(not(feature = "std"))]
-mod arena {
+#[cfg(any(not(feature = "std"), test))]
+mod global {
     use core::{
+        cell::UnsafeCell,
+        hint::spin_loop,
         ptr::{self, NonNull},
-        sync::atomic::{AtomicPtr, Ordering},
+        sync::atomic::{AtomicBool, AtomicPtr, Ordering},
     };
 
     use crate::ser::allocator::Arena;
 
-    static GLOBAL_ARENA: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+    /// A global arena which is swapped out while it is in use.
+    ///
+    /// Callers which find the arena in use allocate a new one instead. When
+    /// they finish, only one of the arenas is kept and the others are freed.
+    pub struct SwapArena {
+        ptr: AtomicPtr<()>,
+    }
 
-    pub fn with_arena<T>(f: impl FnOnce(&mut Arena) -> T) -> T {
-        let ptr = GLOBAL_ARENA.swap(ptr::null_mut(), Ordering::AcqRel);
+    /// The pointer stored in a `SwapArena` while its arena is in use.
+    const BUSY: NonNull<()> = NonNull::dangling();
 
-        let mut arena = if let Some(raw) = NonNull::new(ptr) {
-            unsafe { Arena::from_raw(raw) }
-        } else {
-            Arena::new()
-        };
+    impl SwapArena {
+        pub const fn new() -> Self {
+            Self {
+                ptr: AtomicPtr::new(ptr::null_mut()),
+            }
+        }
 
-        let result = f(&mut arena);
-        arena.shrink();
-
-        let raw = arena.into_raw();
-
-        let swap = GLOBAL_ARENA.compare_exchange(
-            ptr::null_mut(),
-            raw.as_ptr(),
-            Ordering::AcqRel,
-            Ordering::Relaxed,
-        );
-        if swap.is_err() {
-            // Another arena was swapped in while we were executing `f`. We need
-            // to free the current arena.
-            unsafe {
-                drop(Arena::from_raw(raw));
+        pub fn try_with_arena<T>(
+            &self,
+            f: impl FnOnce(&mut Arena) -> T,
+        ) -> Option<T> {
+            let ptr = self.ptr.swap(BUSY.as_ptr(), Ordering::AcqRel);
+            if ptr == BUSY.as_ptr() {
+                return None;
             }
+
+            let arena = match NonNull::new(ptr) {
+                // SAFETY: Every non-null pointer other than `BUSY` stored in
+                // `ptr` was returned from `Arena::into_raw`, and we just took
+                // ownership of it by swapping it out.
+                Some(raw) => unsafe { Arena::from_raw(raw) },
+                None => Arena::new(),
+            };
+            let mut guard = SwapGuard {
+                owner: self,
+                arena: Some(arena),
+            };
+            Some(f(guard.arena.as_mut().unwrap()))
+        }
+
+        pub fn with_arena<T>(&self, f: impl FnOnce(&mut Arena) -> T) -> T {
+            let mut f = Some(f);
+            self.try_with_arena(|arena| (f.take().unwrap())(arena))
+                .unwrap_or_else(|| (f.take().unwrap())(&mut Arena::new()))
         }
 
-        result
+        /// Frees the arena if it is not in use.
+        ///
+        /// An arena which is in use is left alone. Replacing `BUSY` would let
+        /// another caller swap in a second arena while the first is still in
+        /// use.
+        pub fn clear(&self) {
+            let mut ptr = self.ptr.load(Ordering::Acquire);
+            while ptr != BUSY.as_ptr() {
+                match self.ptr.compare_exchange_weak(
+                    ptr,
+                    ptr::null_mut(),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) => {
+                        if let Some(raw) = NonNull::new(ptr) {
+                            // SAFETY: We just took ownership of the arena by
+                            // swapping it out.
+                            unsafe {
+                                drop(Arena::from_raw(raw));
+                            }
+                        }
+                        return;
+                    }
+                    Err(current) => ptr = current,
+                }
+            }
+        }
     }
 
-    #[inline]
-    pub fn clear_arena() {
-        let ptr = GLOBAL_ARENA.swap(ptr::null_mut(), Ordering::AcqRel);
+    /// Returns an arena to its `SwapArena` when dropped, even if the function
+    /// using it panicked.
+    struct SwapGuard<'a> {
+        owner: &'a SwapArena,
+        arena: Option<Arena>,
+    }
+
+    impl Drop for SwapGuard<'_> {
+        fn drop(&mut self) {
+            let mut arena = self.arena.take().unwrap();
+            arena.shrink();
+            let raw = arena.into_raw();
 
-        if let Some(raw) = NonNull::new(ptr) {
-            unsafe {
-                drop(Arena::from_raw(raw));
+            let swap = self.owner.ptr.compare_exchange(
+                BUSY.as_ptr(),
+                raw.as_ptr(),
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            );
+            if swap.is_err() {
+                // Only the guard replaces `BUSY`, so this should not happen. If
+                // it does, free the current arena instead of leaking it.
+                // SAFETY: `raw` was just returned from `into_raw`.
+                unsafe {
+                    drop(Arena::from_raw(raw));
+                }
             }
         }
     }
+
+    /// A global arena which is guarded by a spinlock.
+    ///
+    /// Callers which find the arena in use wait for it to become available, so
+    /// only one arena is ever allocated.
+    pub struct LockedArena {
+        locked: AtomicBool,
+        arena: UnsafeCell<Option<Arena>>,
+    }
+
+    // SAFETY: The arena is only accessed while the lock is held, and `Arena` is
+    // `Send`.
+    unsafe impl Sync for LockedArena {}
+
+    impl LockedArena {
+        pub const fn new() -> Self {
+            Self {
+                locked: AtomicBool::new(false),
+                arena: UnsafeCell::new(None),
+            }
+        }
+
+        #[cfg(not(feature = "critical-section"))]
+        fn try_lock(&self) -> Option<LockGuard<'_>> {
+            self.locked
+                .compare_exchange(
+                    false,
+                    true,
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .ok()
+                .map(|_| LockGuard { owner: self })
+        }
+
+        // Some targets can load and store atomics but not compare and swap
+        // them. A critical section makes the load and store atomic with
+        // respect to other threads and interrupts instead. It only lasts as
+        // long as it takes to acquire the lock, not while the arena is used.
+        #[cfg(feature = "critical-section")]
+        fn try_lock(&self) -> Option<LockGuard<'_>> {
+            critical_section::with(|_| {
+                if self.locked.load(Ordering::Acquire) {
+                    None
+                } else {
+                    self.locked.store(true, Ordering::Relaxed);
+                    Some(LockGuard { owner: self })
+                }
+            })
+        }
+
+        fn lock(&self) -> LockGuard<'_> {
+            loop {
+                if let Some(guard) = self.try_lock() {
+                    return guard;
+                }
+                while self.locked.load(Ordering::Relaxed) {
+                    spin_loop();
+                }
+            }
+        }
+
+        pub fn try_with_arena<T>(
+            &self,
+            f: impl FnOnce(&mut Arena) -> T,
+        ) -> Option<T> {
+            let mut guard = self.try_lock()?;
+            Some(f(guard.arena()))
+        }
+
+        pub fn with_arena<T>(&self, f: impl FnOnce(&mut Arena) -> T) -> T {
+            f(self.lock().arena())
+        }
+
+        pub fn clear(&self) {
+            if let Some(guard) = self.try_lock() {
+                // SAFETY: We hold the lock, so we have exclusive access to the
+                // arena.
+                unsafe {
+                    *guard.owner.arena.get() = None;
+                }
+            }
+        }
+    }
+
+    /// Releases the lock on a `LockedArena` when dropped, even if the function
+    /// using the arena panicked.
+    struct LockGuard<'a> {
+        owner: &'a LockedArena,
+    }
+
+    impl LockGuard<'_> {
+        fn arena(&mut self) -> &mut Arena {
+            // SAFETY: We hold the lock, so we have exclusive access to the
+            // arena.
+            let arena = unsafe { &mut *self.owner.arena.get() };
+            arena.get_or_insert_with(Arena::new)
+        }
+    }
+
+    impl Drop for LockGuard<'_> {
+        fn drop(&mut self) {
+            // SAFETY: We hold the lock, so we have exclusive access to the
+            // arena.
+            if let Some(arena) = unsafe { &mut *self.owner.arena.get() } {
+                arena.shrink();
+            }
+            self.owner.locked.store(false, Ordering::Release);
+        }
+    }
+}
+
+#[cfg(not(feature = "std"))]
+mod arena {
+    use super::global;
+    use crate::ser::allocator::Arena;
+
+    #[cfg(not(feature = "locked_arena"))]
+    static GLOBAL_ARENA: global::SwapArena = global::SwapArena::new();
+    #[cfg(feature = "locked_arena")]
+    static GLOBAL_ARENA: global::LockedArena = global::LockedArena::new();
+
+    pub fn try_with_arena<T>(f: impl FnOnce(&mut Arena) -> T) -> Option<T> {
+        GLOBAL_ARENA.try_with_arena(f)
+    }
+
+    pub fn with_arena<T>(f: impl FnOnce(&mut Arena) -> T) -> T {
+        GLOBAL_ARENA.with_arena(f)
+    }
+
+    #[inline]
+    pub fn clear_arena() {
+        GLOBAL_ARENA.clear();
+    }
+}
+
+#[derive(Debug)]
+struct ArenaBusy;
+
+impl fmt::Display for ArenaBusy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the builtin arena allocator is already in use")
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for ArenaBusy {}
+
 /// Calls the given function with the builtin arena allocator.
 ///
 /// When the `std` feature is enabled, the builtin arena allocator is a
 /// thread-local variable, with one allocator per thread. Otherwise, it is a
-/// global static and all threads share the same arena.
+/// global static and all threads share the same arena:
+///
+/// - By default, a caller which finds the arena in use allocates a new arena.
+///   Only one arena is kept after multiple threads serialize at the same time,
+///   so the others are freed.
+/// - With the `locked_arena` feature, the arena is guarded by a spinlock and
+///   callers wait until it is available. Calling `with_arena` from inside `f`,
+///   or from an interrupt handler which may interrupt `f`, deadlocks. Use
+///   [`try_with_arena`] in those situations instead.
+/// - With the `critical-section` feature, the spinlock is acquired inside a
+///   `critical_section::with` block, so it works on targets without atomic
+///   compare-and-swap. This also enables `locked_arena`.
+///
+/// If the arena is in use by the current thread (e.g. by an enclosing call to
+/// `with_arena`) when `std` is enabled, a new arena is allocated for `f`. The
+/// arena is returned for later use even if `f` panics.
 pub fn with_arena<T>(f: impl FnOnce(&mut Arena) -> T) -> T {
     arena::with_arena(f)
 }
 
+/// Calls the given function with the builtin arena allocator, returning an
+/// error if it is already in use.
+///
+/// Unlike [`with_arena`], this never allocates a second arena or waits for the
+/// arena to become available.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     util::{try_with_arena, with_arena},
+/// };
+///
+/// with_arena(|_| {
+///     // The arena is already in use
+///     assert!(try_with_arena::<_, Error>(|_| ()).is_err());
+/// });
+/// assert!(try_with_arena::<_, Error>(|_| ()).is_ok());
+/// ```
+pub fn try_with_arena<T, E: Source>(
+    f: impl FnOnce(&mut Arena) -> T,
+) -> Result<T, E> {
+    match arena::try_with_arena(f) {
+        Some(result) => Ok(result),
+        None => fail!(ArenaBusy),
+    }
+}
+
 /// Clears the builtin arena allocator.
 ///
 /// When the `std` feature is enabled, this only clears the allocator for the
-/// current thread.
+/// current thread. An arena which is in use may not be cleared.
 #[inline]
 pub fn clear_arena() {
     arena::clear_arena()
@@ -123,6 +403,10 @@ pub fn clear_arena() {
 /// Serializes the given value and returns the resulting bytes in an
 /// [`AlignedVec`].
 ///
+/// The root of the archive is stored at the end of the returned bytes, and its
+/// position is a multiple of [`root_alignment`](crate::util::root_alignment).
+/// Use [`to_bytes_padded_to`] to produce buffers which can be concatenated.
+///
 /// # Examples
 /// ```
 /// use rkyv::rancor::Error;
@@ -151,6 +435,9 @@ where
 }
 
 /// Serializes the given value and writes the bytes to the given `writer`.
+///
+/// The root of the archive is written last, and its position is a multiple of
+/// [`root_alignment`](crate::util::root_alignment).
 pub fn to_bytes_in<W, E>(
     value: &impl for<'a> Serialize<DefaultSerializer<'a, W, E>>,
     writer: W,
@@ -160,14 +447,165 @@ where
     E: rancor::Source,
 {
     with_arena(|arena| {
-        Ok(serialize_into(
-            value,
-            Serializer::new(writer, arena.acquire(), Share::new()),
-        )?
-        .into_writer())
+        let mut serializer =
+            Serializer::new(writer, arena.acquire(), Share::new());
+        let root = serialize(value, &mut serializer)?;
+        debug_assert_root(value, root, serializer.pos());
+        Ok(serializer.into_writer())
+    })
+}
+
+/// Serializes the given value using memory from the given allocator, and
+/// returns the resulting bytes in an [`AlignedVec`].
+///
+/// Both the output buffer and the arena used for scratch space are allocated
+/// with `alloc`. The builtin arena is not used, so a new arena is created for
+/// each call. To reuse an arena, create one with [`Arena::new_in`] and
+/// serialize with a [`Serializer`] instead.
+///
+/// # Examples
+/// ```
+/// use rkyv::{rancor::Error, util::allocator::Global};
+///
+/// let value = vec![1, 2, 3, 4];
+/// let bytes = rkyv::util::to_bytes_in_alloc::<_, Error>(&value, Global)
+///     .expect("failed to serialize vec");
+/// let archived =
+///     rkyv::access::<rkyv::Archived<Vec<i32>>, Error>(&bytes).unwrap();
+/// assert_eq!(archived.as_slice(), [1, 2, 3, 4]);
+/// ```
+#[cfg(feature = "allocator-api2")]
+pub fn to_bytes_in_alloc<A, E>(
+    value: &impl for<'a> Serialize<
+        Strategy<Serializer<AlignedVec<16, A>, ArenaHandle<'a, A>, Share>, E>,
+    >,
+    alloc: A,
+) -> Result<AlignedVec<16, A>, E>
+where
+    A: allocator::Allocator + Clone,
+    E: rancor::Source,
+{
+    let mut arena = Arena::new_in(alloc.clone());
+    let mut serializer = Serializer::new(
+        AlignedVec::new_in(alloc),
+        arena.acquire(),
+        Share::new(),
+    );
+    let root = serialize(value, &mut serializer)?;
+    debug_assert_root(value, root, serializer.pos());
+    Ok(serializer.into_writer())
+}
+
+fn debug_assert_root<T: Archive>(_: &T, root: usize, end: usize) {
+    debug_assert_eq!(
+        root % root_alignment::<T>(),
+        0,
+        "the root position is not aligned",
+    );
+    debug_assert_eq!(
+        root + mem::size_of::<T::Archived>(),
+        end,
+        "the root is not at the end of the buffer",
+    );
+}
+
+#[derive(Debug)]
+pub(crate) struct InvalidPadAlignment {
+    align: usize,
+}
+
+impl fmt::Display for InvalidPadAlignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "padding alignment must be a nonzero power of two, but was {}",
+            self.align,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidPadAlignment {}
+
+/// Serializes the given value and returns the resulting bytes in an
+/// [`AlignedVec`], with a length that is a multiple of `align`.
+///
+/// Padding is inserted before the root of the archive so that the root stays
+/// at the end of the returned bytes. If `align` is at least the alignment of
+/// every archive and of the start of the buffer they are concatenated into,
+/// then each archive starts at an aligned position in the concatenated buffer
+/// and can be accessed from its own subslice.
+///
+/// Returns an error if `align` is not a power of two.
+///
+/// # Examples
+/// ```
+/// use rkyv::{rancor::Error, to_bytes_padded_to, util::AlignedVec, Archived};
+///
+/// let first = to_bytes_padded_to::<Error>(&"hello".to_string(), 16).unwrap();
+/// let second = to_bytes_padded_to::<Error>(&vec![1u64, 2, 3], 16).unwrap();
+/// assert_eq!(first.len() % 16, 0);
+///
+/// let mut bytes = AlignedVec::<16>::new();
+/// bytes.extend_from_slice(&first);
+/// bytes.extend_from_slice(&second);
+///
+/// let (a, b) = bytes.split_at(first.len());
+/// let a = rkyv::access::<Archived<String>, Error>(a).unwrap();
+/// let b = rkyv::access::<Archived<Vec<u64>>, Error>(b).unwrap();
+/// assert_eq!(a, "hello");
+/// assert_eq!(b[2], 3);
+/// ```
+pub fn to_bytes_padded_to<E>(
+    value: &impl for<'a> Serialize<DefaultSerializer<'a, AlignedVec, E>>,
+    align: usize,
+) -> Result<AlignedVec, E>
+where
+    E: rancor::Source,
+{
+    if !align.is_power_of_two() {
+        fail!(InvalidPadAlignment { align });
+    }
+
+    with_arena(|arena| {
+        let mut serializer =
+            Serializer::new(AlignedVec::new(), arena.acquire(), Share::new());
+        let root = serialize_padded(value, align, &mut serializer)?;
+        debug_assert_root(value, root, serializer.pos());
+        debug_assert_eq!(serializer.pos() % align, 0);
+        Ok(serializer.into_writer())
     })
 }
 
+fn serialize_padded<T, S, E>(
+    value: &T,
+    align: usize,
+    serializer: &mut S,
+) -> Result<usize, E>
+where
+    T: Serialize<Strategy<S, E>>,
+    S: Writer<E>,
+{
+    let serializer = Strategy::<S, E>::wrap(serializer);
+    let resolver = value.serialize(serializer)?;
+
+    // The size of the root is a multiple of its alignment, so positioning the
+    // root to end on a multiple of `align` also aligns the root.
+    let align = align.max(root_alignment::<T>());
+    let size = mem::size_of::<T::Archived>();
+    let end = (serializer.pos() + size + align - 1) & !(align - 1);
+    let mut padding = end - size - serializer.pos();
+    while padding > 0 {
+        let len = padding.min(16);
+        serializer.pad(len)?;
+        padding -= len;
+    }
+
+    // SAFETY: `resolver` is the result of serializing `value`, and the
+    // serializer is aligned for `T::Archived`.
+    unsafe { serializer.resolve_aligned(value, resolver) }
+}
+
 /// Deserializes a value from the given bytes.
 ///
 /// This function is only available with the `alloc` feature because it uses a
@@ -206,14 +644,117 @@ where
     // SAFETY: The caller has guaranteed that a valid `T` is located at the root
     // position in the byte slice.
     let archived = unsafe { access_unchecked::<T::Archived>(bytes) };
-    deserialize(archived, &mut Pool::new())
+    deserialize(archived, &mut Pool::for_archive(bytes))
+}
+
+/// Returns the given bytes if they are aligned to `A`, or copies them into
+/// `scratch` and returns the copy otherwise.
+///
+/// `scratch` is cleared before the bytes are copied into it. Its capacity is
+/// kept, so it can be reused to avoid allocating for every misaligned buffer.
+///
+/// # Examples
+/// ```
+/// use rkyv::util::{align_or_copy, AlignedVec};
+///
+/// let mut bytes = AlignedVec::<16>::new();
+/// bytes.extend_from_slice(&[0, 1, 2, 3, 4]);
+/// let mut scratch = AlignedVec::<16>::new();
+///
+/// // Aligned bytes are used in place
+/// let aligned = align_or_copy(&bytes[..4], &mut scratch);
+/// assert_eq!(aligned.as_ptr(), bytes.as_ptr());
+///
+/// // Misaligned bytes are copied into `scratch`
+/// let misaligned = align_or_copy(&bytes[1..], &mut scratch);
+/// assert_eq!(misaligned, &[1, 2, 3, 4]);
+/// assert_eq!(misaligned.as_ptr() as usize % 16, 0);
+/// ```
+pub fn align_or_copy<'a, const A: usize>(
+    bytes: &'a [u8],
+    scratch: &'a mut AlignedVec<A>,
+) -> &'a [u8] {
+    if bytes.as_ptr() as usize & (A - 1) == 0 {
+        bytes
+    } else {
+        scratch.clear();
+        scratch.extend_from_slice(bytes);
+        scratch
+    }
+}
+
+/// Accesses an archived value from the given byte slice, copying the bytes
+/// into `scratch` first if they are not aligned.
+///
+/// This is an alternative to [`access_unchecked`] for bytes which may not be
+/// aligned, such as buffers received from the network. If the bytes are
+/// aligned to [`AlignedVec::ALIGNMENT`], they are accessed in place. Otherwise
+/// they are copied into `scratch` with [`align_or_copy`], and the copy is
+/// accessed instead. The returned reference borrows from both, so it is valid
+/// regardless of which one it points into.
+///
+/// This function does not check that the data is valid. Use
+/// [`access_or_copy`](crate::validation::util::access_or_copy) to validate the
+/// data instead.
+///
+/// # Safety
+///
+/// - The byte slice must represent an archived object.
+/// - The root of the object must be stored at the end of the slice (this is the
+///   default behavior).
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     to_bytes,
+///     util::{access_or_copy_unchecked, AlignedVec},
+///     Archived,
+/// };
+///
+/// let bytes = to_bytes::<Error>(&vec![1u64, 2, 3]).unwrap();
+///
+/// // Simulate a buffer which is not aligned
+/// let mut buffer = AlignedVec::<16>::new();
+/// buffer.push(0);
+/// buffer.extend_from_slice(&bytes);
+///
+/// let mut scratch = AlignedVec::new();
+/// // SAFETY: The bytes represent an archived `Vec<u64>`.
+/// let archived = unsafe {
+///     access_or_copy_unchecked::<Archived<Vec<u64>>>(
+///         &buffer[1..],
+///         &mut scratch,
+///     )
+/// };
+/// assert_eq!(archived[2], 3);
+/// ```
+pub unsafe fn access_or_copy_unchecked<'a, T: Portable>(
+    bytes: &'a [u8],
+    scratch: &'a mut AlignedVec,
+) -> &'a T {
+    // SAFETY: The caller has guaranteed that a valid `T` is located at the root
+    // position in the byte slice, and `align_or_copy` returns the same bytes.
+    unsafe { access_unchecked::<T>(align_or_copy(bytes, scratch)) }
 }
 
 #[cfg(test)]
 mod tests {
-    use rancor::Panic;
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+    use core::mem::size_of;
 
-    use crate::to_bytes_in;
+    use rancor::{Failure, Panic};
+
+    use crate::{
+        access, to_bytes, to_bytes_in, to_bytes_padded_to,
+        util::{root_alignment, AlignedVec},
+        Archive, Archived, Serialize,
+    };
 
     #[test]
     fn to_bytes_in_vec() {
@@ -221,4 +762,276 @@ mod tests {
         let bytes = to_bytes_in::<_, Panic>(&value, Vec::new()).unwrap();
         assert!(!bytes.is_empty());
     }
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Wide {
+        tag: u8,
+        values: Vec<u64>,
+        wide: u64,
+    }
+
+    #[test]
+    fn root_is_aligned() {
+        let value = Wide {
+            tag: 1,
+            values: vec![2, 3],
+            wide: 4,
+        };
+        let bytes = to_bytes::<Panic>(&value).unwrap();
+        let align = root_alignment::<Wide>();
+        assert_eq!(align, core::mem::align_of::<ArchivedWide>());
+        assert_eq!(bytes.len() % align, 0);
+
+        let root = bytes.len() - size_of::<ArchivedWide>();
+        assert_eq!((bytes.as_ptr() as usize + root) % align, 0);
+        // SAFETY: The root is at `root` and is aligned.
+        let archived =
+            unsafe { &*bytes.as_ptr().add(root).cast::<ArchivedWide>() };
+        assert_eq!(archived.tag, 1);
+        assert_eq!(archived.wide.to_native(), 4);
+    }
+
+    #[test]
+    fn concatenate_padded() {
+        let first = "a string which is longer than eight bytes".to_string();
+        let second = Wide {
+            tag: 5,
+            values: vec![6, 7, 8],
+            wide: 9,
+        };
+
+        for align in [1, 2, 8, 16] {
+            let a = to_bytes_padded_to::<Panic>(&first, align).unwrap();
+            let b = to_bytes_padded_to::<Panic>(&second, align).unwrap();
+            assert_eq!(a.len() % align, 0);
+            assert_eq!(b.len() % align, 0);
+            if align < root_alignment::<Wide>() {
+                continue;
+            }
+
+            // The first archive is followed by the second, and again by the
+            // first to check that an archive with a smaller root alignment
+            // can follow one with a larger alignment.
+            let mut bytes = AlignedVec::<16>::new();
+            bytes.extend_from_slice(&a);
+            bytes.extend_from_slice(&b);
+            bytes.extend_from_slice(&a);
+
+            let (a_bytes, rest) = bytes.split_at(a.len());
+            let (b_bytes, c_bytes) = rest.split_at(b.len());
+            let a_archived =
+                access::<Archived<String>, Panic>(a_bytes).unwrap();
+            let b_archived = access::<ArchivedWide, Panic>(b_bytes).unwrap();
+            let c_archived =
+                access::<Archived<String>, Panic>(c_bytes).unwrap();
+            assert_eq!(a_archived, first.as_str());
+            assert_eq!(c_archived, first.as_str());
+            assert_eq!(b_archived.tag, 5);
+            let values = b_archived.values.iter().map(|v| v.to_native());
+            assert!(values.eq([6, 7, 8]));
+            assert_eq!(b_archived.wide.to_native(), 9);
+        }
+    }
+
+    #[test]
+    fn access_or_copy_unchecked() {
+        let value = Wide {
+            tag: 1,
+            values: vec![2, 3],
+            wide: 4,
+        };
+        let bytes = to_bytes::<Panic>(&value).unwrap();
+        let mut buffer = AlignedVec::<16>::new();
+        buffer.push(0);
+        buffer.extend_from_slice(&bytes);
+
+        // Aligned bytes are accessed in place
+        let mut scratch = AlignedVec::new();
+        let archived = unsafe {
+            super::access_or_copy_unchecked::<ArchivedWide>(
+                &bytes,
+                &mut scratch,
+            )
+        };
+        assert!(bytes
+            .as_ptr_range()
+            .contains(&(archived as *const _).cast()));
+        assert_eq!(archived.wide.to_native(), 4);
+        assert!(scratch.is_empty());
+
+        // Misaligned bytes are copied into the scratch space
+        let mut scratch = AlignedVec::new();
+        let archived = unsafe {
+            super::access_or_copy_unchecked::<ArchivedWide>(
+                &buffer[1..],
+                &mut scratch,
+            )
+        };
+        assert!(!buffer
+            .as_ptr_range()
+            .contains(&(archived as *const _).cast()));
+        assert_eq!(archived.tag, 1);
+        assert!(archived.values.iter().map(|v| v.to_native()).eq([2, 3]));
+        assert_eq!(archived.wide.to_native(), 4);
+        assert_eq!(scratch.as_slice(), bytes.as_slice());
+    }
+
+    #[test]
+    fn invalid_pad_alignment() {
+        let value = 1u32;
+        assert!(to_bytes_padded_to::<Failure>(&value, 0).is_err());
+        assert!(to_bytes_padded_to::<Failure>(&value, 3).is_err());
+        assert!(to_bytes_padded_to::<Failure>(&value, 64).is_ok());
+    }
+
+    #[cfg(feature = "std")]
+    mod arena {
+        use core::alloc::Layout;
+        use std::{
+            panic::{catch_unwind, AssertUnwindSafe},
+            thread,
+        };
+
+        use rancor::{Error, Panic};
+
+        use crate::{
+            ser::{allocator::Arena, sharing::Share, Allocator, Serializer},
+            to_bytes,
+            util::{
+                alloc::global::{LockedArena, SwapArena},
+                serialize_into, try_with_arena, with_arena, AlignedVec,
+            },
+        };
+
+        fn grow(arena: &mut Arena) {
+            // Allocations which are much larger than the arena get dedicated
+            // blocks, so grow it with many small allocations instead.
+            let mut handle = arena.acquire();
+            let layout = Layout::from_size_align(1 << 10, 1).unwrap();
+            for _ in 0..1 << 8 {
+                unsafe {
+                    Allocator::<Panic>::push_alloc(&mut handle, layout)
+                        .unwrap();
+                }
+            }
+        }
+
+        #[test]
+        fn thread_arena_panic() {
+            thread::spawn(|| {
+                with_arena(grow);
+                let capacity = with_arena(|arena| arena.capacity());
+                assert!(capacity >= 1 << 16);
+
+                with_arena(|_| {
+                    assert!(try_with_arena::<_, Error>(|_| ()).is_err());
+                });
+
+                catch_unwind(AssertUnwindSafe(|| {
+                    with_arena(|_| panic!("panicked while using the arena"))
+                }))
+                .unwrap_err();
+
+                let after =
+                    try_with_arena::<_, Error>(|arena| arena.capacity());
+                assert_eq!(after.unwrap(), capacity);
+            })
+            .join()
+            .unwrap();
+        }
+
+        macro_rules! global_arena_tests {
+            ($name:ident, $arena:ty) => {
+                mod $name {
+                    use super::*;
+
+                    #[test]
+                    fn concurrent() {
+                        static ARENA: $arena = <$arena>::new();
+
+                        let value = (0..100)
+                            .map(|i| {
+                                format!("a string which is not inline {}", i)
+                            })
+                            .collect::<Vec<_>>();
+                        let expected = to_bytes::<Panic>(&value).unwrap();
+
+                        thread::scope(|scope| {
+                            for _ in 0..8 {
+                                scope.spawn(|| {
+                                    for _ in 0..100 {
+                                        let bytes = ARENA.with_arena(|arena| {
+                                            serialize_into::<_, Panic>(
+                                                &value,
+                                                Serializer::new(
+                                                    AlignedVec::<16>::new(),
+                                                    arena.acquire(),
+                                                    Share::new(),
+                                                ),
+                                            )
+                                            .unwrap()
+                                            .into_writer()
+                                        });
+                                        assert_eq!(&*bytes, &*expected);
+                                    }
+                                });
+                            }
+                        });
+
+                        assert!(ARENA.try_with_arena(|_| ()).is_some());
+                    }
+
+                    #[test]
+                    fn busy() {
+                        static ARENA: $arena = <$arena>::new();
+
+                        ARENA.with_arena(|_| {
+                            assert!(ARENA.try_with_arena(|_| ()).is_none());
+                        });
+                        assert!(ARENA.try_with_arena(|_| ()).is_some());
+                    }
+
+                    #[test]
+                    fn clear_busy() {
+                        static ARENA: $arena = <$arena>::new();
+
+                        ARENA.with_arena(|_| {
+                            ARENA.clear();
+                            assert!(ARENA.try_with_arena(|_| ()).is_none());
+                        });
+                        assert!(ARENA.try_with_arena(|_| ()).is_some());
+                    }
+
+                    #[test]
+                    fn panic() {
+                        static ARENA: $arena = <$arena>::new();
+
+                        ARENA.with_arena(grow);
+                        let capacity =
+                            ARENA.with_arena(|arena| arena.capacity());
+                        assert!(capacity >= 1 << 16);
+
+                        catch_unwind(|| {
+                            ARENA.with_arena(|_| {
+                                panic!("panicked while using the arena")
+                            })
+                        })
+                        .unwrap_err();
+
+                        let after =
+                            ARENA.try_with_arena(|arena| arena.capacity());
+                        assert_eq!(after, Some(capacity));
+
+                        ARENA.clear();
+                        let after = ARENA.with_arena(|arena| arena.capacity());
+                        assert_eq!(after, Arena::new().capacity());
+                    }
+                }
+            };
+        }
+
+        global_arena_tests!(swap_arena, SwapArena);
+        global_arena_tests!(locked_arena, LockedArena);
+    }
 }
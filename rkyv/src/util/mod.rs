@@ -14,6 +14,10 @@
 mod alloc;
 mod inline_vec;
 mod ser_vec;
+#[cfg(target_has_atomic = "8")]
+mod static_archive;
+#[cfg(target_has_atomic = "8")]
+mod trusted_archive;
 
 use core::{
     mem,
@@ -27,14 +31,28 @@ use rancor::Strategy;
 #[cfg(feature = "alloc")]
 pub use self::alloc::*;
 #[doc(inline)]
-pub use self::{inline_vec::InlineVec, ser_vec::SerVec};
-use crate::{ser::Writer, Archive, Deserialize, Portable, SerializeUnsized};
+#[cfg(target_has_atomic = "8")]
+pub use self::static_archive::{StaticArchive, StaticMap};
+#[doc(inline)]
+#[cfg(target_has_atomic = "8")]
+pub use self::trusted_archive::TrustedArchive;
+#[doc(inline)]
+pub use self::{
+    inline_vec::InlineVec,
+    ser_vec::{ScratchVec, SerVec},
+};
+use crate::{
+    ser::Writer, Archive, Deserialize, DeserializeInto, Portable,
+    SerializeUnsized,
+};
 
-#[cfg(debug_assertions)]
+// Misaligned buffers are always caught in debug builds, and in release builds
+// too with the `strict_alignment` feature.
+#[cfg(any(debug_assertions, feature = "strict_alignment"))]
 fn check_alignment<T: Portable>(ptr: *const u8) {
     let expect_align = core::mem::align_of::<T>();
     let actual_align = (ptr as usize) & (expect_align - 1);
-    debug_assert_eq!(
+    assert_eq!(
         actual_align,
         0,
         concat!(
@@ -63,7 +81,7 @@ pub unsafe fn access_pos_unchecked<T: Portable>(
     bytes: &[u8],
     pos: usize,
 ) -> &T {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "strict_alignment"))]
     check_alignment::<T>(bytes.as_ptr());
 
     // SAFETY: The caller has guaranteed that a valid `T` is located at `pos` in
@@ -85,7 +103,7 @@ pub unsafe fn access_pos_unchecked_mut<T: Portable>(
     bytes: &mut [u8],
     pos: usize,
 ) -> Pin<&mut T> {
-    #[cfg(debug_assertions)]
+    #[cfg(any(debug_assertions, feature = "strict_alignment"))]
     check_alignment::<T>(bytes.as_ptr());
 
     // SAFETY: The caller has guaranteed that a valid `T` is located at `pos` in
@@ -104,6 +122,10 @@ pub unsafe fn access_pos_unchecked_mut<T: Portable>(
 /// store the position of the root object returned from
 /// [`serialize_and_resolve`](crate::Serialize::serialize_and_resolve).
 ///
+/// The byte slice must also be aligned, which is checked in debug builds and
+/// with the `strict_alignment` feature. For byte slices which may not be
+/// aligned, use [`access_or_copy_unchecked`] instead.
+///
 /// # Safety
 ///
 /// - The byte slice must represent an archived object.
@@ -117,6 +139,52 @@ pub unsafe fn access_unchecked<T: Portable>(bytes: &[u8]) -> &T {
     }
 }
 
+/// Accesses an archived value from the given byte slice by calculating the root
+/// position in a const context.
+///
+/// This is a const version of [`access_unchecked`] which can be used with
+/// archives embedded in the binary, for example with `include_bytes!`. It does
+/// not check the alignment of the byte slice in debug builds, but const
+/// evaluation will still fail if the root object is misaligned.
+///
+/// Only some accessors can be called on the returned value in a const context,
+/// such as `len` and `is_empty` on archived strings, vecs, and hash maps, and
+/// `to_native` on archived primitives. Any operation which follows a relative
+/// pointer (such as
+/// [`ArchivedVec::as_slice`](crate::vec::ArchivedVec::as_slice) or
+/// [`ArchivedString::as_str`](crate::string::ArchivedString::as_str)) can only
+/// be performed at runtime.
+///
+/// # Safety
+///
+/// - The byte slice must represent an archived object.
+/// - The root of the object must be stored at the end of the slice (this is the
+///   default behavior).
+/// - The byte slice must be aligned for `T`. Unlike with [`access_unchecked`],
+///   this is not checked in debug builds or with the `strict_alignment`
+///   feature.
+///
+/// # Examples
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error, to_bytes, util::access_unchecked_const,
+///     vec::ArchivedVec, Archived,
+/// };
+///
+/// // In practice, this would be a `&'static [u8]` embedded in the binary.
+/// let bytes = to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+/// let archived =
+///     unsafe { access_unchecked_const::<ArchivedVec<Archived<u32>>>(&bytes) };
+/// assert_eq!(archived.len(), 3);
+/// ```
+pub const unsafe fn access_unchecked_const<T: Portable>(bytes: &[u8]) -> &T {
+    let pos = bytes.len() - mem::size_of::<T>();
+    // SAFETY: The caller has guaranteed that a valid `T` is located at the root
+    // position in the byte slice.
+    unsafe { &*bytes.as_ptr().add(pos).cast() }
+}
+
 /// Accesses a mutable archived value from the given byte slice by calculating
 /// the root position.
 ///
@@ -140,6 +208,29 @@ pub unsafe fn access_unchecked_mut<T: Portable>(
     unsafe { access_pos_unchecked_mut::<T>(bytes, pos) }
 }
 
+/// Returns the alignment of the root of an archived `T`.
+///
+/// When a `T` is serialized with [`to_bytes`](crate::to_bytes), the root is
+/// stored at the end of the buffer and its position is a multiple of this
+/// alignment. The length of the buffer is also a multiple of this alignment. A
+/// pointer to the root may be cast directly to a pointer to `T::Archived` as
+/// long as the start of the buffer is aligned to at least this alignment, which
+/// is always true for an [`AlignedVec`](crate::util::AlignedVec) with at least
+/// this alignment.
+///
+/// # Example
+///
+/// ```
+/// use core::mem::align_of;
+///
+/// use rkyv::{util::root_alignment, Archived};
+///
+/// assert_eq!(root_alignment::<u64>(), align_of::<Archived<u64>>());
+/// ```
+pub const fn root_alignment<T: Archive>() -> usize {
+    mem::align_of::<T::Archived>()
+}
+
 /// A wrapper which aligns its inner value to 16 bytes.
 #[derive(Clone, Copy, Debug)]
 #[repr(C, align(16))]
@@ -198,3 +289,71 @@ where
 {
     value.deserialize(Strategy::wrap(deserializer))
 }
+
+/// Deserializes the given archived value into an existing value using the
+/// provided deserializer.
+///
+/// See [`DeserializeInto`] for details.
+pub fn deserialize_into<T, D, E>(
+    value: &T::Archived,
+    out: &mut T,
+    deserializer: &mut D,
+) -> Result<(), E>
+where
+    T: Archive,
+    T::Archived: DeserializeInto<T, Strategy<D, E>>,
+{
+    value.deserialize_into(out, Strategy::wrap(deserializer))
+}
+
+#[cfg(all(
+    test,
+    feature = "alloc",
+    not(feature = "big_endian"),
+    not(any(feature = "pointer_width_16", feature = "pointer_width_64")),
+))]
+mod tests {
+    #[cfg(not(feature = "std"))]
+    use alloc::{
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    use super::{access_unchecked_const, Align};
+    use crate::{option::ArchivedOption, test::to_bytes, Archived};
+
+    type Example = (Vec<u32>, String);
+
+    // The archived bytes of `(vec![1, 2, 3, 4], "archive".to_string())`.
+    const ARCHIVE: &Align<[u8; 32]> =
+        &Align(*include_bytes!("../../tests/data/const_archive.bin"));
+
+    const ROOT: &Archived<Example> =
+        unsafe { access_unchecked_const::<Archived<Example>>(&ARCHIVE.0) };
+
+    const _: () = {
+        assert!(ROOT.0.len() == 4);
+        assert!(!ROOT.0.is_empty());
+        assert!(ROOT.1.len() == 7);
+        assert!(!ROOT.1.is_empty());
+    };
+
+    const SOME: ArchivedOption<Archived<u32>> =
+        ArchivedOption::Some(Archived::<u32>::from_native(42));
+
+    const _: () = {
+        assert!(SOME.is_some());
+        assert!(!SOME.is_none());
+    };
+
+    #[test]
+    fn const_archive() {
+        let value: Example = (vec![1, 2, 3, 4], "archive".to_string());
+        to_bytes(&value, |bytes| assert_eq!(bytes, &ARCHIVE.0));
+
+        let values = ROOT.0.iter().map(|x| x.to_native()).collect::<Vec<_>>();
+        assert_eq!(values, [1, 2, 3, 4]);
+        assert_eq!(ROOT.1.as_str(), "archive");
+    }
+}
@@ -3,7 +3,7 @@ use core::{
     borrow::{Borrow, BorrowMut},
     fmt,
     marker::PhantomData,
-    mem::MaybeUninit,
+    mem::{ManuallyDrop, MaybeUninit},
     ops,
     ptr::NonNull,
     slice::{self, from_raw_parts_mut},
@@ -32,7 +32,10 @@ impl<T> SerVec<T> {
     /// Constructs a new, empty `SerVec` with the specified capacity.
     ///
     /// The vector will be able to hold exactly `capacity` elements. If
-    /// `capacity` is 0, the vector will not allocate.
+    /// `capacity` is 0, the vector will not allocate. The vector's space is
+    /// returned to the serializer after `f` returns, even if it panics.
+    ///
+    /// See [`ScratchVec`] for a version which doesn't require a closure.
     pub fn with_capacity<S, R>(
         serializer: &mut S,
         cap: usize,
@@ -41,27 +44,10 @@ impl<T> SerVec<T> {
     where
         S: Fallible + Allocator + ?Sized,
     {
-        let layout = Layout::array::<T>(cap).unwrap();
-
-        let mut vec = Self {
-            ptr: if layout.size() != 0 {
-                unsafe { serializer.push_alloc(layout)?.cast() }
-            } else {
-                NonNull::dangling()
-            },
-            cap,
-            len: 0,
-        };
-
-        let result = f(&mut vec, serializer);
-
-        vec.clear();
-
-        if layout.size() != 0 {
-            unsafe {
-                serializer.pop_alloc(vec.ptr.cast(), layout)?;
-            }
-        }
+        let mut scratch = ScratchVec::new(serializer, cap)?;
+        let (vec, serializer) = scratch.split_mut();
+        let result = f(vec, serializer);
+        scratch.free()?;
 
         Ok(result)
     }
@@ -249,6 +235,160 @@ impl<T> SerVec<T> {
     }
 }
 
+/// A [`SerVec`] which borrows its space from a serializer.
+///
+/// The space is returned to the serializer when the `ScratchVec` is dropped,
+/// including during unwinding. While the `ScratchVec` is alive, the serializer
+/// can still be used through [`serializer`](Self::serializer) or
+/// [`split_mut`](Self::split_mut). Any scratch space allocated through it must
+/// be returned before the `ScratchVec` is dropped, which is always the case for
+/// nested `ScratchVec`s.
+///
+/// # Examples
+///
+/// ```
+/// use rkyv::{
+///     rancor::{Error, Fallible, Source},
+///     ser::{Allocator, Writer},
+///     util::ScratchVec,
+///     vec::{ArchivedVec, VecResolver},
+///     Archive, Archived, Place, Serialize,
+/// };
+///
+/// struct SortedVec(Vec<u32>);
+///
+/// impl Archive for SortedVec {
+///     type Archived = ArchivedVec<Archived<u32>>;
+///     type Resolver = VecResolver;
+///
+///     fn resolve(
+///         &self,
+///         resolver: Self::Resolver,
+///         out: Place<Self::Archived>,
+///     ) {
+///         ArchivedVec::resolve_from_len(self.0.len(), resolver, out);
+///     }
+/// }
+///
+/// impl<S> Serialize<S> for SortedVec
+/// where
+///     S: Fallible + Allocator + Writer + ?Sized,
+///     S::Error: Source,
+/// {
+///     fn serialize(
+///         &self,
+///         serializer: &mut S,
+///     ) -> Result<VecResolver, S::Error> {
+///         let mut scratch = ScratchVec::new(serializer, self.0.len())?;
+///         scratch.extend_from_slice(&self.0);
+///         scratch.sort_unstable();
+///
+///         let (sorted, serializer) = scratch.split_mut();
+///         ArchivedVec::serialize_from_slice(sorted.as_slice(), serializer)
+///     }
+/// }
+///
+/// let value = SortedVec(vec![3, 1, 2]);
+/// let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+/// let archived =
+///     rkyv::access::<ArchivedVec<Archived<u32>>, Error>(&bytes).unwrap();
+/// let sorted = archived.iter().map(|x| x.to_native()).collect::<Vec<_>>();
+/// assert_eq!(sorted, [1, 2, 3]);
+/// ```
+pub struct ScratchVec<'a, T, S: Fallible + Allocator + ?Sized> {
+    vec: SerVec<T>,
+    serializer: &'a mut S,
+}
+
+impl<'a, T, S: Fallible + Allocator + ?Sized> ScratchVec<'a, T, S> {
+    /// Constructs a new, empty `ScratchVec` with the specified capacity.
+    ///
+    /// The vector will be able to hold exactly `capacity` elements. If
+    /// `capacity` is 0, the vector will not allocate.
+    pub fn new(serializer: &'a mut S, cap: usize) -> Result<Self, S::Error> {
+        let layout = Layout::array::<T>(cap).unwrap();
+
+        let ptr = if layout.size() != 0 {
+            unsafe { serializer.push_alloc(layout)?.cast() }
+        } else {
+            NonNull::dangling()
+        };
+
+        Ok(Self {
+            vec: SerVec { ptr, cap, len: 0 },
+            serializer,
+        })
+    }
+
+    /// Returns the serializer that the vector borrowed its space from.
+    pub fn serializer(&mut self) -> &mut S {
+        self.serializer
+    }
+
+    /// Returns the vector and the serializer it borrowed its space from.
+    pub fn split_mut(&mut self) -> (&mut SerVec<T>, &mut S) {
+        (&mut self.vec, &mut *self.serializer)
+    }
+
+    /// Drops the elements of the vector and returns its space to the
+    /// serializer.
+    ///
+    /// This is equivalent to dropping the vector, but returns any error which
+    /// occurs while returning the space.
+    pub fn free(self) -> Result<(), S::Error> {
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never used again after being released.
+        unsafe { this.release() }
+    }
+
+    /// # Safety
+    ///
+    /// The vector must not be used again after calling `release`.
+    unsafe fn release(&mut self) -> Result<(), S::Error> {
+        self.vec.clear();
+
+        let layout = Layout::array::<T>(self.vec.cap).unwrap();
+        if layout.size() != 0 {
+            unsafe {
+                self.serializer.pop_alloc(self.vec.ptr.cast(), layout)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T, S: Fallible + Allocator + ?Sized> Drop for ScratchVec<'_, T, S> {
+    fn drop(&mut self) {
+        // SAFETY: The vector is being dropped, so it will not be used again.
+        let _ = unsafe { self.release() };
+    }
+}
+
+impl<T: fmt::Debug, S: Fallible + Allocator + ?Sized> fmt::Debug
+    for ScratchVec<'_, T, S>
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.vec.fmt(f)
+    }
+}
+
+impl<T, S: Fallible + Allocator + ?Sized> ops::Deref for ScratchVec<'_, T, S> {
+    type Target = SerVec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+impl<T, S: Fallible + Allocator + ?Sized> ops::DerefMut
+    for ScratchVec<'_, T, S>
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.vec
+    }
+}
+
 impl<T> SerVec<MaybeUninit<T>> {
     /// Assuming that all the elements are initialized, removes the
     /// `MaybeUninit` wrapper from the vector.
@@ -398,3 +538,80 @@ impl<T> Drop for Drain<'_, T> {
 impl<T> ExactSizeIterator for Drain<'_, T> {}
 
 impl<T> core::iter::FusedIterator for Drain<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use core::mem::MaybeUninit;
+
+    use rancor::{Failure, Strategy};
+
+    use super::ScratchVec;
+    use crate::ser::allocator::{AllocationTracker, SubAllocator};
+
+    #[test]
+    fn nested_scratch_vecs() {
+        let mut bytes = [MaybeUninit::<u8>::uninit(); 256];
+        let mut tracker = AllocationTracker::new(SubAllocator::new(&mut bytes));
+        let serializer = Strategy::<_, Failure>::wrap(&mut tracker);
+
+        let mut outer = ScratchVec::<u32, _>::new(serializer, 4).unwrap();
+        outer.push(1);
+        {
+            let mut inner =
+                ScratchVec::<u64, _>::new(outer.serializer(), 2).unwrap();
+            inner.push(2);
+            inner.push(3);
+            assert_eq!(inner.as_slice(), &[2, 3]);
+        }
+        outer.push(4);
+        {
+            let inner =
+                ScratchVec::<u16, _>::new(outer.serializer(), 8).unwrap();
+            assert!(inner.is_empty());
+            inner.free().unwrap();
+        }
+        assert_eq!(outer.as_slice(), &[1, 4]);
+        outer.free().unwrap();
+
+        let stats = tracker.into_stats();
+        assert_eq!(stats.max_bytes_allocated, 16 + 16);
+        assert_eq!(stats.max_allocations, 2);
+        assert_eq!(stats.max_alignment, 8);
+    }
+
+    #[test]
+    fn zero_sized_scratch_vecs() {
+        let mut bytes = [MaybeUninit::<u8>::uninit(); 16];
+        let mut tracker = AllocationTracker::new(SubAllocator::new(&mut bytes));
+        let serializer = Strategy::<_, Failure>::wrap(&mut tracker);
+
+        let mut units = ScratchVec::<(), _>::new(serializer, 4).unwrap();
+        units.push(());
+        drop(ScratchVec::<u32, _>::new(units.serializer(), 0).unwrap());
+        drop(units);
+
+        assert_eq!(tracker.into_stats().max_allocations, 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn scratch_vec_is_returned_on_unwind() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let mut bytes = [MaybeUninit::<u8>::uninit(); 128];
+        let mut allocator = SubAllocator::new(&mut bytes);
+        let serializer = Strategy::<_, Failure>::wrap(&mut allocator);
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut vec = ScratchVec::<u64, _>::new(&mut *serializer, 8)
+                .expect("failed to allocate scratch space");
+            vec.push(1);
+            panic!("unwinding with scratch space allocated");
+        }));
+        assert!(result.is_err());
+
+        // All of the space was returned, so it can be allocated again
+        let vec = ScratchVec::<u64, _>::new(serializer, 8).unwrap();
+        vec.free().unwrap();
+    }
+}
@@ -0,0 +1,288 @@
+use core::{
+    cell::UnsafeCell,
+    hint::spin_loop,
+    mem::MaybeUninit,
+    slice,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use rancor::{Failure, Fallible, Source};
+
+use crate::{
+    collections::swiss_table::{
+        ArchivedHashMap, HashMapResolver, DEFAULT_LOAD_FACTOR,
+    },
+    ser::{
+        allocator::SubAllocator, sharing::Unshare, writer::Buffer, Allocator,
+        CoreSerializer, Serializer, Writer,
+    },
+    util::{access_unchecked, serialize_into, Align},
+    Archive, Place, Serialize,
+};
+
+pub(super) const UNINIT: u8 = 0;
+pub(super) const RUNNING: u8 = 1;
+pub(super) const READY: u8 = 2;
+
+/// An archive of a value which is built into a static buffer the first time it
+/// is accessed.
+///
+/// The value is constructed at compile time, but it is serialized at run time
+/// because serializers can't run in const contexts. Only the memory for the
+/// archive is reserved ahead of time.
+///
+/// `StaticArchive` can be constructed in const contexts and does not allocate,
+/// which makes it suitable for lookup tables in embedded environments. The
+/// archive is serialized into a buffer of `N` bytes, and up to `N` bytes of
+/// scratch space are used on the stack while serializing.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     collections::swiss_table::ArchivedHashMap,
+///     util::{StaticArchive, StaticMap},
+///     Archived,
+/// };
+///
+/// static TABLE: StaticArchive<StaticMap<u16, u16, 3>, 256> =
+///     StaticArchive::new(StaticMap::new([(1, 10), (2, 20), (3, 30)]));
+///
+/// fn table() -> &'static ArchivedHashMap<Archived<u16>, Archived<u16>> {
+///     TABLE.get()
+/// }
+///
+/// assert_eq!(table().get(&2u16).unwrap().to_native(), 20);
+/// ```
+pub struct StaticArchive<T, const N: usize> {
+    value: T,
+    state: AtomicU8,
+    len: UnsafeCell<usize>,
+    bytes: UnsafeCell<Align<[MaybeUninit<u8>; N]>>,
+}
+
+// SAFETY: The buffer is only written once while `state` is `RUNNING`, and is
+// only read after `state` has been set to `READY`. Shared references to the
+// value and its archived counterpart may be sent to other threads.
+unsafe impl<T, const N: usize> Sync for StaticArchive<T, N>
+where
+    T: Archive + Sync,
+    T::Archived: Sync,
+{
+}
+
+impl<T, const N: usize> StaticArchive<T, N> {
+    /// Returns a new `StaticArchive` of the given value.
+    pub const fn new(value: T) -> Self {
+        Self {
+            value,
+            state: AtomicU8::new(UNINIT),
+            len: UnsafeCell::new(0),
+            bytes: UnsafeCell::new(Align([MaybeUninit::uninit(); N])),
+        }
+    }
+
+    /// Returns the value which is archived.
+    pub const fn value(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T, const N: usize> StaticArchive<T, N>
+where
+    T: Archive + for<'a> Serialize<CoreSerializer<'a, Buffer<'a>, Failure>>,
+{
+    /// Returns the archived value, serializing it first if this is the first
+    /// time it has been accessed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the archived value does not fit in `N` bytes.
+    pub fn get(&self) -> &T::Archived {
+        loop {
+            match self.state.compare_exchange_weak(
+                UNINIT,
+                RUNNING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // If serialization panics, the archive returns to its
+                    // uninitialized state so that other threads don't wait on
+                    // it forever.
+                    let guard = ResetOnDrop(&self.state);
+                    // SAFETY: The state was `UNINIT` and is now `RUNNING`, so
+                    // no other thread is accessing the buffer.
+                    unsafe {
+                        self.serialize();
+                    }
+                    core::mem::forget(guard);
+                    self.state.store(READY, Ordering::Release);
+                    break;
+                }
+                Err(READY) => break,
+                Err(_) => spin_loop(),
+            }
+        }
+
+        // SAFETY: The state is `READY`, so the buffer has been initialized with
+        // `len` bytes containing an archived `T` and will not be written to
+        // again.
+        unsafe {
+            let bytes = slice::from_raw_parts(
+                (*self.bytes.get()).as_ptr().cast::<u8>(),
+                *self.len.get(),
+            );
+            access_unchecked::<T::Archived>(bytes)
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the buffer.
+    unsafe fn serialize(&self) {
+        let mut scratch = [MaybeUninit::<u8>::uninit(); N];
+        // SAFETY: The caller has guaranteed that we have exclusive access to
+        // the buffer.
+        let bytes = unsafe { &mut (*self.bytes.get()).0 };
+        let buffer = serialize_into::<_, Failure>(
+            &self.value,
+            Serializer::new(
+                Buffer::from(bytes),
+                SubAllocator::new(&mut scratch),
+                Unshare,
+            ),
+        )
+        .expect("failed to serialize static archive; N may be too small")
+        .into_writer();
+        // SAFETY: The caller has guaranteed that we have exclusive access to
+        // the length.
+        unsafe {
+            *self.len.get() = buffer.len();
+        }
+    }
+}
+
+/// Returns an initialization state to `UNINIT` when dropped.
+pub(super) struct ResetOnDrop<'a>(pub(super) &'a AtomicU8);
+
+impl Drop for ResetOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.store(UNINIT, Ordering::Release);
+    }
+}
+
+/// A fixed-size list of key-value pairs which archives as a hash map.
+///
+/// `StaticMap` can be constructed in const contexts, and is intended for use
+/// with [`StaticArchive`]. The keys must be unique.
+pub struct StaticMap<K, V, const M: usize>([(K, V); M]);
+
+impl<K, V, const M: usize> StaticMap<K, V, M> {
+    /// Returns a new `StaticMap` of the given entries.
+    pub const fn new(entries: [(K, V); M]) -> Self {
+        Self(entries)
+    }
+
+    /// Returns the entries of the map.
+    pub fn entries(&self) -> &[(K, V)] {
+        &self.0
+    }
+}
+
+impl<K, V, const M: usize> Archive for StaticMap<K, V, M>
+where
+    K: Archive,
+    V: Archive,
+{
+    type Archived = ArchivedHashMap<K::Archived, V::Archived>;
+    type Resolver = HashMapResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedHashMap::resolve_from_len(
+            M,
+            DEFAULT_LOAD_FACTOR,
+            resolver,
+            out,
+        );
+    }
+}
+
+impl<K, V, S, const M: usize> Serialize<S> for StaticMap<K, V, M>
+where
+    K: Serialize<S> + core::hash::Hash + Eq,
+    V: Serialize<S>,
+    S: Fallible + Allocator + Writer + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedHashMap::<K::Archived, V::Archived>::serialize_from_iter(
+            self.0.iter().map(|(key, value)| (key, value)),
+            DEFAULT_LOAD_FACTOR,
+            serializer,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StaticArchive, StaticMap};
+
+    static TABLE: StaticArchive<StaticMap<u16, u16, 4>, 256> =
+        StaticArchive::new(StaticMap::new([
+            (1, 100),
+            (2, 200),
+            (3, 300),
+            (4, 400),
+        ]));
+
+    static NAMES: StaticArchive<StaticMap<u8, &str, 3>, 256> =
+        StaticArchive::new(StaticMap::new([
+            (1, "one"),
+            (2, "two"),
+            (3, "a name which is too long to be inline"),
+        ]));
+
+    static PRIMES: StaticArchive<[u32; 5], 64> =
+        StaticArchive::new([2, 3, 5, 7, 11]);
+
+    #[test]
+    fn static_map_lookups() {
+        let table = TABLE.get();
+        assert_eq!(table.len(), 4);
+        for (key, value) in TABLE.value().entries() {
+            assert_eq!(table.get(key).unwrap().to_native(), *value);
+        }
+        assert!(table.get(&5u16).is_none());
+
+        // The archive is only built once.
+        assert!(core::ptr::eq(table, TABLE.get()));
+    }
+
+    #[test]
+    fn static_map_strings() {
+        let names = NAMES.get();
+        assert_eq!(names.get(&1u8).unwrap(), "one");
+        assert_eq!(
+            names.get(&3u8).unwrap(),
+            "a name which is too long to be inline"
+        );
+        assert!(names.get(&4u8).is_none());
+    }
+
+    #[test]
+    fn static_array() {
+        let primes = PRIMES.get().map(|prime| prime.to_native());
+        assert_eq!(primes, [2, 3, 5, 7, 11]);
+    }
+
+    #[test]
+    #[should_panic = "failed to serialize static archive"]
+    fn too_small() {
+        let archive = StaticArchive::<[u32; 8], 16>::new([0; 8]);
+        archive.get();
+    }
+}
@@ -0,0 +1,331 @@
+#[cfg(feature = "alloc")]
+use core::cell::UnsafeCell;
+use core::{
+    hint::spin_loop,
+    marker::PhantomData,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+use super::static_archive::{ResetOnDrop, READY, RUNNING, UNINIT};
+#[cfg(feature = "alloc")]
+use crate::util::AlignedVec;
+use crate::{util::access_unchecked, Portable};
+
+/// Embeds an archive in the binary and returns a [`TrustedArchive`] of it.
+///
+/// The first argument is the path of the archive, relative to the current
+/// file, and the second argument is the archived type of its root. The bytes
+/// are stored in an [`Align`](crate::util::Align) so that they are always
+/// aligned for access. The archive can be used to initialize a `static`.
+///
+/// The archive is trusted to contain a valid archived value of the given type,
+/// typically because it was produced by a build script. Debug builds validate
+/// it the first time it is accessed (see [`TrustedArchive`] for details), but
+/// release builds do not. Only embed archives which are produced by the same
+/// build.
+///
+/// # Example
+///
+/// ```ignore
+/// use rkyv::{include_archive, util::TrustedArchive, Archived};
+///
+/// static CONFIG: TrustedArchive<Archived<Config>> = include_archive!(
+///     concat!(env!("OUT_DIR"), "/config.bin"),
+///     Archived<Config>
+/// );
+///
+/// fn config() -> &'static Archived<Config> {
+///     CONFIG.get()
+/// }
+/// ```
+#[macro_export]
+macro_rules! include_archive {
+    ($path:expr, $ty:ty $(,)?) => {{
+        const BYTES: &$crate::util::Align<
+            [u8; ::core::include_bytes!($path).len()],
+        > = &$crate::util::Align(*::core::include_bytes!($path));
+        // SAFETY: The archive embedded at `$path` is trusted to contain a
+        // valid archived value of the given type.
+        unsafe { $crate::util::TrustedArchive::<$ty>::new(&BYTES.0) }
+    }};
+}
+
+/// An archive embedded in the binary which is trusted to be valid.
+///
+/// Embedding an archive produced by a build script with `include_bytes!` and
+/// accessing it with [`access_unchecked`] is a common pattern. If the archived
+/// type changes but the archive isn't rebuilt, the archive is silently
+/// misinterpreted. `TrustedArchive` keeps the unchecked access for release
+/// builds, but the first time the archive is accessed in a debug build it is
+/// validated, and panics with a clear message if it doesn't match the
+/// archived type. Validation requires the `alloc` and `bytecheck` features,
+/// and can be enabled for release builds with the `validate_embedded`
+/// feature.
+///
+/// The bounds on [`get`](TrustedArchive::get) only depend on the enabled
+/// features, so code which compiles in debug builds also compiles in release
+/// builds.
+///
+/// Archives embedded with [`include_archive!`](crate::include_archive) are
+/// always aligned. Misaligned archives are copied into aligned memory the
+/// first time they are accessed, which requires the `alloc` feature.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{rancor::Error, util::TrustedArchive, Archived};
+///
+/// // In practice, this would be embedded with `include_archive!`.
+/// let bytes = rkyv::to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+/// let bytes: &'static [u8] = Box::leak(Box::new(bytes));
+///
+/// let archive = unsafe { TrustedArchive::<Archived<Vec<u32>>>::new(bytes) };
+/// assert_eq!(archive.get().len(), 3);
+/// assert_eq!(archive.get()[2], 3);
+/// ```
+pub struct TrustedArchive<T> {
+    bytes: &'static [u8],
+    state: AtomicU8,
+    #[cfg(feature = "alloc")]
+    aligned: UnsafeCell<Option<AlignedVec>>,
+    _phantom: PhantomData<T>,
+}
+
+// SAFETY: The aligned copy of the bytes is only written while the state is
+// `RUNNING`, which only one thread can observe at a time. It is only read
+// after the state becomes `READY`, after which it is never written again.
+unsafe impl<T: Sync> Sync for TrustedArchive<T> {}
+
+impl<T> TrustedArchive<T> {
+    /// Returns a new `TrustedArchive` of the given bytes.
+    ///
+    /// # Safety
+    ///
+    /// - The byte slice must represent an archived `T`.
+    /// - The root of the archive must be stored at the end of the slice (this
+    ///   is the default behavior).
+    pub const unsafe fn new(bytes: &'static [u8]) -> Self {
+        Self {
+            bytes,
+            state: AtomicU8::new(UNINIT),
+            #[cfg(feature = "alloc")]
+            aligned: UnsafeCell::new(None),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the bytes of the archive.
+    pub const fn bytes(&self) -> &'static [u8] {
+        self.bytes
+    }
+}
+
+impl<T: Portable> TrustedArchive<T> {
+    fn is_aligned(&self) -> bool {
+        let align = core::mem::align_of::<crate::util::Align<()>>();
+        self.bytes.as_ptr() as usize & (align - 1) == 0
+    }
+
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the aligned copy.
+    #[cfg(feature = "alloc")]
+    unsafe fn align(&self) {
+        if !self.is_aligned() {
+            let mut aligned = AlignedVec::with_capacity(self.bytes.len());
+            aligned.extend_from_slice(self.bytes);
+            // SAFETY: The caller has guaranteed that we have exclusive access
+            // to the aligned copy.
+            unsafe {
+                *self.aligned.get() = Some(aligned);
+            }
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the aligned copy.
+    #[cfg(not(feature = "alloc"))]
+    unsafe fn align(&self) {
+        assert!(
+            self.is_aligned(),
+            "embedded archive of `{}` is not aligned to {} bytes\nhelp: embed \
+             archives with `include_archive!` or store them in an `Align`",
+            core::any::type_name::<T>(),
+            core::mem::align_of::<crate::util::Align<()>>(),
+        );
+    }
+
+    /// # Safety
+    ///
+    /// The archive must have been prepared, and the aligned copy must not be
+    /// written to again.
+    unsafe fn aligned_bytes(&self) -> &[u8] {
+        #[cfg(feature = "alloc")]
+        // SAFETY: The caller has guaranteed that the aligned copy will not be
+        // written to again.
+        if let Some(aligned) = unsafe { &*self.aligned.get() } {
+            return aligned;
+        }
+        self.bytes
+    }
+
+    fn get_with(&self, validate: impl FnOnce(&[u8])) -> &T {
+        let mut validate = Some(validate);
+        loop {
+            match self.state.compare_exchange_weak(
+                UNINIT,
+                RUNNING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // If validation panics, the archive returns to its
+                    // uninitialized state so that later accesses panic too
+                    // instead of waiting on it forever.
+                    let guard = ResetOnDrop(&self.state);
+                    // SAFETY: The state was `UNINIT` and is now `RUNNING`, so
+                    // no other thread is accessing the aligned copy.
+                    let bytes = unsafe {
+                        self.align();
+                        self.aligned_bytes()
+                    };
+                    if let Some(validate) = validate.take() {
+                        validate(bytes);
+                    }
+                    core::mem::forget(guard);
+                    self.state.store(READY, Ordering::Release);
+                    break;
+                }
+                Err(READY) => break,
+                Err(_) => spin_loop(),
+            }
+        }
+
+        // SAFETY: The state is `READY`, so the archive has been prepared and
+        // the aligned copy will not be written to again. The caller of `new`
+        // has guaranteed that the bytes represent an archived `T`, and they
+        // are aligned.
+        unsafe { access_unchecked::<T>(self.aligned_bytes()) }
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "bytecheck"))]
+impl<T> TrustedArchive<T>
+where
+    T: Portable
+        + for<'a> bytecheck::CheckBytes<
+            rancor::Strategy<
+                crate::validation::validators::DefaultValidator<'a>,
+                rancor::Error,
+            >,
+        >,
+{
+    /// Returns the archived value, preparing it first if this is the first
+    /// time it has been accessed.
+    ///
+    /// Misaligned archives are copied into aligned memory. Debug builds and
+    /// builds with the `validate_embedded` feature also validate the archive.
+    ///
+    /// # Panics
+    ///
+    /// Panics if validation is enabled and the archive fails validation.
+    pub fn get(&self) -> &T {
+        self.get_with(|bytes| {
+            if cfg!(any(debug_assertions, feature = "validate_embedded")) {
+                if let Err(error) = crate::access::<T, rancor::Error>(bytes) {
+                    panic!(
+                        "embedded archive failed validation as `{}`: \
+                         {}\nhelp: the archive may have been built with an \
+                         older version of the type; try rebuilding it",
+                        core::any::type_name::<T>(),
+                        error,
+                    );
+                }
+            }
+        })
+    }
+}
+
+#[cfg(not(all(feature = "alloc", feature = "bytecheck")))]
+impl<T: Portable> TrustedArchive<T> {
+    /// Returns the archived value, preparing it first if this is the first
+    /// time it has been accessed.
+    ///
+    /// Misaligned archives are copied into aligned memory if the `alloc`
+    /// feature is enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the archive is misaligned and the `alloc` feature is not
+    /// enabled.
+    pub fn get(&self) -> &T {
+        self.get_with(|_| ())
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "alloc",
+    feature = "bytecheck",
+    not(feature = "big_endian"),
+))]
+mod tests {
+    use super::TrustedArchive;
+    use crate::{test::to_bytes, util::Align, Archive, Serialize};
+
+    #[derive(Archive, Serialize)]
+    #[rkyv(crate, check_bytes)]
+    struct Config {
+        version: u32,
+        enabled: bool,
+    }
+
+    // The archived bytes of `Config { version: 3, enabled: true }`.
+    static CONFIG: TrustedArchive<ArchivedConfig> = crate::include_archive!(
+        "../../tests/data/trusted_archive.bin",
+        ArchivedConfig
+    );
+
+    #[test]
+    fn embedded_archive() {
+        let value = Config {
+            version: 3,
+            enabled: true,
+        };
+        to_bytes(&value, |bytes| assert_eq!(bytes, CONFIG.bytes()));
+
+        let config = CONFIG.get();
+        assert_eq!(config.version, 3);
+        assert!(config.enabled);
+        assert!(core::ptr::eq(config, CONFIG.get()));
+    }
+
+    #[test]
+    #[cfg(any(debug_assertions, feature = "validate_embedded"))]
+    #[should_panic = "embedded archive failed validation as"]
+    fn stale_archive() {
+        // The archived bytes of an older version of `Config` where `enabled`
+        // was a `u32` with the value 7.
+        static STALE: TrustedArchive<ArchivedConfig> = crate::include_archive!(
+            "../../tests/data/trusted_archive_stale.bin",
+            ArchivedConfig
+        );
+
+        STALE.get();
+    }
+
+    #[test]
+    fn misaligned_archive() {
+        static BYTES: Align<[u8; 9]> = Align([0, 3, 0, 0, 0, 1, 0, 0, 0]);
+
+        let archive =
+            unsafe { TrustedArchive::<ArchivedConfig>::new(&BYTES.0[1..]) };
+        let config = archive.get();
+        assert_eq!(config.version, 3);
+        assert!(config.enabled);
+        // The archive is copied into aligned memory once
+        assert_eq!(config as *const ArchivedConfig as usize % 16, 0);
+        assert!(core::ptr::eq(config, archive.get()));
+    }
+}
@@ -0,0 +1,374 @@
+//! Types which validate archives once and then access them without checking.
+//!
+//! [`ValidatedArchive`] owns an archive which was validated when it was
+//! created, and [`ValidationCache`] remembers which positions of an archive
+//! have already been validated so that [`access_pos_cached`] only validates
+//! each of them once.
+
+#[cfg(feature = "std")]
+use core::{any::TypeId, ops::Range};
+use core::{fmt, marker::PhantomData, ops::Deref};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashSet,
+    sync::{Mutex, PoisonError},
+};
+
+use bytecheck::CheckBytes;
+use rancor::{Source, Strategy};
+use stable_deref_trait::StableDeref;
+
+#[cfg(feature = "std")]
+use crate::{util::access_pos_unchecked, validation::util::access_pos};
+use crate::{
+    util::{access_unchecked, AlignedVec},
+    validation::validators::DefaultValidator,
+    Portable,
+};
+
+/// An archive which has been validated as an archived `T`.
+///
+/// The archive is validated once when the `ValidatedArchive` is created, and
+/// [`access`](ValidatedArchive::access) then returns the root without checking
+/// it again. This is useful when the same archive is accessed many times, for
+/// example when it is loaded once and shared between requests.
+///
+/// The bytes can be held by any [`StableDeref`] owner, such as an
+/// [`AlignedVec`], a `Box<[u8]>`, an `Arc<[u8]>`, or a borrowed `&[u8]`.
+/// `StableDeref` only guarantees that the owner keeps dereferencing to the
+/// same address, so the bytes must also not be modified while they are owned
+/// by a `ValidatedArchive`. None of the owners listed above allow that, but
+/// an owner with interior mutability (for example one which shares a buffer
+/// with another handle that can write to it) must not be used to change the
+/// bytes. Modifying them may make [`access`](ValidatedArchive::access) return
+/// an invalid archived value.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error, to_bytes, validation::cached::ValidatedArchive, Archived,
+/// };
+///
+/// let bytes = to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+/// let archive =
+///     ValidatedArchive::<Archived<Vec<u32>>>::new::<Error>(bytes).unwrap();
+///
+/// assert_eq!(archive.access().len(), 3);
+/// assert_eq!(archive.access()[2], 3);
+///
+/// // Borrowed bytes can be validated without copying them.
+/// let borrowed = ValidatedArchive::<Archived<Vec<u32>>, &[u8]>::new::<Error>(
+///     archive.bytes(),
+/// )
+/// .unwrap();
+/// assert_eq!(borrowed.access()[0], 1);
+/// ```
+pub struct ValidatedArchive<T, B = AlignedVec> {
+    bytes: B,
+    _phantom: PhantomData<T>,
+}
+
+impl<T, B> ValidatedArchive<T, B>
+where
+    T: Portable,
+    B: Deref<Target = [u8]> + StableDeref,
+{
+    /// Validates the given bytes as an archived `T` and returns a
+    /// `ValidatedArchive` of them.
+    ///
+    /// The root of the archive must be stored at the end of the bytes, which is
+    /// the default behavior.
+    pub fn new<E>(bytes: B) -> Result<Self, E>
+    where
+        T: for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
+        E: Source,
+    {
+        crate::access::<T, E>(&bytes)?;
+        Ok(Self {
+            bytes,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Returns the archived value.
+    pub fn access(&self) -> &T {
+        // SAFETY: The bytes were validated as an archived `T` when this
+        // archive was created. `StableDeref` guarantees that the owner keeps
+        // dereferencing to the same address, and the owner is never exposed
+        // mutably. As documented on `ValidatedArchive`, the bytes at that
+        // address must not be modified through the owner in any other way,
+        // so they are still valid.
+        unsafe { access_unchecked::<T>(&self.bytes) }
+    }
+}
+
+impl<T, B: Deref<Target = [u8]>> ValidatedArchive<T, B> {
+    /// Returns the bytes of the archive.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl<T, B> ValidatedArchive<T, B> {
+    /// Returns the owner of the bytes, consuming the `ValidatedArchive`.
+    pub fn into_bytes(self) -> B {
+        self.bytes
+    }
+}
+
+impl<T, B> fmt::Debug for ValidatedArchive<T, B>
+where
+    T: Portable + fmt::Debug,
+    B: Deref<Target = [u8]> + StableDeref,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ValidatedArchive")
+            .field(self.access())
+            .finish()
+    }
+}
+
+#[cfg(feature = "std")]
+const SHARDS: usize = 16;
+
+/// A cache of the positions of an archive which have been validated.
+///
+/// [`access_pos_cached`] validates the value at a position the first time it
+/// is accessed as a given type, and skips validation for every access after
+/// that. The cache can be shared between threads: positions are split between
+/// several independently-locked shards, and a position is locked while it is
+/// being validated so that it is only ever validated once. Failed validations
+/// are not cached.
+///
+/// The cache borrows the archive it was created for, so the archive can't be
+/// modified or freed while the cache is in use.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     to_bytes,
+///     validation::cached::{access_pos_cached, ValidationCache},
+///     Archived,
+/// };
+///
+/// let bytes = to_bytes::<Error>(&"hello world".to_string()).unwrap();
+/// let pos = bytes.len() - core::mem::size_of::<Archived<String>>();
+///
+/// let cache = ValidationCache::new(&bytes);
+/// let first = access_pos_cached::<Archived<String>, Error>(&cache, pos);
+/// assert_eq!(first.unwrap(), "hello world");
+///
+/// // The second access doesn't validate the string again.
+/// let second = access_pos_cached::<Archived<String>, Error>(&cache, pos);
+/// assert_eq!(second.unwrap(), "hello world");
+/// assert!(cache.is_validated::<Archived<String>>(pos));
+/// ```
+#[cfg(feature = "std")]
+pub struct ValidationCache<'a> {
+    bytes: &'a [u8],
+    shards: [Mutex<HashSet<(usize, TypeId)>>; SHARDS],
+}
+
+#[cfg(feature = "std")]
+impl<'a> ValidationCache<'a> {
+    /// Returns a new, empty cache for the given archive.
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            shards: core::array::from_fn(|_| Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Returns the bytes of the archive.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Returns whether the value at the given position has been validated as
+    /// a `T`.
+    pub fn is_validated<T: 'static>(&self, pos: usize) -> bool {
+        self.shard(pos)
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&(pos, TypeId::of::<T>()))
+    }
+
+    /// Returns the number of positions which have been validated.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| {
+                shard.lock().unwrap_or_else(PoisonError::into_inner).len()
+            })
+            .sum()
+    }
+
+    /// Returns whether no positions have been validated.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forgets every validated position in the given range.
+    pub fn invalidate(&self, range: Range<usize>) {
+        for shard in self.shards.iter() {
+            shard
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .retain(|(pos, _)| !range.contains(pos));
+        }
+    }
+
+    fn shard(&self, pos: usize) -> &Mutex<HashSet<(usize, TypeId)>> {
+        // Positions are usually aligned, so the low bits are discarded to
+        // spread them across the shards.
+        &self.shards[(pos / 8) % SHARDS]
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for ValidationCache<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidationCache")
+            .field("len", &self.bytes.len())
+            .field("validated", &self.len())
+            .finish()
+    }
+}
+
+/// Accesses an archived value from the archive of the given cache at the given
+/// position, checking its validity only if it hasn't already been validated.
+///
+/// This behaves like [`access_pos`], but each position is validated at most
+/// once per type for the lifetime of the cache, even when it is accessed from
+/// many threads at the same time. See [`ValidationCache`] for more details.
+#[cfg(feature = "std")]
+pub fn access_pos_cached<'a, T, E>(
+    cache: &ValidationCache<'a>,
+    pos: usize,
+) -> Result<&'a T, E>
+where
+    T: Portable
+        + 'static
+        + for<'b> CheckBytes<Strategy<DefaultValidator<'b>, E>>,
+    E: Source,
+{
+    let key = (pos, TypeId::of::<T>());
+    // The set is only modified after validation succeeds, so it's still
+    // consistent if a validation panicked while holding the lock.
+    let mut validated = cache
+        .shard(pos)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if validated.contains(&key) {
+        // SAFETY: The value at `pos` was validated as a `T` by an earlier
+        // access, and the cache borrows the bytes so they can't have been
+        // modified since.
+        return Ok(unsafe { access_pos_unchecked::<T>(cache.bytes, pos) });
+    }
+
+    let value = access_pos::<T, E>(cache.bytes, pos)?;
+    validated.insert(key);
+    Ok(value)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::{
+        mem::size_of,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use bytecheck::CheckBytes;
+    use rancor::{Error, Fallible};
+
+    use super::{access_pos_cached, ValidatedArchive, ValidationCache};
+    use crate::{
+        primitive::ArchivedU32, to_bytes, util::AlignedVec, Archived, Portable,
+    };
+
+    static CHECKS: AtomicUsize = AtomicUsize::new(0);
+
+    // Counts how many times it has been validated.
+    #[derive(Debug)]
+    #[repr(transparent)]
+    struct Counted(ArchivedU32);
+
+    // SAFETY: `Counted` is a transparent wrapper around a portable type.
+    unsafe impl Portable for Counted {}
+
+    unsafe impl<C: Fallible + ?Sized> CheckBytes<C> for Counted {
+        unsafe fn check_bytes(
+            _: *const Self,
+            _: &mut C,
+        ) -> Result<(), C::Error> {
+            CHECKS.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn validated_archive() {
+        let bytes = to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap();
+        let archive =
+            ValidatedArchive::<Archived<Vec<u32>>>::new::<Error>(bytes)
+                .unwrap();
+        assert_eq!(archive.access().len(), 3);
+        assert_eq!(archive.access()[2], 3);
+
+        let borrowed = ValidatedArchive::<Archived<Vec<u32>>, &[u8]>::new::<
+            Error,
+        >(archive.bytes())
+        .unwrap();
+        assert_eq!(borrowed.access()[1], 2);
+        assert_eq!(borrowed.into_bytes().as_ptr(), archive.bytes().as_ptr());
+
+        let mut bytes = archive.into_bytes();
+        let len = bytes.len();
+        bytes[len - 4..].fill(0x7f);
+        ValidatedArchive::<Archived<Vec<u32>>>::new::<Error>(bytes)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn validates_once_across_threads() {
+        let bytes = to_bytes::<Error>(&[1u32, 2, 3, 4]).unwrap();
+        let cache = ValidationCache::new(&bytes);
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    for _ in 0..100 {
+                        let value =
+                            access_pos_cached::<Counted, Error>(&cache, 8)
+                                .unwrap();
+                        assert_eq!(value.0, 3);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(CHECKS.load(Ordering::Relaxed), 1);
+        assert!(cache.is_validated::<Counted>(8));
+        assert!(!cache.is_validated::<Counted>(4));
+        assert!(!cache.is_validated::<ArchivedU32>(8));
+        assert_eq!(cache.len(), 1);
+
+        cache.invalidate(0..size_of::<[u32; 4]>());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn failures_are_not_cached() {
+        let mut bytes = AlignedVec::<16>::new();
+        bytes.extend_from_slice(&[2u8]);
+        let cache = ValidationCache::new(&bytes);
+
+        access_pos_cached::<bool, Error>(&cache, 0).unwrap_err();
+        assert!(!cache.is_validated::<bool>(0));
+        access_pos_cached::<bool, Error>(&cache, 0).unwrap_err();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,14 +1,22 @@
 //! Validation implementations and helper types.
 
+#[cfg(feature = "alloc")]
+pub mod cached;
 pub mod util;
 pub mod validators;
 
-use core::{alloc::Layout, any::TypeId, ops::Range};
+use core::{
+    alloc::Layout,
+    any::{type_name, TypeId},
+    fmt,
+    mem::size_of,
+    ops::Range,
+};
 
 use bytecheck::rancor::{Fallible, Source, Strategy};
 use rancor::ResultExt as _;
 
-use crate::LayoutRaw;
+use crate::{fmt::Pointer, LayoutRaw};
 
 /// A context that can validate nonlocal archive memory.
 ///
@@ -53,6 +61,37 @@ pub unsafe trait ArchiveContext<E = <Self as Fallible>::Error> {
         &mut self,
         range: Range<usize>,
     ) -> Result<(), E>;
+
+    /// Returns the offset of the given pointer from the start of the archive,
+    /// or `None` if the start of the archive is not known.
+    ///
+    /// This is only used to report where validation errors occurred.
+    fn archive_offset(&self, ptr: *const u8) -> Option<usize> {
+        let _ = ptr;
+        None
+    }
+
+    /// Returns whether hash maps and hash sets should be checked for duplicate
+    /// keys.
+    ///
+    /// Checking for duplicate keys looks up every key in every hash map, which
+    /// may be skipped when validating trusted archives. Returns `true` by
+    /// default.
+    fn check_duplicate_keys(&self) -> bool {
+        true
+    }
+
+    /// Reports that `bytes` more bytes of the archive have been validated.
+    ///
+    /// Collections call this periodically while checking their elements, so
+    /// that contexts like
+    /// [`BudgetedValidator`](validators::BudgetedValidator) can cancel the
+    /// validation of large archives by returning an error. Does nothing by
+    /// default.
+    fn report_progress(&mut self, bytes: usize) -> Result<(), E> {
+        let _ = bytes;
+        Ok(())
+    }
 }
 
 unsafe impl<T, E> ArchiveContext<E> for Strategy<T, E>
@@ -85,6 +124,91 @@ where
         // has the same safety requirements.
         unsafe { T::pop_subtree_range(self, range) }
     }
+
+    fn archive_offset(&self, ptr: *const u8) -> Option<usize> {
+        T::archive_offset(self, ptr)
+    }
+
+    fn check_duplicate_keys(&self) -> bool {
+        T::check_duplicate_keys(self)
+    }
+
+    fn report_progress(&mut self, bytes: usize) -> Result<(), E> {
+        T::report_progress(self, bytes)
+    }
+}
+
+/// The number of bytes that collections validate between progress reports.
+pub(crate) const PROGRESS_INTERVAL: usize = 64 * 1024;
+
+/// Returns the number of `T`s that collections check between progress
+/// reports.
+pub(crate) const fn progress_batch_len<T>() -> usize {
+    let size = size_of::<T>();
+    if size == 0 || size >= PROGRESS_INTERVAL {
+        1
+    } else {
+        PROGRESS_INTERVAL / size
+    }
+}
+
+/// The location of a subtree which failed validation.
+#[derive(Debug)]
+struct SubtreeTrace {
+    address: usize,
+    offset: Option<usize>,
+    size: usize,
+    type_name: Option<&'static str>,
+}
+
+impl fmt::Display for SubtreeTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "while checking the {} bytes", self.size)?;
+        if let Some(type_name) = self.type_name {
+            write!(f, " of `{}`", type_name)?;
+        }
+        match self.offset {
+            Some(offset) => write!(f, " at offset {:#x}", offset),
+            None => write!(f, " at {}", Pointer(self.address)),
+        }
+    }
+}
+
+/// Checks and pushes the subtree range for `ptr` and `layout`, then calls `f`.
+///
+/// Errors returned from `f` are traced with the location of the subtree and
+/// the name of its type, if it is known.
+fn in_subtree_traced<C, E, R>(
+    context: &mut C,
+    ptr: *const u8,
+    layout: Layout,
+    type_name: Option<&'static str>,
+    f: impl FnOnce(&mut C) -> Result<R, E>,
+) -> Result<R, E>
+where
+    C: ArchiveContext<E> + ?Sized,
+    E: Source,
+{
+    context.check_subtree_ptr(ptr, &layout)?;
+
+    // SAFETY: We checked that the entire range from `ptr` to
+    // `ptr + layout.size()` is located within the buffer.
+    let range =
+        unsafe { context.push_subtree_range(ptr, ptr.add(layout.size()))? };
+
+    let result = f(context).with_trace(|| SubtreeTrace {
+        address: ptr as usize,
+        offset: context.archive_offset(ptr),
+        size: layout.size(),
+        type_name,
+    })?;
+
+    // SAFETY: `range` was returned from `push_subtree_range`.
+    unsafe {
+        context.pop_subtree_range(range)?;
+    }
+
+    Ok(result)
 }
 
 /// Helper methods for [`ArchiveContext`].
@@ -117,21 +241,7 @@ impl<C: ArchiveContext<E> + ?Sized, E: Source> ArchiveContextExt<E> for C {
         layout: Layout,
         f: impl FnOnce(&mut Self) -> Result<R, E>,
     ) -> Result<R, E> {
-        self.check_subtree_ptr(ptr, &layout)?;
-
-        // SAFETY: We checked that the entire range from `ptr` to
-        // `ptr + layout.size()` is located within the buffer.
-        let range =
-            unsafe { self.push_subtree_range(ptr, ptr.add(layout.size()))? };
-
-        let result = f(self)?;
-
-        // SAFETY: `range` was returned from `push_subtree_range`.
-        unsafe {
-            self.pop_subtree_range(range)?;
-        }
-
-        Ok(result)
+        in_subtree_traced(self, ptr, layout, None, f)
     }
 
     #[allow(clippy::not_unsafe_ptr_arg_deref)]
@@ -143,7 +253,7 @@ impl<C: ArchiveContext<E> + ?Sized, E: Source> ArchiveContextExt<E> for C {
         let layout = T::layout_raw(ptr_meta::metadata(ptr)).into_error()?;
         let root = ptr as *const u8;
 
-        self.in_subtree_raw(root, layout, f)
+        in_subtree_traced(self, root, layout, Some(type_name::<T>()), f)
     }
 }
 
@@ -175,7 +285,7 @@ where
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
     use rancor::Failure;
 
@@ -381,4 +491,180 @@ mod tests {
 
         access_pos::<ArchivedNode, Failure>(&*synthetic_buf, 0).unwrap_err();
     }
+
+    #[test]
+    fn error_location() {
+        #[cfg(not(feature = "std"))]
+        use alloc::{format, string::ToString, vec::Vec};
+
+        use rancor::Error;
+
+        use crate::{access_unchecked, to_bytes, Archive, Serialize};
+
+        #[derive(Archive, Serialize)]
+        #[rkyv(crate, check_bytes)]
+        struct User {
+            id: u32,
+            active: bool,
+        }
+
+        #[derive(Archive, Serialize)]
+        #[rkyv(crate, check_bytes)]
+        struct Root {
+            users: Vec<User>,
+        }
+
+        let value = Root {
+            users: (0..4).map(|id| User { id, active: true }).collect(),
+        };
+        let mut bytes = to_bytes::<Error>(&value).unwrap();
+
+        let archived = unsafe { access_unchecked::<ArchivedRoot>(&bytes) };
+        let start = bytes.as_ptr() as usize;
+        let users_offset = archived.users.as_ptr() as usize - start;
+        let active_offset =
+            &archived.users[2].active as *const bool as usize - start;
+        bytes[active_offset] = 2;
+
+        let error = access::<ArchivedRoot, Error>(&bytes)
+            .expect_err("expected invalid bool error")
+            .to_string();
+        assert!(
+            error.contains(&format!("at offset {:#x}", users_offset)),
+            "{}",
+            error,
+        );
+        assert!(error.contains("active"), "{}", error);
+        assert!(error.contains("element at index 2"), "{}", error);
+        assert!(error.contains("ArchivedUser]`"), "{}", error);
+    }
+
+    // This test creates structures too big to fit in 16-bit offsets, and MIRI
+    // can't run it quickly enough.
+    #[cfg(not(any(feature = "pointer_width_16", miri)))]
+    #[test]
+    fn error_location_in_batch() {
+        #[cfg(not(feature = "std"))]
+        use alloc::{format, string::ToString, vec};
+
+        use rancor::Error;
+
+        use crate::{
+            access_unchecked, to_bytes, validation::progress_batch_len,
+            vec::ArchivedVec,
+        };
+
+        // Long enough to be checked in more than one batch
+        let len = 2 * progress_batch_len::<bool>() + 10;
+        let index = progress_batch_len::<bool>() + 5;
+        let mut bytes = to_bytes::<Error>(&vec![true; len]).unwrap();
+
+        let archived = unsafe { access_unchecked::<ArchivedVec<bool>>(&bytes) };
+        let offset = archived.as_ptr() as usize - bytes.as_ptr() as usize;
+        bytes[offset + index] = 2;
+
+        let error = access::<ArchivedVec<bool>, Error>(&bytes)
+            .expect_err("expected invalid bool error")
+            .to_string();
+        assert!(
+            error.contains(&format!("element at index {}", index)),
+            "{}",
+            error,
+        );
+    }
+
+    #[test]
+    fn shared_buffer() {
+        #[cfg(not(feature = "std"))]
+        use alloc::{rc::Rc, string::ToString, vec, vec::Vec};
+        use core::mem::MaybeUninit;
+        #[cfg(feature = "std")]
+        use std::rc::Rc;
+
+        use rancor::Error;
+
+        use crate::{
+            access_with_buffer, to_bytes, validation::validators::SharedEntry,
+        };
+
+        let a = Rc::new(1u32);
+        let b = Rc::new(2u32);
+        let value = vec![a.clone(), b.clone(), a, b];
+        let bytes = to_bytes::<Error>(&value).unwrap();
+
+        // Each distinct shared pointer uses one entry
+        let mut buffer = [MaybeUninit::<SharedEntry>::uninit(); 2];
+        let archived = access_with_buffer::<Archived<Vec<Rc<u32>>>, Error>(
+            &bytes,
+            &mut buffer,
+        )
+        .unwrap();
+        assert_eq!(archived.len(), 4);
+        assert_eq!(archived[2].to_native(), 1);
+        assert_eq!(archived[3].to_native(), 2);
+
+        let mut buffer = [MaybeUninit::<SharedEntry>::uninit(); 1];
+        let error = access_with_buffer::<Archived<Vec<Rc<u32>>>, Error>(
+            &bytes,
+            &mut buffer,
+        )
+        .expect_err("expected buffer exhausted error")
+        .to_string();
+        assert!(
+            error.contains("shared pointer buffer exhausted"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn access_or_copy() {
+        #[cfg(not(feature = "std"))]
+        use alloc::{string::String, vec, vec::Vec};
+
+        use crate::{
+            to_bytes, util::AlignedVec, validation::util::access_or_copy,
+        };
+
+        let value = vec![String::from("a string which is out of line"); 3];
+        let bytes = to_bytes::<Failure>(&value).unwrap();
+        let mut buffer = AlignedVec::<16>::new();
+        buffer.push(0);
+        buffer.extend_from_slice(&bytes);
+
+        // Aligned bytes are accessed in place
+        let mut scratch = AlignedVec::new();
+        let archived = access_or_copy::<Archived<Vec<String>>, Failure>(
+            &bytes,
+            &mut scratch,
+        )
+        .unwrap();
+        assert_eq!(archived, &value);
+        assert!(scratch.is_empty());
+
+        // Misaligned bytes are copied into the scratch space
+        let mut scratch = AlignedVec::new();
+        let archived = access_or_copy::<Archived<Vec<String>>, Failure>(
+            &buffer[1..],
+            &mut scratch,
+        )
+        .unwrap();
+        assert_eq!(archived, &value);
+        assert_eq!(scratch.as_slice(), bytes.as_slice());
+
+        // Invalid bytes fail validation on both paths
+        let mut invalid = AlignedVec::<16>::new();
+        invalid.extend_from_slice(&bytes);
+        let len = invalid.len();
+        invalid[len - 1] ^= 0x80;
+        buffer[len] ^= 0x80;
+        for bytes in [&invalid[..], &buffer[1..]] {
+            let mut scratch = AlignedVec::new();
+            assert!(access_or_copy::<Archived<Vec<String>>, Failure>(
+                bytes,
+                &mut scratch,
+            )
+            .is_err());
+        }
+    }
 }
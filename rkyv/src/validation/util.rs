@@ -1,25 +1,149 @@
 //! Utility methods for accessing and deserializing safely.
 
-use core::{mem::size_of, pin::Pin};
+use core::{
+    fmt,
+    mem::{align_of, size_of, MaybeUninit},
+    pin::Pin,
+};
 
 use bytecheck::CheckBytes;
 use ptr_meta::Pointee;
-use rancor::{Source, Strategy};
+use rancor::{ResultExt as _, Source, Strategy};
 
+#[cfg(not(feature = "alloc"))]
+use crate::validation::validators::DEFAULT_SHARED_ENTRIES;
+#[cfg(feature = "alloc")]
 use crate::{
     de::pooling::Pool,
     deserialize,
+    util::{align_or_copy, AlignedVec},
+    Archive, Deserialize,
+};
+use crate::{
     util::{access_pos_unchecked, access_pos_unchecked_mut},
     validation::{
-        validators::DefaultValidator, ArchiveContext, ArchiveContextExt,
+        validators::{
+            BudgetedValidator, DefaultValidator, SharedEntry,
+            ValidationCancelled, ValidatorWithBuffer,
+        },
+        ArchiveContext, ArchiveContextExt,
     },
-    Archive, Deserialize, Portable,
+    Portable,
 };
 
 fn root_position<T: Portable>(bytes: &[u8]) -> usize {
     bytes.len().saturating_sub(size_of::<T>())
 }
 
+/// Calls `f` with a new [`DefaultValidator`] for the given bytes.
+///
+/// Without `alloc`, the validator tracks shared pointers in a buffer of
+/// [`DEFAULT_SHARED_ENTRIES`] entries on the stack.
+#[inline]
+fn with_default_validator<R>(
+    bytes: &[u8],
+    f: impl FnOnce(DefaultValidator<'_>) -> R,
+) -> R {
+    #[cfg(feature = "alloc")]
+    let validator = DefaultValidator::new(bytes);
+    #[cfg(not(feature = "alloc"))]
+    let mut buffer = [MaybeUninit::uninit(); DEFAULT_SHARED_ENTRIES];
+    #[cfg(not(feature = "alloc"))]
+    let validator = DefaultValidator::new(bytes, &mut buffer);
+    f(validator)
+}
+
+/// An error indicating that a byte slice can't contain an archived root value.
+///
+/// This is returned by [`check_preconditions`], and is also the first error in
+/// the chain when these checks fail during access.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PreconditionError {
+    /// The byte slice is shorter than the root value.
+    TooShort {
+        /// The minimum length of the byte slice.
+        min_len: usize,
+        /// The actual length of the byte slice.
+        len: usize,
+    },
+    /// The root value is not aligned to the alignment of its type.
+    Misaligned {
+        /// The alignment required by the type of the root value.
+        align: usize,
+        /// The address of the root value modulo `align`.
+        modulo: usize,
+    },
+}
+
+impl fmt::Display for PreconditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooShort { min_len, len } => write!(
+                f,
+                "buffer too short: the root value requires at least {} bytes \
+                 but the buffer is only {} bytes long",
+                min_len, len,
+            ),
+            Self::Misaligned { align, modulo } => write!(
+                f,
+                "buffer misaligned: the root value requires an alignment of \
+                 {} but its address modulo {} is {}",
+                align, align, modulo,
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PreconditionError {}
+
+/// Checks the preconditions for accessing an archived `T` at the root position
+/// of the given bytes.
+///
+/// This checks that the bytes are long enough to contain a `T` and that the
+/// root position is aligned for `T`, which are the cheapest checks performed
+/// by [`access`] and the other functions which calculate the root position.
+/// Passing these checks does not mean that the bytes are valid.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     to_bytes,
+///     validation::util::{check_preconditions, PreconditionError},
+///     Archived,
+/// };
+///
+/// let bytes = to_bytes::<Error>(&42u32).unwrap();
+/// assert!(check_preconditions::<Archived<u32>>(&bytes).is_ok());
+///
+/// assert_eq!(
+///     check_preconditions::<Archived<u32>>(&bytes[..2]),
+///     Err(PreconditionError::TooShort { min_len: 4, len: 2 }),
+/// );
+/// ```
+pub fn check_preconditions<T: Portable>(
+    bytes: &[u8],
+) -> Result<(), PreconditionError> {
+    if bytes.len() < size_of::<T>() {
+        return Err(PreconditionError::TooShort {
+            min_len: size_of::<T>(),
+            len: bytes.len(),
+        });
+    }
+
+    let address = bytes.as_ptr() as usize + root_position::<T>(bytes);
+    let modulo = address % align_of::<T>();
+    if modulo != 0 {
+        return Err(PreconditionError::Misaligned {
+            align: align_of::<T>(),
+            modulo,
+        });
+    }
+
+    Ok(())
+}
+
 /// Checks a byte slice for a valid instance of the given archived type at the
 /// given position with the given context.
 pub fn check_pos_with_context<T, C, E>(
@@ -78,6 +202,7 @@ where
     C: ArchiveContext<E> + ?Sized,
     E: Source,
 {
+    check_preconditions::<T>(bytes).into_error()?;
     access_pos_with_context::<T, C, E>(
         bytes,
         root_position::<T>(bytes),
@@ -94,12 +219,13 @@ where
     T: Portable + for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
     E: Source,
 {
-    let mut validator = DefaultValidator::new(bytes);
-    access_pos_with_context::<T, DefaultValidator, E>(
-        bytes,
-        pos,
-        &mut validator,
-    )
+    with_default_validator(bytes, |mut validator| {
+        access_pos_with_context::<T, DefaultValidator, E>(
+            bytes,
+            pos,
+            &mut validator,
+        )
+    })
 }
 
 /// Accesses an archived value from the given byte slice by calculating the root
@@ -139,8 +265,142 @@ where
     T: Portable + for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
     E: Source,
 {
-    let mut validator = DefaultValidator::new(bytes);
-    access_with_context::<T, DefaultValidator, E>(bytes, &mut validator)
+    with_default_validator(bytes, |mut validator| {
+        access_with_context::<T, DefaultValidator, E>(bytes, &mut validator)
+    })
+}
+
+/// Accesses an archived value from the given byte slice by calculating the root
+/// position after checking its validity, stopping early if `budget` runs out.
+///
+/// Roughly every 64 KiB of validated data, `budget` is called with the number
+/// of bytes validated so far. If it returns `false`, validation is cancelled
+/// and a [`ValidationCancelled`] error is returned. Progress is reported by
+/// collections as they check their elements, so a single large element may be
+/// checked before the budget is called again.
+///
+/// See [`BudgetedValidator`] for more details.
+///
+/// # Examples
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// use rkyv::{access_with_budget, rancor::Error, to_bytes, Archived};
+///
+/// let bytes = to_bytes::<Error>(&vec![0u64; 100_000]).unwrap();
+///
+/// // Give up if validation takes longer than a second.
+/// let deadline = Instant::now() + Duration::from_secs(1);
+/// let archived =
+///     access_with_budget::<Archived<Vec<u64>>, Error>(&bytes, &mut |_| {
+///         Instant::now() < deadline
+///     })
+///     .unwrap();
+/// assert_eq!(archived.len(), 100_000);
+///
+/// // Give up after validating the first megabyte.
+/// let result = access_with_budget::<Archived<Vec<u64>>, Error>(
+///     &bytes,
+///     &mut |validated| validated < 1 << 20,
+/// );
+/// assert!(result.is_err());
+/// ```
+pub fn access_with_budget<'a, T, E>(
+    bytes: &'a [u8],
+    budget: &mut impl FnMut(usize) -> bool,
+) -> Result<&'a T, E>
+where
+    T: Portable
+        + for<'b, 'c> CheckBytes<
+            Strategy<BudgetedValidator<'b, DefaultValidator<'c>>, E>,
+        >,
+    E: Source,
+{
+    with_default_validator(bytes, |validator| {
+        let mut validator = BudgetedValidator::new(validator, budget);
+        access_with_context::<T, BudgetedValidator<DefaultValidator>, E>(
+            bytes,
+            &mut validator,
+        )
+    })
+}
+
+/// Accesses an archived value from the given byte slice after checking its
+/// validity, copying the bytes into `scratch` first if they are not aligned.
+///
+/// This is a safe alternative to
+/// [`access_or_copy_unchecked`](crate::util::access_or_copy_unchecked). If the
+/// bytes are aligned to [`AlignedVec::ALIGNMENT`], they are validated and
+/// accessed in place. Otherwise they are copied into `scratch` with
+/// [`align_or_copy`], and the copy is validated and accessed instead.
+///
+/// # Examples
+/// ```
+/// use rkyv::{
+///     rancor::Error, to_bytes, util::AlignedVec,
+///     validation::util::access_or_copy, Archived,
+/// };
+///
+/// let bytes = to_bytes::<Error>(&"hello world".to_string()).unwrap();
+///
+/// // Simulate a buffer which is not aligned
+/// let mut buffer = AlignedVec::<16>::new();
+/// buffer.push(0);
+/// buffer.extend_from_slice(&bytes);
+///
+/// let mut scratch = AlignedVec::new();
+/// let archived =
+///     access_or_copy::<Archived<String>, Error>(&buffer[1..], &mut scratch)
+///         .unwrap();
+/// assert_eq!(archived, "hello world");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn access_or_copy<'a, T, E>(
+    bytes: &'a [u8],
+    scratch: &'a mut AlignedVec,
+) -> Result<&'a T, E>
+where
+    T: Portable + for<'b> CheckBytes<Strategy<DefaultValidator<'b>, E>>,
+    E: Source,
+{
+    access::<T, E>(align_or_copy(bytes, scratch))
+}
+
+/// Accesses an archived value from the given byte slice by calculating the root
+/// position after checking its validity, tracking shared pointers in the given
+/// buffer.
+///
+/// This is an alternative to [`access`] which does not allocate. Validation
+/// fails if the archive contains more distinct shared pointers than there are
+/// entries in `buffer`.
+///
+/// # Examples
+/// ```
+/// use core::mem::MaybeUninit;
+///
+/// use rkyv::{
+///     access_with_buffer, rancor::Error, to_bytes,
+///     validation::validators::SharedEntry, Archived,
+/// };
+///
+/// let bytes = to_bytes::<Error>(&vec![1, 2, 3]).unwrap();
+///
+/// let mut buffer = [MaybeUninit::<SharedEntry>::uninit(); 16];
+/// let archived =
+///     access_with_buffer::<Archived<Vec<i32>>, Error>(&bytes, &mut buffer)
+///         .unwrap();
+/// assert_eq!(archived.len(), 3);
+/// ```
+pub fn access_with_buffer<'a, T, E>(
+    bytes: &'a [u8],
+    buffer: &mut [MaybeUninit<SharedEntry>],
+) -> Result<&'a T, E>
+where
+    T: Portable + for<'b> CheckBytes<Strategy<ValidatorWithBuffer<'b>, E>>,
+    E: Source,
+{
+    let mut validator = ValidatorWithBuffer::new(bytes, buffer);
+    access_with_context::<T, ValidatorWithBuffer, E>(bytes, &mut validator)
 }
 
 // TODO: `Pin` is not technically correct for the return type. `Pin` requires
@@ -184,6 +444,7 @@ where
     C: ArchiveContext<E> + ?Sized,
     E: Source,
 {
+    check_preconditions::<T>(bytes).into_error()?;
     access_pos_with_context_mut::<T, C, E>(
         bytes,
         root_position::<T>(bytes),
@@ -203,8 +464,9 @@ where
     T: Portable + for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
     E: Source,
 {
-    let mut context = DefaultValidator::new(bytes);
-    check_pos_with_context::<T, _, E>(bytes, pos, &mut context)?;
+    with_default_validator(bytes, |mut context| {
+        check_pos_with_context::<T, _, E>(bytes, pos, &mut context)
+    })?;
     unsafe { Ok(access_pos_unchecked_mut::<T>(bytes, pos)) }
 }
 
@@ -219,9 +481,11 @@ where
     T: Portable + for<'a> CheckBytes<Strategy<DefaultValidator<'a>, E>>,
     E: Source,
 {
-    let mut context = DefaultValidator::new(bytes);
+    check_preconditions::<T>(bytes).into_error()?;
     let pos = root_position::<T>(bytes);
-    check_pos_with_context::<T, _, E>(bytes, pos, &mut context)?;
+    with_default_validator(bytes, |mut context| {
+        check_pos_with_context::<T, _, E>(bytes, pos, &mut context)
+    })?;
     unsafe { Ok(access_pos_unchecked_mut::<T>(bytes, pos)) }
 }
 
@@ -250,6 +514,7 @@ where
 ///
 /// assert_eq!(deserialized, value);
 /// ```
+#[cfg(feature = "alloc")]
 pub fn from_bytes<T, E>(bytes: &[u8]) -> Result<T, E>
 where
     T: Archive,
@@ -257,6 +522,171 @@ where
         + Deserialize<T, Strategy<Pool, E>>,
     E: Source,
 {
-    let mut deserializer = Pool::default();
+    let mut deserializer = Pool::for_archive(bytes);
     deserialize(access::<T::Archived, E>(bytes)?, &mut deserializer)
 }
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use core::mem::align_of;
+    use std::collections::{BTreeMap, HashMap};
+
+    use rancor::Error;
+
+    use crate::{
+        access, access_with_budget,
+        primitive::{ArchivedU32, ArchivedU64},
+        string::ArchivedString,
+        to_bytes,
+        util::AlignedVec,
+        validation::{
+            util::{check_preconditions, PreconditionError},
+            PROGRESS_INTERVAL,
+        },
+        Archived,
+    };
+
+    #[test]
+    fn preconditions_too_short() {
+        let bytes = to_bytes::<Error>(&42u32).unwrap();
+
+        assert_eq!(check_preconditions::<ArchivedU32>(&bytes), Ok(()));
+        assert_eq!(
+            check_preconditions::<ArchivedU32>(&bytes[..3]),
+            Err(PreconditionError::TooShort { min_len: 4, len: 3 }),
+        );
+        assert_eq!(
+            check_preconditions::<ArchivedU32>(&[]),
+            Err(PreconditionError::TooShort { min_len: 4, len: 0 }),
+        );
+
+        let error = access::<ArchivedU32, Error>(&bytes[..3]).unwrap_err();
+        assert!(error.to_string().contains("buffer too short"));
+    }
+
+    #[test]
+    fn preconditions_misaligned() {
+        let bytes = to_bytes::<Error>(&42u64).unwrap();
+
+        let mut buffer = AlignedVec::<16>::new();
+        buffer.push(0);
+        buffer.extend_from_slice(&bytes);
+
+        let result = check_preconditions::<ArchivedU64>(&buffer[1..]);
+        if align_of::<ArchivedU64>() == 1 {
+            assert_eq!(result, Ok(()));
+        } else {
+            assert_eq!(
+                result,
+                Err(PreconditionError::Misaligned {
+                    align: align_of::<ArchivedU64>(),
+                    modulo: 1,
+                }),
+            );
+
+            let error = access::<ArchivedU64, Error>(&buffer[1..]).unwrap_err();
+            assert!(error.to_string().contains("buffer misaligned"));
+        }
+    }
+
+    #[test]
+    fn preconditions_do_not_check_contents() {
+        let bytes = to_bytes::<Error>(&"hello world".to_string()).unwrap();
+        let mut corrupted = AlignedVec::<16>::new();
+        corrupted.extend_from_slice(&bytes);
+        let len = corrupted.len();
+        corrupted[len - 4..].fill(0x7f);
+
+        assert_eq!(check_preconditions::<ArchivedString>(&corrupted), Ok(()));
+        assert!(access::<ArchivedString, Error>(&corrupted).is_err());
+    }
+
+    #[test]
+    fn budget_cancels_validation() {
+        const LIMIT: usize = 1 << 20;
+
+        let bytes = to_bytes::<Error>(&vec![0u64; 1_000_000]).unwrap();
+
+        let mut calls = 0;
+        let mut last = 0;
+        let error = access_with_budget::<Archived<Vec<u64>>, Error>(
+            &bytes,
+            &mut |validated| {
+                calls += 1;
+                last = validated;
+                validated < LIMIT
+            },
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("validation cancelled"));
+
+        // Validation stopped soon after the budget ran out, long before the
+        // whole archive was validated.
+        assert!(last >= LIMIT);
+        assert!(last < LIMIT + 2 * PROGRESS_INTERVAL);
+        assert!(calls <= last / PROGRESS_INTERVAL);
+    }
+
+    #[test]
+    fn budget_reports_collection_progress() {
+        type Value = (HashMap<u32, String>, BTreeMap<u32, String>);
+
+        let hash_map = (0..100_000u32)
+            .map(|i| (i, i.to_string()))
+            .collect::<HashMap<_, _>>();
+        let btree_map = (0..100_000u32)
+            .map(|i| (i, format!("a longer string {}", i)))
+            .collect::<BTreeMap<_, _>>();
+        let value: Value = (hash_map, btree_map);
+        let bytes = to_bytes::<Error>(&value).unwrap();
+
+        let mut last = 0;
+        let archived = access_with_budget::<Archived<Value>, Error>(
+            &bytes,
+            &mut |validated| {
+                assert!(validated > last);
+                last = validated;
+                true
+            },
+        )
+        .unwrap();
+        assert_eq!(archived.0.len(), 100_000);
+        assert_eq!(archived.1.len(), 100_000);
+        assert!(last > bytes.len() / 2);
+        assert!(last <= bytes.len());
+
+        // Cancel validation partway through.
+        let error = access_with_budget::<Archived<Value>, Error>(
+            &bytes,
+            &mut |validated| validated < bytes.len() / 4,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("validation cancelled"));
+    }
+
+    #[test]
+    fn budget_cancels_btree_map_validation() {
+        let value = (0..200_000u32)
+            .map(|i| (i, u64::from(i)))
+            .collect::<BTreeMap<_, _>>();
+        let bytes = to_bytes::<Error>(&value).unwrap();
+
+        let mut calls = 0;
+        let mut last = 0;
+        let error = access_with_budget::<Archived<BTreeMap<u32, u64>>, Error>(
+            &bytes,
+            &mut |validated| {
+                calls += 1;
+                last = validated;
+                validated < bytes.len() / 4
+            },
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("validation cancelled"));
+
+        // The nodes report their progress as they are validated, so the
+        // budget ran out before most of the tree was validated.
+        assert!(calls > 1);
+        assert!(last < bytes.len() / 4 + 2 * PROGRESS_INTERVAL);
+    }
+}
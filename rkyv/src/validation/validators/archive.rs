@@ -93,8 +93,10 @@ impl std::error::Error for RangePoppedOutOfOrder {}
 /// A validator that can verify archives with nonlocal memory.
 #[derive(Debug)]
 pub struct ArchiveValidator<'a> {
+    start: usize,
     subtree_range: Range<usize>,
     max_subtree_depth: Option<NonZeroUsize>,
+    check_duplicate_keys: bool,
     _phantom: PhantomData<&'a [u8]>,
 }
 
@@ -124,14 +126,24 @@ impl<'a> ArchiveValidator<'a> {
     ) -> Self {
         let Range { start, end } = bytes.as_ptr_range();
         Self {
+            start: start as usize,
             subtree_range: Range {
                 start: start as usize,
                 end: end as usize,
             },
             max_subtree_depth,
+            check_duplicate_keys: true,
             _phantom: PhantomData,
         }
     }
+
+    /// Sets whether hash maps and hash sets are checked for duplicate keys.
+    ///
+    /// See [`ArchiveContext::check_duplicate_keys`] for more details.
+    #[inline]
+    pub fn set_check_duplicate_keys(&mut self, check: bool) {
+        self.check_duplicate_keys = check;
+    }
 }
 
 unsafe impl<E: Source> ArchiveContext<E> for ArchiveValidator<'_> {
@@ -191,4 +203,12 @@ unsafe impl<E: Source> ArchiveContext<E> for ArchiveValidator<'_> {
         }
         Ok(())
     }
+
+    fn archive_offset(&self, ptr: *const u8) -> Option<usize> {
+        (ptr as usize).checked_sub(self.start)
+    }
+
+    fn check_duplicate_keys(&self) -> bool {
+        self.check_duplicate_keys
+    }
 }
@@ -0,0 +1,149 @@
+//! A validator which can be cancelled partway through validation.
+
+use core::{alloc::Layout, any::TypeId, fmt, ops::Range};
+
+use rancor::{fail, Source};
+
+use crate::validation::{ArchiveContext, SharedContext, PROGRESS_INTERVAL};
+
+/// The error returned when a [`BudgetedValidator`]'s budget cancels
+/// validation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationCancelled {
+    /// The number of bytes which had been validated when validation was
+    /// cancelled.
+    pub validated: usize,
+}
+
+impl fmt::Display for ValidationCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "validation cancelled after validating {} bytes",
+            self.validated,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationCancelled {}
+
+/// A validator which periodically asks a budget whether to keep validating.
+///
+/// Collections report their progress as they check their elements. Once
+/// roughly every 64 KiB, the budget is called with the total number of bytes
+/// validated so far. If it returns `false`, validation fails with a
+/// [`ValidationCancelled`] error. This can be used to enforce a deadline when
+/// validating large untrusted archives.
+///
+/// All other checks are forwarded to the inner validator.
+pub struct BudgetedValidator<'b, C> {
+    inner: C,
+    budget: &'b mut dyn FnMut(usize) -> bool,
+    validated: usize,
+    next_report: usize,
+}
+
+impl<'b, C> BudgetedValidator<'b, C> {
+    /// Wraps a validator with a budget.
+    #[inline]
+    pub fn new(inner: C, budget: &'b mut dyn FnMut(usize) -> bool) -> Self {
+        Self {
+            inner,
+            budget,
+            validated: 0,
+            next_report: PROGRESS_INTERVAL,
+        }
+    }
+
+    /// Returns the number of bytes which have been reported as validated.
+    #[inline]
+    pub fn validated(&self) -> usize {
+        self.validated
+    }
+
+    /// Returns the inner validator.
+    #[inline]
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for BudgetedValidator<'_, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BudgetedValidator")
+            .field("inner", &self.inner)
+            .field("validated", &self.validated)
+            .finish_non_exhaustive()
+    }
+}
+
+unsafe impl<C, E> ArchiveContext<E> for BudgetedValidator<'_, C>
+where
+    C: ArchiveContext<E>,
+    E: Source,
+{
+    fn check_subtree_ptr(
+        &mut self,
+        ptr: *const u8,
+        layout: &Layout,
+    ) -> Result<(), E> {
+        self.inner.check_subtree_ptr(ptr, layout)
+    }
+
+    unsafe fn push_subtree_range(
+        &mut self,
+        root: *const u8,
+        end: *const u8,
+    ) -> Result<Range<usize>, E> {
+        // SAFETY: This just forwards the call to the inner validator, which
+        // has the same safety requirements.
+        unsafe { self.inner.push_subtree_range(root, end) }
+    }
+
+    unsafe fn pop_subtree_range(
+        &mut self,
+        range: Range<usize>,
+    ) -> Result<(), E> {
+        // SAFETY: This just forwards the call to the inner validator, which
+        // has the same safety requirements.
+        unsafe { self.inner.pop_subtree_range(range) }
+    }
+
+    fn archive_offset(&self, ptr: *const u8) -> Option<usize> {
+        <C as ArchiveContext<E>>::archive_offset(&self.inner, ptr)
+    }
+
+    fn check_duplicate_keys(&self) -> bool {
+        <C as ArchiveContext<E>>::check_duplicate_keys(&self.inner)
+    }
+
+    fn report_progress(&mut self, bytes: usize) -> Result<(), E> {
+        self.inner.report_progress(bytes)?;
+
+        self.validated = self.validated.saturating_add(bytes);
+        if self.validated >= self.next_report {
+            if !(self.budget)(self.validated) {
+                fail!(ValidationCancelled {
+                    validated: self.validated,
+                });
+            }
+            self.next_report = self.validated.saturating_add(PROGRESS_INTERVAL);
+        }
+
+        Ok(())
+    }
+}
+
+impl<C, E> SharedContext<E> for BudgetedValidator<'_, C>
+where
+    C: SharedContext<E>,
+{
+    fn register_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Result<bool, E> {
+        self.inner.register_shared_ptr(address, type_id)
+    }
+}
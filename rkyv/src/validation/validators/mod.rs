@@ -1,22 +1,43 @@
 //! Validators that can check archived types.
 
 mod archive;
+mod budget;
 mod shared;
 
-use core::{any::TypeId, ops::Range};
+use core::{any::TypeId, mem::MaybeUninit, ops::Range};
 
 pub use archive::*;
+pub use budget::*;
 pub use shared::*;
 
 use crate::validation::{ArchiveContext, SharedContext};
 
 /// The default validator.
+///
+/// Without the `alloc` feature, this is a [`ValidatorWithBuffer`]. Functions
+/// like [`access`](crate::access) which create their own default validator
+/// then track shared pointers in a buffer of [`DEFAULT_SHARED_ENTRIES`]
+/// entries on the stack.
+#[cfg(not(feature = "alloc"))]
+pub type DefaultValidator<'a> = ValidatorWithBuffer<'a>;
+
+/// The number of distinct shared pointers that the default validator can track
+/// without the `alloc` feature.
+///
+/// Archives with more shared pointers can be validated with a larger buffer
+/// using [`access_with_buffer`](crate::access_with_buffer).
+#[cfg(not(feature = "alloc"))]
+pub const DEFAULT_SHARED_ENTRIES: usize = 32;
+
+/// The default validator.
+#[cfg(feature = "alloc")]
 #[derive(Debug)]
 pub struct DefaultValidator<'a> {
     archive: ArchiveValidator<'a>,
     shared: SharedValidator,
 }
 
+#[cfg(feature = "alloc")]
 impl<'a> DefaultValidator<'a> {
     /// Creates a new validator from a byte range.
     #[inline]
@@ -35,8 +56,17 @@ impl<'a> DefaultValidator<'a> {
             shared: SharedValidator::with_capacity(capacity),
         }
     }
+
+    /// Sets whether hash maps and hash sets are checked for duplicate keys.
+    ///
+    /// See [`ArchiveContext::check_duplicate_keys`] for more details.
+    #[inline]
+    pub fn set_check_duplicate_keys(&mut self, check: bool) {
+        self.archive.set_check_duplicate_keys(check);
+    }
 }
 
+#[cfg(feature = "alloc")]
 unsafe impl<'a, E> ArchiveContext<E> for DefaultValidator<'a>
 where
     ArchiveValidator<'a>: ArchiveContext<E>,
@@ -67,8 +97,26 @@ where
         // `ArchiveValidator`, which has the same safety requirements.
         unsafe { self.archive.pop_subtree_range(range) }
     }
+
+    fn archive_offset(&self, ptr: *const u8) -> Option<usize> {
+        <ArchiveValidator<'a> as ArchiveContext<E>>::archive_offset(
+            &self.archive,
+            ptr,
+        )
+    }
+
+    fn check_duplicate_keys(&self) -> bool {
+        <ArchiveValidator<'a> as ArchiveContext<E>>::check_duplicate_keys(
+            &self.archive,
+        )
+    }
+
+    fn report_progress(&mut self, bytes: usize) -> Result<(), E> {
+        self.archive.report_progress(bytes)
+    }
 }
 
+#[cfg(feature = "alloc")]
 impl<E> SharedContext<E> for DefaultValidator<'_>
 where
     SharedValidator: SharedContext<E>,
@@ -81,3 +129,126 @@ where
         self.shared.register_shared_ptr(address, type_id)
     }
 }
+
+/// A validator which tracks shared pointers in a caller-provided buffer.
+///
+/// This validator does not allocate, and so can be used to validate archives
+/// in environments without `alloc`. Validation fails if the archive contains
+/// more distinct shared pointers than there are entries in the buffer.
+///
+/// # Examples
+///
+/// ```
+/// use core::mem::MaybeUninit;
+///
+/// use rkyv::{
+///     rancor::Error,
+///     to_bytes,
+///     validation::{
+///         util::access_with_context,
+///         validators::{SharedEntry, ValidatorWithBuffer},
+///     },
+///     Archived,
+/// };
+///
+/// let bytes = to_bytes::<Error>(&vec![1, 2, 3]).unwrap();
+///
+/// let mut buffer = [MaybeUninit::<SharedEntry>::uninit(); 16];
+/// let mut validator = ValidatorWithBuffer::new(&bytes, &mut buffer);
+/// let archived = access_with_context::<Archived<Vec<i32>>, _, Error>(
+///     &bytes,
+///     &mut validator,
+/// )
+/// .unwrap();
+/// assert_eq!(archived.len(), 3);
+/// ```
+#[derive(Debug)]
+pub struct ValidatorWithBuffer<'a> {
+    archive: ArchiveValidator<'a>,
+    shared: BufferSharedValidator<'a>,
+}
+
+impl<'a> ValidatorWithBuffer<'a> {
+    /// Creates a new validator from a byte range and a buffer to track shared
+    /// pointers in.
+    #[inline]
+    pub fn new(
+        bytes: &'a [u8],
+        buffer: &'a mut [MaybeUninit<SharedEntry>],
+    ) -> Self {
+        Self {
+            archive: ArchiveValidator::new(bytes),
+            shared: BufferSharedValidator::new(buffer),
+        }
+    }
+
+    /// Sets whether hash maps and hash sets are checked for duplicate keys.
+    ///
+    /// See [`ArchiveContext::check_duplicate_keys`] for more details.
+    #[inline]
+    pub fn set_check_duplicate_keys(&mut self, check: bool) {
+        self.archive.set_check_duplicate_keys(check);
+    }
+}
+
+unsafe impl<'a, E> ArchiveContext<E> for ValidatorWithBuffer<'a>
+where
+    ArchiveValidator<'a>: ArchiveContext<E>,
+{
+    fn check_subtree_ptr(
+        &mut self,
+        ptr: *const u8,
+        layout: &core::alloc::Layout,
+    ) -> Result<(), E> {
+        self.archive.check_subtree_ptr(ptr, layout)
+    }
+
+    unsafe fn push_subtree_range(
+        &mut self,
+        root: *const u8,
+        end: *const u8,
+    ) -> Result<Range<usize>, E> {
+        // SAFETY: This just forwards the call to the underlying
+        // `ArchiveValidator`, which has the same safety requirements.
+        unsafe { self.archive.push_subtree_range(root, end) }
+    }
+
+    unsafe fn pop_subtree_range(
+        &mut self,
+        range: Range<usize>,
+    ) -> Result<(), E> {
+        // SAFETY: This just forwards the call to the underlying
+        // `ArchiveValidator`, which has the same safety requirements.
+        unsafe { self.archive.pop_subtree_range(range) }
+    }
+
+    fn archive_offset(&self, ptr: *const u8) -> Option<usize> {
+        <ArchiveValidator<'a> as ArchiveContext<E>>::archive_offset(
+            &self.archive,
+            ptr,
+        )
+    }
+
+    fn check_duplicate_keys(&self) -> bool {
+        <ArchiveValidator<'a> as ArchiveContext<E>>::check_duplicate_keys(
+            &self.archive,
+        )
+    }
+
+    fn report_progress(&mut self, bytes: usize) -> Result<(), E> {
+        self.archive.report_progress(bytes)
+    }
+}
+
+impl<'a, E> SharedContext<E> for ValidatorWithBuffer<'a>
+where
+    BufferSharedValidator<'a>: SharedContext<E>,
+{
+    fn register_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Result<bool, E> {
+        self.shared.register_shared_ptr(address, type_id)
+    }
+}
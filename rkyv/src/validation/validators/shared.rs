@@ -1,11 +1,11 @@
 //! Validators add validation capabilities by wrapping and extending basic
 //! validators.
 
-use core::{any::TypeId, fmt};
+use core::{any::TypeId, fmt, mem::MaybeUninit};
 #[cfg(feature = "std")]
 use std::collections::HashMap;
 
-#[cfg(not(feature = "std"))]
+#[cfg(all(feature = "alloc", not(feature = "std")))]
 use hashbrown::HashMap;
 use rancor::{fail, Source};
 
@@ -46,11 +46,13 @@ impl std::error::Error for SharedError {
 }
 
 /// A validator that can verify shared memory.
+#[cfg(feature = "alloc")]
 #[derive(Debug, Default)]
 pub struct SharedValidator {
     shared: HashMap<usize, TypeId>,
 }
 
+#[cfg(feature = "alloc")]
 impl SharedValidator {
     /// Wraps the given context and adds shared memory validation.
     #[inline]
@@ -69,6 +71,7 @@ impl SharedValidator {
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<E: Source> SharedContext<E> for SharedValidator {
     fn register_shared_ptr(
         &mut self,
@@ -100,3 +103,87 @@ impl<E: Source> SharedContext<E> for SharedValidator {
         }
     }
 }
+
+#[derive(Debug)]
+struct SharedBufferExhausted {
+    capacity: usize,
+}
+
+impl fmt::Display for SharedBufferExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "shared pointer buffer exhausted: all {} entries are in use",
+            self.capacity,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SharedBufferExhausted {}
+
+/// A shared pointer tracked by a [`BufferSharedValidator`].
+#[derive(Clone, Copy, Debug)]
+pub struct SharedEntry {
+    address: usize,
+    type_id: TypeId,
+}
+
+/// A validator that can verify shared memory using a fixed-capacity buffer.
+///
+/// Each distinct shared pointer occupies one entry of the buffer. Validation
+/// fails if the archive contains more distinct shared pointers than there are
+/// entries in the buffer.
+#[derive(Debug)]
+pub struct BufferSharedValidator<'a> {
+    entries: &'a mut [MaybeUninit<SharedEntry>],
+    len: usize,
+}
+
+impl<'a> BufferSharedValidator<'a> {
+    /// Creates a new shared memory validator which tracks shared pointers in
+    /// the given buffer.
+    #[inline]
+    pub fn new(entries: &'a mut [MaybeUninit<SharedEntry>]) -> Self {
+        Self { entries, len: 0 }
+    }
+
+    /// Returns the number of shared pointers that can be tracked.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl<E: Source> SharedContext<E> for BufferSharedValidator<'_> {
+    fn register_shared_ptr(
+        &mut self,
+        address: usize,
+        type_id: TypeId,
+    ) -> Result<bool, E> {
+        for entry in self.entries[..self.len].iter() {
+            // SAFETY: The first `len` entries are always initialized.
+            let entry = unsafe { entry.assume_init_ref() };
+            if entry.address == address {
+                if entry.type_id != type_id {
+                    fail!(SharedError::TypeMismatch {
+                        previous: entry.type_id,
+                        current: type_id,
+                    });
+                }
+                return Ok(false);
+            }
+        }
+
+        if self.len == self.entries.len() {
+            fail!(SharedBufferExhausted {
+                capacity: self.entries.len(),
+            });
+        }
+
+        self.entries[self.len] =
+            MaybeUninit::new(SharedEntry { address, type_id });
+        self.len += 1;
+        Ok(true)
+    }
+}
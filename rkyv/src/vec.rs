@@ -3,17 +3,23 @@
 use core::{
     borrow::Borrow,
     cmp, fmt, hash,
-    ops::{Deref, Index, IndexMut},
+    iter::Peekable,
+    marker::PhantomData,
+    mem,
+    ops::{Deref, Index, IndexMut, Range},
     pin::Pin,
-    slice::SliceIndex,
+    slice::{self, SliceIndex},
 };
 
 use munge::munge;
-use rancor::Fallible;
+use rancor::{fail, Fallible, Source};
 
 use crate::{
-    primitive::ArchivedUsize,
+    collections::btree_map::LookupOrd,
+    hash::LookupKey,
+    primitive::{usize_from_archived, ArchivedUsize},
     ser::{Allocator, Writer, WriterExt as _},
+    util::SerVec,
     Archive, Place, Portable, RelPtr, Serialize, SerializeUnsized,
 };
 
@@ -42,20 +48,40 @@ impl<T> ArchivedVec<T> {
     }
 
     /// Returns the number of elements in the archived vec.
-    pub fn len(&self) -> usize {
-        self.len.to_native() as usize
+    pub const fn len(&self) -> usize {
+        usize_from_archived(self.len)
     }
 
     /// Returns whether the archived vec is empty.
-    pub fn is_empty(&self) -> bool {
+    pub const fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
+    /// Returns whether the elements of the archived vec start at an address
+    /// which is a multiple of `align`.
+    ///
+    /// Elements serialized with [`AlignElements`](crate::with::AlignElements)
+    /// are only aligned in memory if the archive buffer itself is aligned to at
+    /// least as many bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is zero.
+    pub fn is_aligned_to(&self, align: usize) -> bool {
+        self.as_ptr() as usize % align == 0
+    }
+
     /// Gets the elements of the archived vec as a slice.
     pub fn as_slice(&self) -> &[T] {
         unsafe { core::slice::from_raw_parts(self.as_ptr(), self.len()) }
     }
 
+    /// Gets the elements in the given range of the archived vec, or `None` if
+    /// the range is out of bounds.
+    pub fn get_range(&self, range: Range<usize>) -> Option<&[T]> {
+        self.as_slice().get(range)
+    }
+
     /// Gets the elements of the archived vec as a pinned mutable slice.
     pub fn pin_mut_slice(self: Pin<&mut Self>) -> Pin<&mut [T]> {
         let len = self.len();
@@ -68,6 +94,40 @@ impl<T> ArchivedVec<T> {
         }
     }
 
+    /// Calls `f` with each element of the archived vec as a pinned mutable
+    /// reference.
+    ///
+    /// This updates every element in place without projecting the pin to each
+    /// element by hand. `f` can mutate elements through their pinned methods,
+    /// and can overwrite elements which are `Unpin` entirely.
+    pub fn for_each_mut<F>(self: Pin<&mut Self>, mut f: F)
+    where
+        F: FnMut(Pin<&mut T>),
+    {
+        // SAFETY: The elements are never moved out of the slice.
+        let slice = unsafe { self.pin_mut_slice().get_unchecked_mut() };
+        for element in slice {
+            // SAFETY: The elements of a pinned slice are structurally pinned.
+            f(unsafe { Pin::new_unchecked(element) });
+        }
+    }
+
+    /// Overwrites every element of the archived vec with a clone of `value`.
+    ///
+    /// Only `Unpin` elements can be overwritten wholesale. Archived types which
+    /// contain relative pointers (like archived strings, boxes, and vecs) are
+    /// `!Unpin`, since a relative pointer is invalidated when it's moved to a
+    /// different position. Archived primitives and derived archived types made
+    /// only of them are `Unpin`, and their bytes don't refer to any other part
+    /// of the archive. Use [`for_each_mut`](Self::for_each_mut) to update
+    /// elements which aren't `Unpin`.
+    pub fn fill(self: Pin<&mut Self>, value: T)
+    where
+        T: Clone + Unpin,
+    {
+        self.pin_mut_slice().get_mut().fill(value);
+    }
+
     // This method can go away once pinned slices have indexing support
     // https://github.com/rust-lang/rust/pull/78370
 
@@ -104,13 +164,19 @@ impl<T> ArchivedVec<T> {
     }
 
     /// Serializes an archived `Vec` from a given slice.
-    pub fn serialize_from_slice<
-        U: Serialize<S, Archived = T>,
-        S: Fallible + Allocator + Writer + ?Sized,
-    >(
+    pub fn serialize_from_slice<U, S>(
         slice: &[U],
         serializer: &mut S,
-    ) -> Result<VecResolver, S::Error> {
+    ) -> Result<VecResolver, S::Error>
+    where
+        U: Serialize<S, Archived = T>,
+        S: Fallible + Allocator + Writer + ?Sized,
+        S::Error: Source,
+    {
+        let Some(size) = slice.len().checked_mul(mem::size_of::<T>()) else {
+            fail!(SliceTooLarge { len: slice.len() });
+        };
+        serializer.reserve_hint(size);
         Ok(VecResolver {
             pos: slice.serialize_unsized(serializer)?,
         })
@@ -118,6 +184,12 @@ impl<T> ArchivedVec<T> {
 
     /// Serializes an archived `Vec` from a given iterator.
     ///
+    /// The items and their resolvers are buffered in the serializer's scratch
+    /// space until all of them have been serialized, so the iterator does not
+    /// need to know its length in advance and is only iterated once. The
+    /// archived `Vec` must be resolved with the number of items that the
+    /// iterator yielded.
+    ///
     /// This method is unable to perform copy optimizations; prefer
     /// [`serialize_from_slice`](ArchivedVec::serialize_from_slice) when
     /// possible.
@@ -127,31 +199,13 @@ impl<T> ArchivedVec<T> {
     ) -> Result<VecResolver, S::Error>
     where
         U: Serialize<S, Archived = T>,
-        I: ExactSizeIterator + Clone,
+        I: IntoIterator,
         I::Item: Borrow<U>,
         S: Fallible + Allocator + Writer + ?Sized,
     {
-        use crate::util::SerVec;
-
-        SerVec::with_capacity(
-            serializer,
-            iter.len(),
-            |resolvers, serializer| {
-                for value in iter.clone() {
-                    let resolver = value.borrow().serialize(serializer)?;
-                    resolvers.push(resolver);
-                }
-
-                let pos = serializer.align_for::<T>()?;
-                for (value, resolver) in iter.zip(resolvers.drain()) {
-                    unsafe {
-                        serializer.resolve_aligned(value.borrow(), resolver)?;
-                    }
-                }
-
-                Ok(VecResolver { pos })
-            },
-        )?
+        let mut iter = iter.into_iter().peekable();
+        let capacity = cmp::max(iter.size_hint().0, MIN_CHUNK_CAPACITY);
+        serialize_chunks::<U, _, _>(&mut iter, capacity, None, serializer)
     }
 
     /// Serializes an archived `Vec` from a given iterator. Compared to
@@ -185,6 +239,111 @@ impl<T> ArchivedVec<T> {
     }
 }
 
+/// An error which occurs when the archived elements of a slice would be too
+/// large to fit in memory.
+#[derive(Debug)]
+struct SliceTooLarge {
+    len: usize,
+}
+
+impl fmt::Display for SliceTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the archived elements of a slice with {} items are too large",
+            self.len,
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SliceTooLarge {}
+
+/// The minimum number of items buffered by each chunk of
+/// [`ArchivedVec::serialize_from_iter`].
+const MIN_CHUNK_CAPACITY: usize = 8;
+
+/// The serialized items of [`ArchivedVec::serialize_from_iter`] which are
+/// waiting to be resolved.
+trait Buffered<S: Fallible + ?Sized> {
+    /// Resolves all of the buffered items in order.
+    fn resolve(&mut self, serializer: &mut S) -> Result<(), S::Error>;
+}
+
+/// A chunk of buffered items, along with all of the chunks before it.
+struct Chunk<'a, 'b, U: Archive, B, S: Fallible + ?Sized> {
+    entries: &'a mut SerVec<(B, U::Resolver)>,
+    prev: Option<&'a mut (dyn Buffered<S> + 'b)>,
+    _phantom: PhantomData<U>,
+}
+
+impl<U, B, S> Buffered<S> for Chunk<'_, '_, U, B, S>
+where
+    U: Archive,
+    B: Borrow<U>,
+    S: Fallible + Writer + ?Sized,
+{
+    fn resolve(&mut self, serializer: &mut S) -> Result<(), S::Error> {
+        if let Some(prev) = self.prev.as_mut() {
+            prev.resolve(serializer)?;
+        }
+        for (item, resolver) in self.entries.drain() {
+            unsafe {
+                serializer.resolve_aligned(item.borrow(), resolver)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Serializes items from `iter` into a chunk with the given capacity. When the
+/// chunk fills up, the remaining items are serialized into a new chunk with
+/// twice the capacity. Once the iterator is exhausted, all of the chunks are
+/// resolved in order.
+///
+/// Chunks are allocated from the serializer's scratch space, which is freed in
+/// reverse order, so each chunk lives on the stack frame of a recursive call.
+fn serialize_chunks<U, I, S>(
+    iter: &mut Peekable<I>,
+    capacity: usize,
+    prev: Option<&mut dyn Buffered<S>>,
+    serializer: &mut S,
+) -> Result<VecResolver, S::Error>
+where
+    U: Serialize<S>,
+    I: Iterator,
+    I::Item: Borrow<U>,
+    S: Fallible + Allocator + Writer + ?Sized,
+{
+    SerVec::with_capacity(serializer, capacity, |entries, serializer| {
+        while entries.len() < capacity {
+            let Some(item) = iter.next() else {
+                break;
+            };
+            let resolver = item.borrow().serialize(serializer)?;
+            entries.push((item, resolver));
+        }
+
+        let mut chunk = Chunk::<U, I::Item, S> {
+            entries,
+            prev,
+            _phantom: PhantomData,
+        };
+        if iter.peek().is_some() {
+            serialize_chunks::<U, I, S>(
+                iter,
+                capacity.saturating_mul(2),
+                Some(&mut chunk),
+                serializer,
+            )
+        } else {
+            let pos = serializer.align_for::<U::Archived>()?;
+            chunk.resolve(serializer)?;
+            Ok(VecResolver { pos })
+        }
+    })?
+}
+
 impl<T> AsRef<[T]> for ArchivedVec<T> {
     fn as_ref(&self) -> &[T] {
         self.as_slice()
@@ -214,11 +373,33 @@ impl<T> Deref for ArchivedVec<T> {
 impl<T: Eq> Eq for ArchivedVec<T> {}
 
 impl<T: hash::Hash> hash::Hash for ArchivedVec<T> {
+    #[inline]
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
         self.as_slice().hash(state)
     }
 }
 
+impl<T: LookupKey<U>, U> LookupKey<ArchivedVec<U>> for [T] {
+    #[inline]
+    fn equivalent(&self, key: &ArchivedVec<U>) -> bool {
+        self.len() == key.len()
+            && self.iter().zip(key.iter()).all(|(a, b)| a.equivalent(b))
+    }
+}
+
+impl<T: LookupOrd<U>, U> LookupOrd<ArchivedVec<U>> for [T] {
+    #[inline]
+    fn compare(&self, key: &ArchivedVec<U>) -> cmp::Ordering {
+        for (a, b) in self.iter().zip(key.iter()) {
+            match a.compare(b) {
+                cmp::Ordering::Equal => continue,
+                result => return result,
+            }
+        }
+        self.len().cmp(&key.len())
+    }
+}
+
 impl<T, I: SliceIndex<[T]>> Index<I> for ArchivedVec<T> {
     type Output = <[T] as Index<I>>::Output;
 
@@ -227,19 +408,31 @@ impl<T, I: SliceIndex<[T]>> Index<I> for ArchivedVec<T> {
     }
 }
 
+impl<'a, T> IntoIterator for &'a ArchivedVec<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl<T: Ord> Ord for ArchivedVec<T> {
+    #[inline]
     fn cmp(&self, other: &Self) -> cmp::Ordering {
         self.as_slice().cmp(other.as_slice())
     }
 }
 
 impl<T: PartialEq<U>, U> PartialEq<ArchivedVec<U>> for ArchivedVec<T> {
+    #[inline]
     fn eq(&self, other: &ArchivedVec<U>) -> bool {
         self.as_slice().eq(other.as_slice())
     }
 }
 
 impl<T: PartialEq<U>, U, const N: usize> PartialEq<[U; N]> for ArchivedVec<T> {
+    #[inline]
     fn eq(&self, other: &[U; N]) -> bool {
         self.as_slice().eq(&other[..])
     }
@@ -252,12 +445,14 @@ impl<T: PartialEq<U>, U, const N: usize> PartialEq<ArchivedVec<T>> for [U; N] {
 }
 
 impl<T: PartialEq<U>, U> PartialEq<[U]> for ArchivedVec<T> {
+    #[inline]
     fn eq(&self, other: &[U]) -> bool {
         self.as_slice().eq(other)
     }
 }
 
 impl<T: PartialEq<U>, U> PartialEq<ArchivedVec<U>> for [T] {
+    #[inline]
     fn eq(&self, other: &ArchivedVec<U>) -> bool {
         self.eq(other.as_slice())
     }
@@ -296,16 +491,59 @@ impl VecResolver {
 
 #[cfg(feature = "bytecheck")]
 mod verify {
+    use core::{fmt, mem::size_of};
+
     use bytecheck::{
         rancor::{Fallible, Source},
         CheckBytes, Verify,
     };
+    use rancor::ResultExt as _;
 
     use crate::{
-        validation::{ArchiveContext, ArchiveContextExt},
+        primitive::native_len,
+        validation::{progress_batch_len, ArchiveContext, ArchiveContextExt},
         vec::ArchivedVec,
     };
 
+    #[derive(Debug)]
+    struct ElementTrace {
+        index: usize,
+    }
+
+    impl fmt::Display for ElementTrace {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "while checking the element at index {}", self.index)
+        }
+    }
+
+    /// Checks `count` elements starting at the element `start` of `ptr`.
+    ///
+    /// Errors are traced with the index of the element in the whole vec,
+    /// rather than its index in the checked range.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must point to at least `start + count` properly aligned `T`s.
+    unsafe fn check_elements<T, C>(
+        ptr: *const T,
+        start: usize,
+        count: usize,
+        context: &mut C,
+    ) -> Result<(), C::Error>
+    where
+        T: CheckBytes<C>,
+        C: Fallible + ?Sized,
+        C::Error: Source,
+    {
+        for index in start..start + count {
+            // SAFETY: The caller has guaranteed that `ptr` points to at least
+            // `start + count` properly aligned `T`s.
+            unsafe { T::check_bytes(ptr.add(index), context) }
+                .with_trace(|| ElementTrace { index })?;
+        }
+        Ok(())
+    }
+
     unsafe impl<T, C> Verify<C> for ArchivedVec<T>
     where
         T: CheckBytes<C>,
@@ -313,13 +551,40 @@ mod verify {
         C::Error: Source,
     {
         fn verify(&self, context: &mut C) -> Result<(), C::Error> {
+            let len = native_len(self.len)?;
             let ptr = core::ptr::slice_from_raw_parts(
                 self.ptr.as_ptr_wrapping(),
-                self.len.to_native() as usize,
+                len,
             );
 
             context.in_subtree(ptr, |context| unsafe {
-                <[T]>::check_bytes(ptr, context)
+                if core::mem::size_of::<T>() == 0 {
+                    // Every element of a slice of zero-sized types is located
+                    // at the same address and has no bytes, so they are all
+                    // valid if the first one is. Checking only the first one
+                    // keeps archives which claim huge lengths from taking
+                    // forever to validate.
+                    if len > 0 {
+                        T::check_bytes(ptr.cast::<T>(), context)?;
+                    }
+                    Ok(())
+                } else if len <= progress_batch_len::<T>() {
+                    check_elements(ptr.cast::<T>(), 0, len, context)?;
+                    context.report_progress(len * size_of::<T>())
+                } else {
+                    // Check the elements in batches and report progress after
+                    // each one, so that validating a huge vec can be cancelled
+                    // partway through.
+                    let batch_len = progress_batch_len::<T>();
+                    let mut start = 0;
+                    while start < len {
+                        let count = usize::min(batch_len, len - start);
+                        check_elements(ptr.cast::<T>(), start, count, context)?;
+                        context.report_progress(count * size_of::<T>())?;
+                        start += count;
+                    }
+                    Ok(())
+                }
             })
         }
     }
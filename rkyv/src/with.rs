@@ -1,15 +1,17 @@
 //! Wrapper type support and commonly used wrappers.
 //!
-//! Wrappers can be applied with the `#[with(...)]` attribute in the
-//! [`Archive`](macro@crate::Archive) macro.
+//! Wrappers can be applied with the `#[with(...)]` or `#[rkyv(with = ...)]`
+//! attributes in the [`Archive`](macro@crate::Archive) macro.
 
 // mod impls;
 
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+use alloc::boxed::Box;
 use core::marker::PhantomData;
 
 use rancor::Fallible;
 
-use crate::{Place, Portable};
+use crate::{nullable::Packed, Place, Portable};
 
 /// A variant of [`Archive`](crate::Archive) that works with wrappers.
 ///
@@ -319,6 +321,28 @@ pub struct InlineAsBox;
 #[derive(Debug)]
 pub struct AsString;
 
+/// A wrapper that archives a `String` inline with a fixed capacity of `N`
+/// bytes.
+///
+/// The string is archived as an
+/// [`ArchivedInlineString`](crate::inline::ArchivedInlineString), which stores
+/// its bytes without a relative pointer. `N` may be at most 255, and
+/// serialization fails if the string is longer than `N` bytes.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{with::AsInlineString, Archive};
+///
+/// #[derive(Archive)]
+/// struct Sensor {
+///     #[with(AsInlineString<16>)]
+///     id: String,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsInlineString<const N: usize>;
+
 /// A wrapper that locks a lock and serializes the value immutably.
 ///
 /// This wrapper can panic under very specific circumstances when:
@@ -394,6 +418,71 @@ pub struct AsOwned;
 #[derive(Debug)]
 pub struct AsVec;
 
+/// A wrapper that archives a `HashMap`, `BTreeMap`, or `Vec` of key-value pairs
+/// as an [`ArchivedHashMap`](crate::collections::swiss_table::ArchivedHashMap).
+///
+/// By default, a `HashMap` archives as an `ArchivedHashMap` and a `BTreeMap`
+/// archives as an [`ArchivedBTreeMap`]. This wrapper archives either one as a
+/// hash map, which has faster point lookups than a B-tree map. The archived
+/// map deserializes back into the type of the field.
+///
+/// A `Vec` of pairs, like the rows returned by a query, is archived as a hash
+/// map without collecting it into a map first. Its keys must be unique, and it
+/// deserializes back into a `Vec` in the order of the archived map.
+/// Serializing a `Vec` with a duplicate key fails. To archive borrowed rows
+/// like `&[(&str, &T)]`, use
+/// the `serialize_from_borrowed_iter` method of [`ArchivedHashMap`] with a
+/// [`StrAdapter`] instead.
+///
+/// [`ArchivedHashMap`]: crate::collections::swiss_table::ArchivedHashMap
+/// [`StrAdapter`]: crate::string::StrAdapter
+/// [`ArchivedBTreeMap`]: crate::collections::btree_map::ArchivedBTreeMap
+///
+/// # Example
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use rkyv::{with::AsHashMap, Archive};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(AsHashMap)]
+///     values: BTreeMap<String, u32>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsHashMap;
+
+/// A wrapper that archives a `HashMap` or `BTreeMap` as an
+/// [`ArchivedBTreeMap`](crate::collections::btree_map::ArchivedBTreeMap).
+///
+/// By default, a `HashMap` archives as an
+/// [`ArchivedHashMap`](crate::collections::swiss_table::ArchivedHashMap) and a
+/// `BTreeMap` archives as an `ArchivedBTreeMap`. This wrapper archives either
+/// one as a B-tree map, which iterates in order by key and is smaller than a
+/// hash map for large keys. The archived map deserializes back into the type
+/// of the field.
+///
+/// Serializing a `HashMap` with this wrapper sorts its entries in scratch
+/// space first, so the keys must implement `Ord`.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use rkyv::{with::AsBTreeMap, Archive};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(AsBTreeMap)]
+///     values: HashMap<String, u32>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsBTreeMap;
+
 /// A wrapper that niches some type combinations.
 ///
 /// A common type combination is `Option<Box<T>>`. By using a null pointer, the
@@ -425,6 +514,90 @@ pub struct AsVec;
 #[derive(Debug)]
 pub struct Niche;
 
+/// A wrapper that archives a fieldless enum as its discriminant.
+///
+/// The enum must implement [`ToDiscriminant<Repr>`], which is implemented by
+/// deriving `Archive` with `#[rkyv(as_discriminant)]`. `Repr` may be `u8`,
+/// `u16`, or `u32`, and the field is archived as an
+/// [`ArchivedDiscriminant`](crate::discriminant::ArchivedDiscriminant) with
+/// the same layout as `Archived<Repr>`. Validation fails if the discriminant
+/// does not belong to any variant.
+///
+/// [`ToDiscriminant<Repr>`]: crate::discriminant::ToDiscriminant
+///
+/// # Example
+///
+/// ```
+/// use core::mem::size_of;
+///
+/// use rkyv::{
+///     discriminant::{ToDiscriminant, UnknownDiscriminant},
+///     with::AsDiscriminant,
+///     Archive, Archived,
+/// };
+///
+/// #[derive(Clone, Copy)]
+/// enum Level {
+///     Low = 1,
+///     High = 8,
+/// }
+///
+/// impl TryFrom<u32> for Level {
+///     type Error = UnknownDiscriminant;
+///
+///     fn try_from(value: u32) -> Result<Self, Self::Error> {
+///         match value {
+///             1 => Ok(Level::Low),
+///             8 => Ok(Level::High),
+///             _ => Err(UnknownDiscriminant::new("Level", value)),
+///         }
+///     }
+/// }
+///
+/// impl ToDiscriminant<u32> for Level {
+///     fn to_discriminant(&self) -> u32 {
+///         *self as u32
+///     }
+/// }
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(AsDiscriminant<u32>)]
+///     level: Level,
+/// }
+///
+/// assert_eq!(size_of::<Archived<Example>>(), 4);
+/// ```
+#[derive(Debug)]
+pub struct AsDiscriminant<Repr = u8> {
+    _phantom: PhantomData<Repr>,
+}
+
+/// A wrapper that archives an unsigned integer which must be between `MIN` and
+/// `MAX` inclusive.
+///
+/// The field is archived as an
+/// [`ArchivedBounded`](crate::bounded::ArchivedBounded) with the same layout
+/// as the archived integer, and validation fails if the archived integer is
+/// out of bounds. Serializing an integer which is out of bounds panics in
+/// debug builds. This wrapper may be used on `u8`, `u16`, `u32`, `u64`, and
+/// `u128` fields.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{rancor::Error, with::Bounded, Archive, Serialize};
+///
+/// #[derive(Archive, Serialize)]
+/// struct PortNumber(#[rkyv(with = Bounded<1, 49151>)] u16);
+///
+/// let bytes = rkyv::to_bytes::<Error>(&PortNumber(8080)).unwrap();
+/// let archived = rkyv::access::<ArchivedPortNumber, Error>(&bytes).unwrap();
+/// assert_eq!(archived.0.get(), 8080);
+/// ```
+#[derive(Debug)]
+pub struct Bounded<const MIN: u128, const MAX: u128>;
+
 /// A wrapper that converts a [`SystemTime`](::std::time::SystemTime) to a
 /// [`Duration`](::std::time::Duration) since
 /// [`UNIX_EPOCH`](::std::time::UNIX_EPOCH).
@@ -500,10 +673,467 @@ pub struct Unsafe;
 #[derive(Debug)]
 pub struct Skip;
 
+/// A wrapper that archives a field with its own [`Archive`](crate::Archive)
+/// implementation.
+///
+/// This is the default wrapper for wrappers which take another wrapper as a
+/// parameter, like [`DefaultOnNone`].
+#[derive(Debug)]
+pub struct Identity;
+
+/// A value used in place of a missing field during deserialization.
+///
+/// Implement this on a unit struct to use a value other than
+/// `Default::default()` with [`DefaultOnNone`].
+pub trait Fallback<T> {
+    /// Returns the value to use in place of the missing field.
+    fn fallback() -> T;
+}
+
+/// A [`Fallback`] which returns `Default::default()`.
+#[derive(Debug)]
+pub struct UseDefault;
+
+impl<T: Default> Fallback<T> for UseDefault {
+    fn fallback() -> T {
+        T::default()
+    }
+}
+
+/// A wrapper that archives a field as an
+/// [`ArchivedOption`](crate::option::ArchivedOption) and deserializes `None`
+/// as a fallback value.
+///
+/// This supports changing a field from `Option<T>` to `T` without breaking
+/// existing archives. The field is always archived as `Some` using the wrapper
+/// `W`, so older readers expecting an `Option<T>` can still read new archives.
+/// When deserializing, `None` becomes the value returned by `F`, which is
+/// `T::default()` by default.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     with::{DefaultOnNone, Fallback, Identity},
+///     Archive, Deserialize, Serialize,
+/// };
+///
+/// // The old version of the type.
+/// #[derive(Archive, Serialize)]
+/// struct Old {
+///     retries: Option<u32>,
+/// }
+///
+/// struct Three;
+///
+/// impl Fallback<u32> for Three {
+///     fn fallback() -> u32 {
+///         3
+///     }
+/// }
+///
+/// // The new version of the type.
+/// #[derive(Archive, Deserialize, Serialize)]
+/// #[rkyv(check_bytes)]
+/// struct New {
+///     #[with(DefaultOnNone<Identity, Three>)]
+///     retries: u32,
+/// }
+///
+/// let bytes = rkyv::to_bytes::<Error>(&Old { retries: None }).unwrap();
+/// let new = rkyv::from_bytes::<New, Error>(&bytes).unwrap();
+/// assert_eq!(new.retries, 3);
+/// ```
+pub struct DefaultOnNone<W = Identity, F = UseDefault> {
+    _phantom: PhantomData<(W, F)>,
+}
+
 /// A wrapper that clones the contents of `Arc` and `Rc` pointers.
 #[derive(Debug)]
 pub struct Unshare;
 
+/// A wrapper that archives `Rc<str>`, `Arc<str>`, `Rc<[u8]>`, and `Arc<[u8]>`
+/// as an [`ArchivedCow`](crate::cow::ArchivedCow).
+///
+/// Values with a length of at most `N` bytes are stored inline, and longer
+/// values are shared between every clone of the same pointer. `N` may be at
+/// most [`INLINE_CAPACITY`](crate::cow::INLINE_CAPACITY), and larger values are
+/// treated as `INLINE_CAPACITY`. Deserializing creates a new pointer for every
+/// value.
+///
+/// # Example
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use rkyv::{with::CowThreshold, Archive};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(CowThreshold<8>)]
+///     name: Rc<str>,
+///     #[with(CowThreshold<0>)]
+///     data: Rc<[u8]>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CowThreshold<const N: usize>;
+
+/// A wrapper that always stores the bytes of a `String` out-of-line.
+///
+/// Short strings are normally stored inline in their
+/// [`ArchivedString`](crate::string::ArchivedString), so their bytes can't be
+/// shared or pointed to. With this wrapper, the bytes of every nonempty string
+/// are stored out-of-line and shared through the serializer's
+/// [`Sharing`](crate::ser::Sharing), so serializing the same string more than
+/// once only writes its bytes once. Empty strings are still stored inline.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{with::ForceOutOfLine, Archive};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(ForceOutOfLine)]
+///     name: String,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ForceOutOfLine;
+
+/// A wrapper that aligns the elements of a `Vec` to `N` bytes.
+///
+/// Elements are normally aligned to the alignment of their archived type. This
+/// wrapper pads the archive before the elements so that the first element is
+/// at a multiple of `N` bytes, which lets SIMD kernels use aligned loads on
+/// the archived elements. `N` must be a power of two. The archived type is a
+/// plain [`ArchivedVec`](crate::vec::ArchivedVec), since the alignment is only
+/// a property of where the elements are written.
+///
+/// Positions in the archive only correspond to aligned addresses if the buffer
+/// holding the archive is aligned to at least `N` bytes as well. Serialize
+/// into an [`AlignedVec`](crate::util::AlignedVec) with an `ALIGNMENT` of at
+/// least `N`, and use
+/// [`ArchivedVec::is_aligned_to`](crate::vec::ArchivedVec::is_aligned_to) to
+/// check the alignment of the elements at runtime.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     util::{to_bytes_in, AlignedVec},
+///     with::AlignElements,
+///     Archive, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// struct Samples {
+///     name: String,
+///     #[with(AlignElements<64>)]
+///     values: Vec<f32>,
+/// }
+///
+/// let samples = Samples {
+///     name: "sensor readings".to_string(),
+///     values: vec![1.0, 2.0, 3.0],
+/// };
+/// let bytes =
+///     to_bytes_in::<_, Error>(&samples, AlignedVec::<64>::new()).unwrap();
+/// let archived = rkyv::access::<ArchivedSamples, Error>(&bytes).unwrap();
+/// assert!(archived.values.is_aligned_to(64));
+/// ```
+#[derive(Debug)]
+pub struct AlignElements<const N: usize>;
+
+/// A wrapper that archives a `Vec<Option<T>>` as a validity bitmap and a dense
+/// column of values.
+///
+/// The layout parameter chooses whether only the present values are stored
+/// ([`Packed`](crate::nullable::Packed), the default) or a value is stored for
+/// every element ([`Slotted`](crate::nullable::Slotted)). The archived type is
+/// an [`ArchivedNullableColumn`](crate::nullable::ArchivedNullableColumn). See
+/// the [`nullable`](crate::nullable) module for details.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{with::AsNullableColumn, Archive, Serialize};
+///
+/// #[derive(Archive, Serialize)]
+/// struct Example {
+///     #[with(AsNullableColumn)]
+///     values: Vec<Option<f64>>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct AsNullableColumn<L = Packed> {
+    _phantom: PhantomData<L>,
+}
+
+/// A wrapper that archives a `HashMap<String, V>` as an [`ArchivedSymbolMap`].
+///
+/// Each key is replaced with a `u32` symbol assigned by the serializer's
+/// [`Interning`](crate::ser::Interning) strategy. The strings for the symbols
+/// are archived once by a [`Symbols`] field, which must be declared after every
+/// `SymbolKeys` field. Archived maps can't be deserialized without their symbol
+/// table, so this wrapper doesn't implement `DeserializeWith`. Use
+/// the `deserialize_with_symbols` method of [`ArchivedSymbolMap`] instead.
+///
+/// [`ArchivedSymbolMap`]: crate::collections::symbol_table::ArchivedSymbolMap
+/// [`Symbols`]: crate::collections::symbol_table::Symbols
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use rkyv::{collections::symbol_table::Symbols, with::SymbolKeys, Archive};
+///
+/// #[derive(Archive)]
+/// struct Example {
+///     #[with(SymbolKeys)]
+///     a: HashMap<String, u32>,
+///     #[with(SymbolKeys)]
+///     b: HashMap<String, u32>,
+///     symbols: Symbols,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SymbolKeys;
+
+/// A wrapper that deserializes a field without converting it.
+///
+/// The field deserializes into an [`OwnedArchivedField`], which owns a copy of
+/// the bytes of the archived subtree. Copying the bytes is much cheaper than
+/// deserializing large fields element by element, and the archived value can
+/// still be accessed through the field. Serializing an `OwnedArchivedField`
+/// writes the copied bytes back out, so the field archives the same way as
+/// the `T` it was copied from.
+///
+/// The archived type must implement [`ArchivedExtent`].
+///
+/// [`OwnedArchivedField`]: crate::extent::OwnedArchivedField
+/// [`ArchivedExtent`]: crate::extent::ArchivedExtent
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     extent::OwnedArchivedField, with::KeepArchived, Archive, Deserialize,
+///     Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize, Deserialize)]
+/// struct Example {
+///     name: String,
+///     #[with(KeepArchived)]
+///     blob: OwnedArchivedField<Vec<u8>>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct KeepArchived;
+
+/// A wrapper that deserializes `&'static str` and `&'static [T]` by leaking
+/// them.
+///
+/// `&'static` references are archived by value, the same as `String` and
+/// `Vec<T>`. Deserializing one allocates a new box for the value and leaks it
+/// with [`Box::leak`] to get a `'static` reference.
+///
+/// **Every deserialization leaks memory which is never freed.** This is
+/// appropriate for values which are deserialized once and live for the rest of
+/// the program, like configuration loaded at startup. For strings which are
+/// deserialized repeatedly, use [`Interned`] instead.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{with::Leak, Archive, Deserialize, Serialize};
+///
+/// #[derive(Archive, Serialize, Deserialize)]
+/// struct Example {
+///     #[with(Leak)]
+///     name: &'static str,
+///     #[with(Leak)]
+///     values: &'static [u32],
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Leak;
+
+/// A wrapper that deserializes `&'static str` through the deserializer's
+/// interner.
+///
+/// `&'static str`s are archived by value, the same as `String`. Deserializing
+/// one asks the deserializer's [`Interning`](crate::de::Interning) strategy for
+/// a `'static` string equal to the archived string. A
+/// [`WithInterner`](crate::de::WithInterner) deserializes with a user-supplied
+/// [`StrInterner`](crate::de::StrInterner), which decides whether strings are
+/// leaked or returned from a fixed set.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{with::Interned, Archive, Deserialize, Serialize};
+///
+/// #[derive(Archive, Serialize, Deserialize)]
+/// struct Example {
+///     #[with(Interned)]
+///     name: &'static str,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Interned;
+
+/// A wrapper that embeds the bytes of an already-serialized archive of `T`.
+///
+/// This can be applied to `AlignedVec` and `Vec<u8>` fields which hold the
+/// output of [`to_bytes`](crate::to_bytes). The bytes are copied verbatim and
+/// aligned so that the inner archive can be accessed in place through
+/// [`ArchivedBytesOf`], without serializing `T` again.
+///
+/// `AlignedVec` bytes are aligned to at least the alignment of the vector, and
+/// `Vec<u8>` bytes are aligned to at least 16 bytes. Both are also aligned to
+/// at least the alignment of the archived `T`.
+///
+/// [`ArchivedBytesOf`]: crate::nested::ArchivedBytesOf
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error, to_bytes, util::AlignedVec, with::BytesOf, Archive,
+///     Archived, Serialize,
+/// };
+///
+/// #[derive(Archive, Serialize)]
+/// #[rkyv(check_bytes)]
+/// struct Example {
+///     #[with(BytesOf<Vec<u32>>)]
+///     inner: AlignedVec,
+/// }
+///
+/// let value = Example {
+///     inner: to_bytes::<Error>(&vec![1u32, 2, 3]).unwrap(),
+/// };
+/// let bytes = to_bytes::<Error>(&value).unwrap();
+/// let archived = rkyv::access::<Archived<Example>, Error>(&bytes).unwrap();
+///
+/// let inner = archived.inner.access_inner::<Error>().unwrap();
+/// assert_eq!(inner[2], 3);
+/// ```
+pub struct BytesOf<T> {
+    _phantom: PhantomData<T>,
+}
+
+/// A wrapper that archives a `Vec` of enums as a struct of arrays.
+///
+/// The enum must derive `Archive` with `#[rkyv(soa)]`. Instead of storing each
+/// element with the size of the largest variant, the fields of each variant
+/// are stored in their own columns. See the [`soa`](crate::soa) module for
+/// details.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{with::SoA, Archive, Serialize};
+///
+/// #[derive(Archive, Serialize)]
+/// #[rkyv(soa)]
+/// enum Event {
+///     Tick,
+///     Data([u64; 4]),
+/// }
+///
+/// #[derive(Archive, Serialize)]
+/// struct Example {
+///     #[with(SoA)]
+///     events: Vec<Event>,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SoA;
+
+/// A wrapper that archives a linked list of boxed nodes as an
+/// [`ArchivedVec`](crate::vec::ArchivedVec) of their values.
+///
+/// This can be applied to `Option<Box<N>>` fields which hold the head of a
+/// list, where `N` implements [`ListNode`]. Serializing a `Box`-linked list
+/// normally recurses once for each node, which can overflow the stack when the
+/// list is long. `IterateList` walks the nodes in a loop instead, both when
+/// serializing and when deserializing. Dropping such a list is also recursive
+/// by default, so long lists should implement `Drop` iteratively as well.
+///
+/// # Example
+///
+/// ```
+/// use rkyv::{
+///     rancor::Error,
+///     to_bytes,
+///     with::{IterateList, ListNode},
+///     Archive, Archived, Serialize,
+/// };
+///
+/// struct Node {
+///     value: u32,
+///     next: Option<Box<Node>>,
+/// }
+///
+/// impl ListNode for Node {
+///     type Value = u32;
+///
+///     fn value(&self) -> &u32 {
+///         &self.value
+///     }
+///
+///     fn next(&self) -> Option<&Self> {
+///         self.next.as_deref()
+///     }
+///
+///     fn from_parts(value: u32, next: Option<Box<Self>>) -> Self {
+///         Node { value, next }
+///     }
+/// }
+///
+/// #[derive(Archive, Serialize)]
+/// struct List {
+///     #[with(IterateList)]
+///     head: Option<Box<Node>>,
+/// }
+///
+/// let list = List {
+///     head: Some(Box::new(Node::from_parts(
+///         1,
+///         Some(Box::new(Node::from_parts(2, None))),
+///     ))),
+/// };
+/// let bytes = to_bytes::<Error>(&list).unwrap();
+/// let archived = rkyv::access::<Archived<List>, Error>(&bytes).unwrap();
+/// let values = archived.head.iter().map(|v| v.to_native());
+/// assert!(values.eq([1, 2]));
+/// ```
+#[derive(Debug)]
+pub struct IterateList;
+
+/// A node of a singly-linked list which can be archived with [`IterateList`].
+#[cfg(feature = "alloc")]
+pub trait ListNode: Sized {
+    /// The value held by each node.
+    type Value;
+
+    /// Returns the value held by this node.
+    fn value(&self) -> &Self::Value;
+
+    /// Returns the next node in the list, if any.
+    fn next(&self) -> Option<&Self>;
+
+    /// Returns a new node with the given value, followed by the given nodes.
+    fn from_parts(value: Self::Value, next: Option<Box<Self>>) -> Self;
+}
+
 #[cfg(test)]
 mod tests {
     use core::{convert::Infallible, str::FromStr};
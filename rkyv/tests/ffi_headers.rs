@@ -0,0 +1,107 @@
+//! Golden files of the C headers generated for archived structs.
+//!
+//! The headers are for the default archive layout: little-endian and aligned
+//! primitives.
+
+#![cfg(all(
+    feature = "ffi_describe",
+    not(feature = "big_endian"),
+    not(feature = "unaligned"),
+))]
+// The structs are only used for their archived layouts.
+#![allow(dead_code)]
+
+use rkyv::{
+    ffi::{write_header, Describe, DescribeError},
+    Archive, Archived,
+};
+
+#[derive(Archive)]
+#[rkyv(derive_describe)]
+struct Point {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Archive)]
+#[rkyv(derive_describe)]
+struct Id(u32, u16);
+
+#[derive(Archive)]
+#[rkyv(derive_describe)]
+struct Packet {
+    sequence: u64,
+    flags: u8,
+    origin: Point,
+    samples: [i16; 3],
+    matrix: [[u8; 2]; 2],
+    valid: bool,
+    source: Id,
+}
+
+fn header<T: Describe>() -> String {
+    let mut out = String::new();
+    write_header::<T>(&mut out).unwrap();
+    out
+}
+
+#[test]
+fn point() {
+    assert_eq!(header::<ArchivedPoint>(), include_str!("headers/point.h"));
+}
+
+#[test]
+fn packet() {
+    assert_eq!(header::<ArchivedPacket>(), include_str!("headers/packet.h"));
+}
+
+#[test]
+fn reject_relative_pointers() {
+    #[derive(Archive)]
+    #[rkyv(derive_describe)]
+    struct Named {
+        id: u32,
+        name: String,
+    }
+
+    let error = write_header::<ArchivedNamed>(&mut String::new()).unwrap_err();
+    assert!(matches!(
+        error,
+        DescribeError::Unsupported { type_name, .. }
+            if type_name.ends_with("ArchivedString"),
+    ));
+    assert!(error.to_string().contains("relative pointer"));
+}
+
+#[test]
+fn reject_non_structs() {
+    let error =
+        write_header::<Archived<[u32; 4]>>(&mut String::new()).unwrap_err();
+    assert!(matches!(error, DescribeError::NotAStruct { .. }));
+}
+
+#[test]
+fn reject_conflicting_names() {
+    mod a {
+        #[derive(rkyv::Archive)]
+        #[rkyv(derive_describe)]
+        pub struct Point {
+            pub x: u8,
+        }
+    }
+
+    #[derive(Archive)]
+    #[rkyv(derive_describe)]
+    struct Line {
+        start: a::Point,
+        end: Point,
+    }
+
+    let error = write_header::<ArchivedLine>(&mut String::new()).unwrap_err();
+    assert_eq!(
+        error,
+        DescribeError::ConflictingNames {
+            name: "ArchivedPoint".to_string(),
+        },
+    );
+}
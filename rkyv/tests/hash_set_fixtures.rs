@@ -0,0 +1,87 @@
+//! Writes the golden hash set archives in `tests/data`.
+//!
+//! `single_pass_matches_archive` in `swiss_table::table` compares the
+//! single-pass and counted serializations of the same keys against these
+//! archives. They must be written by a serializer whose layout is known to be
+//! correct, not by the code under test. Only public API which predates
+//! single-pass serialization is used, so this file can be copied into a
+//! checkout of an older commit and run there:
+//!
+//! ```text
+//! RKYV_FIXTURE_DIR=$PWD/rkyv/tests/data \
+//!     cargo test -p rkyv --test hash_set_fixtures -- --ignored
+//! ```
+
+#![cfg(all(
+    feature = "std",
+    target_pointer_width = "64",
+    not(feature = "big_endian"),
+    not(feature = "unaligned"),
+    not(any(feature = "pointer_width_16", feature = "pointer_width_64")),
+))]
+
+use std::{env, fs, path::PathBuf};
+
+use rkyv::{
+    collections::swiss_table::{ArchivedHashSet, HashSetResolver},
+    rancor::{Error, Fallible, Source},
+    ser::{Allocator, Writer},
+    Archive, Archived, Place, Serialize,
+};
+
+struct Keys {
+    keys: Vec<u32>,
+    load_factor: (usize, usize),
+}
+
+impl Archive for Keys {
+    type Archived = ArchivedHashSet<Archived<u32>>;
+    type Resolver = HashSetResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedHashSet::resolve_from_len(
+            self.keys.len(),
+            self.load_factor,
+            resolver,
+            out,
+        );
+    }
+}
+
+impl<S> Serialize<S> for Keys
+where
+    S: Fallible + Writer + Allocator + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedHashSet::serialize_from_iter(
+            self.keys.iter(),
+            self.load_factor,
+            serializer,
+        )
+    }
+}
+
+#[test]
+#[ignore = "writes the golden archives in `tests/data`"]
+fn write_hash_set_fixtures() {
+    let dir = env::var_os("RKYV_FIXTURE_DIR").map_or_else(
+        || PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data"),
+        PathBuf::from,
+    );
+
+    let keys = (0..100).map(|i| i * 31 + 7).collect::<Vec<u32>>();
+    for (name, load_factor) in
+        [("1_4", (1, 4)), ("1_2", (1, 2)), ("7_8", (7, 8))]
+    {
+        let bytes = rkyv::to_bytes::<Error>(&Keys {
+            keys: keys.clone(),
+            load_factor,
+        })
+        .unwrap();
+        fs::write(dir.join(format!("hash_set_{name}.bin")), &bytes).unwrap();
+    }
+}
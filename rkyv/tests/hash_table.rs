@@ -0,0 +1,164 @@
+//! Tests for the public API of `ArchivedHashTable` as used by downstream
+//! crates to build their own archived collections.
+
+#![cfg(all(feature = "alloc", feature = "bytecheck"))]
+
+use core::ptr::NonNull;
+
+use rkyv::{
+    collections::{
+        swiss_table::{table::RawIter, ArchivedHashTable, HashTableResolver},
+        util::{Entry, EntryAdapter},
+    },
+    hash::hash_value,
+    rancor::{Error, Fallible, Source},
+    ser::{Allocator, Writer},
+    string::ArchivedString,
+    Archive, Archived, Place, Serialize,
+};
+
+const LOAD_FACTOR: (usize, usize) = (7, 8);
+
+type ArchivedCounts = ArchivedHashTable<Entry<ArchivedString, Archived<u32>>>;
+
+struct Counts(Vec<(String, u32)>);
+
+impl Archive for Counts {
+    type Archived = ArchivedCounts;
+    type Resolver = HashTableResolver;
+
+    fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+        ArchivedHashTable::resolve_from_len(
+            self.0.len(),
+            LOAD_FACTOR,
+            resolver,
+            out,
+        );
+    }
+}
+
+impl<S> Serialize<S> for Counts
+where
+    S: Fallible + Writer + Allocator + ?Sized,
+    S::Error: Source,
+{
+    fn serialize(
+        &self,
+        serializer: &mut S,
+    ) -> Result<Self::Resolver, S::Error> {
+        ArchivedHashTable::serialize_from_iter(
+            self.0.iter().map(|(key, value)| {
+                let hash = hash_value::<String>(key);
+                (hash, EntryAdapter { key, value })
+            }),
+            LOAD_FACTOR,
+            serializer,
+        )
+    }
+}
+
+fn get(table: &ArchivedCounts, key: &str) -> Option<u32> {
+    let hash = hash_value::<str>(key);
+    table
+        .get_with(hash, |e| e.key.as_str() == key)
+        .map(|e| e.value.to_native())
+}
+
+fn counts(len: u32) -> Counts {
+    Counts((0..len).map(|i| (format!("key {i}"), i * 3)).collect())
+}
+
+#[test]
+fn custom_collection() {
+    for len in [0, 1, 7, 8, 100] {
+        let value = counts(len);
+        let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+        let table = rkyv::access::<ArchivedCounts, Error>(&bytes).unwrap();
+
+        assert_eq!(table.len(), len as usize);
+        assert_eq!(table.is_empty(), len == 0);
+        assert!(table.capacity() >= table.len());
+
+        for (key, count) in value.0.iter() {
+            assert_eq!(get(table, key), Some(*count));
+        }
+        assert_eq!(get(table, "missing"), None);
+
+        // Every full bucket holds one of the items
+        let mut from_buckets = (0..table.capacity())
+            .filter_map(|i| table.bucket(i))
+            .map(|e| e.value.to_native())
+            .collect::<Vec<_>>();
+        assert!(table.bucket(table.capacity()).is_none());
+
+        // SAFETY: The pointers from `raw_iter` are valid while `table` is
+        // borrowed.
+        let raw_iter = table.raw_iter();
+        assert_eq!(raw_iter.len(), table.len());
+        let mut from_iter = raw_iter
+            .map(|e| unsafe { e.as_ref().value.to_native() })
+            .collect::<Vec<_>>();
+
+        from_buckets.sort();
+        from_iter.sort();
+        let expected = value.0.iter().map(|(_, c)| *c).collect::<Vec<_>>();
+        assert_eq!(from_buckets, expected);
+        assert_eq!(from_iter, expected);
+    }
+}
+
+#[test]
+fn invalid_load_factor() {
+    struct BadLoadFactor;
+
+    impl Archive for BadLoadFactor {
+        type Archived = ArchivedHashTable<Archived<u32>>;
+        type Resolver = HashTableResolver;
+
+        fn resolve(
+            &self,
+            resolver: Self::Resolver,
+            out: Place<Self::Archived>,
+        ) {
+            ArchivedHashTable::resolve_from_len(1, (1, 2), resolver, out);
+        }
+    }
+
+    impl<S> Serialize<S> for BadLoadFactor
+    where
+        S: Fallible + Writer + Allocator + ?Sized,
+        S::Error: Source,
+    {
+        fn serialize(
+            &self,
+            serializer: &mut S,
+        ) -> Result<Self::Resolver, S::Error> {
+            ArchivedHashTable::serialize_from_iter(
+                [(0, 1u32)].into_iter(),
+                (2, 1),
+                serializer,
+            )
+        }
+    }
+
+    assert!(rkyv::to_bytes::<Error>(&BadLoadFactor).is_err());
+}
+
+// These bindings fail to compile if the signatures of the public API change.
+#[test]
+fn api_signatures() {
+    type Table = ArchivedHashTable<u8>;
+
+    let _: fn(&Table) -> usize = Table::len;
+    let _: fn(&Table) -> bool = Table::is_empty;
+    let _: fn(&Table) -> usize = Table::capacity;
+    let _: fn(&Table, usize) -> Option<&u8> = Table::bucket;
+    let _: fn(&Table, u64, fn(&u8) -> bool) -> Option<&u8> =
+        Table::get_with::<fn(&u8) -> bool>;
+    let _: fn(&Table) -> RawIter<u8> = Table::raw_iter;
+    let _: fn(usize, (usize, usize), HashTableResolver, Place<Table>) =
+        Table::resolve_from_len;
+
+    let mut empty = RawIter::<u8>::empty();
+    let _: Option<NonNull<u8>> = empty.next();
+}
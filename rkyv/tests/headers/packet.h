@@ -0,0 +1,41 @@
+/* Generated by rkyv. Do not edit. */
+
+#ifndef RKYV_ARCHIVEDPACKET_H
+#define RKYV_ARCHIVEDPACKET_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+/* Multibyte values are stored in little-endian byte order. */
+
+#pragma pack(push, 1)
+
+typedef struct ArchivedPoint {
+    float x;
+    float y;
+} ArchivedPoint;
+typedef char ArchivedPoint_size_check[sizeof(ArchivedPoint) == 8 ? 1 : -1];
+
+typedef struct ArchivedId {
+    uint32_t _0;
+    uint16_t _1;
+    uint8_t _pad0[2];
+} ArchivedId;
+typedef char ArchivedId_size_check[sizeof(ArchivedId) == 8 ? 1 : -1];
+
+typedef struct ArchivedPacket {
+    uint64_t sequence;
+    uint8_t flags;
+    uint8_t _pad0[3];
+    ArchivedPoint origin;
+    int16_t samples[3];
+    uint8_t matrix[2][2];
+    bool valid;
+    uint8_t _pad1[1];
+    ArchivedId source;
+} ArchivedPacket;
+typedef char ArchivedPacket_size_check[sizeof(ArchivedPacket) == 40 ? 1 : -1];
+
+#pragma pack(pop)
+
+#endif /* RKYV_ARCHIVEDPACKET_H */
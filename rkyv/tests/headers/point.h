@@ -0,0 +1,21 @@
+/* Generated by rkyv. Do not edit. */
+
+#ifndef RKYV_ARCHIVEDPOINT_H
+#define RKYV_ARCHIVEDPOINT_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+/* Multibyte values are stored in little-endian byte order. */
+
+#pragma pack(push, 1)
+
+typedef struct ArchivedPoint {
+    float x;
+    float y;
+} ArchivedPoint;
+typedef char ArchivedPoint_size_check[sizeof(ArchivedPoint) == 8 ? 1 : -1];
+
+#pragma pack(pop)
+
+#endif /* RKYV_ARCHIVEDPOINT_H */
@@ -0,0 +1,180 @@
+//! Tests which use the crate without `std` or `alloc`.
+//!
+//! These only run when the crate is built without the `alloc` feature, for
+//! example with `cargo test --no-default-features --features bytecheck --test
+//! no_std`.
+
+#![cfg(not(feature = "alloc"))]
+#![no_std]
+
+use rkyv::{
+    collections::swiss_table::ArchivedHashMap,
+    util::{StaticArchive, StaticMap},
+    Archived,
+};
+
+static TABLE: StaticArchive<StaticMap<u16, u16, 4>, 256> =
+    StaticArchive::new(StaticMap::new([
+        (1, 100),
+        (2, 200),
+        (3, 300),
+        (4, 400),
+    ]));
+
+fn table() -> &'static ArchivedHashMap<Archived<u16>, Archived<u16>> {
+    TABLE.get()
+}
+
+static NAMES: StaticArchive<StaticMap<u8, &str, 2>, 256> =
+    StaticArchive::new(StaticMap::new([
+        (1, "one"),
+        (2, "a name which is too long to be inline"),
+    ]));
+
+#[test]
+fn static_map_lookups() {
+    assert_eq!(table().len(), 4);
+    for (key, value) in TABLE.value().entries() {
+        assert_eq!(table().get(key).unwrap().to_native(), *value);
+    }
+    assert!(table().get(&5u16).is_none());
+}
+
+#[test]
+fn static_map_strings() {
+    let names = NAMES.get();
+    assert_eq!(names.get(&1u8).unwrap(), "one");
+    assert_eq!(
+        names.get(&2u8).unwrap(),
+        "a name which is too long to be inline"
+    );
+}
+
+#[cfg(feature = "heapless")]
+mod inline {
+    use core::mem::MaybeUninit;
+
+    use heapless::{String, Vec};
+    use rkyv::{
+        access, access_with_buffer,
+        bytecheck::CheckBytes,
+        deserialize,
+        inline::ArchivedInlineString,
+        rancor::{Failure, Strategy},
+        ser::{
+            allocator::SubAllocator, sharing::Unshare, writer::Buffer,
+            CoreSerializer, Serializer,
+        },
+        util::{serialize_into, Align},
+        validation::validators::{SharedEntry, ValidatorWithBuffer},
+        Archive, Deserialize, Serialize,
+    };
+
+    type Validator<'a> = Strategy<ValidatorWithBuffer<'a>, Failure>;
+
+    fn roundtrip<T>(value: &T, check: impl FnOnce(&T::Archived))
+    where
+        T: Archive
+            + PartialEq
+            + core::fmt::Debug
+            + for<'a> Serialize<CoreSerializer<'a, Buffer<'a>, Failure>>,
+        T::Archived: for<'a> CheckBytes<Validator<'a>>
+            + Deserialize<T, Strategy<(), Failure>>,
+    {
+        let mut bytes = Align([0u8; 256]);
+        let mut scratch = [MaybeUninit::<u8>::uninit(); 256];
+        let len = serialize_into::<_, Failure>(
+            value,
+            Serializer::new(
+                Buffer::from(&mut bytes.0),
+                SubAllocator::new(&mut scratch),
+                Unshare,
+            ),
+        )
+        .unwrap()
+        .into_writer()
+        .len();
+
+        // `access` tracks shared pointers in a buffer on the stack
+        access::<T::Archived, Failure>(&bytes[..len]).unwrap();
+
+        let mut shared = [MaybeUninit::<SharedEntry>::uninit(); 4];
+        let archived = access_with_buffer::<T::Archived, Failure>(
+            &bytes[..len],
+            &mut shared,
+        )
+        .unwrap();
+        check(archived);
+        let deserialized =
+            deserialize::<T, _, Failure>(archived, &mut ()).unwrap();
+        assert_eq!(&deserialized, value);
+    }
+
+    #[test]
+    fn heapless_string() {
+        for value in ["", "probe-01", "héllo"] {
+            let mut string = String::<8>::new();
+            string.push_str(value).unwrap();
+            roundtrip(&string, |archived| assert_eq!(archived, value));
+        }
+    }
+
+    #[test]
+    fn heapless_vec() {
+        let value = Vec::<u16, 4>::from_slice(&[1, 2, 3]).unwrap();
+        roundtrip(&value, |archived| {
+            assert_eq!(archived.len(), 3);
+            assert!(archived
+                .iter()
+                .zip(value.iter())
+                .all(|(a, b)| a.to_native() == *b));
+        });
+    }
+
+    #[derive(Archive, Serialize, Deserialize, Debug, PartialEq)]
+    struct Reading {
+        sensor: String<16>,
+        samples: Vec<String<4>, 3>,
+    }
+
+    #[test]
+    fn heapless_nested() {
+        let mut value = Reading {
+            sensor: String::new(),
+            samples: Vec::new(),
+        };
+        value.sensor.push_str("thermometer").unwrap();
+        for sample in ["21.5", "22", "-4"] {
+            let mut string = String::new();
+            string.push_str(sample).unwrap();
+            value.samples.push(string).unwrap();
+        }
+
+        roundtrip(&value, |archived| {
+            assert_eq!(archived.sensor, "thermometer");
+            assert_eq!(archived.samples.len(), 3);
+            assert_eq!(archived.samples[2], "-4");
+        });
+    }
+
+    #[test]
+    fn reject_invalid_inline_strings() {
+        let mut shared = [MaybeUninit::<SharedEntry>::uninit(); 1];
+
+        // Longer than the capacity
+        let too_long = Align([5, b'a', b'b', b'c', b'd']);
+        access_with_buffer::<ArchivedInlineString<4>, Failure>(
+            &too_long.0,
+            &mut shared,
+        )
+        .unwrap_err();
+
+        // Invalid UTF-8
+        let invalid = Align([2, b'a', 0xff, 0, 0]);
+        access_with_buffer::<ArchivedInlineString<4>, Failure>(
+            &invalid.0,
+            &mut shared,
+        )
+        .unwrap_err();
+    }
+}
@@ -0,0 +1,108 @@
+//! Tests for the projections generated by `derive_project`, which mutate the
+//! fields of archived values without any unsafe code.
+
+#![cfg(all(feature = "std", feature = "bytecheck"))]
+
+use std::fs;
+
+use memmap2::MmapMut;
+use rkyv::{
+    access, access_mut, rancor::Error, to_bytes, util::AlignedVec, Archive,
+    Serialize,
+};
+
+#[derive(Archive, Serialize)]
+#[rkyv(check_bytes, derive_project)]
+struct Config {
+    name: String,
+    server: Server,
+    backend: Backend,
+}
+
+#[derive(Archive, Serialize)]
+#[rkyv(check_bytes, derive_project)]
+struct Server {
+    port: u16,
+    limits: Limits,
+}
+
+#[derive(Archive, Serialize)]
+#[rkyv(check_bytes, derive_project)]
+struct Limits(u32, u32);
+
+#[derive(Archive, Serialize)]
+#[rkyv(check_bytes, derive_project)]
+enum Backend {
+    Memory,
+    Disk { path: String, capacity: u64 },
+}
+
+#[test]
+fn project_mapped_archive() {
+    let value = Config {
+        name: "primary".to_string(),
+        server: Server {
+            port: 8080,
+            limits: Limits(16, 32),
+        },
+        backend: Backend::Disk {
+            path: "/var/lib/data".to_string(),
+            capacity: 1 << 20,
+        },
+    };
+    let bytes = to_bytes::<Error>(&value).unwrap();
+
+    let path = std::env::temp_dir()
+        .join(format!("rkyv_project_{}.bin", std::process::id()));
+    fs::write(&path, &bytes).unwrap();
+
+    {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        // Mapping the file is the only unsafe operation. Everything after it
+        // goes through validated access and generated projections.
+        let mut map = unsafe { MmapMut::map_mut(&file).unwrap() };
+        let mut config = access_mut::<ArchivedConfig, Error>(&mut map).unwrap();
+
+        // Projections can be chained to reach nested fields.
+        let mut limits = config.as_mut().project().server.project().limits;
+        *limits.as_mut().project().1 = 64.into();
+        *config.as_mut().project().server.project().port = 9090.into();
+        config
+            .as_mut()
+            .project()
+            .name
+            .pin_mut_str()
+            .make_ascii_uppercase();
+
+        // Enum fields can only be reached by matching a variant first.
+        match config.as_mut().project().backend.project() {
+            ArchivedBackendProjection::Disk { mut capacity, .. } => {
+                *capacity = (1u64 << 30).into();
+            }
+            ArchivedBackendProjection::Memory => panic!("expected Disk"),
+        }
+
+        map.flush().unwrap();
+    }
+
+    let mut written = AlignedVec::<16>::new();
+    written.extend_from_slice(&fs::read(&path).unwrap());
+    fs::remove_file(&path).unwrap();
+
+    let config = access::<ArchivedConfig, Error>(&written).unwrap();
+    assert_eq!(config.name, "PRIMARY");
+    assert_eq!(config.server.port, 9090);
+    assert_eq!(config.server.limits.0, 16);
+    assert_eq!(config.server.limits.1, 64);
+    match &config.backend {
+        ArchivedBackend::Disk { path, capacity } => {
+            assert_eq!(path, "/var/lib/data");
+            assert_eq!(*capacity, 1 << 30);
+        }
+        ArchivedBackend::Memory => panic!("expected Disk"),
+    }
+}
@@ -0,0 +1,77 @@
+//! Snapshots of the archived layouts of primitives and collections.
+//!
+//! These pin the exact bytes that rkyv writes for some common types. Any
+//! change to these layouts breaks compatibility with existing archives, so a
+//! failing snapshot should be treated as a breaking change.
+//!
+//! The snapshots are for the default archive layout: little-endian, 32-bit
+//! `usize`, and aligned primitives.
+
+#![cfg(all(
+    feature = "golden",
+    not(feature = "big_endian"),
+    not(feature = "unaligned"),
+    not(feature = "pointer_width_16"),
+    not(feature = "pointer_width_64"),
+))]
+
+use std::collections::{BTreeMap, HashMap};
+
+use rkyv::{assert_archive_snapshot, Archive, Serialize};
+
+#[test]
+fn primitives() {
+    assert_archive_snapshot!(0x0102_0304u32, "tests/snapshots/u32.bin");
+    assert_archive_snapshot!(
+        Some(0x0102_0304u32),
+        "tests/snapshots/option_some.bin",
+    );
+}
+
+#[test]
+fn strings() {
+    assert_archive_snapshot!(
+        String::from("hi"),
+        "tests/snapshots/string_inline.bin",
+    );
+    assert_archive_snapshot!(
+        String::from("hello, world!"),
+        "tests/snapshots/string_out_of_line.bin",
+    );
+}
+
+#[test]
+fn collections() {
+    assert_archive_snapshot!(vec![1u16, 2, 3], "tests/snapshots/vec.bin");
+
+    // A single entry always serializes the same way, even though the map
+    // uses a randomly-seeded hasher.
+    let mut hash_map = HashMap::new();
+    hash_map.insert(1u32, 2u32);
+    assert_archive_snapshot!(hash_map, "tests/snapshots/hash_map.bin");
+
+    let mut btree_map = BTreeMap::new();
+    btree_map.insert(0x0102_0304u32, 0x0506u16);
+    assert_archive_snapshot!(btree_map, "tests/snapshots/btree_map.bin");
+}
+
+#[test]
+fn derived() {
+    #[derive(Archive, Serialize)]
+    struct Record {
+        id: u64,
+        name: String,
+        scores: Vec<u32>,
+        parent: Option<u16>,
+    }
+
+    assert_archive_snapshot!(
+        Record {
+            id: 0x0102_0304_0506_0708,
+            name: "hello, world!".to_string(),
+            scores: vec![7],
+            parent: Some(0x0506),
+        },
+        "tests/snapshots/record.bin",
+    );
+}
@@ -1,6 +1,15 @@
 #[test]
 fn ui() {
     let t = trybuild::TestCases::new();
+    t.pass("tests/ui/archived_visibility.rs");
     t.pass("tests/ui/derive_visibility.rs");
+    t.pass("tests/ui/no_implicit_prelude.rs");
     t.pass("tests/ui/raw_identifiers.rs");
+    t.pass("tests/ui/transparent_generic.rs");
+    t.pass("tests/ui/union_as_bytes.rs");
+    t.compile_fail("tests/ui/portable_enum_repr.rs");
+    t.compile_fail("tests/ui/portable_repr_rust.rs");
+    t.compile_fail("tests/ui/union_as_bytes_non_pod.rs");
+    t.compile_fail("tests/ui/union_as_bytes_pointer_alias.rs");
+    t.compile_fail("tests/ui/union_as_bytes_reference.rs");
 }
@@ -0,0 +1,99 @@
+use core::pin::Pin;
+
+mod types {
+    use rkyv::{Archive, Deserialize, Serialize};
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(archived_vis = "pub(crate)")]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(archived_vis = "pub", resolver_vis = "pub(crate)")]
+    pub(crate) enum Shape {
+        Circle { center: Point, radius: u32 },
+        Square(Point, u32),
+    }
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(archived_vis = "pub(crate)", resolver_vis = "pub(super)")]
+    pub struct Wrapper(pub u64);
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(
+        extensible,
+        archived_vis = "pub(crate)",
+        resolver_vis = "pub(crate)"
+    )]
+    pub struct Config {
+        pub name: String,
+    }
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(archived_vis = "", resolver_vis = "")]
+    struct Private;
+
+    #[derive(Archive, Serialize, Deserialize)]
+    #[rkyv(archived_vis = "pub(crate)", derive_as_ref, derive_project)]
+    enum Either<L, R> {
+        Left(L),
+        Right { value: R },
+    }
+
+    pub fn private_resolver() {
+        let _: Option<PrivateResolver> = None;
+        let _: Option<ArchivedPrivate> = None;
+    }
+}
+
+mod wire {
+    pub use crate::types::{
+        ArchivedConfig, ArchivedEither, ArchivedEitherProjection,
+        ArchivedEitherRef, ArchivedPoint, ArchivedShape, ArchivedWrapper,
+    };
+}
+
+fn unwrap_left(either: &wire::ArchivedEither<u32, u64>) -> u32 {
+    match either.as_ref() {
+        wire::ArchivedEitherRef::Left(left) => left.to_native(),
+        wire::ArchivedEitherRef::Right { .. } => panic!("expected left"),
+    }
+}
+
+fn set_left(either: Pin<&mut wire::ArchivedEither<u32, u64>>, value: u32) {
+    match either.project() {
+        wire::ArchivedEitherProjection::Left(mut left) => {
+            *left = value.into();
+        }
+        wire::ArchivedEitherProjection::Right { .. } => panic!("expected left"),
+    }
+}
+
+use wire::{ArchivedPoint, ArchivedShape, ArchivedWrapper};
+
+fn main() {
+    ArchivedPoint {
+        x: 1.into(),
+        y: 2.into(),
+    };
+    ArchivedShape::Square(
+        ArchivedPoint {
+            x: 1.into(),
+            y: 2.into(),
+        },
+        3.into(),
+    );
+    ArchivedWrapper(4.into());
+    let mut either = wire::ArchivedEither::Left(1.into());
+    assert_eq!(unwrap_left(&either), 1);
+    set_left(Pin::new(&mut either), 2);
+    assert_eq!(unwrap_left(&either), 2);
+    let _: Option<&wire::ArchivedConfig> = None;
+
+    let _: Option<types::ShapeResolver> = None;
+    let _: Option<types::WrapperResolver> = None;
+    let _: Option<types::ConfigResolver> = None;
+    types::private_resolver();
+}
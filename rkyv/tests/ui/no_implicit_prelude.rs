@@ -0,0 +1,120 @@
+// Derived code must not depend on the prelude, or on `core` resolving to the
+// real `core` crate.
+
+#![no_implicit_prelude]
+
+#[allow(dead_code)]
+mod core {}
+
+// These shadow items from the prelude which derived code could otherwise
+// refer to.
+#[allow(dead_code)]
+struct Option;
+#[allow(dead_code)]
+struct Result;
+#[allow(dead_code)]
+trait Sized {}
+#[allow(dead_code, non_camel_case_types)]
+struct i128;
+
+use ::rkyv::{Archive, Deserialize, Serialize};
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(check_bytes, compare(PartialEq, PartialOrd))]
+struct Named {
+    a: u32,
+    b: i64,
+    c: ::std::string::String,
+}
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(check_bytes, compare(PartialEq, PartialOrd))]
+struct Tuple(u8, ::std::vec::Vec<u16>);
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(check_bytes, compare(PartialEq, PartialOrd))]
+struct Unit;
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(check_bytes)]
+struct Generic<T> {
+    value: T,
+}
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(check_bytes, compare(PartialEq, PartialOrd))]
+enum Enum {
+    A,
+    B(u32),
+    C { value: i16 },
+}
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(check_bytes, soa)]
+enum Event {
+    Tick,
+    Move { x: i32, y: i32 },
+}
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(transparent)]
+struct Transparent(u32);
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(as_discriminant, repr(u16))]
+#[repr(u16)]
+enum Opcode {
+    Nop = 0,
+    Load = 3,
+}
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(check_bytes, extensible)]
+struct Extensible {
+    id: u32,
+    name: ::std::string::String,
+}
+
+#[derive(
+    Archive, Serialize, Deserialize, ::core::clone::Clone, ::core::marker::Copy,
+)]
+#[rkyv(union_as_bytes, check_bytes)]
+#[repr(C)]
+union Bytes {
+    int: u32,
+    bytes: [u8; 4],
+}
+
+// SAFETY: Every field of `Bytes` is as large as the union and has no padding.
+unsafe impl ::rkyv::place::Initialized for Bytes {}
+
+#[derive(Archive, Serialize, Deserialize)]
+#[rkyv(check_bytes, derive_project, derive_diff)]
+struct Projected {
+    id: u32,
+    values: ::std::vec::Vec<u8>,
+}
+
+fn main() {
+    let value = Named {
+        a: 1,
+        b: 2,
+        c: ::std::string::String::new(),
+    };
+    let bytes = ::rkyv::to_bytes::<::rkyv::rancor::Error>(&value).unwrap();
+    let archived =
+        ::rkyv::access::<ArchivedNamed, ::rkyv::rancor::Error>(&bytes).unwrap();
+    ::std::assert!(*archived == value);
+
+    let bytes =
+        ::rkyv::to_bytes::<::rkyv::rancor::Error>(&Opcode::Load).unwrap();
+    let archived = ::rkyv::access::<
+        ::rkyv::Archived<Opcode>,
+        ::rkyv::rancor::Error,
+    >(&bytes)
+    .unwrap();
+    ::std::assert!(::std::matches!(
+        archived.try_to_native(),
+        ::core::result::Result::Ok(Opcode::Load),
+    ));
+}
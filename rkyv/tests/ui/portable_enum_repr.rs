@@ -0,0 +1,10 @@
+use rkyv::Portable;
+
+#[derive(Portable)]
+#[repr(u16)]
+enum Tag {
+    A,
+    B,
+}
+
+fn main() {}
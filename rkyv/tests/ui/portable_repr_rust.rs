@@ -0,0 +1,9 @@
+use rkyv::Portable;
+
+#[derive(Portable)]
+struct Padded {
+    a: u8,
+    b: u32,
+}
+
+fn main() {}
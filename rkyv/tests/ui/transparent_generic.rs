@@ -0,0 +1,44 @@
+use core::marker::PhantomData;
+
+use rkyv::{rancor::Error, Archive, Archived, Deserialize, Serialize};
+
+#[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
+#[rkyv(transparent)]
+struct Wrapper<T>(T);
+
+#[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
+#[rkyv(transparent)]
+struct Named<T> {
+    value: T,
+}
+
+#[derive(Archive, Deserialize, Serialize, Debug, PartialEq)]
+#[rkyv(transparent)]
+struct Tagged<T, U> {
+    _tag: PhantomData<U>,
+    #[rkyv(transparent)]
+    values: Vec<T>,
+}
+
+fn main() {
+    let value = Wrapper(Named { value: 42u32 });
+    let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+    let archived =
+        rkyv::access::<Archived<Wrapper<Named<u32>>>, Error>(&bytes).unwrap();
+    assert_eq!(archived.to_native(), 42);
+    let deserialized =
+        rkyv::from_bytes::<Wrapper<Named<u32>>, Error>(&bytes).unwrap();
+    assert_eq!(deserialized, value);
+
+    let value = Tagged::<u32, String> {
+        _tag: PhantomData,
+        values: vec![1, 2, 3],
+    };
+    let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+    let archived =
+        rkyv::access::<Archived<Tagged<u32, String>>, Error>(&bytes).unwrap();
+    assert_eq!(archived.len(), 3);
+    let deserialized =
+        rkyv::from_bytes::<Tagged<u32, String>, Error>(&bytes).unwrap();
+    assert_eq!(deserialized, value);
+}
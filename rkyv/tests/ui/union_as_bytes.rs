@@ -0,0 +1,77 @@
+use rkyv::{
+    place::Initialized, rancor::Error, Archive, Archived, Deserialize,
+    Serialize,
+};
+
+#[derive(Archive, Serialize, Deserialize, Clone, Copy)]
+#[rkyv(union_as_bytes, check_bytes)]
+#[repr(C)]
+union Value {
+    int: u32,
+    float: f32,
+    bytes: [u8; 4],
+}
+
+// SAFETY: Every field of `Value` is as large as the union and has no padding.
+unsafe impl Initialized for Value {}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Rgba {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: u8,
+}
+
+// SAFETY: `Rgba` contains only bytes and has no padding.
+unsafe impl Initialized for Rgba {}
+
+#[derive(Archive, Serialize, Deserialize, Clone, Copy)]
+#[rkyv(union_as_bytes, check_bytes)]
+#[repr(C)]
+union Color {
+    rgba: Rgba,
+    packed: u32,
+}
+
+// SAFETY: Every field of `Color` is as large as the union and has no padding.
+unsafe impl Initialized for Color {}
+
+fn main() {
+    let value = Value { int: 0x0102_0304 };
+
+    let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+    let archived = rkyv::access::<Archived<Value>, Error>(&bytes).unwrap();
+    assert_eq!(archived.as_bytes(), &0x0102_0304u32.to_ne_bytes());
+    assert_eq!(unsafe { archived.int() }, 0x0102_0304);
+    assert_eq!(unsafe { archived.bytes() }, 0x0102_0304u32.to_ne_bytes());
+
+    let deserialized = rkyv::from_bytes::<Value, Error>(&bytes).unwrap();
+    assert_eq!(unsafe { deserialized.int }, 0x0102_0304);
+
+    let value = Value { float: 1.5 };
+    let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+    let archived = rkyv::access::<Archived<Value>, Error>(&bytes).unwrap();
+    assert_eq!(unsafe { archived.float() }, 1.5);
+
+    let value = Color {
+        rgba: Rgba {
+            r: 1,
+            g: 2,
+            b: 3,
+            a: 4,
+        },
+    };
+    let bytes = rkyv::to_bytes::<Error>(&value).unwrap();
+    let archived = rkyv::access::<Archived<Color>, Error>(&bytes).unwrap();
+    assert_eq!(archived.as_bytes(), &[1, 2, 3, 4]);
+    assert_eq!(unsafe { archived.rgba() }.b, 3);
+    assert_eq!(
+        unsafe { archived.packed() },
+        u32::from_ne_bytes([1, 2, 3, 4])
+    );
+
+    let deserialized = rkyv::from_bytes::<Color, Error>(&bytes).unwrap();
+    assert_eq!(unsafe { deserialized.rgba }.a, 4);
+}
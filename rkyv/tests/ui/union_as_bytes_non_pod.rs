@@ -0,0 +1,15 @@
+use core::mem::ManuallyDrop;
+
+use rkyv::{place::Initialized, Archive};
+
+#[derive(Archive)]
+#[rkyv(union_as_bytes)]
+union Value {
+    words: [usize; 3],
+    string: ManuallyDrop<String>,
+}
+
+// SAFETY: Every field of `Value` is as large as the union and has no padding.
+unsafe impl Initialized for Value {}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use rkyv::{place::Initialized, Archive};
+
+type Ptr = *const u8;
+
+#[derive(Archive)]
+#[rkyv(union_as_bytes)]
+union Value {
+    int: usize,
+    ptr: Ptr,
+}
+
+// SAFETY: Every field of `Value` is as large as the union and has no padding.
+unsafe impl Initialized for Value {}
+
+fn main() {}
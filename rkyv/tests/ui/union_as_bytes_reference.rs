@@ -0,0 +1,13 @@
+use rkyv::{place::Initialized, Archive};
+
+#[derive(Archive)]
+#[rkyv(union_as_bytes)]
+union Value {
+    int: u64,
+    ptr: &'static u64,
+}
+
+// SAFETY: Every field of `Value` is as large as the union and has no padding.
+unsafe impl Initialized for Value {}
+
+fn main() {}
@@ -1,44 +1,53 @@
+mod describe;
+mod diff;
+mod discriminant;
 mod r#enum;
+mod extensible;
 mod printing;
+mod project;
+mod projections;
+mod soa;
 mod r#struct;
+mod transparent;
+mod union;
 
 use core::fmt::Display;
 
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::quote;
 use syn::{Data, DeriveInput, Error, Field, Ident, Meta};
 
-use crate::attributes::Attributes;
+use crate::{
+    attributes::Attributes,
+    util::{is_bound_skipped, omit_recursive_bounds},
+};
 
 pub fn derive(input: &mut DeriveInput) -> Result<TokenStream, Error> {
-    let attributes = Attributes::parse(input)?;
+    let mut attributes = Attributes::parse(input)?;
+    omit_recursive_bounds(input, &mut attributes);
     derive_archive_impl(input, &attributes)
 }
 
 fn field_archive_attrs<'a>(
     attributes: &'a Attributes,
     field: &'a Field,
-) -> impl 'a + Iterator<Item = &'a dyn ToTokens> {
-    field.attrs.iter().filter_map(|attr| match &attr.meta {
-        Meta::Path(path) => {
-            if path.is_ident("omit_bounds") && attributes.check_bytes.is_some()
-            {
-                Some(path as _)
-            } else {
-                None
-            }
-        }
+) -> impl 'a + Iterator<Item = TokenStream> {
+    let omit_bounds = (attributes.check_bytes.is_some()
+        && is_bound_skipped(field))
+    .then(|| quote! { omit_bounds });
+    let attrs = field.attrs.iter().filter_map(|attr| match &attr.meta {
         Meta::List(list) => {
             if list.path.is_ident("archive_attr")
                 || list.path.is_ident("rkyv_attr")
             {
-                Some(&list.tokens as _)
+                Some(list.tokens.clone())
             } else {
                 None
             }
         }
-        Meta::NameValue(_) => None,
-    })
+        Meta::Path(_) | Meta::NameValue(_) => None,
+    });
+    omit_bounds.into_iter().chain(attrs)
 }
 
 fn archived_doc(name: &Ident) -> String {
@@ -96,27 +105,70 @@ fn derive_archive_impl(
 
     let printing = printing::Printing::new(input, attributes)?;
 
+    if let Some(union_as_bytes) = &attributes.union_as_bytes {
+        if !matches!(input.data, Data::Union(_)) {
+            return Err(Error::new_spanned(
+                union_as_bytes,
+                "`union_as_bytes` may only be used on unions",
+            ));
+        }
+    }
+
+    if let Some(as_discriminant) = &attributes.as_discriminant {
+        if !matches!(input.data, Data::Enum(_)) {
+            return Err(Error::new_spanned(
+                as_discriminant,
+                "`as_discriminant` may only be used on enums",
+            ));
+        }
+    }
+
+    if let Some(derive_describe) = &attributes.derive_describe {
+        if !matches!(input.data, Data::Struct(_)) {
+            return Err(Error::new_spanned(
+                derive_describe,
+                "`derive_describe` may only be used on structs",
+            ));
+        }
+    }
+
+    if let Some(derive_projections) = &attributes.derive_projections {
+        if !matches!(input.data, Data::Struct(_)) {
+            return Err(Error::new_spanned(
+                derive_projections,
+                "`derive_projections` may only be used on structs",
+            ));
+        }
+    }
+
+    if let Some(unit_resolver) = &attributes.unit_resolver {
+        if !matches!(input.data, Data::Struct(_)) {
+            return Err(Error::new_spanned(
+                unit_resolver,
+                "`unit_resolver` may only be used on structs",
+            ));
+        }
+    }
+
     let (archive_types, archive_impls) = match input.data {
         Data::Struct(_) => r#struct::impl_struct(input, attributes, &printing)?,
         Data::Enum(_) => r#enum::impl_enum(input, attributes, &printing)?,
+        Data::Union(_) if attributes.union_as_bytes.is_some() => {
+            union::impl_union(input, attributes, &printing)?
+        }
         Data::Union(_) => {
             return Err(Error::new_spanned(
                 input,
-                "Archive cannot be derived for unions",
+                "Archive cannot be derived for unions without `union_as_bytes`",
             ))
         }
     };
 
-    let rkyv_path = &printing.rkyv_path;
-
     Ok(quote! {
         #archive_types
 
         #[automatically_derived]
         const _: () = {
-            use core::marker::PhantomData;
-            use #rkyv_path::{Archive, Archived};
-
             #archive_impls
         };
     })
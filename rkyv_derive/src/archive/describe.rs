@@ -0,0 +1,72 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Fields, Member, Path};
+
+use crate::{
+    archive::printing::Printing,
+    util::{archived, members, strip_raw},
+};
+
+fn c_field_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => strip_raw(ident),
+        // C identifiers can't start with a digit.
+        Member::Unnamed(index) => format!("_{}", index.index),
+    }
+}
+
+pub fn generate_struct_describe_impl(
+    input: &DeriveInput,
+    printing: &Printing,
+    fields: &Fields,
+    derive_describe: &Path,
+) -> Result<TokenStream, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`derive_describe` may not be used on structs with generic \
+             parameters",
+        ));
+    }
+    if fields.is_empty() {
+        return Err(Error::new_spanned(
+            derive_describe,
+            "`derive_describe` may not be used on structs without fields \
+             because C structs must have at least one member",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let archived_name = &printing.archived_name;
+    let c_name = strip_raw(archived_name);
+
+    let push_fields = members(fields)
+        .map(|(member, field)| {
+            let archived = archived(rkyv_path, field)?;
+            let name = c_field_name(&member);
+            Ok(quote! {
+                out.push_field::<#archived>(
+                    #name,
+                    ::core::mem::offset_of!(Self, #member),
+                )?;
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(quote! {
+        #[automatically_derived]
+        impl #rkyv_path::ffi::Describe for #archived_name {
+            fn describe() -> ::core::result::Result<
+                #rkyv_path::ffi::CType,
+                #rkyv_path::ffi::DescribeError,
+            > {
+                let mut out =
+                    #rkyv_path::ffi::CStruct::new::<Self>(#c_name);
+                #(#push_fields)*
+                ::core::result::Result::Ok(
+                    #rkyv_path::ffi::CType::Struct(out),
+                )
+            }
+        }
+    })
+}
@@ -0,0 +1,189 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_quote, spanned::Spanned as _, DataEnum, DeriveInput, Error, Field,
+    Fields, Ident, Member, WherePredicate,
+};
+
+use crate::{
+    archive::printing::Printing,
+    util::{archived, members, mentions_generics, strip_raw},
+};
+
+fn member_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => strip_raw(ident),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+fn diff_bounds<'a>(
+    input: &DeriveInput,
+    printing: &Printing,
+    fields: impl Iterator<Item = &'a Field>,
+) -> Result<Vec<WherePredicate>, Error> {
+    let rkyv_path = &printing.rkyv_path;
+    fields
+        .filter(|field| mentions_generics(&input.generics, field))
+        .map(|field| {
+            let archived = archived(rkyv_path, field)?;
+            Ok(parse_quote! { #archived: #rkyv_path::diff::Diff })
+        })
+        .collect()
+}
+
+fn generate_diff_impl(
+    input: &DeriveInput,
+    printing: &Printing,
+    bounds: Vec<WherePredicate>,
+    body: TokenStream,
+) -> TokenStream {
+    let rkyv_path = &printing.rkyv_path;
+    let archived_name = &printing.archived_name;
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(bounds);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #rkyv_path::diff::Diff
+            for #archived_name #ty_generics
+        #where_clause
+        {
+            // Enums fall back to a wildcard arm, which is unreachable when
+            // they only have one variant.
+            #[allow(unreachable_patterns)]
+            fn diff(
+                &self,
+                other: &Self,
+                out: &mut #rkyv_path::diff::DiffReport,
+            ) {
+                #body
+            }
+        }
+    }
+}
+
+pub fn generate_struct_diff_impl(
+    input: &DeriveInput,
+    printing: &Printing,
+    fields: &Fields,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = &printing.rkyv_path;
+    let bounds = diff_bounds(input, printing, fields.iter())?;
+    let field_diffs = members(fields).map(|(member, _)| {
+        let name = member_name(&member);
+        quote! {
+            out.field(#name, |out| {
+                #rkyv_path::diff::Diff::diff(
+                    &self.#member,
+                    &other.#member,
+                    out,
+                )
+            });
+        }
+    });
+
+    // Structs without fields never differ.
+    let ignore_args =
+        fields.is_empty().then(|| quote! { let _ = (other, out); });
+
+    Ok(generate_diff_impl(
+        input,
+        printing,
+        bounds,
+        quote! {
+            #ignore_args
+            #(#field_diffs)*
+        },
+    ))
+}
+
+pub fn generate_enum_diff_impl(
+    input: &DeriveInput,
+    printing: &Printing,
+    data: &DataEnum,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = &printing.rkyv_path;
+    let archived_name = &printing.archived_name;
+    let bounds = diff_bounds(
+        input,
+        printing,
+        data.variants.iter().flat_map(|v| v.fields.iter()),
+    )?;
+
+    let arms = data.variants.iter().map(|v| {
+        let variant = &v.ident;
+        let members = members(&v.fields).collect::<Vec<_>>();
+        let old_bindings = members
+            .iter()
+            .map(|(member, field)| {
+                Ident::new(
+                    &format!("old_{}", member_name(member)),
+                    field.span(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let new_bindings = members
+            .iter()
+            .map(|(member, field)| {
+                Ident::new(
+                    &format!("new_{}", member_name(member)),
+                    field.span(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let names = members.iter().map(|(member, _)| member_name(member));
+        let field_diffs = quote! {
+            #(
+                out.field(#names, |out| {
+                    #rkyv_path::diff::Diff::diff(
+                        #old_bindings,
+                        #new_bindings,
+                        out,
+                    )
+                });
+            )*
+        };
+
+        let members = members.iter().map(|(member, _)| member);
+        let (old_pattern, new_pattern) = match v.fields {
+            Fields::Named(_) | Fields::Unnamed(_) => {
+                let members = members.collect::<Vec<_>>();
+                (
+                    quote! {
+                        #archived_name::#variant {
+                            #(#members: #old_bindings,)*
+                        }
+                    },
+                    quote! {
+                        #archived_name::#variant {
+                            #(#members: #new_bindings,)*
+                        }
+                    },
+                )
+            }
+            Fields::Unit => (
+                quote! { #archived_name::#variant },
+                quote! { #archived_name::#variant },
+            ),
+        };
+
+        quote! {
+            (#old_pattern, #new_pattern) => { #field_diffs }
+        }
+    });
+
+    Ok(generate_diff_impl(
+        input,
+        printing,
+        bounds,
+        quote! {
+            // Values of different variants differ as a whole.
+            match (self, other) {
+                #(#arms)*
+                _ => out.changed(),
+            }
+        },
+    ))
+}
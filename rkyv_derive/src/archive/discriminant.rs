@@ -0,0 +1,198 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens as _};
+use syn::{DataEnum, DeriveInput, Error, Fields, Ident, LitStr, Path};
+
+use crate::{
+    archive::printing::Printing, attributes::Attributes, util::strip_raw,
+};
+
+pub fn impl_discriminant_enum(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+    data: &DataEnum,
+    as_discriminant: &Path,
+) -> Result<(TokenStream, TokenStream), Error> {
+    let conflicts = [
+        (
+            attributes.archive_as.as_ref().map(|x| x.to_token_stream()),
+            "as = \"..\"",
+        ),
+        (
+            attributes.compares.as_ref().map(|x| x.to_token_stream()),
+            "compare(...)",
+        ),
+        (
+            attributes.archived.as_ref().map(|x| x.to_token_stream()),
+            "archived = \"...\"",
+        ),
+        (
+            attributes.resolver.as_ref().map(|x| x.to_token_stream()),
+            "resolver = \"...\"",
+        ),
+        (
+            attributes
+                .archived_vis
+                .as_ref()
+                .map(|x| x.to_token_stream()),
+            "archived_vis = \"...\"",
+        ),
+        (
+            attributes
+                .resolver_vis
+                .as_ref()
+                .map(|x| x.to_token_stream()),
+            "resolver_vis = \"...\"",
+        ),
+        (
+            attributes.attrs.first().map(|x| x.to_token_stream()),
+            "attr(...) and derive(...)",
+        ),
+        (
+            attributes
+                .derive_as_ref
+                .as_ref()
+                .map(|x| x.to_token_stream()),
+            "derive_as_ref",
+        ),
+        (
+            attributes
+                .derive_project
+                .as_ref()
+                .map(|x| x.to_token_stream()),
+            "derive_project",
+        ),
+        (
+            attributes.derive_diff.as_ref().map(|x| x.to_token_stream()),
+            "derive_diff",
+        ),
+        (attributes.soa.as_ref().map(|x| x.to_token_stream()), "soa"),
+    ];
+    for (tokens, name) in conflicts {
+        if let Some(tokens) = tokens {
+            return Err(Error::new_spanned(
+                tokens,
+                format!(
+                    "{} may not be used with `as_discriminant` because the \
+                     archived type is an `ArchivedDiscriminant`",
+                    name,
+                ),
+            ));
+        }
+    }
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`as_discriminant` may not be used on enums with generic \
+             parameters",
+        ));
+    }
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(
+            as_discriminant,
+            "`as_discriminant` may not be used on enums without variants",
+        ));
+    }
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|v| !matches!(v.fields, Fields::Unit))
+    {
+        return Err(Error::new_spanned(
+            &variant.fields,
+            "`as_discriminant` may only be used on fieldless enums",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let name_str = LitStr::new(&strip_raw(name), name.span());
+    let repr = attributes
+        .repr
+        .clone()
+        .unwrap_or_else(|| Ident::new("u8", name.span()));
+    let variants = data.variants.iter().map(|v| &v.ident).collect::<Vec<_>>();
+    // The primitive types may be shadowed at the derive site, so they are
+    // referred to by their full paths.
+    let repr_ty = quote! { ::core::primitive::#repr };
+
+    let fit_checks = variants.iter().map(|variant| {
+        let message = LitStr::new(
+            &format!(
+                "the discriminant of `{}::{}` does not fit in `{}`",
+                strip_raw(name),
+                strip_raw(variant),
+                repr,
+            ),
+            variant.span(),
+        );
+        quote! {
+            if #name::#variant as ::core::primitive::i128
+                != (#name::#variant as #repr_ty) as ::core::primitive::i128
+            {
+                ::core::panic!(#message);
+            }
+        }
+    });
+
+    Ok((
+        TokenStream::new(),
+        quote! {
+            const _: () = {
+                #(#fit_checks)*
+            };
+
+            impl ::core::convert::TryFrom<#repr_ty> for #name {
+                type Error = #rkyv_path::discriminant::UnknownDiscriminant;
+
+                #[inline]
+                fn try_from(
+                    value: #repr_ty,
+                ) -> ::core::result::Result<Self, Self::Error> {
+                    #(
+                        if value == Self::#variants as #repr_ty {
+                            return ::core::result::Result::Ok(
+                                Self::#variants,
+                            );
+                        }
+                    )*
+                    ::core::result::Result::Err(
+                        #rkyv_path::discriminant::UnknownDiscriminant::new(
+                            #name_str,
+                            ::core::convert::Into::into(value),
+                        ),
+                    )
+                }
+            }
+
+            impl #rkyv_path::discriminant::ToDiscriminant<#repr_ty> for #name {
+                #[inline]
+                fn to_discriminant(&self) -> #repr_ty {
+                    match self {
+                        #(Self::#variants => Self::#variants as #repr_ty,)*
+                    }
+                }
+            }
+
+            impl #rkyv_path::Archive for #name {
+                type Archived =
+                    #rkyv_path::discriminant::ArchivedDiscriminant<
+                        Self,
+                        #repr_ty,
+                    >;
+                type Resolver = ();
+
+                #[inline]
+                fn resolve(
+                    &self,
+                    _: Self::Resolver,
+                    out: #rkyv_path::Place<Self::Archived>,
+                ) {
+                    #rkyv_path::discriminant::ArchivedDiscriminant::
+                        resolve_from_enum(self, out);
+                }
+            }
+        },
+    ))
+}
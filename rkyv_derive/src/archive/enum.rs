@@ -1,20 +1,22 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Literal, TokenStream};
 use quote::quote;
 use syn::{
     parse_quote, spanned::Spanned as _, Data, DataEnum, DeriveInput, Error,
-    Fields, Ident,
+    Fields, Ident, Path,
 };
 
 use crate::{
     archive::{
-        archived_doc, enum_field_doc, enum_resolver_field_doc,
-        field_archive_attrs, printing::Printing, resolver_doc,
-        resolver_variant_doc, variant_doc,
+        archived_doc, diff::generate_enum_diff_impl,
+        discriminant::impl_discriminant_enum, enum_field_doc,
+        enum_resolver_field_doc, field_archive_attrs, printing::Printing,
+        project::generate_enum_project_def, resolver_doc, resolver_variant_doc,
+        soa::generate_soa_def, variant_doc,
     },
     attributes::Attributes,
     util::{
-        archive_bound, archived, is_not_omitted, members_starting_at, resolve,
-        resolver, strip_raw,
+        archive_bound, archived, is_not_omitted, members_starting_at,
+        mentions_generics, resolve, resolver, strip_raw,
     },
 };
 
@@ -28,49 +30,118 @@ pub fn impl_enum(
         _ => unreachable!(),
     };
 
-    if data.variants.len() > 256 {
+    if let Some(transparent) = &attributes.transparent {
         return Err(Error::new_spanned(
-            &input.ident,
-            "enums with more than 256 variants cannot derive Archive",
+            transparent,
+            "`transparent` may only be used on structs",
         ));
     }
 
-    let rkyv_path = &printing.rkyv_path;
+    if let Some(as_discriminant) = &attributes.as_discriminant {
+        return impl_discriminant_enum(
+            input,
+            attributes,
+            printing,
+            data,
+            as_discriminant,
+        );
+    }
+
+    let tag = tag_repr(input, attributes, data)?;
+    if tag != "u8" && attributes.archive_as.is_some() {
+        return Err(Error::new_spanned(
+            &tag,
+            "enums with tags wider than u8 may not be used with `as = \"..\"` \
+             because the archived type's discriminants must be byte-swapped",
+        ));
+    }
 
-    let where_clause = input.generics.make_where_clause();
+    let rkyv_path = &printing.rkyv_path;
 
-    for field in data
+    let archive_bounds = data
         .variants
         .iter()
         .flat_map(|v| v.fields.iter())
         .filter(is_not_omitted)
-    {
-        where_clause
-            .predicates
-            .push(archive_bound(rkyv_path, field)?);
-    }
+        .filter(|field| mentions_generics(&input.generics, field))
+        .map(|field| archive_bound(rkyv_path, field))
+        .collect::<Result<Vec<_>, Error>>()?;
+    input
+        .generics
+        .make_where_clause()
+        .predicates
+        .extend(archive_bounds);
 
     let (impl_generics, ty_generics, where_clause) =
         input.generics.split_for_impl();
     let where_clause = where_clause.unwrap();
 
+    let discriminants = tag_discriminants(data, &tag, rkyv_path);
+
     let archived_def = attributes
         .archive_as
         .is_none()
-        .then(|| generate_archived_def(input, attributes, printing, data))
+        .then(|| {
+            generate_archived_def(
+                input,
+                attributes,
+                printing,
+                data,
+                &tag,
+                &discriminants,
+            )
+        })
+        .transpose()?;
+
+    let (soa_types, soa_impls) = match &attributes.soa {
+        Some(soa) => {
+            let (types, impls) =
+                generate_soa_def(input, attributes, printing, data, soa)?;
+            (Some(types), Some(impls))
+        }
+        None => (None, None),
+    };
+
+    // The struct of arrays returns the same enum of references as `as_ref`.
+    let as_ref_def = attributes
+        .derive_as_ref
+        .as_ref()
+        .or(attributes.soa.as_ref())
+        .map(|path| {
+            generate_as_ref_def(
+                input,
+                printing,
+                data,
+                path,
+                attributes.derive_as_ref.is_some(),
+            )
+        })
+        .transpose()?;
+
+    let project_def = attributes
+        .derive_project
+        .as_ref()
+        .map(|derive_project| {
+            generate_enum_project_def(input, printing, data, derive_project)
+        })
+        .transpose()?;
+
+    let diff_impl = attributes
+        .derive_diff
+        .is_some()
+        .then(|| generate_enum_diff_impl(input, printing, data))
         .transpose()?;
 
     let resolver_def = generate_resolver_def(input, printing, data)?;
     let resolve_arms = generate_resolve_arms(input, printing, data)?;
 
-    let archived_variant_tags = data.variants.iter().map(|v| {
-        let variant = &v.ident;
-        let discriminant = v
-            .discriminant
-            .as_ref()
-            .map(|(eq, expr)| quote! { #eq #expr });
-        quote! { #variant #discriminant }
-    });
+    let archived_variant_tags =
+        data.variants.iter().zip(discriminants.iter()).map(
+            |(v, discriminant)| {
+                let variant = &v.ident;
+                quote! { #variant #discriminant }
+            },
+        );
 
     let archived_variant_structs =
         generate_variant_structs(input, printing, data)?;
@@ -83,8 +154,9 @@ pub fn impl_enum(
                 partial_eq_impl =
                     Some(generate_partial_eq_impl(input, data, printing)?);
             } else if compare.is_ident("PartialOrd") {
-                partial_ord_impl =
-                    Some(generate_partial_ord_impl(input, data, printing)?);
+                partial_ord_impl = Some(generate_partial_ord_impl(
+                    input, data, printing, &tag,
+                )?);
             } else {
                 return Err(Error::new_spanned(
                     compare,
@@ -102,18 +174,23 @@ pub fn impl_enum(
     Ok((
         quote! {
             #archived_def
+            #as_ref_def
+            #project_def
+            #soa_types
             #resolver_def
         },
         quote! {
-            #[derive(PartialEq, PartialOrd)]
-            #[repr(u8)]
+            #[derive(::core::cmp::PartialEq, ::core::cmp::PartialOrd)]
+            #[repr(#tag)]
             enum ArchivedTag {
                 #(#archived_variant_tags,)*
             }
 
             #(#archived_variant_structs)*
 
-            impl #impl_generics Archive for #name #ty_generics #where_clause {
+            impl #impl_generics #rkyv_path::Archive for #name #ty_generics
+            #where_clause
+            {
                 type Archived = #archived_type;
                 type Resolver = #resolver_name #ty_generics;
 
@@ -122,8 +199,10 @@ pub fn impl_enum(
                 #[allow(clippy::unit_arg)]
                 fn resolve(
                     &self,
-                    resolver: <Self as Archive>::Resolver,
-                    out: #rkyv_path::Place<<Self as Archive>::Archived>,
+                    resolver: <Self as #rkyv_path::Archive>::Resolver,
+                    out: #rkyv_path::Place<
+                        <Self as #rkyv_path::Archive>::Archived,
+                    >,
                 ) {
                     match resolver {
                         #(#resolve_arms,)*
@@ -131,17 +210,102 @@ pub fn impl_enum(
                 }
             }
 
+            #soa_impls
+
             #partial_eq_impl
             #partial_ord_impl
+            #diff_impl
         },
     ))
 }
 
+/// Returns the archived tag type of the enum, which is the smallest of `u8`,
+/// `u16`, and `u32` that can fit every variant unless one is specified with
+/// `repr(...)`.
+fn tag_repr(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    data: &DataEnum,
+) -> Result<Ident, Error> {
+    let variants = data.variants.len();
+    let repr = match &attributes.repr {
+        Some(repr) => repr.clone(),
+        None if variants <= 1 << 8 => Ident::new("u8", input.ident.span()),
+        None if variants <= 1 << 16 => Ident::new("u16", input.ident.span()),
+        None => Ident::new("u32", input.ident.span()),
+    };
+
+    let max_variants = match repr.to_string().as_str() {
+        "u8" => 1 << 8,
+        "u16" => 1 << 16,
+        _ => usize::MAX,
+    };
+    if variants > max_variants {
+        return Err(Error::new_spanned(
+            &repr,
+            format!(
+                "enums with more than {} variants cannot be archived with a \
+                 `repr({})` tag",
+                max_variants, repr,
+            ),
+        ));
+    }
+
+    Ok(repr)
+}
+
+/// Returns the discriminants of the archived enum's variants.
+///
+/// Multibyte tags are stored with the endianness of the archive, so their
+/// discriminants are converted with the `primitive::archived_tag_*` functions.
+/// Every variant is given an explicit discriminant to do so, continuing from
+/// the last explicit discriminant of the original enum.
+fn tag_discriminants(
+    data: &DataEnum,
+    tag: &Ident,
+    rkyv_path: &Path,
+) -> Vec<Option<TokenStream>> {
+    if tag == "u8" {
+        return data
+            .variants
+            .iter()
+            .map(|v| {
+                v.discriminant
+                    .as_ref()
+                    .map(|(eq, expr)| quote! { #eq #expr })
+            })
+            .collect();
+    }
+
+    let tag_fn = Ident::new(&format!("archived_tag_{}", tag), tag.span());
+    let mut base = quote! { 0 };
+    let mut offset = 0;
+    data.variants
+        .iter()
+        .map(|v| {
+            if let Some((_, expr)) = &v.discriminant {
+                base = quote! { #expr };
+                offset = 0;
+            }
+            let value = if offset == 0 {
+                base.clone()
+            } else {
+                let offset = Literal::usize_unsuffixed(offset);
+                quote! { (#base) + #offset }
+            };
+            offset += 1;
+            Some(quote! { = #rkyv_path::primitive::#tag_fn(#value) })
+        })
+        .collect()
+}
+
 fn generate_archived_def(
     input: &DeriveInput,
     attributes: &Attributes,
     printing: &Printing,
     data: &DataEnum,
+    tag: &Ident,
+    discriminants: &[Option<TokenStream>],
 ) -> Result<TokenStream, Error> {
     let name = &input.ident;
     let rkyv_path = &printing.rkyv_path;
@@ -149,12 +313,9 @@ fn generate_archived_def(
     let archived_variants = data
         .variants
         .iter()
-        .map(|v| {
+        .zip(discriminants.iter())
+        .map(|(v, discriminant)| {
             let variant = &v.ident;
-            let discriminant = v
-                .discriminant
-                .as_ref()
-                .map(|(eq, expr)| quote! { #eq #expr });
 
             let variant_doc = variant_doc(name, variant);
 
@@ -227,7 +388,7 @@ fn generate_archived_def(
     let archived_doc = archived_doc(&input.ident);
     let archive_attrs = &printing.archive_attrs;
 
-    let vis = &input.vis;
+    let vis = &printing.archived_vis;
     let archived_name = &printing.archived_name;
     let generics = &input.generics;
     let (impl_generics, ty_generics, where_clause) =
@@ -237,7 +398,7 @@ fn generate_archived_def(
         #[automatically_derived]
         #[doc = #archived_doc]
         #(#archive_attrs)*
-        #[repr(u8)]
+        #[repr(#tag)]
         #vis enum #archived_name #generics #where_clause {
             #(#archived_variants,)*
         }
@@ -251,6 +412,159 @@ fn generate_archived_def(
     })
 }
 
+fn generate_as_ref_def(
+    input: &DeriveInput,
+    printing: &Printing,
+    data: &DataEnum,
+    derive_as_ref: &Path,
+    with_as_ref: bool,
+) -> Result<TokenStream, Error> {
+    if data.variants.iter().all(|v| v.fields.is_empty()) {
+        return Err(Error::new_spanned(
+            derive_as_ref,
+            "`derive_as_ref` may only be used on enums with at least one field",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let archived_name = &printing.archived_name;
+    let ref_name = Ident::new(
+        &format!("{}Ref", strip_raw(archived_name)),
+        archived_name.span(),
+    );
+
+    let mut ref_variants = Vec::new();
+    let mut as_ref_arms = Vec::new();
+    for v in data.variants.iter() {
+        let variant = &v.ident;
+        let variant_doc = variant_doc(name, variant);
+
+        match v.fields {
+            Fields::Named(ref fields) => {
+                let field_names = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect::<Vec<_>>();
+                let field_defs = fields
+                    .named
+                    .iter()
+                    .map(|f| {
+                        let field_name = f.ident.as_ref().unwrap();
+                        let field_doc =
+                            enum_field_doc(name, variant, field_name);
+                        let archived = archived(rkyv_path, f)?;
+                        Ok(quote! {
+                            #[doc = #field_doc]
+                            #field_name: &'__a #archived
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                ref_variants.push(quote! {
+                    #[doc = #variant_doc]
+                    #[allow(dead_code)]
+                    #variant {
+                        #(#field_defs,)*
+                    }
+                });
+                as_ref_arms.push(quote! {
+                    Self::#variant { #(#field_names,)* } => {
+                        #ref_name::#variant { #(#field_names,)* }
+                    }
+                });
+            }
+            Fields::Unnamed(ref fields) => {
+                let bindings = fields
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| Ident::new(&format!("field_{}", i), f.span()))
+                    .collect::<Vec<_>>();
+                let field_defs = fields
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| {
+                        let field_doc = enum_field_doc(name, variant, &i);
+                        let archived = archived(rkyv_path, f)?;
+                        Ok(quote! {
+                            #[doc = #field_doc]
+                            &'__a #archived
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                ref_variants.push(quote! {
+                    #[doc = #variant_doc]
+                    #[allow(dead_code)]
+                    #variant(#(#field_defs,)*)
+                });
+                as_ref_arms.push(quote! {
+                    Self::#variant(#(#bindings,)*) => {
+                        #ref_name::#variant(#(#bindings,)*)
+                    }
+                });
+            }
+            Fields::Unit => {
+                ref_variants.push(quote! {
+                    #[doc = #variant_doc]
+                    #[allow(dead_code)]
+                    #variant
+                });
+                as_ref_arms.push(quote! {
+                    Self::#variant => #ref_name::#variant
+                });
+            }
+        }
+    }
+
+    let ref_doc = format!(
+        "An enum of references to the fields of an archived [`{}`]",
+        name,
+    );
+    let as_ref_doc = format!(
+        "Converts from `&{}` to `{}`, which holds references to the fields of \
+         each variant",
+        archived_name, ref_name,
+    );
+
+    let vis = &printing.archived_vis;
+    let generics = &input.generics;
+    let mut ref_generics = generics.clone();
+    ref_generics.params.insert(0, parse_quote! { '__a });
+    let (_, ref_ty_generics, _) = ref_generics.split_for_impl();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let as_ref_impl = with_as_ref.then(|| {
+        quote! {
+            #[automatically_derived]
+            impl #impl_generics #archived_name #ty_generics #where_clause {
+                #[doc = #as_ref_doc]
+                #[inline]
+                #vis fn as_ref<'__a>(
+                    &'__a self,
+                ) -> #ref_name #ref_ty_generics {
+                    match self {
+                        #(#as_ref_arms,)*
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #[automatically_derived]
+        #[doc = #ref_doc]
+        #vis enum #ref_name #ref_generics #where_clause {
+            #(#ref_variants,)*
+        }
+
+        #as_ref_impl
+    })
+}
+
 fn generate_resolver_def(
     input: &DeriveInput,
     printing: &Printing,
@@ -325,7 +639,7 @@ fn generate_resolver_def(
 
     let resolver_doc = resolver_doc(name);
 
-    let vis = &input.vis;
+    let vis = &printing.resolver_vis;
     let resolver_name = &printing.resolver_name;
     let generics = &input.generics;
     let where_clause = generics.where_clause.as_ref().unwrap();
@@ -474,8 +788,8 @@ fn generate_resolve_arms(
                         let out = unsafe {
                             out.cast_unchecked::<ArchivedTag>()
                         };
-                        // SAFETY: `ArchivedTag` is `repr(u8)` and so is always
-                        // initialized.
+                        // SAFETY: `ArchivedTag` is a primitive repr and so is
+                        // always initialized.
                         unsafe {
                             out.write_unchecked(ArchivedTag::#variant);
                         }
@@ -522,7 +836,8 @@ fn generate_variant_structs(
                         struct #archived_variant_name #generics #where_clause {
                             __tag: ArchivedTag,
                             #(#fields,)*
-                            __phantom: PhantomData<#name #ty_generics>,
+                            __phantom:
+                                ::core::marker::PhantomData<#name #ty_generics>,
                         }
                     })
                 }
@@ -540,7 +855,7 @@ fn generate_variant_structs(
                         struct #archived_variant_name #generics (
                             ArchivedTag,
                             #(#fields,)*
-                            PhantomData<#name #ty_generics>,
+                            ::core::marker::PhantomData<#name #ty_generics>,
                         ) #where_clause;
                     })
                 }
@@ -568,7 +883,7 @@ fn generate_partial_eq_impl(
         let archived = archived(&printing.rkyv_path, field)?;
         partial_eq_where
             .predicates
-            .push(parse_quote! { #archived: PartialEq<#ty> });
+            .push(parse_quote! { #archived: ::core::cmp::PartialEq<#ty> });
     }
 
     let archived_name = &printing.archived_name;
@@ -602,7 +917,10 @@ fn generate_partial_eq_impl(
                     } => match other {
                         #archived_name::#variant {
                             #(#field_names: #other_fields,)*
-                        } => true #(&& #other_fields.eq(#self_fields))*,
+                        } => true #(&& ::core::cmp::PartialEq::eq(
+                            #other_fields,
+                            #self_fields,
+                        ))*,
                         #[allow(unreachable_patterns)]
                         _ => false,
                     }
@@ -612,7 +930,10 @@ fn generate_partial_eq_impl(
                 quote! {
                     #name::#variant(#(#self_fields,)*) => match other {
                         #archived_name::#variant(#(#other_fields,)*) => {
-                            true #(&& #other_fields.eq(#self_fields))*
+                            true #(&& ::core::cmp::PartialEq::eq(
+                                #other_fields,
+                                #self_fields,
+                            ))*
                         }
                         #[allow(unreachable_patterns)]
                         _ => false,
@@ -630,21 +951,26 @@ fn generate_partial_eq_impl(
     });
 
     Ok(quote! {
-        impl #impl_generics PartialEq<#archived_type> for #name #ty_generics
+        impl #impl_generics ::core::cmp::PartialEq<#archived_type>
+            for #name #ty_generics
         #partial_eq_where
         {
-            fn eq(&self, other: &#archived_type) -> bool {
+            fn eq(&self, other: &#archived_type) -> ::core::primitive::bool {
                 match self {
                     #(#variant_impls,)*
                 }
             }
         }
 
-        impl #impl_generics PartialEq<#name #ty_generics> for #archived_type
+        impl #impl_generics ::core::cmp::PartialEq<#name #ty_generics>
+            for #archived_type
         #partial_eq_where
         {
-            fn eq(&self, other: &#name #ty_generics) -> bool {
-                other.eq(self)
+            fn eq(
+                &self,
+                other: &#name #ty_generics,
+            ) -> ::core::primitive::bool {
+                ::core::cmp::PartialEq::eq(other, self)
             }
         }
     })
@@ -654,6 +980,7 @@ fn generate_partial_ord_impl(
     input: &DeriveInput,
     data: &DataEnum,
     printing: &Printing,
+    tag: &Ident,
 ) -> Result<TokenStream, Error> {
     let mut partial_ord_where =
         input.generics.where_clause.as_ref().unwrap().clone();
@@ -668,7 +995,7 @@ fn generate_partial_ord_impl(
         let archived = archived(&printing.rkyv_path, field)?;
         partial_ord_where
             .predicates
-            .push(parse_quote! { #archived: PartialOrd<#ty> });
+            .push(parse_quote! { #archived: ::core::cmp::PartialOrd<#ty> });
     }
 
     let archived_name = &printing.archived_name;
@@ -676,31 +1003,50 @@ fn generate_partial_ord_impl(
     let name = &input.ident;
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
 
+    // Multibyte tags are byte-swapped on some targets, so they're converted
+    // back before they're compared.
+    let tag_value = |variant: &Ident| {
+        if tag == "u8" {
+            quote! { ArchivedTag::#variant }
+        } else {
+            let rkyv_path = &printing.rkyv_path;
+            let tag_fn =
+                Ident::new(&format!("archived_tag_{}", tag), tag.span());
+            quote! {
+                #rkyv_path::primitive::#tag_fn(
+                    ArchivedTag::#variant as ::core::primitive::#tag
+                )
+            }
+        }
+    };
+
     let self_disc = data.variants.iter().map(|v| {
         let variant = &v.ident;
+        let value = tag_value(variant);
         match v.fields {
             Fields::Named(_) => quote! {
-                #name::#variant { .. } => ArchivedTag::#variant
+                #name::#variant { .. } => #value
             },
             Fields::Unnamed(_) => quote! {
-                #name::#variant ( .. ) => ArchivedTag::#variant
+                #name::#variant ( .. ) => #value
             },
             Fields::Unit => quote! {
-                #name::#variant => ArchivedTag::#variant
+                #name::#variant => #value
             },
         }
     });
     let other_disc = data.variants.iter().map(|v| {
         let variant = &v.ident;
+        let value = tag_value(variant);
         match v.fields {
             Fields::Named(_) => quote! {
-                #archived_name::#variant { .. } => ArchivedTag::#variant
+                #archived_name::#variant { .. } => #value
             },
             Fields::Unnamed(_) => quote! {
-                #archived_name::#variant ( .. ) => ArchivedTag::#variant
+                #archived_name::#variant ( .. ) => #value
             },
             Fields::Unit => quote! {
-                #archived_name::#variant => ArchivedTag::#variant
+                #archived_name::#variant => #value
             },
         }
     });
@@ -733,14 +1079,21 @@ fn generate_partial_ord_impl(
                             #(#field_names: #other_fields,)*
                         } => {
                             #(
-                                match #other_fields.partial_cmp(#self_fields) {
-                                    Some(::core::cmp::Ordering::Equal) => (),
+                                match ::core::cmp::PartialOrd::partial_cmp(
+                                    #other_fields,
+                                    #self_fields,
+                                ) {
+                                    ::core::option::Option::Some(
+                                        ::core::cmp::Ordering::Equal,
+                                    ) => (),
                                     cmp => return cmp.map(
                                         ::core::cmp::Ordering::reverse
                                     ),
                                 }
                             )*
-                            Some(::core::cmp::Ordering::Equal)
+                            ::core::option::Option::Some(
+                                ::core::cmp::Ordering::Equal,
+                            )
                         }
                         #[allow(unreachable_patterns)]
                         _ => unsafe { ::core::hint::unreachable_unchecked() },
@@ -752,14 +1105,21 @@ fn generate_partial_ord_impl(
                     #name::#variant(#(#self_fields,)*) => match other {
                         #archived_name::#variant(#(#other_fields,)*) => {
                             #(
-                                match #other_fields.partial_cmp(#self_fields) {
-                                    Some(::core::cmp::Ordering::Equal) => (),
+                                match ::core::cmp::PartialOrd::partial_cmp(
+                                    #other_fields,
+                                    #self_fields,
+                                ) {
+                                    ::core::option::Option::Some(
+                                        ::core::cmp::Ordering::Equal,
+                                    ) => (),
                                     cmp => return cmp.map(
                                         ::core::cmp::Ordering::reverse
                                     ),
                                 }
                             )*
-                            Some(::core::cmp::Ordering::Equal)
+                            ::core::option::Option::Some(
+                                ::core::cmp::Ordering::Equal,
+                            )
                         }
                         #[allow(unreachable_patterns)]
                         _ => unsafe { ::core::hint::unreachable_unchecked() },
@@ -769,7 +1129,9 @@ fn generate_partial_ord_impl(
             Fields::Unit => quote! {
                 #name::#variant => match other {
                     #archived_name::#variant => {
-                        Some(::core::cmp::Ordering::Equal)
+                        ::core::option::Option::Some(
+                            ::core::cmp::Ordering::Equal,
+                        )
                     }
                     #[allow(unreachable_patterns)]
                     _ => unsafe { ::core::hint::unreachable_unchecked() },
@@ -779,13 +1141,14 @@ fn generate_partial_ord_impl(
     });
 
     Ok(quote! {
-        impl #impl_generics PartialOrd<#archived_type> for #name #ty_generics
+        impl #impl_generics ::core::cmp::PartialOrd<#archived_type>
+            for #name #ty_generics
         #partial_ord_where
         {
             fn partial_cmp(
                 &self,
                 other: &#archived_type,
-            ) -> Option<::core::cmp::Ordering> {
+            ) -> ::core::option::Option<::core::cmp::Ordering> {
                 let self_disc = match self { #(#self_disc,)* };
                 let other_disc = match other { #(#other_disc,)* };
                 if self_disc == other_disc {
@@ -793,27 +1156,24 @@ fn generate_partial_ord_impl(
                         #(#variant_impls,)*
                     }
                 } else {
-                    self_disc.partial_cmp(&other_disc)
+                    ::core::cmp::PartialOrd::partial_cmp(
+                        &self_disc,
+                        &other_disc,
+                    )
                 }
             }
         }
 
-        impl #impl_generics PartialOrd<#name #ty_generics> for #archived_type
+        impl #impl_generics ::core::cmp::PartialOrd<#name #ty_generics>
+            for #archived_type
         #partial_ord_where
         {
             fn partial_cmp(
                 &self,
                 other: &#name #ty_generics,
-            ) -> Option<::core::cmp::Ordering> {
-                match other.partial_cmp(self) {
-                    Some(::core::cmp::Ordering::Less) => {
-                        Some(::core::cmp::Ordering::Greater)
-                    }
-                    Some(::core::cmp::Ordering::Greater) => {
-                        Some(::core::cmp::Ordering::Less)
-                    }
-                    cmp => cmp,
-                }
+            ) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::cmp::PartialOrd::partial_cmp(other, self)
+                    .map(::core::cmp::Ordering::reverse)
             }
         }
     })
@@ -0,0 +1,249 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_quote, punctuated::Punctuated, DeriveInput, Error, FieldsNamed,
+    Generics, Ident,
+};
+
+use crate::{
+    archive::{
+        archived_doc, printing::Printing, resolver_doc, struct_field_doc,
+    },
+    attributes::Attributes,
+    util::{archived, archived_fields_name, is_not_omitted},
+};
+
+fn fields_doc(name: &Ident) -> String {
+    format!("The archived fields of an extensible [`{}`]", name)
+}
+
+fn accessor_doc(name: &Ident, field_name: &Ident) -> String {
+    format!(
+        "Returns the archived counterpart of [`{}::{}`], or `None` if the \
+         archive was written by a version without it",
+        name, field_name,
+    )
+}
+
+pub fn impl_extensible_struct(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+    fields: &FieldsNamed,
+) -> Result<(TokenStream, TokenStream), Error> {
+    if let Some(compares) = &attributes.compares {
+        return Err(Error::new_spanned(
+            compares,
+            "compare(...) may not be used with `extensible` because fields \
+             may be missing from the archived type",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let archived_vis = &printing.archived_vis;
+    let resolver_vis = &printing.resolver_vis;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let where_clause = where_clause.unwrap();
+
+    let archived_name = &printing.archived_name;
+    let archived_type = &printing.archived_type;
+    let resolver_name = &printing.resolver_name;
+    let fields_name = archived_fields_name(name, attributes);
+    let fields_type = quote! { #fields_name #ty_generics };
+
+    let mut field_defs = Vec::new();
+    let mut accessors = Vec::new();
+    for field in fields.named.iter() {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_ty = archived(rkyv_path, field)?;
+        let field_vis = &field.vis;
+
+        let field_doc = struct_field_doc(name, field_name);
+        field_defs.push(quote! {
+            #[doc = #field_doc]
+            #field_vis #field_name: #field_ty
+        });
+
+        let accessor_doc = accessor_doc(name, field_name);
+        accessors.push(quote! {
+            #[doc = #accessor_doc]
+            #[inline]
+            #field_vis fn #field_name(
+                &self,
+            ) -> ::core::option::Option<&#field_ty> {
+                // SAFETY: The offset is that of a field with the same type.
+                unsafe {
+                    self.fields.get(
+                        ::core::mem::offset_of!(#fields_type, #field_name),
+                    )
+                }
+            }
+        });
+    }
+
+    // The serialized length ends at the last field rather than including
+    // trailing padding, which later versions may fill with new fields.
+    let len = match fields.named.last() {
+        Some(field) => {
+            let field_name = field.ident.as_ref().unwrap();
+            let field_ty = archived(rkyv_path, field)?;
+            quote! {
+                ::core::mem::offset_of!(#fields_type, #field_name)
+                    + ::core::mem::size_of::<#field_ty>()
+            }
+        }
+        None => quote! { 0 },
+    };
+
+    let fields_doc = fields_doc(name);
+    let archived_doc = archived_doc(name);
+    let resolver_doc = resolver_doc(name);
+    let archive_attrs = &printing.archive_attrs;
+
+    let check_fields_impl = if attributes.check_bytes.is_some()
+        && cfg!(feature = "bytecheck")
+    {
+        let mut check_where = where_clause.clone();
+        for field in fields.named.iter().filter(is_not_omitted) {
+            let field_ty = archived(rkyv_path, field)?;
+            check_where.predicates.push(parse_quote! {
+                #field_ty: #rkyv_path::bytecheck::CheckBytes<__C>
+            });
+        }
+        check_where.predicates.push(parse_quote! {
+            <__C as #rkyv_path::rancor::Fallible>::Error:
+                #rkyv_path::rancor::Source
+        });
+
+        let mut impl_input_params = Punctuated::default();
+        impl_input_params.push(parse_quote! {
+            __C: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+        });
+        for param in generics.params.iter() {
+            impl_input_params.push(param.clone());
+        }
+        let impl_input_generics = Generics {
+            lt_token: Some(Default::default()),
+            params: impl_input_params,
+            gt_token: Some(Default::default()),
+            where_clause: None,
+        };
+        let (check_impl_generics, ..) = impl_input_generics.split_for_impl();
+
+        let checks = fields
+            .named
+            .iter()
+            .map(|field| {
+                let field_name = field.ident.as_ref().unwrap();
+                let field_ty = archived(rkyv_path, field)?;
+                Ok(quote! {
+                    // SAFETY: The caller has guaranteed that `ptr` is valid
+                    // for reads of `len` bytes.
+                    unsafe {
+                        #rkyv_path::extensible::check_field::<#field_ty, __C>(
+                            ptr,
+                            ::core::mem::offset_of!(#fields_type, #field_name),
+                            len,
+                            context,
+                        )?;
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        Some(quote! {
+            // SAFETY: Each field which lies within the first `len` bytes is
+            // checked.
+            unsafe impl #check_impl_generics
+                #rkyv_path::extensible::CheckFields<__C> for #fields_type
+            #check_where
+            {
+                unsafe fn check_fields(
+                    ptr: *const ::core::primitive::u8,
+                    len: ::core::primitive::usize,
+                    context: &mut __C,
+                ) -> ::core::result::Result<
+                    (),
+                    <__C as #rkyv_path::rancor::Fallible>::Error,
+                > {
+                    #(#checks)*
+                    ::core::result::Result::Ok(())
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    Ok((
+        quote! {
+            #[automatically_derived]
+            #[doc = #fields_doc]
+            #[repr(C)]
+            #archived_vis struct #fields_name #generics #where_clause {
+                #(#field_defs,)*
+            }
+
+            #[automatically_derived]
+            #[doc = #archived_doc]
+            #(#archive_attrs)*
+            #[repr(transparent)]
+            #archived_vis struct #archived_name #generics #where_clause {
+                fields: #rkyv_path::extensible::ArchivedExtensible<
+                    #fields_type
+                >,
+            }
+
+            // SAFETY: As long as the `Archive` impl holds, the archived
+            // fields are guaranteed to be `Portable`.
+            unsafe impl #impl_generics #rkyv_path::Portable for #fields_type
+            #where_clause
+            {}
+
+            // SAFETY: As long as the `Archive` impl holds, the archived
+            // type is guaranteed to be `Portable`.
+            unsafe impl #impl_generics #rkyv_path::Portable
+                for #archived_name #ty_generics
+            #where_clause
+            {}
+
+            #[automatically_derived]
+            #[doc = #resolver_doc]
+            #resolver_vis struct #resolver_name #generics #where_clause {
+                fields: #rkyv_path::extensible::ExtensibleResolver,
+                _phantom: ::core::marker::PhantomData<#name #ty_generics>,
+            }
+        },
+        quote! {
+            impl #impl_generics #archived_name #ty_generics #where_clause {
+                #(#accessors)*
+            }
+
+            impl #impl_generics #rkyv_path::Archive for #name #ty_generics
+            #where_clause
+            {
+                type Archived = #archived_type;
+                type Resolver = #resolver_name #ty_generics;
+
+                fn resolve(
+                    &self,
+                    resolver: Self::Resolver,
+                    out: #rkyv_path::Place<Self::Archived>,
+                ) {
+                    let field_ptr = unsafe {
+                        ::core::ptr::addr_of_mut!((*out.ptr()).fields)
+                    };
+                    let out_field = unsafe {
+                        #rkyv_path::Place::from_field_unchecked(out, field_ptr)
+                    };
+                    #rkyv_path::extensible::ArchivedExtensible
+                        ::resolve_from_len(#len, resolver.fields, out_field);
+                }
+            }
+
+            #check_fields_impl
+        },
+    ))
+}
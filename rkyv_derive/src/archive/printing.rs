@@ -1,7 +1,7 @@
 use quote::quote;
 use syn::{
     parse_quote, spanned::Spanned as _, Attribute, DeriveInput, Error, Ident,
-    LitStr, Meta, Path, Type,
+    LitStr, Meta, Path, Type, Visibility,
 };
 
 use crate::{attributes::Attributes, util::strip_raw};
@@ -10,7 +10,9 @@ pub struct Printing {
     pub rkyv_path: Path,
     pub archived_name: Ident,
     pub archived_type: Type,
+    pub archived_vis: Visibility,
     pub resolver_name: Ident,
+    pub resolver_vis: Visibility,
     pub archive_attrs: Vec<Attribute>,
 }
 
@@ -34,6 +36,13 @@ impl Printing {
                      because no type is generated",
                 ));
             }
+            if let Some(ref vis) = attributes.archived_vis {
+                return Err(Error::new_spanned(
+                    vis,
+                    "archived_vis = \"...\" may not be used with as = \"...\" \
+                     because no type is generated",
+                ));
+            }
             if let Some(first) = attributes.attrs.first() {
                 return Err(Error::new_spanned(
                     first,
@@ -46,6 +55,39 @@ impl Printing {
             }
         }
 
+        if attributes.transparent.is_some() {
+            if let Some(ident) = attributes
+                .archived
+                .as_ref()
+                .or(attributes.resolver.as_ref())
+            {
+                return Err(Error::new_spanned(
+                    ident,
+                    "archived = \"...\" and resolver = \"...\" may not be \
+                     used with `transparent` because no types are generated",
+                ));
+            }
+            if let Some(vis) = attributes
+                .archived_vis
+                .as_ref()
+                .or(attributes.resolver_vis.as_ref())
+            {
+                return Err(Error::new_spanned(
+                    vis,
+                    "archived_vis = \"...\" and resolver_vis = \"...\" may \
+                     not be used with `transparent` because no types are \
+                     generated",
+                ));
+            }
+            if let Some(first) = attributes.attrs.first() {
+                return Err(Error::new_spanned(
+                    first,
+                    "attributes may not be used with `transparent` because no \
+                     archived type is generated",
+                ));
+            }
+        }
+
         let archived_name = attributes.archived.as_ref().map_or_else(
             || Ident::new(&format!("Archived{}", strip_raw(name)), name.span()),
             |value| value.clone(),
@@ -56,6 +98,15 @@ impl Printing {
             |value| value.clone(),
         );
 
+        let archived_vis = attributes
+            .archived_vis
+            .clone()
+            .unwrap_or_else(|| input.vis.clone());
+        let resolver_vis = attributes
+            .resolver_vis
+            .clone()
+            .unwrap_or_else(|| input.vis.clone());
+
         let archived_type = attributes.archive_as.as_ref().map_or_else(
             || {
                 let (_, ty_generics, _) = input.generics.split_for_impl();
@@ -99,7 +150,9 @@ impl Printing {
             rkyv_path,
             archived_name,
             archived_type,
+            archived_vis,
             resolver_name,
+            resolver_vis,
             archive_attrs,
         })
     }
@@ -0,0 +1,293 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{
+    parse_quote, spanned::Spanned as _, DataEnum, DeriveInput, Error, Fields,
+    Ident, Path,
+};
+
+use crate::{
+    archive::{
+        enum_field_doc, printing::Printing, struct_field_doc, variant_doc,
+    },
+    util::{archived, members, strip_raw},
+};
+
+fn projection_name(printing: &Printing) -> Ident {
+    let archived_name = &printing.archived_name;
+    Ident::new(
+        &format!("{}Projection", strip_raw(archived_name)),
+        archived_name.span(),
+    )
+}
+
+fn project_doc(printing: &Printing, projection_name: &Ident) -> String {
+    format!(
+        "Converts from `Pin<&mut {}>` to `{}`, which holds pinned mutable \
+         references to its fields",
+        printing.archived_name, projection_name,
+    )
+}
+
+fn generate_project_impl(
+    input: &DeriveInput,
+    printing: &Printing,
+    projection_name: &Ident,
+    body: TokenStream,
+) -> TokenStream {
+    let vis = &printing.archived_vis;
+    let archived_name = &printing.archived_name;
+    let generics = &input.generics;
+    let mut projection_generics = generics.clone();
+    projection_generics.params.insert(0, parse_quote! { '__a });
+    let (_, projection_ty_generics, _) = projection_generics.split_for_impl();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let project_doc = project_doc(printing, projection_name);
+
+    quote! {
+        #[automatically_derived]
+        impl #impl_generics #archived_name #ty_generics #where_clause {
+            #[doc = #project_doc]
+            #[inline]
+            #vis fn project<'__a>(
+                self: ::core::pin::Pin<&'__a mut Self>,
+            ) -> #projection_name #projection_ty_generics {
+                // SAFETY: The fields of archived types are structurally
+                // pinned. Archived types are never packed and never move out
+                // of their fields.
+                unsafe {
+                    let this = ::core::pin::Pin::get_unchecked_mut(self);
+                    #body
+                }
+            }
+        }
+    }
+}
+
+pub fn generate_struct_project_def(
+    input: &DeriveInput,
+    printing: &Printing,
+    fields: &Fields,
+    derive_project: &Path,
+) -> Result<TokenStream, Error> {
+    if fields.is_empty() {
+        return Err(Error::new_spanned(
+            derive_project,
+            "`derive_project` may only be used on types with at least one \
+             field",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let projection_name = projection_name(printing);
+
+    let field_defs = members(fields)
+        .map(|(member, field)| {
+            let field_doc = struct_field_doc(name, &quote!(#member));
+            let vis = &field.vis;
+            let archived = archived(rkyv_path, field)?;
+            let ty = quote! { ::core::pin::Pin<&'__a mut #archived> };
+            Ok(match &field.ident {
+                Some(ident) => quote! {
+                    #[doc = #field_doc]
+                    #vis #ident: #ty
+                },
+                None => quote! {
+                    #[doc = #field_doc]
+                    #vis #ty
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let field_projections = members(fields).map(|(member, _)| {
+        quote! {
+            #member: ::core::pin::Pin::new_unchecked(&mut this.#member)
+        }
+    });
+
+    let projection_doc = format!(
+        "A struct of pinned mutable references to the fields of an archived \
+         [`{}`]",
+        name,
+    );
+
+    let vis = &printing.archived_vis;
+    let mut projection_generics = input.generics.clone();
+    projection_generics.params.insert(0, parse_quote! { '__a });
+    let where_clause = &input.generics.where_clause;
+
+    let projection_def = match fields {
+        Fields::Named(_) => quote! {
+            #vis struct #projection_name #projection_generics #where_clause {
+                #(#field_defs,)*
+            }
+        },
+        _ => quote! {
+            #vis struct #projection_name #projection_generics (
+                #(#field_defs,)*
+            ) #where_clause;
+        },
+    };
+    let project_impl = generate_project_impl(
+        input,
+        printing,
+        &projection_name,
+        quote! {
+            #projection_name { #(#field_projections,)* }
+        },
+    );
+
+    Ok(quote! {
+        #[automatically_derived]
+        #[doc = #projection_doc]
+        #projection_def
+
+        #project_impl
+    })
+}
+
+pub fn generate_enum_project_def(
+    input: &DeriveInput,
+    printing: &Printing,
+    data: &DataEnum,
+    derive_project: &Path,
+) -> Result<TokenStream, Error> {
+    if data.variants.iter().all(|v| v.fields.is_empty()) {
+        return Err(Error::new_spanned(
+            derive_project,
+            "`derive_project` may only be used on types with at least one \
+             field",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let archived_name = &printing.archived_name;
+    let projection_name = projection_name(printing);
+
+    let mut projection_variants = Vec::new();
+    let mut project_arms = Vec::new();
+    for v in data.variants.iter() {
+        let variant = &v.ident;
+        let variant_doc = variant_doc(name, variant);
+
+        match v.fields {
+            Fields::Named(ref fields) => {
+                let field_names = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect::<Vec<_>>();
+                let field_defs = fields
+                    .named
+                    .iter()
+                    .map(|f| {
+                        let field_name = f.ident.as_ref().unwrap();
+                        let field_doc =
+                            enum_field_doc(name, variant, field_name);
+                        let archived = archived(rkyv_path, f)?;
+                        Ok(quote! {
+                            #[doc = #field_doc]
+                            #field_name: ::core::pin::Pin<&'__a mut #archived>
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                projection_variants.push(quote! {
+                    #[doc = #variant_doc]
+                    #[allow(dead_code)]
+                    #variant {
+                        #(#field_defs,)*
+                    }
+                });
+                project_arms.push(quote! {
+                    #archived_name::#variant { #(#field_names,)* } => {
+                        #projection_name::#variant {
+                            #(
+                                #field_names:
+                                    ::core::pin::Pin::new_unchecked(
+                                        #field_names,
+                                    ),
+                            )*
+                        }
+                    }
+                });
+            }
+            Fields::Unnamed(ref fields) => {
+                let bindings = fields
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| Ident::new(&format!("field_{}", i), f.span()))
+                    .collect::<Vec<_>>();
+                let field_defs = fields
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| {
+                        let field_doc = enum_field_doc(name, variant, &i);
+                        let archived = archived(rkyv_path, f)?;
+                        Ok(quote! {
+                            #[doc = #field_doc]
+                            ::core::pin::Pin<&'__a mut #archived>
+                        })
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                projection_variants.push(quote! {
+                    #[doc = #variant_doc]
+                    #[allow(dead_code)]
+                    #variant(#(#field_defs,)*)
+                });
+                project_arms.push(quote! {
+                    #archived_name::#variant(#(#bindings,)*) => {
+                        #projection_name::#variant(
+                            #(::core::pin::Pin::new_unchecked(#bindings),)*
+                        )
+                    }
+                });
+            }
+            Fields::Unit => {
+                projection_variants.push(quote! {
+                    #[doc = #variant_doc]
+                    #[allow(dead_code)]
+                    #variant
+                });
+                project_arms.push(quote! {
+                    #archived_name::#variant => #projection_name::#variant
+                });
+            }
+        }
+    }
+
+    let projection_doc = format!(
+        "An enum of pinned mutable references to the fields of an archived \
+         [`{}`]",
+        name,
+    );
+
+    let vis = &printing.archived_vis;
+    let mut projection_generics = input.generics.clone();
+    projection_generics.params.insert(0, parse_quote! { '__a });
+    let where_clause = &input.generics.where_clause;
+    let project_impl = generate_project_impl(
+        input,
+        printing,
+        &projection_name,
+        quote! {
+            match this {
+                #(#project_arms,)*
+            }
+        },
+    );
+
+    Ok(quote! {
+        #[automatically_derived]
+        #[doc = #projection_doc]
+        #vis enum #projection_name #projection_generics #where_clause {
+            #(#projection_variants,)*
+        }
+
+        #project_impl
+    })
+}
@@ -0,0 +1,141 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Fields, Ident, Path};
+
+use crate::{
+    archive::printing::Printing,
+    util::{archived, has_with, is_copy_native, is_phantom_data, strip_raw},
+};
+
+/// Returns the name of the method which projects the field named `field`.
+///
+/// The name is the plural of the field name: "es" is appended to names ending
+/// in "s", "x", "z", "ch", or "sh", "ies" replaces a "y" after a consonant, and
+/// "s" is appended to every other name.
+fn projection_method_name(field: &Ident) -> Ident {
+    let name = strip_raw(field);
+    let is_consonant =
+        |c: char| c.is_ascii_alphabetic() && !"aeiou".contains(c);
+    let plural = if ["s", "x", "z", "ch", "sh"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        format!("{}es", name)
+    } else if let Some(stem) = name
+        .strip_suffix('y')
+        .filter(|stem| stem.ends_with(is_consonant))
+    {
+        format!("{}ies", stem)
+    } else {
+        format!("{}s", name)
+    };
+    // Plurals like "as" are keywords, so they need to be raw identifiers.
+    if syn::parse_str::<Ident>(&plural).is_ok() {
+        Ident::new(&plural, field.span())
+    } else {
+        Ident::new_raw(&plural, field.span())
+    }
+}
+
+pub fn generate_struct_projections_def(
+    input: &DeriveInput,
+    printing: &Printing,
+    fields: &Fields,
+    derive_projections: &Path,
+) -> Result<TokenStream, Error> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`derive_projections` may not be used on structs with generic \
+             parameters",
+        ));
+    }
+    let named = match fields {
+        Fields::Named(named) if !named.named.is_empty() => named,
+        _ => {
+            return Err(Error::new_spanned(
+                derive_projections,
+                "`derive_projections` may only be used on structs with named \
+                 fields",
+            ));
+        }
+    };
+
+    let rkyv_path = &printing.rkyv_path;
+    let vis = &printing.archived_vis;
+    let name = &input.ident;
+    let archived_name = &printing.archived_name;
+    let trait_name = Ident::new(
+        &format!("{}Projections", strip_raw(archived_name)),
+        archived_name.span(),
+    );
+
+    let mut decls = Vec::new();
+    let mut impls = Vec::new();
+    for field in named.named.iter() {
+        if is_phantom_data(&field.ty) && !has_with(field) {
+            continue;
+        }
+
+        let ident = field.ident.as_ref().unwrap();
+        let method = projection_method_name(ident);
+        let archived = archived(rkyv_path, field)?;
+        let (column, doc) = if is_copy_native(field) {
+            (
+                quote! { NativeColumn },
+                format!(
+                    "Returns an iterator which copies the `{}` field out of \
+                     every element",
+                    strip_raw(ident),
+                ),
+            )
+        } else {
+            (
+                quote! { Column },
+                format!(
+                    "Returns an iterator over the `{}` field of every element",
+                    strip_raw(ident),
+                ),
+            )
+        };
+        let ty = quote! {
+            #rkyv_path::column::#column<'_, #archived_name, #archived>
+        };
+
+        decls.push(quote! {
+            #[doc = #doc]
+            fn #method(&self) -> #ty;
+        });
+        impls.push(quote! {
+            #[inline]
+            fn #method(&self) -> #ty {
+                // SAFETY: The offset is the offset of the field, which has
+                // the archived type of the field.
+                unsafe {
+                    #rkyv_path::column::#column::new(
+                        self,
+                        ::core::mem::offset_of!(#archived_name, #ident),
+                    )
+                }
+            }
+        });
+    }
+
+    let trait_doc = format!(
+        "Projections of the fields of every element of a slice of archived \
+         [`{}`]s",
+        name,
+    );
+
+    Ok(quote! {
+        #[doc = #trait_doc]
+        #vis trait #trait_name {
+            #(#decls)*
+        }
+
+        #[automatically_derived]
+        impl #trait_name for [#archived_name] {
+            #(#impls)*
+        }
+    })
+}
@@ -0,0 +1,503 @@
+use proc_macro2::{Literal, TokenStream};
+use quote::quote;
+use syn::{
+    parse_quote, punctuated::Punctuated, DataEnum, DeriveInput, Error, Fields,
+    Generics, Ident, LitStr, Path, WherePredicate,
+};
+
+use crate::{
+    archive::printing::Printing,
+    attributes::Attributes,
+    util::{
+        archived, deserialize, deserialize_bound, is_not_omitted, members,
+        resolve, serialize, serialize_bound, strip_raw,
+    },
+};
+
+struct Column<'a> {
+    ident: Ident,
+    variant: &'a Ident,
+    pattern: TokenStream,
+    archived: TokenStream,
+}
+
+fn variant_pattern(variant: &Ident, fields: &Fields) -> TokenStream {
+    match fields {
+        Fields::Named(_) => quote! { Self::#variant { .. } },
+        Fields::Unnamed(_) => quote! { Self::#variant(..) },
+        Fields::Unit => quote! { Self::#variant },
+    }
+}
+
+fn impl_generics_with(
+    generics: &Generics,
+    param: TokenStream,
+    predicates: Vec<WherePredicate>,
+) -> Generics {
+    let mut params = Punctuated::new();
+    params.push(parse_quote! { #param });
+    for param in generics.params.iter() {
+        params.push(param.clone());
+    }
+    let mut where_clause = generics.where_clause.clone().unwrap();
+    where_clause.predicates.extend(predicates);
+    Generics {
+        lt_token: Some(Default::default()),
+        params,
+        gt_token: Some(Default::default()),
+        where_clause: Some(where_clause),
+    }
+}
+
+pub fn generate_soa_def(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+    data: &DataEnum,
+    soa: &Path,
+) -> Result<(TokenStream, TokenStream), Error> {
+    if data.variants.iter().all(|v| v.fields.is_empty()) {
+        return Err(Error::new_spanned(
+            soa,
+            "`soa` may only be used on enums with at least one field",
+        ));
+    }
+    if data.variants.len() > 256 {
+        return Err(Error::new_spanned(
+            soa,
+            "`soa` may only be used on enums with at most 256 variants",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let vis = &printing.archived_vis;
+    let resolver_vis = &printing.resolver_vis;
+    let name = &input.ident;
+    let archived_name = &printing.archived_name;
+    let soa_name = Ident::new(
+        &format!("{}Soa", strip_raw(archived_name)),
+        archived_name.span(),
+    );
+    let soa_resolver_name =
+        Ident::new(&format!("{}SoaResolver", strip_raw(name)), name.span());
+    let ref_name = Ident::new(
+        &format!("{}Ref", strip_raw(archived_name)),
+        archived_name.span(),
+    );
+
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let mut ref_generics = generics.clone();
+    ref_generics.params.insert(0, parse_quote! { '__a });
+    let (_, ref_ty_generics, _) = ref_generics.split_for_impl();
+
+    let mut columns = Vec::new();
+    let mut variant_columns = Vec::new();
+    let mut tag_arms = Vec::new();
+    let mut get_arms = Vec::new();
+    let mut deserialize_arms = Vec::new();
+    let mut serialize_where = Vec::new();
+    let mut deserialize_where = Vec::new();
+    for (i, v) in data.variants.iter().enumerate() {
+        let variant = &v.ident;
+        let tag = Literal::u8_suffixed(i as u8);
+        let pattern = variant_pattern(variant, &v.fields);
+        tag_arms.push(quote! { #pattern => #tag });
+
+        let mut idents = Vec::new();
+        for (position, (member, field)) in members(&v.fields).enumerate() {
+            let ident = Ident::new(
+                &format!(
+                    "{}_{}",
+                    strip_raw(variant),
+                    quote!(#member).to_string().trim_start_matches("r#"),
+                ),
+                variant.span(),
+            );
+            let pattern = match &v.fields {
+                Fields::Named(_) => quote! {
+                    Self::#variant { #member: __field, .. }
+                },
+                _ => {
+                    let skipped = (0..position).map(|_| quote! { _ });
+                    quote! { Self::#variant(#(#skipped,)* __field, ..) }
+                }
+            };
+            if is_not_omitted(&field) {
+                serialize_where.push(serialize_bound(rkyv_path, field)?);
+                deserialize_where.push(deserialize_bound(rkyv_path, field)?);
+            }
+            idents.push(ident.clone());
+            columns.push((
+                Column {
+                    ident,
+                    variant,
+                    pattern,
+                    archived: archived(rkyv_path, field)?,
+                },
+                field,
+            ));
+        }
+
+        let deserializes = v
+            .fields
+            .iter()
+            .map(|f| deserialize(rkyv_path, f))
+            .collect::<Result<Vec<_>, Error>>()?;
+        match &v.fields {
+            Fields::Named(fields) => {
+                let field_names = fields
+                    .named
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect::<Vec<_>>();
+                get_arms.push(quote! {
+                    #tag => #ref_name::#variant {
+                        #(#field_names: self.#idents.get(index)?,)*
+                    }
+                });
+                deserialize_arms.push(quote! {
+                    #ref_name::#variant { #(#field_names,)* } => {
+                        Self::#variant {
+                            #(
+                                #field_names: #deserializes(
+                                    #field_names,
+                                    deserializer,
+                                )?,
+                            )*
+                        }
+                    }
+                });
+            }
+            Fields::Unnamed(_) => {
+                let bindings = (0..idents.len())
+                    .map(|i| {
+                        Ident::new(&format!("field_{}", i), v.ident.span())
+                    })
+                    .collect::<Vec<_>>();
+                get_arms.push(quote! {
+                    #tag => #ref_name::#variant(
+                        #(self.#idents.get(index)?,)*
+                    )
+                });
+                deserialize_arms.push(quote! {
+                    #ref_name::#variant(#(#bindings,)*) => {
+                        Self::#variant(
+                            #(#deserializes(#bindings, deserializer)?,)*
+                        )
+                    }
+                });
+            }
+            Fields::Unit => {
+                get_arms.push(quote! { #tag => #ref_name::#variant });
+                deserialize_arms.push(quote! {
+                    #ref_name::#variant => Self::#variant
+                });
+            }
+        }
+        variant_columns.push(idents);
+    }
+
+    let column_idents =
+        columns.iter().map(|(c, _)| &c.ident).collect::<Vec<_>>();
+    let column_defs = columns.iter().map(|(c, _)| {
+        let ident = &c.ident;
+        let archived = &c.archived;
+        quote! { #ident: #rkyv_path::vec::ArchivedVec<#archived> }
+    });
+    let column_resolves = columns.iter().map(|(c, _)| {
+        let ident = &c.ident;
+        let variant = c.variant;
+        let fields = &data
+            .variants
+            .iter()
+            .find(|v| &v.ident == variant)
+            .unwrap()
+            .fields;
+        let pattern = variant_pattern(variant, fields);
+        quote! {
+            let field_ptr = unsafe {
+                ::core::ptr::addr_of_mut!((*out.ptr()).#ident)
+            };
+            let out_field = unsafe {
+                #rkyv_path::Place::from_field_unchecked(out, field_ptr)
+            };
+            let len = ::core::iter::Iterator::count(
+                ::core::iter::Iterator::filter(values.iter(), |value| {
+                    ::core::matches!(value, #pattern)
+                }),
+            );
+            #rkyv_path::vec::ArchivedVec::resolve_from_len(
+                len,
+                resolver.#ident,
+                out_field,
+            );
+        }
+    });
+    let column_serializes = columns
+        .iter()
+        .map(|(c, field)| {
+            let ident = &c.ident;
+            let pattern = &c.pattern;
+            let serialize = serialize(rkyv_path, field)?;
+            let resolve = resolve(rkyv_path, field)?;
+            Ok(quote! {
+                let #ident = #rkyv_path::soa::serialize_column(
+                    ::core::iter::Iterator::filter_map(values.iter(), |value| {
+                        match value {
+                            #pattern => ::core::option::Option::Some(__field),
+                            #[allow(unreachable_patterns)]
+                            _ => ::core::option::Option::None,
+                        }
+                    }),
+                    #serialize,
+                    #resolve,
+                    serializer,
+                )?;
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let (check_bytes_attrs, verify_impl) =
+        if attributes.check_bytes.is_some() && cfg!(feature = "bytecheck") {
+            let path = quote!(#rkyv_path::bytecheck).to_string();
+            let path_lit_str = LitStr::new(&path, name.span());
+            let verify_generics = impl_generics_with(
+                generics,
+                quote! {
+                    __C: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+                },
+                vec![parse_quote! {
+                    <__C as #rkyv_path::rancor::Fallible>::Error:
+                        #rkyv_path::rancor::Source
+                }],
+            );
+            let (verify_impl_generics, _, verify_where_clause) =
+                verify_generics.split_for_impl();
+            let column_lens = variant_columns.iter().map(|idents| {
+                quote! { &[#(self.#idents.len(),)*] }
+            });
+
+            (
+                Some(quote! {
+                    #[derive(#rkyv_path::bytecheck::CheckBytes)]
+                    #[check_bytes(crate = #path_lit_str)]
+                    #[check_bytes(verify)]
+                }),
+                Some(quote! {
+                    unsafe impl #verify_impl_generics
+                        #rkyv_path::bytecheck::Verify<__C>
+                        for #soa_name #ty_generics
+                    #verify_where_clause
+                    {
+                        fn verify(
+                            &self,
+                            _: &mut __C,
+                        ) -> ::core::result::Result<
+                            (),
+                            <__C as #rkyv_path::rancor::Fallible>::Error,
+                        > {
+                            let columns: &[&[::core::primitive::usize]] =
+                                &[#(#column_lens,)*];
+                            self.__index.check_columns(columns)
+                        }
+                    }
+                }),
+            )
+        } else {
+            (None, None)
+        };
+
+    let soa_doc = format!(
+        "A struct of arrays which holds a slice of archived [`{}`]",
+        name,
+    );
+    let soa_resolver_doc = format!(
+        "The resolver for a struct of arrays of archived [`{}`]",
+        name,
+    );
+    let get_doc = format!(
+        "Returns references to the fields of the [`{}`] at the given index",
+        name,
+    );
+    let iter_doc = format!(
+        "Returns an iterator over references to the fields of each [`{}`] in \
+         order",
+        name,
+    );
+
+    let serialize_generics = impl_generics_with(
+        generics,
+        quote! {
+            __S: #rkyv_path::rancor::Fallible
+                + #rkyv_path::ser::Allocator
+                + #rkyv_path::ser::Writer
+                + ?::core::marker::Sized
+        },
+        serialize_where
+            .into_iter()
+            .chain(attributes.serialize_bounds.iter().flatten().cloned())
+            .collect(),
+    );
+    let (serialize_impl_generics, _, serialize_where_clause) =
+        serialize_generics.split_for_impl();
+    let deserialize_generics = impl_generics_with(
+        generics,
+        quote! { __D: #rkyv_path::rancor::Fallible + ?::core::marker::Sized },
+        deserialize_where
+            .into_iter()
+            .chain(attributes.deserialize_bounds.iter().flatten().cloned())
+            .collect(),
+    );
+    let (deserialize_impl_generics, _, deserialize_where_clause) =
+        deserialize_generics.split_for_impl();
+
+    let types = quote! {
+        #[automatically_derived]
+        #[doc = #soa_doc]
+        #[derive(#rkyv_path::Portable)]
+        #[rkyv(crate = #rkyv_path)]
+        #check_bytes_attrs
+        #[repr(C)]
+        #[allow(non_snake_case)]
+        #vis struct #soa_name #generics #where_clause {
+            __index: #rkyv_path::soa::ArchivedSoaIndex,
+            #(#column_defs,)*
+        }
+
+        #[automatically_derived]
+        impl #impl_generics #soa_name #ty_generics #where_clause {
+            /// Returns the number of elements.
+            #[inline]
+            #vis fn len(&self) -> ::core::primitive::usize {
+                self.__index.len()
+            }
+
+            /// Returns whether there are no elements.
+            #[inline]
+            #vis fn is_empty(&self) -> ::core::primitive::bool {
+                self.__index.is_empty()
+            }
+
+            #[doc = #get_doc]
+            #vis fn get<'__a>(
+                &'__a self,
+                index: ::core::primitive::usize,
+            ) -> ::core::option::Option<#ref_name #ref_ty_generics> {
+                let (tag, index) = self.__index.get(index)?;
+                ::core::option::Option::Some(match tag {
+                    #(#get_arms,)*
+                    _ => return ::core::option::Option::None,
+                })
+            }
+
+            #[doc = #iter_doc]
+            #vis fn iter<'__a>(
+                &'__a self,
+            ) -> impl ::core::iter::Iterator<Item = #ref_name #ref_ty_generics>
+                + '__a
+            {
+                ::core::iter::Iterator::map_while(0..self.len(), move |i| {
+                    self.get(i)
+                })
+            }
+        }
+
+        #verify_impl
+
+        #[automatically_derived]
+        #[doc = #soa_resolver_doc]
+        #[allow(non_snake_case)]
+        #resolver_vis struct #soa_resolver_name {
+            __index: #rkyv_path::soa::SoaIndexResolver,
+            #(#column_idents: #rkyv_path::vec::VecResolver,)*
+        }
+    };
+
+    let impls = quote! {
+        impl #impl_generics #rkyv_path::soa::ArchiveSoa for #name #ty_generics
+        #where_clause
+        {
+            type ArchivedSoa = #soa_name #ty_generics;
+            type SoaResolver = #soa_resolver_name;
+
+            #[inline]
+            fn soa_len(
+                archived: &Self::ArchivedSoa,
+            ) -> ::core::primitive::usize {
+                archived.len()
+            }
+
+            fn resolve_soa(
+                values: &[Self],
+                resolver: Self::SoaResolver,
+                out: #rkyv_path::Place<Self::ArchivedSoa>,
+            ) {
+                let field_ptr = unsafe {
+                    ::core::ptr::addr_of_mut!((*out.ptr()).__index)
+                };
+                let out_field = unsafe {
+                    #rkyv_path::Place::from_field_unchecked(out, field_ptr)
+                };
+                #rkyv_path::soa::ArchivedSoaIndex::resolve_from_len(
+                    values.len(),
+                    resolver.__index,
+                    out_field,
+                );
+                #(#column_resolves)*
+            }
+        }
+
+        impl #serialize_impl_generics #rkyv_path::soa::SerializeSoa<__S>
+            for #name #ty_generics
+        #serialize_where_clause
+        {
+            #[allow(non_snake_case)]
+            fn serialize_soa(
+                values: &[Self],
+                serializer: &mut __S,
+            ) -> ::core::result::Result<
+                Self::SoaResolver,
+                <__S as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                let __index =
+                    #rkyv_path::soa::ArchivedSoaIndex::serialize_from_tags(
+                        ::core::iter::Iterator::map(values.iter(), |value| {
+                            match value {
+                                #(#tag_arms,)*
+                            }
+                        }),
+                        serializer,
+                    )?;
+                #(#column_serializes)*
+                ::core::result::Result::Ok(#soa_resolver_name {
+                    __index,
+                    #(#column_idents,)*
+                })
+            }
+        }
+
+        impl #deserialize_impl_generics #rkyv_path::soa::DeserializeSoa<__D>
+            for #name #ty_generics
+        #deserialize_where_clause
+        {
+            fn deserialize_soa(
+                archived: &Self::ArchivedSoa,
+                index: ::core::primitive::usize,
+                deserializer: &mut __D,
+            ) -> ::core::result::Result<
+                Self,
+                <__D as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                let value = archived
+                    .get(index)
+                    .expect("struct of arrays index out of bounds");
+                ::core::result::Result::Ok(match value {
+                    #(#deserialize_arms,)*
+                })
+            }
+        }
+    };
+
+    Ok((types, impls))
+}
@@ -7,12 +7,16 @@ use syn::{
 
 use crate::{
     archive::{
-        archived_doc, field_archive_attrs, printing::Printing, resolver_doc,
-        struct_field_doc,
+        archived_doc, describe::generate_struct_describe_impl,
+        diff::generate_struct_diff_impl, extensible, field_archive_attrs,
+        printing::Printing, project::generate_struct_project_def,
+        projections::generate_struct_projections_def, resolver_doc,
+        struct_field_doc, transparent,
     },
     attributes::Attributes,
     util::{
-        archive_bound, archived, is_not_omitted, members, resolve, resolver,
+        archive_bound, archived, has_unit_resolver, is_bound_skipped,
+        is_not_omitted, members, mentions_generics, resolve, resolver,
     },
 };
 
@@ -28,12 +32,73 @@ pub fn impl_struct(
 
     let rkyv_path = &printing.rkyv_path;
 
-    let where_clause = input.generics.make_where_clause();
+    let archive_bounds = fields
+        .iter()
+        .filter(is_not_omitted)
+        .filter(|field| mentions_generics(&input.generics, field))
+        .map(|field| archive_bound(rkyv_path, field))
+        .collect::<Result<Vec<_>, Error>>()?;
+    input
+        .generics
+        .make_where_clause()
+        .predicates
+        .extend(archive_bounds);
+
+    if let Some(repr) = &attributes.repr {
+        return Err(Error::new_spanned(
+            repr,
+            "`repr(...)` may only be used on enums",
+        ));
+    }
 
-    for field in fields.iter().filter(is_not_omitted) {
-        where_clause
-            .predicates
-            .push(archive_bound(rkyv_path, field)?);
+    if let Some(derive_as_ref) = &attributes.derive_as_ref {
+        return Err(Error::new_spanned(
+            derive_as_ref,
+            "`derive_as_ref` may only be used on enums",
+        ));
+    }
+
+    if let Some(soa) = &attributes.soa {
+        return Err(Error::new_spanned(soa, "`soa` may only be used on enums"));
+    }
+
+    if let Some(unit_resolver) = &attributes.unit_resolver {
+        if attributes.extensible.is_some() || attributes.transparent.is_some() {
+            return Err(Error::new_spanned(
+                unit_resolver,
+                "`unit_resolver` may not be used with `extensible` or \
+                 `transparent` because they don't generate a resolver",
+            ));
+        }
+        if !input.generics.params.is_empty() {
+            return Err(Error::new_spanned(
+                &input.generics,
+                "`unit_resolver` may not be used on structs with generic \
+                 parameters",
+            ));
+        }
+    }
+
+    if let Some(extensible) = &attributes.extensible {
+        return match fields {
+            Fields::Named(fields) => extensible::impl_extensible_struct(
+                input, attributes, printing, fields,
+            ),
+            _ => Err(Error::new_spanned(
+                extensible,
+                "`extensible` may only be used on structs with named fields",
+            )),
+        };
+    }
+
+    if let Some(transparent_path) = &attributes.transparent {
+        return transparent::impl_transparent_struct(
+            input,
+            attributes,
+            printing,
+            transparent_path,
+            fields,
+        );
     }
 
     let (impl_generics, ty_generics, where_clause) =
@@ -46,11 +111,67 @@ pub fn impl_struct(
         .then(|| generate_archived_def(input, attributes, printing, fields))
         .transpose()?;
 
-    let resolver_def = generate_resolver_def(input, printing, fields)?;
+    let resolver_def = if attributes.unit_resolver.is_some() {
+        generate_unit_resolver_def(input, printing)
+    } else {
+        generate_resolver_def(input, printing, fields)?
+    };
+    let resolver_default_impl = attributes
+        .unit_resolver
+        .is_none()
+        .then(|| generate_resolver_default_impl(input, printing, fields))
+        .transpose()?
+        .flatten();
+
+    let project_def = attributes
+        .derive_project
+        .as_ref()
+        .map(|derive_project| {
+            generate_struct_project_def(input, printing, fields, derive_project)
+        })
+        .transpose()?;
+
+    let diff_impl = attributes
+        .derive_diff
+        .is_some()
+        .then(|| generate_struct_diff_impl(input, printing, fields))
+        .transpose()?;
+
+    let describe_impl = attributes
+        .derive_describe
+        .as_ref()
+        .map(|derive_describe| {
+            generate_struct_describe_impl(
+                input,
+                printing,
+                fields,
+                derive_describe,
+            )
+        })
+        .transpose()?;
+
+    let projections_def = attributes
+        .derive_projections
+        .as_ref()
+        .map(|derive_projections| {
+            generate_struct_projections_def(
+                input,
+                printing,
+                fields,
+                derive_projections,
+            )
+        })
+        .transpose()?;
 
     let resolve_statements = members(fields)
         .map(|(member, field)| {
             let resolves = resolve(rkyv_path, field)?;
+            // Unit resolvers are passed to every field.
+            let field_resolver = if attributes.unit_resolver.is_some() {
+                quote! { resolver }
+            } else {
+                quote! { resolver.#member }
+            };
             Ok(quote! {
                 let field_ptr = unsafe {
                     ::core::ptr::addr_of_mut!((*out.ptr()).#member)
@@ -58,7 +179,7 @@ pub fn impl_struct(
                 let out_field = unsafe {
                     #rkyv_path::Place::from_field_unchecked(out, field_ptr)
                 };
-                #resolves(&self.#member, resolver.#member, out_field);
+                #resolves(&self.#member, #field_resolver, out_field);
             })
         })
         .collect::<Result<Vec<_>, Error>>()?;
@@ -88,6 +209,8 @@ pub fn impl_struct(
     Ok((
         quote! {
             #archived_def
+            #project_def
+            #projections_def
             #resolver_def
         },
         quote! {
@@ -109,8 +232,11 @@ pub fn impl_struct(
                 }
             }
 
+            #resolver_default_impl
             #partial_eq_impl
             #partial_ord_impl
+            #diff_impl
+            #describe_impl
         },
     ))
 }
@@ -176,7 +302,7 @@ fn generate_archived_def_named(
 
     let archived_doc = archived_doc(&input.ident);
     let archive_attrs = &printing.archive_attrs;
-    let vis = &input.vis;
+    let vis = &printing.archived_vis;
     let archived_name = &printing.archived_name;
     let generics = &input.generics;
     let where_clause = generics.where_clause.as_ref().unwrap();
@@ -220,7 +346,7 @@ fn generate_archived_def_unnamed(
 
     let archived_doc = archived_doc(&input.ident);
     let archive_attrs = &printing.archive_attrs;
-    let vis = &input.vis;
+    let vis = &printing.archived_vis;
     let archived_name = &printing.archived_name;
     let generics = &input.generics;
     let where_clause = generics.where_clause.as_ref().unwrap();
@@ -242,7 +368,7 @@ fn generate_archived_def_unit(
 ) -> Result<TokenStream, Error> {
     let archived_doc = archived_doc(&input.ident);
     let archive_attrs = &printing.archive_attrs;
-    let vis = &input.vis;
+    let vis = &printing.archived_vis;
     let archived_name = &printing.archived_name;
     let generics = &input.generics;
     let where_clause = generics.where_clause.as_ref().unwrap();
@@ -279,7 +405,7 @@ fn generate_resolver_def_named(
 ) -> Result<TokenStream, Error> {
     let rkyv_path = &printing.rkyv_path;
     let resolver_name = &printing.resolver_name;
-    let vis = &input.vis;
+    let vis = &printing.resolver_vis;
     let generics = &input.generics;
     let where_clause = generics.where_clause.as_ref().unwrap();
     let resolver_doc = resolver_doc(&input.ident);
@@ -311,7 +437,7 @@ fn generate_resolver_def_unnamed(
 ) -> Result<TokenStream, Error> {
     let rkyv_path = &printing.rkyv_path;
     let resolver_name = &printing.resolver_name;
-    let vis = &input.vis;
+    let vis = &printing.resolver_vis;
     let generics = &input.generics;
     let where_clause = generics.where_clause.as_ref().unwrap();
     let resolver_doc = resolver_doc(&input.ident);
@@ -339,7 +465,7 @@ fn generate_resolver_def_unit(
     printing: &Printing,
 ) -> Result<TokenStream, Error> {
     let resolver_name = &printing.resolver_name;
-    let vis = &input.vis;
+    let vis = &printing.resolver_vis;
     let generics = &input.generics;
     let where_clause = generics.where_clause.as_ref().unwrap();
     let resolver_doc = resolver_doc(&input.ident);
@@ -351,6 +477,69 @@ fn generate_resolver_def_unit(
     })
 }
 
+fn generate_unit_resolver_def(
+    input: &DeriveInput,
+    printing: &Printing,
+) -> TokenStream {
+    let resolver_name = &printing.resolver_name;
+    let vis = &printing.resolver_vis;
+    let resolver_doc = resolver_doc(&input.ident);
+
+    quote! {
+        #[doc = #resolver_doc]
+        #vis type #resolver_name = ();
+    }
+}
+
+/// Implements `Default` for the resolver if every field's resolver is known to
+/// be `()` or mentions a generic parameter and isn't bound-skipped.
+///
+/// The resolvers of other fields would need bounds which don't mention any
+/// generic parameters, and those bounds are errors when they don't hold.
+fn generate_resolver_default_impl(
+    input: &DeriveInput,
+    printing: &Printing,
+    fields: &Fields,
+) -> Result<Option<TokenStream>, Error> {
+    let rkyv_path = &printing.rkyv_path;
+    let mut default_where = input.generics.where_clause.clone().unwrap();
+    for field in fields.iter() {
+        if has_unit_resolver(field) {
+            continue;
+        } else if mentions_generics(&input.generics, field)
+            && !is_bound_skipped(field)
+        {
+            let resolver_ty = resolver(rkyv_path, field)?;
+            default_where.predicates.push(parse_quote! {
+                #resolver_ty: ::core::default::Default
+            });
+        } else {
+            return Ok(None);
+        }
+    }
+
+    let defaults = members(fields).map(|(member, _)| {
+        quote! { #member: ::core::default::Default::default() }
+    });
+
+    let resolver_name = &printing.resolver_name;
+    let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
+
+    Ok(Some(quote! {
+        impl #impl_generics ::core::default::Default
+            for #resolver_name #ty_generics
+        #default_where
+        {
+            #[inline]
+            fn default() -> Self {
+                Self {
+                    #(#defaults,)*
+                }
+            }
+        }
+    }))
+}
+
 fn generate_partial_eq_impl(
     input: &DeriveInput,
     fields: &Fields,
@@ -362,9 +551,9 @@ fn generate_partial_eq_impl(
     for field in fields.iter().filter(is_not_omitted) {
         let ty = &field.ty;
         let archived_ty = archived(&printing.rkyv_path, field)?;
-        partial_eq_where
-            .predicates
-            .push(parse_quote! { #archived_ty: PartialEq<#ty> });
+        partial_eq_where.predicates.push(parse_quote! {
+            #archived_ty: ::core::cmp::PartialEq<#ty>
+        });
     }
 
     let members = members(fields).map(|(member, _)| member);
@@ -374,19 +563,27 @@ fn generate_partial_eq_impl(
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
 
     Ok(quote! {
-        impl #impl_generics PartialEq<#archived_type> for #name #ty_generics
+        impl #impl_generics ::core::cmp::PartialEq<#archived_type>
+            for #name #ty_generics
         #partial_eq_where
         {
-            fn eq(&self, other: &#archived_type) -> bool {
-                true #(&& other.#members.eq(&self.#members))*
+            fn eq(&self, other: &#archived_type) -> ::core::primitive::bool {
+                true #(&& ::core::cmp::PartialEq::eq(
+                    &other.#members,
+                    &self.#members,
+                ))*
             }
         }
 
-        impl #impl_generics PartialEq<#name #ty_generics> for #archived_type
+        impl #impl_generics ::core::cmp::PartialEq<#name #ty_generics>
+            for #archived_type
         #partial_eq_where
         {
-            fn eq(&self, other: &#name #ty_generics) -> bool {
-                other.eq(self)
+            fn eq(
+                &self,
+                other: &#name #ty_generics,
+            ) -> ::core::primitive::bool {
+                ::core::cmp::PartialEq::eq(other, self)
             }
         }
     })
@@ -403,9 +600,9 @@ fn generate_partial_ord_impl(
     for field in fields.iter().filter(is_not_omitted) {
         let ty = &field.ty;
         let archived_ty = archived(&printing.rkyv_path, field)?;
-        partial_ord_where
-            .predicates
-            .push(parse_quote! { #archived_ty: PartialOrd<#ty> });
+        partial_ord_where.predicates.push(parse_quote! {
+            #archived_ty: ::core::cmp::PartialOrd<#ty>
+        });
     }
 
     let members = members(fields).map(|(member, _)| member);
@@ -415,31 +612,39 @@ fn generate_partial_ord_impl(
     let (impl_generics, ty_generics, _) = input.generics.split_for_impl();
 
     Ok(quote! {
-        impl #impl_generics PartialOrd<#archived_type> for #name #ty_generics
+        impl #impl_generics ::core::cmp::PartialOrd<#archived_type>
+            for #name #ty_generics
         #partial_ord_where
         {
             fn partial_cmp(
                 &self,
                 other: &#archived_type,
-            ) -> Option<::core::cmp::Ordering> {
+            ) -> ::core::option::Option<::core::cmp::Ordering> {
                 #(
-                    match other.#members.partial_cmp(&self.#members) {
-                        Some(::core::cmp::Ordering::Equal) => (),
+                    match ::core::cmp::PartialOrd::partial_cmp(
+                        &other.#members,
+                        &self.#members,
+                    ) {
+                        ::core::option::Option::Some(
+                            ::core::cmp::Ordering::Equal,
+                        ) => (),
                         x => return x.map(::core::cmp::Ordering::reverse),
                     }
                 )*
-                Some(::core::cmp::Ordering::Equal)
+                ::core::option::Option::Some(::core::cmp::Ordering::Equal)
             }
         }
 
-        impl #impl_generics PartialOrd<#name #ty_generics> for #archived_type
+        impl #impl_generics ::core::cmp::PartialOrd<#name #ty_generics>
+            for #archived_type
         #partial_ord_where
         {
             fn partial_cmp(
                 &self,
                 other: &#name #ty_generics,
-            ) -> Option<::core::cmp::Ordering> {
-                other.partial_cmp(self).map(::core::cmp::Ordering::reverse)
+            ) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::cmp::PartialOrd::partial_cmp(other, self)
+                    .map(::core::cmp::Ordering::reverse)
             }
         }
     })
@@ -0,0 +1,71 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{DeriveInput, Error, Fields, Path};
+
+use crate::{
+    archive::printing::Printing,
+    attributes::Attributes,
+    util::{
+        archive_bound, archived, is_bound_skipped, resolve, resolver,
+        transparent_assert_zero_sized, transparent_field,
+    },
+};
+
+pub fn impl_transparent_struct(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+    transparent: &Path,
+    fields: &Fields,
+) -> Result<(TokenStream, TokenStream), Error> {
+    if let Some(compares) = &attributes.compares {
+        return Err(Error::new_spanned(
+            compares,
+            "compare(...) may not be used with `transparent` because the \
+             archived type is the archived type of its field, which already \
+             has its own comparisons",
+        ));
+    }
+
+    let (member, field) = transparent_field(transparent, fields)?;
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) =
+        input.generics.split_for_impl();
+    // The archived type is the archived type of the field, so bound the field
+    // directly instead of relying on the bounds added for every field.
+    let mut where_clause = where_clause.unwrap().clone();
+    if !is_bound_skipped(field) {
+        where_clause
+            .predicates
+            .push(archive_bound(rkyv_path, field)?);
+    }
+
+    let field_archived = archived(rkyv_path, field)?;
+    let field_resolver = resolver(rkyv_path, field)?;
+    let field_resolve = resolve(rkyv_path, field)?;
+    let assert_zero_sized = transparent_assert_zero_sized(fields, &member);
+
+    Ok((
+        TokenStream::new(),
+        quote! {
+            impl #impl_generics #rkyv_path::Archive for #name #ty_generics
+            #where_clause
+            {
+                type Archived = #field_archived;
+                type Resolver = #field_resolver;
+
+                #[inline]
+                fn resolve(
+                    &self,
+                    resolver: Self::Resolver,
+                    out: #rkyv_path::Place<Self::Archived>,
+                ) {
+                    #assert_zero_sized
+                    #field_resolve(&self.#member, resolver, out);
+                }
+            }
+        },
+    ))
+}
@@ -0,0 +1,197 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{parse_quote_spanned, spanned::Spanned, Data, DeriveInput, Error};
+
+use crate::{
+    archive::{archived_doc, printing::Printing, resolver_doc},
+    attributes::Attributes,
+};
+
+fn reject_with_union_as_bytes(
+    attribute: Option<&impl ToTokens>,
+    name: &str,
+) -> Result<(), Error> {
+    if let Some(attribute) = attribute {
+        Err(Error::new_spanned(
+            attribute,
+            format!("`{}` may not be used with `union_as_bytes`", name),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn impl_union(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    printing: &Printing,
+) -> Result<(TokenStream, TokenStream), Error> {
+    let fields = match &input.data {
+        Data::Union(data) => &data.fields,
+        _ => unreachable!(),
+    };
+
+    reject_with_union_as_bytes(attributes.archive_as.as_ref(), "as")?;
+    reject_with_union_as_bytes(attributes.compares.as_ref(), "compare")?;
+    reject_with_union_as_bytes(attributes.extensible.as_ref(), "extensible")?;
+    reject_with_union_as_bytes(attributes.transparent.as_ref(), "transparent")?;
+    reject_with_union_as_bytes(attributes.repr.as_ref(), "repr")?;
+    reject_with_union_as_bytes(
+        attributes.derive_as_ref.as_ref(),
+        "derive_as_ref",
+    )?;
+    reject_with_union_as_bytes(
+        attributes.derive_project.as_ref(),
+        "derive_project",
+    )?;
+    reject_with_union_as_bytes(attributes.derive_diff.as_ref(), "derive_diff")?;
+    reject_with_union_as_bytes(attributes.soa.as_ref(), "soa")?;
+
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "`union_as_bytes` may not be used with generic unions",
+        ));
+    }
+
+    let rkyv_path = &printing.rkyv_path;
+    let name = &input.ident;
+    let where_clause = input.generics.where_clause.as_ref().unwrap();
+
+    // Every field must be plain data. `ManuallyDrop`, references, and pointers
+    // don't implement `Initialized`, so this rejects fields which could own
+    // data or whose bytes are meaningless after being archived, even when
+    // they are named through an alias.
+    let mut archive_where_clause = where_clause.clone();
+    for field in fields.named.iter() {
+        let ty = &field.ty;
+        archive_where_clause.predicates.push(
+            parse_quote_spanned! { ty.span() =>
+                #ty: #rkyv_path::place::Initialized
+            },
+        );
+    }
+
+    let archived_name = &printing.archived_name;
+    let archived_vis = &printing.archived_vis;
+    let archive_attrs = &printing.archive_attrs;
+    let resolver_name = &printing.resolver_name;
+    let resolver_vis = &printing.resolver_vis;
+
+    let archived_doc = archived_doc(name);
+    let resolver_doc = resolver_doc(name);
+
+    let field_tys = fields.named.iter().map(|field| &field.ty);
+    let accessors = fields.named.iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap();
+        let field_ty = &field.ty;
+        let vis = &field.vis;
+        let doc = format!(
+            "Reads the archived counterpart of [`{}::{}`].",
+            name, field_name,
+        );
+
+        quote! {
+            #[doc = #doc]
+            ///
+            /// # Safety
+            ///
+            /// The archived bytes must be a valid value of the field type. The
+            /// bytes are in the byte order of the machine which serialized the
+            /// union.
+            #[inline]
+            #vis unsafe fn #field_name(&self) -> #field_ty {
+                // SAFETY: The caller has guaranteed that the bytes are a valid
+                // value of the field type, and the size of the union is at
+                // least the size of each of its fields.
+                unsafe {
+                    ::core::ptr::read_unaligned(
+                        self.bytes.as_ptr().cast::<#field_ty>(),
+                    )
+                }
+            }
+        }
+    });
+
+    Ok((
+        quote! {
+            #[automatically_derived]
+            #[doc = #archived_doc]
+            #(#archive_attrs)*
+            #[repr(C)]
+            #archived_vis struct #archived_name #where_clause {
+                bytes: [::core::primitive::u8; ::core::mem::size_of::<#name>()],
+            }
+
+            impl #archived_name {
+                /// Returns the archived bytes of the union.
+                #[inline]
+                pub fn as_bytes(
+                    &self,
+                ) -> &[::core::primitive::u8; ::core::mem::size_of::<#name>()] {
+                    &self.bytes
+                }
+
+                #(#accessors)*
+            }
+
+            // SAFETY: Byte arrays are always `Portable`.
+            unsafe impl #rkyv_path::Portable for #archived_name #where_clause {}
+
+            #[automatically_derived]
+            #[doc = #resolver_doc]
+            #resolver_vis struct #resolver_name;
+        },
+        quote! {
+            const _: fn() = || {
+                fn assert_copy<T: ::core::marker::Copy>() {}
+                #(assert_copy::<#field_tys>();)*
+
+                // Unions may have uninitialized bytes, so the bytes of the
+                // union may only be read if the user has implemented the
+                // unsafe `Initialized` trait for it.
+                fn assert_initialized<T: #rkyv_path::place::Initialized>() {}
+                assert_initialized::<#name>();
+            };
+
+            impl #rkyv_path::Archive for #name #archive_where_clause {
+                type Archived = #archived_name;
+                type Resolver = #resolver_name;
+
+                #[inline]
+                fn resolve(
+                    &self,
+                    _: Self::Resolver,
+                    out: #rkyv_path::Place<Self::Archived>,
+                ) {
+                    let mut bytes = ::core::mem::MaybeUninit::<
+                        [
+                            ::core::primitive::u8;
+                            ::core::mem::size_of::<#name>()
+                        ],
+                    >::uninit();
+                    // SAFETY: `bytes` is as large as the union, and the two
+                    // do not overlap.
+                    unsafe {
+                        ::core::ptr::copy_nonoverlapping(
+                            (self as *const Self)
+                                .cast::<::core::primitive::u8>(),
+                            bytes.as_mut_ptr().cast::<::core::primitive::u8>(),
+                            ::core::mem::size_of::<#name>(),
+                        );
+                    }
+                    // SAFETY: The union implements `Initialized`, so all of
+                    // the copied bytes are initialized.
+                    let bytes = unsafe { bytes.assume_init() };
+                    let field_ptr = unsafe {
+                        ::core::ptr::addr_of_mut!((*out.ptr()).bytes)
+                    };
+                    let out_field = unsafe {
+                        #rkyv_path::Place::from_field_unchecked(out, field_ptr)
+                    };
+                    out_field.write(bytes);
+                }
+            }
+        },
+    ))
+}
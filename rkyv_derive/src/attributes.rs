@@ -3,7 +3,8 @@ use quote::ToTokens;
 use syn::{
     meta::ParseNestedMeta, parenthesized, parse::Parse, parse_quote,
     punctuated::Punctuated, token, AttrStyle, DeriveInput, Error, Ident,
-    LitStr, MacroDelimiter, Meta, MetaList, Path, Token, WherePredicate,
+    LitStr, MacroDelimiter, Meta, MetaList, Path, Token, Visibility,
+    WherePredicate,
 };
 
 fn try_set_attribute<T: ToTokens>(
@@ -27,6 +28,8 @@ pub struct Attributes {
     pub archive_as: Option<LitStr>,
     pub archived: Option<Ident>,
     pub resolver: Option<Ident>,
+    pub archived_vis: Option<Visibility>,
+    pub resolver_vis: Option<Visibility>,
     pub attrs: Vec<Meta>,
     pub compares: Option<Punctuated<Path, Token![,]>>,
     pub archive_bounds: Option<Punctuated<WherePredicate, Token![,]>>,
@@ -34,6 +37,19 @@ pub struct Attributes {
     pub deserialize_bounds: Option<Punctuated<WherePredicate, Token![,]>>,
     pub check_bytes: Option<Meta>,
     pub crate_path: Option<Path>,
+    pub extensible: Option<Path>,
+    pub transparent: Option<Path>,
+    pub repr: Option<Ident>,
+    pub derive_as_ref: Option<Path>,
+    pub derive_project: Option<Path>,
+    pub derive_diff: Option<Path>,
+    pub deserialize_into: Option<Path>,
+    pub derive_describe: Option<Path>,
+    pub derive_projections: Option<Path>,
+    pub soa: Option<Path>,
+    pub union_as_bytes: Option<Path>,
+    pub as_discriminant: Option<Path>,
+    pub unit_resolver: Option<Path>,
 }
 
 impl Attributes {
@@ -114,6 +130,12 @@ impl Attributes {
                 meta.value()?.parse()?,
                 "resolver",
             )
+        } else if meta.path.is_ident("archived_vis") {
+            let vis = meta.value()?.parse::<LitStr>()?.parse()?;
+            try_set_attribute(&mut self.archived_vis, vis, "archived_vis")
+        } else if meta.path.is_ident("resolver_vis") {
+            let vis = meta.value()?.parse::<LitStr>()?.parse()?;
+            try_set_attribute(&mut self.resolver_vis, vis, "resolver_vis")
         } else if meta.path.is_ident("as") {
             try_set_attribute(
                 &mut self.archive_as,
@@ -133,6 +155,74 @@ impl Attributes {
             } else {
                 Err(meta.error("expected `crate` or `crate = ...`"))
             }
+        } else if meta.path.is_ident("extensible") {
+            try_set_attribute(&mut self.extensible, meta.path, "extensible")
+        } else if meta.path.is_ident("transparent") {
+            try_set_attribute(&mut self.transparent, meta.path, "transparent")
+        } else if meta.path.is_ident("repr") {
+            let content;
+            parenthesized!(content in meta.input);
+            let repr = content.parse::<Ident>()?;
+            if repr != "u8" && repr != "u16" && repr != "u32" {
+                return Err(Error::new_spanned(
+                    repr,
+                    "unsupported archived repr, supported reprs are u8, u16, \
+                     and u32",
+                ));
+            }
+            try_set_attribute(&mut self.repr, repr, "repr")
+        } else if meta.path.is_ident("derive_as_ref") {
+            try_set_attribute(
+                &mut self.derive_as_ref,
+                meta.path,
+                "derive_as_ref",
+            )
+        } else if meta.path.is_ident("derive_project") {
+            try_set_attribute(
+                &mut self.derive_project,
+                meta.path,
+                "derive_project",
+            )
+        } else if meta.path.is_ident("derive_diff") {
+            try_set_attribute(&mut self.derive_diff, meta.path, "derive_diff")
+        } else if meta.path.is_ident("deserialize_into") {
+            try_set_attribute(
+                &mut self.deserialize_into,
+                meta.path,
+                "deserialize_into",
+            )
+        } else if meta.path.is_ident("derive_describe") {
+            try_set_attribute(
+                &mut self.derive_describe,
+                meta.path,
+                "derive_describe",
+            )
+        } else if meta.path.is_ident("derive_projections") {
+            try_set_attribute(
+                &mut self.derive_projections,
+                meta.path,
+                "derive_projections",
+            )
+        } else if meta.path.is_ident("soa") {
+            try_set_attribute(&mut self.soa, meta.path, "soa")
+        } else if meta.path.is_ident("union_as_bytes") {
+            try_set_attribute(
+                &mut self.union_as_bytes,
+                meta.path,
+                "union_as_bytes",
+            )
+        } else if meta.path.is_ident("as_discriminant") {
+            try_set_attribute(
+                &mut self.as_discriminant,
+                meta.path,
+                "as_discriminant",
+            )
+        } else if meta.path.is_ident("unit_resolver") {
+            try_set_attribute(
+                &mut self.unit_resolver,
+                meta.path,
+                "unit_resolver",
+            )
         } else if meta.path.is_ident("derive") {
             let metas;
             parenthesized!(metas in meta.input);
@@ -189,6 +279,101 @@ impl Attributes {
                 "cannot generate a `CheckBytes` impl because `as = \"..\"` \
                  does not generate an archived type",
             ))
+        } else if result.archive_as.is_some() && result.derive_as_ref.is_some()
+        {
+            Err(Error::new_spanned(
+                result.derive_as_ref.unwrap(),
+                "`derive_as_ref` may not be used with `as = \"..\"` because \
+                 the archived type must be generated",
+            ))
+        } else if result.archive_as.is_some() && result.soa.is_some() {
+            Err(Error::new_spanned(
+                result.soa.unwrap(),
+                "`soa` may not be used with `as = \"..\"` because the \
+                 archived type must be generated",
+            ))
+        } else if let Some(derive_project) =
+            result.derive_project.as_ref().filter(|_| {
+                result.archive_as.is_some()
+                    || result.extensible.is_some()
+                    || result.transparent.is_some()
+            })
+        {
+            Err(Error::new_spanned(
+                derive_project,
+                "`derive_project` may not be used with `as = \"..\"`, \
+                 `extensible`, or `transparent` because the archived type \
+                 must be generated",
+            ))
+        } else if let Some(derive_diff) =
+            result.derive_diff.as_ref().filter(|_| {
+                result.archive_as.is_some()
+                    || result.extensible.is_some()
+                    || result.transparent.is_some()
+            })
+        {
+            Err(Error::new_spanned(
+                derive_diff,
+                "`derive_diff` may not be used with `as = \"..\"`, \
+                 `extensible`, or `transparent` because the archived type \
+                 must be generated",
+            ))
+        } else if let Some(deserialize_into) =
+            result.deserialize_into.as_ref().filter(|_| {
+                result.archive_as.is_some()
+                    || result.extensible.is_some()
+                    || result.transparent.is_some()
+            })
+        {
+            Err(Error::new_spanned(
+                deserialize_into,
+                "`deserialize_into` may not be used with `as = \"..\"`, \
+                 `extensible`, or `transparent` because the archived type \
+                 must be generated",
+            ))
+        } else if let Some(derive_describe) =
+            result.derive_describe.as_ref().filter(|_| {
+                result.archive_as.is_some()
+                    || result.extensible.is_some()
+                    || result.transparent.is_some()
+            })
+        {
+            Err(Error::new_spanned(
+                derive_describe,
+                "`derive_describe` may not be used with `as = \"..\"`, \
+                 `extensible`, or `transparent` because the archived type \
+                 must be generated",
+            ))
+        } else if let Some(derive_projections) =
+            result.derive_projections.as_ref().filter(|_| {
+                result.archive_as.is_some()
+                    || result.extensible.is_some()
+                    || result.transparent.is_some()
+            })
+        {
+            Err(Error::new_spanned(
+                derive_projections,
+                "`derive_projections` may not be used with `as = \"..\"`, \
+                 `extensible`, or `transparent` because the archived type \
+                 must be generated",
+            ))
+        } else if result.archive_as.is_some() && result.extensible.is_some() {
+            Err(Error::new_spanned(
+                result.extensible.unwrap(),
+                "`extensible` may not be used with `as = \"..\"` because the \
+                 archived type must be generated",
+            ))
+        } else if let Some(transparent) =
+            result.transparent.as_ref().filter(|_| {
+                result.archive_as.is_some() || result.extensible.is_some()
+            })
+        {
+            Err(Error::new_spanned(
+                transparent,
+                "`transparent` may not be used with `as = \"..\"` or \
+                 `extensible` because it archives as the archived type of its \
+                 field",
+            ))
         } else {
             Ok(result)
         }
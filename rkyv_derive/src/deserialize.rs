@@ -2,16 +2,22 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
     parse_quote, punctuated::Punctuated, spanned::Spanned, Data, DeriveInput,
-    Error, Fields, Generics, Ident, Index,
+    Error, Fields, FieldsNamed, Generics, Ident, Index, WhereClause,
 };
 
 use crate::{
     attributes::Attributes,
-    util::{archive_bound, deserialize, deserialize_bound, is_not_omitted},
+    util::{
+        archive_bound, archived, deserialize, deserialize_bound, has_with,
+        is_bound_skipped, is_not_omitted, is_phantom_data, members,
+        omit_recursive_bounds, other_variant, transparent_assert_zero_sized,
+        transparent_construct, transparent_field,
+    },
 };
 
-pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
-    let attributes = Attributes::parse(&input)?;
+pub fn derive(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    let mut attributes = Attributes::parse(&input)?;
+    omit_recursive_bounds(&mut input, &mut attributes);
     derive_deserialize_impl(input, &attributes)
 }
 
@@ -34,8 +40,9 @@ fn derive_deserialize_impl(
     }
 
     let mut impl_input_params = Punctuated::default();
-    impl_input_params
-        .push(parse_quote! { __D: #rkyv_path::rancor::Fallible + ?Sized });
+    impl_input_params.push(parse_quote! {
+        __D: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+    });
     for param in input.generics.params.iter() {
         impl_input_params.push(param.clone());
     }
@@ -53,6 +60,22 @@ fn derive_deserialize_impl(
 
     let deserialize_impl = match input.data {
         Data::Struct(ref data) => match data.fields {
+            ref fields if attributes.transparent.is_some() => {
+                deserialize_transparent(
+                    &input,
+                    attributes,
+                    fields,
+                    where_clause,
+                )?
+            }
+            Fields::Named(ref fields) if attributes.extensible.is_some() => {
+                deserialize_extensible(
+                    &input,
+                    attributes,
+                    fields,
+                    where_clause,
+                )?
+            }
             Fields::Named(ref fields) => {
                 let mut deserialize_where = where_clause.clone();
                 for field in fields.named.iter().filter(is_not_omitted) {
@@ -89,7 +112,7 @@ fn derive_deserialize_impl(
                             #name #ty_generics,
                             <__D as #rkyv_path::rancor::Fallible>::Error,
                         > {
-                            Ok(#name {
+                            ::core::result::Result::Ok(#name {
                                 #(#deserialize_fields,)*
                             })
                         }
@@ -136,7 +159,7 @@ fn derive_deserialize_impl(
                             #name #ty_generics,
                             <__D as #rkyv_path::rancor::Fallible>::Error,
                         > {
-                            Ok(#name(
+                            ::core::result::Result::Ok(#name(
                                 #(#deserialize_fields,)*
                             ))
                         }
@@ -156,11 +179,47 @@ fn derive_deserialize_impl(
                         #name #ty_generics,
                         <__D as #rkyv_path::rancor::Fallible>::Error,
                     > {
-                        Ok(#name)
+                        ::core::result::Result::Ok(#name)
                     }
                 }
             },
         },
+        Data::Enum(ref data) if attributes.as_discriminant.is_some() => {
+            if let Some(other) = other_variant(data)? {
+                return Err(Error::new_spanned(
+                    other,
+                    "`other` may not be used with `as_discriminant` because \
+                     unknown discriminants fail to validate",
+                ));
+            }
+
+            let mut deserialize_where = where_clause.clone();
+            deserialize_where.predicates.push(parse_quote! {
+                <__D as #rkyv_path::rancor::Fallible>::Error:
+                    #rkyv_path::rancor::Source
+            });
+
+            quote! {
+                impl #impl_generics
+                    #rkyv_path::Deserialize<#name #ty_generics, __D>
+                    for #rkyv_path::Archived<#name #ty_generics>
+                #deserialize_where
+                {
+                    #[inline]
+                    fn deserialize(
+                        &self,
+                        _: &mut __D,
+                    ) -> ::core::result::Result<
+                        #name #ty_generics,
+                        <__D as #rkyv_path::rancor::Fallible>::Error,
+                    > {
+                        #rkyv_path::rancor::ResultExt::into_error(
+                            self.try_to_native(),
+                        )
+                    }
+                }
+            }
+        }
         Data::Enum(ref data) => {
             let mut deserialize_where = where_clause.clone();
             for variant in data.variants.iter() {
@@ -192,9 +251,11 @@ fn derive_deserialize_impl(
                 }
             }
 
+            let other = other_variant(data)?;
             let deserialize_variants = data
                 .variants
                 .iter()
+                .filter(|v| other.map_or(true, |other| other.ident != v.ident))
                 .map(|v| {
                     let variant = &v.ident;
                     match v.fields {
@@ -262,6 +323,16 @@ fn derive_deserialize_impl(
                     }
                 })
                 .collect::<Result<Vec<_>, Error>>()?;
+            // Every other archived variant is deserialized as the fallback
+            // variant. This arm is unreachable if the archived enum has no
+            // other variants.
+            let other_arm = other.map(|other| {
+                let variant = &other.ident;
+                quote! {
+                    #[allow(unreachable_patterns)]
+                    _ => #name::#variant,
+                }
+            });
 
             quote! {
                 impl #impl_generics
@@ -276,8 +347,35 @@ fn derive_deserialize_impl(
                         #name #ty_generics,
                         <__D as #rkyv_path::rancor::Fallible>::Error,
                     > {
-                        Ok(match self {
+                        ::core::result::Result::Ok(match self {
                             #(#deserialize_variants,)*
+                            #other_arm
+                        })
+                    }
+                }
+            }
+        }
+        Data::Union(_) if attributes.union_as_bytes.is_some() => {
+            quote! {
+                impl #impl_generics
+                    #rkyv_path::Deserialize<#name #ty_generics, __D>
+                    for #rkyv_path::Archived<#name #ty_generics>
+                #where_clause
+                {
+                    #[inline]
+                    fn deserialize(
+                        &self,
+                        _: &mut __D,
+                    ) -> ::core::result::Result<
+                        #name #ty_generics,
+                        <__D as #rkyv_path::rancor::Fallible>::Error,
+                    > {
+                        // SAFETY: The archived bytes are as long as the union,
+                        // and unions of plain data may hold any bytes.
+                        ::core::result::Result::Ok(unsafe {
+                            ::core::ptr::read_unaligned(
+                                self.as_bytes().as_ptr().cast(),
+                            )
                         })
                     }
                 }
@@ -286,13 +384,287 @@ fn derive_deserialize_impl(
         Data::Union(_) => {
             return Err(Error::new_spanned(
                 input,
-                "Deserialize cannot be derived for unions",
+                "Deserialize cannot be derived for unions without \
+                 `union_as_bytes`",
             ))
         }
     };
 
+    let deserialize_into_impl = match attributes.deserialize_into {
+        Some(ref deserialize_into) => {
+            let fields = match input.data {
+                Data::Struct(ref data) => &data.fields,
+                _ => {
+                    return Err(Error::new_spanned(
+                        deserialize_into,
+                        "`deserialize_into` may only be used on structs",
+                    ))
+                }
+            };
+            let deserialize_into_impl = deserialize_into_struct(
+                &input,
+                attributes,
+                fields,
+                where_clause,
+            )?;
+            quote! {
+                #[automatically_derived]
+                #deserialize_into_impl
+            }
+        }
+        None => quote! {},
+    };
+
     Ok(quote! {
         #[automatically_derived]
         #deserialize_impl
+
+        #deserialize_into_impl
+    })
+}
+
+fn deserialize_into_struct(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    fields: &Fields,
+    where_clause: &WhereClause,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = attributes.crate_path();
+
+    let mut impl_input_params = Punctuated::default();
+    impl_input_params.push(parse_quote! {
+        __D: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+    });
+    for param in input.generics.params.iter() {
+        impl_input_params.push(param.clone());
+    }
+    let impl_input_generics = Generics {
+        lt_token: Some(Default::default()),
+        params: impl_input_params,
+        gt_token: Some(Default::default()),
+        where_clause: None,
+    };
+    let (impl_generics, ..) = impl_input_generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let name = &input.ident;
+
+    let mut deserialize_into_where = where_clause.clone();
+    let mut deserialize_fields = Vec::new();
+    for (member, field) in members(fields) {
+        // Phantom fields have nothing to deserialize
+        if is_phantom_data(&field.ty) && !has_with(field) {
+            continue;
+        }
+
+        if !is_bound_skipped(field) {
+            deserialize_into_where
+                .predicates
+                .push(archive_bound(&rkyv_path, field)?);
+            deserialize_into_where
+                .predicates
+                .push(deserialize_bound(&rkyv_path, field)?);
+        }
+
+        // Fields with wrappers and fields without bounds are deserialized
+        // fresh and assigned, the rest are deserialized in place.
+        if has_with(field) || is_bound_skipped(field) {
+            let deserialize = deserialize(&rkyv_path, field)?;
+            deserialize_fields.push(quote! {
+                out.#member = #deserialize(&self.#member, deserializer)?;
+            });
+        } else {
+            let ty = &field.ty;
+            deserialize_into_where.predicates.push(parse_quote! {
+                <#ty as #rkyv_path::Archive>::Archived:
+                    #rkyv_path::DeserializeInto<#ty, __D>
+            });
+            deserialize_fields.push(quote! {
+                #rkyv_path::DeserializeInto::<#ty, __D>::deserialize_into(
+                    &self.#member,
+                    &mut out.#member,
+                    deserializer,
+                )?;
+            });
+        }
+    }
+
+    if deserialize_fields.is_empty() {
+        deserialize_fields.push(quote! { let _ = (out, deserializer); });
+    }
+
+    Ok(quote! {
+        impl #impl_generics
+            #rkyv_path::DeserializeInto<#name #ty_generics, __D>
+            for #rkyv_path::Archived<#name #ty_generics>
+        #deserialize_into_where
+        {
+            fn deserialize_into(
+                &self,
+                out: &mut #name #ty_generics,
+                deserializer: &mut __D,
+            ) -> ::core::result::Result<
+                (),
+                <__D as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                #(#deserialize_fields)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+fn deserialize_extensible(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    fields: &FieldsNamed,
+    where_clause: &WhereClause,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = attributes.crate_path();
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let mut impl_input_params = Punctuated::default();
+    impl_input_params.push(parse_quote! {
+        __D: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+    });
+    for param in input.generics.params.iter() {
+        impl_input_params.push(param.clone());
+    }
+    let impl_input_generics = Generics {
+        lt_token: Some(Default::default()),
+        params: impl_input_params,
+        gt_token: Some(Default::default()),
+        where_clause: None,
+    };
+    let (impl_generics, ..) = impl_input_generics.split_for_impl();
+
+    let mut deserialize_where = where_clause.clone();
+    for field in fields.named.iter().filter(is_not_omitted) {
+        deserialize_where
+            .predicates
+            .push(archive_bound(&rkyv_path, field)?);
+        deserialize_where
+            .predicates
+            .push(deserialize_bound(&rkyv_path, field)?);
+    }
+    // Fields missing from older archives are taken from the default value.
+    deserialize_where.predicates.push(parse_quote! {
+        #name #ty_generics: ::core::default::Default
+    });
+
+    let deserialize_fields = fields
+        .named
+        .iter()
+        .map(|field| {
+            let field_name = &field.ident;
+            let deserialize = deserialize(&rkyv_path, field)?;
+            Ok(quote! {
+                #field_name: match self.#field_name() {
+                    ::core::option::Option::Some(field) => {
+                        #deserialize(field, deserializer)?
+                    }
+                    ::core::option::Option::None => <
+                        #name #ty_generics as ::core::default::Default
+                    >::default().#field_name,
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::Deserialize<#name #ty_generics, __D>
+            for #rkyv_path::Archived<#name #ty_generics>
+        #deserialize_where
+        {
+            fn deserialize(
+                &self,
+                deserializer: &mut __D,
+            ) -> ::core::result::Result<
+                #name #ty_generics,
+                <__D as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                ::core::result::Result::Ok(#name {
+                    #(#deserialize_fields,)*
+                })
+            }
+        }
+    })
+}
+
+fn deserialize_transparent(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    fields: &Fields,
+    where_clause: &WhereClause,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = attributes.crate_path();
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let mut impl_input_params = Punctuated::default();
+    impl_input_params.push(parse_quote! {
+        __D: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+    });
+    for param in input.generics.params.iter() {
+        impl_input_params.push(param.clone());
+    }
+    let impl_input_generics = Generics {
+        lt_token: Some(Default::default()),
+        params: impl_input_params,
+        gt_token: Some(Default::default()),
+        where_clause: None,
+    };
+    let (impl_generics, ..) = impl_input_generics.split_for_impl();
+
+    let (member, field) =
+        transparent_field(attributes.transparent.as_ref().unwrap(), fields)?;
+
+    let mut deserialize_where = where_clause.clone();
+    if is_not_omitted(&field) {
+        deserialize_where
+            .predicates
+            .push(archive_bound(&rkyv_path, field)?);
+        deserialize_where
+            .predicates
+            .push(deserialize_bound(&rkyv_path, field)?);
+    }
+    // The fields which aren't archived are deserialized with their defaults.
+    for (_, other) in members(fields).filter(|(m, _)| *m != member) {
+        let ty = &other.ty;
+        deserialize_where
+            .predicates
+            .push(parse_quote! { #ty: ::core::default::Default });
+    }
+
+    // The archived type is the archived type of the field, so implement
+    // `Deserialize` for it directly instead of projecting through the
+    // `Archive` impl of the struct.
+    let field_archived = archived(&rkyv_path, field)?;
+    let deserialize = deserialize(&rkyv_path, field)?;
+    let assert_zero_sized = transparent_assert_zero_sized(fields, &member);
+    let construct = transparent_construct(
+        name,
+        fields,
+        &member,
+        quote! { #deserialize(self, deserializer)? },
+    );
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::Deserialize<#name #ty_generics, __D>
+            for #field_archived
+        #deserialize_where
+        {
+            #[inline]
+            fn deserialize(
+                &self,
+                deserializer: &mut __D,
+            ) -> ::core::result::Result<
+                #name #ty_generics,
+                <__D as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                #assert_zero_sized
+                ::core::result::Result::Ok(#construct)
+            }
+        }
     })
 }
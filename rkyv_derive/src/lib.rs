@@ -50,6 +50,13 @@ pub fn derive_portable(
 /// - `resolver = "..."`: Changes the name of the generated resolver type to the
 ///   given value. By default, resolver types are named `the name of the type` +
 ///   "Resolver".
+/// - `archived_vis = "..."` and `resolver_vis = "..."`: Change the visibility
+///   of the generated archived and resolver types to the given value, such as
+///   `"pub(crate)"` or `""` for private. By default, they have the same
+///   visibility as the type. Because they appear in the `Archive` impl, they
+///   must be visible everywhere the type is. To make archived types available
+///   from a different module, re-export them from that module with `pub use`.
+///   Not compatible with `as = "..."` or `transparent`.
 /// - `derive(...)`: Adds the derives passed as arguments to the generated type.
 /// - `compare(...)`: Implements common comparison operators between the
 ///   original and archived types. Supported comparisons are `PartialEq` and
@@ -68,22 +75,143 @@ pub fn derive_portable(
 ///   will archive as the named type. This is useful for types which are generic
 ///   over their parameters.
 /// - `crate = "..."`: Chooses an alternative crate path to import rkyv from.
+/// - `extensible`: Stores the archived fields out-of-line along with their
+///   serialized length, so that fields can be added to the end of the struct
+///   without breaking old archives. Fields are read through generated accessor
+///   methods which return `None` if the archive was written by a version
+///   without that field, and deserializing fills missing fields from the
+///   struct's `Default` value. Only supported on structs with named fields, and
+///   not compatible with `as = "..."` or `compare(...)`.
+/// - `repr(...)`: Sets the type of the archived enum's tag to `u8`, `u16`, or
+///   `u32`. By default, the smallest of these which can fit every variant is
+///   used. Multibyte tags are stored with the same endianness as other archived
+///   primitives. Only supported on enums, and wider tags are not compatible
+///   with `as = "..."`.
+/// - `as_discriminant`: Archives a fieldless enum as an `ArchivedDiscriminant`,
+///   which has the layout of its discriminant's archived integer, instead of
+///   generating an archived type. The integer is `u8` unless a different one is
+///   chosen with `repr(...)`, and every discriminant must fit in it. Also
+///   implements `TryFrom` from the integer and `ToDiscriminant`, so the enum
+///   can be used with the `AsDiscriminant` wrapper. Validation fails for
+///   discriminants which don't belong to any variant. Only supported on
+///   fieldless enums without generic parameters, and not compatible with `as =
+///   "..."`, `compare(...)`, `archived = "..."`, `resolver = "..."`,
+///   `archived_vis = "..."`, `resolver_vis = "..."`, `attr(...)`,
+///   `derive_as_ref`, `derive_project`, `derive_diff`, or `soa`.
+/// - `derive_as_ref`: Generates an enum of references to the fields of each
+///   variant of the archived enum, named "Archived" + `the name of the type` +
+///   "Ref", along with an `as_ref` method to convert to it. Only supported on
+///   enums with at least one field, and not compatible with `as = "..."`.
+/// - `derive_project`: Generates a type of pinned mutable references to the
+///   fields of the archived type, named "Archived" + `the name of the type` +
+///   "Projection", along with a `project` method on `Pin<&mut Self>` to convert
+///   to it. Projecting an archived enum produces an enum, so the variant must
+///   be matched before its fields can be mutated. Only supported on types with
+///   at least one field, and not compatible with `as = "..."`, `extensible`, or
+///   `transparent`.
+/// - `derive_diff`: Implements `Diff` for the archived type, which records the
+///   paths of the fields which differ between two archived values. Every
+///   field's archived type must implement `Diff`. Archived enums of different
+///   variants differ as a whole. Not compatible with `as = "..."`,
+///   `extensible`, or `transparent`.
+/// - `derive_describe`: Implements `ffi::Describe` for the archived type, so
+///   that a C header with an equivalent struct can be generated with
+///   `ffi::write_header`. Requires the `ffi_describe` feature of rkyv. Only
+///   supported on structs with at least one field and without generic
+///   parameters, and not compatible with `as = "..."`, `extensible`, or
+///   `transparent`.
+/// - `derive_projections`: Generates a trait for slices of the archived type,
+///   named "Archived" + `the name of the type` + "Projections", with a method
+///   for each field that iterates over that field of every element. The methods
+///   are named with the plural of the field name, for example `timestamps` for
+///   a field named `timestamp`. Fields of `bool`, `char`, and fixed-size
+///   integer and floating-point types are copied out as their native type with
+///   a `column::NativeColumn`, and other fields are iterated by reference with
+///   a `column::Column`. Only supported on structs with named fields and
+///   without generic parameters, and not compatible with `as = "..."`,
+///   `extensible`, or `transparent`.
+/// - `soa`: Implements the traits needed to archive a `Vec` of the enum as a
+///   struct of arrays with the `SoA` wrapper. Generates the archived struct of
+///   arrays, named "Archived" + `the name of the type` + "Soa", and the same
+///   enum of references as `derive_as_ref`, which its `get` and `iter` methods
+///   return. Only supported on enums with at least one field and at most 256
+///   variants, and not compatible with `as = "..."`.
+/// - `transparent`: Archives the type as the archived type of its only field
+///   instead of generating an archived type. Its `Archived` and `Resolver` are
+///   the field's, so `check_bytes` has no effect and validation is performed by
+///   the field's archived type. Structs with more than one field must mark the
+///   archived field with `#[rkyv(transparent)]`. The other fields must be
+///   zero-sized, which is checked when the impls are compiled, and are
+///   deserialized with `Default::default()`. Not compatible with `as = "..."`,
+///   `extensible`, `compare(...)`, `archived = "..."`, `resolver = "..."`,
+///   `archived_vis = "..."`, `resolver_vis = "..."`, or `attr(...)`.
+/// - `unit_resolver`: Defines the resolver as an alias of `()` instead of
+///   generating a resolver struct, so that `Serialize` impls can return
+///   `Ok(())`. Every field's resolver must be `()`. Only supported on structs
+///   without generic parameters, and not compatible with `extensible` or
+///   `transparent`.
+/// - `union_as_bytes`: Archives a union as a byte array of its size, and
+///   generates an unsafe accessor method on the archived type for each field of
+///   the union which reads the field from the bytes. Every field must be `Copy`
+///   and implement `rkyv::place::Initialized`, which rkyv implements for
+///   primitives and arrays of them but not for references, pointers, or
+///   `ManuallyDrop`. The bytes are copied without conversion, so they are in
+///   the byte order of the machine which serialized the union. Validation
+///   accepts any bytes. Serializing a union reads all of its bytes, so the
+///   union must also implement `Initialized`, which promises that every byte of
+///   every value of the union is initialized. That only holds when every field
+///   is as large as the union, because a union created from a smaller field
+///   leaves the rest of its bytes uninitialized. Only supported on unions
+///   without generic parameters, and not compatible with `as = "..."`,
+///   `compare(...)`, `extensible`, `transparent`, `repr(...)`, `derive_as_ref`,
+///   `derive_project`, `derive_diff`, or `soa`.
 ///
 /// There are also shorthand attributes:
 ///
 /// - `#[rkyv_attr(...)]` is shorthand for `#[rkyv(attr(...))]`.
 /// - `#[rkyv_derive(...)]` is shorthand for `#[rkyv(derive(...))]`.
 ///
-/// # Recursive types
+/// # Bounds
 ///
 /// This derive macro automatically adds a type bound `field: Archive` for each
-/// field type. This can cause an overflow while evaluating trait bounds if the
-/// structure eventually references its own type, as the implementation of
+/// field type which mentions a generic parameter of the type. Bounds are never
+/// added for `PhantomData` fields, so types which are generic over marker types
+/// (e.g. `PhantomData<dyn Trait>` or `PhantomData<fn()>`) can be archived
+/// without requiring the marker types to implement `Archive`. The same applies
+/// to the bounds added to the `Serialize` and `Deserialize` implementations.
+///
+/// # Resolvers
+///
+/// Generated resolver structs implement `Default` when the resolver of each
+/// field is either known to be `()` or mentions a generic parameter, in which
+/// case the field's resolver must implement `Default` as well. Fields of
+/// primitive types, `PhantomData`, and tuples of them without wrappers are
+/// known to have `()` resolvers. This makes implementing `Serialize` by hand
+/// for types with trivial resolvers as easy as returning
+/// `Ok(Default::default())`.
+///
+/// # Recursive types
+///
+/// Bounds on field types can cause an overflow while evaluating trait bounds if
+/// the structure eventually references its own type, as the implementation of
 /// `Archive` for a struct depends on each field type implementing it
-/// as well. Adding the attribute `#[omit_bounds]` to a field will suppress this
-/// trait bound and allow recursive structures. This may be too coarse for some
-/// types, in which case additional type bounds may be required with
-/// `bound(...)`.
+/// as well. Adding the attribute `#[omit_bounds]` (or `#[rkyv(omit_bounds)]`
+/// or `#[rkyv(bound_skip)]`) to a field will suppress its trait bounds and
+/// allow recursive structures. This may be too coarse for some types, in which
+/// case additional type bounds may be required with `bound(...)`.
+///
+/// Fields which name their own type (or `Self`) directly, like `Vec<Tree>` or
+/// `Option<Box<Self>>`, have their bounds omitted automatically. Their bounds
+/// are replaced with the ones needed by `Box`, `Vec`, `Option`, and the
+/// standard maps:
+///
+/// - `serialize_bounds(__S: Writer + Allocator, __S::Error: Source)`
+/// - `deserialize_bounds(__D::Error: Source)`
+/// - `check_bytes(bounds(__C: ArchiveContext))`
+///
+/// Each of these is only added if the corresponding bounds were not specified.
+/// Mutually recursive types can't be detected, so at least one field in the
+/// cycle needs `#[omit_bounds]` and the bounds above.
 ///
 /// # Wrappers
 ///
@@ -91,9 +219,9 @@ pub fn derive_portable(
 /// implementations of core traits. For example, references cannot be archived,
 /// but the `Inline` wrapper serializes a reference as if it were a field of the
 /// struct. Wrappers can be applied to fields using the `#[with(...)]`
-/// attribute. Multiple wrappers can be used, and they are applied in reverse
-/// order (i.e. `#[with(A, B, C)]` will archive `MyType` as
-/// `With<With<With<MyType, C>, B, A>`).
+/// attribute, or equivalently `#[rkyv(with = ...)]`. Multiple wrappers can be
+/// used, and they are applied in reverse order (i.e. `#[with(A, B, C)]` will
+/// archive `MyType` as `With<With<With<MyType, C>, B, A>`).
 #[proc_macro_derive(
     Archive,
     attributes(
@@ -139,6 +267,30 @@ pub fn derive_serialize(
 ///
 /// This macro also supports the `#[archive]`, `#[omit_bounds]`, and `#[with]`
 /// attributes. See [`Archive`] for more information.
+///
+/// # Fallback variants
+///
+/// Archived enum variants are deserialized as the native variant with the same
+/// name. Adding `#[rkyv(other)]` to a fieldless variant deserializes every
+/// archived variant which isn't otherwise handled as that variant instead. This
+/// allows readers to declare a native enum with fewer variants than the one
+/// that was serialized, as long as its archived type is the writer's (e.g. with
+/// `as = "..."`). The archived enum is still validated with the writer's
+/// definition.
+///
+/// The fallback variant is still archived with its own tag, which may belong to
+/// a different variant of the writer's enum. Native enums with a fallback
+/// variant should only be used to read archives written with the writer's
+/// enum.
+///
+/// # Deserializing in place
+///
+/// `#[rkyv(deserialize_into)]` also implements `DeserializeInto` for the
+/// archived type of a struct. Each field is deserialized into the matching
+/// field of the existing value, so that it can reuse its allocations. Fields
+/// with a wrapper or with omitted bounds are deserialized and assigned
+/// instead. It may not be used on enums or unions, or with `as = "..."`,
+/// `extensible`, or `transparent`.
 #[proc_macro_derive(Deserialize, attributes(archive, rkyv, omit_bounds, with))]
 pub fn derive_deserialize(
     input: proc_macro::TokenStream,
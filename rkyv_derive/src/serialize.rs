@@ -2,16 +2,20 @@ use proc_macro2::TokenStream;
 use quote::quote;
 use syn::{
     parse_quote, punctuated::Punctuated, spanned::Spanned, Data, DeriveInput,
-    Error, Fields, Generics, Ident, Index,
+    Error, Fields, FieldsNamed, Generics, Ident, Index, WhereClause,
 };
 
 use crate::{
     attributes::Attributes,
-    util::{is_not_omitted, serialize, serialize_bound, strip_raw},
+    util::{
+        archived_fields_name, is_not_omitted, members, omit_recursive_bounds,
+        resolve, serialize, serialize_bound, strip_raw, transparent_field,
+    },
 };
 
-pub fn derive(input: DeriveInput) -> Result<TokenStream, Error> {
-    let attributes = Attributes::parse(&input)?;
+pub fn derive(mut input: DeriveInput) -> Result<TokenStream, Error> {
+    let mut attributes = Attributes::parse(&input)?;
+    omit_recursive_bounds(&mut input, &mut attributes);
     derive_serialize_impl(input, &attributes)
 }
 
@@ -34,8 +38,9 @@ fn derive_serialize_impl(
     }
 
     let mut impl_input_params = Punctuated::default();
-    impl_input_params
-        .push(parse_quote! { __S: #rkyv_path::rancor::Fallible + ?Sized });
+    impl_input_params.push(parse_quote! {
+        __S: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+    });
     for param in input.generics.params.iter() {
         impl_input_params.push(param.clone());
     }
@@ -59,6 +64,33 @@ fn derive_serialize_impl(
     let serialize_impl =
         match input.data {
             Data::Struct(ref data) => match data.fields {
+                ref fields if attributes.transparent.is_some() => {
+                    serialize_transparent(
+                        &input,
+                        attributes,
+                        fields,
+                        where_clause,
+                    )?
+                }
+                ref fields if attributes.unit_resolver.is_some() => {
+                    serialize_unit_resolver(
+                        &input,
+                        attributes,
+                        fields,
+                        where_clause,
+                    )?
+                }
+                Fields::Named(ref fields)
+                    if attributes.extensible.is_some() =>
+                {
+                    serialize_extensible(
+                        &input,
+                        attributes,
+                        fields,
+                        &resolver,
+                        where_clause,
+                    )?
+                }
                 Fields::Named(ref fields) => {
                     let mut serialize_where = where_clause.clone();
                     for field in fields.named.iter().filter(is_not_omitted) {
@@ -85,7 +117,7 @@ fn derive_serialize_impl(
                                 Self::Resolver,
                                 <__S as #rkyv_path::rancor::Fallible>::Error,
                             > {
-                                Ok(#resolver {
+                                ::core::result::Result::Ok(#resolver {
                                     #(#resolver_values,)*
                                 })
                             }
@@ -123,7 +155,7 @@ fn derive_serialize_impl(
                                 Self::Resolver,
                                 <__S as #rkyv_path::rancor::Fallible>::Error,
                             > {
-                                Ok(#resolver(
+                                ::core::result::Result::Ok(#resolver(
                                     #(#resolver_values,)*
                                 ))
                             }
@@ -143,12 +175,31 @@ fn derive_serialize_impl(
                                 Self::Resolver,
                                 <__S as #rkyv_path::rancor::Fallible>::Error,
                             > {
-                                Ok(#resolver)
+                                ::core::result::Result::Ok(#resolver)
                             }
                         }
                     }
                 }
             },
+            Data::Enum(_) if attributes.as_discriminant.is_some() => {
+                quote! {
+                    impl #impl_generics #rkyv_path::Serialize<__S>
+                        for #name #ty_generics
+                    #where_clause
+                    {
+                        #[inline]
+                        fn serialize(
+                            &self,
+                            _: &mut __S,
+                        ) -> ::core::result::Result<
+                            <Self as #rkyv_path::Archive>::Resolver,
+                            <__S as #rkyv_path::rancor::Fallible>::Error,
+                        > {
+                            ::core::result::Result::Ok(())
+                        }
+                    }
+                }
+            }
             Data::Enum(ref data) => {
                 let mut serialize_where = where_clause.clone();
                 for variant in data.variants.iter() {
@@ -241,17 +292,37 @@ fn derive_serialize_impl(
                             <Self as #rkyv_path::Archive>::Resolver,
                             <__S as #rkyv_path::rancor::Fallible>::Error,
                         > {
-                            Ok(match self {
+                            ::core::result::Result::Ok(match self {
                                 #(#serialize_arms,)*
                             })
                         }
                     }
                 }
             }
+            Data::Union(_) if attributes.union_as_bytes.is_some() => {
+                quote! {
+                    impl #impl_generics #rkyv_path::Serialize<__S>
+                        for #name #ty_generics
+                    #where_clause
+                    {
+                        #[inline]
+                        fn serialize(
+                            &self,
+                            _: &mut __S,
+                        ) -> ::core::result::Result<
+                            <Self as #rkyv_path::Archive>::Resolver,
+                            <__S as #rkyv_path::rancor::Fallible>::Error,
+                        > {
+                            ::core::result::Result::Ok(#resolver)
+                        }
+                    }
+                }
+            }
             Data::Union(_) => {
                 return Err(Error::new_spanned(
                     input,
-                    "Serialize cannot be derived for unions",
+                    "Serialize cannot be derived for unions without \
+                     `union_as_bytes`",
                 ))
             }
         };
@@ -261,3 +332,196 @@ fn derive_serialize_impl(
         #serialize_impl
     })
 }
+
+fn serialize_extensible(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    fields: &FieldsNamed,
+    resolver: &Ident,
+    where_clause: &WhereClause,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = attributes.crate_path();
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let mut impl_input_params = Punctuated::default();
+    impl_input_params.push(parse_quote! {
+        __S: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+    });
+    for param in input.generics.params.iter() {
+        impl_input_params.push(param.clone());
+    }
+    let impl_input_generics = Generics {
+        lt_token: Some(Default::default()),
+        params: impl_input_params,
+        gt_token: Some(Default::default()),
+        where_clause: None,
+    };
+    let (impl_generics, ..) = impl_input_generics.split_for_impl();
+
+    let mut serialize_where = where_clause.clone();
+    for field in fields.named.iter().filter(is_not_omitted) {
+        serialize_where
+            .predicates
+            .push(serialize_bound(&rkyv_path, field)?);
+    }
+    serialize_where.predicates.push(parse_quote! {
+        __S: #rkyv_path::ser::Writer
+    });
+
+    let fields_name = archived_fields_name(name, attributes);
+
+    let mut serialize_fields = Vec::new();
+    let mut resolve_fields = Vec::new();
+    for field in fields.named.iter() {
+        let field_name = field.ident.as_ref().unwrap();
+        let binding = Ident::new(
+            &format!("__{}", strip_raw(field_name)),
+            field_name.span(),
+        );
+        let serialize = serialize(&rkyv_path, field)?;
+        let resolve = resolve(&rkyv_path, field)?;
+
+        serialize_fields.push(quote! {
+            let #binding = #serialize(&self.#field_name, serializer)?;
+        });
+        resolve_fields.push(quote! {
+            let field_ptr = unsafe {
+                ::core::ptr::addr_of_mut!((*out.ptr()).#field_name)
+            };
+            let out_field = unsafe {
+                #rkyv_path::Place::from_field_unchecked(out, field_ptr)
+            };
+            #resolve(&self.#field_name, #binding, out_field);
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::Serialize<__S> for #name #ty_generics
+        #serialize_where
+        {
+            // Some resolvers will be (), this allow is to prevent clippy
+            // from complaining.
+            #[allow(clippy::unit_arg)]
+            fn serialize(
+                &self,
+                serializer: &mut __S,
+            ) -> ::core::result::Result<
+                Self::Resolver,
+                <__S as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                #(#serialize_fields)*
+                let fields = #rkyv_path::extensible::ArchivedExtensible::<
+                    #fields_name #ty_generics
+                >::serialize_from_fn(serializer, |out| {
+                    #(#resolve_fields)*
+                })?;
+                ::core::result::Result::Ok(#resolver {
+                    fields,
+                    _phantom: ::core::marker::PhantomData,
+                })
+            }
+        }
+    })
+}
+
+fn serialize_unit_resolver(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    fields: &Fields,
+    where_clause: &WhereClause,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = attributes.crate_path();
+    let name = &input.ident;
+
+    let mut serialize_where = where_clause.clone();
+    for field in fields.iter().filter(is_not_omitted) {
+        serialize_where
+            .predicates
+            .push(serialize_bound(&rkyv_path, field)?);
+    }
+
+    // Every field is still serialized, and `resolve` requires that their
+    // resolvers are `()`.
+    let serialize_fields = members(fields)
+        .map(|(member, field)| {
+            let serialize = serialize(&rkyv_path, field)?;
+            Ok(quote! {
+                #serialize(&self.#member, serializer)?;
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    Ok(quote! {
+        impl<__S: #rkyv_path::rancor::Fallible + ?::core::marker::Sized>
+            #rkyv_path::Serialize<__S> for #name
+        #serialize_where
+        {
+            fn serialize(
+                &self,
+                serializer: &mut __S,
+            ) -> ::core::result::Result<
+                Self::Resolver,
+                <__S as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                #(#serialize_fields)*
+                ::core::result::Result::Ok(())
+            }
+        }
+    })
+}
+
+fn serialize_transparent(
+    input: &DeriveInput,
+    attributes: &Attributes,
+    fields: &Fields,
+    where_clause: &WhereClause,
+) -> Result<TokenStream, Error> {
+    let rkyv_path = attributes.crate_path();
+    let name = &input.ident;
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let mut impl_input_params = Punctuated::default();
+    impl_input_params.push(parse_quote! {
+        __S: #rkyv_path::rancor::Fallible + ?::core::marker::Sized
+    });
+    for param in input.generics.params.iter() {
+        impl_input_params.push(param.clone());
+    }
+    let impl_input_generics = Generics {
+        lt_token: Some(Default::default()),
+        params: impl_input_params,
+        gt_token: Some(Default::default()),
+        where_clause: None,
+    };
+    let (impl_generics, ..) = impl_input_generics.split_for_impl();
+
+    let (member, field) =
+        transparent_field(attributes.transparent.as_ref().unwrap(), fields)?;
+
+    let mut serialize_where = where_clause.clone();
+    if is_not_omitted(&field) {
+        serialize_where
+            .predicates
+            .push(serialize_bound(&rkyv_path, field)?);
+    }
+
+    let serialize = serialize(&rkyv_path, field)?;
+
+    Ok(quote! {
+        impl #impl_generics #rkyv_path::Serialize<__S> for #name #ty_generics
+        #serialize_where
+        {
+            #[inline]
+            fn serialize(
+                &self,
+                serializer: &mut __S,
+            ) -> ::core::result::Result<
+                Self::Resolver,
+                <__S as #rkyv_path::rancor::Fallible>::Error,
+            > {
+                #serialize(&self.#member, serializer)
+            }
+        }
+    })
+}
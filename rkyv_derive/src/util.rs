@@ -1,10 +1,12 @@
-use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
+use proc_macro2::{Ident, Span, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
 use syn::{
-    parse_quote, Error, Field, Fields, Index, Member, Meta, Path, Type,
-    WherePredicate,
+    parse_quote, Attribute, Data, DataEnum, DeriveInput, Error, Field, Fields,
+    Generics, Index, Member, Meta, Path, Type, Variant, WherePredicate,
 };
 
+use crate::attributes::Attributes;
+
 pub fn strip_raw(ident: &Ident) -> String {
     let as_string = ident.to_string();
     as_string
@@ -13,16 +15,269 @@ pub fn strip_raw(ident: &Ident) -> String {
         .unwrap_or(as_string)
 }
 
-pub fn is_not_omitted(f: &&Field) -> bool {
-    f.attrs.iter().all(|attr| {
-        if let Meta::Path(path) = &attr.meta {
-            !path.is_ident("omit_bounds")
-        } else {
-            true
+pub fn archived_fields_name(name: &Ident, attributes: &Attributes) -> Ident {
+    let archived_name = attributes
+        .archived
+        .as_ref()
+        .map_or_else(|| format!("Archived{}", strip_raw(name)), strip_raw);
+    Ident::new(&format!("{}Fields", archived_name), name.span())
+}
+
+/// Returns whether the field has `#[omit_bounds]`, `#[rkyv(omit_bounds)]`, or
+/// `#[rkyv(bound_skip)]`.
+pub fn is_bound_skipped(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| match &attr.meta {
+        Meta::Path(path) => path.is_ident("omit_bounds"),
+        Meta::List(list) => {
+            (list.path.is_ident("rkyv") || list.path.is_ident("archive"))
+                && attr.parse_args::<Path>().map_or(false, |path| {
+                    path.is_ident("bound_skip") || path.is_ident("omit_bounds")
+                })
         }
+        Meta::NameValue(_) => false,
     })
 }
 
+/// Returns whether bounds should be generated for the field.
+///
+/// `PhantomData` fields implement every trait for all type parameters, so
+/// bounding them would only restrict the parameters they are generic over.
+pub fn is_not_omitted(f: &&Field) -> bool {
+    !is_bound_skipped(f) && !(is_phantom_data(&f.ty) && !has_with(f))
+}
+
+/// Returns the variant with `#[rkyv(other)]`, if any.
+///
+/// Archived variants which don't have a native variant of the same name are
+/// deserialized as this variant, so it must be fieldless.
+pub fn other_variant(data: &DataEnum) -> Result<Option<&Variant>, Error> {
+    let mut result = None;
+    for variant in data.variants.iter() {
+        let is_other = variant.attrs.iter().any(|attr| match &attr.meta {
+            Meta::List(list) => {
+                (list.path.is_ident("rkyv") || list.path.is_ident("archive"))
+                    && attr
+                        .parse_args::<Path>()
+                        .map_or(false, |path| path.is_ident("other"))
+            }
+            Meta::Path(_) | Meta::NameValue(_) => false,
+        });
+        if !is_other {
+            continue;
+        }
+
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "`other` may only be used on fieldless variants",
+            ));
+        }
+        if result.is_some() {
+            return Err(Error::new_spanned(
+                variant,
+                "`other` may only be used on one variant",
+            ));
+        }
+        result = Some(variant);
+    }
+    Ok(result)
+}
+
+/// Returns whether the type of the field mentions the type being derived.
+///
+/// Only unqualified mentions of the type name or `Self` are recognized, so
+/// `Box<Tree>` and `Option<Box<Self>>` are recursive but `other::Tree` and
+/// `Self::Output` are not.
+pub fn is_recursive(name: &Ident, field: &Field) -> bool {
+    fn mentions(name: &Ident, tokens: TokenStream) -> bool {
+        let mut prev_is_colon = false;
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match &token {
+                TokenTree::Group(group) => {
+                    if mentions(name, group.stream()) {
+                        return true;
+                    }
+                }
+                TokenTree::Ident(ident)
+                    if !prev_is_colon && (ident == name || ident == "Self") =>
+                {
+                    let next_is_colon = matches!(
+                        tokens.peek(),
+                        Some(TokenTree::Punct(punct)) if punct.as_char() == ':'
+                    );
+                    if !next_is_colon {
+                        return true;
+                    }
+                }
+                _ => (),
+            }
+            prev_is_colon = matches!(
+                &token,
+                TokenTree::Punct(punct) if punct.as_char() == ':'
+            );
+        }
+        false
+    }
+
+    mentions(name, field.ty.to_token_stream())
+}
+
+/// Omits the bounds of recursive fields and bounds the serializer,
+/// deserializer, and validator with the traits that recursive fields usually
+/// need instead.
+///
+/// The replacement bounds are only added when the corresponding bounds were
+/// not specified with `serialize_bounds`, `deserialize_bounds`, or
+/// `check_bytes(bounds(...))`. They cover recursion through `Box`, `Vec`,
+/// `Option`, and the standard maps.
+pub fn omit_recursive_bounds(
+    input: &mut DeriveInput,
+    attributes: &mut Attributes,
+) {
+    let name = input.ident.clone();
+    let fields: Vec<&mut Field> = match &mut input.data {
+        Data::Struct(data) => data.fields.iter_mut().collect(),
+        Data::Enum(data) => data
+            .variants
+            .iter_mut()
+            .flat_map(|variant| variant.fields.iter_mut())
+            .collect(),
+        Data::Union(_) => return,
+    };
+
+    let mut any_recursive = false;
+    for field in fields {
+        if is_recursive(&name, field) && !is_phantom_data(&field.ty) {
+            any_recursive = true;
+            if !is_bound_skipped(field) {
+                field.attrs.push(parse_quote! { #[omit_bounds] });
+            }
+        }
+    }
+    if !any_recursive {
+        return;
+    }
+
+    let rkyv_path = attributes.crate_path();
+    if attributes.serialize_bounds.is_none() {
+        attributes.serialize_bounds = Some(parse_quote! {
+            __S: #rkyv_path::ser::Writer + #rkyv_path::ser::Allocator,
+            <__S as #rkyv_path::rancor::Fallible>::Error:
+                #rkyv_path::rancor::Source
+        });
+    }
+    if attributes.deserialize_bounds.is_none() {
+        attributes.deserialize_bounds = Some(parse_quote! {
+            <__D as #rkyv_path::rancor::Fallible>::Error:
+                #rkyv_path::rancor::Source
+        });
+    }
+    let bounds = quote! {
+        bounds(__C: #rkyv_path::validation::ArchiveContext)
+    };
+    match &mut attributes.check_bytes {
+        Some(Meta::Path(path)) => {
+            let path = path.clone();
+            attributes.check_bytes = Some(parse_quote! { #path(#bounds) });
+        }
+        Some(Meta::List(list)) => {
+            let has_bounds = list.tokens.clone().into_iter().any(|token| {
+                matches!(token, TokenTree::Ident(ident) if ident == "bounds")
+            });
+            if !has_bounds {
+                let tokens = &list.tokens;
+                list.tokens = if tokens.is_empty() {
+                    bounds
+                } else {
+                    quote! { #tokens, #bounds }
+                };
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Returns whether the type or wrappers of the field mention any of the given
+/// generic parameters.
+///
+/// Fields which don't mention any generic parameters don't need `Archive`
+/// bounds because the compiler can check them directly.
+pub fn mentions_generics(generics: &Generics, field: &Field) -> bool {
+    fn mentions(generics: &Generics, tokens: TokenStream) -> bool {
+        let mut tokens = tokens.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            match token {
+                TokenTree::Group(group) => {
+                    if mentions(generics, group.stream()) {
+                        return true;
+                    }
+                }
+                TokenTree::Punct(punct) if punct.as_char() == '\'' => {
+                    if let Some(TokenTree::Ident(ident)) = tokens.next() {
+                        if generics
+                            .lifetimes()
+                            .any(|param| param.lifetime.ident == ident)
+                        {
+                            return true;
+                        }
+                    }
+                }
+                TokenTree::Ident(ident) => {
+                    if generics.type_params().any(|param| param.ident == ident)
+                        || generics
+                            .const_params()
+                            .any(|param| param.ident == ident)
+                    {
+                        return true;
+                    }
+                }
+                _ => (),
+            }
+        }
+        false
+    }
+
+    mentions(generics, field.ty.to_token_stream())
+        || field
+            .attrs
+            .iter()
+            .filter(|attr| is_with_attr(attr))
+            .any(|attr| mentions(generics, attr.meta.to_token_stream()))
+}
+
+/// Returns the tokens of the wrapper type in `#[rkyv(with = ...)]` or
+/// `#[archive(with = ...)]`, if the attribute is one of those.
+fn rkyv_with_tokens(attr: &Attribute) -> Option<TokenStream> {
+    let list = match &attr.meta {
+        Meta::List(list)
+            if list.path.is_ident("rkyv") || list.path.is_ident("archive") =>
+        {
+            list
+        }
+        _ => return None,
+    };
+
+    let mut tokens = list.tokens.clone().into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ident)), Some(TokenTree::Punct(punct)))
+            if ident == "with" && punct.as_char() == '=' =>
+        {
+            Some(tokens.collect())
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether the attribute is `#[with(...)]` or `#[rkyv(with = ...)]`.
+fn is_with_attr(attr: &Attribute) -> bool {
+    attr.path().is_ident("with") || rkyv_with_tokens(attr).is_some()
+}
+
+pub fn has_with(field: &Field) -> bool {
+    field.attrs.iter().any(is_with_attr)
+}
+
 pub fn members_starting_at(
     fields: &Fields,
     start: usize,
@@ -41,17 +296,217 @@ pub fn members(fields: &Fields) -> impl Iterator<Item = (Member, &Field)> {
     members_starting_at(fields, 0)
 }
 
+pub fn is_phantom_data(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map_or(false, |segment| segment.ident == "PhantomData"),
+        _ => false,
+    }
+}
+
+/// Returns whether the field is known to have a `()` resolver.
+///
+/// This is checked syntactically, so only fields of primitive types,
+/// `PhantomData`, and tuples of them without wrappers are recognized.
+pub fn has_unit_resolver(field: &Field) -> bool {
+    fn is_unit_resolver_type(ty: &Type) -> bool {
+        const PRIMITIVES: [&str; 17] = [
+            "bool",
+            "char",
+            "f32",
+            "f64",
+            "i8",
+            "i16",
+            "i32",
+            "i64",
+            "i128",
+            "isize",
+            "u8",
+            "u16",
+            "u32",
+            "u64",
+            "u128",
+            "usize",
+            "PhantomPinned",
+        ];
+
+        match ty {
+            Type::Path(path) if path.qself.is_none() => {
+                is_phantom_data(ty)
+                    || path.path.get_ident().map_or(false, |ident| {
+                        PRIMITIVES.iter().any(|primitive| ident == primitive)
+                    })
+            }
+            // Tuples only implement `Default` up to 12 elements.
+            Type::Tuple(tuple) => {
+                tuple.elems.len() <= 12
+                    && tuple.elems.iter().all(is_unit_resolver_type)
+            }
+            Type::Paren(paren) => is_unit_resolver_type(&paren.elem),
+            Type::Group(group) => is_unit_resolver_type(&group.elem),
+            _ => false,
+        }
+    }
+
+    !has_with(field) && is_unit_resolver_type(&field.ty)
+}
+
+/// Returns whether the field is known to be archived as a primitive which
+/// implements `CopyNative`.
+///
+/// This is checked syntactically, so only fields of `bool`, `char`, and the
+/// fixed-size integer and floating-point types without wrappers are
+/// recognized.
+pub fn is_copy_native(field: &Field) -> bool {
+    const PRIMITIVES: [&str; 14] = [
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "u8",
+        "u16", "u32", "u64", "u128",
+    ];
+
+    !has_with(field)
+        && match &field.ty {
+            Type::Path(path) if path.qself.is_none() => {
+                path.path.get_ident().map_or(false, |ident| {
+                    PRIMITIVES.iter().any(|primitive| ident == primitive)
+                })
+            }
+            _ => false,
+        }
+}
+
+/// Returns whether the field has `#[rkyv(transparent)]`.
+fn is_transparent_marked(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| match &attr.meta {
+        Meta::List(list) => {
+            (list.path.is_ident("rkyv") || list.path.is_ident("archive"))
+                && attr
+                    .parse_args::<Path>()
+                    .map_or(false, |path| path.is_ident("transparent"))
+        }
+        Meta::Path(_) | Meta::NameValue(_) => false,
+    })
+}
+
+/// Returns the field of a `transparent` struct which is archived.
+///
+/// Structs with more than one field must mark the archived field with
+/// `#[rkyv(transparent)]`. The sizes of types aren't known to the macro, so
+/// the other fields are checked to be zero-sized by
+/// [`transparent_assert_zero_sized`] when the generated code is compiled.
+pub fn transparent_field<'a>(
+    transparent: &Path,
+    fields: &'a Fields,
+) -> Result<(Member, &'a Field), Error> {
+    if fields.is_empty() {
+        return Err(Error::new_spanned(
+            transparent,
+            "`transparent` structs must have a field",
+        ));
+    }
+    if fields.len() == 1 {
+        return Ok(members(fields).next().unwrap());
+    }
+
+    let mut result = None;
+    for (member, field) in members(fields) {
+        if !is_transparent_marked(field) {
+            continue;
+        }
+        if result.is_some() {
+            return Err(Error::new_spanned(
+                field,
+                "only one field of a `transparent` struct may be marked with \
+                 `#[rkyv(transparent)]`",
+            ));
+        }
+        result = Some((member, field));
+    }
+
+    result.ok_or_else(|| {
+        Error::new_spanned(
+            transparent,
+            "`transparent` structs with more than one field must mark the \
+             archived field with `#[rkyv(transparent)]`",
+        )
+    })
+}
+
+/// Returns statements which fail to compile if any field of a `transparent`
+/// struct other than the archived one is not zero-sized.
+///
+/// The check is evaluated when the enclosing function is monomorphized.
+pub fn transparent_assert_zero_sized(
+    fields: &Fields,
+    archived: &Member,
+) -> TokenStream {
+    let tys = members(fields)
+        .filter(|(member, _)| member != archived)
+        .map(|(_, field)| &field.ty)
+        .collect::<Vec<_>>();
+    if tys.is_empty() {
+        return TokenStream::new();
+    }
+
+    quote! {
+        struct __AssertZeroSized<T>(::core::marker::PhantomData<T>);
+
+        impl<T> __AssertZeroSized<T> {
+            const ASSERT: () = ::core::assert!(
+                ::core::mem::size_of::<T>() == 0,
+                "the fields of a `transparent` struct which are not archived \
+                 must be zero-sized",
+            );
+        }
+
+        #(let () = __AssertZeroSized::<#tys>::ASSERT;)*
+    }
+}
+
+/// Returns an expression which constructs a `transparent` struct from the value
+/// of its archived field.
+///
+/// The other fields are constructed with `Default::default()`.
+pub fn transparent_construct(
+    name: &Ident,
+    fields: &Fields,
+    archived: &Member,
+    value: TokenStream,
+) -> TokenStream {
+    let mut value = Some(value);
+    let values = members(fields)
+        .map(|(member, _)| {
+            if member == *archived {
+                value.take().unwrap()
+            } else {
+                quote! { ::core::default::Default::default() }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    match fields {
+        Fields::Named(_) => {
+            let names = fields.iter().map(|field| &field.ident);
+            quote! { #name { #(#names: #values,)* } }
+        }
+        _ => quote! { #name(#(#values,)*) },
+    }
+}
+
 pub fn map_with_or_else<T>(
     field: &Field,
     f: impl FnOnce(Type) -> T,
     d: impl FnOnce() -> T,
 ) -> Result<T, Error> {
-    let with_attr = field
-        .attrs
-        .iter()
-        .find(|attr| attr.meta.path().is_ident("with"));
+    let with_attr = field.attrs.iter().find(|attr| is_with_attr(attr));
     if let Some(with) = with_attr {
-        Ok(f(with.parse_args::<Type>()?))
+        let with_ty = match rkyv_with_tokens(with) {
+            Some(tokens) => syn::parse2::<Type>(tokens)?,
+            None => with.parse_args::<Type>()?,
+        };
+        Ok(f(with_ty))
     } else {
         Ok(d())
     }